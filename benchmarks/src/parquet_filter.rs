@@ -90,16 +90,25 @@ impl RunOpt {
             ParquetScanOptions {
                 pushdown_filters: false,
                 reorder_filters: false,
+                late_materialization: false,
                 enable_page_index: false,
             },
+            ParquetScanOptions {
+                pushdown_filters: false,
+                reorder_filters: false,
+                late_materialization: true,
+                enable_page_index: true,
+            },
             ParquetScanOptions {
                 pushdown_filters: true,
                 reorder_filters: true,
+                late_materialization: false,
                 enable_page_index: true,
             },
             ParquetScanOptions {
                 pushdown_filters: true,
                 reorder_filters: true,
+                late_materialization: false,
                 enable_page_index: false,
             },
         ];
@@ -21,6 +21,7 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
+use std::sync::Arc;
 
 use crate::print_format::PrintFormat;
 use crate::{
@@ -227,6 +228,8 @@ async fn exec_and_print(
                 | LogicalPlan::Analyze(_)
         );
         let df = ctx.execute_logical_plan(plan).await?;
+        // tracks the progress of the query in `ctx.running_queries()`
+        let task_ctx = Arc::new(df.task_ctx());
         let physical_plan = df.create_physical_plan().await?;
 
         if physical_plan.execution_mode().is_unbounded() {
@@ -70,6 +70,7 @@ async fn main() -> Result<()> {
         limit: Some(5),
         table_partition_cols: vec![],
         output_ordering: vec![],
+        bucket_by: None,
     };
 
     let result =
@@ -21,9 +21,10 @@ use std::any::Any;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Display};
 use std::str::FromStr;
+use std::time::Duration;
 
 use crate::error::_config_err;
-use crate::parsers::CompressionTypeVariant;
+use crate::parsers::{CompressionTypeVariant, ConfigDuration};
 use crate::{DataFusionError, FileType, Result};
 
 /// A macro that wraps a configuration struct and automatically derives
@@ -268,6 +269,17 @@ config_namespace! {
         /// batches and merged.
         pub sort_in_place_threshold_bytes: usize, default = 1024 * 1024
 
+        /// The compression of the Arrow IPC files that sorts and aggregations
+        /// spill to disk: `uncompressed`, `lz4_frame` or `zstd`. Compressed
+        /// spill files use less disk space at the cost of CPU time
+        pub spill_compression: String, default = "uncompressed".to_string()
+
+        /// How long operators that cannot spill, such as the build side of a
+        /// hash join, wait for spillable operators to release memory when
+        /// their reservation fails. The memory pool must support spill
+        /// requests, like the `FairSpillPool`. `0s` fails immediately
+        pub spill_request_timeout: ConfigDuration, default = ConfigDuration(Duration::from_secs(1))
+
         /// Number of files to read in parallel when inferring schema and statistics
         pub meta_fetch_concurrency: usize, default = 32
 
@@ -289,14 +301,57 @@ config_namespace! {
         /// memory consumption
         pub max_buffered_batches_per_output_file: usize, default = 2
 
+        /// Should INSERT and COPY statements writing to files return one row
+        /// per written file (with its `path` and row `count`) instead of a
+        /// single row with the total `count`
+        pub report_written_files: bool, default = false
+
         /// Should sub directories be ignored when scanning directories for data
         /// files. Defaults to true (ignores subdirectories), consistent with
         /// Hive. Note that this setting does not affect reading partitioned
         /// tables (e.g. `/table/year=2021/month=01/data.parquet`).
         pub listing_table_ignore_subdirectory: bool, default = true
 
+        /// Should listing tables created without partition columns infer them
+        /// from Hive-style directories (e.g. `/table/year=2021/day=2021-01-01/data.parquet`).
+        /// Partition columns are typed as integers or dates if all their values are,
+        /// and as strings otherwise. This also types partition columns declared
+        /// without a schema. Defaults to false
+        pub listing_table_infer_partitions: bool, default = false
+
+        /// How to read table columns that a scanned file does not contain,
+        /// including fields of nested structs: `fill_null` reads them as nulls,
+        /// and `error` fails the scan
+        pub missing_column_policy: String, default = "fill_null".to_string()
+
+        /// How to read columns of a scanned file that the table does not contain,
+        /// including fields of nested structs: `ignore` skips them, and `error`
+        /// fails the scan
+        pub extra_column_policy: String, default = "ignore".to_string()
+
+        /// If true, the columns of CSV files with a header are matched to the
+        /// columns of the table by the names in their header rather than by
+        /// position. A table column missing from the header fails the scan,
+        /// and other columns of the header follow `extra_column_policy`
+        pub csv_columns_by_name: bool, default = false
+
         /// Should DataFusion support recursive CTEs
         pub enable_recursive_ctes: bool, default = true
+
+        /// Maximum number of bytes of memory a single query executed by a
+        /// `DataFrame` may reserve, within the memory pool of the runtime.
+        /// If NULL, queries are only limited by the memory pool
+        pub query_memory_limit: Option<usize>, default = None
+
+        /// The priority class of the queries executed by a `DataFrame` when
+        /// the runtime has a query scheduler: `low`, `normal` or `high`.
+        /// Queued queries of a higher priority are admitted first
+        pub query_priority: String, default = "normal".to_string()
+
+        /// Maximum duration of the queries executed by a `DataFrame`, such as
+        /// `30s`, `500ms` or `5m`. Queries running longer fail with a
+        /// cancellation error. If NULL, queries have no timeout
+        pub query_timeout: Option<ConfigDuration>, default = None
     }
 }
 
@@ -337,6 +392,14 @@ config_namespace! {
         /// the filters are applied in the same order as written in the query
         pub reorder_filters: bool, default = false
 
+        /// If true, and `pushdown_filters` is false, filter expressions are still
+        /// evaluated during the parquet decoding operation when the other projected
+        /// columns are larger than the filter columns. Only the pages of those columns
+        /// containing rows that pass the filters are then fetched and decoded. Set to
+        /// false to decode all of the projected columns and filter the rows after the
+        /// scan, as in previous versions
+        pub late_materialization: bool, default = true
+
         // The following map to parquet::file::properties::WriterProperties
 
         /// Sets best effort maximum size of data page in bytes
@@ -571,6 +634,33 @@ config_namespace! {
         /// when an exact selectivity cannot be determined. Valid values are
         /// between 0 (no selectivity) and 100 (all rows are selected).
         pub default_filter_selectivity: u8, default = 20
+
+        /// When set to true, the physical planner will execute identical subplans,
+        /// such as a common table expression referenced several times, only once
+        /// and buffer their output for each consumer
+        pub share_common_subplans: bool, default = false
+
+        /// When set to true, the optimizer will compute aggregations below inner
+        /// joins when the join keys of the other join input are unique, and combine
+        /// the partial results after the join
+        pub eager_aggregation: bool, default = false
+
+        /// When set to true, the physical plan is re-optimized during execution:
+        /// the build sides of hash joins are materialized at their exchange boundaries,
+        /// and the join modes are chosen again from their actual row counts and sizes,
+        /// while the probe sides keep streaming
+        pub enable_adaptive_execution: bool, default = false
+
+        /// When set to true, the hash repartitions of partitioned hash joins
+        /// sample the frequencies of their keys, and spread the rows of heavy
+        /// keys across all partitions, sending the matching rows of the build
+        /// side to all partitions. Aggregations above such joins are computed
+        /// in two phases, so that their hot groups are spread too
+        pub enable_skew_mitigation: bool, default = false
+
+        /// The number of rows sampled from each input partition to detect the
+        /// heavy keys when `enable_skew_mitigation` is set
+        pub skew_sample_rows: usize, default = 8192
     }
 }
 
@@ -976,6 +1066,17 @@ impl ConfigField for CompressionTypeVariant {
     }
 }
 
+impl ConfigField for ConfigDuration {
+    fn visit<V: Visit>(&self, v: &mut V, key: &str, description: &'static str) {
+        v.some(key, self, description)
+    }
+
+    fn set(&mut self, _: &str, value: &str) -> Result<()> {
+        *self = ConfigDuration::from_str(value)?;
+        Ok(())
+    }
+}
+
 /// An implementation trait used to recursively walk configuration
 pub trait Visit {
     fn some<V: Display>(&mut self, key: &str, value: V, description: &'static str);
@@ -1125,6 +1226,14 @@ pub struct TableOptions {
     /// Configuration options for JSON file handling.
     pub json: JsonOptions,
 
+    /// Configuration options for XLSX workbook handling, such as the worksheet
+    /// to read and whether it has a header row.
+    pub xlsx: XlsxOptions,
+
+    /// Configuration options for fixed-width text file handling, including the
+    /// positions of the columns within each line.
+    pub fixed_width: FixedWidthOptions,
+
     /// The current file format that the table operations should assume. This option allows
     /// for dynamic switching between the supported file types (e.g., CSV, Parquet, JSON).
     pub current_format: Option<FileType>,
@@ -1148,12 +1257,16 @@ impl ConfigField for TableOptions {
                 FileType::PARQUET => self.parquet.visit(v, "format", ""),
                 FileType::CSV => self.csv.visit(v, "format", ""),
                 FileType::JSON => self.json.visit(v, "format", ""),
+                FileType::XLSX => self.xlsx.visit(v, "format", ""),
+                FileType::FIXEDWIDTH => self.fixed_width.visit(v, "format", ""),
                 _ => {}
             }
         } else {
             self.csv.visit(v, "csv", "");
             self.parquet.visit(v, "parquet", "");
             self.json.visit(v, "json", "");
+            self.xlsx.visit(v, "xlsx", "");
+            self.fixed_width.visit(v, "fixed_width", "");
         }
     }
 
@@ -1184,6 +1297,8 @@ impl ConfigField for TableOptions {
                 FileType::PARQUET => self.parquet.set(rem, value),
                 FileType::CSV => self.csv.set(rem, value),
                 FileType::JSON => self.json.set(rem, value),
+                FileType::XLSX => self.xlsx.set(rem, value),
+                FileType::FIXEDWIDTH => self.fixed_width.set(rem, value),
                 _ => {
                     _config_err!("Config value \"{key}\" is not supported on {}", format)
                 }
@@ -1616,6 +1731,41 @@ config_namespace! {
     }
 }
 
+config_namespace! {
+    /// Options controlling XLSX format
+    pub struct XlsxOptions {
+        /// Name of the worksheet to read, the first worksheet if unset
+        pub sheet_name: Option<String>, default = None
+        /// Whether the first read row holds the column names
+        pub has_header: bool, default = true
+        /// Number of rows of the worksheet skipped before the header or
+        /// first data row
+        pub skip_rows: usize, default = 0
+        /// Maximum number of data rows read from the worksheet of each file
+        /// to infer the column types
+        pub schema_infer_max_rec: usize, default = 100
+    }
+}
+
+config_namespace! {
+    /// Options controlling fixed-width text format
+    pub struct FixedWidthOptions {
+        /// The columns of each line, as a comma separated list of
+        /// `name:start-end` entries, where `start` and `end` are the 1-based,
+        /// inclusive character positions of the column (e.g. `id:1-5,name:6-25`)
+        pub columns: String, default = "".to_string()
+        /// Whether the first line is a header, which is skipped
+        pub has_header: bool, default = false
+        /// Whether surrounding whitespace is removed from values. Empty
+        /// values are read as nulls
+        pub trim: bool, default = true
+        pub compression: CompressionTypeVariant, default = CompressionTypeVariant::UNCOMPRESSED
+        /// Maximum number of lines read, across all files, to infer the
+        /// column types. Columns without a non-empty value are read as strings
+        pub schema_infer_max_rec: usize, default = 100
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum FormatOptions {
     CSV(CsvOptions),
@@ -1624,6 +1774,8 @@ pub enum FormatOptions {
     PARQUET(TableParquetOptions),
     AVRO,
     ARROW,
+    XLSX(XlsxOptions),
+    FIXEDWIDTH(FixedWidthOptions),
 }
 impl Display for FormatOptions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1634,6 +1786,8 @@ impl Display for FormatOptions {
             FormatOptions::PARQUET(_) => "parquet",
             FormatOptions::AVRO => "avro",
             FormatOptions::ARROW => "arrow",
+            FormatOptions::XLSX(_) => "xlsx",
+            FormatOptions::FIXEDWIDTH(_) => "fixedwidth",
         };
         write!(f, "{}", out)
     }
@@ -1648,6 +1802,10 @@ impl From<FileType> for FormatOptions {
             FileType::PARQUET => FormatOptions::PARQUET(TableParquetOptions::default()),
             FileType::CSV => FormatOptions::CSV(CsvOptions::default()),
             FileType::JSON => FormatOptions::JSON(JsonOptions::default()),
+            FileType::XLSX => FormatOptions::XLSX(XlsxOptions::default()),
+            FileType::FIXEDWIDTH => {
+                FormatOptions::FIXEDWIDTH(FixedWidthOptions::default())
+            }
         }
     }
 }
@@ -22,9 +22,13 @@ pub use graphviz::*;
 
 use std::{
     fmt::{self, Display, Formatter},
+    str::FromStr,
     sync::Arc,
 };
 
+use crate::error::{_plan_err, DataFusionError, Result};
+use crate::instant::Instant;
+
 /// Represents which type of plan, when storing multiple
 /// for use in EXPLAIN plans
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -119,3 +123,335 @@ pub trait ToStringifiedPlan {
     /// Create a stringified plan with the specified type
     fn to_stringified(&self, plan_type: PlanType) -> StringifiedPlan;
 }
+
+/// Format of the plans produced by `EXPLAIN`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ExplainFormat {
+    /// Indented text, one line per plan node
+    #[default]
+    Tree,
+    /// A JSON object holding the tree of plan nodes
+    Json,
+}
+
+impl FromStr for ExplainFormat {
+    type Err = DataFusionError;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "tree" => Ok(ExplainFormat::Tree),
+            "json" => Ok(ExplainFormat::Json),
+            _ => _plan_err!("Unknown EXPLAIN format '{format}', expected tree or json"),
+        }
+    }
+}
+
+impl Display for ExplainFormat {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ExplainFormat::Tree => write!(f, "tree"),
+            ExplainFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Options of `EXPLAIN (FORMAT json, RULES on) ...`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ExplainOptions {
+    /// Format of the produced plans
+    pub format: ExplainFormat,
+    /// Only show the analyzer and optimizer rules that changed the plan,
+    /// with the time they took and the diff of the plan, instead of every
+    /// intermediate plan
+    pub rules: bool,
+}
+
+impl ExplainOptions {
+    /// Returns true if all options have their default value
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl Display for ExplainOptions {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut options = vec![];
+        if self.format != ExplainFormat::default() {
+            options.push(format!("FORMAT {}", self.format));
+        }
+        if self.rules {
+            options.push("RULES on".to_string());
+        }
+        write!(f, "({})", options.join(", "))
+    }
+}
+
+impl StringifiedPlan {
+    /// Returns this plan encoded as JSON, for `EXPLAIN (FORMAT json)`
+    ///
+    /// Plans are encoded as a tree of nodes, each with its name (the text
+    /// before the first `:` of its line), its details and its children, from
+    /// the indentation of the plan lines. The changes recorded by
+    /// [`RuleChangeRecorder`] are encoded as the time the rule took and the
+    /// lines of the diff.
+    pub fn to_json(&self) -> StringifiedPlan {
+        let mut json = serde_json::Map::new();
+        json.insert("plan_type".into(), self.plan_type.to_string().into());
+        match self.plan.strip_prefix(ELAPSED_PREFIX) {
+            Some(change) => {
+                let mut lines = change.lines();
+                let elapsed = lines.next().unwrap_or_default();
+                json.insert("elapsed".into(), elapsed.into());
+                json.insert("diff".into(), lines.collect::<Vec<_>>().into());
+            }
+            None => {
+                json.insert("plan".into(), plan_nodes_to_json(&self.plan));
+            }
+        }
+        StringifiedPlan::new(
+            self.plan_type.clone(),
+            serde_json::Value::Object(json).to_string(),
+        )
+    }
+}
+
+/// Builds the tree of nodes of an indented plan, where each level of the
+/// tree is indented by two spaces
+fn plan_nodes_to_json(plan: &str) -> serde_json::Value {
+    fn node(line: &str) -> serde_json::Map<String, serde_json::Value> {
+        let mut node = serde_json::Map::new();
+        match line.split_once(": ") {
+            Some((name, details)) => {
+                node.insert("node".into(), name.into());
+                node.insert("details".into(), details.into());
+            }
+            None => {
+                node.insert("node".into(), line.into());
+            }
+        }
+        node.insert("children".into(), serde_json::Value::Array(vec![]));
+        node
+    }
+
+    // Adds the last node of `stack` to the children of its parent
+    fn pop(
+        stack: &mut Vec<(usize, serde_json::Map<String, serde_json::Value>)>,
+        roots: &mut Vec<serde_json::Value>,
+    ) {
+        let (_, child) = stack.pop().unwrap();
+        match stack.last_mut() {
+            Some((_, parent)) => {
+                if let Some(serde_json::Value::Array(children)) =
+                    parent.get_mut("children")
+                {
+                    children.push(child.into());
+                }
+            }
+            None => roots.push(child.into()),
+        }
+    }
+
+    // The nodes whose children are still being read, with their depth
+    let mut stack = vec![];
+    let mut roots = vec![];
+    for line in plan.lines() {
+        let trimmed = line.trim_start();
+        let depth = (line.len() - trimmed.len()) / 2;
+        while matches!(stack.last(), Some((d, _)) if *d >= depth) {
+            pop(&mut stack, &mut roots);
+        }
+        stack.push((depth, node(trimmed)));
+    }
+    while !stack.is_empty() {
+        pop(&mut stack, &mut roots);
+    }
+
+    if roots.len() == 1 {
+        roots.pop().unwrap()
+    } else {
+        roots.into()
+    }
+}
+
+/// The first line of the changes recorded by [`RuleChangeRecorder`]
+const ELAPSED_PREFIX: &str = "elapsed=";
+
+/// Records the changes successive rules make to a plan, for
+/// `EXPLAIN (RULES on)`
+///
+/// The time a rule took is measured from the previous call to
+/// [`Self::record`], so the recorder should be created right before the
+/// first rule runs.
+#[derive(Debug)]
+pub struct RuleChangeRecorder {
+    /// The plan produced by the previous rule
+    plan: String,
+    /// When the previous rule finished
+    start: Instant,
+}
+
+impl RuleChangeRecorder {
+    /// Create a recorder for rules applied to `plan`
+    pub fn new(plan: impl Display) -> Self {
+        Self {
+            plan: plan.to_string(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Record that a rule produced `plan`, returning the time the rule took
+    /// along with a diff to the previous plan, or `None` if the rule did not
+    /// change the plan
+    pub fn record(
+        &mut self,
+        plan_type: PlanType,
+        plan: impl Display,
+    ) -> Option<StringifiedPlan> {
+        let elapsed = self.start.elapsed();
+        let plan = plan.to_string();
+        let change = (plan != self.plan).then(|| {
+            let diff = line_diff(&self.plan, &plan);
+            StringifiedPlan::new(
+                plan_type,
+                format!("{ELAPSED_PREFIX}{elapsed:?}\n{diff}"),
+            )
+        });
+        self.plan = plan;
+        // don't count the time spent displaying the plan towards the next rule
+        self.start = Instant::now();
+        change
+    }
+}
+
+/// Returns a line diff from `before` to `after`, where removed lines are
+/// prefixed by `-`, added lines by `+` and unchanged lines by a space
+///
+/// Rules usually change a single part of the plan, so the lines between the
+/// common leading and trailing lines are shown as removed and added, which
+/// keeps the diff linear in the size of the plans.
+fn line_diff(before: &str, after: &str) -> String {
+    let before = before.lines().collect::<Vec<_>>();
+    let after = after.lines().collect::<Vec<_>>();
+
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(before, after)| before == after)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(before, after)| before == after)
+        .count();
+
+    let unchanged = |line: &&str| format!(" {line}");
+    before[..prefix]
+        .iter()
+        .map(unchanged)
+        .chain(
+            before[prefix..before.len() - suffix]
+                .iter()
+                .map(|line| format!("-{line}")),
+        )
+        .chain(
+            after[prefix..after.len() - suffix]
+                .iter()
+                .map(|line| format!("+{line}")),
+        )
+        .chain(before[before.len() - suffix..].iter().map(unchanged))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let before = "Projection: a\n  Filter: a > 1\n    TableScan: t";
+        let after = "Projection: a\n  TableScan: t, full_filters=[a > 1]";
+        let expected = " Projection: a\n-  Filter: a > 1\n-    TableScan: t\n+  TableScan: t, full_filters=[a > 1]";
+        assert_eq!(line_diff(before, after), expected);
+        assert_eq!(
+            line_diff(before, before),
+            format!(" {}", before.replace('\n', "\n "))
+        );
+
+        let before = "Limit: 1\n  Sort: a\n    TableScan: t";
+        let after = "Limit: 1\n  Projection: a\n    Sort: a\n      TableScan: t";
+        let expected = " Limit: 1\n-  Sort: a\n-    TableScan: t\n+  Projection: a\n+    Sort: a\n+      TableScan: t";
+        assert_eq!(line_diff(before, after), expected);
+        let expected = " Limit: 1\n+  Projection: a\n   Sort: a\n     TableScan: t";
+        assert_eq!(
+            line_diff(
+                "Limit: 1\n  Sort: a\n    TableScan: t",
+                "Limit: 1\n  Projection: a\n  Sort: a\n    TableScan: t"
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_rule_change_recorder() {
+        let mut recorder = RuleChangeRecorder::new("TableScan: t");
+        let plan_type = |name: &str| PlanType::OptimizedLogicalPlan {
+            optimizer_name: name.to_string(),
+        };
+        assert!(recorder.record(plan_type("a"), "TableScan: t").is_none());
+
+        let change = recorder
+            .record(plan_type("b"), "Limit: 1\n  TableScan: t")
+            .unwrap();
+        assert_eq!(change.plan_type, plan_type("b"));
+        let lines = change.plan.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("elapsed="));
+        assert_eq!(
+            lines[1..],
+            ["-TableScan: t", "+Limit: 1", "+  TableScan: t"]
+        );
+
+        let json = change.to_json();
+        let json: serde_json::Value = serde_json::from_str(&json.plan).unwrap();
+        assert_eq!(json["plan_type"], "logical_plan after b");
+        assert!(json["elapsed"].is_string());
+        assert_eq!(
+            json["diff"],
+            serde_json::json!(["-TableScan: t", "+Limit: 1", "+  TableScan: t"])
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let plan = StringifiedPlan::new(
+            PlanType::FinalLogicalPlan,
+            "Union\n  Filter: a = \"x\"\n    TableScan: t\n  TableScan: u",
+        );
+        let json: serde_json::Value = serde_json::from_str(&plan.to_json().plan).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "plan_type": "logical_plan",
+                "plan": {
+                    "node": "Union",
+                    "children": [
+                        {
+                            "node": "Filter",
+                            "details": "a = \"x\"",
+                            "children": [
+                                {"node": "TableScan", "details": "t", "children": []}
+                            ]
+                        },
+                        {"node": "TableScan", "details": "u", "children": []}
+                    ]
+                }
+            })
+        );
+        assert_eq!(
+            "JSON".parse::<ExplainFormat>().unwrap(),
+            ExplainFormat::Json
+        );
+        assert!("yaml".parse::<ExplainFormat>().is_err());
+    }
+}
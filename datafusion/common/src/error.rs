@@ -116,6 +116,11 @@ pub enum DataFusionError {
     /// This error is thrown when a consumer cannot acquire additional memory
     /// or other resources needed to execute the query from the Memory Manager.
     ResourcesExhausted(String),
+    /// Error when a query is cancelled before it completes.
+    ///
+    /// This error is returned when the cancellation token of the query is
+    /// cancelled, or when the query runs longer than its timeout.
+    Cancelled(String),
     /// Errors originating from outside DataFusion's core codebase.
     ///
     /// For example, a custom S3Error from the crate datafusion-objectstore-s3
@@ -325,6 +330,9 @@ impl Display for DataFusionError {
             DataFusionError::ResourcesExhausted(ref desc) => {
                 write!(f, "Resources exhausted: {desc}")
             }
+            DataFusionError::Cancelled(ref desc) => {
+                write!(f, "Cancelled: {desc}")
+            }
             DataFusionError::External(ref desc) => {
                 write!(f, "External error: {desc}")
             }
@@ -361,6 +369,7 @@ impl Error for DataFusionError {
             DataFusionError::SchemaError(e, _) => Some(e),
             DataFusionError::Execution(_) => None,
             DataFusionError::ResourcesExhausted(_) => None,
+            DataFusionError::Cancelled(_) => None,
             DataFusionError::External(e) => Some(e.as_ref()),
             DataFusionError::Context(_, e) => Some(e.as_ref()),
             DataFusionError::Substrait(_) => None,
@@ -0,0 +1,117 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Bucketed (hash clustered) file layout
+
+use std::fmt::{self, Display};
+
+use crate::error::{DataFusionError, Result};
+
+/// Prefix of the names of bucketed files, see [`BucketSpec::file_name`]
+const BUCKET_FILE_PREFIX: &str = "bucket_";
+
+/// Describes a table whose rows are clustered into a fixed number of
+/// buckets by hashing a set of columns.
+///
+/// A row belongs to bucket `hash(columns) % num_buckets`, using the same
+/// hash function as hash repartitioning, and each file holds the rows of a
+/// single bucket. Reading the files of bucket `i` as partition `i` therefore
+/// produces the same partitioning as a hash repartition on `columns` into
+/// `num_buckets` partitions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BucketSpec {
+    /// The columns whose values are hashed to choose the bucket of a row
+    pub columns: Vec<String>,
+    /// The number of buckets
+    pub num_buckets: usize,
+}
+
+impl BucketSpec {
+    /// Create a new [`BucketSpec`], validating that it has at least one
+    /// column and one bucket
+    pub fn try_new(columns: Vec<String>, num_buckets: usize) -> Result<Self> {
+        if columns.is_empty() {
+            return Err(DataFusionError::Configuration(
+                "Bucketing requires at least one column".to_string(),
+            ));
+        }
+        if num_buckets == 0 {
+            return Err(DataFusionError::Configuration(
+                "The number of buckets must be greater than zero".to_string(),
+            ));
+        }
+        Ok(Self {
+            columns,
+            num_buckets,
+        })
+    }
+
+    /// The name of a file holding rows of bucket `bucket`, written by the
+    /// write identified by `write_id`
+    pub fn file_name(bucket: usize, write_id: &str, file_extension: &str) -> String {
+        format!("{BUCKET_FILE_PREFIX}{bucket:05}_{write_id}.{file_extension}")
+    }
+
+    /// Returns the bucket of the file named `file_name`, or `None` if the
+    /// name was not produced by [`Self::file_name`] for one of the buckets
+    /// of this spec
+    pub fn bucket_of_file(&self, file_name: &str) -> Option<usize> {
+        let rest = file_name.strip_prefix(BUCKET_FILE_PREFIX)?;
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        if !rest[digits..].starts_with('_') {
+            return None;
+        }
+        rest[..digits]
+            .parse::<usize>()
+            .ok()
+            .filter(|bucket| *bucket < self.num_buckets)
+    }
+}
+
+impl Display for BucketSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} BY ({})", self.num_buckets, self.columns.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_file_names_roundtrip() -> Result<()> {
+        let spec = BucketSpec::try_new(vec!["a".to_string()], 32)?;
+
+        let name = BucketSpec::file_name(7, "abcdef", "parquet");
+        assert_eq!(name, "bucket_00007_abcdef.parquet");
+        assert_eq!(spec.bucket_of_file(&name), Some(7));
+
+        // buckets outside of the spec and foreign files are not bucket files
+        let name = BucketSpec::file_name(32, "abcdef", "parquet");
+        assert_eq!(spec.bucket_of_file(&name), None);
+        assert_eq!(spec.bucket_of_file("bucket_00001.parquet"), None);
+        assert_eq!(spec.bucket_of_file("bucket__abcdef.parquet"), None);
+        assert_eq!(spec.bucket_of_file("part-0.parquet"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_bucket_spec() {
+        assert!(BucketSpec::try_new(vec![], 4).is_err());
+        assert!(BucketSpec::try_new(vec!["a".to_string()], 0).is_err());
+    }
+}
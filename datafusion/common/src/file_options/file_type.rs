@@ -33,6 +33,10 @@ pub const DEFAULT_CSV_EXTENSION: &str = ".csv";
 pub const DEFAULT_JSON_EXTENSION: &str = ".json";
 /// The default file extension of parquet files
 pub const DEFAULT_PARQUET_EXTENSION: &str = ".parquet";
+/// The default file extension of xlsx files
+pub const DEFAULT_XLSX_EXTENSION: &str = ".xlsx";
+/// The default file extension of fixed-width text files
+pub const DEFAULT_FIXED_WIDTH_EXTENSION: &str = ".txt";
 
 /// Define each `FileType`/`FileCompressionType`'s extension
 pub trait GetExt {
@@ -54,6 +58,10 @@ pub enum FileType {
     CSV,
     /// JSON file
     JSON,
+    /// Excel XLSX workbook
+    XLSX,
+    /// Fixed-width text file
+    FIXEDWIDTH,
 }
 
 impl From<&FormatOptions> for FileType {
@@ -65,6 +73,8 @@ impl From<&FormatOptions> for FileType {
             FormatOptions::PARQUET(_) => FileType::PARQUET,
             FormatOptions::AVRO => FileType::AVRO,
             FormatOptions::ARROW => FileType::ARROW,
+            FormatOptions::XLSX(_) => FileType::XLSX,
+            FormatOptions::FIXEDWIDTH(_) => FileType::FIXEDWIDTH,
         }
     }
 }
@@ -78,6 +88,8 @@ impl GetExt for FileType {
             FileType::PARQUET => DEFAULT_PARQUET_EXTENSION.to_owned(),
             FileType::CSV => DEFAULT_CSV_EXTENSION.to_owned(),
             FileType::JSON => DEFAULT_JSON_EXTENSION.to_owned(),
+            FileType::XLSX => DEFAULT_XLSX_EXTENSION.to_owned(),
+            FileType::FIXEDWIDTH => DEFAULT_FIXED_WIDTH_EXTENSION.to_owned(),
         }
     }
 }
@@ -91,6 +103,8 @@ impl Display for FileType {
             FileType::PARQUET => "parquet",
             FileType::AVRO => "avro",
             FileType::ARROW => "arrow",
+            FileType::XLSX => "xlsx",
+            FileType::FIXEDWIDTH => "fixedwidth",
         };
         write!(f, "{}", out)
     }
@@ -108,6 +122,8 @@ impl FromStr for FileType {
             "PARQUET" => Ok(FileType::PARQUET),
             "CSV" => Ok(FileType::CSV),
             "JSON" | "NDJSON" => Ok(FileType::JSON),
+            "XLSX" => Ok(FileType::XLSX),
+            "FIXEDWIDTH" | "FIXED_WIDTH" => Ok(FileType::FIXEDWIDTH),
             _ => Err(DataFusionError::NotImplemented(format!(
                 "Unknown FileType: {s}"
             ))),
@@ -134,6 +150,9 @@ mod tests {
             ("AVRO", FileType::AVRO),
             ("parquet", FileType::PARQUET),
             ("PARQUET", FileType::PARQUET),
+            ("xlsx", FileType::XLSX),
+            ("fixedwidth", FileType::FIXEDWIDTH),
+            ("fixed_width", FileType::FIXEDWIDTH),
         ] {
             assert_eq!(FileType::from_str(ext).unwrap(), file_type);
         }
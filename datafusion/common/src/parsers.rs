@@ -20,9 +20,13 @@
 use std::fmt::Display;
 use std::result;
 use std::str::FromStr;
+use std::time::Duration;
 
 use sqlparser::parser::ParserError;
 
+use crate::error::_config_err;
+use crate::{DataFusionError, Result};
+
 /// Readable file compression type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CompressionTypeVariant {
@@ -74,3 +78,52 @@ impl CompressionTypeVariant {
         !matches!(self, &Self::UNCOMPRESSED)
     }
 }
+
+/// A duration set in the configuration, such as `30s`, `500ms`, `5m` or
+/// `1h`. A number without unit is a number of seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ConfigDuration(pub Duration);
+
+impl FromStr for ConfigDuration {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (value, unit) = s.split_at(split);
+        let Ok(value) = value.parse::<u64>() else {
+            return _config_err!("Invalid duration '{s}'");
+        };
+        let secs = |multiplier: u64| match value.checked_mul(multiplier) {
+            Some(secs) => Ok(Duration::from_secs(secs)),
+            None => _config_err!("Duration '{s}' is too large"),
+        };
+        let duration = match unit.trim() {
+            "ms" => Duration::from_millis(value),
+            "" | "s" => Duration::from_secs(value),
+            "m" | "min" => secs(60)?,
+            "h" => secs(60 * 60)?,
+            unit => {
+                return _config_err!(
+                    "Unsupported duration unit '{unit}', expected one of 'ms', 's', 'm' or 'h'"
+                )
+            }
+        };
+        Ok(Self(duration))
+    }
+}
+
+impl Display for ConfigDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let millis = self.0.as_millis();
+        if millis % 1000 != 0 {
+            write!(f, "{millis}ms")
+        } else if millis % (60 * 60 * 1000) == 0 && millis > 0 {
+            write!(f, "{}h", millis / (60 * 60 * 1000))
+        } else if millis % (60 * 1000) == 0 && millis > 0 {
+            write!(f, "{}m", millis / (60 * 1000))
+        } else {
+            write!(f, "{}s", millis / 1000)
+        }
+    }
+}
@@ -28,7 +28,10 @@ use crate::arrow::record_batch::RecordBatch;
 use crate::arrow::util::pretty;
 use crate::datasource::{provider_as_source, MemTable, TableProvider};
 use crate::error::Result;
+use crate::execution::cancellation::CancellationToken;
 use crate::execution::context::{SessionState, TaskContext};
+use crate::execution::memory_pool::ScopedMemoryPool;
+use crate::execution::scheduler::QueryPermit;
 use crate::execution::FunctionRegistry;
 use crate::logical_expr::utils::find_window_exprs;
 use crate::logical_expr::{
@@ -158,6 +161,8 @@ impl Default for DataFrameWriteOptions {
 pub struct DataFrame {
     session_state: SessionState,
     plan: LogicalPlan,
+    /// Cancels the query of this DataFrame, see [`Self::with_cancellation_token`]
+    cancellation_token: Option<CancellationToken>,
 }
 
 impl DataFrame {
@@ -170,6 +175,7 @@ impl DataFrame {
         Self {
             session_state,
             plan,
+            cancellation_token: None,
         }
     }
 
@@ -230,7 +236,10 @@ impl DataFrame {
         };
         let project_plan = LogicalPlanBuilder::from(plan).project(expr_list)?.build()?;
 
-        Ok(DataFrame::new(self.session_state, project_plan))
+        Ok(DataFrame {
+            plan: project_plan,
+            ..self
+        })
     }
 
     /// Expand each list element of a column to multiple rows.
@@ -269,7 +278,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .unnest_column_with_options(column, options)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Return a DataFrame with only rows for which `predicate` evaluates to
@@ -294,7 +303,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .filter(predicate)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Return a new `DataFrame` that aggregates the rows of the current
@@ -325,7 +334,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .aggregate(group_expr, aggr_expr)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Return a new DataFrame that adds the result of evaluating one or more
@@ -334,7 +343,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .window(window_exprs)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Returns a new `DataFrame` with a limited number of rows.
@@ -359,7 +368,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .limit(skip, fetch)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Calculate the union of two [`DataFrame`]s, preserving duplicate rows.
@@ -383,7 +392,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .union(dataframe.plan)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Calculate the distinct union of two [`DataFrame`]s.
@@ -405,12 +414,12 @@ impl DataFrame {
     /// # }
     /// ```
     pub fn union_distinct(self, dataframe: DataFrame) -> Result<DataFrame> {
-        Ok(DataFrame::new(
-            self.session_state,
-            LogicalPlanBuilder::from(self.plan)
+        Ok(DataFrame {
+            plan: LogicalPlanBuilder::from(self.plan)
                 .union_distinct(dataframe.plan)?
                 .build()?,
-        ))
+            ..self
+        })
     }
 
     /// Return a new `DataFrame` with all duplicated rows removed.
@@ -428,10 +437,10 @@ impl DataFrame {
     /// # }
     /// ```
     pub fn distinct(self) -> Result<DataFrame> {
-        Ok(DataFrame::new(
-            self.session_state,
-            LogicalPlanBuilder::from(self.plan).distinct()?.build()?,
-        ))
+        Ok(DataFrame {
+            plan: LogicalPlanBuilder::from(self.plan).distinct()?.build()?,
+            ..self
+        })
     }
 
     /// Return a new `DataFrame` that has statistics for a DataFrame.
@@ -599,15 +608,15 @@ impl DataFrame {
             describe_record_batch.schema(),
             vec![vec![describe_record_batch]],
         )?;
-        Ok(DataFrame::new(
-            self.session_state,
-            LogicalPlanBuilder::scan(
+        Ok(DataFrame {
+            plan: LogicalPlanBuilder::scan(
                 UNNAMED_TABLE,
                 provider_as_source(Arc::new(provider)),
                 None,
             )?
             .build()?,
-        ))
+            ..self
+        })
     }
 
     /// Sort the DataFrame by the specified sorting expressions.
@@ -633,7 +642,7 @@ impl DataFrame {
     /// ```
     pub fn sort(self, expr: Vec<Expr>) -> Result<DataFrame> {
         let plan = LogicalPlanBuilder::from(self.plan).sort(expr)?.build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Join this `DataFrame` with another `DataFrame` using explicitly specified
@@ -687,7 +696,7 @@ impl DataFrame {
                 filter,
             )?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Join this `DataFrame` with another `DataFrame` using the specified
@@ -737,7 +746,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .join_on(right.plan, join_type, expr)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Repartition a DataFrame based on a logical partitioning scheme.
@@ -758,7 +767,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .repartition(partitioning_scheme)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Return the total number of rows in this `DataFrame`.
@@ -817,6 +826,7 @@ impl DataFrame {
     /// # }
     /// ```
     pub async fn collect(self) -> Result<Vec<RecordBatch>> {
+        let _permit = self.admit().await?;
         let task_ctx = Arc::new(self.task_ctx());
         let plan = self.create_physical_plan().await?;
         collect(plan, task_ctx).await
@@ -861,9 +871,78 @@ impl DataFrame {
         Ok(pretty::print_batches(&results)?)
     }
 
+    /// Return a new `DataFrame` whose query is cancelled when `token` is
+    /// cancelled with [`CancellationToken::cancel`], e.g. from another task
+    /// while the query runs. The token is kept by the DataFrames derived from
+    /// this one.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # use datafusion::execution::cancellation::CancellationToken;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let token = CancellationToken::new();
+    /// let df = ctx
+    ///     .read_csv("tests/data/example.csv", CsvReadOptions::new())
+    ///     .await?
+    ///     .with_cancellation_token(token.clone());
+    /// token.cancel();
+    /// assert!(df.collect().await.is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
     /// Return a new [`TaskContext`] which would be used to execute this DataFrame
+    ///
+    /// The progress of the query is tracked in the [`TaskContext`], and
+    /// reported by [`SessionContext::running_queries`] while it runs. If
+    /// `datafusion.execution.query_memory_limit` is set, the memory of the
+    /// query is reserved in a [`ScopedMemoryPool`] with that limit. The query
+    /// is cancelled with the token of [`Self::with_cancellation_token`], and,
+    /// if `datafusion.execution.query_timeout` is set, once it runs longer
+    /// than the timeout.
     pub fn task_ctx(&self) -> TaskContext {
-        TaskContext::from(&self.session_state)
+        let progress = self.session_state.progress_registry().register();
+        let query_id = progress.query_id();
+        let mut task_ctx =
+            TaskContext::from(&self.session_state).with_query_progress(progress);
+        let options = self.session_state.config_options();
+        if let Some(limit) = options.execution.query_memory_limit {
+            let pool = task_ctx.memory_pool().clone();
+            let name = format!("query {query_id}");
+            let scope = ScopedMemoryPool::new(name, pool).with_limit(limit);
+            task_ctx = task_ctx.with_memory_pool(Arc::new(scope));
+        }
+        let token = match (&self.cancellation_token, options.execution.query_timeout) {
+            (Some(token), Some(timeout)) => Some(token.child_with_timeout(timeout.0)),
+            (Some(token), None) => Some(token.clone()),
+            (None, Some(timeout)) => Some(CancellationToken::with_timeout(timeout.0)),
+            (None, None) => None,
+        };
+        if let Some(token) = token {
+            task_ctx = task_ctx.with_cancellation_token(token);
+        }
+        task_ctx
+    }
+
+    /// Waits for the [`QueryScheduler`] of the runtime, if any, to admit this
+    /// query with the priority of `datafusion.execution.query_priority`
+    ///
+    /// [`QueryScheduler`]: crate::execution::scheduler::QueryScheduler
+    async fn admit(&self) -> Result<Option<QueryPermit>> {
+        let runtime = self.session_state.runtime_env();
+        let Some(scheduler) = &runtime.query_scheduler else {
+            return Ok(None);
+        };
+        let options = self.session_state.config_options();
+        let priority = options.execution.query_priority.parse()?;
+        scheduler.admit(priority).await.map(Some)
     }
 
     /// Executes this DataFrame and returns a stream over a single partition
@@ -888,9 +967,14 @@ impl DataFrame {
     /// Dropping the stream will abort the execution of the query, and free up
     /// any allocated resources
     pub async fn execute_stream(self) -> Result<SendableRecordBatchStream> {
+        let permit = self.admit().await?;
         let task_ctx = Arc::new(self.task_ctx());
         let plan = self.create_physical_plan().await?;
-        execute_stream(plan, task_ctx)
+        let stream = execute_stream(plan, task_ctx)?;
+        Ok(match permit {
+            Some(permit) => permit.hold(stream),
+            None => stream,
+        })
     }
 
     /// Executes this DataFrame and collects all results into a vector of vector of RecordBatch
@@ -909,6 +993,7 @@ impl DataFrame {
     /// # }
     /// ```
     pub async fn collect_partitioned(self) -> Result<Vec<Vec<RecordBatch>>> {
+        let _permit = self.admit().await?;
         let task_ctx = Arc::new(self.task_ctx());
         let plan = self.create_physical_plan().await?;
         collect_partitioned(plan, task_ctx).await
@@ -935,9 +1020,14 @@ impl DataFrame {
     pub async fn execute_stream_partitioned(
         self,
     ) -> Result<Vec<SendableRecordBatchStream>> {
+        let permit = self.admit().await?;
         let task_ctx = Arc::new(self.task_ctx());
         let plan = self.create_physical_plan().await?;
-        execute_stream_partitioned(plan, task_ctx)
+        let streams = execute_stream_partitioned(plan, task_ctx)?;
+        Ok(match permit {
+            Some(permit) => permit.hold_partitions(streams),
+            None => streams,
+        })
     }
 
     /// Returns the `DFSchema` describing the output of this DataFrame.
@@ -1023,7 +1113,7 @@ impl DataFrame {
         let plan = LogicalPlanBuilder::from(self.plan)
             .explain(verbose, analyze)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, plan))
+        Ok(DataFrame { plan, ..self })
     }
 
     /// Return a `FunctionRegistry` used to plan udf's calls
@@ -1062,10 +1152,10 @@ impl DataFrame {
     pub fn intersect(self, dataframe: DataFrame) -> Result<DataFrame> {
         let left_plan = self.plan;
         let right_plan = dataframe.plan;
-        Ok(DataFrame::new(
-            self.session_state,
-            LogicalPlanBuilder::intersect(left_plan, right_plan, true)?,
-        ))
+        Ok(DataFrame {
+            plan: LogicalPlanBuilder::intersect(left_plan, right_plan, true)?,
+            ..self
+        })
     }
 
     /// Calculate the exception of two [`DataFrame`]s.  The two [`DataFrame`]s must have exactly the same schema
@@ -1086,10 +1176,10 @@ impl DataFrame {
         let left_plan = self.plan;
         let right_plan = dataframe.plan;
 
-        Ok(DataFrame::new(
-            self.session_state,
-            LogicalPlanBuilder::except(left_plan, right_plan, true)?,
-        ))
+        Ok(DataFrame {
+            plan: LogicalPlanBuilder::except(left_plan, right_plan, true)?,
+            ..self
+        })
     }
 
     /// Execute this `DataFrame` and write the results to `table_name`.
@@ -1114,7 +1204,7 @@ impl DataFrame {
             write_options.overwrite,
         )?
         .build()?;
-        DataFrame::new(self.session_state, plan).collect().await
+        DataFrame { plan, ..self }.collect_write().await
     }
 
     /// Execute the `DataFrame` and write the results to CSV file(s).
@@ -1162,7 +1252,7 @@ impl DataFrame {
             options.partition_by,
         )?
         .build()?;
-        DataFrame::new(self.session_state, plan).collect().await
+        DataFrame { plan, ..self }.collect_write().await
     }
 
     /// Execute the `DataFrame` and write the results to JSON file(s).
@@ -1211,7 +1301,28 @@ impl DataFrame {
             options.partition_by,
         )?
         .build()?;
-        DataFrame::new(self.session_state, plan).collect().await
+        DataFrame { plan, ..self }.collect_write().await
+    }
+
+    /// Executes `plan`, a [`LogicalPlan::Copy`] or [`LogicalPlan::Dml`]
+    /// writing a `DataFrame`, which outputs a row per written file if the
+    /// `datafusion.execution.report_written_files` option is set
+    async fn collect_write(self) -> Result<Vec<RecordBatch>> {
+        let written_files = self
+            .session_state
+            .config_options()
+            .execution
+            .report_written_files;
+        let plan = match self.plan {
+            LogicalPlan::Copy(copy) => {
+                LogicalPlan::Copy(copy.with_written_files(written_files))
+            }
+            LogicalPlan::Dml(dml) => {
+                LogicalPlan::Dml(dml.with_written_files(written_files))
+            }
+            plan => plan,
+        };
+        DataFrame { plan, ..self }.collect().await
     }
 
     /// Add an additional column to the DataFrame.
@@ -1258,7 +1369,10 @@ impl DataFrame {
 
         let project_plan = LogicalPlanBuilder::from(plan).project(fields)?.build()?;
 
-        Ok(DataFrame::new(self.session_state, project_plan))
+        Ok(DataFrame {
+            plan: project_plan,
+            ..self
+        })
     }
 
     /// Rename one column by applying a new projection. This is a no-op if the column to be
@@ -1322,7 +1436,10 @@ impl DataFrame {
         let project_plan = LogicalPlanBuilder::from(self.plan)
             .project(projection)?
             .build()?;
-        Ok(DataFrame::new(self.session_state, project_plan))
+        Ok(DataFrame {
+            plan: project_plan,
+            ..self
+        })
     }
 
     /// Replace all parameters in logical plan with the specified
@@ -1384,7 +1501,7 @@ impl DataFrame {
     /// ```
     pub fn with_param_values(self, query_values: impl Into<ParamValues>) -> Result<Self> {
         let plan = self.plan.with_param_values(query_values)?;
-        Ok(Self::new(self.session_state, plan))
+        Ok(Self { plan, ..self })
     }
 
     /// Cache DataFrame as a memory table.
@@ -1405,6 +1522,7 @@ impl DataFrame {
         // The schema is consistent with the output
         let plan = self.clone().create_physical_plan().await?;
         let schema = plan.schema();
+        let _permit = self.admit().await?;
         let task_ctx = Arc::new(self.task_ctx());
         let partitions = collect_partitioned(plan, task_ctx).await?;
         let mem_table = MemTable::try_new(schema, partitions)?;
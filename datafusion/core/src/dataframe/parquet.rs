@@ -68,7 +68,7 @@ impl DataFrame {
             options.partition_by,
         )?
         .build()?;
-        DataFrame::new(self.session_state, plan).collect().await
+        DataFrame { plan, ..self }.collect_write().await
     }
 }
 
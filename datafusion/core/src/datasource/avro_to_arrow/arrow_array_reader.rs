@@ -77,6 +77,10 @@ impl<'a, R: Read> AvroArrowArrayReader<'a, R> {
         })
     }
 
+    pub fn writer_schema(&self) -> &AvroSchema {
+        self.reader.writer_schema()
+    }
+
     pub fn schema_lookup(schema: AvroSchema) -> Result<BTreeMap<String, usize>> {
         match schema {
             AvroSchema::Record(RecordSchema {
@@ -15,7 +15,8 @@
 // specific language governing permissions and limitations
 // under the License.
 
-//! This module contains code for reading [Avro] data into `RecordBatch`es
+//! This module contains code for reading [Avro] data into `RecordBatch`es and
+//! writing `RecordBatch`es as Avro data
 //!
 //! [Avro]: https://avro.apache.org/docs/1.2.0/
 
@@ -25,12 +26,16 @@ mod arrow_array_reader;
 mod reader;
 #[cfg(feature = "avro")]
 mod schema;
+#[cfg(feature = "avro")]
+mod writer;
 
 use crate::arrow::datatypes::Schema;
 use crate::error::Result;
 #[cfg(feature = "avro")]
 pub use reader::{Reader, ReaderBuilder};
 use std::io::Read;
+#[cfg(feature = "avro")]
+pub use writer::{to_avro_schema, Writer};
 
 #[cfg(feature = "avro")]
 /// Read Avro schema given a reader
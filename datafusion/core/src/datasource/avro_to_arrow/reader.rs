@@ -155,6 +155,11 @@ impl<'a, R: Read> Reader<'a, R> {
     pub fn schema(&self) -> SchemaRef {
         self.schema.clone()
     }
+
+    /// Returns the Avro schema the records of the file were written with
+    pub fn writer_schema(&self) -> &apache_avro::Schema {
+        self.array_reader.writer_schema()
+    }
 }
 
 impl<'a, R: Read> Iterator for Reader<'a, R> {
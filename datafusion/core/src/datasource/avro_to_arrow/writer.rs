@@ -0,0 +1,473 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::arrow::array::{Array, ArrayRef, AsArray, OffsetSizeTrait};
+use crate::arrow::datatypes::{
+    ArrowPrimitiveType, DataType, Date32Type, Date64Type, Field, Fields, Float32Type,
+    Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, Schema, SchemaRef,
+    Time32MillisecondType, Time32SecondType, Time64MicrosecondType, Time64NanosecondType,
+    TimeUnit, TimestampMicrosecondType, TimestampMillisecondType,
+    TimestampNanosecondType, TimestampSecondType, UInt16Type, UInt32Type, UInt64Type,
+    UInt8Type,
+};
+use crate::arrow::error::ArrowError;
+use crate::arrow::record_batch::{RecordBatch, RecordBatchWriter};
+use crate::error::Result;
+use apache_avro::schema::{
+    Name, RecordField, RecordFieldOrder, RecordSchema, UnionSchema,
+};
+use apache_avro::types::Value;
+use apache_avro::Schema as AvroSchema;
+use datafusion_common::{exec_err, not_impl_err};
+
+/// The name of the top level record of the Avro schemas of [`Writer`]s
+const RECORD_NAME: &str = "topLevelRecord";
+
+/// Converts an arrow schema to the schema of the Avro records written by
+/// [`Writer`]
+///
+/// Nullable fields are written as unions of `null` and their type, and
+/// integer and temporal types without a corresponding Avro type as the
+/// smallest Avro numeric type able to hold their values.
+pub fn to_avro_schema(schema: &Schema) -> Result<AvroSchema> {
+    record_schema(schema.fields(), Name::new(RECORD_NAME)?)
+}
+
+fn record_schema(fields: &Fields, name: Name) -> Result<AvroSchema> {
+    // Nested records are named after their path, as Avro names must be unique
+    let namespace = match &name.namespace {
+        Some(namespace) => format!("{namespace}.{}", name.name),
+        None => name.name.clone(),
+    };
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(position, field)| {
+            Ok(RecordField {
+                name: field.name().clone(),
+                doc: None,
+                aliases: None,
+                default: None,
+                schema: field_schema(field, &namespace)?,
+                order: RecordFieldOrder::Ascending,
+                position,
+                custom_attributes: BTreeMap::new(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let lookup = fields
+        .iter()
+        .map(|field| (field.name.clone(), field.position))
+        .collect();
+    Ok(AvroSchema::Record(RecordSchema {
+        name,
+        aliases: None,
+        doc: None,
+        fields,
+        lookup,
+        attributes: BTreeMap::new(),
+    }))
+}
+
+fn field_schema(field: &Field, namespace: &str) -> Result<AvroSchema> {
+    let schema = match field.data_type() {
+        DataType::Null => return Ok(AvroSchema::Null),
+        DataType::Boolean => AvroSchema::Boolean,
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::Time32(TimeUnit::Second) => AvroSchema::Int,
+        DataType::Int64
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Date64
+        | DataType::Time64(TimeUnit::Nanosecond)
+        | DataType::Timestamp(TimeUnit::Second | TimeUnit::Nanosecond, _) => {
+            AvroSchema::Long
+        }
+        DataType::Float32 => AvroSchema::Float,
+        DataType::Float64 => AvroSchema::Double,
+        DataType::Utf8 | DataType::LargeUtf8 => AvroSchema::String,
+        DataType::Binary | DataType::LargeBinary => AvroSchema::Bytes,
+        DataType::Date32 => AvroSchema::Date,
+        DataType::Time32(TimeUnit::Millisecond) => AvroSchema::TimeMillis,
+        DataType::Time64(TimeUnit::Microsecond) => AvroSchema::TimeMicros,
+        DataType::Timestamp(TimeUnit::Millisecond, _) => AvroSchema::TimestampMillis,
+        DataType::Timestamp(TimeUnit::Microsecond, _) => AvroSchema::TimestampMicros,
+        DataType::List(item) | DataType::LargeList(item) => {
+            AvroSchema::Array(Box::new(field_schema(item, namespace)?))
+        }
+        DataType::Struct(fields) => record_schema(
+            fields,
+            Name {
+                name: field.name().clone(),
+                namespace: Some(namespace.to_string()),
+            },
+        )?,
+        data_type => {
+            return not_impl_err!("Writing {data_type} columns to Avro is not supported")
+        }
+    };
+    if field.is_nullable() {
+        Ok(AvroSchema::Union(UnionSchema::new(vec![
+            AvroSchema::Null,
+            schema,
+        ])?))
+    } else {
+        Ok(schema)
+    }
+}
+
+/// Writes [`RecordBatch`]es as an Avro object container file
+///
+/// Each batch is written as a single block of records. The header of the
+/// file is written with the first batch, so nothing is written if no
+/// batches are.
+pub struct Writer<W: Write> {
+    writer: W,
+    /// The schema of the written batches, whose columns are matched by
+    /// position
+    schema: SchemaRef,
+    avro_schema: AvroSchema,
+    marker: [u8; 16],
+    has_header: bool,
+}
+
+impl<W: Write> Writer<W> {
+    /// Create a new [`Writer`] of a new Avro object container file
+    pub fn try_new(writer: W, schema: SchemaRef) -> Result<Self> {
+        Ok(Self {
+            writer,
+            avro_schema: to_avro_schema(&schema)?,
+            schema,
+            marker: uuid::Uuid::new_v4().into_bytes(),
+            has_header: false,
+        })
+    }
+
+    /// Create a new [`Writer`] appending to an existing Avro object container
+    /// file, whose header was written with the sync `marker`
+    pub fn try_append_to(writer: W, schema: SchemaRef, marker: [u8; 16]) -> Result<Self> {
+        Ok(Self {
+            writer,
+            avro_schema: to_avro_schema(&schema)?,
+            schema,
+            marker,
+            has_header: true,
+        })
+    }
+
+    /// Write a [`RecordBatch`] as a block of records
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        let mut columns = self
+            .schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .map(|(field, array)| Ok(field_values(field, array)?.into_iter()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut writer = if self.has_header {
+            apache_avro::Writer::append_to(
+                &self.avro_schema,
+                &mut self.writer,
+                self.marker,
+            )
+        } else {
+            apache_avro::Writer::builder()
+                .schema(&self.avro_schema)
+                .writer(&mut self.writer)
+                .marker(self.marker)
+                .build()
+        };
+        for _ in 0..batch.num_rows() {
+            let record = self
+                .schema
+                .fields()
+                .iter()
+                .zip(columns.iter_mut())
+                .map(|(field, values)| (field.name().clone(), values.next().unwrap()))
+                .collect();
+            writer.append(Value::Record(record))?;
+        }
+        writer.flush()?;
+        self.has_header = true;
+        Ok(())
+    }
+
+    /// Flush the underlying writer and return it
+    pub fn into_inner(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> RecordBatchWriter for Writer<W> {
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), ArrowError> {
+        Writer::write(self, batch).map_err(|e| ArrowError::ExternalError(Box::new(e)))
+    }
+
+    fn close(self) -> Result<(), ArrowError> {
+        self.into_inner()
+            .map(|_| ())
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+    }
+}
+
+/// Returns the Avro values of `array`, wrapped in unions if `field` is nullable
+///
+/// Nulls of non-nullable fields, such as the children of null structs, are
+/// returned as [`Value::Null`] and rejected by the schema validation of the
+/// written records.
+fn field_values(field: &Field, array: &ArrayRef) -> Result<Vec<Value>> {
+    if field.data_type() == &DataType::Null {
+        return Ok(vec![Value::Null; array.len()]);
+    }
+    Ok(array_values(array)?
+        .into_iter()
+        .map(|value| match (value, field.is_nullable()) {
+            (Some(value), true) => Value::Union(1, Box::new(value)),
+            (None, true) => Value::Union(0, Box::new(Value::Null)),
+            (value, false) => value.unwrap_or(Value::Null),
+        })
+        .collect())
+}
+
+/// Returns the Avro values of `array`, or `None` for its nulls
+fn array_values(array: &ArrayRef) -> Result<Vec<Option<Value>>> {
+    Ok(match array.data_type() {
+        DataType::Boolean => array
+            .as_boolean()
+            .iter()
+            .map(|v| v.map(Value::Boolean))
+            .collect(),
+        DataType::Int8 => primitive_values::<Int8Type>(array, |v| Value::Int(v.into())),
+        DataType::Int16 => primitive_values::<Int16Type>(array, |v| Value::Int(v.into())),
+        DataType::Int32 => primitive_values::<Int32Type>(array, Value::Int),
+        DataType::Int64 => primitive_values::<Int64Type>(array, Value::Long),
+        DataType::UInt8 => primitive_values::<UInt8Type>(array, |v| Value::Int(v.into())),
+        DataType::UInt16 => {
+            primitive_values::<UInt16Type>(array, |v| Value::Int(v.into()))
+        }
+        DataType::UInt32 => {
+            primitive_values::<UInt32Type>(array, |v| Value::Long(v.into()))
+        }
+        DataType::UInt64 => {
+            let array = array.as_primitive::<UInt64Type>();
+            array
+                .iter()
+                .map(|v| {
+                    v.map(|v| match i64::try_from(v) {
+                        Ok(v) => Ok(Value::Long(v)),
+                        Err(_) => exec_err!("Cannot write UInt64 value {v} to Avro"),
+                    })
+                    .transpose()
+                })
+                .collect::<Result<_>>()?
+        }
+        DataType::Float32 => primitive_values::<Float32Type>(array, Value::Float),
+        DataType::Float64 => primitive_values::<Float64Type>(array, Value::Double),
+        DataType::Utf8 => string_values::<i32>(array),
+        DataType::LargeUtf8 => string_values::<i64>(array),
+        DataType::Binary => binary_values::<i32>(array),
+        DataType::LargeBinary => binary_values::<i64>(array),
+        DataType::Date32 => primitive_values::<Date32Type>(array, Value::Date),
+        DataType::Date64 => primitive_values::<Date64Type>(array, Value::Long),
+        DataType::Time32(TimeUnit::Second) => {
+            primitive_values::<Time32SecondType>(array, Value::Int)
+        }
+        DataType::Time32(TimeUnit::Millisecond) => {
+            primitive_values::<Time32MillisecondType>(array, Value::TimeMillis)
+        }
+        DataType::Time64(TimeUnit::Microsecond) => {
+            primitive_values::<Time64MicrosecondType>(array, Value::TimeMicros)
+        }
+        DataType::Time64(TimeUnit::Nanosecond) => {
+            primitive_values::<Time64NanosecondType>(array, Value::Long)
+        }
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            primitive_values::<TimestampSecondType>(array, Value::Long)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            primitive_values::<TimestampMillisecondType>(array, Value::TimestampMillis)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            primitive_values::<TimestampMicrosecondType>(array, Value::TimestampMicros)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            primitive_values::<TimestampNanosecondType>(array, Value::Long)
+        }
+        DataType::List(item) => list_values::<i32>(array, item)?,
+        DataType::LargeList(item) => list_values::<i64>(array, item)?,
+        DataType::Struct(fields) => {
+            let array = array.as_struct();
+            let mut columns = fields
+                .iter()
+                .zip(array.columns())
+                .map(|(field, column)| Ok(field_values(field, column)?.into_iter()))
+                .collect::<Result<Vec<_>>>()?;
+            (0..array.len())
+                .map(|i| {
+                    let record = fields
+                        .iter()
+                        .zip(columns.iter_mut())
+                        .map(|(field, values)| {
+                            (field.name().clone(), values.next().unwrap())
+                        })
+                        .collect();
+                    array.is_valid(i).then_some(Value::Record(record))
+                })
+                .collect()
+        }
+        data_type => {
+            return not_impl_err!("Writing {data_type} columns to Avro is not supported")
+        }
+    })
+}
+
+fn primitive_values<T: ArrowPrimitiveType>(
+    array: &ArrayRef,
+    value: impl Fn(T::Native) -> Value,
+) -> Vec<Option<Value>> {
+    array
+        .as_primitive::<T>()
+        .iter()
+        .map(|v| v.map(&value))
+        .collect()
+}
+
+fn string_values<O: OffsetSizeTrait>(array: &ArrayRef) -> Vec<Option<Value>> {
+    array
+        .as_string::<O>()
+        .iter()
+        .map(|v| v.map(|v| Value::String(v.to_string())))
+        .collect()
+}
+
+fn binary_values<O: OffsetSizeTrait>(array: &ArrayRef) -> Vec<Option<Value>> {
+    array
+        .as_binary::<O>()
+        .iter()
+        .map(|v| v.map(|v| Value::Bytes(v.to_vec())))
+        .collect()
+}
+
+fn list_values<O: OffsetSizeTrait>(
+    array: &ArrayRef,
+    item: &Field,
+) -> Result<Vec<Option<Value>>> {
+    let array = array.as_list::<O>();
+    let items = field_values(item, array.values())?;
+    Ok(array
+        .offsets()
+        .windows(2)
+        .enumerate()
+        .map(|(i, offsets)| {
+            array.is_valid(i).then(|| {
+                let range = offsets[0].as_usize()..offsets[1].as_usize();
+                Value::Array(items[range].to_vec())
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arrow::array::{
+        Date32Array, Int32Array, Int64Builder, ListBuilder, StringArray, StructArray,
+        TimestampMicrosecondArray,
+    };
+    use crate::datasource::avro_to_arrow::Reader;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    #[test]
+    fn write_and_read() -> Result<()> {
+        let mut list = ListBuilder::new(Int64Builder::new());
+        list.append_value([Some(1), None]);
+        list.append_null();
+        list.append_value([]);
+        let list = list.finish();
+        let point = StructArray::from(vec![
+            (
+                Arc::new(Field::new("x", DataType::Int32, false)),
+                Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("label", DataType::Utf8, true)),
+                Arc::new(StringArray::from(vec![Some("a"), None, Some("c")])),
+            ),
+        ]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("list", list.data_type().clone(), true),
+            Field::new("point", point.data_type().clone(), false),
+            Field::new("day", DataType::Date32, true),
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                true,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(list),
+                Arc::new(point),
+                Arc::new(Date32Array::from(vec![Some(19723), None, Some(0)])),
+                Arc::new(TimestampMicrosecondArray::from(vec![
+                    Some(1_704_067_200_000_000),
+                    Some(-1),
+                    None,
+                ])),
+            ],
+        )?;
+
+        let mut writer = Writer::try_new(Vec::new(), schema.clone())?;
+        writer.write(&batch)?;
+        writer.write(&batch.slice(1, 1))?;
+        let bytes = writer.into_inner()?;
+
+        let batches = Reader::try_new(Cursor::new(bytes), schema, 10, None)?
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].slice(0, 3), batch);
+        assert_eq!(batches[0].slice(3, 1), batch.slice(1, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_type() {
+        let schema =
+            Schema::new(vec![Field::new("d", DataType::Decimal128(10, 2), true)]);
+        let err = to_avro_schema(&schema).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "This feature is not implemented: Writing Decimal128(10, 2) columns to Avro is not supported"
+        );
+    }
+}
@@ -0,0 +1,588 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Replay of the transaction log of Delta tables
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use arrow::json::LineDelimitedWriter;
+use arrow::record_batch::RecordBatch;
+use arrow_schema::{DataType, Field, Fields, Schema, TimeUnit};
+use datafusion_common::{exec_err, plan_err, DataFusionError, Result};
+use futures::TryStreamExt;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+use parquet::arrow::async_reader::ParquetObjectReader;
+use parquet::arrow::{ParquetRecordBatchStreamBuilder, ProjectionMask};
+use serde_json::Value;
+
+/// The metadata key of the Parquet field id of an Arrow [`Field`]
+const FIELD_ID_META_KEY: &str = "PARQUET:field_id";
+/// The metadata key of the column mapping id of a Delta field
+const COLUMN_MAPPING_ID_KEY: &str = "delta.columnMapping.id";
+/// The metadata key of the column mapping physical name of a Delta field
+pub const PHYSICAL_NAME_KEY: &str = "delta.columnMapping.physicalName";
+
+/// The version of a Delta table to read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeltaVersion {
+    /// The latest version
+    #[default]
+    Latest,
+    /// The given version
+    Version(i64),
+    /// The latest version committed at or before the given time, in
+    /// milliseconds since the epoch
+    Timestamp(i64),
+}
+
+/// The state of a Delta table at a version, as replayed from its log
+#[derive(Debug, Clone)]
+pub struct DeltaSnapshot {
+    /// The version of the table
+    pub version: i64,
+    /// The latest `metaData` action
+    pub metadata: Metadata,
+    /// The latest `protocol` action
+    pub protocol: Protocol,
+    /// The data files of the table, ordered by path
+    pub files: Vec<AddFile>,
+}
+
+/// A `metaData` action
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    /// The schema of the table, in Delta's JSON format
+    pub schema_string: String,
+    /// The names of the partition columns
+    pub partition_columns: Vec<String>,
+    /// The configuration of the table, e.g. `delta.columnMapping.mode`
+    pub configuration: HashMap<String, String>,
+}
+
+impl Metadata {
+    /// Returns the schema of the table as an Arrow schema. Fields with a column
+    /// mapping id carry it as their Parquet field id.
+    pub fn schema(&self) -> Result<Schema> {
+        let value: Value = serde_json::from_str(&self.schema_string)
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        Ok(Schema::new(parse_struct_fields(&value)?))
+    }
+}
+
+/// A `protocol` action
+#[derive(Debug, Clone)]
+pub struct Protocol {
+    /// The minimum version of the protocol readers must support
+    pub min_reader_version: i64,
+    /// The table features readers must support, for reader version 3
+    pub reader_features: Vec<String>,
+}
+
+/// An `add` action of a data file
+#[derive(Debug, Clone)]
+pub struct AddFile {
+    /// The path of the file, relative to the table or absolute
+    pub path: String,
+    /// The partition values of the file by partition column, `None` for nulls
+    pub partition_values: HashMap<String, Option<String>>,
+    /// The size of the file in bytes
+    pub size: i64,
+    /// When the file was created, in milliseconds since the epoch
+    pub modification_time: i64,
+    /// The statistics of the file, in Delta's JSON format
+    pub stats: Option<String>,
+    /// Whether rows of the file are deleted by a deletion vector
+    pub has_deletion_vector: bool,
+}
+
+/// A file of the `_delta_log` directory
+#[derive(Debug, PartialEq)]
+enum LogFile {
+    /// A commit, `<version>.json`
+    Commit(i64),
+    /// A part of a checkpoint, `<version>.checkpoint.parquet` or
+    /// `<version>.checkpoint.<part>.<parts>.parquet`
+    Checkpoint { version: i64, parts: usize },
+}
+
+fn parse_log_file_name(name: &str) -> Option<LogFile> {
+    let (version, rest) = name.split_once('.')?;
+    if version.len() != 20 {
+        return None;
+    }
+    let version = version.parse().ok()?;
+    match rest.split('.').collect::<Vec<_>>()[..] {
+        ["json"] => Some(LogFile::Commit(version)),
+        ["checkpoint", "parquet"] => Some(LogFile::Checkpoint { version, parts: 1 }),
+        ["checkpoint", _part, parts, "parquet"] => Some(LogFile::Checkpoint {
+            version,
+            parts: parts.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+/// Load the state of the table at `table_path` at `version`, replaying the
+/// latest complete checkpoint at or before it and the following commits
+pub async fn load_snapshot(
+    store: &Arc<dyn ObjectStore>,
+    table_path: &Path,
+    version: DeltaVersion,
+) -> Result<DeltaSnapshot> {
+    let log_dir = table_path.child("_delta_log");
+    let mut commits = BTreeMap::new();
+    let mut checkpoints: BTreeMap<i64, (usize, Vec<ObjectMeta>)> = BTreeMap::new();
+    let files: Vec<ObjectMeta> = store.list(Some(&log_dir)).try_collect().await?;
+    for meta in files {
+        match meta.location.filename().and_then(parse_log_file_name) {
+            Some(LogFile::Commit(version)) => {
+                commits.insert(version, meta);
+            }
+            Some(LogFile::Checkpoint { version, parts }) => checkpoints
+                .entry(version)
+                .or_insert((parts, vec![]))
+                .1
+                .push(meta),
+            None => {}
+        }
+    }
+
+    let latest = commits.keys().chain(checkpoints.keys()).max().copied();
+    let Some(latest) = latest else {
+        return plan_err!("No Delta transaction log found in {log_dir}");
+    };
+    let version = match version {
+        DeltaVersion::Latest => latest,
+        DeltaVersion::Version(version) if (0..=latest).contains(&version) => version,
+        DeltaVersion::Version(version) => {
+            return plan_err!(
+                "Delta table version {version} not found, the latest version is {latest}"
+            )
+        }
+        DeltaVersion::Timestamp(timestamp_ms) => {
+            // commit timestamps increase with the version, so the latest commit
+            // at or before `timestamp_ms` is found with a binary search
+            let commits: Vec<_> = commits.iter().collect();
+            let (mut low, mut high) = (0, commits.len());
+            while low < high {
+                let mid = low + (high - low) / 2;
+                match commit_timestamp(store, commits[mid].1).await? <= timestamp_ms {
+                    true => low = mid + 1,
+                    false => high = mid,
+                }
+            }
+            match low.checked_sub(1) {
+                Some(idx) => *commits[idx].0,
+                None => {
+                    return plan_err!("No Delta table version found as of {timestamp_ms}")
+                }
+            }
+        }
+    };
+
+    let checkpoint = checkpoints
+        .range(..=version)
+        .rev()
+        .find(|(_, (parts, files))| *parts == files.len());
+    let first_commit = checkpoint.map_or(0, |(version, _)| version + 1);
+    let commit_files = (first_commit..=version)
+        .map(|commit| match commits.get(&commit) {
+            Some(meta) => Ok(meta.location.clone()),
+            None => plan_err!(
+                "Delta table version {version} can not be loaded, commit {commit} is missing"
+            ),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut replay = LogReplay::default();
+    if let Some((_, (_, parts))) = checkpoint {
+        let mut parts = parts.clone();
+        parts.sort_by(|a, b| a.location.cmp(&b.location));
+        for part in parts {
+            for action in read_checkpoint(store, part).await? {
+                replay.apply(&action)?;
+            }
+        }
+    }
+    let commits = futures::future::try_join_all(commit_files.iter().map(|location| {
+        let store = Arc::clone(store);
+        async move { store.get(location).await?.bytes().await }
+    }))
+    .await?;
+    for commit in commits {
+        for action in parse_actions(&commit)? {
+            replay.apply(&action)?;
+        }
+    }
+
+    let (Some(metadata), Some(protocol)) = (replay.metadata, replay.protocol) else {
+        return exec_err!("Delta table version {version} has no metadata or protocol");
+    };
+    let mut files: Vec<_> = replay.files.into_values().collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(DeltaSnapshot {
+        version,
+        metadata,
+        protocol,
+        files,
+    })
+}
+
+/// Returns the time of a commit in milliseconds since the epoch: the
+/// `inCommitTimestamp` of its `commitInfo` action if in-commit timestamps are
+/// enabled, its `timestamp` otherwise, or the modification time of the commit
+/// file if it has no `commitInfo` action
+async fn commit_timestamp(
+    store: &Arc<dyn ObjectStore>,
+    meta: &ObjectMeta,
+) -> Result<i64> {
+    let bytes = store.get(&meta.location).await?.bytes().await?;
+    let timestamp = parse_actions(&bytes)?.iter().find_map(|action| {
+        let commit_info = action.get("commitInfo")?;
+        commit_info
+            .get("inCommitTimestamp")
+            .or_else(|| commit_info.get("timestamp"))
+            .and_then(Value::as_i64)
+    });
+    Ok(timestamp.unwrap_or_else(|| meta.last_modified.timestamp_millis()))
+}
+
+/// The state of a table while its actions are applied in order
+#[derive(Default)]
+struct LogReplay {
+    metadata: Option<Metadata>,
+    protocol: Option<Protocol>,
+    files: HashMap<String, AddFile>,
+}
+
+impl LogReplay {
+    fn apply(&mut self, action: &Value) -> Result<()> {
+        if let Some(add) = action.get("add") {
+            let add = parse_add(add)?;
+            self.files.insert(add.path.clone(), add);
+        } else if let Some(remove) = action.get("remove") {
+            self.files.remove(get_str(remove, "path")?);
+        } else if let Some(metadata) = action.get("metaData") {
+            self.metadata = Some(parse_metadata(metadata)?);
+        } else if let Some(protocol) = action.get("protocol") {
+            self.protocol = Some(Protocol {
+                min_reader_version: get_i64(protocol, "minReaderVersion")?,
+                reader_features: string_array(protocol.get("readerFeatures")),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn parse_add(add: &Value) -> Result<AddFile> {
+    let partition_values = match add.get("partitionValues") {
+        Some(Value::Object(values)) => values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.as_str().map(str::to_string)))
+            .collect(),
+        _ => HashMap::new(),
+    };
+    Ok(AddFile {
+        path: get_str(add, "path")?.to_string(),
+        partition_values,
+        size: get_i64(add, "size")?,
+        modification_time: add
+            .get("modificationTime")
+            .and_then(Value::as_i64)
+            .unwrap_or(0),
+        stats: add.get("stats").and_then(Value::as_str).map(str::to_string),
+        has_deletion_vector: add
+            .get("deletionVector")
+            .is_some_and(|deletion_vector| !deletion_vector.is_null()),
+    })
+}
+
+fn parse_metadata(metadata: &Value) -> Result<Metadata> {
+    let configuration = match metadata.get("configuration") {
+        Some(Value::Object(configuration)) => configuration
+            .iter()
+            .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+            .collect(),
+        _ => HashMap::new(),
+    };
+    Ok(Metadata {
+        schema_string: get_str(metadata, "schemaString")?.to_string(),
+        partition_columns: string_array(metadata.get("partitionColumns")),
+        configuration,
+    })
+}
+
+/// Parse the newline delimited actions of a commit
+fn parse_actions(bytes: &[u8]) -> Result<Vec<Value>> {
+    bytes
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|line| {
+            serde_json::from_slice(line)
+                .map_err(|e| DataFusionError::External(Box::new(e)))
+        })
+        .collect()
+}
+
+/// Read the actions of a checkpoint part relevant to readers, by converting its
+/// `add`, `metaData` and `protocol` columns to JSON actions
+async fn read_checkpoint(
+    store: &Arc<dyn ObjectStore>,
+    meta: ObjectMeta,
+) -> Result<Vec<Value>> {
+    let reader = ParquetObjectReader::new(Arc::clone(store), meta);
+    let builder = ParquetRecordBatchStreamBuilder::new(reader).await?;
+    let columns: Vec<_> = builder
+        .schema()
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| {
+            matches!(field.name().as_str(), "add" | "metaData" | "protocol")
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+    let mask = ProjectionMask::roots(builder.parquet_schema(), columns);
+    let batches: Vec<RecordBatch> =
+        builder.with_projection(mask).build()?.try_collect().await?;
+
+    let mut writer = LineDelimitedWriter::new(vec![]);
+    writer.write_batches(&batches.iter().collect::<Vec<_>>())?;
+    writer.finish()?;
+    parse_actions(&writer.into_inner())
+}
+
+fn parse_struct_fields(value: &Value) -> Result<Fields> {
+    let Some(fields) = value.get("fields").and_then(Value::as_array) else {
+        return exec_err!("Delta struct type is missing 'fields'");
+    };
+    fields.iter().map(parse_field).collect()
+}
+
+fn parse_field(value: &Value) -> Result<Field> {
+    let name = get_str(value, "name")?;
+    let nullable = value
+        .get("nullable")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    let data_type = parse_type(get(value, "type")?)?;
+
+    let mut metadata = HashMap::new();
+    if let Some(field_metadata) = value.get("metadata") {
+        if let Some(id) = field_metadata.get(COLUMN_MAPPING_ID_KEY) {
+            metadata.insert(FIELD_ID_META_KEY.to_string(), id.to_string());
+        }
+        if let Some(physical_name) = field_metadata
+            .get(PHYSICAL_NAME_KEY)
+            .and_then(Value::as_str)
+        {
+            metadata.insert(PHYSICAL_NAME_KEY.to_string(), physical_name.to_string());
+        }
+    }
+    Ok(Field::new(name, data_type, nullable).with_metadata(metadata))
+}
+
+/// Converts a Delta type to an Arrow type
+fn parse_type(value: &Value) -> Result<DataType> {
+    let Some(name) = value.as_str() else {
+        return match get_str(value, "type")? {
+            "struct" => Ok(DataType::Struct(parse_struct_fields(value)?)),
+            "array" => {
+                let nullable = value
+                    .get("containsNull")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true);
+                let element = parse_type(get(value, "elementType")?)?;
+                Ok(DataType::List(Arc::new(Field::new(
+                    "element", element, nullable,
+                ))))
+            }
+            "map" => {
+                let nullable = value
+                    .get("valueContainsNull")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true);
+                let key = Field::new("key", parse_type(get(value, "keyType")?)?, false);
+                let value =
+                    Field::new("value", parse_type(get(value, "valueType")?)?, nullable);
+                Ok(DataType::Map(
+                    Arc::new(Field::new(
+                        "key_value",
+                        DataType::Struct(Fields::from(vec![key, value])),
+                        false,
+                    )),
+                    false,
+                ))
+            }
+            other => exec_err!("Unsupported Delta type {other}"),
+        };
+    };
+
+    Ok(match name {
+        "boolean" => DataType::Boolean,
+        "byte" => DataType::Int8,
+        "short" => DataType::Int16,
+        "integer" => DataType::Int32,
+        "long" => DataType::Int64,
+        "float" => DataType::Float32,
+        "double" => DataType::Float64,
+        "string" => DataType::Utf8,
+        "binary" => DataType::Binary,
+        "date" => DataType::Date32,
+        "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        "timestamp_ntz" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        _ => {
+            let decimal = name
+                .strip_prefix("decimal(")
+                .and_then(|args| args.strip_suffix(')'))
+                .and_then(|args| args.split_once(','))
+                .and_then(|(p, s)| {
+                    Some((p.trim().parse().ok()?, s.trim().parse().ok()?))
+                });
+            match decimal {
+                Some((precision, scale)) => DataType::Decimal128(precision, scale),
+                None => return exec_err!("Unsupported Delta type {name}"),
+            }
+        }
+    })
+}
+
+fn string_array(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| Some(value.as_str()?.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn get<'a>(value: &'a Value, key: &str) -> Result<&'a Value> {
+    match value.get(key) {
+        Some(value) => Ok(value),
+        None => exec_err!("Delta log action is missing '{key}'"),
+    }
+}
+
+fn get_i64(value: &Value, key: &str) -> Result<i64> {
+    match get(value, key)?.as_i64() {
+        Some(value) => Ok(value),
+        None => exec_err!("Delta log '{key}' is not an integer"),
+    }
+}
+
+fn get_str<'a>(value: &'a Value, key: &str) -> Result<&'a str> {
+    match get(value, key)?.as_str() {
+        Some(value) => Ok(value),
+        None => exec_err!("Delta log '{key}' is not a string"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_file_name() {
+        assert_eq!(
+            parse_log_file_name("00000000000000000010.json"),
+            Some(LogFile::Commit(10))
+        );
+        assert_eq!(
+            parse_log_file_name("00000000000000000010.checkpoint.parquet"),
+            Some(LogFile::Checkpoint {
+                version: 10,
+                parts: 1
+            })
+        );
+        assert_eq!(
+            parse_log_file_name(
+                "00000000000000000010.checkpoint.0000000001.0000000002.parquet"
+            ),
+            Some(LogFile::Checkpoint {
+                version: 10,
+                parts: 2
+            })
+        );
+        assert_eq!(parse_log_file_name("_last_checkpoint"), None);
+        assert_eq!(parse_log_file_name("00000000000000000010.crc"), None);
+    }
+
+    #[test]
+    fn test_parse_schema() -> Result<()> {
+        let metadata = Metadata {
+            schema_string: r#"{"type": "struct", "fields": [
+                {"name": "a", "type": "long", "nullable": false, "metadata": {
+                    "delta.columnMapping.id": 1,
+                    "delta.columnMapping.physicalName": "col-1"
+                }},
+                {"name": "b", "type": "decimal(10,2)", "nullable": true, "metadata": {}},
+                {"name": "c", "type": {"type": "array", "elementType": "timestamp",
+                    "containsNull": true}, "nullable": true, "metadata": {}}
+            ]}"#
+            .to_string(),
+            partition_columns: vec![],
+            configuration: HashMap::new(),
+        };
+        let schema = metadata.schema()?;
+        let a = schema.field(0);
+        assert!(!a.is_nullable());
+        assert_eq!(a.metadata().get(FIELD_ID_META_KEY), Some(&"1".to_string()));
+        assert_eq!(
+            a.metadata().get(PHYSICAL_NAME_KEY),
+            Some(&"col-1".to_string())
+        );
+        assert_eq!(schema.field(1).data_type(), &DataType::Decimal128(10, 2));
+        assert_eq!(
+            schema.field(2).data_type(),
+            &DataType::List(Arc::new(Field::new(
+                "element",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                true
+            )))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay() -> Result<()> {
+        let commit = br#"
+{"protocol": {"minReaderVersion": 1, "minWriterVersion": 2}}
+{"metaData": {"id": "t", "schemaString": "{}", "partitionColumns": ["p"], "configuration": {}}}
+{"add": {"path": "p=1/a.parquet", "partitionValues": {"p": "1"}, "size": 10, "modificationTime": 1, "dataChange": true}}
+{"add": {"path": "p=__HIVE_DEFAULT_PARTITION__/b.parquet", "partitionValues": {"p": null}, "size": 20, "modificationTime": 1, "dataChange": true}}
+"#;
+        let mut replay = LogReplay::default();
+        for action in parse_actions(commit)? {
+            replay.apply(&action)?;
+        }
+        let commit = br#"{"remove": {"path": "p=1/a.parquet", "dataChange": true}}"#;
+        for action in parse_actions(commit)? {
+            replay.apply(&action)?;
+        }
+
+        assert_eq!(replay.metadata.unwrap().partition_columns, vec!["p"]);
+        assert_eq!(replay.files.len(), 1);
+        let file = &replay.files["p=__HIVE_DEFAULT_PARTITION__/b.parquet"];
+        assert_eq!(file.partition_values["p"], None);
+        assert_eq!(file.size, 20);
+        Ok(())
+    }
+}
@@ -0,0 +1,730 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`DeltaTableProvider`]: a [`TableProvider`] reading versions of Delta Lake tables
+
+pub mod log;
+mod stats;
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::datatypes::{Field, SchemaBuilder, SchemaRef};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use datafusion_common::{
+    not_impl_err, plan_err, project_schema, DataFusionError, Result, ScalarValue,
+    Statistics, ToDFSchema,
+};
+use datafusion_expr::utils::conjunction;
+use datafusion_expr::{
+    CreateExternalTable, Expr, TableProviderFilterPushDown, TableType, TableVersion,
+};
+use datafusion_physical_expr::create_physical_expr;
+use object_store::{path::Path, ObjectMeta};
+
+use self::log::{load_snapshot, AddFile, DeltaSnapshot, DeltaVersion, PHYSICAL_NAME_KEY};
+use self::stats::DeltaPruningStatistics;
+use crate::datasource::file_format::parquet::ParquetFormat;
+use crate::datasource::file_format::FileFormat;
+use crate::datasource::listing::{split_files, ListingTableUrl, PartitionedFile};
+use crate::datasource::physical_plan::FileScanConfig;
+use crate::datasource::provider::TableProviderFactory;
+use crate::datasource::TableProvider;
+use crate::execution::context::SessionState;
+use crate::physical_optimizer::pruning::PruningPredicate;
+use crate::physical_plan::empty::EmptyExec;
+use crate::physical_plan::ExecutionPlan;
+
+/// The highest reader version of the Delta protocol that is supported
+const MAX_READER_VERSION: i64 = 3;
+
+/// The reader features of the Delta protocol that are supported. Tables using
+/// deletion vectors can only be read if no data file has one.
+const READER_FEATURES: [&str; 4] = [
+    "columnMapping",
+    "deletionVectors",
+    "timestampNtz",
+    "vacuumProtocolCheck",
+];
+
+/// A read-only [`TableProvider`] reading a version of a [Delta Lake] table.
+///
+/// The data files of the version are found by replaying the JSON commits of
+/// the `_delta_log` directory of the table, starting from its latest
+/// checkpoint. They are pruned with the partition values and the column
+/// statistics recorded in the log, and read with a [`ParquetExec`].
+///
+/// As for a [`ListingTable`], the partition columns are the last columns of
+/// the table schema. Tables with column mapping are read by field id.
+///
+/// [Delta Lake]: https://github.com/delta-io/delta/blob/master/PROTOCOL.md
+/// [`ParquetExec`]: crate::datasource::physical_plan::ParquetExec
+/// [`ListingTable`]: crate::datasource::listing::ListingTable
+#[derive(Debug, Clone)]
+pub struct DeltaTableProvider {
+    table_url: ListingTableUrl,
+    snapshot: Arc<DeltaSnapshot>,
+    /// The schema of the data columns, stored in the data files
+    file_schema: SchemaRef,
+    /// The partition columns, whose values are recorded in the log
+    partition_cols: Vec<Field>,
+    /// The schema of the table, the data columns followed by the partition columns
+    schema: SchemaRef,
+    /// The physical names of the data columns and of the partition columns,
+    /// used in the statistics and partition values of the log
+    physical_names: Vec<String>,
+}
+
+impl DeltaTableProvider {
+    /// Read `version` of the table at `location`
+    pub async fn try_new(
+        state: &SessionState,
+        location: impl AsRef<str>,
+        version: DeltaVersion,
+    ) -> Result<Self> {
+        let table_url = ListingTableUrl::parse(location)?;
+        let store = state.runtime_env().object_store(table_url.object_store())?;
+        let snapshot = load_snapshot(&store, table_url.prefix(), version).await?;
+
+        let protocol = &snapshot.protocol;
+        if protocol.min_reader_version > MAX_READER_VERSION {
+            return not_impl_err!(
+                "Delta reader version {} is not supported",
+                protocol.min_reader_version
+            );
+        }
+        if let Some(feature) = protocol
+            .reader_features
+            .iter()
+            .find(|feature| !READER_FEATURES.contains(&feature.as_str()))
+        {
+            return not_impl_err!("Delta reader feature {feature} is not supported");
+        }
+
+        let table_schema = snapshot.metadata.schema()?;
+        let partition_columns = &snapshot.metadata.partition_columns;
+        let mut file_schema = SchemaBuilder::new();
+        let mut partition_cols = vec![];
+        for field in table_schema.fields() {
+            match partition_columns.contains(field.name()) {
+                true => partition_cols.push(field.as_ref().clone()),
+                false => file_schema.push(field.clone()),
+            }
+        }
+        let file_schema = Arc::new(file_schema.finish());
+        let mut schema = SchemaBuilder::from(file_schema.fields());
+        for field in &partition_cols {
+            schema.push(field.clone());
+        }
+        let schema = Arc::new(schema.finish());
+        let physical_names = schema
+            .fields()
+            .iter()
+            .map(|field| match field.metadata().get(PHYSICAL_NAME_KEY) {
+                Some(physical_name) => physical_name.clone(),
+                None => field.name().clone(),
+            })
+            .collect();
+
+        Ok(Self {
+            table_url,
+            snapshot: Arc::new(snapshot),
+            file_schema,
+            partition_cols,
+            schema,
+            physical_names,
+        })
+    }
+
+    /// Returns the version of the table that is read
+    pub fn version(&self) -> i64 {
+        self.snapshot.version
+    }
+
+    /// Returns the state of the table at the version that is read
+    pub fn snapshot(&self) -> &DeltaSnapshot {
+        &self.snapshot
+    }
+
+    /// Returns the typed partition values of a data file, where missing and
+    /// empty values are nulls
+    fn partition_values(&self, file: &AddFile) -> Result<Vec<ScalarValue>> {
+        let num_file_columns = self.file_schema.fields().len();
+        self.partition_cols
+            .iter()
+            .zip(&self.physical_names[num_file_columns..])
+            .map(|(field, physical_name)| {
+                match file.partition_values.get(physical_name).cloned().flatten() {
+                    Some(value) if !value.is_empty() => {
+                        ScalarValue::try_from_string(value, field.data_type())
+                    }
+                    _ => ScalarValue::try_from(field.data_type()),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the object store location of a data file, whose path is either
+    /// absolute or relative to the table
+    fn object_meta(&self, file: &AddFile) -> Result<ObjectMeta> {
+        let location = if file.path.contains("://") {
+            ListingTableUrl::parse(&file.path)?.prefix().clone()
+        } else {
+            let path = Path::from_url_path(&file.path)?;
+            Path::from_iter(self.table_url.prefix().parts().chain(path.parts()))
+        };
+        Ok(ObjectMeta {
+            location,
+            last_modified: Utc.timestamp_nanos(file.modification_time * 1_000_000),
+            size: file.size as usize,
+            e_tag: None,
+            version: None,
+        })
+    }
+
+    /// Returns true if the data files are read by field id, in which case
+    /// filters, that refer to columns by name, can not be evaluated by them
+    fn has_column_mapping(&self) -> bool {
+        self.snapshot
+            .metadata
+            .configuration
+            .get("delta.columnMapping.mode")
+            .is_some_and(|mode| mode != "none")
+    }
+}
+
+#[async_trait]
+impl TableProvider for DeltaTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let predicate = match conjunction(filters.to_vec()) {
+            Some(expr) => {
+                let df_schema = self.schema.as_ref().clone().to_dfschema()?;
+                Some(create_physical_expr(
+                    &expr,
+                    &df_schema,
+                    state.execution_props(),
+                )?)
+            }
+            None => None,
+        };
+
+        let files = &self.snapshot.files;
+        if let Some(file) = files.iter().find(|file| file.has_deletion_vector) {
+            return not_impl_err!(
+                "Delta deletion vectors are not supported, found one for {}",
+                file.path
+            );
+        }
+        let partition_values = files
+            .iter()
+            .map(|file| self.partition_values(file))
+            .collect::<Result<Vec<_>>>()?;
+
+        // prune the files with the statistics and partition values of the log
+        let keep = match &predicate {
+            Some(predicate) => {
+                match PruningPredicate::try_new(predicate.clone(), self.schema.clone()) {
+                    Ok(pruning_predicate) => {
+                        pruning_predicate.prune(&DeltaPruningStatistics::new(
+                            files,
+                            &self.schema,
+                            &self.physical_names[..self.file_schema.fields().len()],
+                            &partition_values,
+                        ))?
+                    }
+                    Err(e) => {
+                        ::log::debug!("Could not create pruning predicate for: {e}");
+                        vec![true; files.len()]
+                    }
+                }
+            }
+            None => vec![true; files.len()],
+        };
+        let partitioned_files = files
+            .iter()
+            .zip(partition_values)
+            .zip(keep)
+            .filter(|(_, keep)| *keep)
+            .map(|((file, partition_values), _)| {
+                let mut partitioned_file = PartitionedFile::from(self.object_meta(file)?);
+                partitioned_file.partition_values = partition_values;
+                Ok(partitioned_file)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if partitioned_files.is_empty() {
+            let projected_schema = project_schema(&self.schema, projection)?;
+            return Ok(Arc::new(EmptyExec::new(projected_schema)));
+        }
+
+        let predicate = predicate.filter(|_| !self.has_column_mapping());
+        ParquetFormat::default()
+            .with_options(state.default_table_options().parquet)
+            .create_physical_plan(
+                state,
+                FileScanConfig {
+                    object_store_url: self.table_url.object_store(),
+                    file_schema: self.file_schema.clone(),
+                    file_groups: split_files(
+                        partitioned_files,
+                        state.config().target_partitions(),
+                    ),
+                    statistics: Statistics::new_unknown(&self.schema),
+                    projection: projection.cloned(),
+                    limit,
+                    table_partition_cols: self.partition_cols.clone(),
+                    output_ordering: vec![],
+                    bucket_by: None,
+                },
+                predicate.as_ref(),
+            )
+            .await
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        // filters are only used for pruning
+        Ok(vec![TableProviderFilterPushDown::Inexact; filters.len()])
+    }
+
+    async fn with_version(
+        &self,
+        state: &SessionState,
+        version: TableVersion,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let version = match version {
+            TableVersion::Version(version) => DeltaVersion::Version(version),
+            TableVersion::Timestamp(timestamp_ms) => {
+                DeltaVersion::Timestamp(timestamp_ms)
+            }
+        };
+        let table = Self::try_new(state, self.table_url.as_str(), version).await?;
+        Ok(Arc::new(table))
+    }
+}
+
+/// A [`TableProviderFactory`] creating a [`DeltaTableProvider`] for
+/// `CREATE EXTERNAL TABLE ... STORED AS DELTA LOCATION '...'`.
+///
+/// An older version may be read with the `version` option, or with the
+/// `timestamp_as_of` option in milliseconds since the epoch.
+#[derive(Debug, Default)]
+pub struct DeltaTableFactory {}
+
+impl DeltaTableFactory {
+    /// Creates a new `DeltaTableFactory`
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TableProviderFactory for DeltaTableFactory {
+    async fn create(
+        &self,
+        state: &SessionState,
+        cmd: &CreateExternalTable,
+    ) -> Result<Arc<dyn TableProvider>> {
+        if !cmd.schema.fields().is_empty() {
+            return plan_err!(
+                "The schema of a Delta table is read from its log and can not be specified"
+            );
+        }
+
+        let mut version = DeltaVersion::Latest;
+        for (key, value) in &cmd.options {
+            let parsed = value.parse::<i64>().map_err(|e| {
+                DataFusionError::Plan(format!(
+                    "Invalid value '{value}' for Delta option {key}: {e}"
+                ))
+            });
+            version = match key.as_str() {
+                "version" => DeltaVersion::Version(parsed?),
+                "timestamp_as_of" => DeltaVersion::Timestamp(parsed?),
+                _ => return plan_err!("Unknown Delta table option {key}"),
+            };
+        }
+        let table = DeltaTableProvider::try_new(state, &cmd.location, version).await?;
+        Ok(Arc::new(table))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::assert_batches_eq;
+    use crate::physical_plan::displayable;
+    use crate::prelude::SessionContext;
+    use arrow::array::{ArrayRef, Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Fields, Schema};
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
+    use std::fs::File;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    const SCHEMA_STRING: &str = r#"{\"type\":\"struct\",\"fields\":[{\"name\":\"id\",\"type\":\"long\",\"nullable\":true,\"metadata\":{}},{\"name\":\"value\",\"type\":\"string\",\"nullable\":true,\"metadata\":{}},{\"name\":\"part\",\"type\":\"string\",\"nullable\":true,\"metadata\":{}}]}"#;
+
+    /// Writes a data file of the partition `part` with the given ids, returning
+    /// its `add` action
+    fn write_data_file(
+        dir: &std::path::Path,
+        part: &str,
+        ids: Vec<i64>,
+    ) -> Result<String> {
+        let values: Vec<_> = ids.iter().map(|id| format!("v{id}")).collect();
+        let batch = RecordBatch::try_from_iter(vec![
+            ("id", Arc::new(Int64Array::from(ids.clone())) as ArrayRef),
+            ("value", Arc::new(StringArray::from(values)) as ArrayRef),
+        ])?;
+        let path = format!("part={part}/{}.parquet", ids[0]);
+        std::fs::create_dir_all(dir.join(format!("part={part}")))?;
+        let mut writer =
+            ArrowWriter::try_new(File::create(dir.join(&path))?, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        let size = std::fs::metadata(dir.join(&path))?.len();
+        let stats = format!(
+            r#"{{\"numRecords\":{},\"minValues\":{{\"id\":{}}},\"maxValues\":{{\"id\":{}}},\"nullCount\":{{\"id\":0}}}}"#,
+            ids.len(),
+            ids.iter().min().unwrap(),
+            ids.iter().max().unwrap()
+        );
+        Ok(format!(
+            r#"{{"add":{{"path":"{path}","partitionValues":{{"part":"{part}"}},"size":{size},"modificationTime":0,"dataChange":true,"stats":"{stats}"}}}}"#
+        ))
+    }
+
+    fn write_commit(
+        dir: &std::path::Path,
+        version: i64,
+        actions: &[String],
+    ) -> Result<()> {
+        let path = dir.join("_delta_log").join(format!("{version:020}.json"));
+        std::fs::write(path, actions.join("\n"))?;
+        Ok(())
+    }
+
+    /// Writes a checkpoint of the given actions, converting them with the
+    /// Arrow JSON reader
+    fn write_checkpoint(
+        dir: &std::path::Path,
+        version: i64,
+        actions: &[String],
+    ) -> Result<()> {
+        let string_map = || {
+            DataType::Map(
+                Arc::new(Field::new(
+                    "key_value",
+                    DataType::Struct(Fields::from(vec![
+                        Field::new("key", DataType::Utf8, false),
+                        Field::new("value", DataType::Utf8, true),
+                    ])),
+                    false,
+                )),
+                false,
+            )
+        };
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "add",
+                DataType::Struct(Fields::from(vec![
+                    Field::new("path", DataType::Utf8, true),
+                    Field::new("partitionValues", string_map(), true),
+                    Field::new("size", DataType::Int64, true),
+                    Field::new("modificationTime", DataType::Int64, true),
+                    Field::new("dataChange", DataType::Boolean, true),
+                    Field::new("stats", DataType::Utf8, true),
+                ])),
+                true,
+            ),
+            Field::new(
+                "metaData",
+                DataType::Struct(Fields::from(vec![
+                    Field::new("id", DataType::Utf8, true),
+                    Field::new("schemaString", DataType::Utf8, true),
+                    Field::new(
+                        "partitionColumns",
+                        DataType::List(Arc::new(Field::new(
+                            "item",
+                            DataType::Utf8,
+                            true,
+                        ))),
+                        true,
+                    ),
+                    Field::new("configuration", string_map(), true),
+                ])),
+                true,
+            ),
+            Field::new(
+                "protocol",
+                DataType::Struct(Fields::from(vec![
+                    Field::new("minReaderVersion", DataType::Int32, true),
+                    Field::new("minWriterVersion", DataType::Int32, true),
+                ])),
+                true,
+            ),
+        ]));
+        let json = actions.join("\n");
+        let batches = arrow::json::ReaderBuilder::new(schema.clone())
+            .build(Cursor::new(json.as_bytes()))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let path = dir
+            .join("_delta_log")
+            .join(format!("{version:020}.checkpoint.parquet"));
+        let mut writer = ArrowWriter::try_new(File::create(path)?, schema, None)?;
+        for batch in &batches {
+            writer.write(batch)?;
+        }
+        writer.close()?;
+        Ok(())
+    }
+
+    /// Creates a table partitioned by `part`. Version 0 adds the ids 1 to 3 at
+    /// time 1000, version 1 adds the ids 4 and 5 and is checkpointed, and
+    /// version 2 replaces the ids 1 to 3 with the ids 6 and 7, and adds the id 8
+    /// with a null partition value, at the in-commit time 3000.
+    fn create_table(dir: &std::path::Path) -> Result<()> {
+        std::fs::create_dir_all(dir.join("_delta_log"))?;
+        let protocol =
+            r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":2}}"#.to_string();
+        let metadata = format!(
+            r#"{{"metaData":{{"id":"t","schemaString":"{SCHEMA_STRING}","partitionColumns":["part"],"configuration":{{}}}}}}"#
+        );
+        let a = write_data_file(dir, "x", vec![1, 2, 3])?;
+        let b = write_data_file(dir, "y", vec![4, 5])?;
+        let c = write_data_file(dir, "x", vec![6, 7])?;
+        let d = write_data_file(dir, "", vec![8])?;
+
+        let commit_info = r#"{"commitInfo":{"timestamp":1000}}"#.to_string();
+        write_commit(
+            dir,
+            0,
+            &[commit_info, protocol.clone(), metadata.clone(), a.clone()],
+        )?;
+        write_commit(dir, 1, &[b.clone()])?;
+        write_checkpoint(dir, 1, &[protocol, metadata, a, b])?;
+        let commit_info =
+            r#"{"commitInfo":{"inCommitTimestamp":3000,"timestamp":9000}}"#.to_string();
+        let remove = r#"{"remove":{"path":"part=x/1.parquet","dataChange":true}}"#;
+        write_commit(dir, 2, &[commit_info, remove.to_string(), c, d])?;
+        // the commit of the checkpointed version is not needed anymore
+        std::fs::remove_file(dir.join("_delta_log").join(format!("{:020}.json", 1)))?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_versions() -> Result<()> {
+        let dir = TempDir::new()?;
+        create_table(dir.path())?;
+        let location = dir.path().to_str().unwrap();
+
+        let ctx = SessionContext::new();
+        let table =
+            DeltaTableProvider::try_new(&ctx.state(), location, DeltaVersion::Latest)
+                .await?;
+        assert_eq!(table.version(), 2);
+        ctx.register_table("t", Arc::new(table))?;
+
+        let batches = ctx
+            .sql("SELECT * FROM t ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----+-------+------+",
+            "| id | value | part |",
+            "+----+-------+------+",
+            "| 4  | v4    | y    |",
+            "| 5  | v5    | y    |",
+            "| 6  | v6    | x    |",
+            "| 7  | v7    | x    |",
+            "| 8  | v8    |      |",
+            "+----+-------+------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        // empty partition values are nulls
+        let batches = ctx
+            .sql("SELECT id FROM t WHERE part IS NULL")
+            .await?
+            .collect()
+            .await?;
+        let expected = ["+----+", "| id |", "+----+", "| 8  |", "+----+"];
+        assert_batches_eq!(expected, &batches);
+
+        // the files are pruned with the statistics of the log
+        let plan = ctx
+            .sql("SELECT * FROM t WHERE id > 5")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let plan = displayable(plan.as_ref()).indent(true).to_string();
+        assert!(plan.contains("part=x/6.parquet"), "{plan}");
+        assert!(!plan.contains("part=y/4.parquet"), "{plan}");
+
+        ctx.sql(&format!(
+            "CREATE EXTERNAL TABLE t0 STORED AS DELTA LOCATION '{location}' \
+             OPTIONS ('version' '0')"
+        ))
+        .await?;
+        let batches = ctx
+            .sql("SELECT id, part FROM t0 ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----+------+",
+            "| id | part |",
+            "+----+------+",
+            "| 1  | x    |",
+            "| 2  | x    |",
+            "| 3  | x    |",
+            "+----+------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        // version 1 is read from its checkpoint
+        let table =
+            DeltaTableProvider::try_new(&ctx.state(), location, DeltaVersion::Version(1))
+                .await?;
+        assert_eq!(table.snapshot().files.len(), 2);
+
+        let err =
+            DeltaTableProvider::try_new(&ctx.state(), location, DeltaVersion::Version(3))
+                .await
+                .unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: Delta table version 3 not found, the latest version is 2"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_versions_sql() -> Result<()> {
+        let dir = TempDir::new()?;
+        create_table(dir.path())?;
+        let location = dir.path().to_str().unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.sql(&format!(
+            "CREATE EXTERNAL TABLE t STORED AS DELTA LOCATION '{location}'"
+        ))
+        .await?;
+
+        let batches = ctx
+            .sql(
+                "SELECT t0.id, t1.id AS id1 FROM t FOR VERSION AS OF 0 AS t0 \
+                 LEFT JOIN t FOR VERSION AS OF 1 AS t1 ON t0.id + 3 = t1.id \
+                 ORDER BY t0.id",
+            )
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----+-----+",
+            "| id | id1 |",
+            "+----+-----+",
+            "| 1  | 4   |",
+            "| 2  | 5   |",
+            "| 3  |     |",
+            "+----+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let batches = ctx
+            .sql(
+                "SELECT count(*) FROM t FOR TIMESTAMP AS OF '1970-01-01T00:00:02.999' AS t2",
+            )
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----------+",
+            "| COUNT(*) |",
+            "+----------+",
+            "| 3        |",
+            "+----------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let err = ctx
+            .sql("SELECT * FROM t FOR VERSION AS OF 3")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: Delta table version 3 not found, the latest version is 2"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_version_as_of() -> Result<()> {
+        let dir = TempDir::new()?;
+        create_table(dir.path())?;
+        let location = dir.path().to_str().unwrap();
+        let state = SessionContext::new().state();
+
+        // the in-commit timestamp of version 2 is used rather than its
+        // timestamp, and the modification times of the commit files are ignored
+        for (timestamp_ms, version) in [(1000, 0), (2999, 0), (3000, 2), (5000, 2)] {
+            let table = DeltaTableProvider::try_new(
+                &state,
+                location,
+                DeltaVersion::Timestamp(timestamp_ms),
+            )
+            .await?;
+            assert_eq!(table.version(), version, "as of {timestamp_ms}");
+        }
+
+        let err =
+            DeltaTableProvider::try_new(&state, location, DeltaVersion::Timestamp(999))
+                .await
+                .unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: No Delta table version found as of 999"
+        );
+        Ok(())
+    }
+}
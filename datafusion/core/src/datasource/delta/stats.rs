@@ -0,0 +1,276 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`PruningStatistics`] of the data files of a Delta table
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::array::{new_null_array, ArrayRef, BooleanArray, UInt64Array};
+use arrow_schema::{DataType, Schema};
+use datafusion_common::{Column, ScalarValue};
+use serde_json::Value;
+
+use super::log::AddFile;
+use crate::physical_optimizer::pruning::PruningStatistics;
+
+/// [`PruningStatistics`] of the data files of a Delta table, with the
+/// statistics recorded in the log for data columns, and the partition values
+/// for partition columns
+pub(crate) struct DeltaPruningStatistics<'a> {
+    /// The table schema, with the partition columns last
+    schema: &'a Schema,
+    /// The physical names of the data columns
+    physical_names: &'a [String],
+    /// The parsed statistics of each file, if any
+    stats: Vec<Option<Value>>,
+    /// The partition values of each file
+    partition_values: &'a [Vec<ScalarValue>],
+}
+
+impl<'a> DeltaPruningStatistics<'a> {
+    pub(crate) fn new(
+        files: &[AddFile],
+        schema: &'a Schema,
+        physical_names: &'a [String],
+        partition_values: &'a [Vec<ScalarValue>],
+    ) -> Self {
+        let stats = files
+            .iter()
+            .map(|file| serde_json::from_str(file.stats.as_ref()?).ok())
+            .collect();
+        Self {
+            schema,
+            physical_names,
+            stats,
+            partition_values,
+        }
+    }
+
+    /// Returns the values of a partition column, or the statistic of a data
+    /// column, of each file
+    fn values(&self, column: &Column, statistic: &str) -> Option<ArrayRef> {
+        let idx = self.schema.index_of(&column.name).ok()?;
+        let data_type = self.schema.field(idx).data_type();
+        let values: Vec<_> = match self.physical_names.get(idx) {
+            Some(physical_name) => self
+                .stats
+                .iter()
+                .map(|stats| {
+                    let value = stats.as_ref()?.get(statistic)?.get(physical_name)?;
+                    let value = json_to_scalar(value, data_type)?;
+                    match statistic {
+                        "maxValues" => Some(round_up_max(value)),
+                        _ => Some(value),
+                    }
+                })
+                .collect(),
+            None => {
+                let partition_idx = idx - self.physical_names.len();
+                self.partition_values
+                    .iter()
+                    .map(|values| Some(values[partition_idx].clone()))
+                    .collect()
+            }
+        };
+        if values.iter().all(Option::is_none) {
+            return Some(new_null_array(data_type, values.len()));
+        }
+        let null = ScalarValue::try_from(data_type).ok()?;
+        ScalarValue::iter_to_array(
+            values
+                .into_iter()
+                .map(|value| value.unwrap_or_else(|| null.clone())),
+        )
+        .ok()
+    }
+
+    fn num_records(&self, file: usize) -> Option<u64> {
+        self.stats[file].as_ref()?.get("numRecords")?.as_u64()
+    }
+}
+
+impl PruningStatistics for DeltaPruningStatistics<'_> {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column, "minValues")
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column, "maxValues")
+    }
+
+    fn num_containers(&self) -> usize {
+        self.stats.len()
+    }
+
+    fn null_counts(&self, column: &Column) -> Option<ArrayRef> {
+        let idx = self.schema.index_of(&column.name).ok()?;
+        let counts: UInt64Array = match self.physical_names.get(idx) {
+            Some(physical_name) => self
+                .stats
+                .iter()
+                .map(|stats| {
+                    stats
+                        .as_ref()?
+                        .get("nullCount")?
+                        .get(physical_name)?
+                        .as_u64()
+                })
+                .collect(),
+            None => {
+                let partition_idx = idx - self.physical_names.len();
+                self.partition_values
+                    .iter()
+                    .enumerate()
+                    .map(|(file, values)| match values[partition_idx].is_null() {
+                        true => self.num_records(file),
+                        false => Some(0),
+                    })
+                    .collect()
+            }
+        };
+        Some(Arc::new(counts))
+    }
+
+    fn row_counts(&self, _column: &Column) -> Option<ArrayRef> {
+        let counts: UInt64Array = (0..self.stats.len())
+            .map(|file| self.num_records(file))
+            .collect();
+        Some(Arc::new(counts))
+    }
+
+    fn contained(
+        &self,
+        _column: &Column,
+        _values: &HashSet<ScalarValue>,
+    ) -> Option<BooleanArray> {
+        None
+    }
+}
+
+/// Convert a minimum or maximum value of the statistics of a file
+fn json_to_scalar(value: &Value, data_type: &DataType) -> Option<ScalarValue> {
+    let value = match value {
+        Value::Bool(value) => ScalarValue::Boolean(Some(*value)),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => ScalarValue::Int64(Some(value)),
+            None => ScalarValue::Float64(Some(number.as_f64()?)),
+        },
+        Value::String(value) => ScalarValue::Utf8(Some(value.clone())),
+        _ => return None,
+    };
+    value.cast_to(data_type).ok()
+}
+
+/// Timestamp statistics are truncated to milliseconds, so the maximum values
+/// are rounded up to remain upper bounds
+fn round_up_max(value: ScalarValue) -> ScalarValue {
+    match value {
+        ScalarValue::TimestampMicrosecond(Some(value), tz) => {
+            ScalarValue::TimestampMicrosecond(Some(value.saturating_add(999)), tz)
+        }
+        ScalarValue::TimestampNanosecond(Some(value), tz) => {
+            ScalarValue::TimestampNanosecond(Some(value.saturating_add(999_999)), tz)
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::physical_optimizer::pruning::PruningPredicate;
+    use arrow_schema::{Field, TimeUnit};
+    use datafusion_common::Result;
+    use datafusion_expr::Operator;
+    use datafusion_physical_expr::expressions::{col, lit, BinaryExpr};
+    use std::collections::HashMap;
+
+    fn add_file(stats: Option<&str>) -> AddFile {
+        AddFile {
+            path: String::new(),
+            partition_values: HashMap::new(),
+            size: 0,
+            modification_time: 0,
+            stats: stats.map(str::to_string),
+            has_deletion_vector: false,
+        }
+    }
+
+    #[test]
+    fn test_pruning() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new(
+                "t",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                true,
+            ),
+            Field::new("p", DataType::Utf8, true),
+        ]));
+        let files = [
+            add_file(Some(
+                r#"{"numRecords": 2, "minValues": {"a": 1, "t": "2024-01-01T00:00:00.000Z"},
+                "maxValues": {"a": 5, "t": "2024-01-01T00:00:00.000Z"}, "nullCount": {"a": 0}}"#,
+            )),
+            add_file(Some(
+                r#"{"numRecords": 2, "minValues": {"a": 6}, "maxValues": {"a": 10},
+                "nullCount": {"a": 0}}"#,
+            )),
+            add_file(None),
+        ];
+        let physical_names = ["a".to_string(), "t".to_string()];
+        let partition_values = [
+            vec![ScalarValue::from("x")],
+            vec![ScalarValue::from("y")],
+            vec![ScalarValue::Utf8(None)],
+        ];
+        let stats = DeltaPruningStatistics::new(
+            &files,
+            &schema,
+            &physical_names,
+            &partition_values,
+        );
+
+        let predicate = |column: &str, op: Operator, value: ScalarValue| {
+            let expr = Arc::new(BinaryExpr::new(col(column, &schema)?, op, lit(value)));
+            PruningPredicate::try_new(expr, schema.clone())?.prune(&stats)
+        };
+        assert_eq!(
+            predicate("a", Operator::Gt, ScalarValue::Int32(Some(5)))?,
+            vec![false, true, true]
+        );
+        assert_eq!(
+            predicate("p", Operator::Eq, ScalarValue::from("x"))?,
+            vec![true, false, true]
+        );
+        // the maximum is truncated to milliseconds
+        assert_eq!(
+            predicate(
+                "t",
+                Operator::Gt,
+                ScalarValue::TimestampMicrosecond(
+                    Some(1704067200000500),
+                    Some("UTC".into())
+                )
+            )?,
+            vec![true, true, true]
+        );
+        Ok(())
+    }
+}
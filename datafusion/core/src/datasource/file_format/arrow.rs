@@ -25,6 +25,7 @@ use std::fmt::{self, Debug};
 use std::sync::Arc;
 
 use super::file_compression_type::FileCompressionType;
+use super::write::commit::StagedWrite;
 use super::write::demux::start_demuxer_task;
 use super::write::{create_writer, SharedBuffer};
 use crate::datasource::file_format::FileFormat;
@@ -43,7 +44,7 @@ use arrow_schema::{ArrowError, Schema, SchemaRef};
 use datafusion_common::{not_impl_err, DataFusionError, FileType, Statistics};
 use datafusion_execution::{SendableRecordBatchStream, TaskContext};
 use datafusion_physical_expr::{PhysicalExpr, PhysicalSortRequirement};
-use datafusion_physical_plan::insert::{DataSink, FileSinkExec};
+use datafusion_physical_plan::insert::{DataSink, FileSinkExec, WrittenFile};
 use datafusion_physical_plan::metrics::MetricsSet;
 
 use async_trait::async_trait;
@@ -118,7 +119,7 @@ impl FileFormat for ArrowFormat {
     async fn create_writer_physical_plan(
         &self,
         input: Arc<dyn ExecutionPlan>,
-        _state: &SessionState,
+        state: &SessionState,
         conf: FileSinkConfig,
         order_requirements: Option<Vec<PhysicalSortRequirement>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
@@ -129,12 +130,11 @@ impl FileFormat for ArrowFormat {
         let sink_schema = conf.output_schema().clone();
         let sink = Arc::new(ArrowFileSink::new(conf));
 
-        Ok(Arc::new(FileSinkExec::new(
-            input,
-            sink,
-            sink_schema,
-            order_requirements,
-        )) as _)
+        let report_written_files = state.config_options().execution.report_written_files;
+        Ok(Arc::new(
+            FileSinkExec::new(input, sink, sink_schema, order_requirements)
+                .with_written_files(report_written_files),
+        ) as _)
     }
 
     fn file_type(&self) -> FileType {
@@ -176,41 +176,14 @@ impl ArrowFileSink {
             self.config.output_schema().clone()
         }
     }
-}
-
-impl Debug for ArrowFileSink {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("ArrowFileSink").finish()
-    }
-}
-
-impl DisplayAs for ArrowFileSink {
-    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match t {
-            DisplayFormatType::Default | DisplayFormatType::Verbose => {
-                write!(f, "ArrowFileSink(file_groups=",)?;
-                FileGroupDisplay(&self.config.file_groups).fmt_as(t, f)?;
-                write!(f, ")")
-            }
-        }
-    }
-}
 
-#[async_trait]
-impl DataSink for ArrowFileSink {
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-
-    fn metrics(&self) -> Option<MetricsSet> {
-        None
-    }
-
-    async fn write_all(
+    /// Writes the data to files staged by `staged_write`
+    async fn write_files_staged(
         &self,
         data: SendableRecordBatchStream,
         context: &Arc<TaskContext>,
-    ) -> Result<u64> {
+        staged_write: &StagedWrite,
+    ) -> Result<Vec<WrittenFile>> {
         let object_store = context
             .runtime_env()
             .object_store(&self.config.object_store_url)?;
@@ -225,12 +198,14 @@ impl DataSink for ArrowFileSink {
             data,
             context,
             part_col,
+            self.config.bucket_by.clone(),
             self.config.table_paths[0].clone(),
             "arrow".into(),
         );
 
-        let mut file_write_tasks: JoinSet<std::result::Result<usize, DataFusionError>> =
-            JoinSet::new();
+        let mut file_write_tasks: JoinSet<
+            std::result::Result<WrittenFile, DataFusionError>,
+        > = JoinSet::new();
 
         let ipc_options =
             IpcWriteOptions::try_new(64, false, arrow_ipc::MetadataVersion::V5)?
@@ -244,7 +219,7 @@ impl DataSink for ArrowFileSink {
             )?;
             let mut object_store_writer = create_writer(
                 FileCompressionType::UNCOMPRESSED,
-                &path,
+                &staged_write.stage(&path),
                 object_store.clone(),
             )
             .await?;
@@ -266,15 +241,15 @@ impl DataSink for ArrowFileSink {
 
                 object_store_writer.write_all(final_buff.as_slice()).await?;
                 object_store_writer.shutdown().await?;
-                Ok(row_count)
+                Ok(WrittenFile::new(path.to_string(), row_count as u64))
             });
         }
 
-        let mut row_count = 0;
+        let mut written_files = vec![];
         while let Some(result) = file_write_tasks.join_next().await {
             match result {
                 Ok(r) => {
-                    row_count += r?;
+                    written_files.push(r?);
                 }
                 Err(e) => {
                     if e.is_panic() {
@@ -287,7 +262,59 @@ impl DataSink for ArrowFileSink {
         }
 
         demux_task.join_unwind().await?;
-        Ok(row_count as u64)
+        Ok(written_files)
+    }
+}
+
+impl Debug for ArrowFileSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrowFileSink").finish()
+    }
+}
+
+impl DisplayAs for ArrowFileSink {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "ArrowFileSink(file_groups=",)?;
+                FileGroupDisplay(&self.config.file_groups).fmt_as(t, f)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl DataSink for ArrowFileSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64> {
+        let written_files = self.write_files(data, context).await?;
+        Ok(written_files.iter().map(|f| f.row_count).sum())
+    }
+
+    async fn write_files(
+        &self,
+        data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<Vec<WrittenFile>> {
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.config.object_store_url)?;
+
+        let staged_write = StagedWrite::new(object_store, &self.config.table_paths[0]);
+        let result = self.write_files_staged(data, context, &staged_write).await;
+        staged_write.finish(result).await
     }
 }
 
@@ -31,7 +31,7 @@ use crate::datasource::physical_plan::{
 };
 use crate::error::Result;
 use crate::execution::context::SessionState;
-use crate::physical_plan::insert::{DataSink, FileSinkExec};
+use crate::physical_plan::insert::{DataSink, FileSinkExec, WrittenFile};
 use crate::physical_plan::{DisplayAs, DisplayFormatType, Statistics};
 use crate::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
 
@@ -254,7 +254,7 @@ impl FileFormat for CsvFormat {
     async fn create_writer_physical_plan(
         &self,
         input: Arc<dyn ExecutionPlan>,
-        _state: &SessionState,
+        state: &SessionState,
         conf: FileSinkConfig,
         order_requirements: Option<Vec<PhysicalSortRequirement>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
@@ -267,12 +267,11 @@ impl FileFormat for CsvFormat {
         let sink_schema = conf.output_schema().clone();
         let sink = Arc::new(CsvSink::new(conf, writer_options));
 
-        Ok(Arc::new(FileSinkExec::new(
-            input,
-            sink,
-            sink_schema,
-            order_requirements,
-        )) as _)
+        let report_written_files = state.config_options().execution.report_written_files;
+        Ok(Arc::new(
+            FileSinkExec::new(input, sink, sink_schema, order_requirements)
+                .with_written_files(report_written_files),
+        ) as _)
     }
 
     fn file_type(&self) -> FileType {
@@ -471,7 +470,7 @@ impl CsvSink {
         &self,
         data: SendableRecordBatchStream,
         context: &Arc<TaskContext>,
-    ) -> Result<u64> {
+    ) -> Result<Vec<WrittenFile>> {
         let builder = &self.writer_options.writer_options;
 
         let builder_clone = builder.clone();
@@ -516,8 +515,16 @@ impl DataSink for CsvSink {
         data: SendableRecordBatchStream,
         context: &Arc<TaskContext>,
     ) -> Result<u64> {
-        let total_count = self.multipartput_all(data, context).await?;
-        Ok(total_count)
+        let written_files = self.multipartput_all(data, context).await?;
+        Ok(written_files.iter().map(|f| f.row_count).sum())
+    }
+
+    async fn write_files(
+        &self,
+        data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<Vec<WrittenFile>> {
+        self.multipartput_all(data, context).await
     }
 }
 
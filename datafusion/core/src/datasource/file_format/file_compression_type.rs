@@ -250,8 +250,10 @@ impl FileTypeExt for FileType {
         let ext = self.get_ext();
 
         match self {
-            FileType::JSON | FileType::CSV => Ok(format!("{}{}", ext, c.get_ext())),
-            FileType::AVRO | FileType::ARROW => match c.variant {
+            FileType::JSON | FileType::CSV | FileType::FIXEDWIDTH => {
+                Ok(format!("{}{}", ext, c.get_ext()))
+            }
+            FileType::AVRO | FileType::ARROW | FileType::XLSX => match c.variant {
                 UNCOMPRESSED => Ok(ext),
                 _ => Err(DataFusionError::Internal(
                     "FileCompressionType can be specified for CSV/JSON FileType.".into(),
@@ -0,0 +1,478 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`FixedWidthFormat`]: Fixed-width text [`FileFormat`] abstractions
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+
+use super::{FileFormat, FileScanConfig};
+use crate::datasource::file_format::file_compression_type::FileCompressionType;
+use crate::datasource::physical_plan::FixedWidthExec;
+use crate::error::Result;
+use crate::execution::context::SessionState;
+use crate::physical_plan::{ExecutionPlan, Statistics};
+
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use chrono::NaiveDate;
+use datafusion_common::config::FixedWidthOptions;
+use datafusion_common::{DataFusionError, FileType};
+use datafusion_physical_expr::PhysicalExpr;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use object_store::{GetResultPayload, ObjectMeta, ObjectStore};
+
+/// Fixed-width text `FileFormat` implementation.
+///
+/// Each line of a fixed-width file holds one row, and each column is found
+/// at the same character positions of every line, as described by
+/// [`FixedWidthOptions::columns`]. Column types are inferred from the values
+/// unless the table schema is provided.
+#[derive(Debug, Default)]
+pub struct FixedWidthFormat {
+    options: FixedWidthOptions,
+}
+
+impl FixedWidthFormat {
+    /// Set fixed-width options
+    pub fn with_options(mut self, options: FixedWidthOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Retrieve fixed-width options
+    pub fn options(&self) -> &FixedWidthOptions {
+        &self.options
+    }
+
+    /// Set the columns of each line, see [`FixedWidthOptions::columns`]
+    pub fn with_columns(mut self, columns: impl Into<String>) -> Self {
+        self.options.columns = columns.into();
+        self
+    }
+
+    /// Set true to skip the first line of each file.
+    /// - defaults to false
+    pub fn with_has_header(mut self, has_header: bool) -> Self {
+        self.options.has_header = has_header;
+        self
+    }
+
+    /// Set a limit in terms of records to scan to infer the schema
+    /// - defaults to `DEFAULT_SCHEMA_INFER_MAX_RECORD`
+    pub fn with_schema_infer_max_rec(mut self, max_rec: usize) -> Self {
+        self.options.schema_infer_max_rec = max_rec;
+        self
+    }
+
+    /// Set a `FileCompressionType` of fixed-width files
+    /// - defaults to `FileCompressionType::UNCOMPRESSED`
+    pub fn with_file_compression_type(
+        mut self,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        self.options.compression = file_compression_type.into();
+        self
+    }
+
+    /// Infers the type of the columns from the lines of `reader`, taking
+    /// at most `records_to_read` records
+    fn infer_types_from_reader(
+        &self,
+        reader: impl BufRead,
+        columns: &[FixedWidthColumn],
+        types: &mut [Option<DataType>],
+        records_to_read: &mut usize,
+    ) -> Result<()> {
+        let skip = usize::from(self.options.has_header);
+        for line in reader.lines().skip(skip) {
+            if *records_to_read == 0 {
+                break;
+            }
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            *records_to_read -= 1;
+            for (column, data_type) in columns.iter().zip(types.iter_mut()) {
+                if let Some(value) = column.value(&line, self.options.trim) {
+                    let value_type = infer_value_type(value);
+                    *data_type = Some(match data_type.take() {
+                        Some(data_type) => merge_types(data_type, value_type),
+                        None => value_type,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FileFormat for FixedWidthFormat {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn infer_schema(
+        &self,
+        _state: &SessionState,
+        store: &Arc<dyn ObjectStore>,
+        objects: &[ObjectMeta],
+    ) -> Result<SchemaRef> {
+        let columns = FixedWidthColumn::parse_list(&self.options.columns)?;
+        let mut types = vec![None; columns.len()];
+        let mut records_to_read = self.options.schema_infer_max_rec;
+        let file_compression_type = FileCompressionType::from(self.options.compression);
+        for object in objects {
+            let r = store.as_ref().get(&object.location).await?;
+            match r.payload {
+                GetResultPayload::File(file, _) => {
+                    let decoder = file_compression_type.convert_read(file)?;
+                    self.infer_types_from_reader(
+                        BufReader::new(decoder),
+                        &columns,
+                        &mut types,
+                        &mut records_to_read,
+                    )?;
+                }
+                GetResultPayload::Stream(_) => {
+                    let data = r.bytes().await?;
+                    let decoder = file_compression_type.convert_read(data.reader())?;
+                    self.infer_types_from_reader(
+                        BufReader::new(decoder),
+                        &columns,
+                        &mut types,
+                        &mut records_to_read,
+                    )?;
+                }
+            };
+            if records_to_read == 0 {
+                break;
+            }
+        }
+
+        let fields = columns
+            .into_iter()
+            .zip(types)
+            .map(|(column, data_type)| {
+                Field::new(column.name, data_type.unwrap_or(DataType::Utf8), true)
+            })
+            .collect::<Vec<_>>();
+        Ok(Arc::new(Schema::new(fields)))
+    }
+
+    async fn infer_stats(
+        &self,
+        _state: &SessionState,
+        _store: &Arc<dyn ObjectStore>,
+        table_schema: SchemaRef,
+        _object: &ObjectMeta,
+    ) -> Result<Statistics> {
+        Ok(Statistics::new_unknown(&table_schema))
+    }
+
+    async fn create_physical_plan(
+        &self,
+        _state: &SessionState,
+        conf: FileScanConfig,
+        _filters: Option<&Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let exec = FixedWidthExec::try_new(conf, self.options.clone())?;
+        Ok(Arc::new(exec))
+    }
+
+    fn file_type(&self) -> FileType {
+        FileType::FIXEDWIDTH
+    }
+}
+
+/// The position of a column within the lines of a fixed-width file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedWidthColumn {
+    /// The name of the column
+    pub name: String,
+    /// The 0-based character offset of the first character of the column
+    pub start: usize,
+    /// The 0-based character offset one past the last character of the column
+    pub end: usize,
+}
+
+impl FixedWidthColumn {
+    /// Parses a list of columns, see [`FixedWidthOptions::columns`]
+    pub fn parse_list(spec: &str) -> Result<Vec<Self>> {
+        let invalid = |entry: &str| {
+            DataFusionError::Configuration(format!(
+                "Invalid fixed-width column \"{entry}\", expected name:start-end"
+            ))
+        };
+
+        let mut names = HashSet::new();
+        let mut columns = vec![];
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (name, range) = entry.rsplit_once(':').ok_or_else(|| invalid(entry))?;
+            let (start, end) = range.split_once('-').ok_or_else(|| invalid(entry))?;
+            let start = start.trim().parse::<usize>().map_err(|_| invalid(entry))?;
+            let end = end.trim().parse::<usize>().map_err(|_| invalid(entry))?;
+            let name = name.trim();
+            if name.is_empty() || start == 0 || end < start {
+                return Err(invalid(entry));
+            }
+            if !names.insert(name) {
+                return Err(DataFusionError::Configuration(format!(
+                    "Duplicate fixed-width column {name}"
+                )));
+            }
+            columns.push(Self {
+                name: name.to_string(),
+                start: start - 1,
+                end,
+            });
+        }
+
+        if columns.is_empty() {
+            return Err(DataFusionError::Configuration(
+                "Fixed-width files require the 'format.columns' option".to_string(),
+            ));
+        }
+        Ok(columns)
+    }
+
+    /// Returns the value of this column in `line`, or `None` if it is empty
+    pub fn value<'a>(&self, line: &'a str, trim: bool) -> Option<&'a str> {
+        let value = if line.is_ascii() {
+            line.get(self.start.min(line.len())..self.end.min(line.len()))?
+        } else {
+            let mut offsets = line
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain(std::iter::once(line.len()));
+            let start = offsets.nth(self.start)?;
+            let end = offsets.nth(self.end - self.start - 1).unwrap_or(line.len());
+            &line[start..end]
+        };
+        let value = if trim { value.trim() } else { value };
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+/// Returns the narrowest type able to represent `value`
+fn infer_value_type(value: &str) -> DataType {
+    if value.parse::<i64>().is_ok() {
+        DataType::Int64
+    } else if value.parse::<f64>().is_ok() {
+        DataType::Float64
+    } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+        DataType::Boolean
+    } else if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+        DataType::Date32
+    } else {
+        DataType::Utf8
+    }
+}
+
+/// Returns a type able to represent the values of both `a` and `b`
+fn merge_types(a: DataType, b: DataType) -> DataType {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => {
+            DataType::Float64
+        }
+        _ => DataType::Utf8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::ExecutionPlanProperties;
+    use crate::prelude::SessionContext;
+
+    use arrow::util::pretty::pretty_format_batches;
+    use object_store::local::LocalFileSystem;
+    use object_store::path::Path;
+
+    #[test]
+    fn parse_columns() -> Result<()> {
+        let columns = FixedWidthColumn::parse_list("id:1-5, name : 6-25,")?;
+        assert_eq!(
+            columns,
+            vec![
+                FixedWidthColumn {
+                    name: "id".to_string(),
+                    start: 0,
+                    end: 5
+                },
+                FixedWidthColumn {
+                    name: "name".to_string(),
+                    start: 5,
+                    end: 25
+                },
+            ]
+        );
+
+        for spec in ["", "id", "id:0-5", "id:5-1", "id:1-x", "id:1-2,id:3-4"] {
+            assert!(FixedWidthColumn::parse_list(spec).is_err(), "{spec}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn column_values() {
+        let column = FixedWidthColumn {
+            name: "a".to_string(),
+            start: 2,
+            end: 6,
+        };
+        assert_eq!(column.value("xx 12 yy", true), Some("12"));
+        assert_eq!(column.value("xx 12 yy", false), Some(" 12 "));
+        assert_eq!(column.value("xxé12é yy", true), Some("é12é"));
+        assert_eq!(column.value("xx 1", true), Some("1"));
+        assert_eq!(column.value("xx    yy", true), None);
+        assert_eq!(column.value("x", true), None);
+    }
+
+    #[tokio::test]
+    async fn infer_and_read() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("data.txt");
+        std::fs::write(
+            &path,
+            "ID   NAME      AMOUNT  DAY\n\
+             00001alice     12      2024-01-01\n\
+             00002bob       7.5     2024-01-02\n\
+             00003                  2024-01-03\n",
+        )?;
+
+        let ctx = SessionContext::new();
+        let state = ctx.state();
+        let format = FixedWidthFormat::default()
+            .with_columns("id:1-5,name:6-15,amount:16-23,day:24-33")
+            .with_has_header(true);
+        let store: Arc<dyn ObjectStore> = Arc::new(LocalFileSystem::new());
+        let meta = store
+            .head(&Path::from_filesystem_path(&path).unwrap())
+            .await?;
+        let schema = format.infer_schema(&state, &store, &[meta]).await?;
+        assert_eq!(
+            schema
+                .fields()
+                .iter()
+                .map(|f| (f.name().as_str(), f.data_type().clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("id", DataType::Int64),
+                ("name", DataType::Utf8),
+                ("amount", DataType::Float64),
+                ("day", DataType::Date32),
+            ]
+        );
+
+        ctx.register_listing_table(
+            "t",
+            dir.path().to_str().unwrap(),
+            crate::datasource::listing::ListingOptions::new(Arc::new(format))
+                .with_file_extension(".txt"),
+            None,
+            None,
+        )
+        .await?;
+        let batches = ctx
+            .sql("SELECT day, name, amount FROM t WHERE id > 1 ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+------------+------+--------+",
+            "| day        | name | amount |",
+            "+------------+------+--------+",
+            "| 2024-01-02 | bob  | 7.5    |",
+            "| 2024-01-03 |      |        |",
+            "+------------+------+--------+",
+        ];
+        assert_eq!(
+            pretty_format_batches(&batches)?.to_string(),
+            expected.join("\n")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_in_parallel() -> Result<()> {
+        let mut data = "ID  NAME\n".to_string();
+        for id in 0..100 {
+            data.push_str(&format!("{id:03} name{id:03}\r\n"));
+        }
+
+        // the local store yields files, the in-memory store streams
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("data.txt"), &data)?;
+        let memory = Arc::new(object_store::memory::InMemory::new());
+        memory
+            .put(&Path::from("data/data.txt"), data.into())
+            .await?;
+
+        let config = crate::prelude::SessionConfig::new()
+            .with_target_partitions(4)
+            .with_repartition_file_min_size(0);
+        let ctx = SessionContext::new_with_config(config);
+        let url = url::Url::parse("memory://").unwrap();
+        ctx.runtime_env().register_object_store(&url, memory);
+
+        let local = dir.path().to_str().unwrap().to_string();
+        for location in [local.as_str(), "memory:///data/"] {
+            let format = FixedWidthFormat::default()
+                .with_columns("id:1-3,name:5-11")
+                .with_has_header(true);
+            let options =
+                crate::datasource::listing::ListingOptions::new(Arc::new(format))
+                    .with_file_extension(".txt");
+            ctx.register_listing_table("t", location, options, None, None)
+                .await?;
+
+            let plan = ctx
+                .sql("SELECT * FROM t")
+                .await?
+                .create_physical_plan()
+                .await?;
+            assert_eq!(plan.output_partitioning().partition_count(), 4);
+
+            let batches = ctx
+                .sql("SELECT count(*), count(DISTINCT id), min(id), max(id), max(name) FROM t")
+                .await?
+                .collect()
+                .await?;
+            let expected = [
+                "+----------+----------------------+-----------+-----------+-------------+",
+                "| COUNT(*) | COUNT(DISTINCT t.id) | MIN(t.id) | MAX(t.id) | MAX(t.name) |",
+                "+----------+----------------------+-----------+-----------+-------------+",
+                "| 100      | 100                  | 0         | 99        | name099     |",
+                "+----------+----------------------+-----------+-----------+-------------+",
+            ];
+            assert_eq!(
+                pretty_format_batches(&batches)?.to_string(),
+                expected.join("\n"),
+                "{location}"
+            );
+            ctx.deregister_table("t")?;
+        }
+        Ok(())
+    }
+}
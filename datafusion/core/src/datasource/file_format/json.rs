@@ -31,7 +31,7 @@ use crate::datasource::physical_plan::FileGroupDisplay;
 use crate::datasource::physical_plan::{FileSinkConfig, NdJsonExec};
 use crate::error::Result;
 use crate::execution::context::SessionState;
-use crate::physical_plan::insert::{DataSink, FileSinkExec};
+use crate::physical_plan::insert::{DataSink, FileSinkExec, WrittenFile};
 use crate::physical_plan::{
     DisplayAs, DisplayFormatType, SendableRecordBatchStream, Statistics,
 };
@@ -164,7 +164,7 @@ impl FileFormat for JsonFormat {
     async fn create_writer_physical_plan(
         &self,
         input: Arc<dyn ExecutionPlan>,
-        _state: &SessionState,
+        state: &SessionState,
         conf: FileSinkConfig,
         order_requirements: Option<Vec<PhysicalSortRequirement>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
@@ -177,12 +177,11 @@ impl FileFormat for JsonFormat {
         let sink_schema = conf.output_schema().clone();
         let sink = Arc::new(JsonSink::new(conf, writer_options));
 
-        Ok(Arc::new(FileSinkExec::new(
-            input,
-            sink,
-            sink_schema,
-            order_requirements,
-        )) as _)
+        let report_written_files = state.config_options().execution.report_written_files;
+        Ok(Arc::new(
+            FileSinkExec::new(input, sink, sink_schema, order_requirements)
+                .with_written_files(report_written_files),
+        ) as _)
     }
 
     fn file_type(&self) -> FileType {
@@ -259,7 +258,7 @@ impl JsonSink {
         &self,
         data: SendableRecordBatchStream,
         context: &Arc<TaskContext>,
-    ) -> Result<u64> {
+    ) -> Result<Vec<WrittenFile>> {
         let get_serializer = move || Arc::new(JsonSerializer::new()) as _;
 
         stateless_multipart_put(
@@ -293,8 +292,16 @@ impl DataSink for JsonSink {
         data: SendableRecordBatchStream,
         context: &Arc<TaskContext>,
     ) -> Result<u64> {
-        let total_count = self.multipartput_all(data, context).await?;
-        Ok(total_count)
+        let written_files = self.multipartput_all(data, context).await?;
+        Ok(written_files.iter().map(|f| f.row_count).sum())
+    }
+
+    async fn write_files(
+        &self,
+        data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<Vec<WrittenFile>> {
+        self.multipartput_all(data, context).await
     }
 }
 
@@ -25,11 +25,13 @@ pub mod arrow;
 pub mod avro;
 pub mod csv;
 pub mod file_compression_type;
+pub mod fixed_width;
 pub mod json;
 pub mod options;
 #[cfg(feature = "parquet")]
 pub mod parquet;
 pub mod write;
+pub mod xlsx;
 
 use std::any::Any;
 use std::fmt;
@@ -165,6 +167,7 @@ pub(crate) mod test_util {
                     limit,
                     table_partition_cols: vec![],
                     output_ordering: vec![],
+                    bucket_by: None,
                 },
                 None,
             )
@@ -22,6 +22,7 @@ use std::fmt;
 use std::fmt::Debug;
 use std::sync::Arc;
 
+use super::write::commit::StagedWrite;
 use super::write::demux::start_demuxer_task;
 use super::write::{create_writer, SharedBuffer};
 use super::{FileFormat, FileScanConfig};
@@ -30,14 +31,15 @@ use crate::arrow::array::{
 };
 use crate::arrow::datatypes::{DataType, Fields, Schema, SchemaRef};
 use crate::datasource::file_format::file_compression_type::FileCompressionType;
-use crate::datasource::physical_plan::{
-    FileGroupDisplay, FileSinkConfig, ParquetExec, SchemaAdapter,
+use crate::datasource::physical_plan::{FileGroupDisplay, FileSinkConfig, ParquetExec};
+use crate::datasource::schema_adapter::{
+    DefaultSchemaAdapterFactory, SchemaAdapterFactory,
 };
 use crate::datasource::statistics::{create_max_min_accs, get_col_stats};
 use crate::error::Result;
 use crate::execution::context::SessionState;
 use crate::physical_plan::expressions::{MaxAccumulator, MinAccumulator};
-use crate::physical_plan::insert::{DataSink, FileSinkExec};
+use crate::physical_plan::insert::{DataSink, FileSinkExec, WrittenFile};
 use crate::physical_plan::{
     Accumulator, DisplayAs, DisplayFormatType, ExecutionPlan, SendableRecordBatchStream,
     Statistics,
@@ -268,7 +270,7 @@ impl FileFormat for ParquetFormat {
     async fn create_writer_physical_plan(
         &self,
         input: Arc<dyn ExecutionPlan>,
-        _state: &SessionState,
+        state: &SessionState,
         conf: FileSinkConfig,
         order_requirements: Option<Vec<PhysicalSortRequirement>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
@@ -279,12 +281,11 @@ impl FileFormat for ParquetFormat {
         let sink_schema = conf.output_schema().clone();
         let sink = Arc::new(ParquetSink::new(conf, self.options.clone()));
 
-        Ok(Arc::new(FileSinkExec::new(
-            input,
-            sink,
-            sink_schema,
-            order_requirements,
-        )) as _)
+        let report_written_files = state.config_options().execution.report_written_files;
+        Ok(Arc::new(
+            FileSinkExec::new(input, sink, sink_schema, order_requirements)
+                .with_written_files(report_written_files),
+        ) as _)
     }
 
     fn file_type(&self) -> FileType {
@@ -474,7 +475,8 @@ async fn fetch_statistics(
     let mut null_counts = vec![Precision::Exact(0); num_fields];
     let mut has_statistics = false;
 
-    let schema_adapter = SchemaAdapter::new(table_schema.clone());
+    let schema_adapter = DefaultSchemaAdapterFactory::default()
+        .create(table_schema.clone(), table_schema.clone());
 
     let (mut max_values, mut min_values) = create_max_min_accs(&table_schema);
 
@@ -628,23 +630,14 @@ impl ParquetSink {
     pub fn parquet_options(&self) -> &TableParquetOptions {
         &self.parquet_options
     }
-}
 
-#[async_trait]
-impl DataSink for ParquetSink {
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-
-    fn metrics(&self) -> Option<MetricsSet> {
-        None
-    }
-
-    async fn write_all(
+    /// Writes the data to files staged by `staged_write`
+    async fn write_files_staged(
         &self,
         data: SendableRecordBatchStream,
         context: &Arc<TaskContext>,
-    ) -> Result<u64> {
+        staged_write: &StagedWrite,
+    ) -> Result<Vec<WrittenFile>> {
         let parquet_props = ParquetWriterOptions::try_from(&self.parquet_options)?;
 
         let object_store = context
@@ -674,6 +667,7 @@ impl DataSink for ParquetSink {
             data,
             context,
             part_col,
+            self.config.bucket_by.clone(),
             self.config.table_paths[0].clone(),
             "parquet".into(),
         );
@@ -686,7 +680,7 @@ impl DataSink for ParquetSink {
             if !allow_single_file_parallelism {
                 let mut writer = self
                     .create_async_arrow_writer(
-                        &path,
+                        &staged_write.stage(&path),
                         object_store.clone(),
                         parquet_props.writer_options().clone(),
                     )
@@ -706,7 +700,7 @@ impl DataSink for ParquetSink {
                     // Parquet files as a whole are never compressed, since they
                     // manage compressed blocks themselves.
                     FileCompressionType::UNCOMPRESSED,
-                    &path,
+                    &staged_write.stage(&path),
                     object_store.clone(),
                 )
                 .await?;
@@ -727,12 +721,15 @@ impl DataSink for ParquetSink {
             }
         }
 
-        let mut row_count = 0;
+        let mut written = vec![];
         while let Some(result) = file_write_tasks.join_next().await {
             match result {
                 Ok(r) => {
                     let (path, file_metadata) = r?;
-                    row_count += file_metadata.num_rows;
+                    written.push(WrittenFile::new(
+                        path.to_string(),
+                        file_metadata.num_rows as u64,
+                    ));
                     let mut written_files = self.written.lock();
                     written_files
                         .try_insert(path.clone(), file_metadata)
@@ -751,7 +748,41 @@ impl DataSink for ParquetSink {
 
         demux_task.join_unwind().await?;
 
-        Ok(row_count as u64)
+        Ok(written)
+    }
+}
+
+#[async_trait]
+impl DataSink for ParquetSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64> {
+        let written_files = self.write_files(data, context).await?;
+        Ok(written_files.iter().map(|f| f.row_count).sum())
+    }
+
+    async fn write_files(
+        &self,
+        data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<Vec<WrittenFile>> {
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.config.object_store_url)?;
+
+        let staged_write = StagedWrite::new(object_store, &self.config.table_paths[0]);
+        let result = self.write_files_staged(data, context, &staged_write).await;
+        staged_write.finish(result).await
     }
 }
 
@@ -1857,6 +1888,7 @@ mod tests {
             output_schema: schema.clone(),
             table_partition_cols: vec![],
             overwrite: true,
+            bucket_by: None,
         };
         let parquet_sink = Arc::new(ParquetSink::new(
             file_sink_config,
@@ -1928,6 +1960,7 @@ mod tests {
             output_schema: schema.clone(),
             table_partition_cols: vec![("a".to_string(), DataType::Utf8)], // add partitioning
             overwrite: true,
+            bucket_by: None,
         };
         let parquet_sink = Arc::new(ParquetSink::new(
             file_sink_config,
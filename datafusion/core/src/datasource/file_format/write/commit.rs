@@ -0,0 +1,342 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Module containing helper methods/traits related to staging output
+//! files under a temporary name and committing them once every file
+//! of a write has been written successfully
+
+use std::sync::Arc;
+
+use crate::datasource::listing::ListingTableUrl;
+use crate::error::Result;
+
+use datafusion_common::DataFusionError;
+use datafusion_physical_plan::insert::WrittenFile;
+
+use object_store::path::Path;
+use object_store::ObjectStore;
+use parking_lot::Mutex;
+use rand::distributions::DistString;
+
+/// Tracks the files of a single write.
+///
+/// Each file is written under a temporary prefix of the output directory
+/// (`_temporary/<id>/`), with a `.tmp` suffix so that listing tables do not
+/// read it. The files are only moved to their final location by
+/// [`Self::commit`] once all of them have been written. If the write fails,
+/// or is dropped before being committed (e.g. because the query was
+/// cancelled), the staged files are deleted instead.
+///
+/// ```text
+/// out/_temporary/Xa3c/a=1/Ub9f.parquet.tmp  ──commit──▶  out/a=1/Ub9f.parquet
+/// out/_temporary/Xa3c/a=2/Ub9f.parquet.tmp  ──commit──▶  out/a=2/Ub9f.parquet
+/// ```
+///
+/// As the local file system keeps empty directories, the directories of the
+/// prefix are removed once a write to a local table has finished.
+pub(crate) struct StagedWrite {
+    object_store: Arc<dyn ObjectStore>,
+    /// The prefix the files of this write are staged under
+    staging_prefix: Path,
+    /// The directory the files of the write are written to
+    output_dir: Path,
+    /// The local directory of `staging_prefix`, if the output is local
+    local_staging_dir: Option<std::path::PathBuf>,
+    /// `(staged, final)` locations of every file of the write
+    files: Mutex<Vec<(Path, Path)>>,
+    /// Set once the write has been committed or aborted
+    finished: bool,
+}
+
+impl StagedWrite {
+    /// Create a new [`StagedWrite`] for files written to `object_store`
+    /// at `table_path`
+    pub(crate) fn new(
+        object_store: Arc<dyn ObjectStore>,
+        table_path: &ListingTableUrl,
+    ) -> Self {
+        let staging_id =
+            rand::distributions::Alphanumeric.sample_string(&mut rand::thread_rng(), 16);
+        // a single file is staged in the directory it is written to
+        let output_dir = match table_path.is_collection() {
+            true => table_path.prefix().clone(),
+            false => {
+                let parts: Vec<_> = table_path.prefix().parts().collect();
+                Path::from_iter(parts[..parts.len().saturating_sub(1)].iter().cloned())
+            }
+        };
+        let staging_prefix = output_dir.child("_temporary").child(staging_id);
+        let local_staging_dir = (table_path.scheme() == "file")
+            .then(|| std::path::PathBuf::from(format!("/{staging_prefix}")));
+
+        Self {
+            object_store,
+            staging_prefix,
+            output_dir,
+            local_staging_dir,
+            files: Mutex::new(vec![]),
+            finished: false,
+        }
+    }
+
+    /// Register a file which should eventually be written to `final_path`,
+    /// returning the location it should be written to in the meantime
+    pub(crate) fn stage(&self, final_path: &Path) -> Path {
+        let parts: Vec<_> = match final_path.prefix_match(&self.output_dir) {
+            Some(parts) => parts.collect(),
+            None => final_path.parts().last().into_iter().collect(),
+        };
+        let staged_path = match parts.split_last() {
+            Some((file_name, directory)) => {
+                let directory = self.staging_prefix.parts().chain(directory.to_vec());
+                Path::from_iter(directory).child(format!("{}.tmp", file_name.as_ref()))
+            }
+            None => self.staging_prefix.child("_.tmp"),
+        };
+
+        self.files
+            .lock()
+            .push((staged_path.clone(), final_path.clone()));
+        staged_path
+    }
+
+    /// Commits the write if `result` is successful and aborts it otherwise,
+    /// returning `result`
+    pub(crate) async fn finish(
+        self,
+        result: Result<Vec<WrittenFile>>,
+    ) -> Result<Vec<WrittenFile>> {
+        match result {
+            Ok(files) => {
+                self.commit().await?;
+                Ok(files)
+            }
+            Err(e) => {
+                // Report the original error rather than any cleanup failure
+                let _ = self.abort().await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Moves every staged file to its final location. If a file cannot
+    /// be moved, the files committed so far are removed again and the
+    /// remaining staged files are deleted.
+    pub(crate) async fn commit(mut self) -> Result<()> {
+        self.finished = true;
+        let files = std::mem::take(self.files.get_mut());
+
+        for (idx, (staged_path, final_path)) in files.iter().enumerate() {
+            if let Err(e) = self.object_store.rename(staged_path, final_path).await {
+                for (_, committed) in &files[..idx] {
+                    let _ = self.object_store.delete(committed).await;
+                }
+                let _ = cleanup(self.object_store.clone(), files[idx..].to_vec()).await;
+                remove_empty_dirs(self.local_staging_dir.take());
+                return Err(DataFusionError::Execution(format!(
+                    "Error committing {staged_path} to {final_path}, no files were written: {e}"
+                )));
+            }
+        }
+
+        remove_empty_dirs(self.local_staging_dir.take());
+        Ok(())
+    }
+
+    /// Deletes every staged file
+    pub(crate) async fn abort(mut self) -> Result<()> {
+        self.finished = true;
+        let files = std::mem::take(self.files.get_mut());
+        let result = cleanup(self.object_store.clone(), files).await;
+        remove_empty_dirs(self.local_staging_dir.take());
+        result
+    }
+}
+
+impl Drop for StagedWrite {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // The write was neither committed nor aborted, e.g. because the
+        // query was cancelled, so remove the staged files in the background
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let object_store = self.object_store.clone();
+            let files = std::mem::take(self.files.get_mut());
+            let local_staging_dir = self.local_staging_dir.take();
+            handle.spawn(async move {
+                let _ = cleanup(object_store, files).await;
+                remove_empty_dirs(local_staging_dir);
+            });
+        }
+    }
+}
+
+/// Deletes the staged file of every `(staged, final)` pair, ignoring
+/// files that were never created
+async fn cleanup(
+    object_store: Arc<dyn ObjectStore>,
+    files: Vec<(Path, Path)>,
+) -> Result<()> {
+    for (staged_path, _) in files {
+        match object_store.delete(&staged_path).await {
+            Ok(_) | Err(object_store::Error::NotFound { .. }) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Removes the local staging directory `dir` of a write, and the
+/// `_temporary` directory containing it, as long as they are empty
+fn remove_empty_dirs(dir: Option<std::path::PathBuf>) {
+    fn remove(dir: &std::path::Path) {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                    remove(&entry.path());
+                }
+            }
+        }
+        let _ = std::fs::remove_dir(dir);
+    }
+    if let Some(dir) = dir {
+        remove(&dir);
+        if let Some(parent) = dir.parent() {
+            let _ = std::fs::remove_dir(parent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::TryStreamExt;
+    use object_store::memory::InMemory;
+
+    fn table_path() -> ListingTableUrl {
+        ListingTableUrl::parse("memory:///out/").unwrap()
+    }
+
+    async fn list(object_store: &Arc<dyn ObjectStore>) -> Vec<String> {
+        let mut paths: Vec<_> = object_store
+            .list(None)
+            .map_ok(|meta| meta.location.to_string())
+            .try_collect()
+            .await
+            .unwrap();
+        paths.sort();
+        paths
+    }
+
+    #[tokio::test]
+    async fn commit_moves_staged_files() -> Result<()> {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+
+        let write = StagedWrite::new(object_store.clone(), &table_path());
+        let staged = write.stage(&Path::from("out/a=1/f.csv"));
+        assert!(staged.as_ref().starts_with("out/_temporary/"));
+        assert!(staged.as_ref().ends_with("/a=1/f.csv.tmp"));
+        object_store.put(&staged, "1".into()).await?;
+
+        let staged = write.stage(&Path::from("out/a=2/f.csv"));
+        object_store.put(&staged, "2".into()).await?;
+        assert!(!list(&object_store)
+            .await
+            .contains(&"out/a=1/f.csv".to_string()));
+
+        write.commit().await?;
+        assert_eq!(
+            list(&object_store).await,
+            ["out/a=1/f.csv", "out/a=2/f.csv"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failed_commit_is_rolled_back() -> Result<()> {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+
+        let write = StagedWrite::new(object_store.clone(), &table_path());
+        let staged = write.stage(&Path::from("out/f1.csv"));
+        object_store.put(&staged, "1".into()).await?;
+        // the second file was never written, so it cannot be committed
+        write.stage(&Path::from("out/f2.csv"));
+
+        assert!(write.commit().await.is_err());
+        assert!(list(&object_store).await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failed_write_is_aborted() -> Result<()> {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+
+        let write = StagedWrite::new(object_store.clone(), &table_path());
+        let staged = write.stage(&Path::from("out/f.csv"));
+        object_store.put(&staged, "1".into()).await?;
+
+        let result = write
+            .finish(Err(DataFusionError::Execution("failed".into())))
+            .await;
+        assert!(result.is_err());
+        assert!(list(&object_store).await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dropped_write_is_cleaned_up() -> Result<()> {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+
+        let write = StagedWrite::new(object_store.clone(), &table_path());
+        let staged = write.stage(&Path::from("out/f.csv"));
+        object_store.put(&staged, "1".into()).await?;
+        drop(write);
+
+        // Cleanup happens in the background
+        for _ in 0..100 {
+            if list(&object_store).await.is_empty() {
+                return Ok(());
+            }
+            tokio::task::yield_now().await;
+        }
+        panic!("staged files were not cleaned up");
+    }
+
+    #[tokio::test]
+    async fn local_staging_dirs_are_removed() -> Result<()> {
+        let object_store: Arc<dyn ObjectStore> =
+            Arc::new(object_store::local::LocalFileSystem::new());
+        let dir = tempfile::tempdir()?;
+        let out = dir.path().join("out");
+        let table_path = ListingTableUrl::parse(format!("{}/", out.display()))?;
+
+        let write = StagedWrite::new(object_store.clone(), &table_path);
+        let final_path = table_path.prefix().child("a=1").child("f.csv");
+        let staged = write.stage(&final_path);
+        object_store.put(&staged, "1".into()).await?;
+        write.commit().await?;
+
+        let mut entries: Vec<_> = std::fs::read_dir(&out)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<std::io::Result<_>>()?;
+        entries.sort();
+        assert_eq!(entries, ["a=1"]);
+        Ok(())
+    }
+}
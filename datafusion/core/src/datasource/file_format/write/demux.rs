@@ -25,16 +25,20 @@ use std::sync::Arc;
 use crate::datasource::listing::ListingTableUrl;
 
 use crate::error::Result;
-use crate::physical_plan::SendableRecordBatchStream;
+use crate::physical_plan::metrics::Time;
+use crate::physical_plan::repartition::BatchPartitioner;
+use crate::physical_plan::{Partitioning, SendableRecordBatchStream};
 
 use arrow_array::builder::UInt64Builder;
 use arrow_array::cast::AsArray;
 use arrow_array::{downcast_dictionary_array, RecordBatch, StringArray, StructArray};
 use arrow_schema::{DataType, Schema};
 use datafusion_common::cast::as_string_array;
-use datafusion_common::{exec_datafusion_err, DataFusionError};
+use datafusion_common::{exec_datafusion_err, BucketSpec, DataFusionError};
 use datafusion_common_runtime::SpawnedTask;
 use datafusion_execution::TaskContext;
+use datafusion_physical_expr::expressions::Column;
+use datafusion_physical_expr::PhysicalExpr;
 
 use futures::StreamExt;
 use object_store::path::Path;
@@ -58,6 +62,8 @@ type DemuxedStreamReceiver = UnboundedReceiver<(Path, RecordBatchReceiver)>;
 /// overrides all other settings to force only a single file to be written.
 /// partition_by parameter will additionally split the input based on the unique
 /// values of a specific column `<https://github.com/apache/arrow-datafusion/issues/7744>``
+/// bucket_by parameter will additionally split the input (of each partition) into
+/// one file per bucket, see [`BucketSpec`]
 ///                                                                              ┌───────────┐               ┌────────────┐    ┌─────────────┐
 ///                                                                     ┌──────▶ │  batch 1  ├────▶...──────▶│   Batch a  │    │ Output File1│
 ///                                                                     │        └───────────┘               └────────────┘    └─────────────┘
@@ -73,14 +79,15 @@ pub(crate) fn start_demuxer_task(
     input: SendableRecordBatchStream,
     context: &Arc<TaskContext>,
     partition_by: Option<Vec<(String, DataType)>>,
+    bucket_by: Option<BucketSpec>,
     base_output_path: ListingTableUrl,
     file_extension: String,
 ) -> (SpawnedTask<Result<()>>, DemuxedStreamReceiver) {
     let (tx, rx) = mpsc::unbounded_channel();
     let context = context.clone();
     let single_file_output = !base_output_path.is_collection();
-    let task = match partition_by {
-        Some(parts) => {
+    let task = match (partition_by, bucket_by) {
+        (parts, bucket_by) if parts.is_some() || bucket_by.is_some() => {
             // There could be an arbitrarily large number of parallel hive style partitions being written to, so we cannot
             // bound this channel without risking a deadlock.
             SpawnedTask::spawn(async move {
@@ -88,14 +95,15 @@ pub(crate) fn start_demuxer_task(
                     tx,
                     input,
                     context,
-                    parts,
+                    parts.unwrap_or_default(),
+                    bucket_by,
                     base_output_path,
                     file_extension,
                 )
                 .await
             })
         }
-        None => SpawnedTask::spawn(async move {
+        _ => SpawnedTask::spawn(async move {
             row_count_demuxer(
                 tx,
                 input,
@@ -231,6 +239,7 @@ fn create_new_file_stream(
 }
 
 /// Splits an input stream based on the distinct values of a set of columns
+/// and, if `bucket_by` is set, the bucket of each row.
 /// Assumes standard hive style partition paths such as
 /// /col1=val1/col2=val2/outputfile.parquet
 async fn hive_style_partitions_demuxer(
@@ -238,6 +247,7 @@ async fn hive_style_partitions_demuxer(
     mut input: SendableRecordBatchStream,
     context: Arc<TaskContext>,
     partition_by: Vec<(String, DataType)>,
+    bucket_by: Option<BucketSpec>,
     base_output_path: ListingTableUrl,
     file_extension: String,
 ) -> Result<()> {
@@ -247,71 +257,125 @@ async fn hive_style_partitions_demuxer(
     let exec_options = &context.session_config().options().execution;
     let max_buffered_recordbatches = exec_options.max_buffered_batches_per_output_file;
 
-    // To support non string partition col types, cast the type to &str first
-    let mut value_map: HashMap<Vec<String>, Sender<RecordBatch>> = HashMap::new();
-
-    while let Some(rb) = input.next().await.transpose()? {
-        // First compute partition key for each row of batch, e.g. (col1=val1, col2=val2, ...)
-        let all_partition_values = compute_partition_keys_by_row(&rb, &partition_by)?;
-
-        // Next compute how the batch should be split up to take each distinct key to its own batch
-        let take_map = compute_take_arrays(&rb, all_partition_values);
-
-        // Divide up the batch into distinct partition key batches and send each batch
-        for (part_key, mut builder) in take_map.into_iter() {
-            // Take method adapted from https://github.com/lancedb/lance/pull/1337/files
-            // TODO: upstream RecordBatch::take to arrow-rs
-            let take_indices = builder.finish();
-            let struct_array: StructArray = rb.clone().into();
-            let parted_batch = RecordBatch::from(
-                arrow::compute::take(&struct_array, &take_indices, None)?.as_struct(),
-            );
-
-            // Get or create channel for this batch
-            let part_tx = match value_map.get_mut(&part_key) {
-                Some(part_tx) => part_tx,
-                None => {
-                    // Create channel for previously unseen distinct partition key and notify consumer of new file
-                    let (part_tx, part_rx) = tokio::sync::mpsc::channel::<RecordBatch>(
-                        max_buffered_recordbatches,
-                    );
-                    let file_path = compute_hive_style_file_path(
-                        &part_key,
-                        &partition_by,
-                        &write_id,
-                        &file_extension,
-                        &base_output_path,
-                    );
-
-                    tx.send((file_path, part_rx)).map_err(|_| {
-                        DataFusionError::Execution(
-                            "Error sending new file stream!".into(),
-                        )
-                    })?;
-
-                    value_map.insert(part_key.clone(), part_tx);
-                    value_map
-                        .get_mut(&part_key)
-                        .ok_or(DataFusionError::Internal(
-                            "Key must exist since it was just inserted!".into(),
-                        ))?
-                }
-            };
-
-            // remove partitions columns
-            let final_batch_to_send =
-                remove_partition_by_columns(&parted_batch, &partition_by)?;
+    let mut bucket_partitioner = bucket_by
+        .map(|bucket_by| create_bucket_partitioner(&bucket_by, &input.schema()))
+        .transpose()?;
 
-            // Finally send the partial batch partitioned by distinct value!
-            part_tx.send(final_batch_to_send).await.map_err(|_| {
-                DataFusionError::Internal("Unexpected error sending parted batch!".into())
-            })?;
+    // To support non string partition col types, cast the type to &str first
+    let mut value_map: HashMap<(Vec<String>, Option<usize>), Sender<RecordBatch>> =
+        HashMap::new();
+
+    while let Some(input_rb) = input.next().await.transpose()? {
+        for (bucket, rb) in split_into_buckets(bucket_partitioner.as_mut(), input_rb)? {
+            // First compute partition key for each row of batch, e.g. (col1=val1, col2=val2, ...)
+            let all_partition_values = compute_partition_keys_by_row(&rb, &partition_by)?;
+
+            // Next compute how the batch should be split up to take each distinct key to its own batch
+            let take_map = compute_take_arrays(&rb, all_partition_values);
+
+            // Divide up the batch into distinct partition key batches and send each batch
+            for (part_key, mut builder) in take_map.into_iter() {
+                // Take method adapted from https://github.com/lancedb/lance/pull/1337/files
+                // TODO: upstream RecordBatch::take to arrow-rs
+                let take_indices = builder.finish();
+                let struct_array: StructArray = rb.clone().into();
+                let parted_batch = RecordBatch::from(
+                    arrow::compute::take(&struct_array, &take_indices, None)?.as_struct(),
+                );
+
+                // Get or create channel for this batch
+                let part_key = (part_key, bucket);
+                let part_tx = match value_map.get_mut(&part_key) {
+                    Some(part_tx) => part_tx,
+                    None => {
+                        // Create channel for previously unseen distinct partition key and notify consumer of new file
+                        let (part_tx, part_rx) = tokio::sync::mpsc::channel::<RecordBatch>(
+                            max_buffered_recordbatches,
+                        );
+                        let file_path = compute_hive_style_file_path(
+                            &part_key.0,
+                            bucket,
+                            &partition_by,
+                            &write_id,
+                            &file_extension,
+                            &base_output_path,
+                        );
+
+                        tx.send((file_path, part_rx)).map_err(|_| {
+                            DataFusionError::Execution(
+                                "Error sending new file stream!".into(),
+                            )
+                        })?;
+
+                        value_map.insert(part_key.clone(), part_tx);
+                        value_map
+                            .get_mut(&part_key)
+                            .ok_or(DataFusionError::Internal(
+                                "Key must exist since it was just inserted!".into(),
+                            ))?
+                    }
+                };
+
+                // remove partitions columns
+                let final_batch_to_send =
+                    remove_partition_by_columns(&parted_batch, &partition_by)?;
+
+                // Finally send the partial batch partitioned by distinct value!
+                part_tx.send(final_batch_to_send).await.map_err(|_| {
+                    DataFusionError::Internal(
+                        "Unexpected error sending parted batch!".into(),
+                    )
+                })?;
+            }
         }
     }
 
     Ok(())
 }
 
+/// Creates a [`BatchPartitioner`] computing the buckets of rows the same
+/// way hash repartitioning on the bucket columns does
+fn create_bucket_partitioner(
+    bucket_by: &BucketSpec,
+    schema: &Schema,
+) -> Result<BatchPartitioner> {
+    let hash_exprs = bucket_by
+        .columns
+        .iter()
+        .map(|name| {
+            let column = Column::new_with_schema(name, schema).map_err(|_| {
+                exec_datafusion_err!(
+                    "Bucket column {name} does not exist in source data! Got schema {schema}."
+                )
+            })?;
+            Ok(Arc::new(column) as Arc<dyn PhysicalExpr>)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    BatchPartitioner::try_new(
+        Partitioning::Hash(hash_exprs, bucket_by.num_buckets),
+        Time::new(),
+    )
+}
+
+/// Splits a batch into one batch per bucket, or returns it unchanged (without
+/// a bucket) if the output is not bucketed
+fn split_into_buckets(
+    bucket_partitioner: Option<&mut BatchPartitioner>,
+    rb: RecordBatch,
+) -> Result<Vec<(Option<usize>, RecordBatch)>> {
+    let Some(bucket_partitioner) = bucket_partitioner else {
+        return Ok(vec![(None, rb)]);
+    };
+
+    let mut buckets = vec![];
+    bucket_partitioner.partition(rb, |bucket, batch| {
+        buckets.push((Some(bucket), batch));
+        Ok(())
+    })?;
+    Ok(buckets)
+}
+
 fn compute_partition_keys_by_row<'a>(
     rb: &'a RecordBatch,
     partition_by: &'a [(String, DataType)],
@@ -413,6 +477,7 @@ fn remove_partition_by_columns(
 
 fn compute_hive_style_file_path(
     part_key: &[String],
+    bucket: Option<usize>,
     partition_by: &[(String, DataType)],
     write_id: &str,
     file_extension: &str,
@@ -423,5 +488,10 @@ fn compute_hive_style_file_path(
         file_path = file_path.child(format!("{}={}", partition_by[j].0, part_key[j]));
     }
 
-    file_path.child(format!("{}.{}", write_id, file_extension))
+    match bucket {
+        Some(bucket) => {
+            file_path.child(BucketSpec::file_name(bucket, write_id, file_extension))
+        }
+        None => file_path.child(format!("{}.{}", write_id, file_extension)),
+    }
 }
@@ -32,6 +32,7 @@ use object_store::path::Path;
 use object_store::ObjectStore;
 use tokio::io::AsyncWrite;
 
+pub(crate) mod commit;
 pub(crate) mod demux;
 pub(crate) mod orchestration;
 
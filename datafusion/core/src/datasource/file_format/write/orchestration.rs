@@ -21,6 +21,7 @@
 
 use std::sync::Arc;
 
+use super::commit::StagedWrite;
 use super::demux::start_demuxer_task;
 use super::{create_writer, BatchSerializer};
 use crate::datasource::file_format::file_compression_type::FileCompressionType;
@@ -32,9 +33,11 @@ use arrow_array::RecordBatch;
 use datafusion_common::{internal_datafusion_err, internal_err, DataFusionError};
 use datafusion_common_runtime::SpawnedTask;
 use datafusion_execution::TaskContext;
+use datafusion_physical_plan::insert::WrittenFile;
 
 use bytes::Bytes;
 use futures::join;
+use object_store::path::Path;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tokio::sync::mpsc::{self, Receiver};
 use tokio::task::JoinSet;
@@ -120,7 +123,7 @@ pub(crate) async fn serialize_rb_stream_to_object_store(
     Ok((writer, row_count as u64))
 }
 
-type FileWriteBundle = (Receiver<RecordBatch>, SerializerType, WriterType);
+type FileWriteBundle = (Path, Receiver<RecordBatch>, SerializerType, WriterType);
 /// Contains the common logic for serializing RecordBatches and
 /// writing the resulting bytes to an ObjectStore.
 /// Serialization is assumed to be stateless, i.e.
@@ -128,9 +131,9 @@ type FileWriteBundle = (Receiver<RecordBatch>, SerializerType, WriterType);
 /// dependency on the RecordBatches before or after.
 pub(crate) async fn stateless_serialize_and_write_files(
     mut rx: Receiver<FileWriteBundle>,
-    tx: tokio::sync::oneshot::Sender<u64>,
+    tx: tokio::sync::oneshot::Sender<Vec<WrittenFile>>,
 ) -> Result<()> {
-    let mut row_count = 0;
+    let mut written_files = vec![];
     // tracks if any writers encountered an error triggering the need to abort
     let mut any_errors = false;
     // tracks the specific error triggering abort
@@ -139,18 +142,20 @@ pub(crate) async fn stateless_serialize_and_write_files(
     // if true, we may not have a guarentee that all written data was cleaned up.
     let mut any_abort_errors = false;
     let mut join_set = JoinSet::new();
-    while let Some((data_rx, serializer, writer)) = rx.recv().await {
+    while let Some((path, data_rx, serializer, writer)) = rx.recv().await {
         join_set.spawn(async move {
-            serialize_rb_stream_to_object_store(data_rx, serializer, writer).await
+            serialize_rb_stream_to_object_store(data_rx, serializer, writer)
+                .await
+                .map(|(writer, cnt)| (writer, WrittenFile::new(path.to_string(), cnt)))
         });
     }
     let mut finished_writers = Vec::new();
     while let Some(result) = join_set.join_next().await {
         match result {
             Ok(res) => match res {
-                Ok((writer, cnt)) => {
+                Ok((writer, written_file)) => {
                     finished_writers.push(writer);
-                    row_count += cnt;
+                    written_files.push(written_file);
                 }
                 Err((writer, e)) => {
                     finished_writers.push(writer);
@@ -188,9 +193,9 @@ pub(crate) async fn stateless_serialize_and_write_files(
         }
     }
 
-    tx.send(row_count).map_err(|_| {
+    tx.send(written_files).map_err(|_| {
         internal_datafusion_err!(
-            "Error encountered while sending row count back to file sink!"
+            "Error encountered while sending written files back to file sink!"
         )
     })?;
     Ok(())
@@ -199,6 +204,9 @@ pub(crate) async fn stateless_serialize_and_write_files(
 /// Orchestrates multipart put of a dynamic number of output files from a single input stream
 /// for any statelessly serialized file type. That is, any file type for which each [RecordBatch]
 /// can be serialized independently of all other [RecordBatch]s.
+///
+/// The files are staged and only committed to the output location once all of them
+/// have been written successfully, see [`StagedWrite`].
 pub(crate) async fn stateless_multipart_put(
     data: SendableRecordBatchStream,
     context: &Arc<TaskContext>,
@@ -206,7 +214,34 @@ pub(crate) async fn stateless_multipart_put(
     get_serializer: Box<dyn Fn() -> Arc<dyn BatchSerializer> + Send>,
     config: &FileSinkConfig,
     compression: FileCompressionType,
-) -> Result<u64> {
+) -> Result<Vec<WrittenFile>> {
+    let object_store = context
+        .runtime_env()
+        .object_store(&config.object_store_url)?;
+
+    let staged_write = StagedWrite::new(object_store, &config.table_paths[0]);
+    let result = stateless_multipart_put_staged(
+        data,
+        context,
+        file_extension,
+        get_serializer,
+        config,
+        compression,
+        &staged_write,
+    )
+    .await;
+    staged_write.finish(result).await
+}
+
+async fn stateless_multipart_put_staged(
+    data: SendableRecordBatchStream,
+    context: &Arc<TaskContext>,
+    file_extension: String,
+    get_serializer: Box<dyn Fn() -> Arc<dyn BatchSerializer> + Send>,
+    config: &FileSinkConfig,
+    compression: FileCompressionType,
+    staged_write: &StagedWrite,
+) -> Result<Vec<WrittenFile>> {
     let object_store = context
         .runtime_env()
         .object_store(&config.object_store_url)?;
@@ -222,6 +257,7 @@ pub(crate) async fn stateless_multipart_put(
         data,
         context,
         part_cols,
+        config.bucket_by.clone(),
         base_output_path.clone(),
         file_extension,
     );
@@ -233,16 +269,18 @@ pub(crate) async fn stateless_multipart_put(
         .max_buffered_batches_per_output_file;
 
     let (tx_file_bundle, rx_file_bundle) = mpsc::channel(rb_buffer_size / 2);
-    let (tx_row_cnt, rx_row_cnt) = tokio::sync::oneshot::channel();
+    let (tx_written_files, rx_written_files) = tokio::sync::oneshot::channel();
     let write_coordinator_task = SpawnedTask::spawn(async move {
-        stateless_serialize_and_write_files(rx_file_bundle, tx_row_cnt).await
+        stateless_serialize_and_write_files(rx_file_bundle, tx_written_files).await
     });
     while let Some((location, rb_stream)) = file_stream_rx.recv().await {
         let serializer = get_serializer();
-        let writer = create_writer(compression, &location, object_store.clone()).await?;
+        let staged_location = staged_write.stage(&location);
+        let writer =
+            create_writer(compression, &staged_location, object_store.clone()).await?;
 
         tx_file_bundle
-            .send((rb_stream, serializer, writer))
+            .send((location, rb_stream, serializer, writer))
             .await
             .map_err(|_| {
                 internal_datafusion_err!(
@@ -261,9 +299,7 @@ pub(crate) async fn stateless_multipart_put(
     r1?;
     r2?;
 
-    let total_count = rx_row_cnt.await.map_err(|_| {
-        internal_datafusion_err!("Did not receieve row count from write coordinater")
-    })?;
-
-    Ok(total_count)
+    rx_written_files.await.map_err(|_| {
+        internal_datafusion_err!("Did not receieve written files from write coordinater")
+    })
 }
@@ -0,0 +1,234 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`XlsxFormat`]: Excel XLSX workbook [`FileFormat`] abstractions
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::{FileFormat, FileScanConfig};
+use crate::datasource::physical_plan::XlsxExec;
+use crate::datasource::xlsx_to_arrow::read_xlsx_schema;
+use crate::error::Result;
+use crate::execution::context::SessionState;
+use crate::physical_plan::{ExecutionPlan, Statistics};
+
+use arrow::datatypes::{Schema, SchemaRef};
+use datafusion_common::config::XlsxOptions;
+use datafusion_common::FileType;
+use datafusion_physical_expr::PhysicalExpr;
+
+use async_trait::async_trait;
+use object_store::{ObjectMeta, ObjectStore};
+
+/// Excel XLSX `FileFormat` implementation.
+///
+/// Reads one worksheet of each workbook, see [`XlsxOptions`]. Requires the
+/// `xlsx` feature.
+#[derive(Debug, Default)]
+pub struct XlsxFormat {
+    options: XlsxOptions,
+}
+
+impl XlsxFormat {
+    /// Set XLSX options
+    pub fn with_options(mut self, options: XlsxOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Retrieve XLSX options
+    pub fn options(&self) -> &XlsxOptions {
+        &self.options
+    }
+
+    /// Set the name of the worksheet to read.
+    /// - defaults to the first worksheet of each workbook
+    pub fn with_sheet_name(mut self, sheet_name: impl Into<String>) -> Self {
+        self.options.sheet_name = Some(sheet_name.into());
+        self
+    }
+
+    /// Set true to indicate that the first read row is a header.
+    /// - defaults to true
+    pub fn with_has_header(mut self, has_header: bool) -> Self {
+        self.options.has_header = has_header;
+        self
+    }
+
+    /// Set the number of rows skipped before the header or first data row.
+    /// - defaults to 0
+    pub fn with_skip_rows(mut self, skip_rows: usize) -> Self {
+        self.options.skip_rows = skip_rows;
+        self
+    }
+
+    /// Set a limit in terms of records to scan to infer the schema
+    /// - defaults to `DEFAULT_SCHEMA_INFER_MAX_RECORD`
+    pub fn with_schema_infer_max_rec(mut self, max_rec: usize) -> Self {
+        self.options.schema_infer_max_rec = max_rec;
+        self
+    }
+}
+
+#[async_trait]
+impl FileFormat for XlsxFormat {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn infer_schema(
+        &self,
+        _state: &SessionState,
+        store: &Arc<dyn ObjectStore>,
+        objects: &[ObjectMeta],
+    ) -> Result<SchemaRef> {
+        let mut schemas = vec![];
+        for object in objects {
+            // XLSX workbooks are zip archives, which are read as a whole
+            let data = store.as_ref().get(&object.location).await?.bytes().await?;
+            schemas.push(read_xlsx_schema(&data, &self.options)?);
+        }
+        let merged_schema = Schema::try_merge(schemas)?;
+        Ok(Arc::new(merged_schema))
+    }
+
+    async fn infer_stats(
+        &self,
+        _state: &SessionState,
+        _store: &Arc<dyn ObjectStore>,
+        table_schema: SchemaRef,
+        _object: &ObjectMeta,
+    ) -> Result<Statistics> {
+        Ok(Statistics::new_unknown(&table_schema))
+    }
+
+    async fn create_physical_plan(
+        &self,
+        _state: &SessionState,
+        conf: FileScanConfig,
+        _filters: Option<&Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let exec = XlsxExec::new(conf, self.options.clone());
+        Ok(Arc::new(exec))
+    }
+
+    fn file_type(&self) -> FileType {
+        FileType::XLSX
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "xlsx")]
+mod tests {
+    use super::*;
+    use crate::prelude::SessionContext;
+
+    use arrow::datatypes::{DataType, Field};
+    use arrow::util::pretty::pretty_format_batches;
+    use object_store::local::LocalFileSystem;
+    use object_store::path::Path;
+
+    #[tokio::test]
+    async fn infer_schema() -> Result<()> {
+        let ctx = SessionContext::new();
+        let store: Arc<dyn ObjectStore> = Arc::new(LocalFileSystem::new());
+        let path = std::fs::canonicalize("tests/data/orders.xlsx")?;
+        let meta = store
+            .head(&Path::from_filesystem_path(path).unwrap())
+            .await?;
+
+        let format = XlsxFormat::default().with_skip_rows(1);
+        let schema = format
+            .infer_schema(&ctx.state(), &store, &[meta.clone()])
+            .await?;
+        let expected = Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("customer", DataType::Utf8, true),
+            Field::new("amount", DataType::Float64, true),
+            Field::new("order_date", DataType::Date32, true),
+            Field::new("paid", DataType::Boolean, true),
+        ]);
+        assert_eq!(schema.as_ref(), &expected);
+
+        let format = XlsxFormat::default()
+            .with_sheet_name("notes")
+            .with_has_header(false);
+        let schema = format.infer_schema(&ctx.state(), &store, &[meta]).await?;
+        let expected = Schema::new(vec![
+            Field::new("column_1", DataType::Utf8, true),
+            Field::new("column_2", DataType::Int64, true),
+        ]);
+        assert_eq!(schema.as_ref(), &expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_external_table() -> Result<()> {
+        let ctx = SessionContext::new();
+        ctx.sql(
+            "CREATE EXTERNAL TABLE orders STORED AS XLSX \
+             LOCATION 'tests/data/orders.xlsx' \
+             OPTIONS ('format.skip_rows' '1')",
+        )
+        .await?;
+
+        let batches = ctx
+            .sql("SELECT order_date, customer, amount FROM orders WHERE paid ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+------------+----------+--------+",
+            "| order_date | customer | amount |",
+            "+------------+----------+--------+",
+            "| 2024-01-01 | alice    | 12.5   |",
+            "| 2024-01-03 | carol    |        |",
+            "+------------+----------+--------+",
+        ];
+        assert_eq!(
+            pretty_format_batches(&batches)?.to_string(),
+            expected.join("\n")
+        );
+
+        // a declared schema is read by column name
+        ctx.sql(
+            "CREATE EXTERNAL TABLE orders2 (customer VARCHAR, id INT, missing VARCHAR) \
+             STORED AS XLSX LOCATION 'tests/data/orders.xlsx' \
+             OPTIONS ('format.skip_rows' '1')",
+        )
+        .await?;
+        let batches = ctx
+            .sql("SELECT * FROM orders2 WHERE id > 1")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----------+----+---------+",
+            "| customer | id | missing |",
+            "+----------+----+---------+",
+            "| bob      | 2  |         |",
+            "| carol    | 3  |         |",
+            "+----------+----+---------+",
+        ];
+        assert_eq!(
+            pretty_format_batches(&batches)?.to_string(),
+            expected.join("\n")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,515 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reading of Iceberg manifest lists and manifests, and their pruning with
+//! the partition values they record
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use apache_avro::types::Value;
+use arrow::array::{new_null_array, ArrayRef, BooleanArray, UInt64Array};
+use arrow_schema::{DataType, Schema, TimeUnit};
+use datafusion_common::{exec_err, Column, Result, ScalarValue};
+
+use super::metadata::{PartitionSpec, FIELD_ID_META_KEY};
+use crate::physical_optimizer::pruning::PruningStatistics;
+
+/// The content of data files and of manifests listing data files
+pub const CONTENT_DATA: i32 = 0;
+/// The content of positional delete files
+pub const CONTENT_POSITION_DELETES: i32 = 1;
+/// The content of equality delete files
+pub const CONTENT_EQUALITY_DELETES: i32 = 2;
+
+/// The status of manifest entries of deleted files
+const STATUS_DELETED: i32 = 2;
+
+/// An entry of a manifest list
+#[derive(Debug, Clone)]
+pub struct ManifestFile {
+    /// The location of the manifest
+    pub manifest_path: String,
+    /// The id of the partition spec of the files of the manifest
+    pub partition_spec_id: i64,
+    /// Whether the manifest lists data files or delete files
+    pub content: i32,
+    /// The sequence number of the snapshot that added the manifest
+    pub sequence_number: i64,
+    /// Summaries of the values of each partition field, if known
+    pub partitions: Option<Vec<FieldSummary>>,
+}
+
+/// The summary of the values of a partition field in a manifest
+#[derive(Debug, Clone)]
+pub struct FieldSummary {
+    /// Whether any file of the manifest has a null partition value
+    pub contains_null: bool,
+    /// The lower bound of the partition values, in single value serialization
+    pub lower_bound: Option<Vec<u8>>,
+    /// The upper bound of the partition values, in single value serialization
+    pub upper_bound: Option<Vec<u8>>,
+}
+
+/// A live data or delete file of a manifest
+#[derive(Debug, Clone)]
+pub struct DataFile {
+    /// Whether this is a data file, or a positional or equality delete file
+    pub content: i32,
+    /// The location of the file
+    pub file_path: String,
+    /// The id of the partition spec of the file
+    pub partition_spec_id: i64,
+    /// The format of the file, e.g. `PARQUET`
+    pub file_format: String,
+    /// The partition values of the file, in the order of the partition spec
+    pub partition: Vec<Value>,
+    /// The number of records of the file
+    pub record_count: i64,
+    /// The size of the file in bytes
+    pub file_size_in_bytes: i64,
+    /// The field ids compared by an equality delete file
+    pub equality_ids: Vec<i32>,
+    /// The data sequence number of the file
+    pub sequence_number: i64,
+}
+
+/// Read the entries of a manifest list
+pub fn read_manifest_list(bytes: &[u8]) -> Result<Vec<ManifestFile>> {
+    apache_avro::Reader::new(bytes)?
+        .map(|record| {
+            let record = record?;
+            let partitions = match field(&record, "partitions") {
+                Some(Value::Array(summaries)) => Some(
+                    summaries
+                        .iter()
+                        .map(|summary| FieldSummary {
+                            contains_null: matches!(
+                                field(summary, "contains_null"),
+                                Some(Value::Boolean(true))
+                            ),
+                            lower_bound: bytes_field(summary, "lower_bound"),
+                            upper_bound: bytes_field(summary, "upper_bound"),
+                        })
+                        .collect(),
+                ),
+                _ => None,
+            };
+            Ok(ManifestFile {
+                manifest_path: string_field(&record, "manifest_path")?,
+                partition_spec_id: long_field(&record, "partition_spec_id")?,
+                content: long_field(&record, "content").unwrap_or(0) as i32,
+                sequence_number: long_field(&record, "sequence_number").unwrap_or(0),
+                partitions,
+            })
+        })
+        .collect()
+}
+
+/// Read the live files of a manifest, that is all the files not marked as
+/// deleted, inheriting their sequence number from the manifest if unset
+pub fn read_manifest(bytes: &[u8], manifest: &ManifestFile) -> Result<Vec<DataFile>> {
+    let mut files = vec![];
+    for entry in apache_avro::Reader::new(bytes)? {
+        let entry = entry?;
+        if long_field(&entry, "status")? == STATUS_DELETED as i64 {
+            continue;
+        }
+        let Some(data_file) = field(&entry, "data_file") else {
+            return exec_err!("Iceberg manifest entry is missing 'data_file'");
+        };
+        let partition = match field(data_file, "partition") {
+            Some(Value::Record(values)) => values
+                .iter()
+                .map(|(_, value)| unwrap_union(value).clone())
+                .collect(),
+            _ => vec![],
+        };
+        let equality_ids = match field(data_file, "equality_ids") {
+            Some(Value::Array(ids)) => ids
+                .iter()
+                .filter_map(|id| match id {
+                    Value::Int(id) => Some(*id),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        files.push(DataFile {
+            content: long_field(data_file, "content").unwrap_or(0) as i32,
+            file_path: string_field(data_file, "file_path")?,
+            partition_spec_id: manifest.partition_spec_id,
+            file_format: string_field(data_file, "file_format")?,
+            partition,
+            record_count: long_field(data_file, "record_count")?,
+            file_size_in_bytes: long_field(data_file, "file_size_in_bytes")?,
+            equality_ids,
+            sequence_number: long_field(&entry, "sequence_number")
+                .unwrap_or(manifest.sequence_number),
+        });
+    }
+    Ok(files)
+}
+
+/// The identity partition fields of a partition spec, with the columns of the
+/// table schema they are the values of
+fn identity_columns(
+    spec: &PartitionSpec,
+    schema: &Schema,
+) -> Vec<(usize, String, DataType)> {
+    spec.fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.transform == "identity")
+        .filter_map(|(idx, partition_field)| {
+            let source_id = partition_field.source_id.to_string();
+            let field = schema.fields().iter().find(|field| {
+                field.metadata().get(FIELD_ID_META_KEY) == Some(&source_id)
+            })?;
+            Some((idx, field.name().clone(), field.data_type().clone()))
+        })
+        .collect()
+}
+
+/// [`PruningStatistics`] of the manifests of a manifest list, with the bounds
+/// of the values of their identity partition fields
+pub struct ManifestPruningStatistics<'a> {
+    manifests: &'a [ManifestFile],
+    /// The partition field index, name and type of each pruned column
+    columns: Vec<(usize, String, DataType)>,
+}
+
+impl<'a> ManifestPruningStatistics<'a> {
+    /// Create statistics of `manifests`, which must all use `spec`
+    pub fn new(
+        manifests: &'a [ManifestFile],
+        spec: &PartitionSpec,
+        schema: &Schema,
+    ) -> Self {
+        Self {
+            manifests,
+            columns: identity_columns(spec, schema),
+        }
+    }
+
+    fn bounds(
+        &self,
+        column: &Column,
+        bound: impl Fn(&FieldSummary) -> Option<&Vec<u8>>,
+    ) -> Option<ArrayRef> {
+        let (idx, _, data_type) = self.columns.iter().find(|c| c.1 == column.name)?;
+        let values = self.manifests.iter().map(|manifest| {
+            let summary = manifest.partitions.as_ref()?.get(*idx)?;
+            bound(summary).and_then(|bytes| decode_bound(bytes, data_type))
+        });
+        to_array(values, data_type)
+    }
+}
+
+impl PruningStatistics for ManifestPruningStatistics<'_> {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.bounds(column, |summary| summary.lower_bound.as_ref())
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.bounds(column, |summary| summary.upper_bound.as_ref())
+    }
+
+    fn num_containers(&self) -> usize {
+        self.manifests.len()
+    }
+
+    /// The number of nulls is only known to be zero, if the manifest contains
+    /// no null partition values
+    fn null_counts(&self, column: &Column) -> Option<ArrayRef> {
+        let (idx, _, _) = self.columns.iter().find(|c| c.1 == column.name)?;
+        let counts: UInt64Array = self
+            .manifests
+            .iter()
+            .map(|manifest| {
+                let summary = manifest.partitions.as_ref()?.get(*idx)?;
+                (!summary.contains_null).then_some(0)
+            })
+            .collect();
+        Some(Arc::new(counts))
+    }
+
+    fn row_counts(&self, _column: &Column) -> Option<ArrayRef> {
+        None
+    }
+
+    fn contained(
+        &self,
+        _column: &Column,
+        _values: &HashSet<ScalarValue>,
+    ) -> Option<BooleanArray> {
+        None
+    }
+}
+
+/// [`PruningStatistics`] of the data files of a manifest, with the values of
+/// their identity partition fields
+pub struct DataFilePruningStatistics<'a> {
+    files: &'a [DataFile],
+    /// The partition field index, name and type of each pruned column
+    columns: Vec<(usize, String, DataType)>,
+}
+
+impl<'a> DataFilePruningStatistics<'a> {
+    /// Create statistics of `files`, which must all use `spec`
+    pub fn new(files: &'a [DataFile], spec: &PartitionSpec, schema: &Schema) -> Self {
+        Self {
+            files,
+            columns: identity_columns(spec, schema),
+        }
+    }
+
+    fn values(&self, column: &Column) -> Option<ArrayRef> {
+        let (idx, _, data_type) = self.columns.iter().find(|c| c.1 == column.name)?;
+        let values = self
+            .files
+            .iter()
+            .map(|file| to_scalar(file.partition.get(*idx)?, data_type));
+        to_array(values, data_type)
+    }
+}
+
+impl PruningStatistics for DataFilePruningStatistics<'_> {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column)
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column)
+    }
+
+    fn num_containers(&self) -> usize {
+        self.files.len()
+    }
+
+    fn null_counts(&self, column: &Column) -> Option<ArrayRef> {
+        let (idx, _, _) = self.columns.iter().find(|c| c.1 == column.name)?;
+        let counts: UInt64Array = self
+            .files
+            .iter()
+            .map(|file| match file.partition.get(*idx)? {
+                Value::Null => Some(file.record_count as u64),
+                _ => Some(0),
+            })
+            .collect();
+        Some(Arc::new(counts))
+    }
+
+    fn row_counts(&self, column: &Column) -> Option<ArrayRef> {
+        self.columns.iter().find(|c| c.1 == column.name)?;
+        let counts: UInt64Array = self
+            .files
+            .iter()
+            .map(|file| Some(file.record_count as u64))
+            .collect();
+        Some(Arc::new(counts))
+    }
+
+    fn contained(
+        &self,
+        _column: &Column,
+        _values: &HashSet<ScalarValue>,
+    ) -> Option<BooleanArray> {
+        None
+    }
+}
+
+/// Collect optional values of `data_type` into an array, nulls standing for
+/// unknown values
+fn to_array(
+    values: impl Iterator<Item = Option<ScalarValue>>,
+    data_type: &DataType,
+) -> Option<ArrayRef> {
+    let values: Vec<_> = values
+        .map(|value| match value {
+            Some(value) => value.cast_to(data_type).ok(),
+            None => None,
+        })
+        .collect();
+    if values.iter().all(Option::is_none) {
+        return Some(new_null_array(data_type, values.len()));
+    }
+    let values = values.into_iter().map(|value| match value {
+        Some(value) => value,
+        None => ScalarValue::try_from(data_type).unwrap_or(ScalarValue::Null),
+    });
+    ScalarValue::iter_to_array(values).ok()
+}
+
+/// Decode a bound in Iceberg's single value serialization
+fn decode_bound(bytes: &[u8], data_type: &DataType) -> Option<ScalarValue> {
+    Some(match data_type {
+        DataType::Boolean => ScalarValue::Boolean(Some(*bytes.first()? != 0)),
+        DataType::Int32 => {
+            ScalarValue::Int32(Some(i32::from_le_bytes(bytes.try_into().ok()?)))
+        }
+        DataType::Date32 => {
+            ScalarValue::Date32(Some(i32::from_le_bytes(bytes.try_into().ok()?)))
+        }
+        DataType::Int64 => {
+            ScalarValue::Int64(Some(i64::from_le_bytes(bytes.try_into().ok()?)))
+        }
+        DataType::Float32 => {
+            ScalarValue::Float32(Some(f32::from_le_bytes(bytes.try_into().ok()?)))
+        }
+        DataType::Float64 => {
+            ScalarValue::Float64(Some(f64::from_le_bytes(bytes.try_into().ok()?)))
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, tz) => {
+            ScalarValue::TimestampMicrosecond(
+                Some(i64::from_le_bytes(bytes.try_into().ok()?)),
+                tz.clone(),
+            )
+        }
+        DataType::Utf8 => {
+            ScalarValue::Utf8(Some(String::from_utf8(bytes.to_vec()).ok()?))
+        }
+        _ => return None,
+    })
+}
+
+/// Convert a partition value of a manifest entry to a [`ScalarValue`]
+fn to_scalar(value: &Value, data_type: &DataType) -> Option<ScalarValue> {
+    Some(match value {
+        Value::Null => return ScalarValue::try_from(data_type).ok(),
+        Value::Boolean(v) => ScalarValue::Boolean(Some(*v)),
+        Value::Int(v) => ScalarValue::Int32(Some(*v)),
+        Value::Long(v) => ScalarValue::Int64(Some(*v)),
+        Value::Float(v) => ScalarValue::Float32(Some(*v)),
+        Value::Double(v) => ScalarValue::Float64(Some(*v)),
+        Value::Date(v) => ScalarValue::Date32(Some(*v)),
+        Value::TimestampMicros(v) => ScalarValue::TimestampMicrosecond(Some(*v), None),
+        Value::String(v) => ScalarValue::Utf8(Some(v.clone())),
+        _ => return None,
+    })
+}
+
+fn unwrap_union(value: &Value) -> &Value {
+    match value {
+        Value::Union(_, value) => value,
+        value => value,
+    }
+}
+
+fn field<'a>(record: &'a Value, name: &str) -> Option<&'a Value> {
+    match record {
+        Value::Record(fields) => fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| unwrap_union(value))
+            .filter(|value| !matches!(value, Value::Null)),
+        _ => None,
+    }
+}
+
+fn long_field(record: &Value, name: &str) -> Result<i64> {
+    match field(record, name) {
+        Some(Value::Int(value)) => Ok(*value as i64),
+        Some(Value::Long(value)) => Ok(*value),
+        _ => exec_err!("Iceberg manifest is missing the integer '{name}'"),
+    }
+}
+
+fn string_field(record: &Value, name: &str) -> Result<String> {
+    match field(record, name) {
+        Some(Value::String(value)) => Ok(value.clone()),
+        _ => exec_err!("Iceberg manifest is missing the string '{name}'"),
+    }
+}
+
+fn bytes_field(record: &Value, name: &str) -> Option<Vec<u8>> {
+    match field(record, name)? {
+        Value::Bytes(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::physical_optimizer::pruning::PruningPredicate;
+    use arrow_schema::Field;
+    use datafusion_expr::Operator;
+    use datafusion_physical_expr::expressions::{col, lit, BinaryExpr};
+    use std::collections::HashMap;
+
+    use super::super::metadata::PartitionField;
+
+    #[test]
+    fn test_manifest_pruning() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("p", DataType::Int64, true)
+            .with_metadata(HashMap::from([(
+                FIELD_ID_META_KEY.to_string(),
+                "1".to_string(),
+            )]))]));
+        let spec = PartitionSpec {
+            spec_id: 0,
+            fields: vec![PartitionField {
+                source_id: 1,
+                name: "p".to_string(),
+                transform: "identity".to_string(),
+            }],
+        };
+        let manifest = |lower: i64, upper: i64| ManifestFile {
+            manifest_path: String::new(),
+            partition_spec_id: 0,
+            content: CONTENT_DATA,
+            sequence_number: 1,
+            partitions: Some(vec![FieldSummary {
+                contains_null: false,
+                lower_bound: Some(lower.to_le_bytes().to_vec()),
+                upper_bound: Some(upper.to_le_bytes().to_vec()),
+            }]),
+        };
+        let manifests = [manifest(1, 5), manifest(6, 10)];
+
+        let expr = Arc::new(BinaryExpr::new(
+            col("p", &schema)?,
+            Operator::Gt,
+            lit(ScalarValue::Int64(Some(7))),
+        ));
+        let predicate = PruningPredicate::try_new(expr, schema.clone())?;
+        let stats = ManifestPruningStatistics::new(&manifests, &spec, &schema);
+        assert_eq!(predicate.prune(&stats)?, vec![false, true]);
+
+        let file = |value: Value| DataFile {
+            content: CONTENT_DATA,
+            file_path: String::new(),
+            partition_spec_id: 0,
+            file_format: "PARQUET".to_string(),
+            partition: vec![value],
+            record_count: 1,
+            file_size_in_bytes: 1,
+            equality_ids: vec![],
+            sequence_number: 1,
+        };
+        let files = [
+            file(Value::Long(7)),
+            file(Value::Long(8)),
+            file(Value::Null),
+        ];
+        let stats = DataFilePruningStatistics::new(&files, &spec, &schema);
+        assert_eq!(predicate.prune(&stats)?, vec![false, true, false]);
+        Ok(())
+    }
+}
@@ -0,0 +1,419 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Parsing of Iceberg table metadata files
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit};
+use datafusion_common::{exec_err, DataFusionError, Result};
+use serde_json::Value;
+
+/// The metadata key of the Iceberg field id of an Arrow [`Field`], which is
+/// also the one of Parquet field ids
+pub const FIELD_ID_META_KEY: &str = "PARQUET:field_id";
+
+/// The contents of an Iceberg table metadata file, such as
+/// `metadata/v2.metadata.json`
+#[derive(Debug, Clone)]
+pub struct TableMetadata {
+    /// The table format version, 1 or 2
+    pub format_version: i64,
+    /// The base location of the table
+    pub location: String,
+    /// The id of the current schema
+    pub current_schema_id: i64,
+    /// The schemas of the table by id, as Arrow schemas whose fields carry
+    /// their field id in their metadata
+    pub schemas: HashMap<i64, SchemaRef>,
+    /// The partition specs of the table by id
+    pub partition_specs: HashMap<i64, PartitionSpec>,
+    /// The id of the current snapshot, if the table has any
+    pub current_snapshot_id: Option<i64>,
+    /// The valid snapshots of the table
+    pub snapshots: Vec<Snapshot>,
+}
+
+/// A partition spec of an Iceberg table
+#[derive(Debug, Clone, Default)]
+pub struct PartitionSpec {
+    /// The id of the spec
+    pub spec_id: i64,
+    /// The partition fields, in the order of the partition tuples
+    pub fields: Vec<PartitionField>,
+}
+
+/// A field of a [`PartitionSpec`]
+#[derive(Debug, Clone)]
+pub struct PartitionField {
+    /// The id of the source column in the table schema
+    pub source_id: i64,
+    /// The name of the partition field
+    pub name: String,
+    /// The transform applied to the source column, e.g. `identity` or `day`
+    pub transform: String,
+}
+
+/// A snapshot of an Iceberg table
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// The id of the snapshot
+    pub snapshot_id: i64,
+    /// The sequence number of the snapshot, 0 for format version 1
+    pub sequence_number: i64,
+    /// When the snapshot was created, in milliseconds since the epoch
+    pub timestamp_ms: i64,
+    /// The location of the manifest list of the snapshot
+    pub manifest_list: String,
+    /// The id of the schema of the table when the snapshot was created
+    pub schema_id: Option<i64>,
+}
+
+impl TableMetadata {
+    /// Parse a table metadata file
+    pub fn try_new(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+        let format_version = get_i64(&value, "format-version")?;
+        let location = get_str(&value, "location")?.to_string();
+
+        // format version 1 may only have a single schema and partition spec
+        let (current_schema_id, schemas) = match value.get("schemas") {
+            Some(schemas) => (
+                get_i64(&value, "current-schema-id")?,
+                as_array(schemas, "schemas")?
+                    .iter()
+                    .map(|schema| {
+                        let id = schema.get("schema-id").and_then(Value::as_i64);
+                        Ok((id.unwrap_or(0), Arc::new(parse_schema(schema)?)))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            None => {
+                let schema = parse_schema(get(&value, "schema")?)?;
+                (0, HashMap::from([(0, Arc::new(schema))]))
+            }
+        };
+
+        let partition_specs = match value.get("partition-specs") {
+            Some(specs) => as_array(specs, "partition-specs")?
+                .iter()
+                .map(|spec| {
+                    let spec_id = get_i64(spec, "spec-id")?;
+                    let fields = parse_partition_fields(get(spec, "fields")?)?;
+                    Ok((spec_id, PartitionSpec { spec_id, fields }))
+                })
+                .collect::<Result<_>>()?,
+            None => {
+                let fields = match value.get("partition-spec") {
+                    Some(fields) => parse_partition_fields(fields)?,
+                    None => vec![],
+                };
+                HashMap::from([(0, PartitionSpec { spec_id: 0, fields })])
+            }
+        };
+
+        let current_snapshot_id = value
+            .get("current-snapshot-id")
+            .and_then(Value::as_i64)
+            .filter(|id| *id != -1);
+
+        let snapshots = match value.get("snapshots") {
+            Some(snapshots) => as_array(snapshots, "snapshots")?
+                .iter()
+                .map(parse_snapshot)
+                .collect::<Result<_>>()?,
+            None => vec![],
+        };
+
+        Ok(Self {
+            format_version,
+            location,
+            current_schema_id,
+            schemas,
+            partition_specs,
+            current_snapshot_id,
+            snapshots,
+        })
+    }
+
+    /// Returns the current schema of the table
+    pub fn current_schema(&self) -> Result<SchemaRef> {
+        self.schema(self.current_schema_id)
+    }
+
+    /// Returns the schema with the given id
+    pub fn schema(&self, schema_id: i64) -> Result<SchemaRef> {
+        match self.schemas.get(&schema_id) {
+            Some(schema) => Ok(schema.clone()),
+            None => exec_err!("Iceberg schema {schema_id} not found"),
+        }
+    }
+
+    /// Returns the current snapshot of the table, if any
+    pub fn current_snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot(self.current_snapshot_id?)
+    }
+
+    /// Returns the snapshot with the given id
+    pub fn snapshot(&self, snapshot_id: i64) -> Option<&Snapshot> {
+        self.snapshots
+            .iter()
+            .find(|snapshot| snapshot.snapshot_id == snapshot_id)
+    }
+
+    /// Returns the last snapshot created at or before `timestamp_ms`
+    pub fn snapshot_as_of(&self, timestamp_ms: i64) -> Option<&Snapshot> {
+        self.snapshots
+            .iter()
+            .filter(|snapshot| snapshot.timestamp_ms <= timestamp_ms)
+            .max_by_key(|snapshot| snapshot.timestamp_ms)
+    }
+}
+
+fn parse_snapshot(value: &Value) -> Result<Snapshot> {
+    let manifest_list = match value.get("manifest-list") {
+        Some(manifest_list) => as_str(manifest_list, "manifest-list")?.to_string(),
+        None => {
+            return exec_err!(
+                "Iceberg snapshots without a manifest list are not supported"
+            )
+        }
+    };
+    Ok(Snapshot {
+        snapshot_id: get_i64(value, "snapshot-id")?,
+        sequence_number: value
+            .get("sequence-number")
+            .and_then(Value::as_i64)
+            .unwrap_or(0),
+        timestamp_ms: get_i64(value, "timestamp-ms")?,
+        manifest_list,
+        schema_id: value.get("schema-id").and_then(Value::as_i64),
+    })
+}
+
+fn parse_partition_fields(value: &Value) -> Result<Vec<PartitionField>> {
+    as_array(value, "fields")?
+        .iter()
+        .map(|field| {
+            Ok(PartitionField {
+                source_id: get_i64(field, "source-id")?,
+                name: get_str(field, "name")?.to_string(),
+                transform: get_str(field, "transform")?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Converts an Iceberg schema to an Arrow schema
+fn parse_schema(value: &Value) -> Result<Schema> {
+    Ok(Schema::new(parse_struct_fields(value)?))
+}
+
+fn parse_struct_fields(value: &Value) -> Result<Fields> {
+    as_array(get(value, "fields")?, "fields")?
+        .iter()
+        .map(|field| {
+            let required = get(field, "required")?.as_bool().unwrap_or(false);
+            Ok(new_field(
+                get_str(field, "name")?,
+                get_i64(field, "id")?,
+                parse_type(get(field, "type")?)?,
+                !required,
+            ))
+        })
+        .collect()
+}
+
+fn new_field(name: &str, id: i64, data_type: DataType, nullable: bool) -> Field {
+    Field::new(name, data_type, nullable).with_metadata(HashMap::from([(
+        FIELD_ID_META_KEY.to_string(),
+        id.to_string(),
+    )]))
+}
+
+/// Converts an Iceberg type to an Arrow type
+fn parse_type(value: &Value) -> Result<DataType> {
+    let Some(name) = value.as_str() else {
+        return match get_str(value, "type")? {
+            "struct" => Ok(DataType::Struct(parse_struct_fields(value)?)),
+            "list" => {
+                let required = get(value, "element-required")?.as_bool() == Some(true);
+                Ok(DataType::List(Arc::new(new_field(
+                    "element",
+                    get_i64(value, "element-id")?,
+                    parse_type(get(value, "element")?)?,
+                    !required,
+                ))))
+            }
+            "map" => {
+                let required = get(value, "value-required")?.as_bool() == Some(true);
+                let key = new_field(
+                    "key",
+                    get_i64(value, "key-id")?,
+                    parse_type(get(value, "key")?)?,
+                    false,
+                );
+                let value = new_field(
+                    "value",
+                    get_i64(value, "value-id")?,
+                    parse_type(get(value, "value")?)?,
+                    !required,
+                );
+                Ok(DataType::Map(
+                    Arc::new(Field::new(
+                        "key_value",
+                        DataType::Struct(Fields::from(vec![key, value])),
+                        false,
+                    )),
+                    false,
+                ))
+            }
+            other => exec_err!("Unsupported Iceberg type {other}"),
+        };
+    };
+
+    Ok(match name {
+        "boolean" => DataType::Boolean,
+        "int" => DataType::Int32,
+        "long" => DataType::Int64,
+        "float" => DataType::Float32,
+        "double" => DataType::Float64,
+        "date" => DataType::Date32,
+        "time" => DataType::Time64(TimeUnit::Microsecond),
+        "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        "timestamptz" => {
+            DataType::Timestamp(TimeUnit::Microsecond, Some("+00:00".into()))
+        }
+        "string" => DataType::Utf8,
+        "uuid" => DataType::FixedSizeBinary(16),
+        "binary" => DataType::Binary,
+        _ => {
+            if let Some(args) = name
+                .strip_prefix("decimal(")
+                .and_then(|args| args.strip_suffix(')'))
+            {
+                let parsed = args.split_once(',').and_then(|(p, s)| {
+                    Some((p.trim().parse().ok()?, s.trim().parse().ok()?))
+                });
+                if let Some((precision, scale)) = parsed {
+                    return Ok(DataType::Decimal128(precision, scale));
+                }
+            } else if let Some(len) = name
+                .strip_prefix("fixed[")
+                .and_then(|len| len.strip_suffix(']'))
+                .and_then(|len| len.parse().ok())
+            {
+                return Ok(DataType::FixedSizeBinary(len));
+            }
+            return exec_err!("Unsupported Iceberg type {name}");
+        }
+    })
+}
+
+fn get<'a>(value: &'a Value, key: &str) -> Result<&'a Value> {
+    match value.get(key) {
+        Some(value) => Ok(value),
+        None => exec_err!("Iceberg metadata is missing '{key}'"),
+    }
+}
+
+fn get_i64(value: &Value, key: &str) -> Result<i64> {
+    match get(value, key)?.as_i64() {
+        Some(value) => Ok(value),
+        None => exec_err!("Iceberg metadata '{key}' is not an integer"),
+    }
+}
+
+fn get_str<'a>(value: &'a Value, key: &str) -> Result<&'a str> {
+    as_str(get(value, key)?, key)
+}
+
+fn as_str<'a>(value: &'a Value, key: &str) -> Result<&'a str> {
+    match value.as_str() {
+        Some(value) => Ok(value),
+        None => exec_err!("Iceberg metadata '{key}' is not a string"),
+    }
+}
+
+fn as_array<'a>(value: &'a Value, key: &str) -> Result<&'a Vec<Value>> {
+    match value.as_array() {
+        Some(value) => Ok(value),
+        None => exec_err!("Iceberg metadata '{key}' is not an array"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metadata() -> Result<()> {
+        let metadata = TableMetadata::try_new(
+            r#"{
+                "format-version": 2,
+                "location": "file:/tmp/t",
+                "current-schema-id": 1,
+                "schemas": [
+                    {"type": "struct", "schema-id": 0, "fields": [
+                        {"id": 1, "name": "a", "required": true, "type": "long"}
+                    ]},
+                    {"type": "struct", "schema-id": 1, "fields": [
+                        {"id": 1, "name": "b", "required": true, "type": "long"},
+                        {"id": 2, "name": "c", "required": false, "type": "decimal(10, 2)"},
+                        {"id": 3, "name": "d", "required": false, "type": {
+                            "type": "list", "element-id": 4, "element-required": false,
+                            "element": "date"
+                        }}
+                    ]}
+                ],
+                "default-spec-id": 0,
+                "partition-specs": [
+                    {"spec-id": 0, "fields": [
+                        {"source-id": 1, "field-id": 1000, "name": "b", "transform": "identity"}
+                    ]}
+                ],
+                "current-snapshot-id": 2,
+                "snapshots": [
+                    {"snapshot-id": 1, "sequence-number": 1, "timestamp-ms": 1000,
+                     "manifest-list": "file:/tmp/t/metadata/snap-1.avro", "schema-id": 0},
+                    {"snapshot-id": 2, "sequence-number": 2, "timestamp-ms": 2000,
+                     "manifest-list": "file:/tmp/t/metadata/snap-2.avro", "schema-id": 1}
+                ]
+            }"#,
+        )?;
+
+        let schema = metadata.current_schema()?;
+        let names: Vec<_> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, ["b", "c", "d"]);
+        assert_eq!(
+            schema.field(0).metadata().get(FIELD_ID_META_KEY),
+            Some(&"1".to_string())
+        );
+        assert!(!schema.field(0).is_nullable());
+        assert_eq!(schema.field(1).data_type(), &DataType::Decimal128(10, 2));
+        assert_eq!(metadata.schema(0)?.field(0).name(), "a");
+
+        assert_eq!(metadata.partition_specs[&0].fields[0].transform, "identity");
+        assert_eq!(metadata.current_snapshot().unwrap().snapshot_id, 2);
+        assert_eq!(metadata.snapshot_as_of(1500).unwrap().snapshot_id, 1);
+        assert!(metadata.snapshot_as_of(500).is_none());
+        Ok(())
+    }
+}
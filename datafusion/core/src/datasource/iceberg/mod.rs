@@ -0,0 +1,1210 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`IcebergTable`]: a [`TableProvider`] reading snapshots of Apache Iceberg tables
+
+pub mod manifest;
+pub mod metadata;
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use datafusion_common::cast::{as_int64_array, as_string_array};
+use datafusion_common::{
+    exec_err, not_impl_err, plan_err, project_schema, DataFusionError, JoinType, Result,
+    Statistics, ToDFSchema,
+};
+use datafusion_expr::utils::conjunction;
+use datafusion_expr::{
+    CreateExternalTable, Expr, TableProviderFilterPushDown, TableType, TableVersion,
+};
+use datafusion_physical_expr::expressions::Column;
+use datafusion_physical_expr::{create_physical_expr, PhysicalExpr};
+use futures::{StreamExt, TryStreamExt};
+use log::debug;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+use parquet::arrow::async_reader::ParquetObjectReader;
+use parquet::arrow::{ParquetRecordBatchStreamBuilder, ProjectionMask};
+
+use self::manifest::{
+    read_manifest, read_manifest_list, DataFile, DataFilePruningStatistics,
+    ManifestPruningStatistics, CONTENT_DATA, CONTENT_EQUALITY_DELETES,
+    CONTENT_POSITION_DELETES,
+};
+use self::metadata::{PartitionSpec, Snapshot, TableMetadata, FIELD_ID_META_KEY};
+use crate::datasource::file_format::parquet::ParquetFormat;
+use crate::datasource::file_format::FileFormat;
+use crate::datasource::listing::{split_files, ListingTableUrl, PartitionedFile};
+use crate::datasource::physical_plan::{DeletedRows, FileScanConfig};
+use crate::datasource::provider::TableProviderFactory;
+use crate::datasource::schema_adapter::{
+    DefaultSchemaAdapterFactory, SchemaAdapterFactory,
+};
+use crate::datasource::TableProvider;
+use crate::execution::context::SessionState;
+use crate::physical_optimizer::pruning::PruningPredicate;
+use crate::physical_plan::empty::EmptyExec;
+use crate::physical_plan::joins::{HashJoinExec, PartitionMode};
+use crate::physical_plan::limit::LocalLimitExec;
+use crate::physical_plan::memory::MemoryExec;
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::union::UnionExec;
+use crate::physical_plan::ExecutionPlan;
+
+/// The field id of the `file_path` column of positional delete files
+const DELETE_FILE_PATH_FIELD_ID: i64 = 2147483546;
+/// The field id of the `pos` column of positional delete files
+const DELETE_POS_FIELD_ID: i64 = 2147483545;
+
+/// A [`TableProvider`] reading a snapshot of an [Apache Iceberg] table.
+///
+/// The current snapshot is read by default, an older one may be chosen with
+/// [`Self::with_snapshot_id`] or [`Self::with_snapshot_as_of`], or in SQL with
+/// `FOR VERSION AS OF <snapshot id>` or `FOR TIMESTAMP AS OF <timestamp>`.
+/// Only data files in the Parquet format are supported.
+///
+/// Manifests and data files are pruned with the values of the identity
+/// partition fields recorded in the manifest list and in the manifests.
+/// The columns of the data files are matched by field id, so that renamed,
+/// added and dropped columns are read correctly, and the rows removed by
+/// positional and equality delete files are skipped.
+///
+/// [Apache Iceberg]: https://iceberg.apache.org/spec/
+#[derive(Debug, Clone)]
+pub struct IcebergTable {
+    /// The location of the table or of its metadata file
+    table_url: ListingTableUrl,
+    metadata: Arc<TableMetadata>,
+    /// The snapshot that is read, `None` if the table has no snapshots
+    snapshot: Option<Snapshot>,
+    /// The schema of the snapshot
+    schema: SchemaRef,
+}
+
+impl IcebergTable {
+    /// Read the current snapshot of the table at `location`, which is either
+    /// the base location of the table or the location of a metadata file.
+    ///
+    /// The metadata file of a base location is the one named by
+    /// `metadata/version-hint.text`, if any, and the one with the highest
+    /// version in the `metadata` directory otherwise.
+    pub async fn try_new(
+        state: &SessionState,
+        location: impl AsRef<str>,
+    ) -> Result<Self> {
+        let location = location.as_ref();
+        let table_url = ListingTableUrl::parse(location)?;
+        let store = state.runtime_env().object_store(table_url.object_store())?;
+
+        let metadata_path = if location.ends_with(".metadata.json") {
+            table_url.prefix().clone()
+        } else {
+            find_metadata_file(store.as_ref(), table_url.prefix()).await?
+        };
+        let bytes = store.get(&metadata_path).await?.bytes().await?;
+        let json = std::str::from_utf8(&bytes)
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let metadata = TableMetadata::try_new(json)?;
+
+        Ok(Self {
+            table_url,
+            snapshot: metadata.current_snapshot().cloned(),
+            schema: metadata.current_schema()?,
+            metadata: Arc::new(metadata),
+        })
+    }
+
+    /// Read the snapshot with the given id instead of the current one
+    pub fn with_snapshot_id(self, snapshot_id: i64) -> Result<Self> {
+        match self.metadata.snapshot(snapshot_id).cloned() {
+            Some(snapshot) => self.with_snapshot(snapshot),
+            None => plan_err!("Iceberg snapshot {snapshot_id} not found"),
+        }
+    }
+
+    /// Read the last snapshot created at or before `timestamp_ms`, in
+    /// milliseconds since the epoch, instead of the current one
+    pub fn with_snapshot_as_of(self, timestamp_ms: i64) -> Result<Self> {
+        match self.metadata.snapshot_as_of(timestamp_ms).cloned() {
+            Some(snapshot) => self.with_snapshot(snapshot),
+            None => plan_err!("No Iceberg snapshot found as of {timestamp_ms}"),
+        }
+    }
+
+    fn with_snapshot(mut self, snapshot: Snapshot) -> Result<Self> {
+        self.schema = match snapshot.schema_id {
+            Some(schema_id) => self.metadata.schema(schema_id)?,
+            None => self.metadata.current_schema()?,
+        };
+        self.snapshot = Some(snapshot);
+        Ok(self)
+    }
+
+    /// Returns the metadata of the table
+    pub fn metadata(&self) -> &TableMetadata {
+        &self.metadata
+    }
+
+    /// Returns the snapshot that is read, if the table has any
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// Returns the live data and delete files of `snapshot` that may contain
+    /// rows matching `predicate`
+    async fn list_files(
+        &self,
+        store: &Arc<dyn ObjectStore>,
+        snapshot: &Snapshot,
+        predicate: Option<&PruningPredicate>,
+    ) -> Result<Vec<DataFile>> {
+        let manifests =
+            read_manifest_list(&read_bytes(store, &snapshot.manifest_list).await?)?;
+        let manifests = match predicate {
+            Some(predicate) => prune_by_spec(
+                manifests,
+                &self.metadata,
+                |manifest| manifest.partition_spec_id,
+                |manifests, spec| {
+                    predicate.prune(&ManifestPruningStatistics::new(
+                        manifests,
+                        spec,
+                        &self.schema,
+                    ))
+                },
+            )?,
+            None => manifests,
+        };
+
+        let files = futures::future::try_join_all(manifests.iter().map(|manifest| {
+            let store = Arc::clone(store);
+            async move {
+                read_manifest(
+                    &read_bytes(&store, &manifest.manifest_path).await?,
+                    manifest,
+                )
+            }
+        }))
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+
+        match predicate {
+            Some(predicate) => prune_by_spec(
+                files,
+                &self.metadata,
+                |file| file.partition_spec_id,
+                |files, spec| {
+                    predicate.prune(&DataFilePruningStatistics::new(
+                        files,
+                        spec,
+                        &self.schema,
+                    ))
+                },
+            ),
+            None => Ok(files),
+        }
+    }
+
+    /// Read the positional delete files, returning the deleted positions of
+    /// each of the `data_files` they apply to
+    async fn read_position_deletes(
+        &self,
+        store: &Arc<dyn ObjectStore>,
+        delete_files: &[&DataFile],
+        data_files: &[&DataFile],
+    ) -> Result<HashMap<String, Vec<u64>>> {
+        let sequence_numbers: HashMap<&str, i64> = data_files
+            .iter()
+            .map(|file| (file.file_path.as_str(), file.sequence_number))
+            .collect();
+        let schema = Arc::new(Schema::new(vec![
+            field_with_id("file_path", DataType::Utf8, DELETE_FILE_PATH_FIELD_ID),
+            field_with_id("pos", DataType::Int64, DELETE_POS_FIELD_ID),
+        ]));
+
+        let mut positions: HashMap<String, Vec<u64>> = HashMap::new();
+        for delete_file in delete_files {
+            for batch in read_delete_file(store, delete_file, schema.clone()).await? {
+                let paths = as_string_array(batch.column(0))?;
+                let deleted = as_int64_array(batch.column(1))?;
+                for (path, pos) in paths.iter().zip(deleted.iter()) {
+                    let (Some(path), Some(pos)) = (path, pos) else {
+                        continue;
+                    };
+                    // deletes only apply to files that were added before them
+                    if sequence_numbers
+                        .get(path)
+                        .is_some_and(|seq| *seq <= delete_file.sequence_number)
+                    {
+                        positions
+                            .entry(path.to_string())
+                            .or_default()
+                            .push(pos as u64);
+                    }
+                }
+            }
+        }
+        Ok(positions)
+    }
+
+    /// Read the equality delete files, with the columns they compare
+    async fn read_equality_deletes(
+        &self,
+        store: &Arc<dyn ObjectStore>,
+        delete_files: Vec<&DataFile>,
+    ) -> Result<Vec<EqualityDeletes>> {
+        let mut deletes = Vec::with_capacity(delete_files.len());
+        for delete_file in delete_files {
+            let columns = delete_file
+                .equality_ids
+                .iter()
+                .map(|id| match field_index(&self.schema, *id as i64) {
+                    Some(idx) => Ok(idx),
+                    None => not_impl_err!(
+                        "Equality deletes of field {id}, which is not in the schema of the snapshot, are not supported"
+                    ),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let schema = Arc::new(self.schema.project(&columns)?);
+            deletes.push(EqualityDeletes {
+                batches: read_delete_file(store, delete_file, schema).await?,
+                columns,
+                file: delete_file.clone(),
+            });
+        }
+        Ok(deletes)
+    }
+
+    /// Returns true if every column of the table kept its name in all of its
+    /// schemas, so that filters may be evaluated with the by name statistics
+    /// and columns of the data files
+    fn has_stable_column_names(&self) -> bool {
+        let mut names = HashMap::new();
+        let mut ids = HashMap::new();
+        self.metadata
+            .schemas
+            .values()
+            .flat_map(|schema| schema.fields().iter())
+            .all(|field| {
+                let id = field.metadata().get(FIELD_ID_META_KEY);
+                *names.entry(id).or_insert(field.name()) == field.name()
+                    && *ids.entry(field.name()).or_insert(id) == id
+            })
+    }
+}
+
+/// The rows of an equality delete file
+struct EqualityDeletes {
+    file: DataFile,
+    /// The indices of the compared columns in the table schema
+    columns: Vec<usize>,
+    batches: Vec<RecordBatch>,
+}
+
+impl EqualityDeletes {
+    /// Returns true if the deletes apply to `data_file`, that is if it was added
+    /// before them, and is in the same partition unless they are unpartitioned
+    fn applies_to(&self, data_file: &DataFile, metadata: &TableMetadata) -> bool {
+        let unpartitioned = metadata
+            .partition_specs
+            .get(&self.file.partition_spec_id)
+            .map_or(true, |spec| spec.fields.is_empty());
+        data_file.sequence_number < self.file.sequence_number
+            && (unpartitioned
+                || (data_file.partition_spec_id == self.file.partition_spec_id
+                    && data_file.partition == self.file.partition))
+    }
+}
+
+#[async_trait]
+impl TableProvider for IcebergTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let Some(snapshot) = &self.snapshot else {
+            let projected_schema = project_schema(&self.schema, projection)?;
+            return Ok(Arc::new(EmptyExec::new(projected_schema)));
+        };
+        let store = state
+            .runtime_env()
+            .object_store(self.table_url.object_store())?;
+
+        let predicate = match conjunction(filters.to_vec()) {
+            Some(expr) => {
+                let df_schema = self.schema.as_ref().clone().to_dfschema()?;
+                Some(create_physical_expr(
+                    &expr,
+                    &df_schema,
+                    state.execution_props(),
+                )?)
+            }
+            None => None,
+        };
+        let pruning_predicate = match &predicate {
+            Some(predicate) => {
+                match PruningPredicate::try_new(predicate.clone(), self.schema.clone()) {
+                    Ok(pruning_predicate) => Some(pruning_predicate),
+                    Err(e) => {
+                        debug!("Could not create pruning predicate for: {e}");
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let files = self
+            .list_files(&store, snapshot, pruning_predicate.as_ref())
+            .await?;
+        if let Some(file) = files
+            .iter()
+            .find(|file| !file.file_format.eq_ignore_ascii_case("parquet"))
+        {
+            return not_impl_err!(
+                "Iceberg files in the {} format are not supported",
+                file.file_format
+            );
+        }
+
+        let mut data_files = vec![];
+        let mut position_deletes = vec![];
+        let mut equality_deletes = vec![];
+        for file in &files {
+            match file.content {
+                CONTENT_DATA => data_files.push(file),
+                CONTENT_POSITION_DELETES => position_deletes.push(file),
+                CONTENT_EQUALITY_DELETES => equality_deletes.push(file),
+                content => return exec_err!("Unknown Iceberg file content {content}"),
+            }
+        }
+        let mut deleted_positions = self
+            .read_position_deletes(&store, &position_deletes, &data_files)
+            .await?;
+        let equality_deletes =
+            self.read_equality_deletes(&store, equality_deletes).await?;
+
+        // group the data files by the equality deletes that apply to them, each
+        // group is read by a separate scan
+        let mut groups: BTreeMap<Vec<usize>, Vec<PartitionedFile>> = BTreeMap::new();
+        for data_file in data_files {
+            let deletes = equality_deletes
+                .iter()
+                .enumerate()
+                .filter(|(_, deletes)| deletes.applies_to(data_file, &self.metadata))
+                .map(|(idx, _)| idx)
+                .collect();
+            let mut file = PartitionedFile::from(object_meta(data_file)?);
+            if let Some(positions) = deleted_positions.remove(&data_file.file_path) {
+                file.extensions = Some(Arc::new(DeletedRows::new(positions)));
+            }
+            groups.entry(deletes).or_default().push(file);
+        }
+
+        // the predicate refers to columns by name, which may only be used with
+        // the data files if no column was ever renamed
+        let predicate = predicate.filter(|_| self.has_stable_column_names());
+        let format =
+            ParquetFormat::default().with_options(state.default_table_options().parquet);
+        let projection = projection
+            .cloned()
+            .unwrap_or_else(|| (0..self.schema.fields().len()).collect());
+        let mut plans: Vec<Arc<dyn ExecutionPlan>> = vec![];
+        for (deletes, files) in groups {
+            // the columns compared by the equality deletes are read as well
+            let mut scan_projection = projection.clone();
+            for idx in deletes
+                .iter()
+                .flat_map(|idx| &equality_deletes[*idx].columns)
+            {
+                if !scan_projection.contains(idx) {
+                    scan_projection.push(*idx);
+                }
+            }
+            let config = FileScanConfig {
+                object_store_url: self.table_url.object_store(),
+                file_schema: self.schema.clone(),
+                file_groups: split_files(files, state.config().target_partitions()),
+                statistics: Statistics::new_unknown(&self.schema),
+                projection: Some(scan_projection.clone()),
+                // positional deletes are applied by the scan, equality deletes
+                // after it
+                limit: limit.filter(|_| deletes.is_empty()),
+                table_partition_cols: vec![],
+                output_ordering: vec![],
+                bucket_by: None,
+            };
+            let mut plan = format
+                .create_physical_plan(state, config, predicate.as_ref())
+                .await?;
+            if deletes.is_empty() {
+                plans.push(plan);
+                continue;
+            }
+
+            // remove the deleted rows with an anti join per set of compared columns
+            let mut delete_sets: BTreeMap<&[usize], Vec<RecordBatch>> = BTreeMap::new();
+            for deletes in deletes.iter().map(|idx| &equality_deletes[*idx]) {
+                delete_sets
+                    .entry(&deletes.columns)
+                    .or_default()
+                    .extend(deletes.batches.iter().cloned());
+            }
+            for (columns, batches) in delete_sets {
+                let schema = Arc::new(self.schema.project(columns)?);
+                let on = columns
+                    .iter()
+                    .enumerate()
+                    .map(|(left_idx, table_idx)| {
+                        let right_idx = scan_projection
+                            .iter()
+                            .position(|idx| idx == table_idx)
+                            .expect("compared columns are projected");
+                        let name = self.schema.field(*table_idx).name();
+                        (
+                            Arc::new(Column::new(name, left_idx))
+                                as Arc<dyn PhysicalExpr>,
+                            Arc::new(Column::new(name, right_idx))
+                                as Arc<dyn PhysicalExpr>,
+                        )
+                    })
+                    .collect();
+                plan = Arc::new(HashJoinExec::try_new(
+                    Arc::new(MemoryExec::try_new(&[batches], schema, None)?),
+                    plan,
+                    on,
+                    None,
+                    &JoinType::RightAnti,
+                    None,
+                    PartitionMode::CollectLeft,
+                    true,
+                )?);
+            }
+
+            let plan_schema = plan.schema();
+            let exprs = (0..projection.len())
+                .map(|idx| {
+                    let name = plan_schema.field(idx).name();
+                    (
+                        Arc::new(Column::new(name, idx)) as Arc<dyn PhysicalExpr>,
+                        name.clone(),
+                    )
+                })
+                .collect();
+            plan = Arc::new(ProjectionExec::try_new(exprs, plan)?);
+            if let Some(limit) = limit {
+                plan = Arc::new(LocalLimitExec::new(plan, limit));
+            }
+            plans.push(plan);
+        }
+
+        Ok(match plans.len() {
+            0 => Arc::new(EmptyExec::new(project_schema(
+                &self.schema,
+                Some(&projection),
+            )?)),
+            1 => plans.remove(0),
+            _ => Arc::new(UnionExec::new(plans)),
+        })
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        // filters are only used for pruning
+        Ok(vec![TableProviderFilterPushDown::Inexact; filters.len()])
+    }
+
+    async fn with_version(
+        &self,
+        _state: &SessionState,
+        version: TableVersion,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let table = match version {
+            TableVersion::Version(snapshot_id) => {
+                self.clone().with_snapshot_id(snapshot_id)?
+            }
+            TableVersion::Timestamp(timestamp_ms) => {
+                self.clone().with_snapshot_as_of(timestamp_ms)?
+            }
+        };
+        Ok(Arc::new(table))
+    }
+}
+
+/// A [`TableProviderFactory`] creating an [`IcebergTable`] for
+/// `CREATE EXTERNAL TABLE ... STORED AS ICEBERG LOCATION '...'`.
+///
+/// An older snapshot may be read with the `snapshot_id` option, or with the
+/// `snapshot_as_of` option in milliseconds since the epoch.
+#[derive(Debug, Default)]
+pub struct IcebergTableFactory {}
+
+impl IcebergTableFactory {
+    /// Creates a new `IcebergTableFactory`
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TableProviderFactory for IcebergTableFactory {
+    async fn create(
+        &self,
+        state: &SessionState,
+        cmd: &CreateExternalTable,
+    ) -> Result<Arc<dyn TableProvider>> {
+        if !cmd.schema.fields().is_empty() {
+            return plan_err!(
+                "The schema of an Iceberg table is read from its metadata and can not be specified"
+            );
+        }
+
+        let mut table = IcebergTable::try_new(state, &cmd.location).await?;
+        for (key, value) in &cmd.options {
+            let parse = || {
+                value.parse::<i64>().map_err(|e| {
+                    DataFusionError::Plan(format!(
+                        "Invalid value '{value}' for Iceberg option {key}: {e}"
+                    ))
+                })
+            };
+            table = match key.as_str() {
+                "snapshot_id" => table.with_snapshot_id(parse()?)?,
+                "snapshot_as_of" => table.with_snapshot_as_of(parse()?)?,
+                _ => return plan_err!("Unknown Iceberg table option {key}"),
+            };
+        }
+        Ok(Arc::new(table))
+    }
+}
+
+/// Returns the metadata file with the highest version in the `metadata`
+/// directory of the table, or the one named by its version hint
+async fn find_metadata_file(store: &dyn ObjectStore, table_path: &Path) -> Result<Path> {
+    let metadata_dir = table_path.child("metadata");
+    match store.get(&metadata_dir.child("version-hint.text")).await {
+        Ok(hint) => {
+            let hint = hint.bytes().await?;
+            let version = String::from_utf8_lossy(&hint).trim().to_string();
+            return Ok(metadata_dir.child(format!("v{version}.metadata.json")));
+        }
+        Err(object_store::Error::NotFound { .. }) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let files: Vec<ObjectMeta> = store.list(Some(&metadata_dir)).try_collect().await?;
+    files
+        .into_iter()
+        .filter_map(|meta| {
+            let version = metadata_version(meta.location.filename()?)?;
+            Some((version, meta.location))
+        })
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, location)| location)
+        .ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "No Iceberg metadata file found in {metadata_dir}"
+            ))
+        })
+}
+
+/// Returns the version of metadata files named `v<version>.metadata.json`
+/// or `<version>-<uuid>.metadata.json`
+fn metadata_version(file_name: &str) -> Option<u64> {
+    let name = file_name.strip_suffix(".metadata.json")?;
+    let name = name.strip_prefix('v').unwrap_or(name);
+    name.split('-').next()?.parse().ok()
+}
+
+/// Returns the `items` that may match the pruning predicate, evaluated by
+/// `prune` for the items of each partition spec
+fn prune_by_spec<T: Clone>(
+    items: Vec<T>,
+    metadata: &TableMetadata,
+    spec_id: impl Fn(&T) -> i64,
+    prune: impl Fn(&[T], &PartitionSpec) -> Result<Vec<bool>>,
+) -> Result<Vec<T>> {
+    let mut keep = vec![true; items.len()];
+    for spec in metadata.partition_specs.values() {
+        let (indices, group): (Vec<_>, Vec<_>) = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| spec_id(item) == spec.spec_id)
+            .map(|(idx, item)| (idx, item.clone()))
+            .unzip();
+        if group.is_empty() {
+            continue;
+        }
+        for (idx, keep_item) in indices.into_iter().zip(prune(&group, spec)?) {
+            keep[idx] = keep_item;
+        }
+    }
+    Ok(items
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(item, keep)| keep.then_some(item))
+        .collect())
+}
+
+/// Read a delete file, mapping its columns to `schema` by field id
+async fn read_delete_file(
+    store: &Arc<dyn ObjectStore>,
+    file: &DataFile,
+    schema: SchemaRef,
+) -> Result<Vec<RecordBatch>> {
+    let reader = ParquetObjectReader::new(Arc::clone(store), object_meta(file)?);
+    let builder = ParquetRecordBatchStreamBuilder::new(reader).await?;
+    let (mapping, projection) = DefaultSchemaAdapterFactory::default()
+        .create(schema.clone(), schema)
+        .map_schema(builder.schema())?;
+    let mask = ProjectionMask::roots(builder.parquet_schema(), projection);
+    builder
+        .with_projection(mask)
+        .build()?
+        .map(|batch| mapping.map_batch(batch?))
+        .try_collect()
+        .await
+}
+
+async fn read_bytes(store: &Arc<dyn ObjectStore>, location: &str) -> Result<Bytes> {
+    Ok(store.get(&object_path(location)?).await?.bytes().await?)
+}
+
+/// Returns the object store path of a location recorded in the metadata
+fn object_path(location: &str) -> Result<Path> {
+    Ok(ListingTableUrl::parse(location)?.prefix().clone())
+}
+
+fn object_meta(file: &DataFile) -> Result<ObjectMeta> {
+    Ok(ObjectMeta {
+        location: object_path(&file.file_path)?,
+        last_modified: Utc.timestamp_nanos(0),
+        size: file.file_size_in_bytes as usize,
+        e_tag: None,
+        version: None,
+    })
+}
+
+fn field_with_id(name: &str, data_type: DataType, id: i64) -> Field {
+    Field::new(name, data_type, false).with_metadata(HashMap::from([(
+        FIELD_ID_META_KEY.to_string(),
+        id.to_string(),
+    )]))
+}
+
+/// Returns the index of the field of `schema` with the given field id
+fn field_index(schema: &Schema, id: i64) -> Option<usize> {
+    let id = id.to_string();
+    schema
+        .fields()
+        .iter()
+        .position(|field| field.metadata().get(FIELD_ID_META_KEY) == Some(&id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::assert_batches_eq;
+    use crate::physical_plan::displayable;
+    use crate::prelude::SessionContext;
+    use apache_avro::types::Value;
+    use arrow::array::{ArrayRef, AsArray, Int64Array, StringArray};
+    use arrow::datatypes::Int64Type;
+    use parquet::arrow::ArrowWriter;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    const MANIFEST_LIST_SCHEMA: &str = r#"{
+        "type": "record", "name": "manifest_file", "fields": [
+            {"name": "manifest_path", "type": "string"},
+            {"name": "manifest_length", "type": "long"},
+            {"name": "partition_spec_id", "type": "int"},
+            {"name": "content", "type": "int"},
+            {"name": "sequence_number", "type": "long"},
+            {"name": "partitions", "type": {"type": "array", "items": {
+                "type": "record", "name": "field_summary", "fields": [
+                    {"name": "contains_null", "type": "boolean"},
+                    {"name": "lower_bound", "type": "bytes"},
+                    {"name": "upper_bound", "type": "bytes"}
+                ]
+            }}}
+        ]
+    }"#;
+
+    const MANIFEST_SCHEMA: &str = r#"{
+        "type": "record", "name": "manifest_entry", "fields": [
+            {"name": "status", "type": "int"},
+            {"name": "sequence_number", "type": "long"},
+            {"name": "data_file", "type": {
+                "type": "record", "name": "r2", "fields": [
+                    {"name": "content", "type": "int"},
+                    {"name": "file_path", "type": "string"},
+                    {"name": "file_format", "type": "string"},
+                    {"name": "partition", "type": {
+                        "type": "record", "name": "r102", "fields": [
+                            {"name": "p", "type": "long"}
+                        ]
+                    }},
+                    {"name": "record_count", "type": "long"},
+                    {"name": "file_size_in_bytes", "type": "long"},
+                    {"name": "equality_ids", "type": {"type": "array", "items": "int"}}
+                ]
+            }}
+        ]
+    }"#;
+
+    /// Writes a parquet file with the given columns and field ids, returning
+    /// its location and size
+    fn write_parquet(
+        dir: &std::path::Path,
+        name: &str,
+        columns: Vec<(&str, i64, ArrayRef)>,
+    ) -> Result<(String, i64)> {
+        let fields: Vec<_> = columns
+            .iter()
+            .map(|(name, id, array)| field_with_id(name, array.data_type().clone(), *id))
+            .collect();
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            columns.into_iter().map(|(_, _, array)| array).collect(),
+        )?;
+        let path = dir.join("data").join(name);
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path)?, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        let size = std::fs::metadata(&path)?.len() as i64;
+        Ok((path.to_str().unwrap().to_string(), size))
+    }
+
+    fn write_avro(
+        path: &std::path::Path,
+        schema: &str,
+        values: Vec<Value>,
+    ) -> Result<String> {
+        let schema = apache_avro::Schema::parse_str(schema)?;
+        let mut writer = apache_avro::Writer::new(&schema, vec![]);
+        for value in values {
+            writer.append(value)?;
+        }
+        std::fs::write(path, writer.into_inner()?)?;
+        Ok(path.to_str().unwrap().to_string())
+    }
+
+    fn int64(values: Vec<i64>) -> ArrayRef {
+        Arc::new(Int64Array::from(values))
+    }
+
+    fn utf8(values: Vec<&str>) -> ArrayRef {
+        Arc::new(StringArray::from(values))
+    }
+
+    fn record(fields: Vec<(&str, Value)>) -> Value {
+        Value::Record(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+
+    fn manifest_entry(
+        content: i32,
+        file: &(String, i64),
+        seq: i64,
+        p: i64,
+        rows: i64,
+    ) -> Value {
+        let equality_ids = match content {
+            CONTENT_EQUALITY_DELETES => vec![Value::Int(1)],
+            _ => vec![],
+        };
+        record(vec![
+            ("status", Value::Int(1)),
+            ("sequence_number", Value::Long(seq)),
+            (
+                "data_file",
+                record(vec![
+                    ("content", Value::Int(content)),
+                    ("file_path", Value::String(file.0.clone())),
+                    ("file_format", Value::String("PARQUET".to_string())),
+                    ("partition", record(vec![("p", Value::Long(p))])),
+                    ("record_count", Value::Long(rows)),
+                    ("file_size_in_bytes", Value::Long(file.1)),
+                    ("equality_ids", Value::Array(equality_ids)),
+                ]),
+            ),
+        ])
+    }
+
+    fn manifest_file(path: &str, content: i32, seq: i64, bounds: (i64, i64)) -> Value {
+        record(vec![
+            ("manifest_path", Value::String(path.to_string())),
+            ("manifest_length", Value::Long(0)),
+            ("partition_spec_id", Value::Int(0)),
+            ("content", Value::Int(content)),
+            ("sequence_number", Value::Long(seq)),
+            (
+                "partitions",
+                Value::Array(vec![record(vec![
+                    ("contains_null", Value::Boolean(false)),
+                    ("lower_bound", Value::Bytes(bounds.0.to_le_bytes().to_vec())),
+                    ("upper_bound", Value::Bytes(bounds.1.to_le_bytes().to_vec())),
+                ])]),
+            ),
+        ])
+    }
+
+    /// Creates a table partitioned by `p`, whose column `name` was renamed to
+    /// `label`. The first snapshot has a data file with the ids 1 to 3, the
+    /// second one adds a data file with the ids 4 and 5, and the third one
+    /// deletes the ids 2 and 4 with a positional and an equality delete file.
+    fn create_table(dir: &std::path::Path) -> Result<()> {
+        std::fs::create_dir_all(dir.join("data"))?;
+        std::fs::create_dir_all(dir.join("metadata"))?;
+        let metadata = |name: &str| dir.join("metadata").join(name);
+
+        let a = write_parquet(
+            dir,
+            "a.parquet",
+            vec![
+                ("id", 1, int64(vec![1, 2, 3])),
+                ("name", 2, utf8(vec!["a", "b", "c"])),
+                ("p", 3, int64(vec![1, 1, 1])),
+            ],
+        )?;
+        let b = write_parquet(
+            dir,
+            "b.parquet",
+            vec![
+                ("id", 1, int64(vec![4, 5])),
+                ("label", 2, utf8(vec!["d", "e"])),
+                ("p", 3, int64(vec![2, 2])),
+            ],
+        )?;
+        let position_deletes = write_parquet(
+            dir,
+            "position-deletes.parquet",
+            vec![
+                (
+                    "file_path",
+                    DELETE_FILE_PATH_FIELD_ID,
+                    utf8(vec![a.0.as_str()]),
+                ),
+                ("pos", DELETE_POS_FIELD_ID, int64(vec![1])),
+            ],
+        )?;
+        let equality_deletes = write_parquet(
+            dir,
+            "equality-deletes.parquet",
+            vec![("id", 1, int64(vec![4]))],
+        )?;
+
+        let m1 = write_avro(
+            &metadata("m1.avro"),
+            MANIFEST_SCHEMA,
+            vec![manifest_entry(CONTENT_DATA, &a, 1, 1, 3)],
+        )?;
+        let m2 = write_avro(
+            &metadata("m2.avro"),
+            MANIFEST_SCHEMA,
+            vec![manifest_entry(CONTENT_DATA, &b, 2, 2, 2)],
+        )?;
+        let m3 = write_avro(
+            &metadata("m3.avro"),
+            MANIFEST_SCHEMA,
+            vec![
+                manifest_entry(CONTENT_POSITION_DELETES, &position_deletes, 3, 1, 1),
+                manifest_entry(CONTENT_EQUALITY_DELETES, &equality_deletes, 3, 2, 1),
+            ],
+        )?;
+        let snap1 = write_avro(
+            &metadata("snap-1.avro"),
+            MANIFEST_LIST_SCHEMA,
+            vec![manifest_file(&m1, CONTENT_DATA, 1, (1, 1))],
+        )?;
+        let snap2 = write_avro(
+            &metadata("snap-2.avro"),
+            MANIFEST_LIST_SCHEMA,
+            vec![
+                manifest_file(&m1, CONTENT_DATA, 1, (1, 1)),
+                manifest_file(&m2, CONTENT_DATA, 2, (2, 2)),
+            ],
+        )?;
+        let snap3 = write_avro(
+            &metadata("snap-3.avro"),
+            MANIFEST_LIST_SCHEMA,
+            vec![
+                manifest_file(&m1, CONTENT_DATA, 1, (1, 1)),
+                manifest_file(&m2, CONTENT_DATA, 2, (2, 2)),
+                manifest_file(&m3, CONTENT_POSITION_DELETES, 3, (1, 2)),
+            ],
+        )?;
+
+        let schema = |id: i64, name: &str| {
+            format!(
+                r#"{{"type": "struct", "schema-id": {id}, "fields": [
+                    {{"id": 1, "name": "id", "required": true, "type": "long"}},
+                    {{"id": 2, "name": "{name}", "required": true, "type": "string"}},
+                    {{"id": 3, "name": "p", "required": true, "type": "long"}}
+                ]}}"#
+            )
+        };
+        let json = format!(
+            r#"{{
+                "format-version": 2,
+                "location": "{location}",
+                "current-schema-id": 1,
+                "schemas": [{schema0}, {schema1}],
+                "partition-specs": [{{"spec-id": 0, "fields": [
+                    {{"source-id": 3, "field-id": 1000, "name": "p", "transform": "identity"}}
+                ]}}],
+                "current-snapshot-id": 3,
+                "snapshots": [
+                    {{"snapshot-id": 1, "sequence-number": 1, "timestamp-ms": 1000,
+                      "manifest-list": "{snap1}", "schema-id": 0}},
+                    {{"snapshot-id": 2, "sequence-number": 2, "timestamp-ms": 2000,
+                      "manifest-list": "{snap2}", "schema-id": 1}},
+                    {{"snapshot-id": 3, "sequence-number": 3, "timestamp-ms": 3000,
+                      "manifest-list": "{snap3}", "schema-id": 1}}
+                ]
+            }}"#,
+            location = dir.to_str().unwrap(),
+            schema0 = schema(0, "name"),
+            schema1 = schema(1, "label"),
+        );
+        std::fs::write(metadata("v1.metadata.json"), "{}")?;
+        std::fs::write(metadata("v2.metadata.json"), json)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_snapshots() -> Result<()> {
+        let dir = TempDir::new()?;
+        create_table(dir.path())?;
+        let location = dir.path().to_str().unwrap();
+
+        let ctx = SessionContext::new();
+        let table = IcebergTable::try_new(&ctx.state(), location).await?;
+        assert_eq!(table.snapshot().unwrap().snapshot_id, 3);
+        ctx.register_table("t", Arc::new(table))?;
+
+        let batches = ctx
+            .sql("SELECT * FROM t ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----+-------+---+",
+            "| id | label | p |",
+            "+----+-------+---+",
+            "| 1  | a     | 1 |",
+            "| 3  | c     | 1 |",
+            "| 5  | e     | 2 |",
+            "+----+-------+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        // the equality deletes compare a column that is not selected
+        let batches = ctx
+            .sql("SELECT label FROM t WHERE p = 2")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+-------+",
+            "| label |",
+            "+-------+",
+            "| e     |",
+            "+-------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        ctx.sql(&format!(
+            "CREATE EXTERNAL TABLE t1 STORED AS ICEBERG LOCATION '{location}' \
+             OPTIONS ('snapshot_id' '1')"
+        ))
+        .await?;
+        let batches = ctx
+            .sql("SELECT * FROM t1 ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----+------+---+",
+            "| id | name | p |",
+            "+----+------+---+",
+            "| 1  | a    | 1 |",
+            "| 2  | b    | 1 |",
+            "| 3  | c    | 1 |",
+            "+----+------+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        // the second snapshot has no delete files
+        let table = IcebergTable::try_new(&ctx.state(), location)
+            .await?
+            .with_snapshot_as_of(2500)?;
+        assert_eq!(table.snapshot().unwrap().snapshot_id, 2);
+        ctx.register_table("t2", Arc::new(table))?;
+        let batches = ctx.sql("SELECT count(*) FROM t2").await?.collect().await?;
+        let expected = [
+            "+----------+",
+            "| COUNT(*) |",
+            "+----------+",
+            "| 5        |",
+            "+----------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let err = IcebergTable::try_new(&ctx.state(), location)
+            .await?
+            .with_snapshot_as_of(500)
+            .unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: No Iceberg snapshot found as of 500"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_snapshots_sql() -> Result<()> {
+        let dir = TempDir::new()?;
+        create_table(dir.path())?;
+        let location = dir.path().to_str().unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.sql(&format!(
+            "CREATE EXTERNAL TABLE t STORED AS ICEBERG LOCATION '{location}'"
+        ))
+        .await?;
+
+        let batches = ctx
+            .sql("SELECT * FROM t FOR VERSION AS OF 1 ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----+------+---+",
+            "| id | name | p |",
+            "+----+------+---+",
+            "| 1  | a    | 1 |",
+            "| 2  | b    | 1 |",
+            "| 3  | c    | 1 |",
+            "+----+------+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let batches = ctx
+            .sql(
+                "SELECT count(*) FROM t FOR TIMESTAMP AS OF '1970-01-01T00:00:02.500' AS t2",
+            )
+            .await?
+            .collect()
+            .await?;
+        let expected = [
+            "+----------+",
+            "| COUNT(*) |",
+            "+----------+",
+            "| 5        |",
+            "+----------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let err = ctx
+            .sql("SELECT * FROM t FOR VERSION AS OF 4")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: Iceberg snapshot 4 not found"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn limit_with_deletes() -> Result<()> {
+        let dir = TempDir::new()?;
+        create_table(dir.path())?;
+        let location = dir.path().to_str().unwrap();
+
+        let ctx = SessionContext::new();
+        let table = IcebergTable::try_new(&ctx.state(), location).await?;
+        let plan = table.scan(&ctx.state(), None, &[], Some(2)).await?;
+        let plan = displayable(plan.as_ref()).indent(false).to_string();
+        // the positional deletes of the first file are applied by its scan, the
+        // equality deletes of the second one after its scan
+        let lines: Vec<_> = plan.lines().map(str::trim).collect();
+        assert!(lines[1].ends_with("projection=[id, label, p], limit=2"));
+        assert_eq!(lines[2], "LocalLimitExec: fetch=2");
+        assert!(lines[6].ends_with("projection=[id, label, p]"));
+        ctx.register_table("t", Arc::new(table))?;
+
+        // the limit is applied to the rows left by the delete files
+        for (p, expected) in [(1, vec![1, 3]), (2, vec![5])] {
+            let batches = ctx
+                .sql(&format!("SELECT id FROM t WHERE p = {p} LIMIT 2"))
+                .await?
+                .collect()
+                .await?;
+            let ids: Vec<_> = batches
+                .iter()
+                .flat_map(|batch| batch.column(0).as_primitive::<Int64Type>().values())
+                .copied()
+                .collect();
+            assert_eq!(ids, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_version() {
+        assert_eq!(metadata_version("v12.metadata.json"), Some(12));
+        assert_eq!(
+            metadata_version("00003-9f1a1e2c-0c3a-4c1e-8f4d-3b2a1c0d9e8f.metadata.json"),
+            Some(3)
+        );
+        assert_eq!(metadata_version("version-hint.text"), None);
+    }
+}
@@ -31,10 +31,13 @@ use arrow::{
     record_batch::RecordBatch,
 };
 use arrow_schema::Fields;
+use chrono::NaiveDate;
 use datafusion_common::tree_node::{TreeNode, TreeNodeRecursion};
-use datafusion_common::{internal_err, Column, DFField, DFSchema, DataFusionError};
+use datafusion_common::{
+    internal_err, plan_err, Column, DFField, DFSchema, DataFusionError,
+};
 use datafusion_expr::execution_props::ExecutionProps;
-use datafusion_expr::{Expr, ScalarFunctionDefinition, Volatility};
+use datafusion_expr::{BinaryExpr, Expr, Operator, ScalarFunctionDefinition, Volatility};
 use datafusion_physical_expr::create_physical_expr;
 use futures::stream::{BoxStream, FuturesUnordered, StreamExt, TryStreamExt};
 use log::{debug, trace};
@@ -133,6 +136,120 @@ pub fn expr_applicable_for_cols(col_names: &[String], expr: &Expr) -> bool {
     is_applicable
 }
 
+/// Returns the part of `expr` that can be resolved using only the columns
+/// `col_names`, and that is true whenever `expr` is, or `None` if there is no
+/// such part.
+///
+/// For example `(part = 1 AND x > 5) OR part = 2` becomes `part = 1 OR part = 2`
+/// for the partition column `part`.
+pub fn expr_for_cols(col_names: &[String], expr: &Expr) -> Option<Expr> {
+    if expr_applicable_for_cols(col_names, expr) {
+        return Some(expr.clone());
+    }
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => match (
+            expr_for_cols(col_names, left),
+            expr_for_cols(col_names, right),
+        ) {
+            (Some(left), Some(right)) => Some(left.and(right)),
+            (left, right) => left.or(right),
+        },
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        }) => Some(expr_for_cols(col_names, left)?.or(expr_for_cols(col_names, right)?)),
+        _ => None,
+    }
+}
+
+/// Infers the Hive-style partition columns of `table_path` from the paths of
+/// its `files`, e.g. `/table/year=2021/day=2021-01-01/data.parquet` has the
+/// partition columns `year` and `day`.
+///
+/// A column is typed as `Int64` if all its values are integers, as `Date32`
+/// if they are all `%Y-%m-%d` dates, and as `Utf8` otherwise. Values that would
+/// not be written back the same, such as `007`, keep the column a `Utf8`, and
+/// [`HIVE_DEFAULT_PARTITION`] values are ignored. All files must be partitioned
+/// by the same columns.
+pub fn infer_partition_cols(
+    table_path: &ListingTableUrl,
+    files: &[ObjectMeta],
+) -> Result<Vec<(String, DataType)>> {
+    let mut names: Option<Vec<&str>> = None;
+    let mut values: Vec<Vec<&str>> = vec![];
+    for file in files {
+        let Some(segments) = table_path.strip_prefix(&file.location) else {
+            continue;
+        };
+        let segments: Vec<_> = segments.collect();
+        // the last segment is the file name
+        let parts: Vec<_> = segments[..segments.len().saturating_sub(1)]
+            .iter()
+            .map_while(|segment| segment.split_once('='))
+            .collect();
+        let file_names: Vec<_> = parts.iter().map(|(name, _)| *name).collect();
+
+        match &names {
+            None => {
+                values = parts.iter().map(|(_, value)| vec![*value]).collect();
+                names = Some(file_names);
+            }
+            Some(names) if *names == file_names => {
+                for ((_, value), values) in parts.iter().zip(values.iter_mut()) {
+                    values.push(value);
+                }
+            }
+            Some(names) => {
+                return plan_err!(
+                    "Found mixed partition columns {names:?} and {file_names:?} in {table_path}"
+                )
+            }
+        }
+    }
+
+    Ok(names
+        .unwrap_or_default()
+        .into_iter()
+        .zip(values)
+        .map(|(name, values)| (name.to_string(), infer_partition_type(&values)))
+        .collect())
+}
+
+/// Returns the narrowest type of `Int64`, `Date32` and `Utf8` holding all `values`
+/// without changing their text
+fn infer_partition_type(values: &[&str]) -> DataType {
+    let values: Vec<_> = values
+        .iter()
+        .filter(|v| **v != HIVE_DEFAULT_PARTITION)
+        .collect();
+    if values.is_empty() {
+        DataType::Utf8
+    } else if values
+        .iter()
+        .all(|v| matches!(v.parse::<i64>(), Ok(i) if i.to_string() == ***v))
+    {
+        DataType::Int64
+    } else if values.iter().all(|v| {
+        matches!(
+            NaiveDate::parse_from_str(v, "%Y-%m-%d"),
+            Ok(date) if date.format("%Y-%m-%d").to_string() == ***v
+        )
+    }) {
+        DataType::Date32
+    } else {
+        DataType::Utf8
+    }
+}
+
+/// The value of a Hive-style partition directory holding the rows whose
+/// partition value is null, e.g. `/table/year=__HIVE_DEFAULT_PARTITION__/`
+pub const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
 /// The maximum number of concurrent listing requests
 const CONCURRENCY_LIMIT: usize = 100;
 
@@ -232,6 +349,11 @@ async fn prune_partitions(
     filters: &[Expr],
     partition_cols: &[(String, DataType)],
 ) -> Result<Vec<Partition>> {
+    let col_names: Vec<_> = partition_cols.iter().map(|(n, _)| n.clone()).collect();
+    let filters: Vec<_> = filters
+        .iter()
+        .filter_map(|filter| expr_for_cols(&col_names, filter))
+        .collect();
     if filters.is_empty() {
         return Ok(partitions);
     }
@@ -247,7 +369,10 @@ async fn prune_partitions(
 
         let mut builders = builders.iter_mut();
         for (p, b) in parsed.iter().zip(&mut builders) {
-            b.append_value(p);
+            match *p {
+                HIVE_DEFAULT_PARTITION => b.append_null(),
+                p => b.append_value(p),
+            }
         }
         builders.for_each(|b| b.append_null());
     }
@@ -360,8 +485,9 @@ pub async fn pruned_partition_list<'a>(
                 .into_iter()
                 .flatten()
                 .zip(partition_cols)
-                .map(|(parsed, (_, datatype))| {
-                    ScalarValue::try_from_string(parsed.to_string(), datatype)
+                .map(|(parsed, (_, datatype))| match parsed {
+                    HIVE_DEFAULT_PARTITION => ScalarValue::try_from(datatype),
+                    parsed => ScalarValue::try_from_string(parsed.to_string(), datatype),
                 })
                 .collect::<Result<Vec<_>>>()?;
 
@@ -431,6 +557,7 @@ where
 mod tests {
     use std::ops::Not;
 
+    use chrono::TimeZone;
     use futures::StreamExt;
 
     use crate::logical_expr::{case, col, lit};
@@ -589,6 +716,162 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_pruned_partition_list_typed() {
+        let (store, state) = make_test_store_and_state(&[
+            ("tablepath/year=2021/day=2021-01-01/file.parquet", 100),
+            ("tablepath/year=2021/day=2021-01-02/file.parquet", 100),
+            ("tablepath/year=2022/day=2022-01-01/file.parquet", 100),
+        ]);
+        // only the partition columns of the filter can be resolved
+        let filter = col("day")
+            .lt(lit(ScalarValue::Date32(Some(18629))))
+            .and(col("other").eq(lit(1)))
+            .or(col("year").gt(lit(2021_i64)));
+        let pruned = pruned_partition_list(
+            &state,
+            store.as_ref(),
+            &ListingTableUrl::parse("file:///tablepath/").unwrap(),
+            &[filter],
+            ".parquet",
+            &[
+                (String::from("year"), DataType::Int64),
+                (String::from("day"), DataType::Date32),
+            ],
+        )
+        .await
+        .expect("partition pruning failed")
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+        let mut pruned: Vec<_> = pruned
+            .into_iter()
+            .map(|f| (f.object_meta.location.to_string(), f.partition_values))
+            .collect();
+        pruned.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            pruned,
+            vec![
+                (
+                    "tablepath/year=2021/day=2021-01-01/file.parquet".to_string(),
+                    vec![
+                        ScalarValue::Int64(Some(2021)),
+                        ScalarValue::Date32(Some(18628))
+                    ]
+                ),
+                (
+                    "tablepath/year=2022/day=2022-01-01/file.parquet".to_string(),
+                    vec![
+                        ScalarValue::Int64(Some(2022)),
+                        ScalarValue::Date32(Some(18993))
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pruned_partition_list_null() {
+        let (store, state) = make_test_store_and_state(&[
+            ("tablepath/year=2021/file.parquet", 100),
+            (
+                "tablepath/year=__HIVE_DEFAULT_PARTITION__/file.parquet",
+                100,
+            ),
+        ]);
+        let pruned = pruned_partition_list(
+            &state,
+            store.as_ref(),
+            &ListingTableUrl::parse("file:///tablepath/").unwrap(),
+            &[col("year").is_null()],
+            ".parquet",
+            &[(String::from("year"), DataType::Int64)],
+        )
+        .await
+        .expect("partition pruning failed")
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(
+            pruned[0].object_meta.location.as_ref(),
+            "tablepath/year=__HIVE_DEFAULT_PARTITION__/file.parquet"
+        );
+        assert_eq!(pruned[0].partition_values, vec![ScalarValue::Int64(None)]);
+    }
+
+    #[test]
+    fn test_infer_partition_cols() {
+        let table_path = ListingTableUrl::parse("file:///bucket/mytable").unwrap();
+        let files = |paths: &[&str]| -> Vec<ObjectMeta> {
+            paths
+                .iter()
+                .map(|path| ObjectMeta {
+                    location: Path::from(*path),
+                    last_modified: chrono::Utc.timestamp_nanos(0),
+                    size: 100,
+                    e_tag: None,
+                    version: None,
+                })
+                .collect()
+        };
+
+        let inferred = infer_partition_cols(
+            &table_path,
+            &files(&[
+                "bucket/mytable/year=2021/day=2021-01-01/kind=a/file.csv",
+                "bucket/mytable/year=2022/day=2022-12-31/kind=1/file.csv",
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            inferred,
+            vec![
+                (String::from("year"), DataType::Int64),
+                (String::from("day"), DataType::Date32),
+                (String::from("kind"), DataType::Utf8),
+            ]
+        );
+
+        // values that would not be written back the same stay strings, and
+        // null partitions are ignored
+        let inferred = infer_partition_cols(
+            &table_path,
+            &files(&[
+                "bucket/mytable/id=007/day=2021-1-1/year=2021/file.csv",
+                "bucket/mytable/id=8/day=2021-01-02/year=__HIVE_DEFAULT_PARTITION__/file.csv",
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            inferred,
+            vec![
+                (String::from("id"), DataType::Utf8),
+                (String::from("day"), DataType::Utf8),
+                (String::from("year"), DataType::Int64),
+            ]
+        );
+
+        let inferred =
+            infer_partition_cols(&table_path, &files(&["bucket/mytable/file.csv"]))
+                .unwrap();
+        assert!(inferred.is_empty());
+
+        let err = infer_partition_cols(
+            &table_path,
+            &files(&[
+                "bucket/mytable/year=2021/file.csv",
+                "bucket/mytable/year=2021/day=2021-01-01/file.csv",
+            ]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(
+            "Found mixed partition columns [\"year\"] and [\"year\", \"day\"]"
+        ));
+    }
+
     #[test]
     fn test_parse_partitions_for_path() {
         assert_eq!(
@@ -692,4 +975,31 @@ mod tests {
         // this helper function
         assert!(expr_applicable_for_cols(&[], &lit(true)));
     }
+
+    #[test]
+    fn test_expr_for_cols() {
+        let cols = [String::from("c1")];
+        assert_eq!(
+            expr_for_cols(&cols, &col("c1").gt(lit(1))),
+            Some(col("c1").gt(lit(1)))
+        );
+        assert_eq!(expr_for_cols(&cols, &col("c2").gt(lit(1))), None);
+        assert_eq!(
+            expr_for_cols(&cols, &col("c1").gt(lit(1)).and(col("c2").eq(lit(2)))),
+            Some(col("c1").gt(lit(1)))
+        );
+        assert_eq!(
+            expr_for_cols(
+                &cols,
+                &(col("c1").eq(lit(1)).and(col("c2").eq(lit(2))))
+                    .or(col("c1").eq(lit(3)))
+            ),
+            Some(col("c1").eq(lit(1)).or(col("c1").eq(lit(3))))
+        );
+        // the second branch can't be resolved, so neither can the disjunction
+        assert_eq!(
+            expr_for_cols(&cols, &col("c1").eq(lit(1)).or(col("c2").eq(lit(2)))),
+            None
+        );
+    }
 }
@@ -30,6 +30,8 @@ use object_store::{path::Path, ObjectMeta};
 use std::pin::Pin;
 use std::sync::Arc;
 
+#[cfg(any(feature = "iceberg", feature = "delta"))]
+pub(crate) use self::helpers::split_files;
 pub use self::url::ListingTableUrl;
 pub use table::{ListingOptions, ListingTable, ListingTableConfig};
 
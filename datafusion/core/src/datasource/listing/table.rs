@@ -21,7 +21,9 @@ use std::collections::HashMap;
 use std::str::FromStr;
 use std::{any::Any, sync::Arc};
 
-use super::helpers::{expr_applicable_for_cols, pruned_partition_list, split_files};
+use super::helpers::{
+    expr_applicable_for_cols, infer_partition_cols, pruned_partition_list, split_files,
+};
 use super::PartitionedFile;
 
 #[cfg(feature = "parquet")]
@@ -35,7 +37,9 @@ use crate::datasource::{
         avro::AvroFormat,
         csv::CsvFormat,
         file_compression_type::{FileCompressionType, FileTypeExt},
+        fixed_width::FixedWidthFormat,
         json::JsonFormat,
+        xlsx::XlsxFormat,
         FileFormat,
     },
     listing::ListingTableUrl,
@@ -51,7 +55,8 @@ use crate::{
 use arrow::datatypes::{DataType, Field, SchemaBuilder, SchemaRef};
 use arrow_schema::Schema;
 use datafusion_common::{
-    internal_err, plan_err, project_schema, Constraints, FileType, SchemaExt, ToDFSchema,
+    internal_err, plan_err, project_schema, BucketSpec, Constraints, FileType, SchemaExt,
+    ToDFSchema,
 };
 use datafusion_execution::cache::cache_manager::FileStatisticsCache;
 use datafusion_execution::cache::cache_unit::DefaultFileStatisticsCache;
@@ -61,6 +66,7 @@ use datafusion_physical_expr::{
 
 use async_trait::async_trait;
 use futures::{future, stream, StreamExt, TryStreamExt};
+use log::debug;
 use object_store::ObjectStore;
 
 /// Configuration for creating a [`ListingTable`]
@@ -178,6 +184,12 @@ impl ListingTableConfig {
                 Arc::new(JsonFormat::default().with_options(table_options.json))
             }
             FileType::ARROW => Arc::new(ArrowFormat),
+            FileType::XLSX => {
+                Arc::new(XlsxFormat::default().with_options(table_options.xlsx))
+            }
+            FileType::FIXEDWIDTH => Arc::new(
+                FixedWidthFormat::default().with_options(table_options.fixed_width),
+            ),
         };
 
         let listing_options = ListingOptions::new(file_format)
@@ -211,9 +223,44 @@ impl ListingTableConfig {
         }
     }
 
-    /// Convenience wrapper for calling `infer_options` and `infer_schema`
+    /// Infer the partition columns of `self.options` from the Hive-style
+    /// directory layout of the first table path, see
+    /// [`ListingOptions::infer_partitions`]. Requires `self.options` to be set
+    /// prior to using.
+    pub async fn infer_partitions_from_path(self, state: &SessionState) -> Result<Self> {
+        match self.options {
+            Some(options) => {
+                let table_partition_cols = match self.table_paths.first() {
+                    Some(url) => options.infer_partitions(state, url).await?,
+                    None => vec![],
+                };
+
+                Ok(Self {
+                    table_paths: self.table_paths,
+                    file_schema: self.file_schema,
+                    options: Some(
+                        options.with_table_partition_cols(table_partition_cols),
+                    ),
+                })
+            }
+            None => internal_err!("No `ListingOptions` set for inferring partitions"),
+        }
+    }
+
+    /// Convenience wrapper for calling `infer_options` and `infer_schema`, as
+    /// well as `infer_partitions_from_path` if
+    /// `datafusion.execution.listing_table_infer_partitions` is enabled
     pub async fn infer(self, state: &SessionState) -> Result<Self> {
-        self.infer_options(state).await?.infer_schema(state).await
+        let config = self.infer_options(state).await?;
+        let config = match state
+            .config_options()
+            .execution
+            .listing_table_infer_partitions
+        {
+            true => config.infer_partitions_from_path(state).await?,
+            false => config,
+        };
+        config.infer_schema(state).await
     }
 }
 
@@ -250,6 +297,15 @@ pub struct ListingOptions {
     ///       multiple equivalent orderings, the outer `Vec` will have a
     ///       single element.
     pub file_sort_order: Vec<Vec<Expr>>,
+    /// Optional bucketing of the files, see [`BucketSpec`].
+    ///
+    /// Inserts cluster the rows of each partition into one file per bucket.
+    /// Scans read the files of bucket `i` as partition `i`, declaring hash
+    /// partitioning on the bucket columns so that joins and aggregates on
+    /// them can skip repartitioning. This requires the number of buckets to
+    /// be the session's target partitions, and all files to be named as
+    /// written by a bucketed insert.
+    pub bucket_by: Option<BucketSpec>,
 }
 
 impl ListingOptions {
@@ -267,6 +323,7 @@ impl ListingOptions {
             collect_stat: true,
             target_partitions: 1,
             file_sort_order: vec![],
+            bucket_by: None,
         }
     }
 
@@ -295,6 +352,9 @@ impl ListingOptions {
     /// columns added to the data that is read, based on the folder
     /// structure where the data resides.
     ///
+    /// The partition columns and their types may also be discovered with
+    /// [`Self::infer_partitions`].
+    ///
     /// For example, give the following files in your filesystem:
     ///
     /// ```text
@@ -417,6 +477,28 @@ impl ListingOptions {
         self
     }
 
+    /// Set bucketing on [`ListingOptions`] and returns self.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use datafusion::common::BucketSpec;
+    /// # use datafusion::datasource::{listing::ListingOptions, file_format::parquet::ParquetFormat};
+    ///
+    ///  // Cluster the files into 32 buckets by column "user_id"
+    ///  let bucket_by = BucketSpec::try_new(vec!["user_id".to_string()], 32).unwrap();
+    ///
+    /// let listing_options = ListingOptions::new(Arc::new(
+    ///     ParquetFormat::default()
+    ///   ))
+    ///   .with_bucket_by(bucket_by.clone());
+    ///
+    /// assert_eq!(listing_options.bucket_by, Some(bucket_by));
+    /// ```
+    pub fn with_bucket_by(mut self, bucket_by: BucketSpec) -> Self {
+        self.bucket_by = Some(bucket_by);
+        self
+    }
+
     /// Infer the schema of the files at the given path on the provided object store.
     /// The inferred schema does not include the partitioning columns.
     ///
@@ -438,6 +520,28 @@ impl ListingOptions {
 
         self.format.infer_schema(state, &store, &files).await
     }
+
+    /// Infer the Hive-style partition columns of the files at the given path,
+    /// along with their types. For example, files such as
+    /// `/table/year=2021/day=2021-01-01/data.parquet` are partitioned by the
+    /// `Int64` column `year` and the `Date32` column `day`.
+    ///
+    /// Returns an error if the files are not all partitioned by the same columns.
+    pub async fn infer_partitions(
+        &self,
+        state: &SessionState,
+        table_path: &ListingTableUrl,
+    ) -> Result<Vec<(String, DataType)>> {
+        let store = state.runtime_env().object_store(table_path)?;
+
+        let files: Vec<_> = table_path
+            .list_all_files(state, store.as_ref(), &self.file_extension)
+            .await?
+            .try_collect()
+            .await?;
+
+        infer_partition_cols(table_path, &files)
+    }
 }
 
 /// Reads data from one or more files via an
@@ -632,7 +736,7 @@ impl TableProvider for ListingTable {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let (partitioned_file_lists, statistics) =
+        let (mut partitioned_file_lists, statistics) =
             self.list_files_for_scan(state, filters, limit).await?;
 
         // if no files need to be read, return an `EmptyExec`
@@ -665,6 +769,15 @@ impl TableProvider for ListingTable {
         } else {
             return Ok(Arc::new(EmptyExec::new(Arc::new(Schema::empty()))));
         };
+
+        let bucket_by = match self.bucket_file_groups(state, &partitioned_file_lists) {
+            Some(bucket_file_groups) => {
+                partitioned_file_lists = bucket_file_groups;
+                self.options.bucket_by.clone()
+            }
+            None => None,
+        };
+
         // create the execution plan
         self.options
             .format
@@ -679,6 +792,7 @@ impl TableProvider for ListingTable {
                     limit,
                     output_ordering: self.try_create_output_ordering()?,
                     table_partition_cols,
+                    bucket_by,
                 },
                 filters.as_ref(),
             )
@@ -736,6 +850,16 @@ impl TableProvider for ListingTable {
             );
         }
 
+        if let Some(bucket_by) = &self.options.bucket_by {
+            for column in &bucket_by.columns {
+                if self.file_schema.field_with_name(column).is_err() {
+                    return plan_err!(
+                        "Bucket column {column} must be a column of the files of the table"
+                    );
+                }
+            }
+        }
+
         // Get the object store for the table path.
         let store = state.runtime_env().object_store(table_path)?;
 
@@ -759,6 +883,7 @@ impl TableProvider for ListingTable {
             output_schema: self.schema(),
             table_partition_cols: self.options.table_partition_cols.clone(),
             overwrite,
+            bucket_by: self.options.bucket_by.clone(),
         };
 
         let unsorted: Vec<Vec<Expr>> = vec![];
@@ -794,6 +919,46 @@ impl TableProvider for ListingTable {
 }
 
 impl ListingTable {
+    /// Groups the files of a bucketed table into one group per bucket.
+    ///
+    /// Returns `None` if the table is not bucketed, its number of buckets is
+    /// not the target partitions of the session, or some file is not named
+    /// as a bucket file.
+    fn bucket_file_groups(
+        &self,
+        state: &SessionState,
+        file_groups: &[Vec<PartitionedFile>],
+    ) -> Option<Vec<Vec<PartitionedFile>>> {
+        let bucket_by = self.options.bucket_by.as_ref()?;
+        if bucket_by.num_buckets != state.config().target_partitions() {
+            debug!(
+                "Not reading buckets of {:?}: {} buckets but {} target partitions",
+                self.table_paths,
+                bucket_by.num_buckets,
+                state.config().target_partitions()
+            );
+            return None;
+        }
+
+        let mut bucket_file_groups = vec![vec![]; bucket_by.num_buckets];
+        for file in file_groups.iter().flatten() {
+            let bucket = file
+                .object_meta
+                .location
+                .filename()
+                .and_then(|name| bucket_by.bucket_of_file(name));
+            let Some(bucket) = bucket else {
+                debug!(
+                    "Not reading buckets of {:?}: {} is not a bucket file",
+                    self.table_paths, file.object_meta.location
+                );
+                return None;
+            };
+            bucket_file_groups[bucket].push(file.clone());
+        }
+        Some(bucket_file_groups)
+    }
+
     /// Get the list of files for a scan as well as the file level statistics.
     /// The list is grouped to let the execution plan know how the files should
     /// be distributed to different threads / executors.
@@ -898,7 +1063,7 @@ mod tests {
     use crate::datasource::file_format::parquet::ParquetFormat;
     use crate::datasource::{provider_as_source, MemTable};
     use crate::execution::options::ArrowReadOptions;
-    use crate::physical_plan::collect;
+    use crate::physical_plan::{collect, displayable};
     use crate::prelude::*;
     use crate::{
         assert_batches_eq,
@@ -911,7 +1076,7 @@ mod tests {
     use arrow::record_batch::RecordBatch;
     use arrow_schema::SortOptions;
     use datafusion_common::stats::Precision;
-    use datafusion_common::{assert_contains, GetExt, ScalarValue};
+    use datafusion_common::{assert_contains, not_impl_err, GetExt, ScalarValue};
     use datafusion_expr::{BinaryExpr, LogicalPlanBuilder, Operator};
     use datafusion_physical_expr::PhysicalSortExpr;
     use datafusion_physical_plan::ExecutionPlanProperties;
@@ -1624,6 +1789,91 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_insert_into_bucketed_tables_and_join() -> Result<()> {
+        let config = SessionConfig::new()
+            .with_target_partitions(4)
+            .set_usize(
+                "datafusion.optimizer.hash_join_single_partition_threshold",
+                0,
+            )
+            .set_usize(
+                "datafusion.optimizer.hash_join_single_partition_threshold_rows",
+                0,
+            );
+        let ctx = SessionContext::new_with_config(config);
+
+        let tmp_dir = TempDir::new()?;
+        for (name, value_col) in [("l", "v"), ("r", "w")] {
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, true),
+                Field::new(value_col, DataType::Utf8, true),
+            ]));
+            let options = ListingOptions::new(Arc::new(ParquetFormat::default()))
+                .with_file_extension(".parquet")
+                .with_file_sort_order(vec![vec![col("id").sort(true, false)]])
+                .with_bucket_by(BucketSpec::try_new(vec!["id".to_string()], 4)?);
+            let path = tmp_dir.path().join(name);
+            let table_path = ListingTableUrl::parse(format!("{}/", path.display()))?;
+            let config = ListingTableConfig::new(table_path)
+                .with_listing_options(options)
+                .with_schema(schema);
+            ctx.register_table(name, Arc::new(ListingTable::try_new(config)?))?;
+
+            ctx.sql(&format!(
+                "INSERT INTO {name} VALUES (1, 'a'), (2, 'b'), (3, 'c'), (4, 'd'), (5, 'e'), (6, 'f'), (2, 'g')"
+            ))
+            .await?
+            .collect()
+            .await?;
+
+            // one file per non empty bucket
+            for entry in path.read_dir()? {
+                let file_name = entry?.file_name();
+                let file_name = file_name.to_str().unwrap();
+                assert!(file_name.starts_with("bucket_"), "{file_name}");
+            }
+        }
+
+        let sql = "SELECT l.id, v, w FROM l JOIN r ON l.id = r.id ORDER BY l.id, v, w";
+        let plan = ctx.sql(sql).await?.create_physical_plan().await?;
+        let plan = displayable(plan.as_ref()).indent(true).to_string();
+        assert_contains!(&plan, "buckets=4 BY (id)");
+        assert_contains!(&plan, "HashJoinExec: mode=Partitioned");
+        assert!(!plan.contains("RepartitionExec"), "{plan}");
+
+        let batches = ctx.sql(sql).await?.collect().await?;
+        let expected = [
+            "+----+---+---+",
+            "| id | v | w |",
+            "+----+---+---+",
+            "| 1  | a | a |",
+            "| 2  | b | b |",
+            "| 2  | b | g |",
+            "| 2  | g | b |",
+            "| 2  | g | g |",
+            "| 3  | c | c |",
+            "| 4  | d | d |",
+            "| 5  | e | e |",
+            "| 6  | f | f |",
+            "+----+---+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        // the buckets are only read as hash partitions for as many target partitions
+        let other_ctx = SessionContext::new_with_config(
+            SessionConfig::new().with_target_partitions(2),
+        );
+        let table = ctx.table_provider("l").await?;
+        let scan = table.scan(&other_ctx.state(), None, &[], None).await?;
+        assert!(matches!(
+            scan.output_partitioning(),
+            datafusion_physical_plan::Partitioning::UnknownPartitioning(_)
+        ));
+
+        Ok(())
+    }
+
     async fn helper_test_append_new_files_to_table(
         file_type: FileType,
         file_compression_type: FileCompressionType,
@@ -1712,6 +1962,9 @@ mod tests {
                     )
                     .await?;
             }
+            FileType::XLSX | FileType::FIXEDWIDTH => {
+                return not_impl_err!("Writes are not supported for {file_type}");
+            }
         }
 
         // Create and register the source table with the provided schema and inserted data
@@ -24,7 +24,8 @@ use std::sync::Arc;
 #[cfg(feature = "parquet")]
 use crate::datasource::file_format::parquet::ParquetFormat;
 use crate::datasource::file_format::{
-    arrow::ArrowFormat, avro::AvroFormat, csv::CsvFormat, json::JsonFormat, FileFormat,
+    arrow::ArrowFormat, avro::AvroFormat, csv::CsvFormat, fixed_width::FixedWidthFormat,
+    json::JsonFormat, xlsx::XlsxFormat, FileFormat,
 };
 use crate::datasource::listing::{
     ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
@@ -83,24 +84,54 @@ impl TableProviderFactory for ListingTableFactory {
                 Arc::new(JsonFormat::default().with_options(json_options))
             }
             FileType::ARROW => Arc::new(ArrowFormat),
+            FileType::XLSX => {
+                Arc::new(XlsxFormat::default().with_options(table_options.xlsx))
+            }
+            FileType::FIXEDWIDTH => {
+                let mut fixed_width_options = table_options.fixed_width;
+                fixed_width_options.has_header |= cmd.has_header;
+                fixed_width_options.compression = cmd.file_compression_type;
+                Arc::new(FixedWidthFormat::default().with_options(fixed_width_options))
+            }
         };
 
+        let table_path = ListingTableUrl::parse(&cmd.location)?;
+
+        let options = ListingOptions::new(file_format)
+            .with_collect_stat(state.config().collect_statistics())
+            .with_file_extension(file_extension)
+            .with_target_partitions(state.config().target_partitions())
+            .with_file_sort_order(cmd.order_exprs.clone());
+
         let (provided_schema, table_partition_cols) = if cmd.schema.fields().is_empty() {
-            (
-                None,
+            let inferred = if state
+                .config_options()
+                .execution
+                .listing_table_infer_partitions
+            {
+                options.infer_partitions(state, &table_path).await?
+            } else {
+                vec![]
+            };
+            let table_partition_cols = if cmd.table_partition_cols.is_empty() {
+                inferred
+            } else {
                 cmd.table_partition_cols
                     .iter()
                     .map(|x| {
-                        (
-                            x.clone(),
-                            DataType::Dictionary(
+                        let data_type = inferred
+                            .iter()
+                            .find(|(name, _)| name == x)
+                            .map(|(_, data_type)| data_type.clone())
+                            .unwrap_or(DataType::Dictionary(
                                 Box::new(DataType::UInt16),
                                 Box::new(DataType::Utf8),
-                            ),
-                        )
+                            ));
+                        (x.clone(), data_type)
                     })
-                    .collect::<Vec<_>>(),
-            )
+                    .collect::<Vec<_>>()
+            };
+            (None, table_partition_cols)
         } else {
             let schema: SchemaRef = Arc::new(cmd.schema.as_ref().to_owned().into());
             let table_partition_cols = cmd
@@ -128,14 +159,7 @@ impl TableProviderFactory for ListingTableFactory {
             (Some(schema), table_partition_cols)
         };
 
-        let table_path = ListingTableUrl::parse(&cmd.location)?;
-
-        let options = ListingOptions::new(file_format)
-            .with_collect_stat(state.config().collect_statistics())
-            .with_file_extension(file_extension)
-            .with_target_partitions(state.config().target_partitions())
-            .with_table_partition_cols(table_partition_cols)
-            .with_file_sort_order(cmd.order_exprs.clone());
+        let options = options.with_table_partition_cols(table_partition_cols);
 
         let resolved_schema = match provided_schema {
             None => options.infer_schema(state, &table_path).await?,
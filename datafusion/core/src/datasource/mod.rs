@@ -22,18 +22,24 @@
 pub mod avro_to_arrow;
 pub mod cte_worktable;
 pub mod default_table_source;
+#[cfg(feature = "delta")]
+pub mod delta;
 pub mod empty;
 pub mod file_format;
 pub mod function;
+#[cfg(feature = "iceberg")]
+pub mod iceberg;
 pub mod listing;
 pub mod listing_table_factory;
 pub mod memory;
 pub mod physical_plan;
 pub mod provider;
+pub mod schema_adapter;
 mod statistics;
 pub mod stream;
 pub mod streaming;
 pub mod view;
+pub mod xlsx_to_arrow;
 
 // backwards compatibility
 pub use datafusion_execution::object_store;
@@ -25,13 +25,16 @@ use crate::datasource::listing::PartitionedFile;
 use crate::datasource::physical_plan::{
     FileMeta, FileOpenFuture, FileOpener, FileScanConfig,
 };
+use crate::datasource::schema_adapter::{
+    schema_adapter_factory, SchemaAdapter, SchemaAdapterFactory,
+};
 use crate::error::Result;
 use crate::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
 use crate::physical_plan::{
     DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
 };
 
-use arrow_ipc::reader::FileDecoder;
+use arrow_ipc::reader::{FileDecoder, FileReader};
 use arrow_schema::SchemaRef;
 use datafusion_common::config::ConfigOptions;
 use datafusion_common::Statistics;
@@ -53,6 +56,8 @@ pub struct ArrowExec {
     projected_output_ordering: Vec<LexOrdering>,
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
+    /// Optional user defined schema adapter factory
+    schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
     cache: PlanProperties,
 }
 
@@ -72,6 +77,7 @@ impl ArrowExec {
             projected_statistics,
             projected_output_ordering,
             metrics: ExecutionPlanMetricsSet::new(),
+            schema_adapter_factory: None,
             cache,
         }
     }
@@ -80,8 +86,20 @@ impl ArrowExec {
         &self.base_config
     }
 
+    /// Optional user defined schema adapter factory, see
+    /// [`ParquetExec::with_schema_adapter_factory`]
+    ///
+    /// [`ParquetExec::with_schema_adapter_factory`]: crate::datasource::physical_plan::ParquetExec::with_schema_adapter_factory
+    pub fn with_schema_adapter_factory(
+        mut self,
+        schema_adapter_factory: Arc<dyn SchemaAdapterFactory>,
+    ) -> Self {
+        self.schema_adapter_factory = Some(schema_adapter_factory);
+        self
+    }
+
     fn output_partitioning_helper(file_scan_config: &FileScanConfig) -> Partitioning {
-        Partitioning::UnknownPartitioning(file_scan_config.file_groups.len())
+        file_scan_config.output_partitioning()
     }
 
     /// This function creates the cache object that stores the plan properties such as schema, equivalence properties, ordering, partitioning, etc.
@@ -148,6 +166,10 @@ impl ExecutionPlan for ArrowExec {
         target_partitions: usize,
         config: &ConfigOptions,
     ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        // The file groups of a bucketed scan are its buckets
+        if self.base_config.bucket_by.is_some() {
+            return Ok(None);
+        }
         let repartition_file_min_size = config.optimizer.repartition_file_min_size;
         let repartitioned_file_groups_option = FileGroupPartitioner::new()
             .with_target_partitions(target_partitions)
@@ -175,12 +197,19 @@ impl ExecutionPlan for ArrowExec {
             .runtime_env()
             .object_store(&self.base_config.object_store_url)?;
 
+        let schema_adapter =
+            schema_adapter_factory(self.schema_adapter_factory.as_ref(), &context)?
+                .create(
+                    self.base_config.projected_file_schema(),
+                    self.base_config.file_schema.clone(),
+                );
         let opener = ArrowOpener {
             object_store,
-            projection: self.base_config.file_column_projection_indices(),
+            schema_adapter: Arc::from(schema_adapter),
         };
         let stream =
-            FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+            FileStream::new(&self.base_config, partition, opener, &self.metrics)?
+                .with_query_progress(context.query_progress());
         Ok(Box::pin(stream))
     }
 
@@ -195,13 +224,13 @@ impl ExecutionPlan for ArrowExec {
 
 pub struct ArrowOpener {
     pub object_store: Arc<dyn ObjectStore>,
-    pub projection: Option<Vec<usize>>,
+    pub schema_adapter: Arc<dyn SchemaAdapter>,
 }
 
 impl FileOpener for ArrowOpener {
     fn open(&self, file_meta: FileMeta) -> Result<FileOpenFuture> {
         let object_store = self.object_store.clone();
-        let projection = self.projection.clone();
+        let schema_adapter = self.schema_adapter.clone();
         Ok(Box::pin(async move {
             let range = file_meta.range.clone();
             match range {
@@ -209,18 +238,40 @@ impl FileOpener for ArrowOpener {
                     let r = object_store.get(file_meta.location()).await?;
                     match r.payload {
                         GetResultPayload::File(file, _) => {
-                            let arrow_reader = arrow::ipc::reader::FileReader::try_new(
-                                file, projection,
-                            )?;
-                            Ok(futures::stream::iter(arrow_reader).boxed())
+                            // read the footer once to map the file schema
+                            let file_schema =
+                                FileReader::try_new(file.try_clone()?, None)?.schema();
+                            let (mapper, projection) =
+                                schema_adapter.map_schema(&file_schema)?;
+                            let arrow_reader =
+                                FileReader::try_new(file, Some(projection))?;
+                            Ok(futures::stream::iter(arrow_reader)
+                                .map(move |batch| {
+                                    batch.and_then(|batch| {
+                                        mapper.map_batch(batch).map_err(Into::into)
+                                    })
+                                })
+                                .boxed())
                         }
                         GetResultPayload::Stream(_) => {
                             let bytes = r.bytes().await?;
+                            let file_schema = FileReader::try_new(
+                                std::io::Cursor::new(bytes.clone()),
+                                None,
+                            )?
+                            .schema();
+                            let (mapper, projection) =
+                                schema_adapter.map_schema(&file_schema)?;
                             let cursor = std::io::Cursor::new(bytes);
-                            let arrow_reader = arrow::ipc::reader::FileReader::try_new(
-                                cursor, projection,
-                            )?;
-                            Ok(futures::stream::iter(arrow_reader).boxed())
+                            let arrow_reader =
+                                FileReader::try_new(cursor, Some(projection))?;
+                            Ok(futures::stream::iter(arrow_reader)
+                                .map(move |batch| {
+                                    batch.and_then(|batch| {
+                                        mapper.map_batch(batch).map_err(Into::into)
+                                    })
+                                })
+                                .boxed())
                         }
                     }
                 }
@@ -258,10 +309,9 @@ impl FileOpener for ArrowOpener {
                     // build decoder according to footer & projection
                     let schema =
                         arrow_ipc::convert::fb_to_schema(footer.schema().unwrap());
-                    let mut decoder = FileDecoder::new(schema.into(), footer.version());
-                    if let Some(projection) = projection {
-                        decoder = decoder.with_projection(projection);
-                    }
+                    let (mapper, projection) = schema_adapter.map_schema(&schema)?;
+                    let mut decoder = FileDecoder::new(schema.into(), footer.version())
+                        .with_projection(projection);
                     let dict_ranges = footer
                         .dictionaries()
                         .iter()
@@ -315,7 +365,11 @@ impl FileOpener for ArrowOpener {
                             .zip(recordbatch_results)
                             .filter_map(move |(block, data)| {
                                 match decoder.read_record_batch(&block, &data.into()) {
-                                    Ok(Some(record_batch)) => Some(Ok(record_batch)),
+                                    Ok(Some(record_batch)) => Some(
+                                        mapper
+                                            .map_batch(record_batch)
+                                            .map_err(Into::into),
+                                    ),
                                     Ok(None) => None,
                                     Err(err) => Some(Err(err)),
                                 }
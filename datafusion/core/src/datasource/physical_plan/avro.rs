@@ -21,11 +21,12 @@ use std::any::Any;
 use std::sync::Arc;
 
 use super::FileScanConfig;
+use crate::datasource::schema_adapter::SchemaAdapterFactory;
 use crate::error::Result;
 use crate::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
 use crate::physical_plan::{
-    DisplayAs, DisplayFormatType, ExecutionMode, ExecutionPlan, Partitioning,
-    PlanProperties, SendableRecordBatchStream, Statistics,
+    DisplayAs, DisplayFormatType, ExecutionMode, ExecutionPlan, PlanProperties,
+    SendableRecordBatchStream, Statistics,
 };
 
 use arrow::datatypes::SchemaRef;
@@ -42,6 +43,8 @@ pub struct AvroExec {
     projected_output_ordering: Vec<LexOrdering>,
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
+    /// Optional user defined schema adapter factory
+    schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
     cache: PlanProperties,
 }
 
@@ -61,6 +64,7 @@ impl AvroExec {
             projected_statistics,
             projected_output_ordering,
             metrics: ExecutionPlanMetricsSet::new(),
+            schema_adapter_factory: None,
             cache,
         }
     }
@@ -69,6 +73,18 @@ impl AvroExec {
         &self.base_config
     }
 
+    /// Optional user defined schema adapter factory, see
+    /// [`ParquetExec::with_schema_adapter_factory`]
+    ///
+    /// [`ParquetExec::with_schema_adapter_factory`]: crate::datasource::physical_plan::ParquetExec::with_schema_adapter_factory
+    pub fn with_schema_adapter_factory(
+        mut self,
+        schema_adapter_factory: Arc<dyn SchemaAdapterFactory>,
+    ) -> Self {
+        self.schema_adapter_factory = Some(schema_adapter_factory);
+        self
+    }
+
     /// This function creates the cache object that stores the plan properties such as schema, equivalence properties, ordering, partitioning, etc.
     fn compute_properties(
         schema: SchemaRef,
@@ -77,12 +93,11 @@ impl AvroExec {
     ) -> PlanProperties {
         // Equivalence Properties
         let eq_properties = EquivalenceProperties::new_with_orderings(schema, orderings);
-        let n_partitions = file_scan_config.file_groups.len();
 
         PlanProperties::new(
             eq_properties,
-            Partitioning::UnknownPartitioning(n_partitions), // Output Partitioning
-            ExecutionMode::Bounded,                          // Execution Mode
+            file_scan_config.output_partitioning(), // Output Partitioning
+            ExecutionMode::Bounded,                 // Execution Mode
         )
     }
 }
@@ -136,20 +151,27 @@ impl ExecutionPlan for AvroExec {
         context: Arc<TaskContext>,
     ) -> Result<SendableRecordBatchStream> {
         use super::file_stream::FileStream;
+        use crate::datasource::schema_adapter::schema_adapter_factory;
         let object_store = context
             .runtime_env()
             .object_store(&self.base_config.object_store_url)?;
 
+        let schema_adapter =
+            schema_adapter_factory(self.schema_adapter_factory.as_ref(), &context)?
+                .create(
+                    self.base_config.projected_file_schema(),
+                    Arc::clone(&self.base_config.file_schema),
+                );
         let config = Arc::new(private::AvroConfig {
-            schema: Arc::clone(&self.base_config.file_schema),
             batch_size: context.session_config().batch_size(),
-            projection: self.base_config.projected_file_column_names(),
+            schema_adapter: Arc::from(schema_adapter),
             object_store,
         });
         let opener = private::AvroOpener { config };
 
         let stream =
-            FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+            FileStream::new(&self.base_config, partition, opener, &self.metrics)?
+                .with_query_progress(context.query_progress());
         Ok(Box::pin(stream))
     }
 
@@ -165,29 +187,45 @@ impl ExecutionPlan for AvroExec {
 #[cfg(feature = "avro")]
 mod private {
     use super::*;
-    use crate::datasource::avro_to_arrow::Reader as AvroReader;
+    use crate::datasource::avro_to_arrow::{
+        read_avro_schema_from_reader, Reader as AvroReader,
+    };
     use crate::datasource::physical_plan::file_stream::{FileOpenFuture, FileOpener};
     use crate::datasource::physical_plan::FileMeta;
+    use crate::datasource::schema_adapter::{SchemaAdapter, SchemaMapper};
 
+    use arrow::datatypes::Schema;
     use bytes::Buf;
     use futures::StreamExt;
     use object_store::{GetResultPayload, ObjectStore};
+    use std::io::{Seek, SeekFrom};
 
     pub struct AvroConfig {
-        pub schema: SchemaRef,
         pub batch_size: usize,
-        pub projection: Option<Vec<String>>,
+        pub schema_adapter: Arc<dyn SchemaAdapter>,
         pub object_store: Arc<dyn ObjectStore>,
     }
 
     impl AvroConfig {
-        fn open<R: std::io::Read>(&self, reader: R) -> Result<AvroReader<'static, R>> {
-            AvroReader::try_new(
+        /// Opens a file with the given schema, returning the reader of the
+        /// columns mapped to the table and the mapper of its batches
+        fn open<R: std::io::Read>(
+            &self,
+            reader: R,
+            file_schema: Schema,
+        ) -> Result<(AvroReader<'static, R>, Arc<dyn SchemaMapper>)> {
+            let (mapper, projection) = self.schema_adapter.map_schema(&file_schema)?;
+            let projection = projection
+                .into_iter()
+                .map(|idx| file_schema.field(idx).name().clone())
+                .collect();
+            let reader = AvroReader::try_new(
                 reader,
-                self.schema.clone(),
+                Arc::new(file_schema),
                 self.batch_size,
-                self.projection.clone(),
-            )
+                Some(projection),
+            )?;
+            Ok((reader, mapper))
         }
     }
 
@@ -200,17 +238,28 @@ mod private {
             let config = self.config.clone();
             Ok(Box::pin(async move {
                 let r = config.object_store.get(file_meta.location()).await?;
-                match r.payload {
-                    GetResultPayload::File(file, _) => {
-                        let reader = config.open(file)?;
-                        Ok(futures::stream::iter(reader).boxed())
+                let (reader, mapper) = match r.payload {
+                    GetResultPayload::File(mut file, _) => {
+                        let file_schema = read_avro_schema_from_reader(&mut file)?;
+                        file.seek(SeekFrom::Start(0))?;
+                        let (reader, mapper) = config.open(file, file_schema)?;
+                        (futures::stream::iter(reader).boxed(), mapper)
                     }
                     GetResultPayload::Stream(_) => {
                         let bytes = r.bytes().await?;
-                        let reader = config.open(bytes.reader())?;
-                        Ok(futures::stream::iter(reader).boxed())
+                        let file_schema =
+                            read_avro_schema_from_reader(&mut bytes.clone().reader())?;
+                        let (reader, mapper) =
+                            config.open(bytes.reader(), file_schema)?;
+                        (futures::stream::iter(reader).boxed(), mapper)
                     }
-                }
+                };
+                Ok(reader
+                    .map(move |batch| {
+                        batch
+                            .and_then(|batch| mapper.map_batch(batch).map_err(Into::into))
+                    })
+                    .boxed())
             }))
         }
     }
@@ -278,6 +327,7 @@ mod tests {
             limit: None,
             table_partition_cols: vec![],
             output_ordering: vec![],
+            bucket_by: None,
         });
         assert_eq!(
             avro_exec
@@ -355,6 +405,7 @@ mod tests {
             limit: None,
             table_partition_cols: vec![],
             output_ordering: vec![],
+            bucket_by: None,
         });
         assert_eq!(
             avro_exec
@@ -431,6 +482,7 @@ mod tests {
             limit: None,
             table_partition_cols: vec![Field::new("date", DataType::Utf8, false)],
             output_ordering: vec![],
+            bucket_by: None,
         });
         assert_eq!(
             avro_exec
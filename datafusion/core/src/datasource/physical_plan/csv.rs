@@ -18,7 +18,8 @@
 //! Execution plan for reading CSV files
 
 use std::any::Any;
-use std::io::{Read, Seek, SeekFrom};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
 use std::sync::Arc;
 use std::task::Poll;
 
@@ -29,6 +30,9 @@ use crate::datasource::physical_plan::file_stream::{
     FileOpenFuture, FileOpener, FileStream,
 };
 use crate::datasource::physical_plan::FileMeta;
+use crate::datasource::schema_adapter::{
+    schema_adapter_factory, SchemaAdapter, SchemaAdapterFactory, SchemaMapper,
+};
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
 use crate::physical_plan::{
@@ -37,16 +41,20 @@ use crate::physical_plan::{
 };
 
 use arrow::csv;
-use arrow::datatypes::SchemaRef;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use datafusion_common::config::ConfigOptions;
+use datafusion_common::plan_err;
 use datafusion_execution::TaskContext;
 use datafusion_physical_expr::{EquivalenceProperties, LexOrdering};
 
-use bytes::{Buf, Bytes};
+use bytes::{Buf, Bytes, BytesMut};
 use futures::{ready, StreamExt, TryStreamExt};
 use object_store::buffered::BufWriter;
+use object_store::path::Path;
 use object_store::{GetOptions, GetResultPayload, ObjectStore};
+use parking_lot::Mutex;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::OnceCell;
 use tokio::task::JoinSet;
 
 /// Execution plan for scanning a CSV file
@@ -62,6 +70,10 @@ pub struct CsvExec {
     metrics: ExecutionPlanMetricsSet,
     /// Compression type of the file associated with CsvExec
     pub file_compression_type: FileCompressionType,
+    /// Optional user defined schema adapter factory
+    schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
+    /// The headers of the files read, to match columns by name
+    headers: Arc<CsvHeaders>,
     cache: PlanProperties,
 }
 
@@ -91,10 +103,26 @@ impl CsvExec {
             escape,
             metrics: ExecutionPlanMetricsSet::new(),
             file_compression_type,
+            schema_adapter_factory: None,
+            headers: Arc::default(),
             cache,
         }
     }
 
+    /// Optional user defined schema adapter factory, see
+    /// [`ParquetExec::with_schema_adapter_factory`]. The columns of files are
+    /// read by position, or by the names in their header if the
+    /// `datafusion.execution.csv_columns_by_name` option is set.
+    ///
+    /// [`ParquetExec::with_schema_adapter_factory`]: crate::datasource::physical_plan::ParquetExec::with_schema_adapter_factory
+    pub fn with_schema_adapter_factory(
+        mut self,
+        schema_adapter_factory: Arc<dyn SchemaAdapterFactory>,
+    ) -> Self {
+        self.schema_adapter_factory = Some(schema_adapter_factory);
+        self
+    }
+
     /// Ref to the base configs
     pub fn base_config(&self) -> &FileScanConfig {
         &self.base_config
@@ -119,7 +147,7 @@ impl CsvExec {
     }
 
     fn output_partitioning_helper(file_scan_config: &FileScanConfig) -> Partitioning {
-        Partitioning::UnknownPartitioning(file_scan_config.file_groups.len())
+        file_scan_config.output_partitioning()
     }
 
     /// This function creates the cache object that stores the plan properties such as schema, equivalence properties, ordering, partitioning, etc.
@@ -190,6 +218,10 @@ impl ExecutionPlan for CsvExec {
         target_partitions: usize,
         config: &ConfigOptions,
     ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        // The file groups of a bucketed scan are its buckets
+        if self.base_config.bucket_by.is_some() {
+            return Ok(None);
+        }
         let repartition_file_min_size = config.optimizer.repartition_file_min_size;
         // Parallel execution on compressed CSV file is not supported yet.
         if self.file_compression_type.is_compressed() {
@@ -232,12 +264,25 @@ impl ExecutionPlan for CsvExec {
             object_store,
         });
 
-        let opener = CsvOpener {
-            config,
-            file_compression_type: self.file_compression_type.to_owned(),
-        };
+        let schema_adapter =
+            schema_adapter_factory(self.schema_adapter_factory.as_ref(), &context)?
+                .create(
+                    self.base_config.projected_file_schema(),
+                    Arc::clone(&self.base_config.file_schema),
+                );
+        let mut opener = CsvOpener::new(config, self.file_compression_type.to_owned())
+            .with_schema_adapter(Arc::from(schema_adapter));
+        if context
+            .session_config()
+            .options()
+            .execution
+            .csv_columns_by_name
+        {
+            opener = opener.with_headers(Arc::clone(&self.headers));
+        }
         let stream =
-            FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+            FileStream::new(&self.base_config, partition, opener, &self.metrics)?
+                .with_query_progress(context.query_progress());
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
 
@@ -314,6 +359,8 @@ impl CsvConfig {
 pub struct CsvOpener {
     config: Arc<CsvConfig>,
     file_compression_type: FileCompressionType,
+    schema_adapter: Option<Arc<dyn SchemaAdapter>>,
+    headers: Option<Arc<CsvHeaders>>,
 }
 
 impl CsvOpener {
@@ -325,8 +372,38 @@ impl CsvOpener {
         Self {
             config,
             file_compression_type,
+            schema_adapter: None,
+            headers: None,
         }
     }
+
+    /// Adapts the columns of each file with `schema_adapter`, which adapts
+    /// the columns read to the projection of the [`CsvConfig`]. The columns
+    /// are read by position unless [`Self::with_headers`] is set.
+    pub fn with_schema_adapter(mut self, schema_adapter: Arc<dyn SchemaAdapter>) -> Self {
+        self.schema_adapter = Some(schema_adapter);
+        self
+    }
+
+    /// Reads the columns of files with a header by name, see
+    /// [`header_schema`]. The header of each file is read once into `headers`,
+    /// shared by the openers of the partitions of a scan.
+    pub(crate) fn with_headers(mut self, headers: Arc<CsvHeaders>) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+}
+
+/// The column names in the headers of the CSV files of a scan
+#[derive(Debug, Default)]
+pub(crate) struct CsvHeaders {
+    headers: Mutex<HashMap<Path, Arc<OnceCell<Vec<String>>>>>,
+}
+
+impl CsvHeaders {
+    fn get(&self, location: &Path) -> Arc<OnceCell<Vec<String>>> {
+        Arc::clone(self.headers.lock().entry(location.clone()).or_default())
+    }
 }
 
 impl FileOpener for CsvOpener {
@@ -364,10 +441,17 @@ impl FileOpener for CsvOpener {
             }
         }
 
-        let config = CsvConfig {
+        let mut config = CsvConfig {
             has_header: csv_has_header,
             ..(*self.config).clone()
         };
+        let schema_adapter = self.schema_adapter.clone();
+        // the header of the file, if its columns are read by name
+        let header_cell = self
+            .headers
+            .as_ref()
+            .filter(|_| self.config.has_header)
+            .map(|headers| headers.get(file_meta.location()));
 
         let file_compression_type = self.file_compression_type.to_owned();
 
@@ -383,6 +467,15 @@ impl FileOpener for CsvOpener {
         Ok(Box::pin(async move {
             // Current partition contains bytes [start_byte, end_byte) (might contain incomplete lines at boundaries)
 
+            // the header of a file read from its middle is read separately,
+            // unless another partition has already read it
+            let mut header = None;
+            if let Some(cell) = header_cell.as_ref().filter(|_| !config.has_header) {
+                let size = file_meta.object_meta.size;
+                let read = read_header(&store, file_meta.location(), size, &config);
+                header = Some(cell.get_or_try_init(|| read).await?.clone());
+            }
+
             let calculated_range = calculate_range(&file_meta, &store).await?;
 
             let range = match calculated_range {
@@ -402,10 +495,11 @@ impl FileOpener for CsvOpener {
 
             let result = store.get_opts(file_meta.location(), options).await?;
 
-            match result.payload {
+            let mut mapper = None;
+            let stream = match result.payload {
                 GetResultPayload::File(mut file, _) => {
                     let is_whole_file_scanned = file_meta.range.is_none();
-                    let decoder = if is_whole_file_scanned {
+                    let mut decoder = if is_whole_file_scanned {
                         // Don't seek if no range as breaks FIFO files
                         file_compression_type.convert_read(file)?
                     } else {
@@ -415,15 +509,67 @@ impl FileOpener for CsvOpener {
                         )?
                     };
 
-                    Ok(futures::stream::iter(config.open(decoder)?).boxed())
+                    if let Some(schema_adapter) = &schema_adapter {
+                        if let Some(cell) = header_cell.filter(|_| config.has_header) {
+                            // peek the header, rather than reading the file twice
+                            let mut reader = BufReader::new(decoder);
+                            let mut peeked = vec![];
+                            let end = loop {
+                                if let Some(end) = header_len(&peeked, &config) {
+                                    break end;
+                                }
+                                if reader.read_until(b'\n', &mut peeked)? == 0 {
+                                    break peeked.len();
+                                }
+                            };
+                            let parsed = parse_header(&peeked[..end], &config)?;
+                            let _ = cell.set(parsed.clone());
+                            header = Some(parsed);
+                            decoder = Box::new(Read::chain(Cursor::new(peeked), reader));
+                        }
+                        mapper = Some(map_file_schema(
+                            schema_adapter.as_ref(),
+                            header,
+                            file_meta.location(),
+                            &mut config,
+                        )?);
+                    }
+
+                    futures::stream::iter(config.open(decoder)?).boxed()
                 }
                 GetResultPayload::Stream(s) => {
-                    let mut decoder = config.builder().build_decoder();
                     let s = s.map_err(DataFusionError::from);
                     let mut input =
                         file_compression_type.convert_stream(s.boxed())?.fuse();
                     let mut buffered = Bytes::new();
 
+                    if let Some(schema_adapter) = &schema_adapter {
+                        if let Some(cell) = header_cell.filter(|_| config.has_header) {
+                            // peek the header, which is decoded again below
+                            let mut peeked = BytesMut::new();
+                            let end = loop {
+                                if let Some(end) = header_len(&peeked, &config) {
+                                    break end;
+                                }
+                                match input.try_next().await? {
+                                    Some(bytes) => peeked.extend_from_slice(&bytes),
+                                    None => break peeked.len(),
+                                }
+                            };
+                            let parsed = parse_header(&peeked[..end], &config)?;
+                            let _ = cell.set(parsed.clone());
+                            header = Some(parsed);
+                            buffered = peeked.freeze();
+                        }
+                        mapper = Some(map_file_schema(
+                            schema_adapter.as_ref(),
+                            header,
+                            file_meta.location(),
+                            &mut config,
+                        )?);
+                    }
+
+                    let mut decoder = config.builder().build_decoder();
                     let s = futures::stream::poll_fn(move |cx| {
                         loop {
                             if buffered.is_empty() {
@@ -447,13 +593,124 @@ impl FileOpener for CsvOpener {
 
                         Poll::Ready(decoder.flush().transpose())
                     });
-                    Ok(s.boxed())
+                    s.boxed()
                 }
-            }
+            };
+
+            Ok(match mapper {
+                Some(mapper) => stream
+                    .map(move |batch| {
+                        batch
+                            .and_then(|batch| mapper.map_batch(batch).map_err(Into::into))
+                    })
+                    .boxed(),
+                None => stream,
+            })
         }))
     }
 }
 
+/// Reads the header of a CSV file, fetching larger ranges from the start of
+/// the file until its first record is complete. Only uncompressed files are
+/// read in ranges.
+async fn read_header(
+    store: &Arc<dyn ObjectStore>,
+    location: &Path,
+    size: usize,
+    config: &CsvConfig,
+) -> Result<Vec<String>> {
+    let mut len = 8 * 1024;
+    loop {
+        let bytes = store.get_range(location, 0..len.min(size)).await?;
+        if let Some(end) = header_len(&bytes, config) {
+            return parse_header(&bytes[..end], config);
+        }
+        if len >= size {
+            return parse_header(&bytes, config);
+        }
+        len *= 2;
+    }
+}
+
+/// Returns the length of the first record of `bytes`, including its line
+/// terminator, or `None` if it is incomplete. Quoted line terminators are
+/// part of the record.
+fn header_len(bytes: &[u8], config: &CsvConfig) -> Option<usize> {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, b) in bytes.iter().enumerate() {
+        if escaped {
+            escaped = false;
+        } else if *b == config.quote {
+            quoted = !quoted;
+        } else if quoted && Some(*b) == config.escape {
+            escaped = true;
+        } else if *b == b'\n' && !quoted {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+/// Parses the column names of the header record `bytes`
+fn parse_header(bytes: &[u8], config: &CsvConfig) -> Result<Vec<String>> {
+    let mut format = csv::reader::Format::default()
+        .with_header(true)
+        .with_delimiter(config.delimiter)
+        .with_quote(config.quote);
+    if let Some(escape) = config.escape {
+        format = format.with_escape(escape);
+    }
+    let (schema, _) = format.infer_schema(bytes, Some(0))?;
+    Ok(schema.fields().iter().map(|f| f.name().clone()).collect())
+}
+
+/// Maps the columns of a file to the table with `schema_adapter`, updating
+/// the schema and projection `config` decodes the file with
+fn map_file_schema(
+    schema_adapter: &dyn SchemaAdapter,
+    header: Option<Vec<String>>,
+    location: &Path,
+    config: &mut CsvConfig,
+) -> Result<Arc<dyn SchemaMapper>> {
+    let file_schema = match header {
+        Some(header) => header_schema(header, &config.file_schema, location)?,
+        None => Arc::clone(&config.file_schema),
+    };
+    let (mapper, projection) = schema_adapter.map_schema(&file_schema)?;
+    config.file_schema = file_schema;
+    config.file_projection = Some(projection);
+    Ok(mapper)
+}
+
+/// Returns the schema of the CSV file at `location` with the columns of its
+/// `header`. The columns of the table have its types, and other columns are
+/// read as strings. All the columns of the table must be in the header.
+fn header_schema(
+    header: Vec<String>,
+    table_schema: &SchemaRef,
+    location: &Path,
+) -> Result<SchemaRef> {
+    if let Some(field) = table_schema
+        .fields()
+        .iter()
+        .find(|field| !header.contains(field.name()))
+    {
+        return plan_err!(
+            "Table schema field {} is not in the header of CSV file {location}",
+            field.name()
+        );
+    }
+    let fields: Vec<_> = header
+        .into_iter()
+        .map(|name| match table_schema.field_with_name(&name) {
+            Ok(field) => field.clone(),
+            Err(_) => Field::new(name, DataType::Utf8, true),
+        })
+        .collect();
+    Ok(Arc::new(Schema::new(fields)))
+}
+
 pub async fn plan_to_csv(
     task_ctx: Arc<TaskContext>,
     plan: Arc<dyn ExecutionPlan>,
@@ -519,6 +776,7 @@ mod tests {
     use crate::{scalar::ScalarValue, test_util::aggr_test_schema};
 
     use arrow::datatypes::*;
+    use datafusion_common::cast::as_int64_array;
     use datafusion_common::test_util::arrow_test_data;
     use datafusion_common::FileType;
 
@@ -953,6 +1211,67 @@ mod tests {
         Ok(())
     }
 
+    async fn read_csv_by_name(
+        data: &str,
+    ) -> Result<Vec<arrow::record_batch::RecordBatch>> {
+        let mut config = SessionConfig::new()
+            .with_target_partitions(4)
+            .with_repartition_file_min_size(0);
+        config.options_mut().execution.csv_columns_by_name = true;
+        let session_ctx = SessionContext::new_with_config(config);
+        let store = object_store::memory::InMemory::new();
+        let path = object_store::path::Path::from("a.csv");
+        store.put(&path, Bytes::from(data.to_string())).await?;
+
+        let url = Url::parse("memory://").unwrap();
+        session_ctx
+            .runtime_env()
+            .register_object_store(&url, Arc::new(store));
+
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let options = CsvReadOptions::new().schema(&schema);
+        session_ctx
+            .read_csv("memory:///", options)
+            .await?
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_columns_by_name() -> Result<()> {
+        // the header has a quoted line terminator, and is read separately by
+        // the partitions reading the middle of the file
+        let rows: String = (1..=20).map(|i| format!("{i},x,{}\n", i * 10)).collect();
+        let data = format!("b,\"c\nd\",a\n{rows}");
+        let result = read_csv_by_name(&data).await?;
+        assert!(result.len() > 1);
+
+        let mut rows = 0;
+        for batch in &result {
+            let a = as_int64_array(batch.column(0))?;
+            let b = as_int64_array(batch.column(1))?;
+            for (a, b) in a.iter().zip(b) {
+                assert_eq!(a, b.map(|b| b * 10));
+                rows += 1;
+            }
+        }
+        assert_eq!(rows, 20);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_columns_by_name_missing_column() -> Result<()> {
+        let err = read_csv_by_name("b,c\n1,2\n").await.unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: Table schema field a is not in the header of CSV file a.csv"
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_no_trailing_delimiter() {
         let session_ctx = SessionContext::new();
@@ -32,8 +32,9 @@ use arrow::datatypes::{ArrowNativeType, UInt16Type};
 use arrow_array::{ArrayRef, DictionaryArray, RecordBatch, RecordBatchOptions};
 use arrow_schema::{DataType, Field, Schema, SchemaRef};
 use datafusion_common::stats::Precision;
-use datafusion_common::{exec_err, ColumnStatistics, Statistics};
-use datafusion_physical_expr::LexOrdering;
+use datafusion_common::{exec_err, BucketSpec, ColumnStatistics, Statistics};
+use datafusion_physical_expr::expressions::Column;
+use datafusion_physical_expr::{LexOrdering, Partitioning, PhysicalExpr};
 
 use log::warn;
 
@@ -96,6 +97,10 @@ pub struct FileScanConfig {
     pub table_partition_cols: Vec<Field>,
     /// All equivalent lexicographical orderings that describe the schema.
     pub output_ordering: Vec<LexOrdering>,
+    /// When set, `file_groups[i]` holds the files of bucket `i`, so the scan
+    /// is hash partitioned on the bucket columns. Such file groups must not
+    /// be repartitioned.
+    pub bucket_by: Option<BucketSpec>,
 }
 
 impl FileScanConfig {
@@ -146,6 +151,35 @@ impl FileScanConfig {
         (table_schema, table_stats, projected_output_ordering)
     }
 
+    /// The output [`Partitioning`] of a scan of this configuration.
+    ///
+    /// This is hash partitioning on the bucket columns if the file groups are
+    /// the buckets of [`Self::bucket_by`] and all bucket columns are projected,
+    /// and unknown partitioning otherwise.
+    pub fn output_partitioning(&self) -> Partitioning {
+        let num_partitions = self.file_groups.len();
+        let Some(bucket_by) = &self.bucket_by else {
+            return Partitioning::UnknownPartitioning(num_partitions);
+        };
+        if bucket_by.num_buckets != num_partitions {
+            return Partitioning::UnknownPartitioning(num_partitions);
+        }
+
+        let (projected_schema, _, _) = self.project();
+        let hash_exprs = bucket_by
+            .columns
+            .iter()
+            .map(|name| {
+                let index = projected_schema.index_of(name).ok()?;
+                Some(Arc::new(Column::new(name, index)) as Arc<dyn PhysicalExpr>)
+            })
+            .collect::<Option<Vec<_>>>();
+        match hash_exprs {
+            Some(hash_exprs) => Partitioning::Hash(hash_exprs, num_partitions),
+            None => Partitioning::UnknownPartitioning(num_partitions),
+        }
+    }
+
     #[allow(unused)] // Only used by avro
     pub(crate) fn projected_file_column_names(&self) -> Option<Vec<String>> {
         self.projection.as_ref().map(|p| {
@@ -778,6 +812,7 @@ mod tests {
             statistics,
             table_partition_cols,
             output_ordering: vec![],
+            bucket_by: None,
         }
     }
 
@@ -24,6 +24,7 @@
 use std::collections::VecDeque;
 use std::mem;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use crate::datasource::listing::PartitionedFile;
@@ -39,7 +40,9 @@ use arrow::datatypes::SchemaRef;
 use arrow::error::ArrowError;
 use arrow::record_batch::RecordBatch;
 use datafusion_common::instant::Instant;
+use datafusion_common::stats::Precision;
 use datafusion_common::ScalarValue;
+use datafusion_execution::progress::QueryProgress;
 
 use futures::future::BoxFuture;
 use futures::stream::BoxStream;
@@ -95,6 +98,12 @@ pub struct FileStream<F: FileOpener> {
     baseline_metrics: BaselineMetrics,
     /// Describes the behavior of the `FileStream` if file opening or scanning fails
     on_error: OnError,
+    /// The progress of the query the stream belongs to, if tracked
+    query_progress: Option<Arc<QueryProgress>>,
+    /// The estimated number of bytes and rows of the files of the stream
+    estimated_scan: (usize, Option<usize>),
+    /// The sizes of the files that were opened but not completely read yet
+    open_file_sizes: VecDeque<usize>,
 }
 
 /// Represents the state of the next `FileOpenFuture`. Since we need to poll
@@ -261,6 +270,7 @@ impl<F: FileOpener> FileStream<F> {
         );
 
         let files = config.file_groups[partition].clone();
+        let estimated_scan = estimate_scan(config, &files);
 
         Ok(Self {
             file_iter: files.into(),
@@ -272,6 +282,9 @@ impl<F: FileOpener> FileStream<F> {
             file_stream_metrics: FileStreamMetrics::new(metrics, partition),
             baseline_metrics: BaselineMetrics::new(metrics, partition),
             on_error: OnError::Fail,
+            query_progress: None,
+            estimated_scan,
+            open_file_sizes: VecDeque::new(),
         })
     }
 
@@ -284,12 +297,30 @@ impl<F: FileOpener> FileStream<F> {
         self
     }
 
+    /// Report the rows and bytes scanned by the stream to `query_progress`.
+    ///
+    /// The size of the files of the stream, and their number of rows if
+    /// known from the statistics of the scan, are added to the estimated
+    /// size of the input of the query.
+    pub fn with_query_progress(
+        mut self,
+        query_progress: Option<&Arc<QueryProgress>>,
+    ) -> Self {
+        if let Some(progress) = query_progress {
+            let (bytes, rows) = self.estimated_scan;
+            progress.add_estimated_scan(bytes, rows);
+        }
+        self.query_progress = query_progress.cloned();
+        self
+    }
+
     /// Begin opening the next file in parallel while decoding the current file in FileStream.
     ///
     /// Since file opening is mostly IO (and may involve a
     /// bunch of sequential IO), it can be parallelized with decoding.
     fn start_next_file(&mut self) -> Option<Result<(FileOpenFuture, Vec<ScalarValue>)>> {
         let part_file = self.file_iter.pop_front()?;
+        self.open_file_sizes.push_back(file_size(&part_file));
 
         let file_meta = FileMeta {
             object_meta: part_file.object_meta,
@@ -366,6 +397,10 @@ impl<F: FileOpener> FileStream<F> {
                         match self.on_error {
                             OnError::Skip => {
                                 self.file_stream_metrics.time_opening.stop();
+                                file_done(
+                                    &mut self.open_file_sizes,
+                                    self.query_progress.as_ref(),
+                                );
                                 self.state = FileStreamState::Idle
                             }
                             OnError::Fail => {
@@ -392,6 +427,9 @@ impl<F: FileOpener> FileStream<F> {
                         Some(Ok(batch)) => {
                             self.file_stream_metrics.time_scanning_until_data.stop();
                             self.file_stream_metrics.time_scanning_total.stop();
+                            if let Some(progress) = &self.query_progress {
+                                progress.add_scanned_rows(batch.num_rows());
+                            }
                             let result = self
                                 .pc_projector
                                 .project(batch, partition_values)
@@ -426,29 +464,35 @@ impl<F: FileOpener> FileStream<F> {
 
                             match self.on_error {
                                 // If `OnError::Skip` we skip the file as soon as we hit the first error
-                                OnError::Skip => match mem::take(next) {
-                                    Some((future, partition_values)) => {
-                                        self.file_stream_metrics.time_opening.start();
-
-                                        match future {
-                                            NextOpen::Pending(future) => {
-                                                self.state = FileStreamState::Open {
-                                                    future,
-                                                    partition_values,
+                                OnError::Skip => {
+                                    file_done(
+                                        &mut self.open_file_sizes,
+                                        self.query_progress.as_ref(),
+                                    );
+                                    match mem::take(next) {
+                                        Some((future, partition_values)) => {
+                                            self.file_stream_metrics.time_opening.start();
+
+                                            match future {
+                                                NextOpen::Pending(future) => {
+                                                    self.state = FileStreamState::Open {
+                                                        future,
+                                                        partition_values,
+                                                    }
                                                 }
-                                            }
-                                            NextOpen::Ready(reader) => {
-                                                self.state = FileStreamState::Open {
-                                                    future: Box::pin(std::future::ready(
-                                                        reader,
-                                                    )),
-                                                    partition_values,
+                                                NextOpen::Ready(reader) => {
+                                                    self.state = FileStreamState::Open {
+                                                        future: Box::pin(
+                                                            std::future::ready(reader),
+                                                        ),
+                                                        partition_values,
+                                                    }
                                                 }
                                             }
                                         }
+                                        None => return Poll::Ready(None),
                                     }
-                                    None => return Poll::Ready(None),
-                                },
+                                }
                                 OnError::Fail => {
                                     self.state = FileStreamState::Error;
                                     return Poll::Ready(Some(Err(err.into())));
@@ -459,6 +503,10 @@ impl<F: FileOpener> FileStream<F> {
                             self.file_stream_metrics.time_scanning_until_data.stop();
                             self.file_stream_metrics.time_scanning_total.stop();
 
+                            file_done(
+                                &mut self.open_file_sizes,
+                                self.query_progress.as_ref(),
+                            );
                             match mem::take(next) {
                                 Some((future, partition_values)) => {
                                     self.file_stream_metrics.time_opening.start();
@@ -493,6 +541,49 @@ impl<F: FileOpener> FileStream<F> {
     }
 }
 
+/// Report the bytes of the first opened file as scanned, once it was
+/// completely read or skipped
+fn file_done(
+    open_file_sizes: &mut VecDeque<usize>,
+    query_progress: Option<&Arc<QueryProgress>>,
+) {
+    let size = open_file_sizes.pop_front();
+    if let (Some(progress), Some(size)) = (query_progress, size) {
+        progress.add_scanned_bytes(size);
+    }
+}
+
+/// The number of bytes of `file` to scan
+fn file_size(file: &PartitionedFile) -> usize {
+    match &file.range {
+        Some(range) => (range.end - range.start) as usize,
+        None => file.object_meta.size,
+    }
+}
+
+/// Estimate the number of bytes and rows of `files`, a group of the files of
+/// the scan `config`. The rows of the scan are assumed to be evenly
+/// distributed over its bytes.
+fn estimate_scan(
+    config: &FileScanConfig,
+    files: &[PartitionedFile],
+) -> (usize, Option<usize>) {
+    let bytes = files.iter().map(file_size).sum::<usize>();
+    let total_bytes = config
+        .file_groups
+        .iter()
+        .flatten()
+        .map(file_size)
+        .sum::<usize>();
+    let rows = match config.statistics.num_rows {
+        Precision::Exact(rows) | Precision::Inexact(rows) if total_bytes > 0 => {
+            Some((rows as f64 * bytes as f64 / total_bytes as f64).round() as usize)
+        }
+        _ => None,
+    };
+    (bytes, rows)
+}
+
 impl<F: FileOpener> Stream for FileStream<F> {
     type Item = Result<RecordBatch>;
 
@@ -661,6 +752,7 @@ mod tests {
                 limit: self.limit,
                 table_partition_cols: vec![],
                 output_ordering: vec![],
+                bucket_by: None,
             };
             let metrics_set = ExecutionPlanMetricsSet::new();
             let file_stream = FileStream::new(&config, 0, self.opener, &metrics_set)
@@ -0,0 +1,429 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Execution plan for reading fixed-width text files
+
+use std::any::Any;
+use std::io::{BufRead, BufReader, Lines, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+use super::{calculate_range, FileGroupPartitioner, FileScanConfig, RangeCalculation};
+use crate::datasource::file_format::file_compression_type::FileCompressionType;
+use crate::datasource::file_format::fixed_width::FixedWidthColumn;
+use crate::datasource::listing::{FileRange, PartitionedFile};
+use crate::datasource::physical_plan::file_stream::{
+    FileOpenFuture, FileOpener, FileStream,
+};
+use crate::datasource::physical_plan::FileMeta;
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
+use crate::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionMode, ExecutionPlan, Partitioning,
+    PlanProperties, SendableRecordBatchStream, Statistics,
+};
+
+use arrow::array::{ArrayRef, RecordBatch, RecordBatchOptions, StringArray};
+use arrow::compute::{cast_with_options, CastOptions};
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::error::ArrowError;
+use datafusion_common::config::{ConfigOptions, FixedWidthOptions};
+use datafusion_execution::TaskContext;
+use datafusion_physical_expr::{EquivalenceProperties, LexOrdering};
+
+use bytes::{Bytes, BytesMut};
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use object_store::{GetOptions, GetResultPayload, ObjectStore};
+
+/// Execution plan for scanning fixed-width text files
+#[derive(Debug, Clone)]
+pub struct FixedWidthExec {
+    base_config: FileScanConfig,
+    projected_statistics: Statistics,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+    options: FixedWidthOptions,
+    columns: Vec<FixedWidthColumn>,
+    cache: PlanProperties,
+}
+
+impl FixedWidthExec {
+    /// Create a new fixed-width reader execution plan provided base
+    /// configurations and the options describing the columns of the files
+    pub fn try_new(
+        base_config: FileScanConfig,
+        options: FixedWidthOptions,
+    ) -> Result<Self> {
+        let columns = FixedWidthColumn::parse_list(&options.columns)?;
+        let (projected_schema, projected_statistics, projected_output_ordering) =
+            base_config.project();
+        let cache = Self::compute_properties(
+            projected_schema,
+            &projected_output_ordering,
+            &base_config,
+        );
+        Ok(Self {
+            base_config,
+            projected_statistics,
+            metrics: ExecutionPlanMetricsSet::new(),
+            options,
+            columns,
+            cache,
+        })
+    }
+
+    /// Ref to the base configs
+    pub fn base_config(&self) -> &FileScanConfig {
+        &self.base_config
+    }
+
+    /// Ref to the fixed-width options
+    pub fn options(&self) -> &FixedWidthOptions {
+        &self.options
+    }
+
+    fn file_compression_type(&self) -> FileCompressionType {
+        self.options.compression.into()
+    }
+
+    fn output_partitioning_helper(file_scan_config: &FileScanConfig) -> Partitioning {
+        file_scan_config.output_partitioning()
+    }
+
+    /// This function creates the cache object that stores the plan properties such as schema, equivalence properties, ordering, partitioning, etc.
+    fn compute_properties(
+        schema: SchemaRef,
+        orderings: &[LexOrdering],
+        file_scan_config: &FileScanConfig,
+    ) -> PlanProperties {
+        // Equivalence Properties
+        let eq_properties = EquivalenceProperties::new_with_orderings(schema, orderings);
+
+        PlanProperties::new(
+            eq_properties,
+            Self::output_partitioning_helper(file_scan_config), // Output Partitioning
+            ExecutionMode::Bounded,                             // Execution Mode
+        )
+    }
+
+    fn with_file_groups(mut self, file_groups: Vec<Vec<PartitionedFile>>) -> Self {
+        self.base_config.file_groups = file_groups;
+        // Changing file groups may invalidate output partitioning. Update it also
+        let output_partitioning = Self::output_partitioning_helper(&self.base_config);
+        self.cache = self.cache.with_partitioning(output_partitioning);
+        self
+    }
+}
+
+impl DisplayAs for FixedWidthExec {
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        write!(f, "FixedWidthExec: ")?;
+        self.base_config.fmt_as(t, f)
+    }
+}
+
+impl ExecutionPlan for FixedWidthExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.cache
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    /// Redistribute files across partitions according to their size
+    /// See comments on [`FileGroupPartitioner`] for more detail.
+    ///
+    /// Return `None` if can't get repartitioned(empty/compressed file).
+    fn repartitioned(
+        &self,
+        target_partitions: usize,
+        config: &ConfigOptions,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        // The file groups of a bucketed scan are its buckets
+        if self.base_config.bucket_by.is_some() {
+            return Ok(None);
+        }
+        // Lines can't be found from the middle of a compressed file
+        if self.file_compression_type().is_compressed() {
+            return Ok(None);
+        }
+
+        let repartitioned_file_groups = FileGroupPartitioner::new()
+            .with_target_partitions(target_partitions)
+            .with_preserve_order_within_groups(
+                self.properties().output_ordering().is_some(),
+            )
+            .with_repartition_file_min_size(config.optimizer.repartition_file_min_size)
+            .repartition_file_groups(&self.base_config.file_groups);
+
+        Ok(repartitioned_file_groups
+            .map(|file_groups| Arc::new(self.clone().with_file_groups(file_groups)) as _))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.base_config.object_store_url)?;
+        let projected_schema = self.base_config.projected_file_schema();
+        // Columns of the schema missing from the column list are read as nulls
+        let columns = projected_schema
+            .fields()
+            .iter()
+            .map(|field| {
+                self.columns
+                    .iter()
+                    .find(|column| &column.name == field.name())
+                    .cloned()
+            })
+            .collect();
+        let opener = FixedWidthOpener {
+            batch_size: context.session_config().batch_size(),
+            projected_schema,
+            columns,
+            has_header: self.options.has_header,
+            trim: self.options.trim,
+            file_compression_type: self.file_compression_type(),
+            object_store,
+        };
+
+        let stream =
+            FileStream::new(&self.base_config, partition, opener, &self.metrics)?
+                .with_query_progress(context.query_progress());
+        Ok(Box::pin(stream) as SendableRecordBatchStream)
+    }
+
+    fn statistics(&self) -> Result<Statistics> {
+        Ok(self.projected_statistics.clone())
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+/// A [`FileOpener`] that opens a fixed-width text file and yields a
+/// [`FileOpenFuture`]
+struct FixedWidthOpener {
+    batch_size: usize,
+    projected_schema: SchemaRef,
+    /// The position of each field of `projected_schema`
+    columns: Vec<Option<FixedWidthColumn>>,
+    has_header: bool,
+    trim: bool,
+    file_compression_type: FileCompressionType,
+    object_store: Arc<dyn ObjectStore>,
+}
+
+impl FileOpener for FixedWidthOpener {
+    /// Open a fixed-width file, or the lines of the byte range of
+    /// `file_meta` when the file is read in parallel, like
+    /// [`CsvOpener`](super::CsvOpener)
+    fn open(&self, file_meta: FileMeta) -> Result<FileOpenFuture> {
+        let store = self.object_store.clone();
+        let file_compression_type = self.file_compression_type.to_owned();
+        let decoder = FixedWidthDecoder {
+            batch_size: self.batch_size,
+            schema: self.projected_schema.clone(),
+            columns: self.columns.clone(),
+            trim: self.trim,
+        };
+        // only the partition reading the start of a file skips its header
+        let skip = match file_meta.range {
+            Some(FileRange { start, .. }) if start != 0 => 0,
+            _ => usize::from(self.has_header),
+        };
+
+        if file_meta.range.is_some() {
+            assert!(
+                !file_compression_type.is_compressed(),
+                "Reading compressed fixed-width files in parallel is not supported"
+            );
+        }
+
+        Ok(Box::pin(async move {
+            let range = match calculate_range(&file_meta, &store).await? {
+                RangeCalculation::Range(range) => range.map(Into::into),
+                RangeCalculation::TerminateEarly => {
+                    return Ok(futures::stream::empty().boxed())
+                }
+            };
+            let options = GetOptions {
+                range,
+                ..Default::default()
+            };
+            let result = store.get_opts(file_meta.location(), options).await?;
+
+            match result.payload {
+                GetResultPayload::File(mut file, _) => {
+                    let reader = if file_meta.range.is_none() {
+                        // Don't seek if no range as breaks FIFO files
+                        file_compression_type.convert_read(file)?
+                    } else {
+                        file.seek(SeekFrom::Start(result.range.start as _))?;
+                        file_compression_type.convert_read(
+                            file.take((result.range.end - result.range.start) as u64),
+                        )?
+                    };
+                    let reader: Box<dyn BufRead + Send> =
+                        Box::new(BufReader::new(reader));
+                    let mut lines = reader.lines();
+                    for _ in 0..skip {
+                        lines.next().transpose()?;
+                    }
+                    let batches = FixedWidthBatches { decoder, lines };
+                    Ok(futures::stream::iter(batches).boxed())
+                }
+                GetResultPayload::Stream(s) => {
+                    let s = s.map_err(DataFusionError::from).boxed();
+                    let input = file_compression_type.convert_stream(s)?;
+                    let mut skip = skip;
+                    let batches = byte_lines(input)
+                        .try_filter(move |line| {
+                            let keep = skip == 0 && !line.is_empty();
+                            skip = skip.saturating_sub(1);
+                            futures::future::ready(keep)
+                        })
+                        .try_chunks(decoder.batch_size)
+                        .map(move |lines| {
+                            let lines = lines.map_err(|e| e.1)?;
+                            decoder.decode(&lines)
+                        });
+                    Ok(batches.boxed())
+                }
+            }
+        }))
+    }
+}
+
+/// Splits a stream of bytes into its lines, without their `\n` or
+/// `\r\n` terminators, like [`BufRead::lines`]
+fn byte_lines(
+    input: BoxStream<'static, Result<Bytes>>,
+) -> BoxStream<'static, Result<String>> {
+    // the input, the bytes of the lines not yet yielded and how many of
+    // them were already searched for a newline
+    let state = Some((input, BytesMut::new(), 0));
+    futures::stream::unfold(state, |state| async move {
+        let (mut input, mut buffer, mut searched) = state?;
+        loop {
+            if let Some(end) = buffer[searched..].iter().position(|&b| b == b'\n') {
+                let line = buffer.split_to(searched + end + 1);
+                let line = line_to_string(&line[..line.len() - 1]);
+                return Some((line, Some((input, buffer, 0))));
+            }
+            searched = buffer.len();
+            match input.next().await {
+                Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                Some(Err(e)) => return Some((Err(e), None)),
+                None if buffer.is_empty() => return None,
+                None => return Some((line_to_string(&buffer), None)),
+            }
+        }
+    })
+    .boxed()
+}
+
+fn line_to_string(line: &[u8]) -> Result<String> {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8(line.to_vec()).map_err(|e| DataFusionError::External(Box::new(e)))
+}
+
+/// Decodes lines of fixed-width text into [`RecordBatch`]es
+struct FixedWidthDecoder {
+    batch_size: usize,
+    schema: SchemaRef,
+    columns: Vec<Option<FixedWidthColumn>>,
+    trim: bool,
+}
+
+impl FixedWidthDecoder {
+    fn decode(&self, lines: &[String]) -> Result<RecordBatch, ArrowError> {
+        let cast_options = CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+        let arrays =
+            self.schema
+                .fields()
+                .iter()
+                .zip(&self.columns)
+                .map(|(field, column)| {
+                    let values: StringArray = lines
+                        .iter()
+                        .map(|line| column.as_ref()?.value(line, self.trim))
+                        .collect();
+                    let array: ArrayRef = Arc::new(values);
+                    if field.data_type() == &DataType::Utf8 {
+                        Ok(array)
+                    } else {
+                        cast_with_options(&array, field.data_type(), &cast_options)
+                            .map_err(|e| {
+                                ArrowError::ParseError(format!(
+                                    "Error while parsing fixed-width column {}: {e}",
+                                    field.name()
+                                ))
+                            })
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+        let options = RecordBatchOptions::new().with_row_count(Some(lines.len()));
+        RecordBatch::try_new_with_options(self.schema.clone(), arrays, &options)
+    }
+}
+
+/// Iterator of the [`RecordBatch`]es of the remaining lines of a file
+struct FixedWidthBatches {
+    decoder: FixedWidthDecoder,
+    lines: Lines<Box<dyn BufRead + Send>>,
+}
+
+impl Iterator for FixedWidthBatches {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut lines = Vec::with_capacity(self.decoder.batch_size);
+        while lines.len() < self.decoder.batch_size {
+            match self.lines.next() {
+                Some(Ok(line)) if line.is_empty() => {}
+                Some(Ok(line)) => lines.push(line),
+                Some(Err(e)) => return Some(Err(e.into())),
+                None => break,
+            }
+        }
+        (!lines.is_empty()).then(|| self.decoder.decode(&lines))
+    }
+}
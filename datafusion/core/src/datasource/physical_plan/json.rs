@@ -29,6 +29,9 @@ use crate::datasource::physical_plan::file_stream::{
     FileOpenFuture, FileOpener, FileStream,
 };
 use crate::datasource::physical_plan::FileMeta;
+use crate::datasource::schema_adapter::{
+    schema_adapter_factory, SchemaAdapter, SchemaAdapterFactory,
+};
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
 use crate::physical_plan::{
@@ -56,6 +59,8 @@ pub struct NdJsonExec {
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
     file_compression_type: FileCompressionType,
+    /// Optional user defined schema adapter factory
+    schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
     cache: PlanProperties,
 }
 
@@ -77,6 +82,7 @@ impl NdJsonExec {
             projected_statistics,
             metrics: ExecutionPlanMetricsSet::new(),
             file_compression_type,
+            schema_adapter_factory: None,
             cache,
         }
     }
@@ -86,8 +92,23 @@ impl NdJsonExec {
         &self.base_config
     }
 
+    /// Optional user defined schema adapter factory, see
+    /// [`ParquetExec::with_schema_adapter_factory`]. JSON objects are decoded
+    /// by field name, so the adapter is given the projected table schema as
+    /// file schema: fields missing from a file are read as nulls, and other
+    /// fields are ignored.
+    ///
+    /// [`ParquetExec::with_schema_adapter_factory`]: crate::datasource::physical_plan::ParquetExec::with_schema_adapter_factory
+    pub fn with_schema_adapter_factory(
+        mut self,
+        schema_adapter_factory: Arc<dyn SchemaAdapterFactory>,
+    ) -> Self {
+        self.schema_adapter_factory = Some(schema_adapter_factory);
+        self
+    }
+
     fn output_partitioning_helper(file_scan_config: &FileScanConfig) -> Partitioning {
-        Partitioning::UnknownPartitioning(file_scan_config.file_groups.len())
+        file_scan_config.output_partitioning()
     }
 
     /// This function creates the cache object that stores the plan properties such as schema, equivalence properties, ordering, partitioning, etc.
@@ -150,6 +171,10 @@ impl ExecutionPlan for NdJsonExec {
         target_partitions: usize,
         config: &datafusion_common::config::ConfigOptions,
     ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        // The file groups of a bucketed scan are its buckets
+        if self.base_config.bucket_by.is_some() {
+            return Ok(None);
+        }
         let repartition_file_min_size = config.optimizer.repartition_file_min_size;
         let preserve_order_within_groups = self.properties().output_ordering().is_some();
         let file_groups = &self.base_config.file_groups;
@@ -179,15 +204,21 @@ impl ExecutionPlan for NdJsonExec {
         let object_store = context
             .runtime_env()
             .object_store(&self.base_config.object_store_url)?;
-        let opener = JsonOpener {
+        let projected_schema = self.base_config.projected_file_schema();
+        let schema_adapter =
+            schema_adapter_factory(self.schema_adapter_factory.as_ref(), &context)?
+                .create(Arc::clone(&projected_schema), Arc::clone(&projected_schema));
+        let opener = JsonOpener::new(
             batch_size,
-            projected_schema: self.base_config.projected_file_schema(),
-            file_compression_type: self.file_compression_type.to_owned(),
+            projected_schema,
+            self.file_compression_type.to_owned(),
             object_store,
-        };
+        )
+        .with_schema_adapter(Arc::from(schema_adapter));
 
         let stream =
-            FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+            FileStream::new(&self.base_config, partition, opener, &self.metrics)?
+                .with_query_progress(context.query_progress());
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -207,6 +238,7 @@ pub struct JsonOpener {
     projected_schema: SchemaRef,
     file_compression_type: FileCompressionType,
     object_store: Arc<dyn ObjectStore>,
+    schema_adapter: Option<Arc<dyn SchemaAdapter>>,
 }
 
 impl JsonOpener {
@@ -222,8 +254,16 @@ impl JsonOpener {
             projected_schema,
             file_compression_type,
             object_store,
+            schema_adapter: None,
         }
     }
+
+    /// Adapts the batches decoded with the projected schema with
+    /// `schema_adapter`
+    pub fn with_schema_adapter(mut self, schema_adapter: Arc<dyn SchemaAdapter>) -> Self {
+        self.schema_adapter = Some(schema_adapter);
+        self
+    }
 }
 
 impl FileOpener for JsonOpener {
@@ -243,8 +283,13 @@ impl FileOpener for JsonOpener {
         let schema = self.projected_schema.clone();
         let batch_size = self.batch_size;
         let file_compression_type = self.file_compression_type.to_owned();
+        let mapping = self
+            .schema_adapter
+            .as_ref()
+            .map(|schema_adapter| schema_adapter.map_schema(&schema))
+            .transpose()?;
 
-        Ok(Box::pin(async move {
+        let open: FileOpenFuture = Box::pin(async move {
             let calculated_range = calculate_range(&file_meta, &store).await?;
 
             let range = match calculated_range {
@@ -317,6 +362,21 @@ impl FileOpener for JsonOpener {
                     Ok(s.boxed())
                 }
             }
+        });
+
+        Ok(Box::pin(async move {
+            let stream = open.await?;
+            Ok(match mapping {
+                Some((mapper, projection)) => stream
+                    .map(move |batch| {
+                        batch.and_then(|batch| {
+                            let batch = batch.project(&projection)?;
+                            mapper.map_batch(batch).map_err(Into::into)
+                        })
+                    })
+                    .boxed(),
+                None => stream,
+            })
         }))
     }
 }
@@ -526,6 +586,7 @@ mod tests {
                 limit: Some(3),
                 table_partition_cols: vec![],
                 output_ordering: vec![],
+                bucket_by: None,
             },
             file_compression_type.to_owned(),
         );
@@ -604,6 +665,7 @@ mod tests {
                 limit: Some(3),
                 table_partition_cols: vec![],
                 output_ordering: vec![],
+                bucket_by: None,
             },
             file_compression_type.to_owned(),
         );
@@ -651,6 +713,7 @@ mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering: vec![],
+                bucket_by: None,
             },
             file_compression_type.to_owned(),
         );
@@ -703,6 +766,7 @@ mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering: vec![],
+                bucket_by: None,
             },
             file_compression_type.to_owned(),
         );
@@ -0,0 +1,149 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`DeletedRows`] skips deleted rows while reading a parquet file
+
+use parquet::arrow::arrow_reader::{RowSelection, RowSelector};
+use parquet::file::metadata::ParquetMetaData;
+
+/// The positions of the deleted rows of a parquet file, which are skipped
+/// when it is read by a [`ParquetExec`].
+///
+/// Attach it as the `extensions` of a [`PartitionedFile`], for example to
+/// apply the positional delete files of table formats such as Apache Iceberg.
+///
+/// [`ParquetExec`]: super::ParquetExec
+/// [`PartitionedFile`]: crate::datasource::listing::PartitionedFile
+#[derive(Debug, Clone, Default)]
+pub struct DeletedRows {
+    /// Sorted and deduplicated positions of the deleted rows
+    positions: Vec<u64>,
+}
+
+impl DeletedRows {
+    /// Create from the positions of the deleted rows within the file, in
+    /// any order
+    pub fn new(mut positions: Vec<u64>) -> Self {
+        positions.sort_unstable();
+        positions.dedup();
+        Self { positions }
+    }
+
+    /// Returns the number of deleted rows
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns true if no rows are deleted
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the [`RowSelection`] skipping the deleted rows of the
+    /// `row_groups` that are read, in the order they are read
+    pub(crate) fn row_selection(
+        &self,
+        metadata: &ParquetMetaData,
+        row_groups: &[usize],
+    ) -> RowSelection {
+        let offsets: Vec<u64> = metadata
+            .row_groups()
+            .iter()
+            .scan(0, |offset, rg| {
+                let start = *offset;
+                *offset += rg.num_rows() as u64;
+                Some(start)
+            })
+            .collect();
+
+        let mut selectors = vec![];
+        for &idx in row_groups {
+            let start = offsets[idx];
+            let end = start + metadata.row_group(idx).num_rows() as u64;
+            let first = self.positions.partition_point(|p| *p < start);
+
+            let mut next = start;
+            for &pos in self.positions[first..].iter().take_while(|p| **p < end) {
+                if pos > next {
+                    selectors.push(RowSelector::select((pos - next) as usize));
+                }
+                selectors.push(RowSelector::skip(1));
+                next = pos + 1;
+            }
+            if end > next {
+                selectors.push(RowSelector::select((end - next) as usize));
+            }
+        }
+        selectors.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::array::{ArrayRef, Int32Array};
+    use arrow::record_batch::RecordBatch;
+    use bytes::Bytes;
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_row_selection() {
+        // three row groups of 4 rows each
+        let values: ArrayRef = Arc::new(Int32Array::from((0..12).collect::<Vec<_>>()));
+        let batch = RecordBatch::try_from_iter(vec![("a", values)]).unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(4)
+            .build();
+        let mut buffer = vec![];
+        let mut writer =
+            ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        let reader = SerializedFileReader::new(Bytes::from(buffer)).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 3);
+
+        let deleted = DeletedRows::new(vec![9, 0, 5, 4, 0]);
+        assert_eq!(deleted.len(), 4);
+
+        let selection = deleted.row_selection(metadata, &[0, 1, 2]);
+        let expected: RowSelection = vec![
+            RowSelector::skip(1),
+            RowSelector::select(3),
+            RowSelector::skip(2),
+            RowSelector::select(3),
+            RowSelector::skip(1),
+            RowSelector::select(2),
+        ]
+        .into();
+        assert_eq!(selection, expected);
+
+        // only the positions of the row groups that are read are skipped
+        let selection = deleted.row_selection(metadata, &[2]);
+        let expected: RowSelection = vec![
+            RowSelector::select(1),
+            RowSelector::skip(1),
+            RowSelector::select(2),
+        ]
+        .into();
+        assert_eq!(selection, expected);
+    }
+}
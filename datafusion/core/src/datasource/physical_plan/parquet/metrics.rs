@@ -19,6 +19,8 @@ use crate::physical_plan::metrics::{
     Count, ExecutionPlanMetricsSet, MetricBuilder, Time,
 };
 
+use datafusion_execution::cache::caching_object_store::ByteRangeCacheMetrics;
+
 /// Stores metrics about the parquet execution for a particular parquet file.
 ///
 /// This component is a subject to **change** in near future and is exposed for low level integrations
@@ -47,6 +49,14 @@ pub struct ParquetFileMetrics {
     pub page_index_rows_filtered: Count,
     /// Total time spent evaluating parquet page index filters
     pub page_index_eval_time: Time,
+    /// Number of times the metadata was found in the parquet metadata cache
+    pub metadata_cache_hits: Count,
+    /// Number of byte ranges read from the byte range cache of the runtime
+    pub byte_range_cache_hits: Count,
+    /// Number of byte ranges not found in the byte range cache of the runtime
+    pub byte_range_cache_misses: Count,
+    /// Total bytes of column chunks not fetched thanks to late materialization
+    pub late_materialization_bytes_skipped: Count,
 }
 
 impl ParquetFileMetrics {
@@ -95,6 +105,22 @@ impl ParquetFileMetrics {
             .with_new_label("filename", filename.to_string())
             .subset_time("page_index_eval_time", partition);
 
+        let metadata_cache_hits = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("metadata_cache_hits", partition);
+
+        let byte_range_cache_hits = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("byte_range_cache_hits", partition);
+
+        let byte_range_cache_misses = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("byte_range_cache_misses", partition);
+
+        let late_materialization_bytes_skipped = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("late_materialization_bytes_skipped", partition);
+
         Self {
             predicate_evaluation_errors,
             row_groups_matched_bloom_filter,
@@ -106,6 +132,20 @@ impl ParquetFileMetrics {
             pushdown_eval_time,
             page_index_rows_filtered,
             page_index_eval_time,
+            metadata_cache_hits,
+            byte_range_cache_hits,
+            byte_range_cache_misses,
+            late_materialization_bytes_skipped,
         }
     }
 }
+
+impl ByteRangeCacheMetrics for ParquetFileMetrics {
+    fn record_hit(&self) {
+        self.byte_range_cache_hits.add(1);
+    }
+
+    fn record_miss(&self) {
+        self.byte_range_cache_misses.add(1);
+    }
+}
@@ -21,6 +21,7 @@ use std::any::Any;
 use std::fmt::Debug;
 use std::ops::Range;
 use std::sync::Arc;
+use std::task::Poll;
 
 use crate::datasource::listing::PartitionedFile;
 use crate::datasource::physical_plan::file_stream::{
@@ -28,8 +29,9 @@ use crate::datasource::physical_plan::file_stream::{
 };
 use crate::datasource::physical_plan::{
     parquet::page_filter::PagePruningPredicate, DisplayAs, FileGroupPartitioner,
-    FileMeta, FileScanConfig, SchemaAdapter,
+    FileMeta, FileScanConfig,
 };
+use crate::datasource::schema_adapter::{schema_adapter_factory, SchemaAdapterFactory};
 use crate::{
     config::{ConfigOptions, TableParquetOptions},
     datasource::listing::ListingTableUrl,
@@ -48,13 +50,17 @@ use arrow::error::ArrowError;
 use datafusion_physical_expr::{EquivalenceProperties, LexOrdering, PhysicalExpr};
 
 use bytes::Bytes;
+use datafusion_execution::cache::cache_manager::ParquetMetadataCache;
+use datafusion_execution::cache::caching_object_store::{
+    ByteRangeCacheMetrics, CachingObjectStore,
+};
 use futures::future::BoxFuture;
 use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
 use log::debug;
 use object_store::buffered::BufWriter;
 use object_store::path::Path;
-use object_store::ObjectStore;
+use object_store::{ObjectMeta, ObjectStore};
 use parquet::arrow::arrow_reader::ArrowReaderOptions;
 use parquet::arrow::async_reader::{AsyncFileReader, ParquetObjectReader};
 use parquet::arrow::{AsyncArrowWriter, ParquetRecordBatchStreamBuilder, ProjectionMask};
@@ -63,12 +69,14 @@ use parquet::file::{metadata::ParquetMetaData, properties::WriterProperties};
 use parquet::schema::types::ColumnDescriptor;
 use tokio::task::JoinSet;
 
+mod deleted_rows;
 mod metrics;
 mod page_filter;
 mod row_filter;
 mod row_groups;
 mod statistics;
 
+pub use deleted_rows::DeletedRows;
 pub use metrics::ParquetFileMetrics;
 
 /// Execution plan for scanning one or more Parquet partitions
@@ -89,6 +97,8 @@ pub struct ParquetExec {
     metadata_size_hint: Option<usize>,
     /// Optional user defined parquet file reader factory
     parquet_file_reader_factory: Option<Arc<dyn ParquetFileReaderFactory>>,
+    /// Optional user defined schema adapter factory
+    schema_adapter_factory: Option<Arc<dyn SchemaAdapterFactory>>,
     cache: PlanProperties,
     /// Parquet Options
     parquet_options: TableParquetOptions,
@@ -154,6 +164,7 @@ impl ParquetExec {
             page_pruning_predicate,
             metadata_size_hint,
             parquet_file_reader_factory: None,
+            schema_adapter_factory: None,
             cache,
             parquet_options,
         }
@@ -189,6 +200,22 @@ impl ParquetExec {
         self
     }
 
+    /// Optional user defined schema adapter factory.
+    ///
+    /// The [`SchemaAdapter`] adapts the record batches read from each file to
+    /// the table schema. If none is set, a [`DefaultSchemaAdapterFactory`]
+    /// configured for the session is used.
+    ///
+    /// [`SchemaAdapter`]: crate::datasource::schema_adapter::SchemaAdapter
+    /// [`DefaultSchemaAdapterFactory`]: crate::datasource::schema_adapter::DefaultSchemaAdapterFactory
+    pub fn with_schema_adapter_factory(
+        mut self,
+        schema_adapter_factory: Arc<dyn SchemaAdapterFactory>,
+    ) -> Self {
+        self.schema_adapter_factory = Some(schema_adapter_factory);
+        self
+    }
+
     /// If true, any filter [`Expr`]s on the scan will converted to a
     /// [`RowFilter`](parquet::arrow::arrow_reader::RowFilter) in the
     /// `ParquetRecordBatchStream`. These filters are applied by the
@@ -222,6 +249,20 @@ impl ParquetExec {
         self.parquet_options.global.reorder_filters
     }
 
+    /// If true, and filters are not pushed down, the predicate is still
+    /// evaluated while decoding a file when its columns are smaller than
+    /// the other projected columns, so that only the pages of those columns
+    /// containing matching rows are fetched. Defaults to true.
+    pub fn with_late_materialization(mut self, late_materialization: bool) -> Self {
+        self.parquet_options.global.late_materialization = late_materialization;
+        self
+    }
+
+    /// Return the value described in [`Self::with_late_materialization`]
+    fn late_materialization(&self) -> bool {
+        self.parquet_options.global.late_materialization
+    }
+
     /// If enabled, the reader will read the page index
     /// This is used to optimise filter pushdown
     /// via `RowSelector` and `RowFilter` by
@@ -248,7 +289,7 @@ impl ParquetExec {
     }
 
     fn output_partitioning_helper(file_config: &FileScanConfig) -> Partitioning {
-        Partitioning::UnknownPartitioning(file_config.file_groups.len())
+        file_config.output_partitioning()
     }
 
     /// This function creates the cache object that stores the plan properties such as schema, equivalence properties, ordering, partitioning, etc.
@@ -343,6 +384,10 @@ impl ExecutionPlan for ParquetExec {
         target_partitions: usize,
         config: &ConfigOptions,
     ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        // The file groups of a bucketed scan are its buckets
+        if self.base_config.bucket_by.is_some() {
+            return Ok(None);
+        }
         let repartition_file_min_size = config.optimizer.repartition_file_min_size;
         let repartitioned_file_groups_option = FileGroupPartitioner::new()
             .with_target_partitions(target_partitions)
@@ -374,14 +419,22 @@ impl ExecutionPlan for ParquetExec {
             .as_ref()
             .map(|f| Ok(Arc::clone(f)))
             .unwrap_or_else(|| {
-                ctx.runtime_env()
+                let runtime_env = ctx.runtime_env();
+                runtime_env
                     .object_store(&self.base_config.object_store_url)
                     .map(|store| {
-                        Arc::new(DefaultParquetFileReaderFactory::new(store))
-                            as Arc<dyn ParquetFileReaderFactory>
+                        let metadata_cache =
+                            runtime_env.cache_manager.get_parquet_metadata_cache();
+                        Arc::new(
+                            DefaultParquetFileReaderFactory::new(store)
+                                .with_metadata_cache(metadata_cache),
+                        ) as Arc<dyn ParquetFileReaderFactory>
                     })
             })?;
 
+        let schema_adapter_factory =
+            schema_adapter_factory(self.schema_adapter_factory.as_ref(), &ctx)?;
+
         let opener = ParquetOpener {
             partition_index,
             projection: Arc::from(projection),
@@ -394,14 +447,17 @@ impl ExecutionPlan for ParquetExec {
             metadata_size_hint: self.metadata_size_hint,
             metrics: self.metrics.clone(),
             parquet_file_reader_factory,
+            schema_adapter_factory,
             pushdown_filters: self.pushdown_filters(),
             reorder_filters: self.reorder_filters(),
+            late_materialization: self.late_materialization(),
             enable_page_index: self.enable_page_index(),
             enable_bloom_filter: self.enable_bloom_filter(),
         };
 
         let stream =
-            FileStream::new(&self.base_config, partition_index, opener, &self.metrics)?;
+            FileStream::new(&self.base_config, partition_index, opener, &self.metrics)?
+                .with_query_progress(ctx.query_progress());
 
         Ok(Box::pin(stream))
     }
@@ -428,8 +484,10 @@ struct ParquetOpener {
     metadata_size_hint: Option<usize>,
     metrics: ExecutionPlanMetricsSet,
     parquet_file_reader_factory: Arc<dyn ParquetFileReaderFactory>,
+    schema_adapter_factory: Arc<dyn SchemaAdapterFactory>,
     pushdown_filters: bool,
     reorder_filters: bool,
+    late_materialization: bool,
     enable_page_index: bool,
     enable_bloom_filter: bool,
 }
@@ -437,6 +495,7 @@ struct ParquetOpener {
 impl FileOpener for ParquetOpener {
     fn open(&self, file_meta: FileMeta) -> Result<FileOpenFuture> {
         let file_range = file_meta.range.clone();
+        let extensions = file_meta.extensions.clone();
 
         let file_metrics = ParquetFileMetrics::new(
             self.partition_index,
@@ -455,17 +514,25 @@ impl FileOpener for ParquetOpener {
         let batch_size = self.batch_size;
         let projection = self.projection.clone();
         let projected_schema = SchemaRef::from(self.table_schema.project(&projection)?);
-        let schema_adapter = SchemaAdapter::new(projected_schema);
+        let schema_adapter = self
+            .schema_adapter_factory
+            .create(projected_schema, self.table_schema.clone());
         let predicate = self.predicate.clone();
         let pruning_predicate = self.pruning_predicate.clone();
         let page_pruning_predicate = self.page_pruning_predicate.clone();
         let table_schema = self.table_schema.clone();
         let reorder_predicates = self.reorder_filters;
         let pushdown_filters = self.pushdown_filters;
+        let late_materialization_predicate = (self.late_materialization
+            && !pushdown_filters)
+            .then(|| predicate.clone())
+            .flatten();
+        // the offset index lets late materialization skip whole pages
         let enable_page_index = should_enable_page_index(
             self.enable_page_index,
             &self.page_pruning_predicate,
-        );
+        ) || (self.enable_page_index
+            && late_materialization_predicate.is_some());
         let enable_bloom_filter = self.enable_bloom_filter;
         let limit = self.limit;
 
@@ -543,17 +610,68 @@ impl FileOpener for ParquetOpener {
             // page index pruning: if all data on individual pages can
             // be ruled using page metadata, rows from other columns
             // with that range can be skipped as well
+            let mut row_selection = None;
             if enable_page_index && !row_groups.is_empty() {
                 if let Some(p) = page_pruning_predicate {
-                    let pruned = p.prune(
+                    row_selection = p.prune(
                         &file_schema,
                         builder.parquet_schema(),
                         &row_groups,
                         file_metadata.as_ref(),
                         &file_metrics,
                     )?;
-                    if let Some(row_selection) = pruned {
-                        builder = builder.with_row_selection(row_selection);
+                }
+            }
+
+            // skip the rows deleted from the file
+            let deleted_rows = extensions
+                .as_ref()
+                .and_then(|e| e.downcast_ref::<DeletedRows>())
+                .filter(|deleted_rows| !deleted_rows.is_empty());
+            if let Some(deleted_rows) = deleted_rows {
+                let selection = deleted_rows.row_selection(&file_metadata, &row_groups);
+                row_selection = Some(match row_selection {
+                    Some(pruned) => pruned.intersection(&selection),
+                    None => selection,
+                });
+            }
+
+            if let Some(row_selection) = row_selection {
+                builder = builder.with_row_selection(row_selection);
+            }
+
+            // Late materialization: evaluate the predicate first, if it is expected
+            // to be cheaper, and only fetch the pages of the other projected columns
+            // that contain matching rows
+            let mut late_materialized_bytes = None;
+            if let Some(predicate) = late_materialization_predicate {
+                let total_bytes = row_filter::late_materialization_bytes(
+                    &predicate,
+                    &file_schema,
+                    &file_metadata,
+                    &row_groups,
+                    &adapted_projections,
+                );
+                if let Some(total_bytes) = total_bytes {
+                    match row_filter::build_row_filter(
+                        &predicate,
+                        &file_schema,
+                        &table_schema,
+                        &file_metadata,
+                        reorder_predicates,
+                        &file_metrics,
+                    ) {
+                        Ok(Some(filter)) => {
+                            builder = builder.with_row_filter(filter);
+                            late_materialized_bytes = Some(total_bytes);
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            debug!(
+                                "Ignoring error building row filter for '{:?}': {}",
+                                predicate, e
+                            );
+                        }
                     }
                 }
             }
@@ -568,12 +686,25 @@ impl FileOpener for ParquetOpener {
                 .with_row_groups(row_groups)
                 .build()?;
 
+            // bytes fetched from here on are the column chunks read by the stream
+            let bytes_scanned = file_metrics.bytes_scanned.clone();
+            let bytes_scanned_before = bytes_scanned.value();
+            let bytes_skipped = file_metrics.late_materialization_bytes_skipped.clone();
+            let on_complete = futures::stream::poll_fn(move |_| {
+                if let Some(total_bytes) = late_materialized_bytes.take() {
+                    let fetched = bytes_scanned.value() - bytes_scanned_before;
+                    bytes_skipped.add(total_bytes.saturating_sub(fetched));
+                }
+                Poll::Ready(None)
+            });
+
             let adapted = stream
                 .map_err(|e| ArrowError::ExternalError(Box::new(e)))
                 .map(move |maybe_batch| {
                     maybe_batch
                         .and_then(|b| schema_mapping.map_batch(b).map_err(Into::into))
-                });
+                })
+                .chain(on_complete);
 
             Ok(adapted.boxed())
         }))
@@ -610,19 +741,40 @@ pub trait ParquetFileReaderFactory: Debug + Send + Sync + 'static {
 #[derive(Debug)]
 pub struct DefaultParquetFileReaderFactory {
     store: Arc<dyn ObjectStore>,
+    metadata_cache: Option<ParquetMetadataCache>,
 }
 
 impl DefaultParquetFileReaderFactory {
     /// Create a factory.
     pub fn new(store: Arc<dyn ObjectStore>) -> Self {
-        Self { store }
+        Self {
+            store,
+            metadata_cache: None,
+        }
+    }
+
+    /// Look up the metadata of files in `metadata_cache` before fetching it
+    /// from the object store, and add fetched metadata to it.
+    ///
+    /// As the cached metadata may be used by scans needing the page index, it
+    /// is always fetched along with the footer.
+    pub fn with_metadata_cache(
+        mut self,
+        metadata_cache: Option<ParquetMetadataCache>,
+    ) -> Self {
+        self.metadata_cache = metadata_cache;
+        self
     }
 }
 
 /// Implements [`AsyncFileReader`] for a parquet file in object storage
 pub(crate) struct ParquetFileReader {
     file_metrics: ParquetFileMetrics,
+    /// Records the reads served by the byte range cache of the runtime
+    cache_metrics: Arc<dyn ByteRangeCacheMetrics>,
     inner: ParquetObjectReader,
+    /// The file being read, and the cache of its metadata
+    metadata_cache: Option<(ObjectMeta, ParquetMetadataCache)>,
 }
 
 impl AsyncFileReader for ParquetFileReader {
@@ -631,7 +783,11 @@ impl AsyncFileReader for ParquetFileReader {
         range: Range<usize>,
     ) -> BoxFuture<'_, parquet::errors::Result<Bytes>> {
         self.file_metrics.bytes_scanned.add(range.end - range.start);
-        self.inner.get_bytes(range)
+        let metrics = Arc::clone(&self.cache_metrics);
+        Box::pin(CachingObjectStore::with_metrics(
+            metrics,
+            self.inner.get_bytes(range),
+        ))
     }
 
     fn get_byte_ranges(
@@ -643,13 +799,36 @@ impl AsyncFileReader for ParquetFileReader {
     {
         let total = ranges.iter().map(|r| r.end - r.start).sum();
         self.file_metrics.bytes_scanned.add(total);
-        self.inner.get_byte_ranges(ranges)
+        let metrics = Arc::clone(&self.cache_metrics);
+        Box::pin(CachingObjectStore::with_metrics(
+            metrics,
+            self.inner.get_byte_ranges(ranges),
+        ))
     }
 
     fn get_metadata(
         &mut self,
     ) -> BoxFuture<'_, parquet::errors::Result<Arc<ParquetMetaData>>> {
-        self.inner.get_metadata()
+        let metrics = Arc::clone(&self.cache_metrics);
+        let Some((object_meta, cache)) = self.metadata_cache.clone() else {
+            return Box::pin(CachingObjectStore::with_metrics(
+                metrics,
+                self.inner.get_metadata(),
+            ));
+        };
+        if let Some(metadata) = cache.get_with_extra(&object_meta.location, &object_meta)
+        {
+            self.file_metrics.metadata_cache_hits.add(1);
+            return Box::pin(async move { Ok(metadata) });
+        }
+
+        Box::pin(async move {
+            let metadata =
+                CachingObjectStore::with_metrics(metrics, self.inner.get_metadata())
+                    .await?;
+            cache.put_with_extra(&object_meta.location, metadata.clone(), &object_meta);
+            Ok(metadata)
+        })
     }
 }
 
@@ -667,15 +846,26 @@ impl ParquetFileReaderFactory for DefaultParquetFileReaderFactory {
             metrics,
         );
         let store = Arc::clone(&self.store);
+        let metadata_cache = self
+            .metadata_cache
+            .clone()
+            .map(|cache| (file_meta.object_meta.clone(), cache));
         let mut inner = ParquetObjectReader::new(store, file_meta.object_meta);
 
         if let Some(hint) = metadata_size_hint {
             inner = inner.with_footer_size_hint(hint)
         };
+        if metadata_cache.is_some() {
+            inner = inner
+                .with_preload_column_index(true)
+                .with_preload_offset_index(true);
+        }
 
         Ok(Box::new(ParquetFileReader {
             inner,
+            cache_metrics: Arc::new(file_metrics.clone()),
             file_metrics,
+            metadata_cache,
         }))
     }
 }
@@ -784,6 +974,10 @@ mod tests {
     use arrow::record_batch::RecordBatch;
     use arrow_schema::Fields;
     use datafusion_common::{assert_contains, FileType, GetExt, ScalarValue, ToDFSchema};
+    use datafusion_execution::cache::cache_manager::CacheManagerConfig;
+    use datafusion_execution::cache::cache_unit::DefaultParquetMetadataCache;
+    use datafusion_execution::cache::CacheAccessor;
+    use datafusion_execution::runtime_env::{RuntimeConfig, RuntimeEnv};
     use datafusion_expr::execution_props::ExecutionProps;
     use datafusion_expr::{col, lit, when, Expr};
     use datafusion_physical_expr::create_physical_expr;
@@ -813,6 +1007,7 @@ mod tests {
         schema: Option<SchemaRef>,
         predicate: Option<Expr>,
         pushdown_predicate: bool,
+        late_materialization: bool,
         page_index_predicate: bool,
     }
 
@@ -841,6 +1036,11 @@ mod tests {
             self
         }
 
+        fn with_late_materialization(mut self) -> Self {
+            self.late_materialization = true;
+            self
+        }
+
         fn with_page_index_predicate(mut self) -> Self {
             self.page_index_predicate = true;
             self
@@ -861,6 +1061,7 @@ mod tests {
                 schema,
                 predicate,
                 pushdown_predicate,
+                late_materialization,
                 page_index_predicate,
             } = self;
 
@@ -893,11 +1094,13 @@ mod tests {
                     limit: None,
                     table_partition_cols: vec![],
                     output_ordering: vec![],
+                    bucket_by: None,
                 },
                 predicate,
                 None,
                 Default::default(),
-            );
+            )
+            .with_late_materialization(late_materialization);
 
             if pushdown_predicate {
                 parquet_exec = parquet_exec
@@ -1152,6 +1355,45 @@ mod tests {
         assert_eq!(get_value(&metrics, "pushdown_rows_filtered"), 4);
     }
 
+    #[tokio::test]
+    async fn late_materialization() {
+        let c1: ArrayRef = Arc::new(Int32Array::from((1..=8).collect::<Vec<_>>()));
+        let c2: ArrayRef = Arc::new(StringArray::from(
+            (1..=8)
+                .map(|i| i.to_string().repeat(100))
+                .collect::<Vec<_>>(),
+        ));
+        let batch = create_batch(vec![("c1", c1), ("c2", c2)]);
+
+        // the predicate column is smaller than the other projected column
+        let rt = RoundTrip::new()
+            .with_predicate(col("c1").eq(lit(3)))
+            .with_late_materialization()
+            .with_page_index_predicate()
+            .round_trip(vec![batch.clone()])
+            .await;
+
+        let batches = rt.batches.unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+        let metrics = rt.parquet_exec.metrics().unwrap();
+        assert_eq!(get_value(&metrics, "page_index_rows_filtered"), 6);
+        assert_eq!(get_value(&metrics, "pushdown_rows_filtered"), 1);
+        assert!(get_value(&metrics, "late_materialization_bytes_skipped") > 0);
+
+        // the predicate column is larger, so it is evaluated after the scan
+        let rt = RoundTrip::new()
+            .with_predicate(col("c2").eq(lit("3".repeat(100))))
+            .with_late_materialization()
+            .round_trip(vec![batch])
+            .await;
+
+        let batches = rt.batches.unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 8);
+        let metrics = rt.parquet_exec.metrics().unwrap();
+        assert_eq!(get_value(&metrics, "pushdown_rows_filtered"), 0);
+        assert_eq!(get_value(&metrics, "late_materialization_bytes_skipped"), 0);
+    }
+
     #[tokio::test]
     async fn evolved_schema_projection() {
         let c1: ArrayRef =
@@ -1523,6 +1765,40 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn parquet_exec_with_metadata_cache() -> Result<()> {
+        let cache = Arc::new(DefaultParquetMetadataCache::default());
+        let cache_config = CacheManagerConfig::default()
+            .with_parquet_metadata_cache(Some(cache.clone()));
+        let runtime =
+            RuntimeEnv::new(RuntimeConfig::new().with_cache_manager(cache_config))?;
+        let session_ctx =
+            SessionContext::new_with_config_rt(SessionConfig::new(), Arc::new(runtime));
+        let state = session_ctx.state();
+        let testdata = crate::test_util::parquet_test_data();
+
+        let parquet_exec = scan_format(
+            &state,
+            &ParquetFormat::default(),
+            &testdata,
+            "alltypes_plain.parquet",
+            None,
+            None,
+        )
+        .await?;
+
+        // the first scan fetches the metadata, the second one reuses it
+        for expected_hits in [0, 1] {
+            let batches = collect(parquet_exec.clone(), state.task_ctx()).await?;
+            assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 8);
+            assert_eq!(cache.len(), 1);
+            let metrics = parquet_exec.metrics().unwrap();
+            assert_eq!(get_value(&metrics, "metadata_cache_hits"), expected_hits);
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn parquet_exec_with_range() -> Result<()> {
         fn file_range(meta: &ObjectMeta, start: i64, end: i64) -> PartitionedFile {
@@ -1550,6 +1826,7 @@ mod tests {
                     limit: None,
                     table_partition_cols: vec![],
                     output_ordering: vec![],
+                    bucket_by: None,
                 },
                 None,
                 None,
@@ -1671,6 +1948,7 @@ mod tests {
                     ),
                 ],
                 output_ordering: vec![],
+                bucket_by: None,
             },
             None,
             None,
@@ -1738,6 +2016,7 @@ mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering: vec![],
+                bucket_by: None,
             },
             None,
             None,
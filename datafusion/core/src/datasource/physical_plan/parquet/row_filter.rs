@@ -30,8 +30,8 @@ use datafusion_common::tree_node::{
     Transformed, TransformedResult, TreeNode, TreeNodeRecursion, TreeNodeRewriter,
 };
 use datafusion_common::{arrow_err, DataFusionError, Result, ScalarValue};
-use datafusion_physical_expr::expressions::{Column, Literal};
-use datafusion_physical_expr::utils::reassign_predicate_columns;
+use datafusion_physical_expr::expressions::{CastExpr, Column, Literal};
+use datafusion_physical_expr::utils::{collect_columns, reassign_predicate_columns};
 use datafusion_physical_expr::{split_conjunction, PhysicalExpr};
 
 use parquet::arrow::arrow_reader::{ArrowPredicate, RowFilter};
@@ -240,7 +240,15 @@ impl<'a> TreeNodeRewriter for FilterCandidateBuilder<'a> {
         expr: Arc<dyn PhysicalExpr>,
     ) -> Result<Transformed<Arc<dyn PhysicalExpr>>> {
         if let Some(column) = expr.as_any().downcast_ref::<Column>() {
-            if self.file_schema.field_with_name(column.name()).is_err() {
+            if let Ok(file_field) = self.file_schema.field_with_name(column.name()) {
+                // evaluate the predicate with the type of the column in the table
+                if let Ok(field) = self.table_schema.field_with_name(column.name()) {
+                    if field.data_type() != file_field.data_type() {
+                        let cast = CastExpr::new(expr, field.data_type().clone(), None);
+                        return Ok(Transformed::yes(Arc::new(cast)));
+                    }
+                }
+            } else {
                 // the column expr must be in the table schema
                 return match self.table_schema.field_with_name(column.name()) {
                     Ok(field) => {
@@ -315,6 +323,46 @@ fn columns_sorted(
     Ok(false)
 }
 
+/// Determine whether evaluating `expr` while decoding the `row_groups` of a file
+/// (late materialization) is likely cheaper than decoding all of the `projection`
+/// columns and filtering afterwards.
+///
+/// The predicate columns are always fetched in full, so this is only the case if the
+/// other projected columns, whose pages may be skipped, are larger. Returns the total
+/// compressed size of the predicate and projected columns if so, `None` otherwise.
+pub(crate) fn late_materialization_bytes(
+    expr: &Arc<dyn PhysicalExpr>,
+    file_schema: &Schema,
+    metadata: &ParquetMetaData,
+    row_groups: &[usize],
+    projection: &[usize],
+) -> Option<usize> {
+    let predicate_columns: BTreeSet<usize> = collect_columns(expr)
+        .iter()
+        .filter_map(|column| file_schema.index_of(column.name()).ok())
+        .collect();
+    if predicate_columns.is_empty() {
+        return None;
+    }
+
+    let schema_descr = metadata.file_metadata().schema_descr();
+    let mut predicate_bytes = 0;
+    let mut projection_bytes = 0;
+    for rg in row_groups {
+        for (idx, column) in metadata.row_group(*rg).columns().iter().enumerate() {
+            let root = schema_descr.get_column_root_idx(idx);
+            let size = column.compressed_size() as usize;
+            if predicate_columns.contains(&root) {
+                predicate_bytes += size;
+            } else if projection.contains(&root) {
+                projection_bytes += size;
+            }
+        }
+    }
+
+    (projection_bytes > predicate_bytes).then_some(predicate_bytes + projection_bytes)
+}
+
 /// Build a [`RowFilter`] from the given predicate `Expr`
 pub fn build_row_filter(
     expr: &Arc<dyn PhysicalExpr>,
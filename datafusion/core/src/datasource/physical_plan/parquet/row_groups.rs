@@ -1321,7 +1321,9 @@ mod tests {
             ParquetFileMetrics::new(0, object_meta.location.as_ref(), &metrics);
         let reader = ParquetFileReader {
             inner: ParquetObjectReader::new(Arc::new(in_memory), object_meta),
+            cache_metrics: Arc::new(file_metrics.clone()),
             file_metrics: file_metrics.clone(),
+            metadata_cache: None,
         };
         let mut builder = ParquetRecordBatchStreamBuilder::new(reader).await.unwrap();
 
@@ -0,0 +1,205 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Execution plan for reading XLSX workbooks
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::FileScanConfig;
+use crate::error::Result;
+use crate::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
+use crate::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionMode, ExecutionPlan, PlanProperties,
+    SendableRecordBatchStream, Statistics,
+};
+
+use arrow::datatypes::SchemaRef;
+use datafusion_common::config::XlsxOptions;
+use datafusion_execution::TaskContext;
+use datafusion_physical_expr::{EquivalenceProperties, LexOrdering};
+
+/// Execution plan for scanning a worksheet of XLSX workbooks
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct XlsxExec {
+    base_config: FileScanConfig,
+    projected_statistics: Statistics,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+    options: XlsxOptions,
+    cache: PlanProperties,
+}
+
+impl XlsxExec {
+    /// Create a new XLSX reader execution plan provided base configurations
+    /// and the options selecting the worksheet to read
+    pub fn new(base_config: FileScanConfig, options: XlsxOptions) -> Self {
+        let (projected_schema, projected_statistics, projected_output_ordering) =
+            base_config.project();
+        let cache = Self::compute_properties(
+            projected_schema,
+            &projected_output_ordering,
+            &base_config,
+        );
+        Self {
+            base_config,
+            projected_statistics,
+            metrics: ExecutionPlanMetricsSet::new(),
+            options,
+            cache,
+        }
+    }
+
+    /// Ref to the base configs
+    pub fn base_config(&self) -> &FileScanConfig {
+        &self.base_config
+    }
+
+    /// Ref to the XLSX options
+    pub fn options(&self) -> &XlsxOptions {
+        &self.options
+    }
+
+    /// This function creates the cache object that stores the plan properties such as schema, equivalence properties, ordering, partitioning, etc.
+    fn compute_properties(
+        schema: SchemaRef,
+        orderings: &[LexOrdering],
+        file_scan_config: &FileScanConfig,
+    ) -> PlanProperties {
+        // Equivalence Properties
+        let eq_properties = EquivalenceProperties::new_with_orderings(schema, orderings);
+
+        PlanProperties::new(
+            eq_properties,
+            file_scan_config.output_partitioning(), // Output Partitioning
+            ExecutionMode::Bounded,                 // Execution Mode
+        )
+    }
+}
+
+impl DisplayAs for XlsxExec {
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        write!(f, "XlsxExec: ")?;
+        self.base_config.fmt_as(t, f)
+    }
+}
+
+impl ExecutionPlan for XlsxExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.cache
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    #[cfg(not(feature = "xlsx"))]
+    fn execute(
+        &self,
+        _partition: usize,
+        _context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        Err(crate::error::DataFusionError::NotImplemented(
+            "Cannot execute xlsx plan without xlsx feature enabled".to_string(),
+        ))
+    }
+
+    #[cfg(feature = "xlsx")]
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        use super::file_stream::FileStream;
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.base_config.object_store_url)?;
+
+        let opener = private::XlsxOpener {
+            batch_size: context.session_config().batch_size(),
+            projected_schema: self.base_config.projected_file_schema(),
+            options: self.options.clone(),
+            object_store,
+        };
+
+        let stream =
+            FileStream::new(&self.base_config, partition, opener, &self.metrics)?
+                .with_query_progress(context.query_progress());
+        Ok(Box::pin(stream))
+    }
+
+    fn statistics(&self) -> Result<Statistics> {
+        Ok(self.projected_statistics.clone())
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+#[cfg(feature = "xlsx")]
+mod private {
+    use super::*;
+    use crate::datasource::physical_plan::file_stream::{FileOpenFuture, FileOpener};
+    use crate::datasource::physical_plan::FileMeta;
+    use crate::datasource::xlsx_to_arrow::Reader as XlsxReader;
+
+    use futures::StreamExt;
+    use object_store::ObjectStore;
+
+    pub struct XlsxOpener {
+        pub batch_size: usize,
+        pub projected_schema: SchemaRef,
+        pub options: XlsxOptions,
+        pub object_store: Arc<dyn ObjectStore>,
+    }
+
+    impl FileOpener for XlsxOpener {
+        fn open(&self, file_meta: FileMeta) -> Result<FileOpenFuture> {
+            let object_store = self.object_store.clone();
+            let schema = self.projected_schema.clone();
+            let options = self.options.clone();
+            let batch_size = self.batch_size;
+            Ok(Box::pin(async move {
+                // XLSX workbooks are zip archives, which are read as a whole
+                let bytes = object_store
+                    .get(file_meta.location())
+                    .await?
+                    .bytes()
+                    .await?;
+                let reader = XlsxReader::try_new(&bytes, &options, schema, batch_size)?;
+                Ok(futures::stream::iter(reader).boxed())
+            }))
+        }
+    }
+}
@@ -23,7 +23,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use datafusion_common::{not_impl_err, Constraints, Statistics};
 use datafusion_expr::{CreateExternalTable, LogicalPlan};
-pub use datafusion_expr::{TableProviderFilterPushDown, TableType};
+pub use datafusion_expr::{TableProviderFilterPushDown, TableType, TableVersion};
 
 use crate::arrow::datatypes::SchemaRef;
 use crate::datasource::listing_table_factory::ListingTableFactory;
@@ -183,6 +183,18 @@ pub trait TableProvider: Sync + Send {
         None
     }
 
+    /// Return a provider reading this table at `version`, if supported.
+    ///
+    /// This is used by queries such as `SELECT * FROM t FOR VERSION AS OF 3`
+    /// or `SELECT * FROM t FOR TIMESTAMP AS OF '2024-01-01 00:00:00'`.
+    async fn with_version(
+        &self,
+        _state: &SessionState,
+        _version: TableVersion,
+    ) -> Result<Arc<dyn TableProvider>> {
+        not_impl_err!("Reading older versions is not supported for this table")
+    }
+
     /// Return an [`ExecutionPlan`] to insert data into this table, if
     /// supported.
     ///
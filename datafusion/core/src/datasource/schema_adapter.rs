@@ -0,0 +1,688 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`SchemaAdapter`] and [`SchemaAdapterFactory`] to adapt file-level record
+//! batches to a table schema, e.g. when the schema of a table evolves.
+//!
+//! Adapter provides a method of translating the RecordBatches that come out of
+//! the physical format into how they should be used by DataFusion. For
+//! instance, a schema can be stored external to a parquet file that maps
+//! parquet logical types to arrow types.
+
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::array::{new_null_array, Array, ArrayRef, AsArray, GenericListArray};
+use arrow::array::{OffsetSizeTrait, StructArray};
+use arrow::compute::{can_cast_types, cast};
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use arrow_schema::{DataType, Field, FieldRef, Fields, Schema, SchemaRef};
+use datafusion_common::config::ExecutionOptions;
+use datafusion_common::{plan_err, DataFusionError, Result};
+use datafusion_execution::TaskContext;
+
+/// The metadata key of the Parquet field id of a [`Field`]
+const FIELD_ID_META_KEY: &str = "PARQUET:field_id";
+
+/// Factory for creating [`SchemaAdapter`]
+///
+/// This interface provides a way to implement custom schema adaptation logic
+/// for the file scans, e.g. [`ParquetExec`] or [`CsvExec`]. If no factory is
+/// set on a scan, it uses a [`DefaultSchemaAdapterFactory`] configured with
+/// the `datafusion.execution.missing_column_policy` and
+/// `datafusion.execution.extra_column_policy` options.
+///
+/// [`ParquetExec`]: crate::datasource::physical_plan::ParquetExec
+/// [`CsvExec`]: crate::datasource::physical_plan::CsvExec
+pub trait SchemaAdapterFactory: Debug + Send + Sync + 'static {
+    /// Provides a [`SchemaAdapter`] for the `projected_table_schema`, the
+    /// columns of the table read by a scan, out of the `table_schema`, all
+    /// columns of the table but the partition columns.
+    fn create(
+        &self,
+        projected_table_schema: SchemaRef,
+        table_schema: SchemaRef,
+    ) -> Box<dyn SchemaAdapter>;
+}
+
+/// Adapts file-level record batches to a table schema, which may have been
+/// obtained by merging multiple file-level schemas.
+///
+/// This is useful for enabling schema evolution in partitioned datasets.
+///
+/// This has to be done in two stages.
+///
+/// 1. Before reading the file, we have to map projected column indexes from
+///    the table schema to the file schema.
+///
+/// 2. After reading a record batch map the read columns back to the expected
+///    columns indexes and insert null-valued columns wherever the file schema
+///    was missing a column present in the table schema.
+pub trait SchemaAdapter: Send + Sync {
+    /// Map a column index in the projected table schema to a column index in
+    /// a particular file schema
+    ///
+    /// Panics if index is not in range for the projected table schema
+    fn map_column_index(&self, index: usize, file_schema: &Schema) -> Option<usize>;
+
+    /// Creates a [`SchemaMapper`] that can be used to cast or map the columns
+    /// from the file schema to the projected table schema.
+    ///
+    /// Returns the [`SchemaMapper`] along with an ordered list of the columns
+    /// to project from the file, to which the mapper applies
+    fn map_schema(
+        &self,
+        file_schema: &Schema,
+    ) -> Result<(Arc<dyn SchemaMapper>, Vec<usize>)>;
+}
+
+/// Maps the record batches read from a file, projected as returned by
+/// [`SchemaAdapter::map_schema`], to the projected table schema
+pub trait SchemaMapper: Debug + Send + Sync {
+    /// Adapts a `RecordBatch` to match the projected table schema
+    fn map_batch(&self, batch: RecordBatch) -> Result<RecordBatch>;
+}
+
+/// How to read a column of the table that a file does not contain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingColumnPolicy {
+    /// Read the column as nulls
+    #[default]
+    FillNull,
+    /// Fail the scan
+    Error,
+}
+
+impl FromStr for MissingColumnPolicy {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "fill_null" => Ok(Self::FillNull),
+            "error" => Ok(Self::Error),
+            _ => plan_err!(
+                "Invalid missing column policy '{s}', expected 'fill_null' or 'error'"
+            ),
+        }
+    }
+}
+
+/// How to read a column of a file that the table does not contain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExtraColumnPolicy {
+    /// Skip the column
+    #[default]
+    Ignore,
+    /// Fail the scan
+    Error,
+}
+
+impl FromStr for ExtraColumnPolicy {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ignore" => Ok(Self::Ignore),
+            "error" => Ok(Self::Error),
+            _ => plan_err!(
+                "Invalid extra column policy '{s}', expected 'ignore' or 'error'"
+            ),
+        }
+    }
+}
+
+/// The default [`SchemaAdapterFactory`]
+///
+/// Columns are matched by their Parquet field id if both the table and the
+/// file field have one, as columns may be renamed in table formats such as
+/// Apache Iceberg, and by name otherwise. This also applies to the fields of
+/// structs, including structs in lists, so that fields can be added to,
+/// removed from or renamed in nested structs.
+///
+/// Columns of different types are cast to the table type, e.g. to read an
+/// `Int32` column as `Int64` after the type of the column was widened.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultSchemaAdapterFactory {
+    missing_columns: MissingColumnPolicy,
+    extra_columns: ExtraColumnPolicy,
+}
+
+impl DefaultSchemaAdapterFactory {
+    /// Creates a factory with the policies configured in `options`
+    pub fn try_from_options(options: &ExecutionOptions) -> Result<Self> {
+        Ok(Self {
+            missing_columns: options.missing_column_policy.parse()?,
+            extra_columns: options.extra_column_policy.parse()?,
+        })
+    }
+
+    /// Sets how to read table columns, or fields of nested structs, that a
+    /// file does not contain
+    pub fn with_missing_columns(mut self, policy: MissingColumnPolicy) -> Self {
+        self.missing_columns = policy;
+        self
+    }
+
+    /// Sets how to read file columns, or fields of nested structs, that the
+    /// table does not contain
+    pub fn with_extra_columns(mut self, policy: ExtraColumnPolicy) -> Self {
+        self.extra_columns = policy;
+        self
+    }
+}
+
+/// Returns the factory set on a scan, or the [`DefaultSchemaAdapterFactory`]
+/// configured for the session
+pub(crate) fn schema_adapter_factory(
+    factory: Option<&Arc<dyn SchemaAdapterFactory>>,
+    context: &TaskContext,
+) -> Result<Arc<dyn SchemaAdapterFactory>> {
+    match factory {
+        Some(factory) => Ok(Arc::clone(factory)),
+        None => {
+            let options = &context.session_config().options().execution;
+            Ok(Arc::new(DefaultSchemaAdapterFactory::try_from_options(
+                options,
+            )?))
+        }
+    }
+}
+
+impl SchemaAdapterFactory for DefaultSchemaAdapterFactory {
+    fn create(
+        &self,
+        projected_table_schema: SchemaRef,
+        table_schema: SchemaRef,
+    ) -> Box<dyn SchemaAdapter> {
+        Box::new(DefaultSchemaAdapter {
+            projected_table_schema,
+            table_schema,
+            missing_columns: self.missing_columns,
+            extra_columns: self.extra_columns,
+        })
+    }
+}
+
+/// The [`SchemaAdapter`] of [`DefaultSchemaAdapterFactory`]
+#[derive(Clone, Debug)]
+struct DefaultSchemaAdapter {
+    projected_table_schema: SchemaRef,
+    table_schema: SchemaRef,
+    missing_columns: MissingColumnPolicy,
+    extra_columns: ExtraColumnPolicy,
+}
+
+impl SchemaAdapter for DefaultSchemaAdapter {
+    fn map_column_index(&self, index: usize, file_schema: &Schema) -> Option<usize> {
+        let field = self.projected_table_schema.field(index);
+        Some(find_field(file_schema.fields(), field)?.0)
+    }
+
+    fn map_schema(
+        &self,
+        file_schema: &Schema,
+    ) -> Result<(Arc<dyn SchemaMapper>, Vec<usize>)> {
+        let mut projection = Vec::with_capacity(file_schema.fields().len());
+        let mut field_mappings = vec![None; self.projected_table_schema.fields().len()];
+
+        for (file_idx, file_field) in file_schema.fields.iter().enumerate() {
+            if let Some((table_idx, table_field)) =
+                find_field(self.projected_table_schema.fields(), file_field)
+            {
+                self.check_field(file_field, table_field)?;
+                field_mappings[table_idx] = Some(projection.len());
+                projection.push(file_idx);
+            } else if self.extra_columns == ExtraColumnPolicy::Error
+                && find_field(self.table_schema.fields(), file_field).is_none()
+            {
+                return plan_err!(
+                    "File schema field {} is not in the table schema",
+                    file_field.name()
+                );
+            }
+        }
+        if self.missing_columns == MissingColumnPolicy::Error {
+            for table_field in self.table_schema.fields() {
+                if find_field(file_schema.fields(), table_field).is_none() {
+                    return plan_err!(
+                        "Table schema field {} is not in the file schema",
+                        table_field.name()
+                    );
+                }
+            }
+        }
+
+        Ok((
+            Arc::new(SchemaMapping {
+                table_schema: self.projected_table_schema.clone(),
+                field_mappings,
+            }),
+            projection,
+        ))
+    }
+}
+
+impl DefaultSchemaAdapter {
+    /// Checks that the values of `file_field` can be adapted to `table_field`
+    fn check_field(&self, file_field: &Field, table_field: &Field) -> Result<()> {
+        match (file_field.data_type(), table_field.data_type()) {
+            (DataType::Struct(file_fields), DataType::Struct(table_fields)) => {
+                for file_field in file_fields {
+                    match find_field(table_fields, file_field) {
+                        Some((_, table_field)) => {
+                            self.check_field(file_field, table_field)?
+                        }
+                        None if self.extra_columns == ExtraColumnPolicy::Error => {
+                            return plan_err!(
+                                "File schema field {} is not in the table schema",
+                                file_field.name()
+                            )
+                        }
+                        None => {}
+                    }
+                }
+                if self.missing_columns == MissingColumnPolicy::Error {
+                    for table_field in table_fields {
+                        if find_field(file_fields, table_field).is_none() {
+                            return plan_err!(
+                                "Table schema field {} is not in the file schema",
+                                table_field.name()
+                            );
+                        }
+                    }
+                }
+                Ok(())
+            }
+            (DataType::List(file_field), DataType::List(table_field))
+            | (DataType::LargeList(file_field), DataType::LargeList(table_field)) => {
+                self.check_field(file_field, table_field)
+            }
+            (file_type, table_type) if can_cast_types(file_type, table_type) => Ok(()),
+            (file_type, table_type) => plan_err!(
+                "Cannot cast file schema field {} of type {:?} to table schema field of type {:?}",
+                file_field.name(),
+                file_type,
+                table_type
+            ),
+        }
+    }
+}
+
+/// Returns the field of `fields` matching `field`, see
+/// [`DefaultSchemaAdapterFactory`]
+fn find_field<'a>(fields: &'a Fields, field: &Field) -> Option<(usize, &'a FieldRef)> {
+    let field_id = field.metadata().get(FIELD_ID_META_KEY);
+    fields.iter().enumerate().find(|(_, candidate)| {
+        match (field_id, candidate.metadata().get(FIELD_ID_META_KEY)) {
+            (Some(id), Some(candidate_id)) => id == candidate_id,
+            _ => field.name() == candidate.name(),
+        }
+    })
+}
+
+/// The [`SchemaMapper`] of [`DefaultSchemaAdapterFactory`], which holds a
+/// mapping from the file schema to the table schema
+#[derive(Debug)]
+struct SchemaMapping {
+    /// The schema of the table. This is the expected schema after conversion and it should match the schema of the query result.
+    table_schema: SchemaRef,
+    /// Mapping from field index in `table_schema` to index in projected file_schema
+    field_mappings: Vec<Option<usize>>,
+}
+
+impl SchemaMapper for SchemaMapping {
+    fn map_batch(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        let batch_rows = batch.num_rows();
+        let batch_cols = batch.columns().to_vec();
+
+        let cols = self
+            .table_schema
+            .fields()
+            .iter()
+            .zip(&self.field_mappings)
+            .map(|(field, file_idx)| match file_idx {
+                Some(batch_idx) => {
+                    adapt_array(&batch_cols[*batch_idx], field.data_type())
+                }
+                None => Ok(new_null_array(field.data_type(), batch_rows)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Necessary to handle empty batches
+        let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+
+        let schema = self.table_schema.clone();
+        let record_batch = RecordBatch::try_new_with_options(schema, cols, &options)?;
+        Ok(record_batch)
+    }
+}
+
+/// Adapts `array` to `data_type`, matching the fields of structs as columns
+/// and casting other values
+fn adapt_array(array: &ArrayRef, data_type: &DataType) -> Result<ArrayRef> {
+    match (array.data_type(), data_type) {
+        (from, to) if from == to => Ok(array.clone()),
+        (DataType::Struct(from_fields), DataType::Struct(to_fields)) => {
+            let array = array.as_struct();
+            let columns = to_fields
+                .iter()
+                .map(|to_field| match find_field(from_fields, to_field) {
+                    Some((idx, _)) => {
+                        adapt_array(array.column(idx), to_field.data_type())
+                    }
+                    None => Ok(new_null_array(to_field.data_type(), array.len())),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let nulls = array.nulls().cloned();
+            Ok(Arc::new(StructArray::try_new(
+                to_fields.clone(),
+                columns,
+                nulls,
+            )?))
+        }
+        (DataType::List(_), DataType::List(to_field)) => {
+            adapt_list(array.as_list::<i32>(), to_field)
+        }
+        (DataType::LargeList(_), DataType::LargeList(to_field)) => {
+            adapt_list(array.as_list::<i64>(), to_field)
+        }
+        (_, to) => Ok(cast(array, to)?),
+    }
+}
+
+fn adapt_list<O: OffsetSizeTrait>(
+    array: &GenericListArray<O>,
+    to_field: &FieldRef,
+) -> Result<ArrayRef> {
+    let values = adapt_array(array.values(), to_field.data_type())?;
+    Ok(Arc::new(GenericListArray::<O>::try_new(
+        to_field.clone(),
+        array.offsets().clone(),
+        values,
+        array.nulls().cloned(),
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::array::{BinaryArray, BooleanArray, Float32Array, Int32Array};
+    use arrow::array::{Int64Array, ListArray, StringArray, UInt64Array};
+    use arrow::datatypes::{Float32Type, Float64Type, Int32Type, Int64Type, UInt32Type};
+
+    fn adapter(table_schema: SchemaRef) -> Box<dyn SchemaAdapter> {
+        DefaultSchemaAdapterFactory::default().create(table_schema.clone(), table_schema)
+    }
+
+    #[test]
+    fn schema_mapping_map_batch() {
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("c1", DataType::Utf8, true),
+            Field::new("c2", DataType::UInt32, true),
+            Field::new("c3", DataType::Float64, true),
+        ]));
+
+        let adapter = adapter(table_schema.clone());
+
+        let file_schema = Schema::new(vec![
+            Field::new("c1", DataType::Utf8, true),
+            Field::new("c2", DataType::UInt64, true),
+            Field::new("c3", DataType::Float32, true),
+        ]);
+
+        let (mapping, _) = adapter.map_schema(&file_schema).expect("map schema failed");
+
+        let c1 = StringArray::from(vec!["hello", "world"]);
+        let c2 = UInt64Array::from(vec![9_u64, 5_u64]);
+        let c3 = Float32Array::from(vec![2.0_f32, 7.0_f32]);
+        let batch = RecordBatch::try_new(
+            Arc::new(file_schema),
+            vec![Arc::new(c1), Arc::new(c2), Arc::new(c3)],
+        )
+        .unwrap();
+
+        let mapped_batch = mapping.map_batch(batch).unwrap();
+
+        assert_eq!(mapped_batch.schema(), table_schema);
+        assert_eq!(mapped_batch.num_columns(), 3);
+        assert_eq!(mapped_batch.num_rows(), 2);
+
+        let c1 = mapped_batch.column(0).as_string::<i32>();
+        let c2 = mapped_batch.column(1).as_primitive::<UInt32Type>();
+        let c3 = mapped_batch.column(2).as_primitive::<Float64Type>();
+
+        assert_eq!(c1.value(0), "hello");
+        assert_eq!(c1.value(1), "world");
+        assert_eq!(c2.value(0), 9_u32);
+        assert_eq!(c2.value(1), 5_u32);
+        assert_eq!(c3.value(0), 2.0_f64);
+        assert_eq!(c3.value(1), 7.0_f64);
+    }
+
+    #[test]
+    fn schema_adapter_map_schema_with_projection() {
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("c0", DataType::Utf8, true),
+            Field::new("c1", DataType::Utf8, true),
+            Field::new("c2", DataType::Float64, true),
+            Field::new("c3", DataType::Int32, true),
+            Field::new("c4", DataType::Float32, true),
+        ]));
+
+        let file_schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("c1", DataType::Boolean, true),
+            Field::new("c2", DataType::Float32, true),
+            Field::new("c3", DataType::Binary, true),
+            Field::new("c4", DataType::Int64, true),
+        ]);
+
+        let indices = vec![1, 2, 4];
+        let schema = SchemaRef::from(table_schema.project(&indices).unwrap());
+        let adapter = adapter(schema);
+        let (mapping, projection) = adapter.map_schema(&file_schema).unwrap();
+
+        let id = Int32Array::from(vec![Some(1), Some(2), Some(3)]);
+        let c1 = BooleanArray::from(vec![Some(true), Some(false), Some(true)]);
+        let c2 = Float32Array::from(vec![Some(2.0_f32), Some(7.0_f32), Some(3.0_f32)]);
+        let c3 = BinaryArray::from_opt_vec(vec![
+            Some(b"hallo"),
+            Some(b"danke"),
+            Some(b"super"),
+        ]);
+        let c4 = Int64Array::from(vec![1, 2, 3]);
+        let batch = RecordBatch::try_new(
+            Arc::new(file_schema),
+            vec![
+                Arc::new(id),
+                Arc::new(c1),
+                Arc::new(c2),
+                Arc::new(c3),
+                Arc::new(c4),
+            ],
+        )
+        .unwrap();
+        let rows_num = batch.num_rows();
+        let projected = batch.project(&projection).unwrap();
+        let mapped_batch = mapping.map_batch(projected).unwrap();
+
+        assert_eq!(
+            mapped_batch.schema(),
+            Arc::new(table_schema.project(&indices).unwrap())
+        );
+        assert_eq!(mapped_batch.num_columns(), indices.len());
+        assert_eq!(mapped_batch.num_rows(), rows_num);
+
+        let c1 = mapped_batch.column(0).as_string::<i32>();
+        let c2 = mapped_batch.column(1).as_primitive::<Float64Type>();
+        let c4 = mapped_batch.column(2).as_primitive::<Float32Type>();
+
+        assert_eq!(c1.value(0), "true");
+        assert_eq!(c1.value(1), "false");
+        assert_eq!(c1.value(2), "true");
+
+        assert_eq!(c2.value(0), 2.0_f64);
+        assert_eq!(c2.value(1), 7.0_f64);
+        assert_eq!(c2.value(2), 3.0_f64);
+
+        assert_eq!(c4.value(0), 1.0_f32);
+        assert_eq!(c4.value(1), 2.0_f32);
+        assert_eq!(c4.value(2), 3.0_f32);
+    }
+
+    #[test]
+    fn schema_adapter_map_schema_by_field_id() {
+        let field = |name: &str, id: &str| {
+            Field::new(name, DataType::Int32, true)
+                .with_metadata([(FIELD_ID_META_KEY.to_string(), id.to_string())].into())
+        };
+        // `a` was renamed to `b`, and a new column `a` was added
+        let table_schema = Arc::new(Schema::new(vec![field("b", "1"), field("a", "2")]));
+        let file_schema = Schema::new(vec![field("a", "1")]);
+
+        let adapter = adapter(table_schema.clone());
+        assert_eq!(adapter.map_column_index(0, &file_schema), Some(0));
+        assert_eq!(adapter.map_column_index(1, &file_schema), None);
+
+        let (mapping, projection) = adapter.map_schema(&file_schema).unwrap();
+        assert_eq!(projection, vec![0]);
+        let batch = RecordBatch::try_new(
+            Arc::new(file_schema),
+            vec![Arc::new(Int32Array::from(vec![1, 2]))],
+        )
+        .unwrap();
+        let mapped_batch = mapping.map_batch(batch).unwrap();
+        assert_eq!(mapped_batch.schema(), table_schema);
+        assert_eq!(
+            mapped_batch.column(0).as_primitive::<Int32Type>().values(),
+            &[1, 2]
+        );
+        assert_eq!(mapped_batch.column(1).null_count(), 2);
+    }
+
+    #[test]
+    fn schema_adapter_nested_evolution() {
+        // the file struct has the fields `a: Int32` and `b: Utf8`, the table
+        // struct dropped `b`, widened `a` and added `c`
+        let file_fields = Fields::from(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let table_fields = Fields::from(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("c", DataType::Boolean, true),
+        ]);
+        let file_item = Arc::new(Field::new(
+            "item",
+            DataType::Struct(file_fields.clone()),
+            true,
+        ));
+        let table_item = Arc::new(Field::new(
+            "item",
+            DataType::Struct(table_fields.clone()),
+            true,
+        ));
+        let file_schema = Schema::new(vec![
+            Field::new("s", DataType::Struct(file_fields.clone()), true),
+            Field::new("l", DataType::List(file_item.clone()), true),
+        ]);
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("s", DataType::Struct(table_fields.clone()), true),
+            Field::new("l", DataType::List(table_item), true),
+        ]));
+
+        let s = StructArray::new(
+            file_fields,
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec!["x", "y"])),
+            ],
+            None,
+        );
+        let l = ListArray::new(
+            file_item,
+            arrow::buffer::OffsetBuffer::from_lengths([2, 0]),
+            Arc::new(s.clone()),
+            None,
+        );
+        let batch = RecordBatch::try_new(
+            Arc::new(file_schema.clone()),
+            vec![Arc::new(s), Arc::new(l)],
+        )
+        .unwrap();
+
+        let (mapping, projection) = adapter(table_schema.clone())
+            .map_schema(&file_schema)
+            .unwrap();
+        assert_eq!(projection, vec![0, 1]);
+        let mapped_batch = mapping.map_batch(batch).unwrap();
+        assert_eq!(mapped_batch.schema(), table_schema);
+
+        let s = mapped_batch.column(0).as_struct();
+        assert_eq!(s.column(0).as_primitive::<Int64Type>().values(), &[1, 2]);
+        assert_eq!(s.column(1).null_count(), 2);
+        let l = mapped_batch.column(1).as_list::<i32>();
+        assert_eq!(l.value_length(0), 2);
+        assert_eq!(l.values().as_struct().fields(), &table_fields);
+    }
+
+    #[test]
+    fn schema_adapter_policies() {
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+        ]));
+        let file_schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("c", DataType::Int32, true),
+        ]);
+        // only `a` is read, but `b` is missing and `c` is extra
+        let projected = Arc::new(table_schema.project(&[0]).unwrap());
+        let map_schema = |factory: DefaultSchemaAdapterFactory| {
+            factory
+                .create(projected.clone(), table_schema.clone())
+                .map_schema(&file_schema)
+                .map(|(_, projection)| projection)
+                .map_err(|e| e.strip_backtrace())
+        };
+
+        let factory = DefaultSchemaAdapterFactory::default();
+        assert_eq!(map_schema(factory), Ok(vec![0]));
+        assert_eq!(
+            map_schema(factory.with_missing_columns(MissingColumnPolicy::Error)),
+            Err(
+                "Error during planning: Table schema field b is not in the file schema"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            map_schema(factory.with_extra_columns(ExtraColumnPolicy::Error)),
+            Err(
+                "Error during planning: File schema field c is not in the table schema"
+                    .to_string()
+            )
+        );
+
+        let err = "null".parse::<MissingColumnPolicy>().unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: Invalid missing column policy 'null', expected 'fill_null' or 'error'"
+        );
+    }
+}
@@ -20,16 +20,21 @@
 use std::any::Any;
 use std::fmt::Formatter;
 use std::fs::{File, OpenOptions};
-use std::io::BufReader;
+use std::io::{BufReader, ErrorKind};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use arrow::ipc::reader::StreamReader;
 use arrow_array::{RecordBatch, RecordBatchReader, RecordBatchWriter};
-use arrow_schema::SchemaRef;
+use arrow_schema::{ArrowError, Schema, SchemaRef};
 use async_trait::async_trait;
 use futures::StreamExt;
 
+#[cfg(feature = "avro")]
+use datafusion_common::exec_err;
+#[cfg(not(feature = "avro"))]
+use datafusion_common::not_impl_err;
 use datafusion_common::{plan_err, Constraints, DataFusionError, Result};
 use datafusion_common_runtime::SpawnedTask;
 use datafusion_execution::{SendableRecordBatchStream, TaskContext};
@@ -77,6 +82,16 @@ pub enum StreamEncoding {
     Csv,
     /// Newline-delimited JSON records
     Json,
+    /// Arrow IPC streams
+    ///
+    /// Every write appends a new stream to the file, and the streams of a
+    /// file are read in sequence.
+    Arrow,
+    /// Avro object container files
+    ///
+    /// Writes to regular files already holding records append to them,
+    /// other writes start a new object container file.
+    Avro,
 }
 
 impl FromStr for StreamEncoding {
@@ -86,6 +101,8 @@ impl FromStr for StreamEncoding {
         match s.to_ascii_lowercase().as_str() {
             "csv" => Ok(Self::Csv),
             "json" => Ok(Self::Json),
+            "arrow" => Ok(Self::Arrow),
+            "avro" => Ok(Self::Avro),
             _ => plan_err!("Unrecognised StreamEncoding {}", s),
         }
     }
@@ -172,10 +189,31 @@ impl StreamConfig {
 
                 Ok(Box::new(reader))
             }
+            StreamEncoding::Arrow => {
+                Ok(Box::new(IpcStreamsReader::try_new(file, schema)?))
+            }
+            #[cfg(feature = "avro")]
+            StreamEncoding::Avro => {
+                let reader = crate::datasource::avro_to_arrow::Reader::try_new(
+                    BufReader::new(file),
+                    schema.clone(),
+                    self.batch_size,
+                    None,
+                )?;
+                check_avro_schema(&schema, reader.writer_schema())?;
+
+                Ok(Box::new(arrow::record_batch::RecordBatchIterator::new(
+                    reader, schema,
+                )))
+            }
+            #[cfg(not(feature = "avro"))]
+            StreamEncoding::Avro => {
+                not_impl_err!("Reading Avro streams requires the 'avro' feature")
+            }
         }
     }
 
-    fn writer(&self) -> Result<Box<dyn RecordBatchWriter>> {
+    fn writer(&self) -> Result<Box<dyn BatchWriter>> {
         match &self.encoding {
             StreamEncoding::Csv => {
                 let header = self.header && !self.location.exists();
@@ -196,8 +234,185 @@ impl StreamConfig {
                     .open(&self.location)?;
                 Ok(Box::new(arrow::json::LineDelimitedWriter::new(file)))
             }
+            StreamEncoding::Arrow => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.location)?;
+                let writer =
+                    arrow::ipc::writer::StreamWriter::try_new(file, &self.schema)?;
+                Ok(Box::new(writer))
+            }
+            #[cfg(feature = "avro")]
+            StreamEncoding::Avro => {
+                use crate::datasource::avro_to_arrow::Writer;
+
+                let marker = match std::fs::metadata(&self.location) {
+                    Ok(metadata) if metadata.is_file() && metadata.len() > 0 => {
+                        Some(self.avro_sync_marker()?)
+                    }
+                    _ => None,
+                };
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.location)?;
+                let writer = match marker {
+                    Some(marker) => {
+                        Writer::try_append_to(file, self.schema.clone(), marker)?
+                    }
+                    None => Writer::try_new(file, self.schema.clone())?,
+                };
+                Ok(Box::new(writer))
+            }
+            #[cfg(not(feature = "avro"))]
+            StreamEncoding::Avro => {
+                not_impl_err!("Writing Avro streams requires the 'avro' feature")
+            }
         }
     }
+
+    /// Checks the schema of the existing Avro object container file at
+    /// `location` and returns the sync marker its blocks end with
+    #[cfg(feature = "avro")]
+    fn avro_sync_marker(&self) -> Result<[u8; 16]> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = File::open(&self.location)?;
+        let reader = apache_avro::Reader::new(BufReader::new(&file))?;
+        check_avro_schema(&self.schema, reader.writer_schema())?;
+        drop(reader);
+
+        let mut marker = [0; 16];
+        file.seek(SeekFrom::End(-16))?;
+        file.read_exact(&mut marker)?;
+        Ok(marker)
+    }
+}
+
+/// Checks that the fields of `actual` match those of `expected`, ignoring
+/// nullability and metadata
+fn check_schema(expected: &Schema, actual: &Schema) -> Result<(), ArrowError> {
+    let matches = expected.fields().len() == actual.fields().len()
+        && expected
+            .fields()
+            .iter()
+            .zip(actual.fields())
+            .all(|(e, a)| e.name() == a.name() && e.data_type() == a.data_type());
+    if matches {
+        Ok(())
+    } else {
+        Err(ArrowError::SchemaError(format!(
+            "Stream schema {actual} does not match table schema {expected}"
+        )))
+    }
+}
+
+/// Checks that the records of an Avro file have the fields of `expected`,
+/// which are otherwise read as nulls
+#[cfg(feature = "avro")]
+fn check_avro_schema(expected: &Schema, actual: &apache_avro::Schema) -> Result<()> {
+    let apache_avro::Schema::Record(record) = actual else {
+        return exec_err!("Avro stream records must be of a record schema");
+    };
+    match expected
+        .fields()
+        .iter()
+        .find(|field| !record.lookup.contains_key(field.name()))
+    {
+        Some(field) => exec_err!(
+            "Avro stream records have no field {} of the table schema",
+            field.name()
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Reads the Arrow IPC streams of a file in sequence, as batches of a
+/// given schema
+struct IpcStreamsReader {
+    file: File,
+    reader: Option<StreamReader<File>>,
+    schema: SchemaRef,
+}
+
+impl IpcStreamsReader {
+    fn try_new(file: File, schema: SchemaRef) -> Result<Self> {
+        let reader = Self::next_stream(&file, &schema)?;
+        Ok(Self {
+            file,
+            reader,
+            schema,
+        })
+    }
+
+    /// Starts reading the next stream of `file`, if any
+    fn next_stream(
+        file: &File,
+        schema: &Schema,
+    ) -> Result<Option<StreamReader<File>>, ArrowError> {
+        // The streams are read unbuffered from a shared handle to the file,
+        // so that the next stream starts where the previous one ended
+        match StreamReader::try_new_unbuffered(file.try_clone()?, None) {
+            Ok(reader) => {
+                check_schema(schema, &reader.schema())?;
+                Ok(Some(reader))
+            }
+            Err(ArrowError::IoError(_, e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Iterator for IpcStreamsReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.reader.as_mut()?.next() {
+                Some(batch) => {
+                    return Some(batch.and_then(|batch| {
+                        RecordBatch::try_new(
+                            self.schema.clone(),
+                            batch.columns().to_vec(),
+                        )
+                    }))
+                }
+                None => match Self::next_stream(&self.file, &self.schema) {
+                    Ok(reader) => self.reader = reader,
+                    Err(e) => {
+                        self.reader = None;
+                        return Some(Err(e));
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl RecordBatchReader for IpcStreamsReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// A [`RecordBatchWriter`] that can be closed through a trait object
+trait BatchWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), ArrowError>;
+
+    fn close(self: Box<Self>) -> Result<(), ArrowError>;
+}
+
+impl<W: RecordBatchWriter> BatchWriter for W {
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), ArrowError> {
+        RecordBatchWriter::write(self, batch)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), ArrowError> {
+        RecordBatchWriter::close(*self)
+    }
 }
 
 /// A [`TableProvider`] for an unbounded stream source
@@ -350,6 +565,7 @@ impl DataSink for StreamWrite {
                 count += batch.num_rows() as u64;
                 writer.write(&batch)?;
             }
+            writer.close()?;
             Ok(count)
         });
 
@@ -362,3 +578,116 @@ impl DataSink for StreamWrite {
         write_task.join_unwind().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::SessionContext;
+
+    use arrow_array::{Int32Array, StringArray};
+    use arrow_schema::{DataType, Field};
+    use datafusion_common::assert_batches_eq;
+    use tempfile::TempDir;
+
+    fn test_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, true),
+        ]))
+    }
+
+    fn register_stream(
+        ctx: &SessionContext,
+        location: PathBuf,
+        encoding: StreamEncoding,
+    ) -> Result<()> {
+        let config = StreamConfig::new_file(test_schema(), location)
+            .with_encoding(encoding)
+            .with_batch_size(2);
+        ctx.register_table("t", Arc::new(StreamTable::new(Arc::new(config))))?;
+        Ok(())
+    }
+
+    /// Inserts into a stream table twice and reads the appended rows back
+    async fn insert_and_read(encoding: StreamEncoding) -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let ctx = SessionContext::new();
+        register_stream(&ctx, tmp_dir.path().join("stream"), encoding)?;
+
+        ctx.sql("INSERT INTO t VALUES (1, 'a'), (2, NULL), (3, 'c')")
+            .await?
+            .collect()
+            .await?;
+        ctx.sql("INSERT INTO t VALUES (4, 'd')")
+            .await?
+            .collect()
+            .await?;
+
+        let batches = ctx.sql("SELECT * FROM t").await?.collect().await?;
+        let expected = [
+            "+---+---+",
+            "| a | b |",
+            "+---+---+",
+            "| 1 | a |",
+            "| 2 |   |",
+            "| 3 | c |",
+            "| 4 | d |",
+            "+---+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn arrow_ipc_stream() -> Result<()> {
+        insert_and_read(StreamEncoding::Arrow).await
+    }
+
+    #[tokio::test]
+    async fn arrow_ipc_stream_schema_mismatch() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let location = tmp_dir.path().join("stream");
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("c", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["a"])),
+            ],
+        )?;
+        let mut writer =
+            arrow::ipc::writer::StreamWriter::try_new(File::create(&location)?, &schema)?;
+        writer.write(&batch)?;
+        writer.finish()?;
+
+        let ctx = SessionContext::new();
+        register_stream(&ctx, location, StreamEncoding::Arrow)?;
+        let err = ctx
+            .sql("SELECT * FROM t")
+            .await?
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("does not match table schema"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "avro")]
+    #[tokio::test]
+    async fn avro_stream() -> Result<()> {
+        insert_and_read(StreamEncoding::Avro).await
+    }
+
+    #[test]
+    fn parse_encoding() {
+        assert!(matches!("ARROW".parse(), Ok(StreamEncoding::Arrow)));
+        assert!(matches!("avro".parse(), Ok(StreamEncoding::Avro)));
+        assert!("parquet".parse::<StreamEncoding>().is_err());
+    }
+}
@@ -0,0 +1,39 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This module contains code for reading the worksheets of [XLSX] workbooks
+//! into `RecordBatch`es
+//!
+//! [XLSX]: https://en.wikipedia.org/wiki/Office_Open_XML
+
+#[cfg(feature = "xlsx")]
+mod reader;
+
+#[cfg(feature = "xlsx")]
+pub use reader::{read_xlsx_schema, Reader};
+
+#[cfg(not(feature = "xlsx"))]
+/// Infer the schema of the worksheet selected by `options` in the XLSX
+/// workbook `bytes` (requires the xlsx feature)
+pub fn read_xlsx_schema(
+    _bytes: &[u8],
+    _options: &datafusion_common::config::XlsxOptions,
+) -> crate::error::Result<crate::arrow::datatypes::Schema> {
+    Err(crate::error::DataFusionError::NotImplemented(
+        "cannot read xlsx schema without the 'xlsx' feature enabled".to_string(),
+    ))
+}
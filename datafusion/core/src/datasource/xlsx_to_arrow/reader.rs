@@ -0,0 +1,341 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use crate::arrow::array::{
+    ArrayRef, BooleanArray, Date32Array, DurationMillisecondArray, Float64Array,
+    Int64Array, RecordBatch, RecordBatchOptions, StringArray, TimestampMillisecondArray,
+};
+use crate::arrow::compute::{cast_with_options, CastOptions};
+use crate::arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use crate::arrow::error::ArrowError;
+use crate::error::{DataFusionError, Result};
+
+use calamine::{Data, Range, Reader as _, Xlsx};
+use chrono::NaiveDate;
+use datafusion_common::config::XlsxOptions;
+
+/// Infer the schema of the worksheet selected by `options` in the XLSX
+/// workbook `bytes`.
+///
+/// Columns are named after the header row, or `column_1`, `column_2`, ...
+/// without one. Their types are inferred from the first
+/// `options.schema_infer_max_rec` rows: numeric columns whose values are all
+/// whole numbers are read as `Int64`, date columns whose values have no time
+/// of day as `Date32`, and columns of mixed values as `Utf8`.
+pub fn read_xlsx_schema(bytes: &[u8], options: &XlsxOptions) -> Result<Schema> {
+    let range = read_worksheet(bytes, options)?;
+    let names = column_names(&range, options);
+    let mut types: Vec<Option<DataType>> = vec![None; names.len()];
+    for row in range
+        .rows()
+        .skip(data_start(options))
+        .take(options.schema_infer_max_rec)
+    {
+        for (cell, data_type) in row.iter().zip(types.iter_mut()) {
+            if let Some(cell_type) = infer_cell_type(cell) {
+                *data_type = Some(match data_type.take() {
+                    Some(data_type) => merge_types(data_type, cell_type),
+                    None => cell_type,
+                });
+            }
+        }
+    }
+
+    let fields = names
+        .into_iter()
+        .zip(types)
+        .map(|(name, data_type)| {
+            Field::new(name, data_type.unwrap_or(DataType::Utf8), true)
+        })
+        .collect::<Vec<_>>();
+    Ok(Schema::new(fields))
+}
+
+/// Reads the rows of the worksheet selected by [`XlsxOptions`] as
+/// [`RecordBatch`]es of a given schema. Columns are matched by name, and
+/// columns of the schema missing from the worksheet are read as nulls.
+pub struct Reader {
+    range: Range<Data>,
+    schema: SchemaRef,
+    /// The worksheet column of each field of `schema`
+    columns: Vec<Option<usize>>,
+    batch_size: usize,
+    next_row: usize,
+}
+
+impl Reader {
+    /// Create a new [`Reader`] of the XLSX workbook `bytes`
+    pub fn try_new(
+        bytes: &[u8],
+        options: &XlsxOptions,
+        schema: SchemaRef,
+        batch_size: usize,
+    ) -> Result<Self> {
+        let range = read_worksheet(bytes, options)?;
+        let names = column_names(&range, options);
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| names.iter().position(|name| name == field.name()))
+            .collect();
+        Ok(Self {
+            range,
+            schema,
+            columns,
+            batch_size,
+            next_row: data_start(options),
+        })
+    }
+
+    fn decode(&self, rows: &[&[Data]]) -> Result<RecordBatch, ArrowError> {
+        let arrays = self
+            .schema
+            .fields()
+            .iter()
+            .zip(&self.columns)
+            .map(|(field, column)| {
+                let cells = rows
+                    .iter()
+                    .map(|row| column.and_then(|column| row.get(column)))
+                    .map(|cell| cell.filter(|cell| !is_null(cell)))
+                    .collect::<Vec<_>>();
+                decode_column(&cells, field)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+        RecordBatch::try_new_with_options(self.schema.clone(), arrays, &options)
+    }
+}
+
+impl Iterator for Reader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rows = self
+            .range
+            .rows()
+            .skip(self.next_row)
+            .take(self.batch_size)
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return None;
+        }
+        self.next_row += rows.len();
+        Some(self.decode(&rows))
+    }
+}
+
+/// Reads the cells of the worksheet selected by `options`
+fn read_worksheet(bytes: &[u8], options: &XlsxOptions) -> Result<Range<Data>> {
+    let mut workbook = Xlsx::new(Cursor::new(bytes))
+        .map_err(|e| DataFusionError::External(Box::new(e)))?;
+    let range = match &options.sheet_name {
+        Some(sheet_name) => workbook.worksheet_range(sheet_name),
+        None => workbook.worksheet_range_at(0).ok_or_else(|| {
+            DataFusionError::Execution("XLSX workbook has no worksheets".to_string())
+        })?,
+    };
+    range.map_err(|e| DataFusionError::External(Box::new(e)))
+}
+
+/// The index of the first data row of a worksheet
+fn data_start(options: &XlsxOptions) -> usize {
+    options.skip_rows + usize::from(options.has_header)
+}
+
+/// The names of the columns of a worksheet
+fn column_names(range: &Range<Data>, options: &XlsxOptions) -> Vec<String> {
+    let header = options
+        .has_header
+        .then(|| range.rows().nth(options.skip_rows))
+        .flatten();
+    (0..range.width())
+        .map(|i| {
+            header
+                .and_then(|header| header.get(i))
+                .map(|cell| cell.to_string().trim().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("column_{}", i + 1))
+        })
+        .collect()
+}
+
+fn is_null(cell: &Data) -> bool {
+    matches!(cell, Data::Empty | Data::Error(_))
+}
+
+/// Returns the type of the value of `cell`, or `None` if it is empty
+fn infer_cell_type(cell: &Data) -> Option<DataType> {
+    Some(match cell {
+        Data::Int(_) => DataType::Int64,
+        Data::Float(v) if v.fract() == 0.0 && v.abs() < i64::MAX as f64 => {
+            DataType::Int64
+        }
+        Data::Float(_) => DataType::Float64,
+        Data::Bool(_) => DataType::Boolean,
+        Data::DateTime(v) if v.is_duration() => DataType::Duration(TimeUnit::Millisecond),
+        Data::DateTime(v) if v.as_f64().fract() == 0.0 => DataType::Date32,
+        Data::DateTime(_) => DataType::Timestamp(TimeUnit::Millisecond, None),
+        Data::String(_) | Data::DateTimeIso(_) | Data::DurationIso(_) => DataType::Utf8,
+        Data::Empty | Data::Error(_) => return None,
+    })
+}
+
+/// Returns a type able to represent the values of both `a` and `b`
+fn merge_types(a: DataType, b: DataType) -> DataType {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => {
+            DataType::Float64
+        }
+        (DataType::Date32, DataType::Timestamp(unit, tz))
+        | (DataType::Timestamp(unit, tz), DataType::Date32) => {
+            DataType::Timestamp(unit, tz)
+        }
+        _ => DataType::Utf8,
+    }
+}
+
+/// Returns the value of `cell` as a string
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(v) if v.is_duration() => v
+            .as_duration()
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| v.to_string()),
+        Data::DateTime(v) => v
+            .as_datetime()
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| v.to_string()),
+        cell => cell.to_string(),
+    }
+}
+
+/// Decodes the non-null `cells` of a column into an array of `field`'s type
+fn decode_column(cells: &[Option<&Data>], field: &Field) -> Result<ArrayRef, ArrowError> {
+    Ok(match field.data_type() {
+        DataType::Int64 => Arc::new(decode_values::<_, Int64Array>(
+            cells,
+            field,
+            |cell| match cell {
+                Data::Int(v) => Some(*v),
+                Data::Float(v) if v.fract() == 0.0 => Some(*v as i64),
+                _ => None,
+            },
+        )?),
+        DataType::Float64 => Arc::new(decode_values::<_, Float64Array>(
+            cells,
+            field,
+            |cell| match cell {
+                Data::Int(v) => Some(*v as f64),
+                Data::Float(v) => Some(*v),
+                _ => None,
+            },
+        )?),
+        DataType::Boolean => Arc::new(decode_values::<_, BooleanArray>(
+            cells,
+            field,
+            |cell| match cell {
+                Data::Bool(v) => Some(*v),
+                _ => None,
+            },
+        )?),
+        DataType::Date32 => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+            Arc::new(decode_values::<_, Date32Array>(
+                cells,
+                field,
+                |cell| match cell {
+                    Data::DateTime(v) if !v.is_duration() => {
+                        let days = v.as_datetime()?.date().signed_duration_since(epoch);
+                        i32::try_from(days.num_days()).ok()
+                    }
+                    _ => None,
+                },
+            )?)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, None) => {
+            Arc::new(decode_values::<_, TimestampMillisecondArray>(
+                cells,
+                field,
+                |cell| match cell {
+                    Data::DateTime(v) if !v.is_duration() => {
+                        Some(v.as_datetime()?.and_utc().timestamp_millis())
+                    }
+                    _ => None,
+                },
+            )?)
+        }
+        DataType::Duration(TimeUnit::Millisecond) => {
+            Arc::new(decode_values::<_, DurationMillisecondArray>(
+                cells,
+                field,
+                |cell| match cell {
+                    Data::DateTime(v) if v.is_duration() => {
+                        Some(v.as_duration()?.num_milliseconds())
+                    }
+                    _ => None,
+                },
+            )?)
+        }
+        data_type => {
+            let strings: ArrayRef = Arc::new(
+                cells
+                    .iter()
+                    .map(|cell| cell.map(cell_to_string))
+                    .collect::<StringArray>(),
+            );
+            if data_type == &DataType::Utf8 {
+                strings
+            } else {
+                let options = CastOptions {
+                    safe: false,
+                    ..Default::default()
+                };
+                cast_with_options(&strings, data_type, &options)?
+            }
+        }
+    })
+}
+
+/// Decodes the non-null `cells` of a column with `decode`, which returns
+/// `None` for cells that can not be represented as `field`'s type
+fn decode_values<T, A: FromIterator<Option<T>>>(
+    cells: &[Option<&Data>],
+    field: &Field,
+    decode: impl Fn(&Data) -> Option<T>,
+) -> Result<A, ArrowError> {
+    cells
+        .iter()
+        .map(|cell| {
+            cell.map(|cell| {
+                decode(cell).ok_or_else(|| {
+                    ArrowError::ParseError(format!(
+                        "Error while parsing value {} of XLSX column {} as {}",
+                        cell_to_string(cell),
+                        field.name(),
+                        field.data_type()
+                    ))
+                })
+            })
+            .transpose()
+        })
+        .collect()
+}
@@ -41,14 +41,16 @@ use crate::{
         object_store::ObjectStoreUrl,
         provider::{DefaultTableFactory, TableProviderFactory},
     },
-    datasource::{provider_as_source, MemTable, TableProvider, ViewTable},
+    datasource::{
+        provider_as_source, source_as_provider, MemTable, TableProvider, ViewTable,
+    },
     error::{DataFusionError, Result},
     execution::{options::ArrowReadOptions, runtime_env::RuntimeEnv, FunctionRegistry},
     logical_expr::{
         CreateCatalog, CreateCatalogSchema, CreateExternalTable, CreateFunction,
         CreateMemoryTable, CreateView, DropCatalogSchema, DropFunction, DropTable,
         DropView, Explain, LogicalPlan, LogicalPlanBuilder, PlanType, SetVariable,
-        TableSource, TableType, ToStringifiedPlan, UNNAMED_TABLE,
+        TableSource, TableType, TableVersion, ToStringifiedPlan, UNNAMED_TABLE,
     },
     optimizer::analyzer::{Analyzer, AnalyzerRule},
     optimizer::optimizer::{Optimizer, OptimizerConfig, OptimizerRule},
@@ -64,10 +66,12 @@ use arrow_schema::Schema;
 use datafusion_common::{
     alias::AliasGenerator,
     config::{ConfigExtension, TableOptions},
+    display::RuleChangeRecorder,
     exec_err, not_impl_err, plan_datafusion_err, plan_err,
     tree_node::{TreeNode, TreeNodeRecursion, TreeNodeVisitor},
     OwnedTableReference, SchemaReference,
 };
+use datafusion_execution::progress::{ProgressRegistry, ProgressReport};
 use datafusion_execution::registry::SerializerRegistry;
 use datafusion_expr::{
     logical_plan::{DdlStatement, Statement},
@@ -76,7 +80,10 @@ use datafusion_expr::{
 };
 use datafusion_sql::{
     parser::{CopyToSource, CopyToStatement, DFParser},
-    planner::{object_name_to_table_reference, ContextProvider, ParserOptions, SqlToRel},
+    planner::{
+        object_name_to_table_reference, ContextProvider, ParserOptions, PlannerContext,
+        SqlToRel,
+    },
     ResolvedTableReference, TableReference,
 };
 
@@ -355,6 +362,16 @@ impl SessionContext {
         self.state.read().runtime_env.clone()
     }
 
+    /// Returns the progress of the queries of this `SessionContext` that are
+    /// currently running, such as the rows and bytes they scanned so far
+    /// compared to the size of their input.
+    ///
+    /// Queries are tracked when executed through a [`DataFrame`], or with a
+    /// [`TaskContext`] created by [`DataFrame::task_ctx`].
+    pub fn running_queries(&self) -> Vec<ProgressReport> {
+        self.state.read().progress_registry.running()
+    }
+
     /// Returns an id that uniquely identifies this `SessionContext`.
     pub fn session_id(&self) -> String {
         self.session_id.clone()
@@ -1350,6 +1367,8 @@ pub struct SessionState {
     table_factories: HashMap<String, Arc<dyn TableProviderFactory>>,
     /// Runtime environment
     runtime_env: Arc<RuntimeEnv>,
+    /// Progress of the running queries of this session, shared by its clones
+    progress_registry: Arc<ProgressRegistry>,
 
     /// [FunctionFactory] to support pluggable user defined function handler.
     ///
@@ -1402,6 +1421,18 @@ impl SessionState {
         table_factories.insert("NDJSON".into(), Arc::new(DefaultTableFactory::new()));
         table_factories.insert("AVRO".into(), Arc::new(DefaultTableFactory::new()));
         table_factories.insert("ARROW".into(), Arc::new(DefaultTableFactory::new()));
+        table_factories.insert("XLSX".into(), Arc::new(DefaultTableFactory::new()));
+        table_factories.insert("FIXEDWIDTH".into(), Arc::new(DefaultTableFactory::new()));
+        #[cfg(feature = "delta")]
+        table_factories.insert(
+            "DELTA".into(),
+            Arc::new(crate::datasource::delta::DeltaTableFactory::new()),
+        );
+        #[cfg(feature = "iceberg")]
+        table_factories.insert(
+            "ICEBERG".into(),
+            Arc::new(crate::datasource::iceberg::IcebergTableFactory::new()),
+        );
 
         if config.create_default_catalog_and_schema() {
             let default_catalog = MemoryCatalogProvider::new();
@@ -1444,6 +1475,7 @@ impl SessionState {
             config,
             execution_props: ExecutionProps::new(),
             runtime_env: runtime,
+            progress_registry: Arc::new(ProgressRegistry::new()),
             table_factories,
             function_factory: None,
         };
@@ -1794,12 +1826,17 @@ impl SessionState {
         let mut provider = SessionContextProvider {
             state: self,
             tables: HashMap::with_capacity(references.len()),
+            versions: HashMap::new(),
         };
 
         let enable_ident_normalization =
             self.config.options().sql_parser.enable_ident_normalization;
         let parse_float_as_decimal =
             self.config.options().sql_parser.parse_float_as_decimal;
+        let options = || ParserOptions {
+            parse_float_as_decimal,
+            enable_ident_normalization,
+        };
         for reference in references {
             let table = reference.table();
             let resolved = self.resolve_table_ref(&reference);
@@ -1812,16 +1849,91 @@ impl SessionState {
             }
         }
 
-        let query = SqlToRel::new_with_options(
-            &provider,
-            ParserOptions {
-                parse_float_as_decimal,
-                enable_ident_normalization,
-            },
-        );
+        // Reading a version of a table is async as well, so the versions read
+        // in this query are pre-fetched too
+        let versions = Self::resolve_table_versions(&statement);
+        if !versions.is_empty() {
+            let query = SqlToRel::new_with_options(&provider, options());
+            let versions = versions
+                .into_iter()
+                .map(|(name, version)| {
+                    let reference =
+                        object_name_to_table_reference(name, enable_ident_normalization)?;
+                    let name = self.resolve_table_ref(reference).to_string();
+                    let version =
+                        query.table_version(version, &mut PlannerContext::new())?;
+                    Ok((name, version))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            for key in versions {
+                if provider.versions.contains_key(&key) {
+                    continue;
+                }
+                if let Some(source) = provider.tables.get(&key.0) {
+                    let table = source_as_provider(source)?
+                        .with_version(self, key.1)
+                        .await?;
+                    provider.versions.insert(key, provider_as_source(table));
+                }
+            }
+        }
+
+        let query = SqlToRel::new_with_options(&provider, options());
         query.statement_to_plan(statement)
     }
 
+    /// Returns the tables read at a version in the SQL statement, with their
+    /// unresolved versions
+    fn resolve_table_versions(
+        statement: &datafusion_sql::parser::Statement,
+    ) -> Vec<(sqlparser::ast::ObjectName, sqlparser::ast::TableVersion)> {
+        use datafusion_sql::parser::Statement as DFStatement;
+        use sqlparser::ast::{ObjectName, TableFactor, TableVersion, Visit, Visitor};
+
+        struct VersionVisitor(Vec<(ObjectName, TableVersion)>);
+
+        impl Visitor for VersionVisitor {
+            type Break = ();
+
+            fn pre_visit_table_factor(
+                &mut self,
+                table_factor: &TableFactor,
+            ) -> ControlFlow<()> {
+                if let TableFactor::Table {
+                    name,
+                    version: Some(version),
+                    ..
+                } = table_factor
+                {
+                    self.0.push((name.clone(), version.clone()));
+                }
+                ControlFlow::Continue(())
+            }
+        }
+
+        fn visit_statement(statement: &DFStatement, visitor: &mut VersionVisitor) {
+            match statement {
+                DFStatement::Statement(s) => {
+                    let _ = s.as_ref().visit(visitor);
+                }
+                DFStatement::CopyTo(CopyToStatement {
+                    source: CopyToSource::Query(query),
+                    ..
+                }) => {
+                    let _ = query.visit(visitor);
+                }
+                DFStatement::Explain(explain) => {
+                    visit_statement(&explain.statement, visitor)
+                }
+                _ => {}
+            }
+        }
+
+        let mut visitor = VersionVisitor(vec![]);
+        visit_statement(statement, &mut visitor);
+        visitor.0
+    }
+
     /// Creates a [`LogicalPlan`] from the provided SQL string. This
     /// interface will plan any SQL DataFusion supports, including DML
     /// like `CREATE TABLE`, and `COPY` (which can write to local
@@ -1843,6 +1955,12 @@ impl SessionState {
         if let LogicalPlan::Explain(e) = plan {
             let mut stringified_plans = e.stringified_plans.clone();
 
+            // with `EXPLAIN (RULES on)` only capture the rules changing the plan
+            let mut rule_changes = e
+                .options
+                .rules
+                .then(|| RuleChangeRecorder::new(e.plan.display_indent()));
+
             // analyze & capture output of each rule
             let analyzer_result = self.analyzer.execute_and_check(
                 e.plan.as_ref(),
@@ -1850,7 +1968,14 @@ impl SessionState {
                 |analyzed_plan, analyzer| {
                     let analyzer_name = analyzer.name().to_string();
                     let plan_type = PlanType::AnalyzedLogicalPlan { analyzer_name };
-                    stringified_plans.push(analyzed_plan.to_stringified(plan_type));
+                    match &mut rule_changes {
+                        Some(rule_changes) => stringified_plans.extend(
+                            rule_changes
+                                .record(plan_type, analyzed_plan.display_indent()),
+                        ),
+                        None => stringified_plans
+                            .push(analyzed_plan.to_stringified(plan_type)),
+                    }
                 },
             );
             let analyzed_plan = match analyzer_result {
@@ -1866,23 +1991,38 @@ impl SessionState {
                         stringified_plans,
                         schema: e.schema.clone(),
                         logical_optimization_succeeded: false,
+                        options: e.options,
                     }));
                 }
                 Err(e) => return Err(e),
             };
 
             // to delineate the analyzer & optimizer phases in explain output
-            stringified_plans
-                .push(analyzed_plan.to_stringified(PlanType::FinalAnalyzedLogicalPlan));
+            if !e.options.rules {
+                stringified_plans.push(
+                    analyzed_plan.to_stringified(PlanType::FinalAnalyzedLogicalPlan),
+                );
+            }
 
             // optimize the child plan, capturing the output of each optimizer
+            let mut rule_changes = e
+                .options
+                .rules
+                .then(|| RuleChangeRecorder::new(analyzed_plan.display_indent()));
             let optimized_plan = self.optimizer.optimize(
                 &analyzed_plan,
                 self,
                 |optimized_plan, optimizer| {
                     let optimizer_name = optimizer.name().to_string();
                     let plan_type = PlanType::OptimizedLogicalPlan { optimizer_name };
-                    stringified_plans.push(optimized_plan.to_stringified(plan_type));
+                    match &mut rule_changes {
+                        Some(rule_changes) => stringified_plans.extend(
+                            rule_changes
+                                .record(plan_type, optimized_plan.display_indent()),
+                        ),
+                        None => stringified_plans
+                            .push(optimized_plan.to_stringified(plan_type)),
+                    }
                 },
             );
             let (plan, logical_optimization_succeeded) = match optimized_plan {
@@ -1902,6 +2042,7 @@ impl SessionState {
                 stringified_plans,
                 schema: e.schema.clone(),
                 logical_optimization_succeeded,
+                options: e.options,
             }))
         } else {
             let analyzed_plan =
@@ -1938,6 +2079,12 @@ impl SessionState {
         &self.runtime_env
     }
 
+    /// Return the [`ProgressRegistry`] tracking the running queries of this
+    /// session
+    pub fn progress_registry(&self) -> &Arc<ProgressRegistry> {
+        &self.progress_registry
+    }
+
     /// Return the execution properties
     pub fn execution_props(&self) -> &ExecutionProps {
         &self.execution_props
@@ -2003,6 +2150,8 @@ impl SessionState {
 struct SessionContextProvider<'a> {
     state: &'a SessionState,
     tables: HashMap<String, Arc<dyn TableSource>>,
+    /// The tables read at a version, keyed by their resolved name and version
+    versions: HashMap<(String, TableVersion), Arc<dyn TableSource>>,
 }
 
 impl<'a> ContextProvider for SessionContextProvider<'a> {
@@ -2030,6 +2179,18 @@ impl<'a> ContextProvider for SessionContextProvider<'a> {
         Ok(provider_as_source(provider))
     }
 
+    fn get_table_source_with_version(
+        &self,
+        name: TableReference,
+        version: TableVersion,
+    ) -> Result<Arc<dyn TableSource>> {
+        let name = self.state.resolve_table_ref(name).to_string();
+        self.versions
+            .get(&(name.clone(), version))
+            .cloned()
+            .ok_or_else(|| plan_datafusion_err!("table '{name}' not found"))
+    }
+
     /// Create a new CTE work table for a recursive CTE logical plan
     /// This table will be used in conjunction with a Worktable physical plan
     /// to read and write each iteration of a recursive CTE
@@ -2335,20 +2496,29 @@ mod tests {
     use std::env;
     use std::path::PathBuf;
     use std::sync::Weak;
+    use std::time::Duration;
 
     use super::{super::options::CsvReadOptions, *};
     use crate::assert_batches_eq;
+    use crate::datasource::streaming::StreamingTable;
+    use crate::execution::cancellation::CancellationToken;
     use crate::execution::context::QueryPlanner;
     use crate::execution::memory_pool::MemoryConsumer;
     use crate::execution::runtime_env::RuntimeConfig;
+    use crate::execution::scheduler::QueryScheduler;
+    use crate::physical_plan::stream::RecordBatchStreamAdapter;
+    use crate::physical_plan::streaming::PartitionStream;
+    use crate::physical_plan::SendableRecordBatchStream;
     use crate::test;
     use crate::test_util::{plan_and_collect, populate_csv_partitions};
     use crate::variable::VarType;
 
+    use arrow_schema::Field;
     use datafusion_common_runtime::SpawnedTask;
-    use datafusion_expr::Expr;
+    use datafusion_expr::{col, Expr};
 
     use async_trait::async_trait;
+    use futures::TryStreamExt;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -2386,6 +2556,146 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn running_queries() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let partition_count = 4;
+        let ctx = create_ctx(&tmp_dir, partition_count).await?;
+        assert!(ctx.running_queries().is_empty());
+
+        let df = ctx.sql("SELECT c1, c2 FROM test WHERE c3").await?;
+        let task_ctx = Arc::new(df.task_ctx());
+        let progress = task_ctx.query_progress().unwrap().clone();
+        let plan = df.create_physical_plan().await?;
+        let mut stream = crate::physical_plan::execute_stream(plan, task_ctx)?;
+
+        let running = ctx.running_queries();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].query_id, progress.query_id());
+
+        // the scans are started by the first poll
+        assert!(stream.try_next().await?.is_some());
+        let running = ctx.running_queries();
+        assert!(running[0].rows_scanned > 0);
+        assert!(running[0].estimated_bytes.unwrap() > 0);
+
+        while stream.try_next().await?.is_some() {}
+        assert!(ctx.running_queries().is_empty());
+
+        let report = progress.report();
+        assert_eq!(report.bytes_scanned, report.estimated_bytes.unwrap());
+        assert_eq!(report.rows_scanned, 40);
+        assert_eq!(report.fraction(), Some(1.0));
+        assert!(report.active_operators.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_scheduler() -> Result<()> {
+        let scheduler = Arc::new(QueryScheduler::new(1));
+        let runtime = RuntimeConfig::new().with_query_scheduler(scheduler.clone());
+        let ctx = SessionContext::new_with_config_rt(
+            SessionConfig::new(),
+            Arc::new(RuntimeEnv::new(runtime)?),
+        );
+
+        let first = ctx.sql("SELECT 1").await?.execute_stream().await?;
+        assert_eq!(scheduler.running(), 1);
+
+        ctx.sql("SET datafusion.execution.query_priority = 'high'")
+            .await?;
+        let df = ctx.sql("SELECT 2").await?;
+        let mut second = Box::pin(df.collect());
+        assert!(futures::poll!(&mut second).is_pending());
+        assert_eq!(scheduler.queued(), 1);
+
+        // the first query runs until its stream is exhausted
+        let batches = first.try_collect::<Vec<_>>().await?;
+        assert_eq!(batches.len(), 1);
+        assert_eq!(second.await?.len(), 1);
+        assert_eq!(scheduler.running(), 0);
+
+        ctx.sql("SET datafusion.execution.query_priority = 'urgent'")
+            .await?;
+        let err = ctx.sql("SELECT 3").await?.collect().await.unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: Unknown query priority 'urgent', expected one of 'low', 'normal' or 'high'"
+        );
+        Ok(())
+    }
+
+    /// A partition that never produces data
+    struct PendingPartition(SchemaRef);
+
+    impl PartitionStream for PendingPartition {
+        fn schema(&self) -> &SchemaRef {
+            &self.0
+        }
+
+        fn execute(&self, _: Arc<TaskContext>) -> SendableRecordBatchStream {
+            let stream = futures::stream::pending();
+            Box::pin(RecordBatchStreamAdapter::new(self.0.clone(), stream))
+        }
+    }
+
+    #[tokio::test]
+    async fn dataframe_cancellation_token() -> Result<()> {
+        let ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let partition = Arc::new(PendingPartition(schema.clone()));
+        let table = StreamingTable::try_new(schema, vec![partition])?;
+        ctx.register_table("pending", Arc::new(table))?;
+
+        // the token still cancels the query with a query timeout
+        ctx.sql("SET datafusion.execution.query_timeout = '1h'")
+            .await?;
+        let token = CancellationToken::new();
+        let df = ctx
+            .table("pending")
+            .await?
+            .with_cancellation_token(token.clone())
+            .sort(vec![col("a").sort(true, true)])?;
+        let task = SpawnedTask::spawn(async move { df.collect().await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        token.cancel();
+
+        let err = task.join().await.unwrap().unwrap_err();
+        assert_eq!(err.strip_backtrace(), "Cancelled: the query was cancelled");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_timeout() -> Result<()> {
+        let ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let partition = Arc::new(PendingPartition(schema.clone()));
+        let table = StreamingTable::try_new(schema, vec![partition])?;
+        ctx.register_table("pending", Arc::new(table))?;
+
+        ctx.sql("SET datafusion.execution.query_timeout = '10ms'")
+            .await?;
+        let df = ctx.sql("SELECT a FROM pending ORDER BY a").await?;
+        let err = df.collect().await.unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Cancelled: the query exceeded its timeout of 10ms"
+        );
+
+        let err = ctx
+            .sql("SET datafusion.execution.query_timeout = '10 days'")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Unsupported duration unit 'days'"));
+
+        let err = ctx
+            .sql("SET datafusion.execution.query_timeout = '18446744073709551615h'")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("is too large"), "{err}");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn create_variable_expr() -> Result<()> {
         let tmp_dir = TempDir::new()?;
@@ -0,0 +1,598 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`AdaptiveExecution`] re-optimizes the hash joins of a plan during
+//! execution, from the actual statistics of their inputs
+
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::config::ConfigOptions;
+use crate::error::Result;
+use crate::execution::context::TaskContext;
+use crate::execution::memory_pool::{MemoryConsumer, MemoryReservation};
+use crate::physical_optimizer::coalesce_batches::CoalesceBatches;
+use crate::physical_optimizer::enforce_distribution::EnforceDistribution;
+use crate::physical_optimizer::enforce_sorting::EnforceSorting;
+use crate::physical_optimizer::join_selection::JoinSelection;
+use crate::physical_optimizer::skew_mitigation::SkewMitigation;
+use crate::physical_optimizer::PhysicalOptimizerRule;
+use crate::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use crate::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use crate::physical_plan::joins::{HashJoinExec, PartitionMode};
+use crate::physical_plan::memory::MemoryExec;
+use crate::physical_plan::metrics::{
+    Count, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet,
+};
+use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::sorts::sort::SortExec;
+use crate::physical_plan::sorts::sort_preserving_merge::SortPreservingMergeExec;
+use crate::physical_plan::stream::RecordBatchStreamAdapter;
+use crate::physical_plan::{
+    displayable, with_new_children_if_necessary, DisplayAs, DisplayFormatType,
+    Distribution, ExecutionPlan, ExecutionPlanProperties, Partitioning, PlanProperties,
+    SendableRecordBatchStream,
+};
+
+use arrow::record_batch::RecordBatch;
+use datafusion_common::tree_node::{Transformed, TransformedResult, TreeNode};
+use datafusion_common::Statistics;
+use datafusion_physical_expr::PhysicalSortExpr;
+
+use futures::future::{try_join_all, BoxFuture};
+use futures::{FutureExt, StreamExt, TryStreamExt};
+use log::debug;
+use parking_lot::Mutex;
+use tokio::sync::OnceCell;
+
+/// An optimizer rule that wraps bounded plans containing hash joins in an
+/// [`AdaptiveExec`], when `datafusion.optimizer.enable_adaptive_execution`
+/// is set.
+///
+/// It runs after the other rules, so that [`AdaptiveExec`] starts from the
+/// fully optimized plan.
+#[derive(Default)]
+pub struct AdaptiveExecution {}
+
+impl AdaptiveExecution {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for AdaptiveExecution {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &ConfigOptions,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if !config.optimizer.enable_adaptive_execution
+            || plan.as_any().is::<AdaptiveExec>()
+            || plan.execution_mode().is_unbounded()
+            || !contains_hash_join(&plan)
+        {
+            return Ok(plan);
+        }
+        Ok(Arc::new(AdaptiveExec::new(plan)))
+    }
+
+    fn name(&self) -> &str {
+        "AdaptiveExecution"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
+fn contains_hash_join(plan: &Arc<dyn ExecutionPlan>) -> bool {
+    plan.as_any().is::<HashJoinExec>() || plan.children().iter().any(contains_hash_join)
+}
+
+/// Executes a plan after re-optimizing its hash joins from the actual
+/// statistics of their inputs.
+///
+/// Physical plans are optimized from estimated statistics, so a join may for
+/// example be partitioned although one of its inputs is small enough to be
+/// collected. On its first execution, `AdaptiveExec` goes through the hash
+/// joins of its input bottom-up. It materializes the build side of each join
+/// below its exchange boundaries ([`RepartitionExec`],
+/// [`CoalescePartitionsExec`], ...), and runs [`JoinSelection`] again on the
+/// join, which now picks between [`PartitionMode::Partitioned`] and
+/// [`PartitionMode::CollectLeft`] from the exact row count and size of its
+/// build side. The probe side keeps streaming, so that a limit above the join
+/// can still stop it early. [`EnforceDistribution`] and [`EnforceSorting`]
+/// then add back the exchanges required by the new plan, and
+/// [`SkewMitigation`] handles the heavy keys of the remaining joins. Finally,
+/// exchanges and sorts are added on top of the new plan so that it keeps the
+/// partitioning and ordering advertised by `AdaptiveExec`.
+///
+/// The materialized build sides are held in memory, and reserved from the
+/// memory pool batch by batch, until the plan is dropped.
+#[derive(Debug)]
+pub struct AdaptiveExec {
+    input: Arc<dyn ExecutionPlan>,
+    /// The re-optimized plan, shared by the partitions
+    adaptive_plan: Arc<OnceCell<AdaptivePlan>>,
+    metrics: ExecutionPlanMetricsSet,
+    adaptive_metrics: AdaptiveMetrics,
+}
+
+impl AdaptiveExec {
+    /// Create a new `AdaptiveExec` re-optimizing `input`
+    pub fn new(input: Arc<dyn ExecutionPlan>) -> Self {
+        let metrics = ExecutionPlanMetricsSet::new();
+        let adaptive_metrics = AdaptiveMetrics::new(&metrics);
+        Self {
+            input,
+            adaptive_plan: Arc::new(OnceCell::new()),
+            metrics,
+            adaptive_metrics,
+        }
+    }
+
+    /// The input plan, as optimized before execution
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// The re-optimized plan, once the plan has been executed
+    pub fn adaptive_plan(&self) -> Option<Arc<dyn ExecutionPlan>> {
+        self.adaptive_plan
+            .get()
+            .map(|adaptive_plan| Arc::clone(&adaptive_plan.plan))
+    }
+}
+
+#[derive(Debug)]
+struct AdaptivePlan {
+    plan: Arc<dyn ExecutionPlan>,
+    /// The memory of the materialized build sides
+    _reservation: MemoryReservation,
+}
+
+#[derive(Debug, Clone)]
+struct AdaptiveMetrics {
+    /// Number of hash joins that were optimized again
+    replanned_joins: Count,
+    /// Number of rows of the materialized build sides
+    materialized_rows: Count,
+    /// Memory size of the materialized build sides
+    materialized_bytes: Count,
+}
+
+impl AdaptiveMetrics {
+    fn new(metrics: &ExecutionPlanMetricsSet) -> Self {
+        Self {
+            replanned_joins: MetricBuilder::new(metrics)
+                .global_counter("replanned_joins"),
+            materialized_rows: MetricBuilder::new(metrics)
+                .global_counter("materialized_rows"),
+            materialized_bytes: MetricBuilder::new(metrics)
+                .global_counter("materialized_bytes"),
+        }
+    }
+}
+
+impl DisplayAs for AdaptiveExec {
+    fn fmt_as(
+        &self,
+        _t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        write!(f, "AdaptiveExec")
+    }
+}
+
+impl ExecutionPlan for AdaptiveExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        self.input.properties()
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(Self::new(children.remove(0))))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let input = self.input.clone();
+        let adaptive_plan = self.adaptive_plan.clone();
+        let metrics = self.adaptive_metrics.clone();
+        let stream = futures::stream::once(async move {
+            let adaptive_plan = adaptive_plan
+                .get_or_try_init(|| replan(input, &context, &metrics))
+                .await?;
+            adaptive_plan.plan.execute(partition, context)
+        })
+        .try_flatten();
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream,
+        )))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Result<Statistics> {
+        self.input.statistics()
+    }
+}
+
+/// Re-optimize the hash joins of `plan`, and add back the exchanges and
+/// sorts required by the resulting plan
+async fn replan(
+    plan: Arc<dyn ExecutionPlan>,
+    context: &Arc<TaskContext>,
+    metrics: &AdaptiveMetrics,
+) -> Result<AdaptivePlan> {
+    let config = context.session_config().options();
+    let partitioning = plan.output_partitioning().clone();
+    let ordering = plan.output_ordering().map(|ordering| ordering.to_vec());
+    let mut reservation =
+        MemoryConsumer::new("AdaptiveExec").register(context.memory_pool());
+
+    let plan = replan_joins(plan, context, &mut reservation, metrics).await?;
+    // CoalesceBatches is added back below, after the new exchanges
+    let plan = plan
+        .transform_up(&|plan| {
+            Ok(match plan.as_any().downcast_ref::<CoalesceBatchesExec>() {
+                Some(coalesce) => Transformed::yes(coalesce.input().clone()),
+                None => Transformed::no(plan),
+            })
+        })
+        .data()?;
+    let plan = EnforceDistribution::new().optimize(plan, config)?;
+    let plan = EnforceSorting::new().optimize(plan, config)?;
+    let plan = CoalesceBatches::new().optimize(plan, config)?;
+    let plan = SkewMitigation::new().optimize(plan, config)?;
+
+    // The parents of the adaptive plan rely on the advertised properties
+    let plan = keep_properties(plan, &partitioning, ordering.as_deref())?;
+    debug!(
+        "Adaptive plan:\n{}",
+        displayable(plan.as_ref()).indent(false)
+    );
+
+    Ok(AdaptivePlan {
+        plan,
+        _reservation: reservation,
+    })
+}
+
+/// Add the exchanges and sorts making the re-optimized `plan` keep the
+/// `partitioning` and `ordering` of the plan it replaces
+fn keep_properties(
+    mut plan: Arc<dyn ExecutionPlan>,
+    partitioning: &Partitioning,
+    ordering: Option<&[PhysicalSortExpr]>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let partition_count = partitioning.partition_count();
+    let keeps_partitioning = plan.output_partitioning().partition_count()
+        == partition_count
+        && match partitioning {
+            Partitioning::Hash(exprs, _) => plan.output_partitioning().satisfy(
+                &Distribution::HashPartitioned(exprs.clone()),
+                plan.equivalence_properties(),
+            ),
+            _ => true,
+        };
+    if !keeps_partitioning {
+        let sorted = ordering
+            .filter(|ordering| plan.equivalence_properties().ordering_satisfy(ordering));
+        plan = match (partitioning, sorted) {
+            (_, Some(ordering)) if partition_count == 1 => {
+                Arc::new(SortPreservingMergeExec::new(ordering.to_vec(), plan))
+            }
+            _ if partition_count == 1 => Arc::new(CoalescePartitionsExec::new(plan)),
+            (Partitioning::Hash(..), _) => {
+                Arc::new(RepartitionExec::try_new(plan, partitioning.clone())?)
+            }
+            _ => Arc::new(RepartitionExec::try_new(
+                plan,
+                Partitioning::RoundRobinBatch(partition_count),
+            )?),
+        };
+    }
+    if let Some(ordering) = ordering {
+        if !plan.equivalence_properties().ordering_satisfy(ordering) {
+            plan = Arc::new(
+                SortExec::new(ordering.to_vec(), plan)
+                    .with_preserve_partitioning(partition_count > 1),
+            );
+        }
+    }
+    Ok(plan)
+}
+
+/// Re-optimize the hash joins of `plan` bottom-up, materializing their build
+/// sides
+fn replan_joins<'a>(
+    plan: Arc<dyn ExecutionPlan>,
+    context: &'a Arc<TaskContext>,
+    reservation: &'a mut MemoryReservation,
+    metrics: &'a AdaptiveMetrics,
+) -> BoxFuture<'a, Result<Arc<dyn ExecutionPlan>>> {
+    async move {
+        let mut children = vec![];
+        for child in plan.children() {
+            children.push(replan_joins(child, context, reservation, metrics).await?);
+        }
+        let plan = with_new_children_if_necessary(plan, children)?;
+        let Some(hash_join) = plan.as_any().downcast_ref::<HashJoinExec>() else {
+            return Ok(plan);
+        };
+
+        let config = context.session_config().options();
+        let left =
+            materialize_stage(hash_join.left(), context, reservation, metrics).await?;
+        // The exchanges of the probe side are added back for the new
+        // partition mode
+        let mut right = hash_join.right().clone();
+        while is_exchange(&right) {
+            right = right.children().swap_remove(0);
+        }
+        // Like the physical planner, only choose between partitioned and
+        // collected joins if joins may be repartitioned
+        let partition_mode = if config.execution.target_partitions > 1
+            && config.optimizer.repartition_joins
+        {
+            PartitionMode::Auto
+        } else {
+            *hash_join.partition_mode()
+        };
+        let join = HashJoinExec::try_new(
+            left,
+            right,
+            hash_join.on().to_vec(),
+            hash_join.filter().cloned(),
+            hash_join.join_type(),
+            hash_join.projection.clone(),
+            partition_mode,
+            hash_join.null_equals_null(),
+        )?
+        .with_fetch(hash_join.fetch())?;
+        metrics.replanned_joins.add(1);
+
+        JoinSelection::new().optimize(Arc::new(join), config)
+    }
+    .boxed()
+}
+
+/// Execute the build side `plan` below its exchange boundaries, and return
+/// its batches as a [`MemoryExec`] with exact statistics
+async fn materialize_stage(
+    plan: &Arc<dyn ExecutionPlan>,
+    context: &Arc<TaskContext>,
+    reservation: &mut MemoryReservation,
+    metrics: &AdaptiveMetrics,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let config = context.session_config().options();
+    let mut stage = plan.clone();
+    while is_exchange(&stage) {
+        stage = stage.children().swap_remove(0);
+    }
+    // The joins below may have changed their output partitioning
+    let stage = EnforceDistribution::new().optimize(stage, config)?;
+    let stage = EnforceSorting::new().optimize(stage, config)?;
+
+    // Reserve each batch as it arrives, so that a build side too large for
+    // the memory pool fails before being collected entirely
+    let reservation = Mutex::new(reservation);
+    let partitions = (0..stage.output_partitioning().partition_count()).map(|i| {
+        let stream = stage.execute(i, context.clone());
+        let reservation = &reservation;
+        async move {
+            stream?
+                .map(|batch| -> Result<RecordBatch> {
+                    let batch = batch?;
+                    let size = batch.get_array_memory_size();
+                    reservation.lock().try_grow(size)?;
+                    metrics.materialized_rows.add(batch.num_rows());
+                    metrics.materialized_bytes.add(size);
+                    Ok(batch)
+                })
+                .try_collect::<Vec<_>>()
+                .await
+        }
+    });
+    let partitions = try_join_all(partitions).await?;
+    debug!(
+        "Materialized join build side {}",
+        displayable(stage.as_ref()).one_line()
+    );
+
+    let mut exec = MemoryExec::try_new(&partitions, stage.schema(), None)?;
+    if let Some(ordering) = stage.output_ordering() {
+        exec = exec.with_sort_information(vec![ordering.to_vec()]);
+    }
+    Ok(Arc::new(exec))
+}
+
+fn is_exchange(plan: &Arc<dyn ExecutionPlan>) -> bool {
+    let plan = plan.as_any();
+    plan.is::<RepartitionExec>()
+        || plan.is::<CoalescePartitionsExec>()
+        || plan.is::<CoalesceBatchesExec>()
+        || plan.is::<SortPreservingMergeExec>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::datasource::MemTable;
+    use crate::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
+    use crate::physical_plan::collect;
+    use crate::prelude::{SessionConfig, SessionContext};
+    use datafusion_physical_expr::expressions::col;
+
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+
+    fn table(name: &str, values: impl Iterator<Item = i32>) -> Result<MemTable> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new(name, DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from_iter_values(values))],
+        )?;
+        MemTable::try_new(schema, vec![vec![batch]])
+    }
+
+    #[tokio::test]
+    async fn adaptive_collect_left_join() -> Result<()> {
+        // Without collected statistics, the joins are planned as partitioned
+        let config = SessionConfig::new()
+            .with_target_partitions(4)
+            .set_bool("datafusion.optimizer.enable_adaptive_execution", true);
+        let ctx = SessionContext::new_with_config(config);
+        ctx.register_table("t1", Arc::new(table("a", 0..10)?))?;
+        ctx.register_table("t2", Arc::new(table("b", 0..1000)?))?;
+
+        let plan = ctx
+            .sql("SELECT count(*) FROM t1 JOIN t2 ON a = b")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let adaptive = plan.as_any().downcast_ref::<AdaptiveExec>().unwrap();
+        let input = displayable(adaptive.input().as_ref())
+            .indent(true)
+            .to_string();
+        assert!(input.contains("HashJoinExec: mode=Partitioned"), "{input}");
+        assert!(adaptive.adaptive_plan().is_none());
+
+        let batches = collect(plan.clone(), ctx.task_ctx()).await?;
+        assert_batches_eq!(
+            [
+                "+----------+",
+                "| COUNT(*) |",
+                "+----------+",
+                "| 10       |",
+                "+----------+",
+            ],
+            &batches
+        );
+
+        let adaptive_plan = adaptive.adaptive_plan().unwrap();
+        let adaptive_plan = displayable(adaptive_plan.as_ref()).indent(true).to_string();
+        assert!(
+            adaptive_plan.contains("HashJoinExec: mode=CollectLeft"),
+            "{adaptive_plan}"
+        );
+        let metrics = plan.metrics().unwrap();
+        assert_eq!(
+            metrics.sum_by_name("replanned_joins").unwrap().as_usize(),
+            1
+        );
+        assert_eq!(
+            metrics.sum_by_name("materialized_rows").unwrap().as_usize(),
+            10
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn adaptive_build_side_exceeds_memory() -> Result<()> {
+        let config = SessionConfig::new()
+            .with_target_partitions(4)
+            .set_bool("datafusion.optimizer.enable_adaptive_execution", true);
+        let runtime = RuntimeEnv::new(RuntimeConfig::new().with_memory_limit(1024, 1.0))?;
+        let ctx = SessionContext::new_with_config_rt(config, Arc::new(runtime));
+        ctx.register_table("t1", Arc::new(table("a", 0..1000)?))?;
+        ctx.register_table("t2", Arc::new(table("b", 0..1000)?))?;
+
+        let plan = ctx
+            .sql("SELECT count(*) FROM t1 JOIN t2 ON a = b")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let err = collect(plan, ctx.task_ctx()).await.unwrap_err();
+        assert!(err.to_string().contains("for AdaptiveExec"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_advertised_properties() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let a = col("a", &schema)?;
+        let ordering = vec![PhysicalSortExpr {
+            expr: a.clone(),
+            options: Default::default(),
+        }];
+        let plan: Arc<dyn ExecutionPlan> =
+            Arc::new(MemoryExec::try_new(&[vec![], vec![]], schema, None)?);
+
+        let kept =
+            keep_properties(plan.clone(), &Partitioning::UnknownPartitioning(2), None)?;
+        assert!(Arc::ptr_eq(&kept, &plan));
+
+        let partitioning = Partitioning::Hash(vec![a], 3);
+        let kept = keep_properties(plan.clone(), &partitioning, Some(&ordering))?;
+        assert_eq!(kept.output_partitioning(), &partitioning);
+        assert_eq!(kept.output_ordering(), Some(ordering.as_slice()));
+
+        let kept = keep_properties(
+            plan,
+            &Partitioning::UnknownPartitioning(1),
+            Some(&ordering),
+        )?;
+        assert_eq!(kept.output_partitioning().partition_count(), 1);
+        assert_eq!(kept.output_ordering(), Some(ordering.as_slice()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn adaptive_execution_disabled() -> Result<()> {
+        let ctx = SessionContext::new();
+        ctx.register_table("t1", Arc::new(table("a", 0..10)?))?;
+        ctx.register_table("t2", Arc::new(table("b", 0..1000)?))?;
+
+        let plan = ctx
+            .sql("SELECT count(*) FROM t1 JOIN t2 ON a = b")
+            .await?
+            .create_physical_plan()
+            .await?;
+        assert!(!plan.as_any().is::<AdaptiveExec>());
+        Ok(())
+    }
+}
@@ -259,6 +259,7 @@ mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering: vec![],
+                bucket_by: None,
             },
             None,
             None,
@@ -1446,6 +1446,7 @@ pub(crate) mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering,
+                bucket_by: None,
             },
             None,
             None,
@@ -1474,6 +1475,7 @@ pub(crate) mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering,
+                bucket_by: None,
             },
             None,
             None,
@@ -1496,6 +1498,7 @@ pub(crate) mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering,
+                bucket_by: None,
             },
             false,
             b',',
@@ -1526,6 +1529,7 @@ pub(crate) mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering,
+                bucket_by: None,
             },
             false,
             b',',
@@ -3725,6 +3729,7 @@ pub(crate) mod tests {
                         limit: None,
                         table_partition_cols: vec![],
                         output_ordering: vec![],
+                        bucket_by: None,
                     },
                     false,
                     b',',
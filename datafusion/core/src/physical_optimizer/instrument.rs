@@ -0,0 +1,100 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`InstrumentPlan`] wraps the nodes of a plan so that they emit tracing
+//! spans when executed
+
+use std::sync::Arc;
+
+use crate::physical_optimizer::PhysicalOptimizerRule;
+use crate::physical_plan::instrumented::instrument_plan;
+use crate::physical_plan::ExecutionPlan;
+
+use datafusion_common::config::ConfigOptions;
+use datafusion_common::Result;
+
+/// An optimizer rule that wraps every node of the plan in an
+/// [`InstrumentedExec`], which runs its partition streams in `execute` spans.
+///
+/// It runs last, so that the other rules see the plan as is.
+///
+/// [`InstrumentedExec`]: crate::physical_plan::instrumented::InstrumentedExec
+#[derive(Default)]
+pub struct InstrumentPlan {}
+
+impl InstrumentPlan {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for InstrumentPlan {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        _config: &ConfigOptions,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        instrument_plan(plan)
+    }
+
+    fn name(&self) -> &str {
+        "InstrumentPlan"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_batches_eq;
+    use crate::physical_plan::projection::ProjectionExec;
+    use crate::physical_plan::{collect, displayable};
+    use crate::prelude::SessionContext;
+
+    use datafusion_common::Result;
+
+    #[tokio::test]
+    async fn instrumented_plan() -> Result<()> {
+        let ctx = SessionContext::new();
+        let state = ctx.state();
+        let last_rule = state.physical_optimizers().last().unwrap();
+        assert_eq!(last_rule.name(), "InstrumentPlan");
+
+        let df = ctx
+            .sql("SELECT column1 * 2 AS a FROM (VALUES (1), (2), (3))")
+            .await?;
+        let plan = df.create_physical_plan().await?;
+
+        // the instrumented plan is displayed and downcast as the plan itself
+        assert!(plan.as_any().is::<ProjectionExec>());
+        let expected = "ProjectionExec: expr=[column1@0 * 2 as a]\n  ValuesExec\n";
+        assert_eq!(
+            displayable(plan.as_ref()).indent(true).to_string(),
+            expected
+        );
+
+        let batches = collect(plan, ctx.task_ctx()).await?;
+        let expected = [
+            "+---+", "| a |", "+---+", "| 2 |", "| 4 |", "| 6 |", "+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`LimitPushdown`] pushes limits into joins, so they stop joining once
+//! enough rows are produced
+
+use std::sync::Arc;
+
+use crate::physical_optimizer::PhysicalOptimizerRule;
+use crate::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use crate::physical_plan::joins::{HashJoinExec, SortMergeJoinExec};
+use crate::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
+use crate::physical_plan::{ExecutionPlan, ExecutionPlanProperties};
+
+use datafusion_common::config::ConfigOptions;
+use datafusion_common::tree_node::{Transformed, TransformedResult, TreeNode};
+use datafusion_common::Result;
+
+/// An optimizer rule that passes the `fetch` of a limit into the
+/// [`HashJoinExec`] or [`SortMergeJoinExec`] below it, optionally separated by
+/// a [`CoalesceBatchesExec`].
+///
+/// A [`LocalLimitExec`] is replaced by the join, which enforces the limit per
+/// partition itself. A [`GlobalLimitExec`] is kept, as it also skips rows and
+/// combines the partitions, but a join producing a single partition will
+/// only produce `skip + fetch` rows.
+#[derive(Default)]
+pub struct LimitPushdown {}
+
+impl LimitPushdown {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for LimitPushdown {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        _config: &ConfigOptions,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        plan.transform_down(&|plan| {
+            if let Some(local_limit) = plan.as_any().downcast_ref::<LocalLimitExec>() {
+                if let Some(input) = with_fetch(local_limit.input(), local_limit.fetch())?
+                {
+                    return Ok(Transformed::yes(input));
+                }
+            } else if let Some(global_limit) =
+                plan.as_any().downcast_ref::<GlobalLimitExec>()
+            {
+                let input = global_limit.input();
+                if let (Some(fetch), 1) = (
+                    global_limit.fetch(),
+                    input.output_partitioning().partition_count(),
+                ) {
+                    if let Some(input) = with_fetch(input, global_limit.skip() + fetch)? {
+                        return Ok(Transformed::yes(Arc::new(GlobalLimitExec::new(
+                            input,
+                            global_limit.skip(),
+                            global_limit.fetch(),
+                        ))));
+                    }
+                }
+            }
+            Ok(Transformed::no(plan))
+        })
+        .data()
+    }
+
+    fn name(&self) -> &str {
+        "LimitPushdown"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
+/// Returns `plan` with the join limited to `fetch` rows per partition, or
+/// `None` if `plan` is not a join that supports it
+fn with_fetch(
+    plan: &Arc<dyn ExecutionPlan>,
+    fetch: usize,
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    if let Some(coalesce) = plan.as_any().downcast_ref::<CoalesceBatchesExec>() {
+        return Ok(with_fetch(coalesce.input(), fetch)?.map(|input| {
+            Arc::new(CoalesceBatchesExec::new(
+                input,
+                coalesce.target_batch_size(),
+            )) as Arc<dyn ExecutionPlan>
+        }));
+    }
+
+    if let Some(join) = plan.as_any().downcast_ref::<HashJoinExec>() {
+        if join.supports_fetch() {
+            let fetch = join.fetch().map_or(fetch, |f| f.min(fetch));
+            return Ok(Some(Arc::new(join.with_fetch(Some(fetch))?)));
+        }
+    } else if let Some(join) = plan.as_any().downcast_ref::<SortMergeJoinExec>() {
+        let fetch = join.fetch().map_or(fetch, |f| f.min(fetch));
+        return Ok(Some(Arc::new(join.with_fetch(Some(fetch))?)));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_optimizer::enforce_distribution::tests::trim_plan_display;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::{collect, displayable};
+    use crate::prelude::SessionContext;
+
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+    use datafusion_common::JoinType;
+    use datafusion_physical_expr::expressions::col;
+    use datafusion_physical_plan::joins::PartitionMode;
+
+    fn mock_exec(name: &str, values: Vec<i32>) -> Arc<dyn ExecutionPlan> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new(name, DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(values))],
+        )
+        .unwrap();
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    fn hash_join(join_type: JoinType) -> Result<Arc<dyn ExecutionPlan>> {
+        let left = mock_exec("a", vec![1, 2, 3]);
+        let right = mock_exec("b", vec![1, 1, 2, 2, 3, 3]);
+        let on = vec![(col("a", &left.schema())?, col("b", &right.schema())?)];
+        Ok(Arc::new(HashJoinExec::try_new(
+            left,
+            right,
+            on,
+            None,
+            &join_type,
+            None,
+            PartitionMode::CollectLeft,
+            false,
+        )?))
+    }
+
+    fn optimize(plan: Arc<dyn ExecutionPlan>) -> Result<Vec<String>> {
+        let optimized = LimitPushdown::new().optimize(plan, &ConfigOptions::new())?;
+        let display = displayable(optimized.as_ref()).indent(true).to_string();
+        Ok(trim_plan_display(&display)
+            .into_iter()
+            .map(String::from)
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn push_limit_into_hash_join() -> Result<()> {
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(GlobalLimitExec::new(
+            Arc::new(LocalLimitExec::new(
+                Arc::new(CoalesceBatchesExec::new(hash_join(JoinType::Inner)?, 8192)),
+                4,
+            )),
+            1,
+            Some(3),
+        ));
+        let expected = [
+            "GlobalLimitExec: skip=1, fetch=3",
+            "CoalesceBatchesExec: target_batch_size=8192",
+            "HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(a@0, b@0)], fetch=4",
+            "MemoryExec: partitions=1, partition_sizes=[1]",
+            "MemoryExec: partitions=1, partition_sizes=[1]",
+        ];
+        assert_eq!(optimize(plan.clone())?, expected);
+
+        let optimized = LimitPushdown::new().optimize(plan, &ConfigOptions::new())?;
+        let batches = collect(optimized, SessionContext::new().task_ctx()).await?;
+        let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        assert_eq!(num_rows, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn keep_limit_above_left_hash_join() -> Result<()> {
+        // unmatched build side rows are only known once the probe side is exhausted
+        let plan: Arc<dyn ExecutionPlan> =
+            Arc::new(LocalLimitExec::new(hash_join(JoinType::Left)?, 4));
+        let expected = [
+            "LocalLimitExec: fetch=4",
+            "HashJoinExec: mode=CollectLeft, join_type=Left, on=[(a@0, b@0)]",
+            "MemoryExec: partitions=1, partition_sizes=[1]",
+            "MemoryExec: partitions=1, partition_sizes=[1]",
+        ];
+        assert_eq!(optimize(plan)?, expected);
+        Ok(())
+    }
+}
@@ -21,12 +21,16 @@
 //! "Repartition" or "Sortedness"
 //!
 //! [`ExecutionPlan`]: crate::physical_plan::ExecutionPlan
+pub mod adaptive;
 pub mod aggregate_statistics;
 pub mod coalesce_batches;
 pub mod combine_partial_final_agg;
 pub mod enforce_distribution;
 pub mod enforce_sorting;
+#[cfg(feature = "tracing")]
+pub mod instrument;
 pub mod join_selection;
+pub mod limit_pushdown;
 pub mod limited_distinct_aggregation;
 pub mod optimizer;
 pub mod output_requirements;
@@ -34,6 +38,7 @@ pub mod pipeline_checker;
 mod projection_pushdown;
 pub mod pruning;
 pub mod replace_with_order_preserving_variants;
+pub mod skew_mitigation;
 mod sort_pushdown;
 pub mod topk_aggregation;
 mod utils;
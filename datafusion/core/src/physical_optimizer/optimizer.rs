@@ -21,15 +21,20 @@ use std::sync::Arc;
 
 use super::projection_pushdown::ProjectionPushdown;
 use crate::config::ConfigOptions;
+use crate::physical_optimizer::adaptive::AdaptiveExecution;
 use crate::physical_optimizer::aggregate_statistics::AggregateStatistics;
 use crate::physical_optimizer::coalesce_batches::CoalesceBatches;
 use crate::physical_optimizer::combine_partial_final_agg::CombinePartialFinalAggregate;
 use crate::physical_optimizer::enforce_distribution::EnforceDistribution;
 use crate::physical_optimizer::enforce_sorting::EnforceSorting;
+#[cfg(feature = "tracing")]
+use crate::physical_optimizer::instrument::InstrumentPlan;
 use crate::physical_optimizer::join_selection::JoinSelection;
+use crate::physical_optimizer::limit_pushdown::LimitPushdown;
 use crate::physical_optimizer::limited_distinct_aggregation::LimitedDistinctAggregation;
 use crate::physical_optimizer::output_requirements::OutputRequirements;
 use crate::physical_optimizer::pipeline_checker::PipelineChecker;
+use crate::physical_optimizer::skew_mitigation::SkewMitigation;
 use crate::physical_optimizer::topk_aggregation::TopKAggregation;
 use crate::{error::Result, physical_plan::ExecutionPlan};
 
@@ -126,6 +131,21 @@ impl PhysicalOptimizer {
             // are not present, the load of executors such as join or union will be
             // reduced by narrowing their input tables.
             Arc::new(ProjectionPushdown::new()),
+            // The LimitPushdown rule passes limits into joins, so they can stop early.
+            // It runs last as other rules recreate joins without their limit.
+            Arc::new(LimitPushdown::new()),
+            // The SkewMitigation rule spreads the heavy keys of partitioned hash joins
+            // across partitions, if skew mitigation is enabled. It runs after the rules
+            // relying on hash partitioning, as it breaks the partitioning of the joins.
+            Arc::new(SkewMitigation::new()),
+            // The AdaptiveExecution rule wraps the plan in an `AdaptiveExec`, which
+            // re-optimizes its joins during execution, if adaptive execution is enabled.
+            // It runs after the other rules so that it starts from the final plan.
+            Arc::new(AdaptiveExecution::new()),
+            // The InstrumentPlan rule wraps every node of the final plan so that it
+            // emits a tracing span per partition stream.
+            #[cfg(feature = "tracing")]
+            Arc::new(InstrumentPlan::new()),
         ];
 
         Self::with_rules(rules)
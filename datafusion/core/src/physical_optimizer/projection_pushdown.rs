@@ -1724,6 +1724,7 @@ mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering: vec![vec![]],
+                bucket_by: None,
             },
             false,
             0,
@@ -1750,6 +1751,7 @@ mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering: vec![vec![]],
+                bucket_by: None,
             },
             false,
             0,
@@ -1505,6 +1505,7 @@ mod tests {
                 limit: None,
                 table_partition_cols: vec![],
                 output_ordering: vec![sort_exprs],
+                bucket_by: None,
             },
             true,
             0,
@@ -0,0 +1,370 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`SkewMitigation`] spreads the rows of heavy keys across partitions in
+//! partitioned hash joins, and the aggregations above them
+
+use std::sync::Arc;
+
+use crate::config::ConfigOptions;
+use crate::error::Result;
+use crate::physical_optimizer::PhysicalOptimizerRule;
+use crate::physical_plan::aggregates::{AggregateExec, AggregateMode, PhysicalGroupBy};
+use crate::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use crate::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use crate::physical_plan::joins::{HashJoinExec, PartitionMode};
+use crate::physical_plan::repartition::{RepartitionExec, SkewHandling, SkewedKeys};
+use crate::physical_plan::sorts::sort_preserving_merge::SortPreservingMergeExec;
+use crate::physical_plan::{
+    with_new_children_if_necessary, ExecutionPlan, ExecutionPlanProperties,
+    InputOrderMode, Partitioning,
+};
+
+use datafusion_common::JoinType;
+use datafusion_physical_expr::Distribution;
+
+/// An optimizer rule that handles heavy keys in partitioned hash joins, when
+/// `datafusion.optimizer.enable_skew_mitigation` is set.
+///
+/// Hash repartitioning sends all the rows of a key to the same partition, so
+/// a few heavy keys can overload one partition of a join. For hash joins
+/// whose inputs are both hash repartitioned, the probe side repartition
+/// detects the heavy keys from a sample of its input, and spreads their rows
+/// across all partitions ([`SkewHandling::Split`]), while the build side
+/// repartition sends the rows of these keys to all partitions
+/// ([`SkewHandling::Broadcast`]). Every probe row still meets all its
+/// matching build rows, so this only applies to joins that do not track the
+/// matched build rows: inner, right, right semi and right anti joins.
+///
+/// The output of such joins is no longer hash partitioned, so joins whose
+/// output partitioning is relied upon are left as is, except for
+/// [`AggregateMode::SinglePartitioned`] aggregations, which are split into a
+/// partial aggregation below a hash repartition and a final aggregation
+/// above it. The hot groups are then partially aggregated in all partitions.
+///
+/// It runs after the other rules, as it breaks the hash partitioning the
+/// other rules would rely on.
+#[derive(Default)]
+pub struct SkewMitigation {}
+
+impl SkewMitigation {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for SkewMitigation {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &ConfigOptions,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if !config.optimizer.enable_skew_mitigation {
+            return Ok(plan);
+        }
+        mitigate_skew(plan, false, config)
+    }
+
+    fn name(&self) -> &str {
+        "SkewMitigation"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
+/// Mitigate the skew of the joins in `plan`, where `partitioning_required`
+/// tells whether the parent of `plan` relies on its output partitioning
+fn mitigate_skew(
+    plan: Arc<dyn ExecutionPlan>,
+    partitioning_required: bool,
+    config: &ConfigOptions,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    if !partitioning_required {
+        if let Some(aggregate) = plan.as_any().downcast_ref::<AggregateExec>() {
+            if can_split_aggregate(aggregate) {
+                return split_aggregate(aggregate, config);
+            }
+        }
+        if let Some(hash_join) = plan.as_any().downcast_ref::<HashJoinExec>() {
+            if can_split_join(hash_join) {
+                let skewed_keys =
+                    Arc::new(SkewedKeys::new(config.optimizer.skew_sample_rows));
+                let left = with_skew_handling(
+                    hash_join.left().clone(),
+                    SkewHandling::Broadcast(skewed_keys.clone()),
+                    config,
+                )?;
+                let right = with_skew_handling(
+                    hash_join.right().clone(),
+                    SkewHandling::Split(skewed_keys),
+                    config,
+                )?;
+                return plan.with_new_children(vec![left, right]);
+            }
+        }
+    }
+
+    // Exchanges set up the partitioning of their output themselves
+    let exchange = is_exchange(&plan);
+    let children = plan
+        .children()
+        .into_iter()
+        .zip(plan.required_input_distribution())
+        .map(|(child, distribution)| {
+            let required = matches!(distribution, Distribution::HashPartitioned(_))
+                || (partitioning_required && !exchange);
+            mitigate_skew(child, required, config)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    with_new_children_if_necessary(plan, children)
+}
+
+/// Whether the rows of heavy keys can be spread across the partitions of
+/// `hash_join`
+fn can_split_join(hash_join: &HashJoinExec) -> bool {
+    let is_hash_repartition = |plan: &Arc<dyn ExecutionPlan>| {
+        let plan = match plan.as_any().downcast_ref::<CoalesceBatchesExec>() {
+            Some(coalesce) => coalesce.input(),
+            None => plan,
+        };
+        plan.as_any()
+            .downcast_ref::<RepartitionExec>()
+            .is_some_and(|repartition| {
+                matches!(repartition.partitioning(), Partitioning::Hash(_, _))
+                    && !repartition.preserve_order()
+                    && repartition.skew_handling().is_none()
+            })
+    };
+    hash_join.partition_mode() == &PartitionMode::Partitioned
+        && matches!(
+            hash_join.join_type(),
+            JoinType::Inner | JoinType::Right | JoinType::RightSemi | JoinType::RightAnti
+        )
+        && is_hash_repartition(hash_join.left())
+        && is_hash_repartition(hash_join.right())
+}
+
+/// Whether `plan` contains a join that can be split, without exchanges in
+/// between
+fn has_join_to_split(plan: &Arc<dyn ExecutionPlan>) -> bool {
+    if let Some(hash_join) = plan.as_any().downcast_ref::<HashJoinExec>() {
+        if can_split_join(hash_join) {
+            return true;
+        }
+    }
+    !is_exchange(plan) && plan.children().iter().any(has_join_to_split)
+}
+
+/// Set the skew handling of the repartition below the optional
+/// [`CoalesceBatchesExec`] `plan`
+fn with_skew_handling(
+    plan: Arc<dyn ExecutionPlan>,
+    skew_handling: SkewHandling,
+    config: &ConfigOptions,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    if let Some(coalesce) = plan.as_any().downcast_ref::<CoalesceBatchesExec>() {
+        let input = with_skew_handling(coalesce.input().clone(), skew_handling, config)?;
+        return plan.with_new_children(vec![input]);
+    }
+    let repartition = plan.as_any().downcast_ref::<RepartitionExec>().unwrap();
+    let input = mitigate_skew(repartition.input().clone(), false, config)?;
+    Ok(Arc::new(
+        RepartitionExec::try_new(input, repartition.partitioning().clone())?
+            .with_skew_handling(skew_handling),
+    ))
+}
+
+/// Whether `aggregate` can be split into a partial and a final aggregation,
+/// so that the joins below can be split
+fn can_split_aggregate(aggregate: &AggregateExec) -> bool {
+    aggregate.mode() == &AggregateMode::SinglePartitioned
+        && aggregate.group_by().is_single()
+        && !aggregate.group_by().expr().is_empty()
+        && aggregate.input_order_mode() == &InputOrderMode::Linear
+        && aggregate
+            .aggr_expr()
+            .iter()
+            .all(|e| e.order_bys().is_none())
+        && has_join_to_split(aggregate.input())
+}
+
+/// Split `aggregate` into a partial aggregation, a hash repartition of the
+/// partial results, and a final aggregation
+fn split_aggregate(
+    aggregate: &AggregateExec,
+    config: &ConfigOptions,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let input = mitigate_skew(aggregate.input().clone(), false, config)?;
+    let partition_count = input.output_partitioning().partition_count();
+    let partial = Arc::new(AggregateExec::try_new(
+        AggregateMode::Partial,
+        aggregate.group_by().clone(),
+        aggregate.aggr_expr().to_vec(),
+        aggregate.filter_expr().to_vec(),
+        input,
+        aggregate.input_schema(),
+    )?);
+
+    let final_group = partial.output_group_expr();
+    let final_grouping_set = PhysicalGroupBy::new_single(
+        final_group
+            .iter()
+            .zip(aggregate.group_by().expr())
+            .map(|(expr, (_, name))| (expr.clone(), name.clone()))
+            .collect(),
+    );
+    let aggr_expr = partial.aggr_expr().to_vec();
+    let repartition = Arc::new(RepartitionExec::try_new(
+        partial,
+        Partitioning::Hash(final_group, partition_count),
+    )?);
+    let repartition = Arc::new(CoalesceBatchesExec::new(
+        repartition,
+        config.execution.batch_size,
+    ));
+    Ok(Arc::new(
+        AggregateExec::try_new(
+            AggregateMode::FinalPartitioned,
+            final_grouping_set,
+            aggr_expr,
+            aggregate.filter_expr().to_vec(),
+            repartition,
+            aggregate.input_schema(),
+        )?
+        .with_limit(aggregate.limit()),
+    ))
+}
+
+fn is_exchange(plan: &Arc<dyn ExecutionPlan>) -> bool {
+    let plan = plan.as_any();
+    plan.is::<RepartitionExec>()
+        || plan.is::<CoalescePartitionsExec>()
+        || plan.is::<SortPreservingMergeExec>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::datasource::MemTable;
+    use crate::physical_plan::{collect, displayable};
+    use crate::prelude::{SessionConfig, SessionContext};
+
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+
+    /// A table with 4 partitions of 100 rows, 90% of which have key 1
+    fn skewed_table(name: &str) -> Result<MemTable> {
+        let schema =
+            Arc::new(Schema::new(vec![Field::new(name, DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from_iter_values((0..100).map(|i| {
+                if i < 90 {
+                    1
+                } else {
+                    i
+                }
+            })))],
+        )?;
+        MemTable::try_new(schema, vec![vec![batch]; 4])
+    }
+
+    #[tokio::test]
+    async fn skewed_join_aggregate() -> Result<()> {
+        let sql = "SELECT a, count(*) FROM t1 JOIN t2 ON a = b GROUP BY a";
+        let mut results = vec![];
+        for enable_skew_mitigation in [false, true] {
+            // Without collected statistics, the joins are planned as partitioned
+            let config = SessionConfig::new().with_target_partitions(4).set_bool(
+                "datafusion.optimizer.enable_skew_mitigation",
+                enable_skew_mitigation,
+            );
+            let ctx = SessionContext::new_with_config(config);
+            ctx.register_table("t1", Arc::new(skewed_table("a")?))?;
+            ctx.register_table("t2", Arc::new(skewed_table("b")?))?;
+
+            let plan = ctx.sql(sql).await?.create_physical_plan().await?;
+            let formatted = displayable(plan.as_ref()).indent(true).to_string();
+            assert_eq!(formatted.contains("skew=split"), enable_skew_mitigation);
+            assert_eq!(formatted.contains("skew=broadcast"), enable_skew_mitigation);
+            assert_eq!(
+                formatted.contains("mode=SinglePartitioned"),
+                !enable_skew_mitigation
+            );
+            results.push(collect(plan.clone(), ctx.task_ctx()).await?);
+            // The 360 rows of key 1 of both inputs
+            let expected_skewed_rows = if enable_skew_mitigation { 720 } else { 0 };
+            assert_eq!(skewed_rows(&plan), expected_skewed_rows);
+        }
+
+        let expected = [
+            "+----+----------+",
+            "| a  | COUNT(*) |",
+            "+----+----------+",
+            "| 1  | 129600   |",
+            "| 90 | 16       |",
+            "| 91 | 16       |",
+            "| 92 | 16       |",
+            "| 93 | 16       |",
+            "| 94 | 16       |",
+            "| 95 | 16       |",
+            "| 96 | 16       |",
+            "| 97 | 16       |",
+            "| 98 | 16       |",
+            "| 99 | 16       |",
+            "+----+----------+",
+        ];
+        for batches in &results {
+            assert_batches_sorted_eq!(expected, batches);
+        }
+        Ok(())
+    }
+
+    fn skewed_rows(plan: &Arc<dyn ExecutionPlan>) -> usize {
+        let rows = plan
+            .as_any()
+            .downcast_ref::<RepartitionExec>()
+            .and_then(|repartition| repartition.metrics())
+            .and_then(|metrics| metrics.sum_by_name("skewed_rows"))
+            .map_or(0, |rows| rows.as_usize());
+        rows + plan.children().iter().map(skewed_rows).sum::<usize>()
+    }
+
+    #[tokio::test]
+    async fn skewed_left_join_is_not_split() -> Result<()> {
+        let config = SessionConfig::new()
+            .with_target_partitions(4)
+            .set_bool("datafusion.optimizer.enable_skew_mitigation", true);
+        let ctx = SessionContext::new_with_config(config);
+        ctx.register_table("t1", Arc::new(skewed_table("a")?))?;
+        ctx.register_table("t2", Arc::new(skewed_table("b")?))?;
+
+        let plan = ctx
+            .sql("SELECT a, b FROM t1 LEFT JOIN t2 ON a = b")
+            .await?
+            .create_physical_plan()
+            .await?;
+        let formatted = displayable(plan.as_ref()).indent(true).to_string();
+        assert!(!formatted.contains("skew="), "{formatted}");
+        Ok(())
+    }
+}
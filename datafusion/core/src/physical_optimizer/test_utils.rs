@@ -284,6 +284,7 @@ pub fn parquet_exec(schema: &SchemaRef) -> Arc<ParquetExec> {
             limit: None,
             table_partition_cols: vec![],
             output_ordering: vec![],
+            bucket_by: None,
         },
         None,
         None,
@@ -308,6 +309,7 @@ pub fn parquet_exec_sorted(
             limit: None,
             table_partition_cols: vec![],
             output_ordering: vec![sort_exprs],
+            bucket_by: None,
         },
         None,
         None,
@@ -17,16 +17,21 @@
 
 //! Planner for [`LogicalPlan`] to [`ExecutionPlan`]
 
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use crate::datasource::file_format::arrow::ArrowFormat;
 use crate::datasource::file_format::avro::AvroFormat;
 use crate::datasource::file_format::csv::CsvFormat;
+use crate::datasource::file_format::fixed_width::FixedWidthFormat;
 use crate::datasource::file_format::json::JsonFormat;
 #[cfg(feature = "parquet")]
 use crate::datasource::file_format::parquet::ParquetFormat;
+use crate::datasource::file_format::xlsx::XlsxFormat;
 use crate::datasource::file_format::FileFormat;
 use crate::datasource::listing::ListingTableUrl;
 use crate::datasource::physical_plan::FileSinkConfig;
@@ -60,6 +65,7 @@ use crate::physical_plan::memory::MemoryExec;
 use crate::physical_plan::projection::ProjectionExec;
 use crate::physical_plan::recursive_query::RecursiveQueryExec;
 use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::shared::{SharedExec, SharedInput};
 use crate::physical_plan::sorts::sort::SortExec;
 use crate::physical_plan::union::UnionExec;
 use crate::physical_plan::unnest::UnnestExec;
@@ -74,23 +80,26 @@ use arrow::compute::SortOptions;
 use arrow::datatypes::{Schema, SchemaRef};
 use arrow_array::builder::StringBuilder;
 use arrow_array::RecordBatch;
-use datafusion_common::display::ToStringifiedPlan;
+use datafusion_common::display::{ExplainFormat, RuleChangeRecorder, ToStringifiedPlan};
+use datafusion_common::tree_node::{Transformed, TreeNode, TreeNodeRecursion};
 use datafusion_common::{
-    exec_err, internal_err, not_impl_err, plan_err, DFSchema, FileType, ScalarValue,
+    exec_err, internal_err, not_impl_err, plan_err, DFSchema, DFSchemaRef, FileType,
+    ScalarValue,
 };
 use datafusion_expr::dml::CopyTo;
 use datafusion_expr::expr::{
-    self, AggregateFunction, AggregateFunctionDefinition, Alias, Between, BinaryExpr,
-    Cast, GetFieldAccess, GetIndexedField, GroupingSet, InList, Like, TryCast,
-    WindowFunction,
+    self, is_volatile, AggregateFunction, AggregateFunctionDefinition, Alias, Between,
+    BinaryExpr, Cast, GetFieldAccess, GetIndexedField, GroupingSet, InList, Like,
+    TryCast, WindowFunction,
 };
 use datafusion_expr::expr_rewriter::unnormalize_cols;
 use datafusion_expr::logical_plan::builder::wrap_projection_for_join_if_necessary;
 use datafusion_expr::{
-    DescribeTable, DmlStatement, RecursiveQuery, ScalarFunctionDefinition,
-    StringifiedPlan, WindowFrame, WindowFrameBound, WriteOp,
+    DescribeTable, DmlStatement, Extension, RecursiveQuery, ScalarFunctionDefinition,
+    StringifiedPlan, UserDefinedLogicalNodeCore, WindowFrame, WindowFrameBound, WriteOp,
 };
 use datafusion_physical_expr::expressions::Literal;
+use datafusion_physical_expr::PhysicalSortRequirement;
 use datafusion_physical_plan::placeholder_row::PlaceholderRowExec;
 use datafusion_sql::utils::window_expr_common_partition_keys;
 
@@ -101,6 +110,7 @@ use futures::{FutureExt, StreamExt, TryStreamExt};
 use itertools::{multiunzip, Itertools};
 use log::{debug, trace};
 use sqlparser::ast::NullTreatment;
+use tokio::sync::OnceCell;
 
 fn create_function_physical_name(
     fun: &str,
@@ -464,8 +474,10 @@ impl PhysicalPlanner for DefaultPhysicalPlanner {
         match self.handle_explain(logical_plan, session_state).await? {
             Some(plan) => Ok(plan),
             None => {
+                let logical_plan =
+                    self.maybe_share_common_subplans(logical_plan, session_state)?;
                 let plan = self
-                    .create_initial_plan(logical_plan, session_state)
+                    .create_initial_plan(&logical_plan, session_state)
                     .await?;
                 self.optimize_internal(plan, session_state, |_, _| {})
             }
@@ -571,11 +583,19 @@ impl DefaultPhysicalPlanner {
                     output_url,
                     format_options,
                     partition_by,
-                    options: source_option_tuples
+                    sort_by,
+                    bucket_by,
+                    options: source_option_tuples,
+                    ..
                 }) => {
                     let input_exec = self.create_initial_plan(input, session_state).await?;
                     let parsed_url = ListingTableUrl::parse(output_url)?;
                     let object_store_url = parsed_url.object_store();
+                    if bucket_by.is_some() && !parsed_url.is_collection() {
+                        return plan_err!(
+                            "Bucketed COPY requires a directory output, URL is possibly missing a trailing `/`"
+                        );
+                    }
 
                     let schema: Schema = (**input.schema()).clone().into();
 
@@ -594,6 +614,7 @@ impl DefaultPhysicalPlanner {
                         output_schema: Arc::new(schema),
                         table_partition_cols,
                         overwrite: false,
+                        bucket_by: bucket_by.clone(),
                     };
                     let mut table_options = session_state.default_table_options();
                     let sink_format: Arc<dyn FileFormat> = match format_options {
@@ -618,9 +639,32 @@ impl DefaultPhysicalPlanner {
                         },
                         FormatOptions::AVRO => Arc::new(AvroFormat {} ),
                         FormatOptions::ARROW => Arc::new(ArrowFormat {}),
+                        FormatOptions::XLSX(options) => {
+                            Arc::new(XlsxFormat::default().with_options(options.clone()))
+                        },
+                        FormatOptions::FIXEDWIDTH(options) => {
+                            Arc::new(FixedWidthFormat::default().with_options(options.clone()))
+                        },
+                    };
+
+                    // Sorting the input sorts every written file, as files hold
+                    // subsequences of the input
+                    let order_requirements = if sort_by.is_empty() {
+                        None
+                    } else {
+                        let input_dfschema = input.schema();
+                        let sort_exprs = sort_by
+                            .iter()
+                            .map(|e| create_physical_sort_expr(
+                                e,
+                                input_dfschema,
+                                session_state.execution_props(),
+                            ))
+                            .collect::<Result<Vec<_>>>()?;
+                        Some(PhysicalSortRequirement::from_sort_exprs(&sort_exprs))
                     };
 
-                    sink_format.create_writer_physical_plan(input_exec, session_state, config, None).await
+                    sink_format.create_writer_physical_plan(input_exec, session_state, config, order_requirements).await
                 }
                 LogicalPlan::Dml(DmlStatement {
                     table_name,
@@ -1282,6 +1326,10 @@ impl DefaultPhysicalPlanner {
                     "Unsupported logical plan: Analyze must be root of the plan"
                 ),
                 LogicalPlan::Extension(e) => {
+                    if let Some(shared) = e.node.as_any().downcast_ref::<SharedSubplan>() {
+                        return self.plan_shared_subplan(shared, session_state).await;
+                    }
+
                     let physical_inputs = self.create_initial_plan_multi(e.node.inputs(), session_state).await?;
 
                     let mut maybe_plan = None;
@@ -1796,6 +1844,7 @@ impl DefaultPhysicalPlanner {
             let mut stringified_plans = vec![];
 
             let config = &session_state.config_options().explain;
+            let rules = e.options.rules;
 
             if !config.physical_plan_only {
                 stringified_plans = e.stringified_plans.clone();
@@ -1805,10 +1854,8 @@ impl DefaultPhysicalPlanner {
             }
 
             if !config.logical_plan_only && e.logical_optimization_succeeded {
-                match self
-                    .create_initial_plan(e.plan.as_ref(), session_state)
-                    .await
-                {
+                let plan = self.maybe_share_common_subplans(&e.plan, session_state)?;
+                match self.create_initial_plan(&plan, session_state).await {
                     Ok(input) => {
                         // This plan will includes statistics if show_statistics is on
                         stringified_plans.push(
@@ -1818,7 +1865,7 @@ impl DefaultPhysicalPlanner {
                         );
 
                         // If the show_statisitcs is off, add another line to show statsitics in the case of explain verbose
-                        if e.verbose && !config.show_statistics {
+                        if e.verbose && !config.show_statistics && !rules {
                             stringified_plans.push(
                                 displayable(input.as_ref())
                                     .set_show_statistics(true)
@@ -1829,17 +1876,30 @@ impl DefaultPhysicalPlanner {
                             );
                         }
 
+                        // with `EXPLAIN (RULES on)` only capture the rules changing the plan
+                        let mut rule_changes = rules.then(|| {
+                            RuleChangeRecorder::new(
+                                displayable(input.as_ref())
+                                    .set_show_statistics(config.show_statistics)
+                                    .indent(e.verbose),
+                            )
+                        });
                         let optimized_plan = self.optimize_internal(
                             input,
                             session_state,
                             |plan, optimizer| {
                                 let optimizer_name = optimizer.name().to_string();
                                 let plan_type = OptimizedPhysicalPlan { optimizer_name };
-                                stringified_plans.push(
-                                    displayable(plan)
-                                        .set_show_statistics(config.show_statistics)
-                                        .to_stringified(e.verbose, plan_type),
-                                );
+                                let plan = displayable(plan)
+                                    .set_show_statistics(config.show_statistics);
+                                match &mut rule_changes {
+                                    Some(rule_changes) => stringified_plans.extend(
+                                        rule_changes
+                                            .record(plan_type, plan.indent(e.verbose)),
+                                    ),
+                                    None => stringified_plans
+                                        .push(plan.to_stringified(e.verbose, plan_type)),
+                                }
                             },
                         );
                         match optimized_plan {
@@ -1852,7 +1912,7 @@ impl DefaultPhysicalPlanner {
                                 );
 
                                 // If the show_statisitcs is off, add another line to show statsitics in the case of explain verbose
-                                if e.verbose && !config.show_statistics {
+                                if e.verbose && !config.show_statistics && !rules {
                                     stringified_plans.push(
                                         displayable(input.as_ref())
                                             .set_show_statistics(true)
@@ -1876,10 +1936,19 @@ impl DefaultPhysicalPlanner {
                 }
             }
 
+            if e.options.format == ExplainFormat::Json {
+                stringified_plans = stringified_plans
+                    .iter()
+                    .map(StringifiedPlan::to_json)
+                    .collect();
+            }
+
+            // the rule changes are only captured when asked for, so show them
+            // without requiring `EXPLAIN VERBOSE`
             Ok(Some(Arc::new(ExplainExec::new(
                 SchemaRef::new(e.schema.as_ref().to_owned().into()),
                 stringified_plans,
-                e.verbose,
+                e.verbose || rules,
             ))))
         } else if let LogicalPlan::Analyze(a) = logical_plan {
             let input = self.create_physical_plan(&a.input, session_state).await?;
@@ -1919,6 +1988,10 @@ impl DefaultPhysicalPlanner {
 
         let mut new_plan = plan;
         for optimizer in optimizers {
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::info_span!("physical_optimizer_rule", rule = optimizer.name())
+                    .entered();
             let before_schema = new_plan.schema();
             new_plan = optimizer
                 .optimize(new_plan, session_state.config_options())
@@ -1952,6 +2025,52 @@ impl DefaultPhysicalPlanner {
         Ok(new_plan)
     }
 
+    /// Replace subplans appearing several times in `logical_plan` with
+    /// [`SharedSubplan`] nodes if `share_common_subplans` is enabled
+    fn maybe_share_common_subplans(
+        &self,
+        logical_plan: &LogicalPlan,
+        session_state: &SessionState,
+    ) -> Result<LogicalPlan> {
+        if session_state
+            .config_options()
+            .optimizer
+            .share_common_subplans
+        {
+            share_common_subplans(logical_plan.clone())
+        } else {
+            Ok(logical_plan.clone())
+        }
+    }
+
+    /// Plan one occurrence of a [`SharedSubplan`].
+    ///
+    /// The first occurrence plans the shared input, every occurrence then
+    /// becomes one consumer of it. The input is optimized with the rest of
+    /// the plan, as the child of each consumer.
+    async fn plan_shared_subplan(
+        &self,
+        shared: &SharedSubplan,
+        session_state: &SessionState,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let consumers = shared.state.consumers.load(Ordering::Relaxed);
+        if consumers < 2 {
+            return self.create_initial_plan(&shared.input, session_state).await;
+        }
+
+        let input = shared
+            .state
+            .physical
+            .get_or_try_init(|| async {
+                let input = self
+                    .create_initial_plan(&shared.input, session_state)
+                    .await?;
+                Ok::<_, DataFusionError>(Arc::new(SharedInput::new(input)))
+            })
+            .await?;
+        Ok(Arc::new(SharedExec::new(Arc::clone(input))))
+    }
+
     // return an record_batch which describes a table's schema.
     fn plan_describe(
         &self,
@@ -1990,6 +2109,258 @@ impl DefaultPhysicalPlanner {
     }
 }
 
+/// Replace the outermost occurrences of subplans that appear more than once
+/// in `plan` with [`SharedSubplan`] nodes, so that they are executed once.
+///
+/// Subplans containing volatile expressions or extension nodes are never
+/// shared, nor are subplans re-executed by a recursive query or bare table
+/// scans.
+fn share_common_subplans(plan: LogicalPlan) -> Result<LogicalPlan> {
+    let (sizes, shared) = find_common_subplans(&plan)?;
+    if shared.is_empty() {
+        return Ok(plan);
+    }
+
+    // Visit the nodes in the same order as `find_common_subplans`
+    let mut index = 0;
+    plan.transform_down_mut(&mut |node| {
+        let Some(state) = shared.get(&index) else {
+            index += 1;
+            let recursion = match node {
+                LogicalPlan::RecursiveQuery(_) => TreeNodeRecursion::Jump,
+                _ => TreeNodeRecursion::Continue,
+            };
+            return Ok(Transformed::new(node, false, recursion));
+        };
+        index += sizes[index];
+        state.consumers.fetch_add(1, Ordering::Relaxed);
+        let shared = LogicalPlan::Extension(Extension {
+            node: Arc::new(SharedSubplan {
+                input: node,
+                state: Arc::clone(state),
+            }),
+        });
+        Ok(Transformed::new(shared, true, TreeNodeRecursion::Jump))
+    })
+    .map(|t| t.data)
+}
+
+/// The state shared by the occurrences of each common subplan, by the index
+/// of the occurrences in pre-order
+type SharedSubplans = HashMap<usize, Arc<SharedSubplanState>>;
+
+/// Find the outermost subplans of `plan` that are worth sharing.
+///
+/// Returns the size of the subplan of every node of `plan`, in pre-order,
+/// and the state shared by the occurrences of each common subplan.
+fn find_common_subplans(plan: &LogicalPlan) -> Result<(Vec<usize>, SharedSubplans)> {
+    let mut nodes = vec![];
+    hash_subplans(plan, &mut nodes);
+    let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+        by_hash.entry(node.hash).or_default().push(index);
+    }
+
+    // Only the subplans with equal hashes are compared. The outermost
+    // occurrences of a subplan are grouped by its first occurrence
+    let mut occurrences: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut index = 0;
+    while index < nodes.len() {
+        let node = &nodes[index];
+        let equal: Vec<usize> = by_hash[&node.hash]
+            .iter()
+            .copied()
+            .filter(|&other| other == index || nodes[other].plan == node.plan)
+            .collect();
+        if equal.len() > 1 && is_shareable(node.plan)? {
+            occurrences.entry(equal[0]).or_default().push(index);
+            index += node.size;
+        } else {
+            index += 1;
+        }
+    }
+
+    let mut shared = HashMap::new();
+    for indices in occurrences
+        .into_values()
+        .filter(|indices| indices.len() > 1)
+    {
+        let state = Arc::new(SharedSubplanState::default());
+        shared.extend(indices.into_iter().map(|index| (index, Arc::clone(&state))));
+    }
+    let sizes = nodes.into_iter().map(|node| node.size).collect();
+    Ok((sizes, shared))
+}
+
+/// A node of a logical plan, see [`hash_subplans`]
+struct SubplanNode<'a> {
+    plan: &'a LogicalPlan,
+    /// Hash of the node and of the hashes of its inputs
+    hash: u64,
+    /// Number of nodes of the subplan, including this one
+    size: usize,
+}
+
+/// Append the nodes of `plan` to `nodes` in pre-order, hashing each
+/// subplan once from the hashes of its inputs. The inputs of recursive
+/// queries are not visited. Returns the hash of `plan`.
+fn hash_subplans<'a>(plan: &'a LogicalPlan, nodes: &mut Vec<SubplanNode<'a>>) -> u64 {
+    let index = nodes.len();
+    nodes.push(SubplanNode {
+        plan,
+        hash: 0,
+        size: 1,
+    });
+
+    let mut hasher = DefaultHasher::new();
+    std::mem::discriminant(plan).hash(&mut hasher);
+    plan.schema().hash(&mut hasher);
+    plan.expressions().hash(&mut hasher);
+    if !matches!(plan, LogicalPlan::RecursiveQuery(_)) {
+        for input in plan.inputs() {
+            hash_subplans(input, nodes).hash(&mut hasher);
+        }
+    }
+
+    let hash = hasher.finish();
+    nodes[index].hash = hash;
+    nodes[index].size = nodes.len() - index;
+    hash
+}
+
+/// Returns true if `plan` is a relational subplan that always produces the
+/// same output, and can therefore be executed once for several consumers
+fn is_shareable(plan: &LogicalPlan) -> Result<bool> {
+    if !matches!(
+        plan,
+        LogicalPlan::Projection(_)
+            | LogicalPlan::Filter(_)
+            | LogicalPlan::Window(_)
+            | LogicalPlan::Aggregate(_)
+            | LogicalPlan::Sort(_)
+            | LogicalPlan::Join(_)
+            | LogicalPlan::CrossJoin(_)
+            | LogicalPlan::Repartition(_)
+            | LogicalPlan::Union(_)
+            | LogicalPlan::Subquery(_)
+            | LogicalPlan::SubqueryAlias(_)
+            | LogicalPlan::Limit(_)
+            | LogicalPlan::Distinct(_)
+            | LogicalPlan::Unnest(_)
+    ) || is_bare_scan(plan)
+    {
+        return Ok(false);
+    }
+
+    let mut shareable = true;
+    plan.apply(&mut |node| {
+        if matches!(node, LogicalPlan::Extension(_)) {
+            shareable = false;
+            return Ok(TreeNodeRecursion::Stop);
+        }
+        node.inspect_expressions(|expr| {
+            expr.apply(&mut |e| {
+                if is_volatile(e)? {
+                    shareable = false;
+                    return Ok(TreeNodeRecursion::Stop);
+                }
+                Ok(TreeNodeRecursion::Continue)
+            })
+            .map(|_| ())
+        })?;
+        Ok(if shareable {
+            TreeNodeRecursion::Continue
+        } else {
+            TreeNodeRecursion::Stop
+        })
+    })?;
+    Ok(shareable)
+}
+
+/// Returns true if `plan` only reads a table, which is as cheap to scan
+/// again as to buffer for several consumers
+fn is_bare_scan(plan: &LogicalPlan) -> bool {
+    match plan {
+        LogicalPlan::TableScan(_) => true,
+        LogicalPlan::SubqueryAlias(alias) => is_bare_scan(&alias.input),
+        LogicalPlan::Projection(projection) => {
+            projection
+                .expr
+                .iter()
+                .all(|expr| matches!(expr, Expr::Column(_)))
+                && is_bare_scan(&projection.input)
+        }
+        _ => false,
+    }
+}
+
+/// State shared by all the occurrences of a [`SharedSubplan`]
+#[derive(Debug, Default)]
+struct SharedSubplanState {
+    /// Number of occurrences replaced by a [`SharedSubplan`]
+    consumers: AtomicUsize,
+    /// The physical input, planned by the first occurrence
+    physical: OnceCell<Arc<SharedInput>>,
+}
+
+/// One occurrence of a subplan that is executed once and read by several
+/// consumers, see [`share_common_subplans`].
+///
+/// This node only exists during physical planning.
+#[derive(Debug)]
+struct SharedSubplan {
+    /// The repeated subplan
+    input: LogicalPlan,
+    state: Arc<SharedSubplanState>,
+}
+
+impl PartialEq for SharedSubplan {
+    fn eq(&self, other: &Self) -> bool {
+        self.input == other.input && Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl Eq for SharedSubplan {}
+
+impl Hash for SharedSubplan {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.input.hash(state);
+    }
+}
+
+impl UserDefinedLogicalNodeCore for SharedSubplan {
+    fn name(&self) -> &str {
+        "SharedSubplan"
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        self.input.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "SharedSubplan: consumers={}",
+            self.state.consumers.load(Ordering::Relaxed)
+        )
+    }
+
+    fn from_template(&self, _exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        Self {
+            input: inputs[0].clone(),
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
 fn tuple_err<T, R>(value: (Result<T>, Result<R>)) -> Result<(T, R)> {
     match value {
         (Ok(e), Ok(e1)) => Ok((e, e1)),
@@ -2163,6 +2534,61 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_share_common_subplans() -> Result<()> {
+        let subplan = test_csv_scan()
+            .await?
+            .aggregate(vec![col("c1")], vec![sum(col("c2"))])?
+            .build()?;
+        let logical_plan = LogicalPlanBuilder::from(subplan.clone())
+            .union(subplan)?
+            .build()?;
+        let planner = DefaultPhysicalPlanner::default();
+
+        // disabled by default
+        let session_state = make_session_state();
+        let plan = planner
+            .create_physical_plan(&logical_plan, &session_state)
+            .await?;
+        let displayed = displayable(plan.as_ref()).indent(false).to_string();
+        assert!(!displayed.contains("SharedExec"), "{displayed}");
+
+        let config = SessionConfig::new()
+            .with_target_partitions(4)
+            .set_bool("datafusion.optimizer.share_common_subplans", true);
+        let session_state =
+            SessionState::new_with_config_rt(config, Arc::new(RuntimeEnv::default()));
+        let plan = planner
+            .create_physical_plan(&logical_plan, &session_state)
+            .await?;
+        let displayed = displayable(plan.as_ref()).indent(false).to_string();
+        assert_contains!(&displayed, "SharedExec: consumer=0/2");
+        assert_contains!(&displayed, "SharedExec: consumer=1/2");
+
+        // The input of both consumers is optimized alike and still shared
+        let mut consumers = vec![];
+        plan.apply(&mut |plan| {
+            if let Some(shared) = plan.as_any().downcast_ref::<SharedExec>() {
+                consumers.push(Arc::clone(shared.shared()));
+            }
+            Ok(TreeNodeRecursion::Continue)
+        })?;
+        assert_eq!(consumers.len(), 2);
+        assert!(Arc::ptr_eq(&consumers[0], &consumers[1]));
+
+        // Table scans are not shared
+        let scan = test_csv_scan().await?.build()?;
+        let logical_plan = LogicalPlanBuilder::from(scan.clone())
+            .union(scan)?
+            .build()?;
+        let plan = planner
+            .create_physical_plan(&logical_plan, &session_state)
+            .await?;
+        let displayed = displayable(plan.as_ref()).indent(false).to_string();
+        assert!(!displayed.contains("SharedExec"), "{displayed}");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn error_during_extension_planning() {
         let session_state = make_session_state();
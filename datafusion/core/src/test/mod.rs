@@ -206,6 +206,7 @@ pub fn partitioned_csv_config(
         limit: None,
         table_partition_cols: vec![],
         output_ordering: vec![],
+        bucket_by: None,
     })
 }
 
@@ -292,6 +293,7 @@ pub fn csv_exec_sorted(
             limit: None,
             table_partition_cols: vec![],
             output_ordering: vec![sort_exprs],
+            bucket_by: None,
         },
         false,
         0,
@@ -353,6 +355,7 @@ pub fn csv_exec_ordered(
             limit: None,
             table_partition_cols: vec![],
             output_ordering: vec![sort_exprs],
+            bucket_by: None,
         },
         true,
         0,
@@ -59,6 +59,8 @@ pub struct ParquetScanOptions {
     pub pushdown_filters: bool,
     /// enable reordering filters
     pub reorder_filters: bool,
+    /// enable late materialization
+    pub late_materialization: bool,
     /// enable page index
     pub enable_page_index: bool,
 }
@@ -69,6 +71,7 @@ impl ParquetScanOptions {
         let mut config = ConfigOptions::new();
         config.execution.parquet.pushdown_filters = self.pushdown_filters;
         config.execution.parquet.reorder_filters = self.reorder_filters;
+        config.execution.parquet.late_materialization = self.late_materialization;
         config.execution.parquet.enable_page_index = self.enable_page_index;
         config.into()
     }
@@ -158,6 +161,7 @@ impl TestParquetFile {
             limit: None,
             table_partition_cols: vec![],
             output_ordering: vec![],
+            bucket_by: None,
         };
 
         let df_schema = self.schema.clone().to_dfschema_ref()?;
@@ -25,6 +25,7 @@ use arrow::{
     array::{
         ArrayRef, FixedSizeListBuilder, Int32Array, Int32Builder, ListBuilder,
         StringArray, StringBuilder, StructBuilder, UInt32Array, UInt32Builder,
+        UInt64Array,
     },
     record_batch::RecordBatch,
 };
@@ -2052,3 +2053,65 @@ async fn write_parquet_results() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn copy_reports_written_files() -> Result<()> {
+    let tmp_dir = TempDir::new()?;
+    let config =
+        SessionConfig::new().set_bool("datafusion.execution.report_written_files", true);
+    let ctx = SessionContext::new_with_config(config);
+
+    for format in ["csv", "json", "parquet", "arrow"] {
+        let out_dir = tmp_dir.path().join(format);
+        let sql = format!(
+            "COPY (VALUES ('a', 1), ('b', 2), ('a', 3)) TO '{}/' STORED AS {format} \
+             PARTITIONED BY (column1)",
+            out_dir.to_str().unwrap()
+        );
+        let df = ctx.sql(&sql).await?;
+        let schema = df.schema().clone();
+        let results = df.collect().await?;
+        let batch = &results[0];
+        assert_eq!(batch.schema().field(0).name(), "path");
+        assert_eq!(batch.schema().field(1).name(), "count");
+        assert_eq!(batch.schema(), Arc::new(schema.into()));
+
+        let paths = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let counts = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        let mut written: Vec<_> = paths
+            .iter()
+            .zip(counts.iter())
+            .map(|(path, count)| {
+                let path = path.unwrap();
+                let partition = path.rsplit('/').nth(1).unwrap().to_string();
+                (partition, count.unwrap())
+            })
+            .collect();
+        written.sort();
+        assert_eq!(
+            written,
+            [("column1=a".to_string(), 2), ("column1=b".to_string(), 1)]
+        );
+
+        // Only the committed files are left in the partition directories
+        for (partition, _) in written {
+            let files: Vec<_> = fs::read_dir(out_dir.join(partition))?
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect();
+            assert_eq!(files.len(), 1);
+            assert!(files[0].ends_with(&format!(".{format}")), "{files:?}");
+        }
+        // The staging directories are removed once the files are committed
+        assert!(!out_dir.join("_temporary").exists());
+    }
+
+    Ok(())
+}
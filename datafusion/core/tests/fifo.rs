@@ -29,9 +29,11 @@ mod unix_test {
     use std::thread;
     use std::time::Duration;
 
-    use arrow::array::Array;
+    use arrow::array::{Array, Int32Array, RecordBatch, StringArray};
     use arrow::csv::ReaderBuilder;
     use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ipc::reader::StreamReader;
+    use arrow::ipc::writer::StreamWriter;
     use arrow_schema::SchemaRef;
     use futures::StreamExt;
     use nix::sys::stat;
@@ -45,7 +47,7 @@ mod unix_test {
         prelude::{CsvReadOptions, SessionConfig, SessionContext},
         test_util::{aggr_test_schema, arrow_test_data},
     };
-    use datafusion_common::{exec_err, DataFusionError, Result};
+    use datafusion_common::{assert_batches_eq, exec_err, DataFusionError, Result};
     use datafusion_expr::Expr;
 
     /// Makes a TableProvider for a fifo file
@@ -410,4 +412,83 @@ mod unix_test {
         futures::future::try_join_all(tasks).await.unwrap();
         Ok(())
     }
+
+    /// It tests INSERT INTO between unbounded tables of Arrow IPC streams.
+    #[tokio::test]
+    async fn test_sql_insert_into_arrow_ipc_fifo() -> Result<()> {
+        let config = SessionConfig::new().with_batch_size(TEST_BATCH_SIZE);
+        let ctx = SessionContext::new_with_config(config);
+        let tmp_dir = TempDir::new()?;
+        let source_fifo_path = create_fifo_file(&tmp_dir, "source.arrow")?;
+        let sink_fifo_path = create_fifo_file(&tmp_dir, "sink.arrow")?;
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a1", DataType::Utf8, false),
+            Field::new("a2", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a"; TEST_BATCH_SIZE])),
+                Arc::new(Int32Array::from_iter_values(0..TEST_BATCH_SIZE as i32)),
+            ],
+        )?;
+
+        let mut tasks: Vec<JoinHandle<()>> = vec![];
+        // Write two consecutive streams of two batches each to the source
+        let source_fifo_path_thread = source_fifo_path.clone();
+        #[allow(clippy::disallowed_methods)] // spawn allowed only in tests
+        tasks.push(spawn_blocking(move || {
+            let file = OpenOptions::new()
+                .write(true)
+                .open(source_fifo_path_thread)
+                .unwrap();
+            for _ in 0..2 {
+                let mut writer = StreamWriter::try_new(&file, &schema).unwrap();
+                writer.write(&batch).unwrap();
+                writer.write(&batch).unwrap();
+                writer.finish().unwrap();
+            }
+        }));
+        let sink_fifo_path_thread = sink_fifo_path.clone();
+        #[allow(clippy::disallowed_methods)] // spawn allowed only in tests
+        tasks.push(spawn_blocking(move || {
+            let file = File::open(sink_fifo_path_thread).unwrap();
+            let reader = StreamReader::try_new(file, None).unwrap();
+            let rows = reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>();
+            assert_eq!(rows, 4 * TEST_BATCH_SIZE);
+        }));
+
+        for (name, path) in [
+            ("source_table", source_fifo_path),
+            ("sink_table", sink_fifo_path),
+        ] {
+            ctx.sql(&format!(
+                "CREATE UNBOUNDED EXTERNAL TABLE {name} (
+                    a1  VARCHAR NOT NULL,
+                    a2  INT NOT NULL
+                )
+                STORED AS ARROW
+                LOCATION '{}'",
+                path.display()
+            ))
+            .await?;
+        }
+
+        let df = ctx
+            .sql("INSERT INTO sink_table SELECT a1, a2 FROM source_table")
+            .await?;
+        let count = df.collect().await?;
+        assert_batches_eq!(
+            [
+                "+-------+",
+                "| count |",
+                "+-------+",
+                "| 80    |",
+                "+-------+",
+            ],
+            &count
+        );
+        futures::future::try_join_all(tasks).await.unwrap();
+        Ok(())
+    }
 }
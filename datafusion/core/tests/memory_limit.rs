@@ -323,6 +323,23 @@ async fn oom_recursive_cte() {
         .await
 }
 
+#[tokio::test]
+async fn query_memory_limit() {
+    let mut config = SessionConfig::new();
+    config.options_mut().execution.query_memory_limit = Some(2_000);
+    TestCase::new()
+        .with_query("select count(*) from t GROUP BY response_bytes")
+        .with_expected_errors(vec![
+            "Resources exhausted: Failed to allocate additional",
+            "GroupedHashAggregateStream",
+            "maximum available is 2000 in query 0",
+        ])
+        .with_memory_limit(1_000_000)
+        .with_config(config)
+        .run()
+        .await
+}
+
 /// Run the query with the specified memory limit,
 /// and verifies the expected errors are returned
 #[derive(Clone, Debug)]
@@ -441,6 +441,7 @@ impl<'a> TestCase<'a> {
                 ParquetScanOptions {
                     pushdown_filters: false,
                     reorder_filters: false,
+                    late_materialization: false,
                     enable_page_index: false,
                 },
                 filter,
@@ -452,6 +453,7 @@ impl<'a> TestCase<'a> {
                 ParquetScanOptions {
                     pushdown_filters: true,
                     reorder_filters: false,
+                    late_materialization: false,
                     enable_page_index: false,
                 },
                 filter,
@@ -465,6 +467,7 @@ impl<'a> TestCase<'a> {
                 ParquetScanOptions {
                     pushdown_filters: true,
                     reorder_filters: true,
+                    late_materialization: false,
                     enable_page_index: false,
                 },
                 filter,
@@ -478,6 +481,7 @@ impl<'a> TestCase<'a> {
                 ParquetScanOptions {
                     pushdown_filters: false,
                     reorder_filters: false,
+                    late_materialization: false,
                     enable_page_index: true,
                 },
                 filter,
@@ -490,6 +494,7 @@ impl<'a> TestCase<'a> {
                 ParquetScanOptions {
                     pushdown_filters: true,
                     reorder_filters: true,
+                    late_materialization: false,
                     enable_page_index: true,
                 },
                 filter,
@@ -497,6 +502,20 @@ impl<'a> TestCase<'a> {
             .await;
 
         assert_eq!(no_pushdown, pushdown_reordering_and_page_index);
+
+        let late_materialization = self
+            .read_with_options(
+                ParquetScanOptions {
+                    pushdown_filters: false,
+                    reorder_filters: false,
+                    late_materialization: true,
+                    enable_page_index: true,
+                },
+                filter,
+            )
+            .await;
+
+        assert_eq!(no_pushdown, late_materialization);
     }
 
     /// Reads data from a test parquet file using the specified scan options
@@ -533,12 +552,15 @@ impl<'a> TestCase<'a> {
         let metrics =
             TestParquetFile::parquet_metrics(exec).expect("found parquet metrics");
 
-        let pushdown_expected = if scan_options.pushdown_filters {
-            self.pushdown_expected
-        } else {
-            // if filter pushdown is not enabled we don't expect it to filter rows
-            PushdownExpected::None
-        };
+        let pushdown_expected =
+            if scan_options.pushdown_filters || scan_options.late_materialization {
+                // the filter columns are smaller than the other projected
+                // columns, so late materialization evaluates them in the scan
+                self.pushdown_expected
+            } else {
+                // if filter pushdown is not enabled we don't expect it to filter rows
+                PushdownExpected::None
+            };
 
         let pushdown_rows_filtered = get_value(&metrics, "pushdown_rows_filtered");
         println!("  pushdown_rows_filtered: {pushdown_rows_filtered}");
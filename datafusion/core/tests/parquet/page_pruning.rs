@@ -80,6 +80,7 @@ async fn get_parquet_exec(state: &SessionState, filter: Expr) -> ParquetExec {
             limit: None,
             table_partition_cols: vec![],
             output_ordering: vec![],
+            bucket_by: None,
         },
         Some(predicate),
         None,
@@ -362,6 +362,67 @@ async fn csv_explain_verbose() {
     assert_contains!(actual, "SAME TEXT AS ABOVE");
 }
 
+#[tokio::test]
+async fn csv_explain_rules() {
+    let config = SessionConfig::new().with_target_partitions(4);
+    let ctx = SessionContext::new_with_config(config);
+    register_aggregate_csv_by_sql(&ctx).await;
+    let sql = "EXPLAIN (RULES on) SELECT c1 FROM aggregate_test_100 where c2 > 10";
+    let actual = execute(&ctx, sql).await;
+    let actual = actual.into_iter().map(|r| r.join("\t")).collect::<String>();
+
+    // only the rules changing the plan are shown, with the time they took
+    assert_contains!(&actual, "initial_logical_plan");
+    assert_contains!(&actual, "elapsed=");
+    assert_contains!(&actual, "physical_plan after EnforceDistribution");
+    assert_contains!(
+        &actual,
+        "+    RepartitionExec: partitioning=RoundRobinBatch(4)"
+    );
+    assert_not_contains!(&actual, "SAME TEXT AS ABOVE");
+    assert_not_contains!(&actual, "physical_plan after PipelineChecker");
+    assert_not_contains!(actual, "physical_plan_with_stats");
+}
+
+#[tokio::test]
+async fn csv_explain_format_json() {
+    let ctx = SessionContext::new();
+    register_aggregate_csv_by_sql(&ctx).await;
+    let sql = "EXPLAIN (FORMAT json) SELECT c1 FROM aggregate_test_100 where c2 > 10";
+    let actual = execute(&ctx, sql).await;
+
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual[0][0], "logical_plan");
+    let logical_plan: serde_json::Value = serde_json::from_str(&actual[0][1]).unwrap();
+    assert_eq!(
+        logical_plan,
+        serde_json::json!({
+            "plan_type": "logical_plan",
+            "plan": {
+                "node": "Projection",
+                "details": "aggregate_test_100.c1",
+                "children": [{
+                    "node": "Filter",
+                    "details": "aggregate_test_100.c2 > Int8(10)",
+                    "children": [{
+                        "node": "TableScan",
+                        "details": "aggregate_test_100 projection=[c1, c2], partial_filters=[aggregate_test_100.c2 > Int8(10)]",
+                        "children": []
+                    }]
+                }]
+            }
+        })
+    );
+    assert_eq!(actual[1][0], "physical_plan");
+    let physical_plan: serde_json::Value = serde_json::from_str(&actual[1][1]).unwrap();
+    assert_eq!(physical_plan["plan_type"], "physical_plan");
+    assert_eq!(physical_plan["plan"]["node"], "ProjectionExec");
+    assert_eq!(
+        physical_plan["plan"]["children"][0]["node"],
+        "CoalesceBatchesExec"
+    );
+}
+
 #[tokio::test]
 async fn csv_explain_inlist_verbose() {
     let ctx = SessionContext::new();
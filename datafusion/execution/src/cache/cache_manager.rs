@@ -16,10 +16,14 @@
 // under the License.
 
 use crate::cache::CacheAccessor;
+use bytes::Bytes;
 use datafusion_common::{Result, Statistics};
 use object_store::path::Path;
 use object_store::ObjectMeta;
+#[cfg(feature = "parquet")]
+use parquet::file::metadata::ParquetMetaData;
 use std::fmt::{Debug, Formatter};
+use std::ops::Range;
 use std::sync::Arc;
 
 /// The cache of listing files statistics.
@@ -32,6 +36,69 @@ pub type FileStatisticsCache =
 pub type ListFilesCache =
     Arc<dyn CacheAccessor<Path, Arc<Vec<ObjectMeta>>, Extra = ObjectMeta>>;
 
+/// The cache of decoded parquet footers, including their page index.
+/// if set [`CacheManagerConfig::with_parquet_metadata_cache`]
+/// Will avoid fetching and decoding the footer of the same file for every
+/// scan, this cache will store in [`crate::runtime_env::RuntimeEnv`].
+#[cfg(feature = "parquet")]
+pub type ParquetMetadataCache = Arc<dyn ParquetMetadataCacheAccessor>;
+
+/// A cache of decoded parquet footers bounded by the memory they use.
+///
+/// The limit can be changed when the cache manager is built, see
+/// [`CacheManagerConfig::with_parquet_metadata_cache_limit`].
+#[cfg(feature = "parquet")]
+pub trait ParquetMetadataCacheAccessor:
+    CacheAccessor<Path, Arc<ParquetMetaData>, Extra = ObjectMeta>
+{
+    /// Returns the maximum memory in bytes of the cached metadata
+    fn memory_limit(&self) -> usize;
+    /// Change the maximum memory in bytes of the cached metadata, evicting
+    /// the least recently used metadata until it fits
+    fn update_memory_limit(&self, limit: usize);
+    /// Returns the memory in bytes of the cached metadata
+    fn memory_size(&self) -> usize;
+}
+
+/// The cache of byte ranges read from object stores.
+/// if set [`CacheManagerConfig::with_byte_range_cache`]
+/// Will avoid reading the same ranges of files, e.g. parquet column chunks,
+/// from remote object stores repeatedly, see
+/// [`crate::cache::caching_object_store::CachingObjectStore`].
+/// The extra value is the version of the object, if known.
+pub type ByteRangeCache = Arc<dyn ByteRangeCacheAccessor>;
+
+/// A cache of byte ranges bounded by the bytes it holds in memory and, if it
+/// has a disk tier, on disk.
+///
+/// The limits can be changed when the cache manager is built, see
+/// [`CacheManagerConfig::with_byte_range_cache_memory_limit`].
+pub trait ByteRangeCacheAccessor:
+    CacheAccessor<ByteRangeKey, Bytes, Extra = Option<String>>
+{
+    /// Change the maximum number of bytes held in memory, evicting the least
+    /// recently used ranges until they fit
+    fn update_memory_limit(&self, limit: usize);
+    /// Change the maximum number of bytes held on disk, evicting the least
+    /// recently used ranges until they fit
+    fn update_disk_limit(&self, limit: usize);
+    /// Returns the number of bytes held in memory
+    fn memory_size(&self) -> usize;
+    /// Returns the number of bytes held on disk
+    fn disk_size(&self) -> usize;
+}
+
+/// Identifies a range of bytes of an object in an object store
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ByteRangeKey {
+    /// The object store holding the object, e.g. `s3://bucket`
+    pub store: String,
+    /// The location of the object in the store
+    pub location: Path,
+    /// The range of bytes of the object
+    pub range: Range<usize>,
+}
+
 impl Debug for dyn CacheAccessor<Path, Arc<Statistics>, Extra = ObjectMeta> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "Cache name: {} with length: {}", self.name(), self.len())
@@ -44,10 +111,26 @@ impl Debug for dyn CacheAccessor<Path, Arc<Vec<ObjectMeta>>, Extra = ObjectMeta>
     }
 }
 
+#[cfg(feature = "parquet")]
+impl Debug for dyn ParquetMetadataCacheAccessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cache name: {} with length: {}", self.name(), self.len())
+    }
+}
+
+impl Debug for dyn ByteRangeCacheAccessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cache name: {} with length: {}", self.name(), self.len())
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct CacheManager {
     file_statistic_cache: Option<FileStatisticsCache>,
     list_files_cache: Option<ListFilesCache>,
+    #[cfg(feature = "parquet")]
+    parquet_metadata_cache: Option<ParquetMetadataCache>,
+    byte_range_cache: Option<ByteRangeCache>,
 }
 
 impl CacheManager {
@@ -59,6 +142,22 @@ impl CacheManager {
         if let Some(lc) = &config.list_files_cache {
             manager.list_files_cache = Some(lc.clone())
         }
+        #[cfg(feature = "parquet")]
+        if let Some(pc) = &config.parquet_metadata_cache {
+            if let Some(limit) = config.parquet_metadata_cache_limit {
+                pc.update_memory_limit(limit);
+            }
+            manager.parquet_metadata_cache = Some(pc.clone())
+        }
+        if let Some(bc) = &config.byte_range_cache {
+            if let Some(limit) = config.byte_range_cache_memory_limit {
+                bc.update_memory_limit(limit);
+            }
+            if let Some(limit) = config.byte_range_cache_disk_limit {
+                bc.update_disk_limit(limit);
+            }
+            manager.byte_range_cache = Some(bc.clone())
+        }
         Ok(Arc::new(manager))
     }
 
@@ -71,6 +170,17 @@ impl CacheManager {
     pub fn get_list_files_cache(&self) -> Option<ListFilesCache> {
         self.list_files_cache.clone()
     }
+
+    /// Get the cache of decoded parquet footers.
+    #[cfg(feature = "parquet")]
+    pub fn get_parquet_metadata_cache(&self) -> Option<ParquetMetadataCache> {
+        self.parquet_metadata_cache.clone()
+    }
+
+    /// Get the cache of byte ranges read from object stores.
+    pub fn get_byte_range_cache(&self) -> Option<ByteRangeCache> {
+        self.byte_range_cache.clone()
+    }
 }
 
 #[derive(Clone, Default)]
@@ -86,6 +196,37 @@ pub struct CacheManagerConfig {
     /// location.  
     /// Default is disable.
     pub list_files_cache: Option<ListFilesCache>,
+    /// Enable cache of decoded parquet footers, including their page index.
+    /// Avoids fetching and decoding the metadata of the same file for every
+    /// query, which matters when repeatedly scanning many remote files.
+    /// Its memory is bounded by `parquet_metadata_cache_limit`.
+    /// Default is disable.
+    #[cfg(feature = "parquet")]
+    pub parquet_metadata_cache: Option<ParquetMetadataCache>,
+    /// Maximum memory in bytes of the decoded footers and page indexes held
+    /// by `parquet_metadata_cache`. The least recently used metadata is
+    /// evicted when the cache is full.
+    /// Default is `None`, where the cache keeps the limit it was created with.
+    #[cfg(feature = "parquet")]
+    pub parquet_metadata_cache_limit: Option<usize>,
+    /// Enable cache of byte ranges read from object stores.
+    /// The object stores of the [`crate::runtime_env::RuntimeEnv`] then
+    /// serve repeated reads of the same ranges, e.g. parquet column chunks,
+    /// from the cache, as long as the objects do not change.
+    /// Its size is bounded by `byte_range_cache_memory_limit` and
+    /// `byte_range_cache_disk_limit`.
+    /// Default is disable.
+    pub byte_range_cache: Option<ByteRangeCache>,
+    /// Maximum number of bytes held in memory by `byte_range_cache`. The
+    /// least recently used ranges are moved to disk, if the cache has a disk
+    /// tier, or evicted when full.
+    /// Default is `None`, where the cache keeps the limit it was created with.
+    pub byte_range_cache_memory_limit: Option<usize>,
+    /// Maximum number of bytes held in temporary files by `byte_range_cache`,
+    /// if it has a disk tier. The least recently used ranges are evicted when
+    /// full.
+    /// Default is `None`, where the cache keeps the limit it was created with.
+    pub byte_range_cache_disk_limit: Option<usize>,
 }
 
 impl CacheManagerConfig {
@@ -101,4 +242,34 @@ impl CacheManagerConfig {
         self.list_files_cache = cache;
         self
     }
+
+    #[cfg(feature = "parquet")]
+    pub fn with_parquet_metadata_cache(
+        mut self,
+        cache: Option<ParquetMetadataCache>,
+    ) -> Self {
+        self.parquet_metadata_cache = cache;
+        self
+    }
+
+    #[cfg(feature = "parquet")]
+    pub fn with_parquet_metadata_cache_limit(mut self, limit: Option<usize>) -> Self {
+        self.parquet_metadata_cache_limit = limit;
+        self
+    }
+
+    pub fn with_byte_range_cache(mut self, cache: Option<ByteRangeCache>) -> Self {
+        self.byte_range_cache = cache;
+        self
+    }
+
+    pub fn with_byte_range_cache_memory_limit(mut self, limit: Option<usize>) -> Self {
+        self.byte_range_cache_memory_limit = limit;
+        self
+    }
+
+    pub fn with_byte_range_cache_disk_limit(mut self, limit: Option<usize>) -> Self {
+        self.byte_range_cache_disk_limit = limit;
+        self
+    }
 }
@@ -17,13 +17,23 @@
 
 use std::sync::Arc;
 
+#[cfg(feature = "parquet")]
+use crate::cache::cache_manager::ParquetMetadataCacheAccessor;
+use crate::cache::cache_manager::{ByteRangeCacheAccessor, ByteRangeKey};
+use crate::cache::lru::LruCache;
 use crate::cache::CacheAccessor;
+use crate::disk_manager::{DiskManager, RefCountedTempFile};
 
-use datafusion_common::Statistics;
+use datafusion_common::{DataFusionError, Result, Statistics};
 
+use bytes::Bytes;
 use dashmap::DashMap;
+use log::debug;
 use object_store::path::Path;
 use object_store::ObjectMeta;
+use parking_lot::Mutex;
+#[cfg(feature = "parquet")]
+use parquet::file::metadata::ParquetMetaData;
 
 /// Collected statistics for files
 /// Cache is invalided when file size or last modification has changed
@@ -157,10 +167,421 @@ impl CacheAccessor<Path, Arc<Vec<ObjectMeta>>> for DefaultListFilesCache {
     }
 }
 
+/// Decoded footers of parquet files, including their page index.
+///
+/// Holds metadata up to a memory limit in bytes, evicting the least recently
+/// used files when full. The metadata of a file is invalidated when its size,
+/// last modification or e_tag changes.
+#[cfg(feature = "parquet")]
+#[derive(Debug)]
+pub struct DefaultParquetMetadataCache {
+    metadata: Mutex<LruCache<Path, (ObjectMeta, Arc<ParquetMetaData>)>>,
+}
+
+#[cfg(feature = "parquet")]
+impl DefaultParquetMetadataCache {
+    /// The memory limit in bytes of [`Self::default`]
+    pub const DEFAULT_MEMORY_LIMIT: usize = 50 * 1024 * 1024;
+
+    /// Create a cache holding up to `memory_limit` bytes of metadata
+    pub fn new(memory_limit: usize) -> Self {
+        Self {
+            metadata: Mutex::new(LruCache::new(memory_limit)),
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl Default for DefaultParquetMetadataCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MEMORY_LIMIT)
+    }
+}
+
+/// Estimates the memory in bytes held by `metadata`, including the variable
+/// length statistics and the page index
+#[cfg(feature = "parquet")]
+fn parquet_metadata_memory_size(metadata: &ParquetMetaData) -> usize {
+    use parquet::file::metadata::{ColumnChunkMetaData, RowGroupMetaData};
+    use parquet::file::page_index::index::Index;
+    use parquet::format::PageLocation;
+    use parquet::schema::types::ColumnDescriptor;
+    use std::mem::{size_of, size_of_val};
+
+    // The size of the pages of a `NativeIndex`, whose value type is private
+    macro_rules! page_index_size {
+        ($index:expr, $value_size:expr) => {
+            $index
+                .indexes
+                .iter()
+                .map(|page| {
+                    size_of_val(page)
+                        + page.min.as_ref().map_or(0, $value_size)
+                        + page.max.as_ref().map_or(0, $value_size)
+                })
+                .sum::<usize>()
+        };
+    }
+
+    let file_metadata = metadata.file_metadata();
+    let mut size = size_of::<ParquetMetaData>()
+        + file_metadata.schema_descr().num_columns() * size_of::<ColumnDescriptor>()
+        + file_metadata.created_by().map_or(0, |s| s.len());
+    for key_value in file_metadata.key_value_metadata().into_iter().flatten() {
+        size += key_value.key.len() + key_value.value.as_ref().map_or(0, |v| v.len());
+    }
+
+    for row_group in metadata.row_groups() {
+        size += size_of::<RowGroupMetaData>();
+        for column in row_group.columns() {
+            size += size_of::<ColumnChunkMetaData>()
+                + column.file_path().map_or(0, |s| s.len());
+            if let Some(statistics) = column.statistics() {
+                if statistics.has_min_max_set() {
+                    size += statistics.min_bytes().len() + statistics.max_bytes().len();
+                }
+            }
+        }
+    }
+
+    for index in metadata.column_index().into_iter().flatten().flatten() {
+        size += size_of::<Index>()
+            + match index {
+                Index::NONE => 0,
+                Index::BOOLEAN(index) => page_index_size!(index, |_| 0),
+                Index::INT32(index) => page_index_size!(index, |_| 0),
+                Index::INT64(index) => page_index_size!(index, |_| 0),
+                Index::INT96(index) => page_index_size!(index, |_| 0),
+                Index::FLOAT(index) => page_index_size!(index, |_| 0),
+                Index::DOUBLE(index) => page_index_size!(index, |_| 0),
+                Index::BYTE_ARRAY(index) => page_index_size!(index, |v| v.len()),
+                Index::FIXED_LEN_BYTE_ARRAY(index) => {
+                    page_index_size!(index, |v| v.len())
+                }
+            };
+    }
+    for pages in metadata.offset_index().into_iter().flatten().flatten() {
+        size += pages.len() * size_of::<PageLocation>();
+    }
+    size
+}
+
+#[cfg(feature = "parquet")]
+impl CacheAccessor<Path, Arc<ParquetMetaData>> for DefaultParquetMetadataCache {
+    type Extra = ObjectMeta;
+
+    fn get(&self, k: &Path) -> Option<Arc<ParquetMetaData>> {
+        self.metadata
+            .lock()
+            .get(k)
+            .map(|(_, metadata)| metadata.clone())
+    }
+
+    /// Get the metadata of file `e`. Returns None if the file has changed or
+    /// is not cached.
+    fn get_with_extra(&self, k: &Path, e: &Self::Extra) -> Option<Arc<ParquetMetaData>> {
+        let mut metadata = self.metadata.lock();
+        let (saved_meta, file_metadata) = metadata.get(k)?;
+        if saved_meta.size != e.size
+            || saved_meta.last_modified != e.last_modified
+            || saved_meta.e_tag != e.e_tag
+        {
+            // file has changed
+            None
+        } else {
+            Some(file_metadata.clone())
+        }
+    }
+
+    fn put(
+        &self,
+        _key: &Path,
+        _value: Arc<ParquetMetaData>,
+    ) -> Option<Arc<ParquetMetaData>> {
+        panic!("Put cache in DefaultParquetMetadataCache without Extra not supported.")
+    }
+
+    fn put_with_extra(
+        &self,
+        key: &Path,
+        value: Arc<ParquetMetaData>,
+        e: &Self::Extra,
+    ) -> Option<Arc<ParquetMetaData>> {
+        let size = parquet_metadata_memory_size(&value);
+        self.metadata
+            .lock()
+            .put(key.clone(), (e.clone(), value), size)
+            .map(|x| x.1)
+    }
+
+    fn remove(&mut self, k: &Path) -> Option<Arc<ParquetMetaData>> {
+        self.metadata.lock().remove(k).map(|x| x.1)
+    }
+
+    fn contains_key(&self, k: &Path) -> bool {
+        self.metadata.lock().contains_key(k)
+    }
+
+    fn len(&self) -> usize {
+        self.metadata.lock().len()
+    }
+
+    fn clear(&self) {
+        self.metadata.lock().clear()
+    }
+
+    fn name(&self) -> String {
+        "DefaultParquetMetadataCache".to_string()
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetMetadataCacheAccessor for DefaultParquetMetadataCache {
+    fn memory_limit(&self) -> usize {
+        self.metadata.lock().capacity()
+    }
+
+    fn update_memory_limit(&self, limit: usize) {
+        let mut metadata = self.metadata.lock();
+        if metadata.capacity() != limit {
+            metadata.set_capacity(limit);
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        self.metadata.lock().weight()
+    }
+}
+
+/// Byte ranges read from object stores, kept in memory and moved to
+/// temporary files of a [`DiskManager`] when the memory is full.
+///
+/// Each tier holds up to its limit in bytes, evicting the least recently used
+/// ranges when full. A range evicted from memory is written to disk, if there
+/// is a disk tier, and a range read from disk moves back to memory.
+///
+/// The extra value is the version of the object the range was read from.
+/// [`CacheAccessor::get_with_extra`] only returns ranges read from the same
+/// version, while [`CacheAccessor::get`] ignores the version.
+#[derive(Debug)]
+pub struct DefaultByteRangeCache {
+    memory: Mutex<LruCache<ByteRangeKey, (Option<String>, Bytes)>>,
+    disk: Option<DiskTier>,
+}
+
+/// The ranges of a [`DefaultByteRangeCache`] kept in temporary files
+#[derive(Debug)]
+struct DiskTier {
+    disk_manager: Arc<DiskManager>,
+    files: Mutex<LruCache<ByteRangeKey, (Option<String>, RefCountedTempFile)>>,
+}
+
+impl DefaultByteRangeCache {
+    /// Create a cache holding up to `memory_limit` bytes in memory
+    pub fn new(memory_limit: usize) -> Self {
+        Self {
+            memory: Mutex::new(LruCache::new(memory_limit)),
+            disk: None,
+        }
+    }
+
+    /// Also hold up to `disk_limit` bytes in temporary files created by
+    /// `disk_manager`
+    pub fn with_disk_tier(
+        mut self,
+        disk_manager: Arc<DiskManager>,
+        disk_limit: usize,
+    ) -> Self {
+        self.disk = Some(DiskTier {
+            disk_manager,
+            files: Mutex::new(LruCache::new(disk_limit)),
+        });
+        self
+    }
+
+    /// Look up `key`, only accepting ranges of `version` if given
+    fn lookup(
+        &self,
+        key: &ByteRangeKey,
+        version: Option<&Option<String>>,
+    ) -> Option<Bytes> {
+        let accept = |cached: &Option<String>| version.map_or(true, |v| v == cached);
+
+        // don't hold the lock while moving the range back from disk
+        let in_memory = self.memory.lock().get(key).cloned();
+        if let Some((cached, data)) = in_memory {
+            if accept(&cached) {
+                return Some(data);
+            }
+        } else if let Some(disk) = &self.disk {
+            let mut files = disk.files.lock();
+            if files.get(key).is_some_and(|(cached, _)| accept(cached)) {
+                let (cached, file) = files.remove(key).expect("just found");
+                drop(files);
+                match std::fs::read(file.path()) {
+                    Ok(data) => {
+                        let data = Bytes::from(data);
+                        self.put_with_extra(key, data.clone(), &cached);
+                        return Some(data);
+                    }
+                    Err(e) => debug!("Failed to read cached byte range: {e}"),
+                }
+            }
+        }
+        None
+    }
+
+    /// Move ranges evicted from memory to disk, if there is a disk tier
+    fn spill(&self, evicted: Vec<(ByteRangeKey, (Option<String>, Bytes))>) {
+        let Some(disk) = &self.disk else {
+            return;
+        };
+        for (key, (version, data)) in evicted {
+            if data.len() > disk.files.lock().capacity() {
+                continue;
+            }
+            match disk.write(&data) {
+                Ok(file) => {
+                    disk.files.lock().put(key, (version, file), data.len());
+                }
+                Err(e) => debug!("Failed to spill cached byte range: {e}"),
+            }
+        }
+    }
+}
+
+impl DiskTier {
+    fn write(&self, data: &[u8]) -> Result<RefCountedTempFile> {
+        let mut file = self.disk_manager.create_tmp_file("caching byte range")?;
+        std::fs::write(file.path(), data).map_err(DataFusionError::IoError)?;
+        file.update_disk_usage()?;
+        Ok(file)
+    }
+}
+
+impl CacheAccessor<ByteRangeKey, Bytes> for DefaultByteRangeCache {
+    type Extra = Option<String>;
+
+    fn get(&self, k: &ByteRangeKey) -> Option<Bytes> {
+        self.lookup(k, None)
+    }
+
+    /// Get the range `k` read from version `e` of the object. Returns None if
+    /// the object has changed or the range is not cached.
+    fn get_with_extra(&self, k: &ByteRangeKey, e: &Self::Extra) -> Option<Bytes> {
+        self.lookup(k, Some(e))
+    }
+
+    fn put(&self, key: &ByteRangeKey, value: Bytes) -> Option<Bytes> {
+        self.put_with_extra(key, value, &None)
+    }
+
+    fn put_with_extra(
+        &self,
+        key: &ByteRangeKey,
+        value: Bytes,
+        e: &Self::Extra,
+    ) -> Option<Bytes> {
+        if let Some(disk) = &self.disk {
+            // don't keep an outdated copy on disk
+            disk.files.lock().remove(key);
+        }
+
+        let size = value.len();
+        let mut memory = self.memory.lock();
+        let previous = memory.remove(key).map(|x| x.1);
+        let evicted = if size > memory.capacity() {
+            // too large for memory, but may fit on disk
+            vec![(key.clone(), (e.clone(), value))]
+        } else {
+            let evicted = memory.make_room(size);
+            memory.put(key.clone(), (e.clone(), value), size);
+            evicted
+        };
+        drop(memory);
+
+        self.spill(evicted);
+        previous
+    }
+
+    fn remove(&mut self, k: &ByteRangeKey) -> Option<Bytes> {
+        let previous = self.memory.lock().remove(k).map(|x| x.1);
+        if let Some(disk) = &self.disk {
+            disk.files.lock().remove(k);
+        }
+        previous
+    }
+
+    fn contains_key(&self, k: &ByteRangeKey) -> bool {
+        self.memory.lock().contains_key(k)
+            || self
+                .disk
+                .as_ref()
+                .is_some_and(|disk| disk.files.lock().contains_key(k))
+    }
+
+    fn len(&self) -> usize {
+        let on_disk = self
+            .disk
+            .as_ref()
+            .map(|disk| disk.files.lock().len())
+            .unwrap_or_default();
+        self.memory.lock().len() + on_disk
+    }
+
+    fn clear(&self) {
+        self.memory.lock().clear();
+        if let Some(disk) = &self.disk {
+            disk.files.lock().clear();
+        }
+    }
+
+    fn name(&self) -> String {
+        "DefaultByteRangeCache".to_string()
+    }
+}
+
+impl ByteRangeCacheAccessor for DefaultByteRangeCache {
+    fn update_memory_limit(&self, limit: usize) {
+        let mut memory = self.memory.lock();
+        if memory.capacity() == limit {
+            return;
+        }
+        let evicted = memory.set_capacity(limit);
+        drop(memory);
+        self.spill(evicted);
+    }
+
+    fn update_disk_limit(&self, limit: usize) {
+        if let Some(disk) = &self.disk {
+            let mut files = disk.files.lock();
+            if files.capacity() != limit {
+                // the evicted temporary files are deleted when dropped
+                files.set_capacity(limit);
+            }
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        self.memory.lock().weight()
+    }
+
+    fn disk_size(&self) -> usize {
+        self.disk
+            .as_ref()
+            .map(|disk| disk.files.lock().weight())
+            .unwrap_or_default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::cache::cache_unit::{DefaultFileStatisticsCache, DefaultListFilesCache};
+    use crate::cache::cache_manager::{ByteRangeCacheAccessor, ByteRangeKey};
+    use crate::cache::cache_unit::{
+        DefaultByteRangeCache, DefaultFileStatisticsCache, DefaultListFilesCache,
+    };
     use crate::cache::CacheAccessor;
+    use crate::disk_manager::{DiskManager, DiskManagerConfig};
     use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
     use chrono::DateTime;
     use datafusion_common::Statistics;
@@ -232,4 +653,141 @@ mod tests {
             meta.clone()
         );
     }
+
+    #[test]
+    fn test_byte_range_cache() {
+        let key = |start: usize| ByteRangeKey {
+            store: "s3://bucket".to_string(),
+            location: Path::from("test"),
+            range: start..start + 4,
+        };
+        let v1 = Some("1".to_string());
+        let disk_manager = DiskManager::try_new(DiskManagerConfig::NewOs).unwrap();
+        let cache = DefaultByteRangeCache::new(8).with_disk_tier(disk_manager, 4);
+
+        cache.put_with_extra(&key(0), "0123".into(), &v1);
+        cache.put_with_extra(&key(4), "4567".into(), &v1);
+        assert_eq!(cache.memory_size(), 8);
+        assert_eq!(cache.disk_size(), 0);
+
+        // the least recently used range moves to disk
+        cache.put_with_extra(&key(8), "89ab".into(), &v1);
+        assert_eq!(cache.disk_size(), 4);
+        assert_eq!(cache.len(), 3);
+
+        // and back to memory when read, moving another range to disk
+        assert_eq!(cache.get_with_extra(&key(0), &v1).unwrap(), "0123");
+        assert_eq!(cache.memory_size(), 8);
+        assert!(cache.contains_key(&key(4)));
+
+        // the disk is full, so a further range is dropped
+        cache.put_with_extra(&key(12), "cdef".into(), &v1);
+        assert_eq!(cache.len(), 3);
+        assert!(!cache.contains_key(&key(4)));
+
+        // ranges of another version of the object are not returned
+        assert!(cache
+            .get_with_extra(&key(12), &Some("2".to_string()))
+            .is_none());
+        assert!(cache.get(&key(12)).is_some());
+
+        // shrinking the memory moves the least recently used range to disk,
+        // evicting the range on disk
+        cache.update_memory_limit(4);
+        assert_eq!(cache.memory_size(), 4);
+        assert_eq!(cache.disk_size(), 4);
+        assert!(cache.contains_key(&key(0)));
+        assert!(!cache.contains_key(&key(8)));
+
+        cache.update_disk_limit(0);
+        assert_eq!(cache.disk_size(), 0);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_cache_manager_limits() {
+        use crate::cache::cache_manager::{CacheManager, CacheManagerConfig};
+        use std::sync::Arc;
+
+        let key = |start: usize| ByteRangeKey {
+            store: "s3://bucket".to_string(),
+            location: Path::from("test"),
+            range: start..start + 4,
+        };
+        let v1 = Some("1".to_string());
+        let cache = Arc::new(DefaultByteRangeCache::new(8));
+        cache.put_with_extra(&key(0), "0123".into(), &v1);
+        cache.put_with_extra(&key(4), "4567".into(), &v1);
+
+        // the limits are applied once, when the cache manager is built
+        let config = CacheManagerConfig::default()
+            .with_byte_range_cache(Some(cache.clone()))
+            .with_byte_range_cache_memory_limit(Some(4));
+        let manager = CacheManager::try_new(&config).unwrap();
+        assert_eq!(cache.memory_size(), 4);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains_key(&key(4)));
+        assert_eq!(manager.get_byte_range_cache().unwrap().memory_size(), 4);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_metadata_cache() {
+        use super::{parquet_metadata_memory_size, DefaultParquetMetadataCache};
+        use crate::cache::cache_manager::ParquetMetadataCacheAccessor;
+        use parquet::file::metadata::{FileMetaData, ParquetMetaData};
+        use parquet::schema::types::{SchemaDescriptor, Type};
+        use std::sync::Arc;
+
+        let schema = Type::group_type_builder("schema").build().unwrap();
+        let metadata = Arc::new(ParquetMetaData::new(
+            FileMetaData::new(
+                1,
+                0,
+                None,
+                None,
+                Arc::new(SchemaDescriptor::new(Arc::new(schema))),
+                None,
+            ),
+            vec![],
+        ));
+        let meta = |location: &str| ObjectMeta {
+            location: Path::from(location),
+            last_modified: DateTime::parse_from_rfc3339("2022-09-27T22:36:00+02:00")
+                .unwrap()
+                .into(),
+            size: 1024,
+            e_tag: Some("1".to_string()),
+            version: None,
+        };
+
+        // room for the metadata of two files
+        let size = parquet_metadata_memory_size(&metadata);
+        let cache = DefaultParquetMetadataCache::new(2 * size);
+        let a = meta("a");
+        assert!(cache.get_with_extra(&a.location, &a).is_none());
+        cache.put_with_extra(&a.location, metadata.clone(), &a);
+        assert!(cache.get_with_extra(&a.location, &a).is_some());
+
+        // file rewritten
+        let mut a2 = a.clone();
+        a2.e_tag = Some("2".to_string());
+        assert!(cache.get_with_extra(&a2.location, &a2).is_none());
+
+        // "b" is evicted as "a" was used more recently
+        let (b, c) = (meta("b"), meta("c"));
+        cache.put_with_extra(&b.location, metadata.clone(), &b);
+        assert!(cache.get_with_extra(&a.location, &a).is_some());
+        cache.put_with_extra(&c.location, metadata, &c);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.memory_size(), 2 * size);
+        assert!(cache.contains_key(&a.location));
+        assert!(!cache.contains_key(&b.location));
+
+        // shrinking the cache evicts the least recently used file
+        cache.update_memory_limit(size);
+        assert_eq!(cache.memory_limit(), size);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains_key(&c.location));
+    }
 }
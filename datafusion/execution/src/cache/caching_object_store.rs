@@ -0,0 +1,604 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`CachingObjectStore`]: serves repeated reads of byte ranges from a
+//! [`ByteRangeCache`]
+
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::cache::cache_manager::{ByteRangeCache, ByteRangeKey};
+use crate::object_store::{get_url_key, ObjectStoreRegistry};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use dashmap::DashMap;
+use datafusion_common::Result;
+use futures::stream::BoxStream;
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetResult, ListResult, MultipartId, ObjectMeta, ObjectStore, PutOptions,
+    PutResult,
+};
+use tokio::io::AsyncWrite;
+use url::Url;
+
+/// An [`ObjectStore`] caching the byte ranges read with
+/// [`ObjectStore::get_range`] and [`ObjectStore::get_ranges`] in a
+/// [`ByteRangeCache`], and passing all other requests to the wrapped store.
+///
+/// The cached ranges of an object are tagged with its version: its e_tag,
+/// or its last modification if it has none, as returned by the request that
+/// read them. Before returning cached ranges, the current version of the
+/// object is looked up with a `HEAD` request, so ranges of an object that
+/// changed since are never returned. Reads of ranges that are not cached
+/// are not delayed by this request, which is only sent when at least one
+/// of the requested ranges is cached.
+///
+/// The cache hits and misses of the reads issued within
+/// [`CachingObjectStore::with_metrics`] are recorded in its metrics.
+#[derive(Debug)]
+pub struct CachingObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    /// Identifies `inner` in the cache keys, e.g. `s3://bucket`
+    store: String,
+    cache: ByteRangeCache,
+}
+
+/// Records the cache hits and misses of the reads of a [`CachingObjectStore`],
+/// e.g. in the metrics of the scan issuing them
+pub trait ByteRangeCacheMetrics: Send + Sync {
+    /// Record that a range was read from the cache
+    fn record_hit(&self);
+    /// Record that a range was not cached, and read from the object store
+    fn record_miss(&self);
+}
+
+tokio::task_local! {
+    /// The metrics of the reads of the current task, see
+    /// [`CachingObjectStore::with_metrics`]
+    static METRICS: Arc<dyn ByteRangeCacheMetrics>;
+}
+
+impl CachingObjectStore {
+    /// Cache the ranges read from `inner`, identified by `store` in the
+    /// cache keys, in `cache`
+    pub fn new(
+        inner: Arc<dyn ObjectStore>,
+        store: String,
+        cache: ByteRangeCache,
+    ) -> Self {
+        Self {
+            inner,
+            store,
+            cache,
+        }
+    }
+
+    /// Run `future`, recording the cache hits and misses of the reads it
+    /// issues to any [`CachingObjectStore`] in `metrics`
+    pub async fn with_metrics<F: Future>(
+        metrics: Arc<dyn ByteRangeCacheMetrics>,
+        future: F,
+    ) -> F::Output {
+        METRICS.scope(metrics, future).await
+    }
+
+    /// Returns the wrapped store
+    pub fn inner(&self) -> &Arc<dyn ObjectStore> {
+        &self.inner
+    }
+
+    fn key(&self, location: &Path, range: Range<usize>) -> ByteRangeKey {
+        ByteRangeKey {
+            store: self.store.clone(),
+            location: location.clone(),
+            range,
+        }
+    }
+
+    /// Returns the current version of `location`
+    async fn head_version(&self, location: &Path) -> object_store::Result<String> {
+        Ok(version(&self.inner.head(location).await?))
+    }
+}
+
+/// Returns the version of the object described by `meta`
+fn version(meta: &ObjectMeta) -> String {
+    meta.e_tag
+        .clone()
+        .unwrap_or_else(|| format!("{}-{}", meta.last_modified, meta.size))
+}
+
+/// Record a cache hit or miss in the metrics of the current task, if any
+fn record(hit: bool) {
+    let _ = METRICS.try_with(|metrics| {
+        if hit {
+            metrics.record_hit()
+        } else {
+            metrics.record_miss()
+        }
+    });
+}
+
+impl Display for CachingObjectStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Caching({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for CachingObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        self.inner.put_opts(location, bytes, opts).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> object_store::Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> object_store::Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn get_range(
+        &self,
+        location: &Path,
+        range: Range<usize>,
+    ) -> object_store::Result<Bytes> {
+        let key = self.key(location, range.clone());
+        if self.cache.contains_key(&key) {
+            let version = Some(self.head_version(location).await?);
+            if let Some(data) = self.cache.get_with_extra(&key, &version) {
+                record(true);
+                return Ok(data);
+            }
+        }
+        record(false);
+
+        let options = GetOptions {
+            range: Some(range.into()),
+            ..Default::default()
+        };
+        let result = self.inner.get_opts(location, options).await?;
+        let version = Some(version(&result.meta));
+        let data = result.bytes().await?;
+        self.cache.put_with_extra(&key, data.clone(), &version);
+        Ok(data)
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> object_store::Result<Vec<Bytes>> {
+        // the version of the ranges fetched below, which is looked up before
+        // fetching them so that they are never tagged with a later version
+        let cached = ranges
+            .iter()
+            .any(|range| self.cache.contains_key(&self.key(location, range.clone())));
+        let mut object_version = if cached {
+            Some(self.head_version(location).await?)
+        } else {
+            None
+        };
+        let mut data = ranges
+            .iter()
+            .map(|range| {
+                let key = self.key(location, range.clone());
+                let data = object_version
+                    .as_ref()
+                    .and_then(|_| self.cache.get_with_extra(&key, &object_version));
+                record(data.is_some());
+                data
+            })
+            .collect::<Vec<_>>();
+
+        let missing = ranges
+            .iter()
+            .zip(&data)
+            .filter(|(_, data)| data.is_none())
+            .map(|(range, _)| range.clone())
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(data.into_iter().flatten().collect());
+        }
+        let fetched = if object_version.is_some() {
+            self.inner.get_ranges(location, &missing).await?
+        } else {
+            // without a HEAD request, the version is the one of the first
+            // range, and the other ranges are fetched once it is known
+            let options = GetOptions {
+                range: Some(missing[0].clone().into()),
+                ..Default::default()
+            };
+            let result = self.inner.get_opts(location, options).await?;
+            object_version = Some(version(&result.meta));
+            let (first, rest) = futures::try_join!(result.bytes(), async {
+                match &missing[1..] {
+                    [] => Ok(vec![]),
+                    rest => self.inner.get_ranges(location, rest).await,
+                }
+            })?;
+            std::iter::once(first).chain(rest).collect()
+        };
+        let mut fetched = missing.into_iter().zip(fetched);
+        for slot in data.iter_mut().filter(|slot| slot.is_none()) {
+            let (range, fetched) = fetched.next().expect("fetched all missing ranges");
+            let key = self.key(location, range);
+            self.cache
+                .put_with_extra(&key, fetched.clone(), &object_version);
+            *slot = Some(fetched);
+        }
+        Ok(data
+            .into_iter()
+            .map(|data| data.expect("fetched all missing ranges"))
+            .collect())
+    }
+
+    async fn head(&self, location: &Path) -> object_store::Result<ObjectMeta> {
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        self.inner.delete(location).await
+    }
+
+    fn delete_stream<'a>(
+        &'a self,
+        locations: BoxStream<'a, object_store::Result<Path>>,
+    ) -> BoxStream<'a, object_store::Result<Path>> {
+        self.inner.delete_stream(locations)
+    }
+
+    fn list(
+        &self,
+        prefix: Option<&Path>,
+    ) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        self.inner.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(
+        &self,
+        prefix: Option<&Path>,
+    ) -> object_store::Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.inner.rename(from, to).await
+    }
+
+    async fn copy_if_not_exists(
+        &self,
+        from: &Path,
+        to: &Path,
+    ) -> object_store::Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+
+    async fn rename_if_not_exists(
+        &self,
+        from: &Path,
+        to: &Path,
+    ) -> object_store::Result<()> {
+        self.inner.rename_if_not_exists(from, to).await
+    }
+}
+
+/// An [`ObjectStoreRegistry`] returning the stores of another registry
+/// wrapped in [`CachingObjectStore`]s sharing one [`ByteRangeCache`].
+///
+/// [`crate::runtime_env::RuntimeEnv`] wraps its registry this way when
+/// a byte range cache is configured in its
+/// [`crate::cache::cache_manager::CacheManagerConfig`].
+#[derive(Debug)]
+pub struct CachingObjectStoreRegistry {
+    inner: Arc<dyn ObjectStoreRegistry>,
+    cache: ByteRangeCache,
+    /// The wrapped stores, by the url key of the store
+    stores: DashMap<String, Arc<CachingObjectStore>>,
+}
+
+impl CachingObjectStoreRegistry {
+    /// Cache the ranges read from the stores of `inner` in `cache`
+    pub fn new(inner: Arc<dyn ObjectStoreRegistry>, cache: ByteRangeCache) -> Self {
+        Self {
+            inner,
+            cache,
+            stores: DashMap::new(),
+        }
+    }
+}
+
+impl ObjectStoreRegistry for CachingObjectStoreRegistry {
+    fn register_store(
+        &self,
+        url: &Url,
+        store: Arc<dyn ObjectStore>,
+    ) -> Option<Arc<dyn ObjectStore>> {
+        self.inner.register_store(url, store)
+    }
+
+    fn get_store(&self, url: &Url) -> Result<Arc<dyn ObjectStore>> {
+        let store = self.inner.get_store(url)?;
+        let key = get_url_key(url);
+
+        // reuse the wrapper unless the store has been replaced
+        let existing = self
+            .stores
+            .get(&key)
+            .filter(|caching| {
+                Arc::as_ptr(caching.inner()) as *const ()
+                    == Arc::as_ptr(&store) as *const ()
+            })
+            .map(|caching| caching.value().clone());
+        let caching = existing.unwrap_or_else(|| {
+            let caching = Arc::new(CachingObjectStore::new(
+                store,
+                key.clone(),
+                self.cache.clone(),
+            ));
+            self.stores.insert(key, caching.clone());
+            caching
+        });
+        Ok(caching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::cache_unit::DefaultByteRangeCache;
+    use crate::object_store::DefaultObjectStoreRegistry;
+    use object_store::memory::InMemory;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Default)]
+    struct TestMetrics {
+        hits: AtomicUsize,
+        misses: AtomicUsize,
+    }
+
+    impl ByteRangeCacheMetrics for TestMetrics {
+        fn record_hit(&self) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn record_miss(&self) {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_ranges_until_object_changes() -> object_store::Result<()> {
+        let inner = Arc::new(InMemory::new());
+        let location = Path::from("file");
+        inner.put(&location, "0123456789".into()).await?;
+
+        let cache = Arc::new(DefaultByteRangeCache::new(1024));
+        let store = CachingObjectStore::new(inner.clone(), "memory://".into(), cache);
+        let metrics = Arc::new(TestMetrics::default());
+
+        CachingObjectStore::with_metrics(metrics.clone(), async {
+            assert_eq!(store.get_range(&location, 0..4).await?, "0123");
+            assert_eq!(store.get_range(&location, 0..4).await?, "0123");
+            let ranges = store.get_ranges(&location, &[0..4, 4..6]).await?;
+            assert_eq!(ranges, vec!["0123", "45"]);
+            assert_eq!(metrics.hits.load(Ordering::Relaxed), 2);
+            assert_eq!(metrics.misses.load(Ordering::Relaxed), 2);
+
+            // changed without going through the caching store
+            inner.put(&location, "abcdefghij".into()).await?;
+            assert_eq!(store.get_range(&location, 0..4).await?, "abcd");
+            let ranges = store.get_ranges(&location, &[0..4, 4..6]).await?;
+            assert_eq!(ranges, vec!["abcd", "ef"]);
+            assert_eq!(metrics.hits.load(Ordering::Relaxed), 3);
+            assert_eq!(metrics.misses.load(Ordering::Relaxed), 4);
+            Ok::<_, object_store::Error>(())
+        })
+        .await?;
+
+        // reads outside of `with_metrics` are not recorded
+        assert_eq!(store.get_range(&location, 0..4).await?, "abcd");
+        assert_eq!(metrics.hits.load(Ordering::Relaxed), 3);
+        Ok(())
+    }
+
+    /// Counts the `HEAD` and `GET` requests sent to an [`InMemory`] store
+    #[derive(Debug, Default)]
+    struct CountingStore {
+        inner: InMemory,
+        heads: AtomicUsize,
+        gets: AtomicUsize,
+    }
+
+    impl Display for CountingStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Counting({})", self.inner)
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for CountingStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            bytes: Bytes,
+            opts: PutOptions,
+        ) -> object_store::Result<PutResult> {
+            self.inner.put_opts(location, bytes, opts).await
+        }
+
+        async fn put_multipart(
+            &self,
+            location: &Path,
+        ) -> object_store::Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)>
+        {
+            self.inner.put_multipart(location).await
+        }
+
+        async fn abort_multipart(
+            &self,
+            location: &Path,
+            multipart_id: &MultipartId,
+        ) -> object_store::Result<()> {
+            self.inner.abort_multipart(location, multipart_id).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: GetOptions,
+        ) -> object_store::Result<GetResult> {
+            self.gets.fetch_add(1, Ordering::Relaxed);
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn head(&self, location: &Path) -> object_store::Result<ObjectMeta> {
+            self.heads.fetch_add(1, Ordering::Relaxed);
+            self.inner.head(location).await
+        }
+
+        async fn delete(&self, location: &Path) -> object_store::Result<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(
+            &self,
+            from: &Path,
+            to: &Path,
+        ) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    #[tokio::test]
+    async fn heads_only_cached_ranges() -> object_store::Result<()> {
+        let inner = Arc::new(CountingStore::default());
+        let location = Path::from("file");
+        inner.put(&location, "0123456789".into()).await?;
+
+        let cache = Arc::new(DefaultByteRangeCache::new(1024));
+        let store = CachingObjectStore::new(inner.clone(), "memory://".into(), cache);
+        let requests = || {
+            (
+                inner.heads.load(Ordering::Relaxed),
+                inner.gets.load(Ordering::Relaxed),
+            )
+        };
+
+        // none of the ranges is cached, so no HEAD request is sent
+        let ranges = store.get_ranges(&location, &[0..2, 4..6, 8..10]).await?;
+        assert_eq!(ranges, vec!["01", "45", "89"]);
+        assert_eq!(requests().0, 0);
+        assert_eq!(store.get_range(&location, 2..4).await?, "23");
+        assert_eq!(requests().0, 0);
+
+        // a cached range is only returned after checking the version
+        let gets = requests().1;
+        let ranges = store.get_ranges(&location, &[0..2, 4..6]).await?;
+        assert_eq!(ranges, vec!["01", "45"]);
+        assert_eq!(requests(), (1, gets));
+        let ranges = store.get_ranges(&location, &[2..4, 6..8]).await?;
+        assert_eq!(ranges, vec!["23", "67"]);
+        assert_eq!(requests(), (2, gets + 1));
+
+        // the ranges fetched without a HEAD request are tagged with the
+        // version of the object
+        inner.put(&location, "abcdefghij".into()).await?;
+        let ranges = store.get_ranges(&location, &[0..2, 8..10]).await?;
+        assert_eq!(ranges, vec!["ab", "ij"]);
+        Ok(())
+    }
+
+    #[test]
+    fn registry_reuses_wrappers() -> Result<()> {
+        let cache = Arc::new(DefaultByteRangeCache::new(1024));
+        let registry = CachingObjectStoreRegistry::new(
+            Arc::new(DefaultObjectStoreRegistry::new()),
+            cache,
+        );
+        let url = Url::parse("memory://").unwrap();
+        registry.register_store(&url, Arc::new(InMemory::new()));
+
+        let same = |a: &Arc<dyn ObjectStore>, b: &Arc<dyn ObjectStore>| {
+            Arc::as_ptr(a) as *const () == Arc::as_ptr(b) as *const ()
+        };
+
+        let store = registry.get_store(&url)?;
+        assert_eq!(store.to_string(), "Caching(InMemory)");
+        assert!(same(&store, &registry.get_store(&url)?));
+
+        // a replaced store is wrapped again
+        registry.register_store(&url, Arc::new(InMemory::new()));
+        assert!(!same(&store, &registry.get_store(&url)?));
+        Ok(())
+    }
+}
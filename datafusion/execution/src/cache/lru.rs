@@ -0,0 +1,215 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`LruCache`]: a map bounded by the total weight of its entries
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// A map holding entries up to a total weight, evicting the least recently
+/// used entries to make room for new ones.
+///
+/// The weight of an entry is chosen by the caller, e.g. `1` to bound the
+/// number of entries or the size in bytes to bound the memory used.
+///
+/// This is not thread safe, the caches using it wrap it in a lock.
+#[derive(Debug)]
+pub(crate) struct LruCache<K, V> {
+    /// The maximum total weight of the entries
+    capacity: usize,
+    /// The total weight of the entries
+    weight: usize,
+    entries: HashMap<K, LruEntry<V>>,
+    /// The keys of the entries, ordered by when they were last used
+    recency: BTreeMap<u64, K>,
+    /// Incremented every time an entry is used
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct LruEntry<V> {
+    value: V,
+    weight: usize,
+    /// When this entry was last used, its key in [`LruCache::recency`]
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Create an empty cache holding entries up to a total weight of `capacity`
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            weight: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Returns the value of `key`, marking it as the most recently used
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let entry = self.entries.get_mut(key)?;
+        let key = self
+            .recency
+            .remove(&entry.tick)
+            .expect("every entry has a recency");
+        self.tick += 1;
+        entry.tick = self.tick;
+        self.recency.insert(entry.tick, key);
+        Some(&entry.value)
+    }
+
+    /// Insert `value` for `key`, evicting the least recently used entries
+    /// until it fits, and returns the previous value of `key`.
+    ///
+    /// A value heavier than the capacity is not inserted.
+    pub fn put(&mut self, key: K, value: V, weight: usize) -> Option<V> {
+        let previous = self.remove(&key);
+        if weight > self.capacity {
+            return previous;
+        }
+        self.make_room(weight);
+
+        self.tick += 1;
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(
+            key,
+            LruEntry {
+                value,
+                weight,
+                tick: self.tick,
+            },
+        );
+        self.weight += weight;
+        previous
+    }
+
+    /// Remove `key`, returning its value
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(&entry.tick);
+        self.weight -= entry.weight;
+        Some(entry.value)
+    }
+
+    /// Evict the least recently used entries until an entry of `weight` fits,
+    /// returning them. Nothing is evicted for an entry heavier than the
+    /// capacity, as it never fits.
+    pub fn make_room(&mut self, weight: usize) -> Vec<(K, V)> {
+        let mut evicted = vec![];
+        if weight <= self.capacity {
+            while self.weight + weight > self.capacity {
+                evicted.extend(self.evict());
+            }
+        }
+        evicted
+    }
+
+    /// Remove the least recently used entry, returning it
+    fn evict(&mut self) -> Option<(K, V)> {
+        let (_, key) = self.recency.pop_first()?;
+        let entry = self
+            .entries
+            .remove(&key)
+            .expect("every recency has an entry");
+        self.weight -= entry.weight;
+        Some((key, entry.value))
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Returns the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the total weight of the entries
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Returns the maximum total weight of the entries
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the maximum total weight of the entries, evicting the least
+    /// recently used entries until they fit, and returns them
+    pub fn set_capacity(&mut self, capacity: usize) -> Vec<(K, V)> {
+        self.capacity = capacity;
+        let mut evicted = vec![];
+        while self.weight > self.capacity {
+            evicted.extend(self.evict());
+        }
+        evicted
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.weight = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = LruCache::new(3);
+        cache.put("a", 1, 1);
+        cache.put("b", 2, 1);
+        cache.put("c", 3, 1);
+
+        // using "a" makes "b" the least recently used entry
+        assert_eq!(cache.get(&"a"), Some(&1));
+        cache.put("d", 4, 1);
+        assert!(!cache.contains_key(&"b"));
+        assert_eq!(cache.len(), 3);
+
+        // a heavy entry evicts several entries
+        cache.put("e", 5, 2);
+        assert!(!cache.contains_key(&"c"));
+        assert!(!cache.contains_key(&"a"));
+        assert_eq!(cache.get(&"d"), Some(&4));
+        assert_eq!(cache.weight(), 3);
+
+        // making room returns the evicted entries
+        assert_eq!(cache.make_room(2), vec![("e", 5)]);
+        assert_eq!(cache.make_room(4), vec![]);
+    }
+
+    #[test]
+    fn replaces_and_rejects_entries() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(cache.put("a", 1, 1), None);
+        assert_eq!(cache.put("a", 2, 2), Some(1));
+        assert_eq!(cache.weight, 2);
+
+        // too heavy to ever fit
+        assert_eq!(cache.put("b", 3, 3), None);
+        assert!(!cache.contains_key(&"b"));
+        assert_eq!(cache.get(&"a"), Some(&2));
+
+        assert_eq!(cache.remove(&"a"), Some(2));
+        assert_eq!(cache.weight, 0);
+        assert_eq!(cache.evict(), None);
+    }
+}
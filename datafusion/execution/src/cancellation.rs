@@ -0,0 +1,296 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Cancellation of running queries
+//!
+//! Dropping the output stream of a query stops it, but its partitions may
+//! still be busy in long loops, and the stream may be shared by code that
+//! cannot drop it. A [`CancellationToken`] of the [`TaskContext`] of a query
+//! stops it from anywhere: operators check it while they buffer their input,
+//! and the output streams of the query fail with
+//! [`DataFusionError::Cancelled`] once it is cancelled or timed out.
+//!
+//! [`TaskContext`]: crate::TaskContext
+
+use std::fmt::{self, Debug};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use datafusion_common::{DataFusionError, Result};
+use futures::{Stream, StreamExt};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use crate::{RecordBatchStream, SendableRecordBatchStream};
+
+/// Cancels a query when [`Self::cancel`] is called on any of its clones, or
+/// once its timeout elapsed
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenState>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    notify: Notify,
+    /// The deadline and the timeout it was computed from
+    timeout: Option<(Instant, Duration)>,
+    /// The token also cancelling this one
+    parent: Option<CancellationToken>,
+}
+
+impl Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.inner.cancelled.load(Ordering::Relaxed))
+            .field("timeout", &self.inner.timeout.map(|(_, timeout)| timeout))
+            .finish()
+    }
+}
+
+impl CancellationToken {
+    /// Create a token that is only cancelled by [`Self::cancel`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a token that is also cancelled once `timeout` elapsed from now
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            inner: Arc::new(TokenState {
+                timeout: Some((Instant::now() + timeout, timeout)),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Create a token that is cancelled when this token is, or once
+    /// `timeout` elapsed from now. Cancelling it does not cancel this token.
+    pub fn child_with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            inner: Arc::new(TokenState {
+                timeout: Some((Instant::now() + timeout, timeout)),
+                parent: Some(self.clone()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Cancel the query
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Returns true if the query was cancelled or timed out
+    pub fn is_cancelled(&self) -> bool {
+        self.check().is_err()
+    }
+
+    /// Returns a [`DataFusionError::Cancelled`] error if the query was
+    /// cancelled or timed out
+    pub fn check(&self) -> Result<()> {
+        if let Some(parent) = &self.inner.parent {
+            parent.check()?;
+        }
+        if self.inner.cancelled.load(Ordering::Relaxed) {
+            return Err(DataFusionError::Cancelled(
+                "the query was cancelled".to_string(),
+            ));
+        }
+        match self.inner.timeout {
+            Some((deadline, timeout)) if Instant::now() >= deadline => {
+                Err(DataFusionError::Cancelled(format!(
+                    "the query exceeded its timeout of {timeout:?}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Waits until the query is cancelled or times out
+    pub async fn cancelled(&self) {
+        // Waiters created before `cancel` is called are always notified
+        let notified = self.inner.notify.notified();
+        if self.inner.cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+        let parent = async {
+            match &self.inner.parent {
+                Some(parent) => Box::pin(parent.cancelled()).await,
+                None => std::future::pending().await,
+            }
+        };
+        match self.inner.timeout {
+            Some((deadline, _)) => {
+                tokio::select! {
+                    _ = notified => {}
+                    _ = parent => {}
+                    _ = tokio::time::sleep_until(deadline) => {}
+                }
+            }
+            None => {
+                tokio::select! {
+                    _ = notified => {}
+                    _ = parent => {}
+                }
+            }
+        }
+    }
+
+    /// Fails `stream` with a [`DataFusionError::Cancelled`] error as soon as
+    /// the query is cancelled or times out, even if `stream` is waiting for
+    /// its input
+    pub fn guard_stream(
+        &self,
+        stream: SendableRecordBatchStream,
+    ) -> SendableRecordBatchStream {
+        let token = self.clone();
+        Box::pin(CancellableStream {
+            input: stream,
+            cancelled: Some(Box::pin(async move { token.cancelled().await })),
+            token: self.clone(),
+        })
+    }
+}
+
+/// A stream that fails once its query is cancelled
+struct CancellableStream {
+    input: SendableRecordBatchStream,
+    /// Completes when the query is cancelled, `None` once the error was
+    /// returned
+    cancelled: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+    token: CancellationToken,
+}
+
+impl Stream for CancellableStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let Some(cancelled) = self.cancelled.as_mut() else {
+            return Poll::Ready(None);
+        };
+        if cancelled.as_mut().poll(cx).is_ready() {
+            self.cancelled = None;
+            let err = self.token.check().err().unwrap_or_else(|| {
+                DataFusionError::Cancelled("the query was cancelled".to_string())
+            });
+            return Poll::Ready(Some(Err(err)));
+        }
+        self.input.poll_next_unpin(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl RecordBatchStream for CancellableStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::datatypes::Schema;
+
+    /// A stream waiting for its input forever
+    struct PendingStream;
+
+    impl Stream for PendingStream {
+        type Item = Result<RecordBatch>;
+
+        fn poll_next(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            Poll::Pending
+        }
+    }
+
+    impl RecordBatchStream for PendingStream {
+        fn schema(&self) -> SchemaRef {
+            Arc::new(Schema::empty())
+        }
+    }
+
+    fn pending_stream() -> SendableRecordBatchStream {
+        Box::pin(PendingStream)
+    }
+
+    #[tokio::test]
+    async fn cancel() {
+        let token = CancellationToken::new();
+        assert!(token.check().is_ok());
+
+        let mut stream = token.guard_stream(pending_stream());
+        assert!(futures::poll!(stream.next()).is_pending());
+
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(err.strip_backtrace(), "Cancelled: the query was cancelled");
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let token = CancellationToken::with_timeout(Duration::from_millis(10));
+        let mut stream = token.guard_stream(pending_stream());
+
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Cancelled: the query exceeded its timeout of 10ms"
+        );
+        assert!(token.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn child_with_timeout() {
+        let token = CancellationToken::new();
+        let child = token.child_with_timeout(Duration::from_secs(3600));
+        let mut stream = child.guard_stream(pending_stream());
+        assert!(futures::poll!(stream.next()).is_pending());
+
+        token.cancel();
+        assert!(child.is_cancelled());
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(err.strip_backtrace(), "Cancelled: the query was cancelled");
+
+        // cancelling or timing out the child does not cancel its parent
+        let token = CancellationToken::new();
+        let child = token.child_with_timeout(Duration::from_millis(10));
+        child.cancelled().await;
+        assert!(child.is_cancelled());
+        child.cancel();
+        assert!(!token.is_cancelled());
+    }
+}
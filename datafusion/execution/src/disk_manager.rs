@@ -23,6 +23,7 @@ use log::debug;
 use parking_lot::Mutex;
 use rand::{thread_rng, Rng};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tempfile::{Builder, NamedTempFile, TempDir};
 
@@ -76,6 +77,27 @@ pub struct DiskManager {
     /// If `Some(vec![])` a new OS specified temporary directory will be created
     /// If `None` an error will be returned (configured not to spill)
     local_dirs: Mutex<Option<Vec<Arc<TempDir>>>>,
+
+    /// The disk space used by the temporary files, shared with the files
+    usage: Arc<DiskUsage>,
+}
+
+/// The disk space used by the temporary files of a [`DiskManager`]
+#[derive(Debug)]
+struct DiskUsage {
+    /// Bytes used by the temporary files
+    used: AtomicU64,
+    /// Maximum number of bytes the temporary files may use, or `u64::MAX`
+    limit: AtomicU64,
+}
+
+impl Default for DiskUsage {
+    fn default() -> Self {
+        Self {
+            used: AtomicU64::new(0),
+            limit: AtomicU64::new(u64::MAX),
+        }
+    }
 }
 
 impl DiskManager {
@@ -85,6 +107,7 @@ impl DiskManager {
             DiskManagerConfig::Existing(manager) => Ok(manager),
             DiskManagerConfig::NewOs => Ok(Arc::new(Self {
                 local_dirs: Mutex::new(Some(vec![])),
+                usage: Default::default(),
             })),
             DiskManagerConfig::NewSpecified(conf_dirs) => {
                 let local_dirs = create_local_dirs(conf_dirs)?;
@@ -94,10 +117,12 @@ impl DiskManager {
                 );
                 Ok(Arc::new(Self {
                     local_dirs: Mutex::new(Some(local_dirs)),
+                    usage: Default::default(),
                 }))
             }
             DiskManagerConfig::Disabled => Ok(Arc::new(Self {
                 local_dirs: Mutex::new(None),
+                usage: Default::default(),
             })),
         }
     }
@@ -109,6 +134,30 @@ impl DiskManager {
         self.local_dirs.lock().is_some()
     }
 
+    /// Limit the disk space used by the temporary files to `limit` bytes, or
+    /// remove the limit if `None`.
+    ///
+    /// Writers of temporary files account for their size with
+    /// [`RefCountedTempFile::update_disk_usage`], which fails once the
+    /// temporary files exceed the limit.
+    pub fn set_max_temp_directory_size(&self, limit: Option<u64>) {
+        let limit = limit.unwrap_or(u64::MAX);
+        self.usage.limit.store(limit, Ordering::Relaxed);
+    }
+
+    /// Returns the maximum disk space the temporary files may use, if any
+    pub fn max_temp_directory_size(&self) -> Option<u64> {
+        match self.usage.limit.load(Ordering::Relaxed) {
+            u64::MAX => None,
+            limit => Some(limit),
+        }
+    }
+
+    /// Returns the disk space currently used by the temporary files
+    pub fn used_disk_space(&self) -> u64 {
+        self.usage.used.load(Ordering::Relaxed)
+    }
+
     /// Return a temporary file from a randomized choice in the configured locations
     ///
     /// If the file can not be created for some reason, returns an
@@ -143,6 +192,8 @@ impl DiskManager {
             tempfile: Builder::new()
                 .tempfile_in(local_dirs[dir_index].as_ref())
                 .map_err(DataFusionError::IoError)?,
+            usage: self.usage.clone(),
+            disk_usage: 0,
         })
     }
 }
@@ -156,6 +207,10 @@ pub struct RefCountedTempFile {
     #[allow(dead_code)]
     parent_temp_dir: Arc<TempDir>,
     tempfile: NamedTempFile,
+    /// The disk space used by the temporary files of the [`DiskManager`]
+    usage: Arc<DiskUsage>,
+    /// The size of this file the last time it was accounted for
+    disk_usage: u64,
 }
 
 impl RefCountedTempFile {
@@ -166,6 +221,51 @@ impl RefCountedTempFile {
     pub fn inner(&self) -> &NamedTempFile {
         &self.tempfile
     }
+
+    /// Returns the size of this file the last time it was accounted for by
+    /// [`Self::update_disk_usage`]
+    pub fn current_disk_usage(&self) -> u64 {
+        self.disk_usage
+    }
+
+    /// Accounts for the current size of this file in the disk space used by
+    /// the temporary files of its [`DiskManager`], returning an error if they
+    /// exceed the limit set with [`DiskManager::set_max_temp_directory_size`].
+    ///
+    /// Writers should call this regularly while writing the file, so a
+    /// query exceeding the limit fails before it fills up the disk.
+    pub fn update_disk_usage(&mut self) -> Result<u64> {
+        let size = self
+            .tempfile
+            .as_file()
+            .metadata()
+            .map_err(DataFusionError::IoError)?
+            .len();
+        let used = if size >= self.disk_usage {
+            let grown = size - self.disk_usage;
+            self.usage.used.fetch_add(grown, Ordering::Relaxed) + grown
+        } else {
+            let shrunk = self.disk_usage - size;
+            self.usage.used.fetch_sub(shrunk, Ordering::Relaxed) - shrunk
+        };
+        self.disk_usage = size;
+
+        let limit = self.usage.limit.load(Ordering::Relaxed);
+        if used > limit {
+            return Err(DataFusionError::ResourcesExhausted(format!(
+                "The temporary files of the DiskManager use {used} bytes, which exceeds its limit of {limit} bytes"
+            )));
+        }
+        Ok(size)
+    }
+}
+
+impl Drop for RefCountedTempFile {
+    fn drop(&mut self) {
+        self.usage
+            .used
+            .fetch_sub(self.disk_usage, Ordering::Relaxed);
+    }
 }
 
 /// Setup local dirs by creating one new dir in each of the given dirs
@@ -263,6 +363,35 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_max_temp_directory_size() -> Result<()> {
+        let dm = DiskManager::try_new(DiskManagerConfig::new())?;
+        dm.set_max_temp_directory_size(Some(100));
+        assert_eq!(dm.max_temp_directory_size(), Some(100));
+
+        let mut file1 = dm.create_tmp_file("Testing")?;
+        std::fs::write(file1.path(), [0; 60])?;
+        assert_eq!(file1.update_disk_usage()?, 60);
+
+        let mut file2 = dm.create_tmp_file("Testing")?;
+        std::fs::write(file2.path(), [0; 60])?;
+        assert_eq!(
+            file2.update_disk_usage().unwrap_err().strip_backtrace(),
+            "Resources exhausted: The temporary files of the DiskManager use 120 bytes, which exceeds its limit of 100 bytes",
+        );
+        assert_eq!(dm.used_disk_space(), 120);
+
+        // the space of the files is released once they are dropped
+        drop(file1);
+        assert_eq!(dm.used_disk_space(), 60);
+        std::fs::write(file2.path(), [0; 80])?;
+        assert_eq!(file2.update_disk_usage()?, 80);
+        drop(file2);
+        assert_eq!(dm.used_disk_space(), 0);
+
+        Ok(())
+    }
+
     /// Asserts that `file_path` is found anywhere in any of `dir` directories
     fn assert_path_in_dirs<'a>(
         file_path: &'a Path,
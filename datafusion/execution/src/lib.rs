@@ -18,14 +18,19 @@
 //! DataFusion execution configuration and runtime structures
 
 pub mod cache;
+pub mod cancellation;
 pub mod config;
 pub mod disk_manager;
 pub mod memory_pool;
 pub mod object_store;
+pub mod progress;
 pub mod registry;
 pub mod runtime_env;
+pub mod scheduler;
 mod stream;
 mod task;
+#[cfg(feature = "tracing")]
+pub mod traced_object_store;
 
 pub use disk_manager::DiskManager;
 pub use registry::FunctionRegistry;
@@ -19,12 +19,23 @@
 //! help with allocation accounting.
 
 use datafusion_common::Result;
+use std::sync::atomic::{self, AtomicUsize};
+use std::time::Duration;
 use std::{cmp::Ordering, sync::Arc};
+use tokio::time::Instant;
 
 mod pool;
 pub mod proxy;
+mod scoped;
+mod spill;
 
 pub use pool::*;
+pub use scoped::*;
+pub use spill::*;
+
+/// How often [`MemoryReservation::try_grow_with_spill`] retries to grow
+/// while consumers are asked to spill
+const SPILL_RETRY_INTERVAL: Duration = Duration::from_millis(10);
 
 /// Tracks and potentially limits memory use across operators during execution.
 ///
@@ -63,6 +74,10 @@ pub use pool::*;
 /// the desired memory. If the allocation fails, the operator must either first
 /// free memory (e.g. by spilling to local disk) and try again, or error.
 ///
+/// Operators that cannot spill can instead ask the spillable consumers of the
+/// pool to release memory with [`MemoryReservation::try_grow_with_spill`],
+/// if the pool supports it and the consumers registered a [`SpillCallback`].
+///
 /// Note that a `MemoryPool` can be shared by concurrently executing plans,
 /// which can be used to control memory usage in a multi-tenant system.
 ///
@@ -80,6 +95,10 @@ pub use pool::*;
 ///
 /// * [`FairSpillPool`]: Limits memory usage to a fixed size, allocating memory
 /// to all spilling operators fairly
+///
+/// [`ScopedMemoryPool`]s can be nested within any of them to limit the memory
+/// used by a single query or user, and to report the largest consumers of
+/// memory when a reservation fails.
 pub trait MemoryPool: Send + Sync + std::fmt::Debug {
     /// Registers a new [`MemoryConsumer`]
     ///
@@ -106,6 +125,26 @@ pub trait MemoryPool: Send + Sync + std::fmt::Debug {
 
     /// Return the total amount of memory reserved
     fn reserved(&self) -> usize;
+
+    /// Asks spillable consumers of this pool to release memory so that
+    /// `reservation` can grow by `additional` bytes, by calling their
+    /// [`SpillCallback`]s. Returns the number of bytes requested from them,
+    /// or 0 if spilling them would not make room for the reservation.
+    ///
+    /// Pools do not request spills by default
+    fn request_spill(
+        &self,
+        _reservation: &MemoryReservation,
+        _additional: usize,
+    ) -> usize {
+        0
+    }
+
+    /// Returns this pool as a [`ScopedMemoryPool`], if it is one, so that
+    /// scopes nested in it report their consumers to it
+    fn as_scope(&self) -> Option<&ScopedMemoryPool> {
+        None
+    }
 }
 
 /// A memory consumer is a named allocation traced by a particular
@@ -117,19 +156,29 @@ pub trait MemoryPool: Send + Sync + std::fmt::Debug {
 /// [proxy]: crate::memory_pool::proxy
 #[derive(Debug)]
 pub struct MemoryConsumer {
+    id: usize,
     name: String,
     can_spill: bool,
+    spill_callback: Option<Arc<dyn SpillCallback>>,
 }
 
 impl MemoryConsumer {
     /// Create a new empty [`MemoryConsumer`] that can be grown using [`MemoryReservation`]
     pub fn new(name: impl Into<String>) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
         Self {
+            id: NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed),
             name: name.into(),
             can_spill: false,
+            spill_callback: None,
         }
     }
 
+    /// Returns the id of this consumer, unique within the process
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
     /// Set whether this allocation can be spilled to disk
     pub fn with_can_spill(self, can_spill: bool) -> Self {
         Self { can_spill, ..self }
@@ -140,6 +189,20 @@ impl MemoryConsumer {
         self.can_spill
     }
 
+    /// Set the callback through which the pool asks this spillable
+    /// allocation to release memory for other consumers
+    pub fn with_spill_callback(self, spill_callback: Arc<dyn SpillCallback>) -> Self {
+        Self {
+            spill_callback: Some(spill_callback),
+            ..self
+        }
+    }
+
+    /// Returns the callback asking this allocation to release memory, if any
+    pub fn spill_callback(&self) -> Option<&Arc<dyn SpillCallback>> {
+        self.spill_callback.as_ref()
+    }
+
     /// Returns the name associated with this allocation
     pub fn name(&self) -> &str {
         &self.name
@@ -252,6 +315,36 @@ impl MemoryReservation {
         Ok(())
     }
 
+    /// Try to increase the size of this reservation by `capacity` bytes,
+    /// asking the spillable consumers of the pool to release memory if there
+    /// is insufficient capacity left, see [`MemoryPool::request_spill`].
+    ///
+    /// Retries until the consumers released enough memory, returning an
+    /// error if they did not after `timeout`
+    pub async fn try_grow_with_spill(
+        &mut self,
+        capacity: usize,
+        timeout: Duration,
+    ) -> Result<()> {
+        let Err(err) = self.try_grow(capacity) else {
+            return Ok(());
+        };
+        if timeout.is_zero() || self.registration.pool.request_spill(self, capacity) == 0
+        {
+            return Err(err);
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            tokio::time::sleep(SPILL_RETRY_INTERVAL).await;
+            match self.try_grow(capacity) {
+                Ok(()) => return Ok(()),
+                Err(err) if Instant::now() >= deadline => return Err(err),
+                Err(_) => {}
+            }
+        }
+    }
+
     /// Splits off `capacity` bytes from this [`MemoryReservation`]
     /// into a new [`MemoryReservation`] with the same
     /// [`MemoryConsumer`].
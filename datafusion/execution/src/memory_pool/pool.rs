@@ -15,11 +15,14 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use crate::memory_pool::{MemoryConsumer, MemoryPool, MemoryReservation};
+use crate::memory_pool::{MemoryConsumer, MemoryPool, MemoryReservation, SpillCallback};
 use datafusion_common::{DataFusionError, Result};
 use log::debug;
 use parking_lot::Mutex;
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 /// A [`MemoryPool`] that enforces no limit
 #[derive(Debug, Default)]
@@ -119,7 +122,10 @@ impl MemoryPool for GreedyMemoryPool {
 ///    └───────────────────────z──────────────────────z───────────────┘
 /// ```
 ///
-/// Unspillable memory is allocated in a first-come, first-serve fashion
+/// Unspillable memory is allocated in a first-come, first-serve fashion.
+/// When an unspillable reservation fails, [`MemoryPool::request_spill`] asks
+/// the largest spillable consumers with a [`SpillCallback`] to release the
+/// missing memory.
 #[derive(Debug)]
 pub struct FairSpillPool {
     /// The total memory limit
@@ -138,6 +144,26 @@ struct FairSpillPoolState {
 
     /// The total amount of memory reserved by consumers that cannot spill
     unspillable: usize,
+
+    /// The spillable consumers that can be asked to spill, by id
+    spillers: HashMap<usize, Spiller>,
+}
+
+/// A spillable consumer with a [`SpillCallback`]
+#[derive(Debug)]
+struct Spiller {
+    callback: Arc<dyn SpillCallback>,
+    reserved: usize,
+}
+
+impl FairSpillPoolState {
+    /// Updates the memory reserved by `reservation` by `delta` bytes
+    fn update_spiller(&mut self, reservation: &MemoryReservation, delta: isize) {
+        let id = reservation.registration.consumer.id;
+        if let Some(spiller) = self.spillers.get_mut(&id) {
+            spiller.reserved = spiller.reserved.saturating_add_signed(delta);
+        }
+    }
 }
 
 impl FairSpillPool {
@@ -150,6 +176,7 @@ impl FairSpillPool {
                 num_spill: 0,
                 spillable: 0,
                 unspillable: 0,
+                spillers: HashMap::new(),
             }),
         }
     }
@@ -158,7 +185,15 @@ impl FairSpillPool {
 impl MemoryPool for FairSpillPool {
     fn register(&self, consumer: &MemoryConsumer) {
         if consumer.can_spill {
-            self.state.lock().num_spill += 1;
+            let mut state = self.state.lock();
+            state.num_spill += 1;
+            if let Some(callback) = &consumer.spill_callback {
+                let spiller = Spiller {
+                    callback: callback.clone(),
+                    reserved: 0,
+                };
+                state.spillers.insert(consumer.id, spiller);
+            }
         }
     }
 
@@ -166,13 +201,17 @@ impl MemoryPool for FairSpillPool {
         if consumer.can_spill {
             let mut state = self.state.lock();
             state.num_spill = state.num_spill.checked_sub(1).unwrap();
+            state.spillers.remove(&consumer.id);
         }
     }
 
     fn grow(&self, reservation: &MemoryReservation, additional: usize) {
         let mut state = self.state.lock();
         match reservation.registration.consumer.can_spill {
-            true => state.spillable += additional,
+            true => {
+                state.spillable += additional;
+                state.update_spiller(reservation, additional as isize);
+            }
             false => state.unspillable += additional,
         }
     }
@@ -180,7 +219,10 @@ impl MemoryPool for FairSpillPool {
     fn shrink(&self, reservation: &MemoryReservation, shrink: usize) {
         let mut state = self.state.lock();
         match reservation.registration.consumer.can_spill {
-            true => state.spillable -= shrink,
+            true => {
+                state.spillable -= shrink;
+                state.update_spiller(reservation, -(shrink as isize));
+            }
             false => state.unspillable -= shrink,
         }
     }
@@ -206,6 +248,7 @@ impl MemoryPool for FairSpillPool {
                     ));
                 }
                 state.spillable += additional;
+                state.update_spiller(reservation, additional as isize);
             }
             false => {
                 let available = self
@@ -229,6 +272,48 @@ impl MemoryPool for FairSpillPool {
         let state = self.state.lock();
         state.spillable + state.unspillable
     }
+
+    fn request_spill(&self, reservation: &MemoryReservation, additional: usize) -> usize {
+        // Spillable consumers are limited to their fair share of the memory,
+        // which does not grow when other consumers spill
+        if reservation.registration.consumer.can_spill {
+            return 0;
+        }
+
+        let state = self.state.lock();
+        let available = self
+            .pool_size
+            .saturating_sub(state.unspillable + state.spillable);
+        let missing = additional.saturating_sub(available);
+        let mut spillers = state
+            .spillers
+            .values()
+            .filter(|spiller| spiller.reserved > 0)
+            .collect::<Vec<_>>();
+        if missing == 0
+            || spillers
+                .iter()
+                .map(|spiller| spiller.reserved)
+                .sum::<usize>()
+                < missing
+        {
+            return 0;
+        }
+
+        // Ask the largest consumers first, so that as few as possible spill
+        spillers.sort_by_key(|spiller| Reverse(spiller.reserved));
+        let mut requested = 0;
+        for spiller in spillers {
+            if requested >= missing {
+                break;
+            }
+            let bytes = spiller.reserved.min(missing - requested);
+            spiller.callback.request_spill(bytes);
+            requested += bytes;
+        }
+        debug!("FairSpillPool requested {requested} bytes from spillable consumers");
+        requested
+    }
 }
 
 fn insufficient_capacity_err(
@@ -242,7 +327,8 @@ fn insufficient_capacity_err(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
+    use crate::memory_pool::SpillRequest;
+    use std::time::Duration;
 
     #[test]
     fn test_fair() {
@@ -310,4 +396,45 @@ mod tests {
         let err = r4.try_grow(30).unwrap_err().strip_backtrace();
         assert_eq!(err, "Resources exhausted: Failed to allocate additional 30 bytes for s4 with 0 bytes already allocated - maximum available is 20");
     }
+
+    #[tokio::test]
+    async fn test_fair_spill_request() {
+        let pool = Arc::new(FairSpillPool::new(100)) as _;
+        let request = Arc::new(SpillRequest::new());
+        let mut r1 = MemoryConsumer::new("r1")
+            .with_can_spill(true)
+            .with_spill_callback(request.clone())
+            .register(&pool);
+        let mut r2 = MemoryConsumer::new("r2")
+            .with_can_spill(true)
+            .register(&pool);
+        r1.try_grow(50).unwrap();
+        r2.try_grow(30).unwrap();
+
+        // r2 has no callback, so r1 alone cannot make room for 80 bytes
+        let mut r3 = MemoryConsumer::new("r3").register(&pool);
+        let timeout = Duration::from_secs(10);
+        let err = r3.try_grow_with_spill(80, timeout).await.unwrap_err();
+        assert_eq!(err.strip_backtrace(), "Resources exhausted: Failed to allocate additional 80 bytes for r3 with 0 bytes already allocated - maximum available is 20");
+        assert_eq!(request.take(), 0);
+
+        // r1 is asked for the 20 missing bytes, and r3 grows once it spilled
+        let spill = async {
+            while request.take() == 0 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+            r1.free();
+        };
+        let (result, _) = tokio::join!(r3.try_grow_with_spill(40, timeout), spill);
+        result.unwrap();
+        assert_eq!(r3.size(), 40);
+        assert_eq!(pool.reserved(), 70);
+
+        // a consumer that does not spill makes the reservation time out
+        r1.try_grow(30).unwrap();
+        let timeout = Duration::from_millis(50);
+        let err = r3.try_grow_with_spill(20, timeout).await.unwrap_err();
+        assert_eq!(err.strip_backtrace(), "Resources exhausted: Failed to allocate additional 20 bytes for r3 with 40 bytes already allocated - maximum available is 0");
+        assert_eq!(request.take(), 20);
+    }
 }
@@ -0,0 +1,338 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::memory_pool::{MemoryConsumer, MemoryPool, MemoryReservation};
+use datafusion_common::{DataFusionError, Result};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The number of consumers listed when a reservation fails
+const REPORTED_CONSUMERS: usize = 5;
+
+/// A [`MemoryPool`] that accounts for the memory of a scope, such as a query
+/// or a user, within a parent pool, optionally limiting it.
+///
+/// Reservations are made in the parent pool once they fit within the limit
+/// of the scope, so scopes can be nested to build a tree of limits, e.g. a
+/// limit per query within a limit per user within the global limit of a
+/// [`GreedyMemoryPool`] or [`FairSpillPool`]:
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use datafusion_execution::memory_pool::*;
+/// let global: Arc<dyn MemoryPool> = Arc::new(GreedyMemoryPool::new(1024));
+/// let user: Arc<dyn MemoryPool> =
+///     Arc::new(ScopedMemoryPool::new("alice", global).with_limit(512));
+/// let query: Arc<dyn MemoryPool> =
+///     Arc::new(ScopedMemoryPool::new("query 1", user.clone()).with_limit(256));
+///
+/// let mut reservation = MemoryConsumer::new("SortExec[0]").register(&query);
+/// reservation.try_grow(300).unwrap_err();
+/// reservation.try_grow(200).unwrap();
+/// assert_eq!(user.reserved(), 200);
+/// ```
+///
+/// Each scope tracks the consumers registered in it and in the scopes nested
+/// in it. When a reservation fails, the error lists the largest consumers of
+/// the scope whose limit was exceeded, or of the outermost scope if the
+/// parent pool of that scope is exhausted.
+///
+/// [`GreedyMemoryPool`]: crate::memory_pool::GreedyMemoryPool
+/// [`FairSpillPool`]: crate::memory_pool::FairSpillPool
+#[derive(Debug)]
+pub struct ScopedMemoryPool {
+    name: String,
+    limit: Option<usize>,
+    parent: Arc<dyn MemoryPool>,
+    state: Mutex<ScopeState>,
+}
+
+#[derive(Debug, Default)]
+struct ScopeState {
+    /// The total amount of memory reserved within the scope
+    reserved: usize,
+
+    /// The consumers of the scope and its nested scopes, by id
+    consumers: HashMap<usize, ConsumerUsage>,
+}
+
+/// The memory reserved by a [`MemoryConsumer`] of a [`ScopedMemoryPool`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumerUsage {
+    /// The path of the scope the consumer was registered in, relative to the
+    /// reporting scope, e.g. `alice/query 1`
+    pub scope: String,
+    /// The name of the consumer
+    pub consumer: String,
+    /// The number of bytes reserved by the consumer
+    pub reserved: usize,
+}
+
+impl ScopedMemoryPool {
+    /// Create a new scope named `name` reserving memory in `parent`
+    pub fn new(name: impl Into<String>, parent: Arc<dyn MemoryPool>) -> Self {
+        Self {
+            name: name.into(),
+            limit: None,
+            parent,
+            state: Mutex::new(ScopeState::default()),
+        }
+    }
+
+    /// Limit the memory reserved within this scope to `limit` bytes
+    pub fn with_limit(self, limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Returns the name of this scope
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the limit of this scope, if any
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Returns the memory reserved by the consumers of this scope and of the
+    /// scopes nested in it, largest first
+    pub fn consumers(&self) -> Vec<ConsumerUsage> {
+        let mut consumers = self
+            .state
+            .lock()
+            .consumers
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        consumers.sort_by(|a, b| {
+            b.reserved
+                .cmp(&a.reserved)
+                .then_with(|| a.scope.cmp(&b.scope))
+                .then_with(|| a.consumer.cmp(&b.consumer))
+        });
+        consumers
+    }
+
+    /// Registers `consumer` of the scope at `path` within this one, or of
+    /// this scope if `None`
+    fn register_consumer(&self, consumer: &MemoryConsumer, path: Option<&str>) {
+        let scope = match path {
+            Some(path) => format!("{}/{path}", self.name),
+            None => self.name.clone(),
+        };
+        match self.parent.as_scope() {
+            Some(parent) => parent.register_consumer(consumer, Some(&scope)),
+            None => self.parent.register(consumer),
+        }
+        let usage = ConsumerUsage {
+            scope,
+            consumer: consumer.name.clone(),
+            reserved: 0,
+        };
+        self.state.lock().consumers.insert(consumer.id, usage);
+    }
+
+    /// Appends the largest consumers of this scope to the message of a
+    /// [`DataFusionError::ResourcesExhausted`]
+    fn with_consumers(&self, err: DataFusionError) -> DataFusionError {
+        let DataFusionError::ResourcesExhausted(message) = err else {
+            return err;
+        };
+        let consumers = self
+            .consumers()
+            .into_iter()
+            .filter(|usage| usage.reserved > 0)
+            .take(REPORTED_CONSUMERS)
+            .map(|usage| {
+                format!(
+                    "{}/{} with {} bytes",
+                    usage.scope, usage.consumer, usage.reserved
+                )
+            })
+            .collect::<Vec<_>>();
+        if consumers.is_empty() {
+            return DataFusionError::ResourcesExhausted(message);
+        }
+        DataFusionError::ResourcesExhausted(format!(
+            "{message}. Top memory consumers of {}: {}",
+            self.name,
+            consumers.join(", ")
+        ))
+    }
+}
+
+impl MemoryPool for ScopedMemoryPool {
+    fn register(&self, consumer: &MemoryConsumer) {
+        self.register_consumer(consumer, None)
+    }
+
+    fn unregister(&self, consumer: &MemoryConsumer) {
+        self.state.lock().consumers.remove(&consumer.id);
+        self.parent.unregister(consumer)
+    }
+
+    fn grow(&self, reservation: &MemoryReservation, additional: usize) {
+        let mut state = self.state.lock();
+        state.reserved += additional;
+        let id = reservation.registration.consumer.id;
+        if let Some(usage) = state.consumers.get_mut(&id) {
+            usage.reserved += additional;
+        }
+        self.parent.grow(reservation, additional)
+    }
+
+    fn shrink(&self, reservation: &MemoryReservation, shrink: usize) {
+        let mut state = self.state.lock();
+        state.reserved -= shrink;
+        let id = reservation.registration.consumer.id;
+        if let Some(usage) = state.consumers.get_mut(&id) {
+            usage.reserved -= shrink;
+        }
+        self.parent.shrink(reservation, shrink)
+    }
+
+    fn try_grow(&self, reservation: &MemoryReservation, additional: usize) -> Result<()> {
+        // Holding the lock while reserving in the parent keeps the limit
+        // exact; locks are always taken from the innermost scope outwards
+        let mut state = self.state.lock();
+        if let Some(limit) = self.limit {
+            let available = limit.saturating_sub(state.reserved);
+            if additional > available {
+                drop(state);
+                let consumer = &reservation.registration.consumer;
+                let err = DataFusionError::ResourcesExhausted(format!(
+                    "Failed to allocate additional {} bytes for {} with {} bytes already allocated - maximum available is {} in {}",
+                    additional, consumer.name, reservation.size, available, self.name
+                ));
+                return Err(self.with_consumers(err));
+            }
+        }
+        if let Err(err) = self.parent.try_grow(reservation, additional) {
+            drop(state);
+            // The outermost scope reports the consumers of an exhausted pool
+            return match self.parent.as_scope() {
+                Some(_) => Err(err),
+                None => Err(self.with_consumers(err)),
+            };
+        }
+        state.reserved += additional;
+        let id = reservation.registration.consumer.id;
+        if let Some(usage) = state.consumers.get_mut(&id) {
+            usage.reserved += additional;
+        }
+        Ok(())
+    }
+
+    fn reserved(&self) -> usize {
+        self.state.lock().reserved
+    }
+
+    fn request_spill(&self, reservation: &MemoryReservation, additional: usize) -> usize {
+        // Consumers spilling in the parent pool do not make room within the
+        // limit of this scope
+        if let Some(limit) = self.limit {
+            if self.state.lock().reserved + additional > limit {
+                return 0;
+            }
+        }
+        self.parent.request_spill(reservation, additional)
+    }
+
+    fn as_scope(&self) -> Option<&ScopedMemoryPool> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_pool::{FairSpillPool, GreedyMemoryPool};
+
+    #[test]
+    fn test_nested_limits() {
+        let global = Arc::new(GreedyMemoryPool::new(100)) as Arc<dyn MemoryPool>;
+        let user = Arc::new(ScopedMemoryPool::new("alice", global.clone()).with_limit(60))
+            as Arc<dyn MemoryPool>;
+        let q1 = Arc::new(ScopedMemoryPool::new("query 1", user.clone()).with_limit(40))
+            as Arc<dyn MemoryPool>;
+        let q2 = Arc::new(ScopedMemoryPool::new("query 2", user.clone()))
+            as Arc<dyn MemoryPool>;
+
+        let mut r1 = MemoryConsumer::new("r1").register(&q1);
+        r1.try_grow(30).unwrap();
+        let err = r1.try_grow(20).unwrap_err().strip_backtrace();
+        assert_eq!(err, "Resources exhausted: Failed to allocate additional 20 bytes for r1 with 30 bytes already allocated - maximum available is 10 in query 1. Top memory consumers of query 1: query 1/r1 with 30 bytes");
+
+        let mut r2 = MemoryConsumer::new("r2").register(&q2);
+        r2.try_grow(20).unwrap();
+        let err = r2.try_grow(20).unwrap_err().strip_backtrace();
+        assert_eq!(err, "Resources exhausted: Failed to allocate additional 20 bytes for r2 with 20 bytes already allocated - maximum available is 10 in alice. Top memory consumers of alice: alice/query 1/r1 with 30 bytes, alice/query 2/r2 with 20 bytes");
+
+        assert_eq!(q1.reserved(), 30);
+        assert_eq!(q2.reserved(), 20);
+        assert_eq!(user.reserved(), 50);
+        assert_eq!(global.reserved(), 50);
+
+        // the global limit applies to all users
+        let mut r3 = MemoryConsumer::new("r3").register(&global);
+        r3.try_grow(45).unwrap();
+        let err = r2.try_grow(10).unwrap_err().strip_backtrace();
+        assert_eq!(err, "Resources exhausted: Failed to allocate additional 10 bytes for r2 with 20 bytes already allocated - maximum available is 5. Top memory consumers of alice: alice/query 1/r1 with 30 bytes, alice/query 2/r2 with 20 bytes");
+
+        drop(r1);
+        assert_eq!(user.reserved(), 20);
+        assert_eq!(global.reserved(), 65);
+        let consumers = user.as_scope().unwrap().consumers();
+        assert_eq!(
+            consumers,
+            vec![ConsumerUsage {
+                scope: "alice/query 2".to_string(),
+                consumer: "r2".to_string(),
+                reserved: 20,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_fair_spill_parent() {
+        let global = Arc::new(FairSpillPool::new(100)) as Arc<dyn MemoryPool>;
+        let query = Arc::new(ScopedMemoryPool::new("query", global.clone()))
+            as Arc<dyn MemoryPool>;
+
+        let mut r1 = MemoryConsumer::new("r1")
+            .with_can_spill(true)
+            .register(&query);
+        let mut r2 = MemoryConsumer::new("r2")
+            .with_can_spill(true)
+            .register(&query);
+
+        // the spillable consumers of the scope share the parent pool fairly
+        r1.try_grow(50).unwrap();
+        let err = r1.try_grow(1).unwrap_err().strip_backtrace();
+        assert_eq!(err, "Resources exhausted: Failed to allocate additional 1 bytes for r1 with 50 bytes already allocated - maximum available is 50. Top memory consumers of query: query/r1 with 50 bytes");
+        r2.try_grow(50).unwrap();
+
+        drop(r2);
+        r1.try_grow(50).unwrap();
+        assert_eq!(query.reserved(), 100);
+        assert_eq!(global.reserved(), 100);
+    }
+}
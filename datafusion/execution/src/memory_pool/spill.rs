@@ -0,0 +1,66 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Asks a spillable [`MemoryConsumer`] to release memory when its
+/// [`MemoryPool`] is under pressure.
+///
+/// A consumer only spills when its own reservation fails, so a consumer that
+/// cannot spill, such as the build side of a hash join, could otherwise fail
+/// while other consumers of the pool hold lots of memory. Pools supporting
+/// cooperative spilling, such as [`FairSpillPool`], call the callbacks of the
+/// largest spillable consumers from [`MemoryPool::request_spill`], and the
+/// consumers release memory when they next can.
+///
+/// [`MemoryConsumer`]: crate::memory_pool::MemoryConsumer
+/// [`MemoryPool`]: crate::memory_pool::MemoryPool
+/// [`MemoryPool::request_spill`]: crate::memory_pool::MemoryPool::request_spill
+/// [`FairSpillPool`]: crate::memory_pool::FairSpillPool
+pub trait SpillCallback: Send + Sync + Debug {
+    /// Asks the consumer to release about `bytes` bytes of memory.
+    ///
+    /// This is called while the pool is locked, so it must not reserve or
+    /// free memory itself
+    fn request_spill(&self, bytes: usize);
+}
+
+/// A [`SpillCallback`] recording the memory requested from a consumer, for
+/// operators that spill the next time they process a batch
+#[derive(Debug, Default)]
+pub struct SpillRequest {
+    requested: AtomicUsize,
+}
+
+impl SpillRequest {
+    /// Create a new request with no memory requested
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bytes requested since the last call, if any
+    pub fn take(&self) -> usize {
+        self.requested.swap(0, Ordering::Relaxed)
+    }
+}
+
+impl SpillCallback for SpillRequest {
+    fn request_spill(&self, bytes: usize) {
+        self.requested.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
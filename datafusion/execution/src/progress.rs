@@ -0,0 +1,335 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Live progress reporting of running queries
+//!
+//! A [`QueryProgress`] is attached to the [`TaskContext`] of a query and
+//! updated by its operators while it runs. Its [`ProgressReport`]s can be
+//! polled at any time, e.g. to display a progress bar, and the
+//! [`ProgressRegistry`] of a session lists the reports of all of its running
+//! queries.
+//!
+//! [`TaskContext`]: crate::TaskContext
+
+use std::fmt::{self, Debug};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use datafusion_common::instant::Instant;
+use parking_lot::{Mutex, RwLock};
+
+/// Reports the state of the operators of a running query, such as its
+/// execution plan, which the execution crate knows nothing about
+pub trait OperatorProgress: Send + Sync {
+    /// The operators with partitions that are currently running
+    fn active_operators(&self) -> Vec<ActiveOperator>;
+
+    /// The number of times the operators spilled data to disk, and the total
+    /// number of bytes they spilled
+    fn spills(&self) -> (usize, usize);
+}
+
+/// An operator of a running query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveOperator {
+    /// The name of the operator, e.g. `SortExec`
+    pub name: String,
+    /// The number of partitions of the operator that are running
+    pub running_partitions: usize,
+}
+
+/// The progress of a single query, shared by all of its partitions
+pub struct QueryProgress {
+    query_id: usize,
+    start: Instant,
+    /// The number of streams of the query that are being consumed
+    streams: AtomicUsize,
+    estimated_bytes: AtomicUsize,
+    bytes_scanned: AtomicUsize,
+    estimated_rows: AtomicUsize,
+    /// Set if a scan has no row count estimate, which makes `estimated_rows`
+    /// a lower bound only
+    rows_unknown: AtomicBool,
+    rows_scanned: AtomicUsize,
+    operators: RwLock<Option<Arc<dyn OperatorProgress>>>,
+}
+
+impl Debug for QueryProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryProgress")
+            .field("query_id", &self.query_id)
+            .field("start", &self.start)
+            .finish_non_exhaustive()
+    }
+}
+
+impl QueryProgress {
+    /// Create the progress of a new query with the given id
+    pub fn new(query_id: usize) -> Self {
+        Self {
+            query_id,
+            start: Instant::now(),
+            streams: AtomicUsize::new(0),
+            estimated_bytes: AtomicUsize::new(0),
+            bytes_scanned: AtomicUsize::new(0),
+            estimated_rows: AtomicUsize::new(0),
+            rows_unknown: AtomicBool::new(false),
+            rows_scanned: AtomicUsize::new(0),
+            operators: RwLock::new(None),
+        }
+    }
+
+    /// The id of the query, unique within its [`ProgressRegistry`]
+    pub fn query_id(&self) -> usize {
+        self.query_id
+    }
+
+    /// Record that a scan will read `bytes` bytes containing `rows` rows, or
+    /// an unknown number of rows if `None`
+    pub fn add_estimated_scan(&self, bytes: usize, rows: Option<usize>) {
+        self.estimated_bytes.fetch_add(bytes, Ordering::Relaxed);
+        match rows {
+            Some(rows) => {
+                self.estimated_rows.fetch_add(rows, Ordering::Relaxed);
+            }
+            None => self.rows_unknown.store(true, Ordering::Relaxed),
+        }
+    }
+
+    /// Record that `bytes` bytes were scanned
+    pub fn add_scanned_bytes(&self, bytes: usize) {
+        self.bytes_scanned.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record that `rows` rows were scanned
+    pub fn add_scanned_rows(&self, rows: usize) {
+        self.rows_scanned.fetch_add(rows, Ordering::Relaxed);
+    }
+
+    /// Set the source of the state of the operators of this query, unless
+    /// one was already set
+    pub fn set_operators(&self, operators: Arc<dyn OperatorProgress>) {
+        let mut current = self.operators.write();
+        if current.is_none() {
+            *current = Some(operators);
+        }
+    }
+
+    /// Record that a stream of the query started being consumed. The query
+    /// is running until the returned guard, and those of the other streams
+    /// of the query, are dropped.
+    pub fn stream_started(self: &Arc<Self>) -> RunningStream {
+        self.streams.fetch_add(1, Ordering::Relaxed);
+        RunningStream {
+            progress: Arc::clone(self),
+        }
+    }
+
+    /// Returns true if any stream of the query is being consumed
+    pub fn is_running(&self) -> bool {
+        self.streams.load(Ordering::Relaxed) > 0
+    }
+
+    /// Returns a snapshot of the progress of the query
+    pub fn report(&self) -> ProgressReport {
+        let operators = self.operators.read().clone();
+        let (active_operators, (spill_count, spilled_bytes)) = match operators {
+            Some(operators) => (operators.active_operators(), operators.spills()),
+            None => (vec![], (0, 0)),
+        };
+        let estimated_bytes = self.estimated_bytes.load(Ordering::Relaxed);
+        let estimated_rows = self.estimated_rows.load(Ordering::Relaxed);
+        ProgressReport {
+            query_id: self.query_id,
+            elapsed: self.start.elapsed(),
+            bytes_scanned: self.bytes_scanned.load(Ordering::Relaxed),
+            estimated_bytes: (estimated_bytes > 0).then_some(estimated_bytes),
+            rows_scanned: self.rows_scanned.load(Ordering::Relaxed),
+            estimated_rows: (estimated_rows > 0
+                && !self.rows_unknown.load(Ordering::Relaxed))
+            .then_some(estimated_rows),
+            active_operators,
+            spill_count,
+            spilled_bytes,
+        }
+    }
+}
+
+/// Marks a stream of a query as being consumed, see
+/// [`QueryProgress::stream_started`]
+#[derive(Debug)]
+pub struct RunningStream {
+    progress: Arc<QueryProgress>,
+}
+
+impl Drop for RunningStream {
+    fn drop(&mut self) {
+        self.progress.streams.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A snapshot of the progress of a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressReport {
+    /// The id of the query
+    pub query_id: usize,
+    /// The time since the query was created
+    pub elapsed: Duration,
+    /// The number of bytes of input files read so far
+    pub bytes_scanned: usize,
+    /// The total size of the input files, if known. Scans add the size of
+    /// their files once started, so the estimate can grow while the query
+    /// runs.
+    pub estimated_bytes: Option<usize>,
+    /// The number of rows read from input files so far
+    pub rows_scanned: usize,
+    /// The total number of rows of the input files, if known from their
+    /// statistics
+    pub estimated_rows: Option<usize>,
+    /// The operators that are currently running
+    pub active_operators: Vec<ActiveOperator>,
+    /// The number of times operators spilled data to disk
+    pub spill_count: usize,
+    /// The number of bytes operators spilled to disk
+    pub spilled_bytes: usize,
+}
+
+impl ProgressReport {
+    /// The fraction of the input of the query that was scanned, between 0
+    /// and 1, or `None` if the size of the input is unknown.
+    ///
+    /// Rows are used when their total is known, as the bytes of a file are
+    /// only counted once the file is completely read.
+    pub fn fraction(&self) -> Option<f64> {
+        let (done, total) = match (self.estimated_rows, self.estimated_bytes) {
+            (Some(rows), _) => (self.rows_scanned, rows),
+            (None, Some(bytes)) => (self.bytes_scanned, bytes),
+            (None, None) => return None,
+        };
+        Some((done as f64 / total as f64).min(1.0))
+    }
+}
+
+/// Tracks the progress of the queries of a session
+#[derive(Debug, Default)]
+pub struct ProgressRegistry {
+    next_query_id: AtomicUsize,
+    queries: Mutex<Vec<Weak<QueryProgress>>>,
+}
+
+impl ProgressRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the progress of a new query. The query is tracked until the
+    /// returned [`QueryProgress`] is dropped.
+    pub fn register(&self) -> Arc<QueryProgress> {
+        let query_id = self.next_query_id.fetch_add(1, Ordering::Relaxed);
+        let progress = Arc::new(QueryProgress::new(query_id));
+        let mut queries = self.queries.lock();
+        queries.retain(|query| query.strong_count() > 0);
+        queries.push(Arc::downgrade(&progress));
+        progress
+    }
+
+    /// Returns the progress of the queries that are currently running
+    pub fn running(&self) -> Vec<ProgressReport> {
+        let queries = self
+            .queries
+            .lock()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        queries
+            .iter()
+            .filter(|query| query.is_running())
+            .map(|query| query.report())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestOperators;
+
+    impl OperatorProgress for TestOperators {
+        fn active_operators(&self) -> Vec<ActiveOperator> {
+            vec![ActiveOperator {
+                name: "SortExec".to_string(),
+                running_partitions: 2,
+            }]
+        }
+
+        fn spills(&self) -> (usize, usize) {
+            (1, 1024)
+        }
+    }
+
+    #[test]
+    fn report() {
+        let progress = QueryProgress::new(0);
+        let report = progress.report();
+        assert_eq!(report.estimated_bytes, None);
+        assert_eq!(report.fraction(), None);
+
+        progress.add_estimated_scan(1000, None);
+        progress.add_scanned_rows(10);
+        progress.add_scanned_bytes(250);
+        let report = progress.report();
+        assert_eq!(report.estimated_bytes, Some(1000));
+        assert_eq!(report.estimated_rows, None);
+        assert_eq!(report.fraction(), Some(0.25));
+
+        let progress = QueryProgress::new(1);
+        progress.add_estimated_scan(1000, Some(40));
+        progress.add_estimated_scan(1000, Some(60));
+        progress.add_scanned_rows(50);
+        progress.set_operators(Arc::new(TestOperators));
+        let report = progress.report();
+        assert_eq!(report.estimated_rows, Some(100));
+        assert_eq!(report.fraction(), Some(0.5));
+        assert_eq!(report.active_operators[0].name, "SortExec");
+        assert_eq!(report.spill_count, 1);
+        assert_eq!(report.spilled_bytes, 1024);
+    }
+
+    #[test]
+    fn registry() {
+        let registry = ProgressRegistry::new();
+        let first = registry.register();
+        let second = registry.register();
+        assert_ne!(first.query_id(), second.query_id());
+        assert!(registry.running().is_empty());
+
+        let stream = second.stream_started();
+        let running = registry.running();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].query_id, second.query_id());
+
+        drop(stream);
+        assert!(registry.running().is_empty());
+
+        let _stream = first.stream_started();
+        drop(first);
+        assert_eq!(registry.running().len(), 1);
+    }
+}
@@ -22,9 +22,11 @@ use crate::{
     disk_manager::{DiskManager, DiskManagerConfig},
     memory_pool::{GreedyMemoryPool, MemoryPool, UnboundedMemoryPool},
     object_store::{DefaultObjectStoreRegistry, ObjectStoreRegistry},
+    scheduler::QueryScheduler,
 };
 
 use crate::cache::cache_manager::{CacheManager, CacheManagerConfig};
+use crate::cache::caching_object_store::CachingObjectStoreRegistry;
 use datafusion_common::{DataFusionError, Result};
 use object_store::ObjectStore;
 use std::fmt::{Debug, Formatter};
@@ -43,6 +45,7 @@ use url::Url;
 /// * [`DiskManager`]: Manage temporary files on local disk
 /// * [`CacheManager`]: Manage temporary cache data during the session lifetime
 /// * [`ObjectStoreRegistry`]: Manage mapping URLs to object store instances
+/// * [`QueryScheduler`]: Optionally limit the number of concurrent queries
 pub struct RuntimeEnv {
     /// Runtime memory management
     pub memory_pool: Arc<dyn MemoryPool>,
@@ -52,6 +55,8 @@ pub struct RuntimeEnv {
     pub cache_manager: Arc<CacheManager>,
     /// Object Store Registry
     pub object_store_registry: Arc<dyn ObjectStoreRegistry>,
+    /// Admits the queries of the sessions using this runtime, if set
+    pub query_scheduler: Option<Arc<QueryScheduler>>,
 }
 
 impl Debug for RuntimeEnv {
@@ -68,16 +73,41 @@ impl RuntimeEnv {
             disk_manager,
             cache_manager,
             object_store_registry,
+            query_scheduler,
+            max_temp_directory_size,
         } = config;
 
         let memory_pool =
             memory_pool.unwrap_or_else(|| Arc::new(UnboundedMemoryPool::default()));
 
+        // trace the requests to the stores, not those served by the cache
+        #[cfg(feature = "tracing")]
+        let object_store_registry: Arc<dyn ObjectStoreRegistry> =
+            Arc::new(crate::traced_object_store::TracedObjectStoreRegistry::new(
+                object_store_registry,
+            ));
+
+        let cache_manager = CacheManager::try_new(&cache_manager)?;
+        let object_store_registry: Arc<dyn ObjectStoreRegistry> =
+            match cache_manager.get_byte_range_cache() {
+                Some(cache) => Arc::new(CachingObjectStoreRegistry::new(
+                    object_store_registry,
+                    cache,
+                )),
+                None => object_store_registry,
+            };
+
+        let disk_manager = DiskManager::try_new(disk_manager)?;
+        if max_temp_directory_size.is_some() {
+            disk_manager.set_max_temp_directory_size(max_temp_directory_size);
+        }
+
         Ok(Self {
             memory_pool,
-            disk_manager: DiskManager::try_new(disk_manager)?,
-            cache_manager: CacheManager::try_new(&cache_manager)?,
+            disk_manager,
+            cache_manager,
             object_store_registry,
+            query_scheduler,
         })
     }
 
@@ -126,6 +156,15 @@ pub struct RuntimeConfig {
     pub cache_manager: CacheManagerConfig,
     /// ObjectStoreRegistry to get object store based on url
     pub object_store_registry: Arc<dyn ObjectStoreRegistry>,
+    /// [`QueryScheduler`] admitting queries
+    ///
+    /// Queries are not queued if `None`
+    pub query_scheduler: Option<Arc<QueryScheduler>>,
+    /// Maximum number of bytes the temporary files of the [`DiskManager`]
+    /// may use, such as the spill files of queries
+    ///
+    /// Defaults to no limit if `None`
+    pub max_temp_directory_size: Option<u64>,
 }
 
 impl Default for RuntimeConfig {
@@ -142,6 +181,8 @@ impl RuntimeConfig {
             memory_pool: Default::default(),
             cache_manager: Default::default(),
             object_store_registry: Arc::new(DefaultObjectStoreRegistry::default()),
+            query_scheduler: None,
+            max_temp_directory_size: None,
         }
     }
 
@@ -172,6 +213,18 @@ impl RuntimeConfig {
         self
     }
 
+    /// Customize query admission
+    pub fn with_query_scheduler(mut self, query_scheduler: Arc<QueryScheduler>) -> Self {
+        self.query_scheduler = Some(query_scheduler);
+        self
+    }
+
+    /// Limit the disk space used by temporary files, such as spill files
+    pub fn with_max_temp_directory_size(mut self, max_temp_directory_size: u64) -> Self {
+        self.max_temp_directory_size = Some(max_temp_directory_size);
+        self
+    }
+
     /// Specify the total memory to use while running the DataFusion
     /// plan to `max_memory * memory_fraction` in bytes.
     ///
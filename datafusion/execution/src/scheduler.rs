@@ -0,0 +1,403 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Admission control of concurrent queries
+//!
+//! Every query spawns tasks for its partitions without any coordination
+//! with the other queries, so a large query can starve small interactive
+//! ones of CPU and memory. A [`QueryScheduler`] shared by the sessions of a
+//! [`RuntimeEnv`] limits the number of queries that run at once, and queues
+//! the others by [`QueryPriority`] until a slot is free.
+//!
+//! [`RuntimeEnv`]: crate::runtime_env::RuntimeEnv
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use datafusion_common::{internal_datafusion_err, plan_err, DataFusionError, Result};
+use futures::{Stream, StreamExt};
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+use crate::memory_pool::MemoryPool;
+use crate::{RecordBatchStream, SendableRecordBatchStream};
+
+/// The priority class of a query. Queued queries of a higher priority are
+/// admitted first, and queries of the same priority in arrival order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QueryPriority {
+    /// Batch queries that can wait for interactive ones
+    Low,
+    /// The default priority
+    #[default]
+    Normal,
+    /// Interactive queries
+    High,
+}
+
+impl FromStr for QueryPriority {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            _ => plan_err!(
+                "Unknown query priority '{s}', expected one of 'low', 'normal' or 'high'"
+            ),
+        }
+    }
+}
+
+impl Display for QueryPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Normal => write!(f, "normal"),
+            Self::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Limits the number of queries running at once, queueing the others.
+///
+/// A query is admitted if fewer than `slots` queries are running, no query of
+/// a higher or equal priority is queued before it, and, if a memory pool is
+/// set with [`Self::with_memory_threshold`], the pool has less than the
+/// threshold reserved. Queued queries are admitted as running queries finish.
+/// A query is always admitted if no query runs, so a saturated memory pool
+/// cannot block the scheduler.
+#[derive(Debug)]
+pub struct QueryScheduler {
+    slots: usize,
+    max_queued: Option<usize>,
+    memory_threshold: Option<(Arc<dyn MemoryPool>, usize)>,
+    state: Mutex<SchedulerState>,
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+    /// The number of admitted queries that have not finished
+    running: usize,
+    /// The arrival number of the next queued query
+    next_arrival: u64,
+    /// The queued queries, highest priority and earliest arrival first
+    queued: BTreeMap<(Reverse<QueryPriority>, u64), oneshot::Sender<QueryPermit>>,
+}
+
+impl QueryScheduler {
+    /// Create a scheduler running up to `slots` queries at once
+    pub fn new(slots: usize) -> Self {
+        Self {
+            slots: slots.max(1),
+            max_queued: None,
+            memory_threshold: None,
+            state: Mutex::new(SchedulerState::default()),
+        }
+    }
+
+    /// Fail queries with [`DataFusionError::ResourcesExhausted`] rather than
+    /// queueing them once `max_queued` queries are queued
+    pub fn with_max_queued(self, max_queued: usize) -> Self {
+        Self {
+            max_queued: Some(max_queued),
+            ..self
+        }
+    }
+
+    /// Queue queries while `memory_pool` has at least `threshold` bytes
+    /// reserved
+    pub fn with_memory_threshold(
+        self,
+        memory_pool: Arc<dyn MemoryPool>,
+        threshold: usize,
+    ) -> Self {
+        Self {
+            memory_threshold: Some((memory_pool, threshold)),
+            ..self
+        }
+    }
+
+    /// Returns the number of queries running at once
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Returns the number of admitted queries that have not finished
+    pub fn running(&self) -> usize {
+        self.state.lock().running
+    }
+
+    /// Returns the number of queries waiting to be admitted
+    pub fn queued(&self) -> usize {
+        let state = self.state.lock();
+        state
+            .queued
+            .values()
+            .filter(|sender| !sender.is_closed())
+            .count()
+    }
+
+    /// Waits until a query of the given `priority` can run. The query runs
+    /// until the returned [`QueryPermit`] is dropped.
+    ///
+    /// Dropping the returned future removes the query from the queue.
+    pub async fn admit(self: &Arc<Self>, priority: QueryPriority) -> Result<QueryPermit> {
+        let receiver = {
+            let mut state = self.state.lock();
+            // Queries that are no longer waiting leave the queue
+            state.queued.retain(|_, sender| !sender.is_closed());
+            let queued_first = state
+                .queued
+                .keys()
+                .next()
+                .is_some_and(|(Reverse(queued), _)| *queued >= priority);
+            if !queued_first && self.has_capacity(state.running) {
+                state.running += 1;
+                return Ok(QueryPermit {
+                    scheduler: Arc::clone(self),
+                });
+            }
+            if let Some(max_queued) = self.max_queued {
+                if state.queued.len() >= max_queued {
+                    return Err(DataFusionError::ResourcesExhausted(format!(
+                        "Query queue is full: {} queries are running and {} are queued",
+                        state.running,
+                        state.queued.len()
+                    )));
+                }
+            }
+            let (sender, receiver) = oneshot::channel();
+            let arrival = state.next_arrival;
+            state.next_arrival += 1;
+            state.queued.insert((Reverse(priority), arrival), sender);
+            receiver
+        };
+        let permit = receiver.await.map_err(|_| {
+            internal_datafusion_err!("Query scheduler dropped a queued query")
+        })?;
+        Ok(permit)
+    }
+
+    fn has_capacity(&self, running: usize) -> bool {
+        if running >= self.slots {
+            return false;
+        }
+        match &self.memory_threshold {
+            Some((pool, threshold)) => running == 0 || pool.reserved() < *threshold,
+            None => true,
+        }
+    }
+
+    /// Records that a query finished, admitting queued queries if possible
+    fn release(self: &Arc<Self>) {
+        let admitted = {
+            let mut state = self.state.lock();
+            state.running -= 1;
+            let mut admitted = vec![];
+            while self.has_capacity(state.running) {
+                let Some((_, sender)) = state.queued.pop_first() else {
+                    break;
+                };
+                if sender.is_closed() {
+                    continue;
+                }
+                state.running += 1;
+                admitted.push(sender);
+            }
+            admitted
+        };
+        for sender in admitted {
+            let permit = QueryPermit {
+                scheduler: Arc::clone(self),
+            };
+            // A query that stopped waiting releases its permit on drop
+            let _ = sender.send(permit);
+        }
+    }
+}
+
+/// Marks a query admitted by a [`QueryScheduler`] as running until dropped
+#[derive(Debug)]
+pub struct QueryPermit {
+    scheduler: Arc<QueryScheduler>,
+}
+
+impl QueryPermit {
+    /// Keeps the query running until `stream` is exhausted or dropped
+    pub fn hold(self, stream: SendableRecordBatchStream) -> SendableRecordBatchStream {
+        let permit = Arc::new(self);
+        Box::pin(AdmittedStream::new(stream, &permit))
+    }
+
+    /// Keeps the query running until all of the `streams` of its partitions
+    /// are exhausted or dropped
+    pub fn hold_partitions(
+        self,
+        streams: Vec<SendableRecordBatchStream>,
+    ) -> Vec<SendableRecordBatchStream> {
+        let permit = Arc::new(self);
+        streams
+            .into_iter()
+            .map(|stream| {
+                Box::pin(AdmittedStream::new(stream, &permit))
+                    as SendableRecordBatchStream
+            })
+            .collect()
+    }
+}
+
+impl Drop for QueryPermit {
+    fn drop(&mut self) {
+        self.scheduler.release()
+    }
+}
+
+/// The output stream of an admitted query
+struct AdmittedStream {
+    input: SendableRecordBatchStream,
+    permit: Option<Arc<QueryPermit>>,
+}
+
+impl AdmittedStream {
+    fn new(input: SendableRecordBatchStream, permit: &Arc<QueryPermit>) -> Self {
+        Self {
+            input,
+            permit: Some(Arc::clone(permit)),
+        }
+    }
+}
+
+impl Stream for AdmittedStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let poll = self.input.poll_next_unpin(cx);
+        if matches!(poll, Poll::Ready(None)) {
+            self.permit = None;
+        }
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl RecordBatchStream for AdmittedStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_pool::{GreedyMemoryPool, MemoryConsumer};
+
+    #[tokio::test]
+    async fn priorities() -> Result<()> {
+        let scheduler = Arc::new(QueryScheduler::new(1));
+        let first = scheduler.admit(QueryPriority::Normal).await?;
+
+        let mut low = Box::pin(scheduler.admit(QueryPriority::Low));
+        let mut normal = Box::pin(scheduler.admit(QueryPriority::Normal));
+        let mut high = Box::pin(scheduler.admit(QueryPriority::High));
+        assert!(futures::poll!(&mut low).is_pending());
+        assert!(futures::poll!(&mut normal).is_pending());
+        assert!(futures::poll!(&mut high).is_pending());
+        assert_eq!(scheduler.running(), 1);
+        assert_eq!(scheduler.queued(), 3);
+
+        // a query that stops waiting leaves the queue
+        drop(normal);
+
+        drop(first);
+        let high = high.await?;
+        assert!(futures::poll!(&mut low).is_pending());
+        assert_eq!(scheduler.queued(), 1);
+
+        drop(high);
+        let low = low.await?;
+        assert_eq!(scheduler.running(), 1);
+        assert_eq!(scheduler.queued(), 0);
+
+        drop(low);
+        assert_eq!(scheduler.running(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn max_queued() -> Result<()> {
+        let scheduler = Arc::new(QueryScheduler::new(1).with_max_queued(1));
+        let _first = scheduler.admit(QueryPriority::Normal).await?;
+        let mut second = Box::pin(scheduler.admit(QueryPriority::Normal));
+        assert!(futures::poll!(&mut second).is_pending());
+
+        let err = scheduler
+            .admit(QueryPriority::High)
+            .await
+            .unwrap_err()
+            .strip_backtrace();
+        assert_eq!(
+            err,
+            "Resources exhausted: Query queue is full: 1 queries are running and 1 are queued"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn memory_threshold() -> Result<()> {
+        let pool = Arc::new(GreedyMemoryPool::new(100)) as Arc<dyn MemoryPool>;
+        let scheduler =
+            Arc::new(QueryScheduler::new(4).with_memory_threshold(pool.clone(), 50));
+
+        // admitted while no query runs, even if the pool is saturated
+        let mut reservation = MemoryConsumer::new("r").register(&pool);
+        reservation.grow(60);
+        let first = scheduler.admit(QueryPriority::Normal).await?;
+
+        let mut second = Box::pin(scheduler.admit(QueryPriority::Normal));
+        assert!(futures::poll!(&mut second).is_pending());
+
+        reservation.free();
+        drop(first);
+        let _second = second.await?;
+        assert_eq!(scheduler.running(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_priority() {
+        assert_eq!(
+            "HIGH".parse::<QueryPriority>().unwrap(),
+            QueryPriority::High
+        );
+        assert!("urgent".parse::<QueryPriority>().is_err());
+    }
+}
@@ -24,8 +24,10 @@ use datafusion_common::{plan_datafusion_err, DataFusionError, Result};
 use datafusion_expr::{AggregateUDF, ScalarUDF, WindowUDF};
 
 use crate::{
+    cancellation::CancellationToken,
     config::SessionConfig,
     memory_pool::MemoryPool,
+    progress::QueryProgress,
     registry::FunctionRegistry,
     runtime_env::{RuntimeConfig, RuntimeEnv},
 };
@@ -53,6 +55,10 @@ pub struct TaskContext {
     window_functions: HashMap<String, Arc<WindowUDF>>,
     /// Runtime environment associated with this task context
     runtime: Arc<RuntimeEnv>,
+    /// Progress of the query this task belongs to, if it is tracked
+    query_progress: Option<Arc<QueryProgress>>,
+    /// Cancels the query this task belongs to
+    cancellation_token: CancellationToken,
 }
 
 impl Default for TaskContext {
@@ -69,6 +75,8 @@ impl Default for TaskContext {
             aggregate_functions: HashMap::new(),
             window_functions: HashMap::new(),
             runtime: Arc::new(runtime),
+            query_progress: None,
+            cancellation_token: CancellationToken::new(),
         }
     }
 }
@@ -96,6 +104,8 @@ impl TaskContext {
             aggregate_functions,
             window_functions,
             runtime,
+            query_progress: None,
+            cancellation_token: CancellationToken::new(),
         }
     }
 
@@ -130,11 +140,54 @@ impl TaskContext {
         self
     }
 
+    /// Return the [`QueryProgress`] of the query this [TaskContext] belongs
+    /// to, if its progress is tracked
+    pub fn query_progress(&self) -> Option<&Arc<QueryProgress>> {
+        self.query_progress.as_ref()
+    }
+
+    /// Return the [`CancellationToken`] of the query this [TaskContext]
+    /// belongs to, which operators check while they buffer their input
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
     /// Update the [`RuntimeEnv`]
     pub fn with_runtime(mut self, runtime: Arc<RuntimeEnv>) -> Self {
         self.runtime = runtime;
         self
     }
+
+    /// Reserve the memory of the task in `memory_pool`, e.g. a
+    /// [`ScopedMemoryPool`] limiting the memory of a single query, rather than
+    /// in the pool of its [`RuntimeEnv`]
+    ///
+    /// [`ScopedMemoryPool`]: crate::memory_pool::ScopedMemoryPool
+    pub fn with_memory_pool(mut self, memory_pool: Arc<dyn MemoryPool>) -> Self {
+        self.runtime = Arc::new(RuntimeEnv {
+            memory_pool,
+            disk_manager: self.runtime.disk_manager.clone(),
+            cache_manager: self.runtime.cache_manager.clone(),
+            object_store_registry: self.runtime.object_store_registry.clone(),
+            query_scheduler: self.runtime.query_scheduler.clone(),
+        });
+        self
+    }
+
+    /// Cancel the query when `cancellation_token` is cancelled
+    pub fn with_cancellation_token(
+        mut self,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    /// Track the progress of the query in `query_progress`
+    pub fn with_query_progress(mut self, query_progress: Arc<QueryProgress>) -> Self {
+        self.query_progress = Some(query_progress);
+        self
+    }
 }
 
 impl FunctionRegistry for TaskContext {
@@ -0,0 +1,382 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`TracedObjectStore`]: emits a [`tracing`] span per object store request
+
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::object_store::ObjectStoreRegistry;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use datafusion_common::Result;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetResult, ListResult, MultipartId, ObjectMeta, ObjectStore, PutOptions,
+    PutResult,
+};
+use tokio::io::AsyncWrite;
+use tracing::field::{display, Empty};
+use tracing::{Instrument, Span};
+use url::Url;
+
+/// An [`ObjectStore`] emitting an `object_store` span for each request to
+/// the wrapped store.
+///
+/// The spans carry the `operation`, e.g. `get_range`, the `location` of the
+/// object and the `store`, and record the number of `bytes` read or written
+/// and the `error` of failed requests.
+#[derive(Debug)]
+pub struct TracedObjectStore {
+    inner: Arc<dyn ObjectStore>,
+}
+
+impl TracedObjectStore {
+    /// Trace the requests to `inner`
+    pub fn new(inner: Arc<dyn ObjectStore>) -> Self {
+        Self { inner }
+    }
+
+    /// The wrapped store
+    pub fn inner(&self) -> &Arc<dyn ObjectStore> {
+        &self.inner
+    }
+
+    fn span(&self, operation: &'static str, location: Option<&Path>) -> Span {
+        tracing::info_span!(
+            "object_store",
+            operation,
+            location = location.map(display),
+            store = %self.inner,
+            bytes = Empty,
+            error = Empty,
+        )
+    }
+}
+
+/// Run `request` in `span`, recording its error if it fails
+async fn traced<T>(
+    span: Span,
+    request: impl Future<Output = object_store::Result<T>>,
+) -> object_store::Result<T> {
+    let result = request.instrument(span.clone()).await;
+    if let Err(e) = &result {
+        span.record("error", display(e));
+    }
+    result
+}
+
+/// A stream polled in a span, recording the first error it returns
+struct TracedStream<'a, T> {
+    inner: BoxStream<'a, object_store::Result<T>>,
+    span: Span,
+}
+
+impl<'a, T> Stream for TracedStream<'a, T> {
+    type Item = object_store::Result<T>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let span = self.span.clone();
+        let _entered = span.enter();
+        let poll = self.inner.poll_next_unpin(cx);
+        if let Poll::Ready(Some(Err(e))) = &poll {
+            span.record("error", display(e));
+        }
+        poll
+    }
+}
+
+fn traced_stream<'a, T: 'a>(
+    span: Span,
+    inner: BoxStream<'a, object_store::Result<T>>,
+) -> BoxStream<'a, object_store::Result<T>> {
+    TracedStream { inner, span }.boxed()
+}
+
+impl Display for TracedObjectStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Traced({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for TracedObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        bytes: Bytes,
+        opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        let span = self.span("put", Some(location));
+        span.record("bytes", bytes.len());
+        traced(span, self.inner.put_opts(location, bytes, opts)).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> object_store::Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        let span = self.span("put_multipart", Some(location));
+        traced(span, self.inner.put_multipart(location)).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        location: &Path,
+        multipart_id: &MultipartId,
+    ) -> object_store::Result<()> {
+        let span = self.span("abort_multipart", Some(location));
+        traced(span, self.inner.abort_multipart(location, multipart_id)).await
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        let span = self.span("get", Some(location));
+        let result = traced(span.clone(), self.inner.get_opts(location, options)).await?;
+        span.record("bytes", result.range.end - result.range.start);
+        Ok(result)
+    }
+
+    async fn get_range(
+        &self,
+        location: &Path,
+        range: Range<usize>,
+    ) -> object_store::Result<Bytes> {
+        let span = self.span("get_range", Some(location));
+        span.record("bytes", range.end - range.start);
+        traced(span, self.inner.get_range(location, range)).await
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> object_store::Result<Vec<Bytes>> {
+        let span = self.span("get_ranges", Some(location));
+        span.record(
+            "bytes",
+            ranges.iter().map(|r| r.end - r.start).sum::<usize>(),
+        );
+        traced(span, self.inner.get_ranges(location, ranges)).await
+    }
+
+    async fn head(&self, location: &Path) -> object_store::Result<ObjectMeta> {
+        let span = self.span("head", Some(location));
+        traced(span, self.inner.head(location)).await
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        let span = self.span("delete", Some(location));
+        traced(span, self.inner.delete(location)).await
+    }
+
+    fn delete_stream<'a>(
+        &'a self,
+        locations: BoxStream<'a, object_store::Result<Path>>,
+    ) -> BoxStream<'a, object_store::Result<Path>> {
+        let span = self.span("delete_stream", None);
+        traced_stream(span, self.inner.delete_stream(locations))
+    }
+
+    fn list(
+        &self,
+        prefix: Option<&Path>,
+    ) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        let span = self.span("list", prefix);
+        traced_stream(span, self.inner.list(prefix))
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        let span = self.span("list_with_offset", prefix);
+        traced_stream(span, self.inner.list_with_offset(prefix, offset))
+    }
+
+    async fn list_with_delimiter(
+        &self,
+        prefix: Option<&Path>,
+    ) -> object_store::Result<ListResult> {
+        let span = self.span("list_with_delimiter", prefix);
+        traced(span, self.inner.list_with_delimiter(prefix)).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        let span = self.span("copy", Some(from));
+        traced(span, self.inner.copy(from, to)).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        let span = self.span("rename", Some(from));
+        traced(span, self.inner.rename(from, to)).await
+    }
+
+    async fn copy_if_not_exists(
+        &self,
+        from: &Path,
+        to: &Path,
+    ) -> object_store::Result<()> {
+        let span = self.span("copy_if_not_exists", Some(from));
+        traced(span, self.inner.copy_if_not_exists(from, to)).await
+    }
+
+    async fn rename_if_not_exists(
+        &self,
+        from: &Path,
+        to: &Path,
+    ) -> object_store::Result<()> {
+        let span = self.span("rename_if_not_exists", Some(from));
+        traced(span, self.inner.rename_if_not_exists(from, to)).await
+    }
+}
+
+/// An [`ObjectStoreRegistry`] returning the stores of another registry
+/// wrapped in [`TracedObjectStore`]s.
+///
+/// [`crate::runtime_env::RuntimeEnv`] wraps its registry this way when the
+/// `tracing` feature is enabled.
+#[derive(Debug)]
+pub struct TracedObjectStoreRegistry {
+    inner: Arc<dyn ObjectStoreRegistry>,
+}
+
+impl TracedObjectStoreRegistry {
+    /// Trace the requests to the stores of `inner`
+    pub fn new(inner: Arc<dyn ObjectStoreRegistry>) -> Self {
+        Self { inner }
+    }
+}
+
+impl ObjectStoreRegistry for TracedObjectStoreRegistry {
+    fn register_store(
+        &self,
+        url: &Url,
+        store: Arc<dyn ObjectStore>,
+    ) -> Option<Arc<dyn ObjectStore>> {
+        self.inner.register_store(url, store)
+    }
+
+    fn get_store(&self, url: &Url) -> Result<Arc<dyn ObjectStore>> {
+        let store = self.inner.get_store(url)?;
+        Ok(Arc::new(TracedObjectStore::new(store)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object_store::DefaultObjectStoreRegistry;
+
+    use std::sync::Mutex;
+
+    use object_store::memory::InMemory;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Records the fields of the spans created while it is the default
+    /// subscriber
+    #[derive(Default)]
+    struct Recorder {
+        spans: Mutex<Vec<Vec<(&'static str, String)>>>,
+    }
+
+    struct Fields<'a>(&'a mut Vec<(&'static str, String)>);
+
+    impl<'a> Visit for Fields<'a> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.push((field.name(), format!("{value:?}")));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut spans = self.spans.lock().unwrap();
+            let mut fields = vec![];
+            attrs.record(&mut Fields(&mut fields));
+            spans.push(fields);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, id: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            values.record(&mut Fields(&mut spans[id.into_u64() as usize - 1]));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn request_spans() -> object_store::Result<()> {
+        let registry =
+            TracedObjectStoreRegistry::new(Arc::new(DefaultObjectStoreRegistry::new()));
+        let url = Url::parse("memory://").unwrap();
+        registry.register_store(&url, Arc::new(InMemory::new()));
+        let store = registry.get_store(&url).unwrap();
+        assert_eq!(store.to_string(), "Traced(InMemory)");
+
+        let recorder = Arc::new(Recorder::default());
+        let dispatch = tracing::Dispatch::from(recorder.clone());
+        let _guard = tracing::dispatcher::set_default(&dispatch);
+
+        let location = Path::from("file");
+        store.put(&location, "0123456789".into()).await?;
+        store.get_range(&location, 2..6).await?;
+        assert!(store.head(&Path::from("missing")).await.is_err());
+
+        let spans = recorder.spans.lock().unwrap().clone();
+        let field = |span: &[(&str, String)], name: &str| {
+            span.iter()
+                .find(|(field, _)| *field == name)
+                .map(|(_, value)| value.clone())
+        };
+        assert_eq!(spans.len(), 3);
+        assert_eq!(field(&spans[0], "operation").unwrap(), "\"put\"");
+        assert_eq!(field(&spans[0], "bytes").unwrap(), "10");
+        assert_eq!(field(&spans[1], "operation").unwrap(), "\"get_range\"");
+        assert_eq!(field(&spans[1], "location").unwrap(), "file");
+        assert_eq!(field(&spans[1], "bytes").unwrap(), "4");
+        assert_eq!(field(&spans[2], "operation").unwrap(), "\"head\"");
+        assert!(field(&spans[2], "error").is_some());
+        Ok(())
+    }
+}
@@ -81,7 +81,9 @@ pub use partition_evaluator::PartitionEvaluator;
 pub use signature::{
     FuncMonotonicity, Signature, TypeSignature, Volatility, TIMEZONE_WILDCARD,
 };
-pub use table_source::{TableProviderFilterPushDown, TableSource, TableType};
+pub use table_source::{
+    TableProviderFilterPushDown, TableSource, TableType, TableVersion,
+};
 pub use udaf::{AggregateUDF, AggregateUDFImpl};
 pub use udf::{ScalarUDF, ScalarUDFImpl};
 pub use udwf::{WindowUDF, WindowUDFImpl};
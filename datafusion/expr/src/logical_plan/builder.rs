@@ -24,7 +24,7 @@ use std::convert::TryFrom;
 use std::iter::zip;
 use std::sync::Arc;
 
-use crate::dml::CopyTo;
+use crate::dml::{make_dml_schema, CopyTo};
 use crate::expr::Alias;
 use crate::expr_rewriter::{
     coerce_plan_expr_for_schema, normalize_col,
@@ -49,7 +49,7 @@ use crate::{
 
 use arrow::datatypes::{DataType, Schema, SchemaRef};
 use datafusion_common::config::FormatOptions;
-use datafusion_common::display::ToStringifiedPlan;
+use datafusion_common::display::{ExplainOptions, ToStringifiedPlan};
 use datafusion_common::{
     get_target_functional_dependencies, plan_datafusion_err, plan_err, Column, DFField,
     DFSchema, DFSchemaRef, DataFusionError, OwnedTableReference, Result, ScalarValue,
@@ -271,6 +271,9 @@ impl LogicalPlanBuilder {
             format_options,
             options,
             partition_by,
+            sort_by: vec![],
+            bucket_by: None,
+            output_schema: make_dml_schema(false),
         })))
     }
 
@@ -294,6 +297,7 @@ impl LogicalPlanBuilder {
             table_schema,
             op,
             input: Arc::new(input),
+            output_schema: make_dml_schema(false),
         })))
     }
 
@@ -963,6 +967,7 @@ impl LogicalPlanBuilder {
                 stringified_plans,
                 schema,
                 logical_optimization_succeeded: false,
+                options: ExplainOptions::default(),
             })))
         }
     }
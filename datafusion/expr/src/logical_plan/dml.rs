@@ -20,10 +20,11 @@ use std::fmt::{self, Display};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+use arrow::datatypes::{DataType, Field, Schema};
 use datafusion_common::config::FormatOptions;
-use datafusion_common::{DFSchemaRef, OwnedTableReference};
+use datafusion_common::{BucketSpec, DFSchemaRef, OwnedTableReference, ToDFSchema};
 
-use crate::LogicalPlan;
+use crate::{Expr, LogicalPlan};
 
 /// Operator that copies the contents of a database to file(s)
 #[derive(Clone)]
@@ -34,10 +35,26 @@ pub struct CopyTo {
     pub output_url: String,
     /// Determines which, if any, columns should be used for hive-style partitioned writes
     pub partition_by: Vec<String>,
+    /// Sort expressions (`Expr::Sort`) ordering the rows within each written file
+    pub sort_by: Vec<Expr>,
+    /// Determines whether rows should be clustered into a fixed number of
+    /// files by hashing a set of columns
+    pub bucket_by: Option<BucketSpec>,
     /// File format options.
     pub format_options: FormatOptions,
     /// SQL Options that can affect the formats
     pub options: HashMap<String, String>,
+    /// The schema of the output of the copy, see [`make_dml_schema`]
+    pub output_schema: DFSchemaRef,
+}
+
+impl CopyTo {
+    /// Outputs the path and number of rows of each written file, rather
+    /// than the total number of rows written, if `written_files` is true
+    pub fn with_written_files(mut self, written_files: bool) -> Self {
+        self.output_schema = make_dml_schema(written_files);
+        self
+    }
 }
 
 // Implement PartialEq manually
@@ -70,6 +87,8 @@ pub struct DmlStatement {
     pub op: WriteOp,
     /// The relation that determines the tuples to add/remove/modify the schema must match with table_schema
     pub input: Arc<LogicalPlan>,
+    /// The schema of the output of the statement, see [`make_dml_schema`]
+    pub output_schema: DFSchemaRef,
 }
 
 impl DmlStatement {
@@ -77,6 +96,27 @@ impl DmlStatement {
     pub fn name(&self) -> &str {
         self.op.name()
     }
+
+    /// Outputs the path and number of rows of each written file, rather
+    /// than the total number of rows written, if `written_files` is true
+    pub fn with_written_files(mut self, written_files: bool) -> Self {
+        self.output_schema = make_dml_schema(written_files);
+        self
+    }
+}
+
+/// Returns the schema of the output of a [`CopyTo`] or [`DmlStatement`]:
+/// the total number of rows written (`count`), or with `written_files` the
+/// `path` and `count` of each written file, as reported with the
+/// `datafusion.execution.report_written_files` option
+pub fn make_dml_schema(written_files: bool) -> DFSchemaRef {
+    let count = Field::new("count", DataType::UInt64, false);
+    let fields = match written_files {
+        true => vec![Field::new("path", DataType::Utf8, false), count],
+        false => vec![count],
+    };
+    // the schema has no duplicate names
+    Schema::new(fields).to_dfschema_ref().unwrap()
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
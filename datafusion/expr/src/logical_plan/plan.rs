@@ -53,6 +53,7 @@ use datafusion_common::{
     FunctionalDependencies, OwnedTableReference, ParamValues, Result, UnnestOptions,
 };
 
+use datafusion_common::display::ExplainOptions;
 // backwards compatibility
 pub use datafusion_common::display::{PlanType, StringifiedPlan, ToStringifiedPlan};
 pub use datafusion_common::{JoinConstraint, JoinType};
@@ -188,8 +189,8 @@ impl LogicalPlan {
             LogicalPlan::DescribeTable(DescribeTable { output_schema, .. }) => {
                 output_schema
             }
-            LogicalPlan::Dml(DmlStatement { table_schema, .. }) => table_schema,
-            LogicalPlan::Copy(CopyTo { input, .. }) => input.schema(),
+            LogicalPlan::Dml(DmlStatement { output_schema, .. }) => output_schema,
+            LogicalPlan::Copy(CopyTo { output_schema, .. }) => output_schema,
             LogicalPlan::Ddl(ddl) => ddl.schema(),
             LogicalPlan::Unnest(Unnest { schema, .. }) => schema,
             LogicalPlan::RecursiveQuery(RecursiveQuery { static_term, .. }) => {
@@ -560,12 +561,14 @@ impl LogicalPlan {
                 table_name,
                 table_schema,
                 op,
+                output_schema,
                 ..
             }) => Ok(LogicalPlan::Dml(DmlStatement {
                 table_name: table_name.clone(),
                 table_schema: table_schema.clone(),
                 op: op.clone(),
                 input: Arc::new(inputs.swap_remove(0)),
+                output_schema: output_schema.clone(),
             })),
             LogicalPlan::Copy(CopyTo {
                 input: _,
@@ -573,12 +576,18 @@ impl LogicalPlan {
                 format_options,
                 options,
                 partition_by,
+                sort_by,
+                bucket_by,
+                output_schema,
             }) => Ok(LogicalPlan::Copy(CopyTo {
                 input: Arc::new(inputs.swap_remove(0)),
                 output_url: output_url.clone(),
                 format_options: format_options.clone(),
                 options: options.clone(),
                 partition_by: partition_by.clone(),
+                sort_by: sort_by.clone(),
+                bucket_by: bucket_by.clone(),
+                output_schema: output_schema.clone(),
             })),
             LogicalPlan::Values(Values { schema, .. }) => {
                 Ok(LogicalPlan::Values(Values {
@@ -833,6 +842,7 @@ impl LogicalPlan {
                     stringified_plans: e.stringified_plans.clone(),
                     schema: e.schema.clone(),
                     logical_optimization_succeeded: e.logical_optimization_succeeded,
+                    options: e.options,
                 }))
             }
             LogicalPlan::Prepare(Prepare {
@@ -1503,6 +1513,8 @@ impl LogicalPlan {
                         output_url,
                         format_options,
                         options,
+                        sort_by,
+                        bucket_by,
                         ..
                     }) => {
                         let op_str = options
@@ -1511,7 +1523,14 @@ impl LogicalPlan {
                             .collect::<Vec<String>>()
                             .join(", ");
 
-                        write!(f, "CopyTo: format={format_options} output_url={output_url} options: ({op_str})")
+                        write!(f, "CopyTo: format={format_options} output_url={output_url} options: ({op_str})")?;
+                        if !sort_by.is_empty() {
+                            write!(f, " sort_by=[{}]", expr_vec_fmt!(sort_by))?;
+                        }
+                        if let Some(bucket_by) = bucket_by {
+                            write!(f, " buckets={bucket_by}")?;
+                        }
+                        Ok(())
                     }
                     LogicalPlan::Ddl(ddl) => {
                         write!(f, "{}", ddl.display())
@@ -2229,6 +2248,8 @@ pub struct Explain {
     pub schema: DFSchemaRef,
     /// Used by physical planner to check if should proceed with planning
     pub logical_optimization_succeeded: bool,
+    /// Options given as `EXPLAIN (FORMAT json, RULES on)`
+    pub options: ExplainOptions,
 }
 
 /// Runs the actual plan, and then prints the physical plan with
@@ -52,6 +52,17 @@ pub enum TableType {
     Temporary,
 }
 
+/// The version of a table read by a query such as
+/// `SELECT * FROM t FOR VERSION AS OF 3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableVersion {
+    /// The version with the given id (`FOR VERSION AS OF`)
+    Version(i64),
+    /// The last version created at or before the given time, in milliseconds
+    /// since the epoch (`FOR TIMESTAMP AS OF` or `FOR SYSTEM_TIME AS OF`)
+    Timestamp(i64),
+}
+
 /// The TableSource trait is used during logical query planning and optimizations and
 /// provides access to schema information and filter push-down capabilities. This trait
 /// provides a subset of the functionality of the TableProvider trait in the core
@@ -137,6 +137,9 @@ impl Analyzer {
 
         // TODO add common rule executor for Analyzer and Optimizer
         for rule in rules {
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::info_span!("analyzer_rule", rule = rule.name()).entered();
             new_plan = rule.analyze(new_plan, config).map_err(|e| {
                 DataFusionError::Context(rule.name().to_string(), Box::new(e))
             })?;
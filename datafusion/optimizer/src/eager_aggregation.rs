@@ -0,0 +1,425 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`EagerAggregation`] pushes aggregations below inner joins
+
+use std::sync::Arc;
+
+use crate::optimizer::ApplyOrder;
+use crate::{OptimizerConfig, OptimizerRule};
+
+use datafusion_common::{Column, DFSchema, Dependency, Result};
+use datafusion_expr::aggregate_function::AggregateFunction::{Count, Max, Min, Sum};
+use datafusion_expr::expr::{AggregateFunction, AggregateFunctionDefinition};
+use datafusion_expr::logical_plan::{Aggregate, Join, JoinType, LogicalPlan, Projection};
+use datafusion_expr::{coalesce, col, lit, Expr};
+
+/// Optimizer rule that computes aggregations before joining ("eager aggregation")
+///
+/// ```text
+///    Before:
+///    SELECT d.name, SUM(f.x)
+///    FROM f JOIN d ON f.k = d.k
+///    GROUP BY d.name
+///
+///    After:
+///    SELECT d.name, SUM(alias)
+///    FROM (
+///      SELECT f.k, SUM(f.x) AS alias
+///      FROM f
+///      GROUP BY f.k
+///    ) f JOIN d ON f.k = d.k
+///    GROUP BY d.name
+/// ```
+///
+/// The input of the aggregation is grouped by its join keys and the group
+/// keys it provides, which for fact-dimension joins usually shrinks the join
+/// input to a fraction of its size. The aggregation above the join combines
+/// the partial results.
+///
+/// This is only valid if every row of the aggregated side matches at most
+/// one row of the other side, which is checked with the functional
+/// dependencies of the other side: its join keys must be a unique key.
+/// Additionally:
+///
+/// * the join is an inner equijoin on columns without an additional filter
+/// * the aggregation has a GROUP BY clause of columns only
+/// * all aggregates are `SUM`, `COUNT`, `MIN` or `MAX` on the same join
+///   input, without `DISTINCT`, `FILTER` or `ORDER BY`
+///
+/// The rule is enabled by `datafusion.optimizer.eager_aggregation`.
+#[derive(Default)]
+pub struct EagerAggregation {}
+
+impl EagerAggregation {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for EagerAggregation {
+    fn try_optimize(
+        &self,
+        plan: &LogicalPlan,
+        config: &dyn OptimizerConfig,
+    ) -> Result<Option<LogicalPlan>> {
+        if !config.options().optimizer.eager_aggregation {
+            return Ok(None);
+        }
+        let LogicalPlan::Aggregate(aggregate) = plan else {
+            return Ok(None);
+        };
+        let LogicalPlan::Join(join) = aggregate.input.as_ref() else {
+            return Ok(None);
+        };
+        if join.join_type != JoinType::Inner
+            || join.filter.is_some()
+            || join.on.is_empty()
+            || aggregate.group_expr.is_empty()
+            || !aggregate
+                .group_expr
+                .iter()
+                .all(|expr| matches!(expr, Expr::Column(_)))
+            || !aggregate.aggr_expr.iter().all(is_decomposable)
+        {
+            return Ok(None);
+        }
+        let Some(join_keys) = join
+            .on
+            .iter()
+            .map(|(l, r)| Some((l.try_into_col().ok()?, r.try_into_col().ok()?)))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(None);
+        };
+        let (left_keys, right_keys): (Vec<_>, Vec<_>) = join_keys.into_iter().unzip();
+
+        // Try the right side first, as the left side is the build side of
+        // hash joins and usually the (smaller) dimension table
+        for push_left in [false, true] {
+            let (aggregated, aggregated_keys, other, other_keys) = if push_left {
+                (&join.left, &left_keys, &join.right, &right_keys)
+            } else {
+                (&join.right, &right_keys, &join.left, &left_keys)
+            };
+            if matches!(aggregated.as_ref(), LogicalPlan::Aggregate(_))
+                || !references_only(&aggregate.aggr_expr, aggregated.schema())?
+                || !is_unique_key(other.schema(), other_keys, join.null_equals_null)
+            {
+                continue;
+            }
+
+            if let Some(plan) = eager_aggregate(
+                plan,
+                aggregate,
+                join,
+                push_left,
+                aggregated_keys,
+                config,
+            )? {
+                return Ok(Some(plan));
+            }
+        }
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "eager_aggregation"
+    }
+
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        Some(ApplyOrder::TopDown)
+    }
+}
+
+/// Returns true if the partial results of `expr` can be combined by another
+/// aggregate function
+fn is_decomposable(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::AggregateFunction(AggregateFunction {
+            func_def: AggregateFunctionDefinition::BuiltIn(Sum | Count | Min | Max),
+            distinct: false,
+            filter: None,
+            order_by: None,
+            ..
+        })
+    )
+}
+
+/// Returns true if all columns referenced by `exprs` are in `schema`
+fn references_only(exprs: &[Expr], schema: &DFSchema) -> Result<bool> {
+    for expr in exprs {
+        for column in expr.to_columns()? {
+            if !schema.has_column(&column) {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Returns true if the functional dependencies of `schema` guarantee that
+/// every combination of `keys` occurs at most once
+fn is_unique_key(schema: &DFSchema, keys: &[Column], null_equals_null: bool) -> bool {
+    let Ok(key_indices) = keys
+        .iter()
+        .map(|key| schema.index_of_column(key))
+        .collect::<Result<Vec<_>>>()
+    else {
+        return false;
+    };
+    schema.functional_dependencies().iter().any(|dependency| {
+        dependency.mode == Dependency::Single
+            // NULLs may occur several times in a unique key, which matters if
+            // they are considered equal when joining
+            && !(dependency.nullable && null_equals_null)
+            && dependency
+                .source_indices
+                .iter()
+                .all(|idx| key_indices.contains(idx))
+    })
+}
+
+/// Rewrites `plan` to aggregate the left (`push_left`) or right input of
+/// `join` before joining.
+///
+/// Returns `None` if the combined aggregates would not produce the same types
+/// as the original ones, e.g. because `SUM` widens decimals.
+fn eager_aggregate(
+    plan: &LogicalPlan,
+    aggregate: &Aggregate,
+    join: &Join,
+    push_left: bool,
+    aggregated_keys: &[Column],
+    config: &dyn OptimizerConfig,
+) -> Result<Option<LogicalPlan>> {
+    let aggregated = if push_left { &join.left } else { &join.right };
+
+    // Group the aggregated side by its join keys and the group keys it provides
+    let mut inner_group_expr: Vec<Expr> = vec![];
+    for expr in aggregated_keys
+        .iter()
+        .cloned()
+        .map(Expr::Column)
+        .chain(aggregate.group_expr.iter().cloned())
+    {
+        if references_only(std::slice::from_ref(&expr), aggregated.schema())?
+            && !inner_group_expr.contains(&expr)
+        {
+            inner_group_expr.push(expr);
+        }
+    }
+
+    let mut inner_aggr_expr = vec![];
+    let mut outer_aggr_expr = vec![];
+    let mut is_count = vec![];
+    for expr in &aggregate.aggr_expr {
+        let Expr::AggregateFunction(AggregateFunction {
+            func_def: AggregateFunctionDefinition::BuiltIn(fun),
+            ..
+        }) = expr
+        else {
+            return Ok(None);
+        };
+        let alias = config.alias_generator().next("__eager_agg");
+        inner_aggr_expr.push(expr.clone().alias(&alias));
+        // Counts are combined by adding them up
+        is_count.push(*fun == Count);
+        let combine = if *fun == Count { Sum } else { fun.clone() };
+        outer_aggr_expr.push(Expr::AggregateFunction(AggregateFunction::new(
+            combine,
+            vec![col(alias)],
+            false,
+            None,
+            None,
+            None,
+        )));
+    }
+
+    let inner_aggregate = LogicalPlan::Aggregate(Aggregate::try_new(
+        aggregated.clone(),
+        inner_group_expr,
+        inner_aggr_expr,
+    )?);
+    let inputs = if push_left {
+        vec![inner_aggregate, join.right.as_ref().clone()]
+    } else {
+        vec![join.left.as_ref().clone(), inner_aggregate]
+    };
+    let join_plan = aggregate
+        .input
+        .with_new_exprs(aggregate.input.expressions(), inputs)?;
+    let outer_aggregate = Aggregate::try_new(
+        Arc::new(join_plan),
+        aggregate.group_expr.clone(),
+        outer_aggr_expr,
+    )?;
+
+    // Restore the names of the original aggregates. The sum of the partial
+    // counts is NULL for an empty group, where COUNT returns 0
+    let group_len = aggregate.group_expr.len();
+    let fields = plan.schema().fields();
+    let expr = outer_aggregate
+        .schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let expr = Expr::Column(field.qualified_column());
+            if idx < group_len {
+                expr
+            } else if is_count[idx - group_len] {
+                coalesce(vec![expr, lit(0_i64)]).alias(fields[idx].qualified_name())
+            } else {
+                expr.alias(fields[idx].qualified_name())
+            }
+        })
+        .collect();
+    let projection = LogicalPlan::Projection(Projection::try_new(
+        expr,
+        Arc::new(LogicalPlan::Aggregate(outer_aggregate)),
+    )?);
+
+    if projection
+        .schema()
+        .equivalent_names_and_types(plan.schema())
+    {
+        Ok(Some(projection))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizer::Optimizer;
+    use crate::test::*;
+    use crate::OptimizerContext;
+    use datafusion_expr::{
+        avg, count, count_distinct, logical_plan::builder::LogicalPlanBuilder, max, min,
+        sum,
+    };
+
+    fn assert_optimized_plan_equal(plan: &LogicalPlan, expected: &str) -> Result<()> {
+        let optimizer = Optimizer::with_rules(vec![Arc::new(EagerAggregation::new())]);
+        let optimized_plan = optimizer
+            .optimize_recursively(
+                optimizer.rules.first().unwrap(),
+                plan,
+                &OptimizerContext::new().with_eager_aggregation(true),
+            )?
+            .unwrap_or_else(|| plan.clone());
+        assert_eq!(optimized_plan.display_indent_schema().to_string(), expected);
+        Ok(())
+    }
+
+    /// `test2` grouped by `a`, which makes `test2.a` a unique key
+    fn dimension() -> Result<LogicalPlan> {
+        LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .aggregate(vec![col("a")], vec![max(col("b"))])?
+            .build()
+    }
+
+    #[test]
+    fn aggregate_below_join() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .join(
+                dimension()?,
+                JoinType::Inner,
+                (vec![Column::from("test.a")], vec![Column::from("test2.a")]),
+                None,
+            )?
+            .aggregate(
+                vec![
+                    Expr::Column(Column::from_name("MAX(test2.b)")),
+                    col("test.b"),
+                ],
+                vec![sum(col("test.c")), count(col("test.c")), min(col("test.c"))],
+            )?
+            .build()?;
+
+        let expected = "Projection: MAX(test2.b), test.b, SUM(__eager_agg_1) AS SUM(test.c), coalesce(SUM(__eager_agg_2), Int64(0)) AS COUNT(test.c), MIN(__eager_agg_3) AS MIN(test.c) [MAX(test2.b):UInt32;N, b:UInt32, SUM(test.c):UInt64;N, COUNT(test.c):Int64;N, MIN(test.c):UInt32;N]\
+        \n  Aggregate: groupBy=[[MAX(test2.b), test.b]], aggr=[[SUM(__eager_agg_1), SUM(__eager_agg_2), MIN(__eager_agg_3)]] [MAX(test2.b):UInt32;N, b:UInt32, SUM(__eager_agg_1):UInt64;N, SUM(__eager_agg_2):Int64;N, MIN(__eager_agg_3):UInt32;N]\
+        \n    Inner Join: test.a = test2.a [a:UInt32, b:UInt32, __eager_agg_1:UInt64;N, __eager_agg_2:Int64;N, __eager_agg_3:UInt32;N, a:UInt32, MAX(test2.b):UInt32;N]\
+        \n      Aggregate: groupBy=[[test.a, test.b]], aggr=[[SUM(test.c) AS __eager_agg_1, COUNT(test.c) AS __eager_agg_2, MIN(test.c) AS __eager_agg_3]] [a:UInt32, b:UInt32, __eager_agg_1:UInt64;N, __eager_agg_2:Int64;N, __eager_agg_3:UInt32;N]\
+        \n        TableScan: test [a:UInt32, b:UInt32, c:UInt32]\
+        \n      Aggregate: groupBy=[[test2.a]], aggr=[[MAX(test2.b)]] [a:UInt32, MAX(test2.b):UInt32;N]\
+        \n        TableScan: test2 [a:UInt32, b:UInt32, c:UInt32]";
+        assert_optimized_plan_equal(&plan, expected)
+    }
+
+    #[test]
+    fn join_key_not_unique() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .join(
+                test_table_scan_with_name("test2")?,
+                JoinType::Inner,
+                (vec![Column::from("test.a")], vec![Column::from("test2.a")]),
+                None,
+            )?
+            .aggregate(vec![col("test2.b")], vec![sum(col("test.c"))])?
+            .build()?;
+
+        let expected = "Aggregate: groupBy=[[test2.b]], aggr=[[SUM(test.c)]] [b:UInt32, SUM(test.c):UInt64;N]\
+        \n  Inner Join: test.a = test2.a [a:UInt32, b:UInt32, c:UInt32, a:UInt32, b:UInt32, c:UInt32]\
+        \n    TableScan: test [a:UInt32, b:UInt32, c:UInt32]\
+        \n    TableScan: test2 [a:UInt32, b:UInt32, c:UInt32]";
+        assert_optimized_plan_equal(&plan, expected)
+    }
+
+    #[test]
+    fn aggregate_not_decomposable() -> Result<()> {
+        for aggr_expr in [avg(col("test.c")), count_distinct(col("test.c"))] {
+            let plan = LogicalPlanBuilder::from(test_table_scan()?)
+                .join(
+                    dimension()?,
+                    JoinType::Inner,
+                    (vec![Column::from("test.a")], vec![Column::from("test2.a")]),
+                    None,
+                )?
+                .aggregate(
+                    vec![Expr::Column(Column::from_name("MAX(test2.b)"))],
+                    vec![aggr_expr],
+                )?
+                .build()?;
+            let expected = plan.display_indent_schema().to_string();
+            assert_optimized_plan_equal(&plan, &expected)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn outer_join() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .join(
+                dimension()?,
+                JoinType::Left,
+                (vec![Column::from("test.a")], vec![Column::from("test2.a")]),
+                None,
+            )?
+            .aggregate(
+                vec![Expr::Column(Column::from_name("MAX(test2.b)"))],
+                vec![sum(col("test.c"))],
+            )?
+            .build()?;
+        let expected = plan.display_indent_schema().to_string();
+        assert_optimized_plan_equal(&plan, &expected)
+    }
+}
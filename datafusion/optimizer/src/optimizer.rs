@@ -22,6 +22,7 @@ use std::sync::Arc;
 
 use crate::common_subexpr_eliminate::CommonSubexprEliminate;
 use crate::decorrelate_predicate_subquery::DecorrelatePredicateSubquery;
+use crate::eager_aggregation::EagerAggregation;
 use crate::eliminate_cross_join::EliminateCrossJoin;
 use crate::eliminate_duplicated_expr::EliminateDuplicatedExpr;
 use crate::eliminate_filter::EliminateFilter;
@@ -132,6 +133,12 @@ impl OptimizerContext {
         self
     }
 
+    /// Specify whether to enable the eager_aggregation rule
+    pub fn with_eager_aggregation(mut self, eager_aggregation: bool) -> Self {
+        self.options.optimizer.eager_aggregation = eager_aggregation;
+        self
+    }
+
     /// Specify whether the optimizer should skip rules that produce
     /// errors, or fail the query
     pub fn with_query_execution_start_time(
@@ -258,6 +265,9 @@ impl Optimizer {
             // Filters can't be pushed down past Limits, we should do PushDownFilter after PushDownLimit
             Arc::new(PushDownLimit::new()),
             Arc::new(PushDownFilter::new()),
+            // Must be after PushDownFilter, which moves filters between
+            // aggregations and joins into the join inputs
+            Arc::new(EagerAggregation::new()),
             Arc::new(SingleDistinctToGroupBy::new()),
             // The previous optimizations added expressions and projections,
             // that might benefit from the following rules
@@ -299,6 +309,10 @@ impl Optimizer {
             log_plan(&format!("Optimizer input (pass {i})"), &new_plan);
 
             for rule in &self.rules {
+                #[cfg(feature = "tracing")]
+                let _span =
+                    tracing::info_span!("optimizer_rule", rule = rule.name(), pass = i)
+                        .entered();
                 let result =
                     self.optimize_recursively(rule, &new_plan, config)
                         .and_then(|plan| {
@@ -406,7 +406,7 @@ fn extract_or_clause(expr: &Expr, schema_columns: &HashSet<Column>) -> Option<Ex
 // push down join/cross-join
 fn push_down_all_join(
     predicates: Vec<Expr>,
-    infer_predicates: Vec<Expr>,
+    infer_predicates: InferredPredicates,
     join_plan: &LogicalPlan,
     left: &LogicalPlan,
     right: &LogicalPlan,
@@ -443,16 +443,9 @@ fn push_down_all_join(
         }
     }
 
-    // For infer predicates, if they can not push through join, just drop them
-    for predicate in infer_predicates {
-        if left_preserved && can_pushdown_join_predicate(&predicate, left_schema)? {
-            left_push.push(predicate);
-        } else if right_preserved
-            && can_pushdown_join_predicate(&predicate, right_schema)?
-        {
-            right_push.push(predicate);
-        }
-    }
+    // Inferred predicates were already assigned to the side they can be pushed to
+    left_push.extend(infer_predicates.left);
+    right_push.extend(infer_predicates.right);
 
     if !on_filter.is_empty() {
         let (on_left_preserved, on_right_preserved) = on_lr_is_preserved(join_plan)?;
@@ -516,6 +509,186 @@ fn push_down_all_join(
     }
 }
 
+/// Predicates inferred from the column equalities of a join, split by the
+/// join input they are pushed down to.
+#[derive(Debug, Default)]
+struct InferredPredicates {
+    left: Vec<Expr>,
+    right: Vec<Expr>,
+}
+
+impl InferredPredicates {
+    /// Adds `predicate` to the first allowed side whose schema it can be
+    /// evaluated against, skipping predicates that are already `known`.
+    /// Predicates that can not be pushed to any allowed side are dropped.
+    fn push(
+        &mut self,
+        predicate: Expr,
+        (to_left, to_right): (bool, bool),
+        join: &Join,
+        known: &[Expr],
+    ) -> Result<()> {
+        if known.contains(&predicate)
+            || self.left.contains(&predicate)
+            || self.right.contains(&predicate)
+        {
+            return Ok(());
+        }
+        if to_left && can_pushdown_join_predicate(&predicate, join.left.schema())? {
+            self.left.push(predicate);
+        } else if to_right
+            && can_pushdown_join_predicate(&predicate, join.right.schema())?
+        {
+            self.right.push(predicate);
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty()
+    }
+}
+
+/// Classes of columns that are known to be equal in the rows of a join.
+///
+/// The classes contain the equi-join keys of the join itself, as well as the
+/// equalities that hold in its inputs: the keys of nested inner joins and the
+/// `col = col` predicates of filters below it.
+#[derive(Debug, Default)]
+struct JoinEquivalences {
+    /// Direct mapping between the equi-join keys of the join
+    join_keys: Vec<(Column, Column)>,
+    classes: Vec<Vec<Column>>,
+}
+
+impl JoinEquivalences {
+    fn new(join: &Join) -> Self {
+        let mut equivalences = Self::default();
+        // Only allow both side key is column.
+        for (l, r) in &join.on {
+            if let (Ok(l), Ok(r)) = (l.try_into_col(), r.try_into_col()) {
+                equivalences.add_equal(l.clone(), r.clone());
+                equivalences.join_keys.push((l, r));
+            }
+        }
+        equivalences.add_input(&join.left);
+        equivalences.add_input(&join.right);
+        equivalences
+    }
+
+    /// Collects the column equalities that hold for every row of `plan`
+    fn add_input(&mut self, plan: &LogicalPlan) {
+        match plan {
+            LogicalPlan::Join(join) if join.join_type == JoinType::Inner => {
+                for (l, r) in &join.on {
+                    if let (Ok(l), Ok(r)) = (l.try_into_col(), r.try_into_col()) {
+                        self.add_equal(l, r);
+                    }
+                }
+                if let Some(filter) = &join.filter {
+                    self.add_predicate(filter);
+                }
+                self.add_input(&join.left);
+                self.add_input(&join.right);
+            }
+            LogicalPlan::CrossJoin(cross_join) => {
+                self.add_input(&cross_join.left);
+                self.add_input(&cross_join.right);
+            }
+            LogicalPlan::Filter(filter) => {
+                self.add_predicate(&filter.predicate);
+                self.add_input(&filter.input);
+            }
+            _ => {}
+        }
+    }
+
+    fn add_predicate(&mut self, predicate: &Expr) {
+        for expr in split_conjunction(predicate) {
+            if let Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Eq,
+                right,
+            }) = expr
+            {
+                if let (Expr::Column(l), Expr::Column(r)) =
+                    (left.as_ref(), right.as_ref())
+                {
+                    self.add_equal(l.clone(), r.clone());
+                }
+            }
+        }
+    }
+
+    fn add_equal(&mut self, l: Column, r: Column) {
+        let l_idx = self.classes.iter().position(|class| class.contains(&l));
+        let r_idx = self.classes.iter().position(|class| class.contains(&r));
+        match (l_idx, r_idx) {
+            (Some(l_idx), Some(r_idx)) if l_idx == r_idx => {}
+            (Some(l_idx), Some(r_idx)) => {
+                let merged = self.classes.swap_remove(l_idx.max(r_idx));
+                self.classes[l_idx.min(r_idx)].extend(merged);
+            }
+            (Some(idx), None) => self.classes[idx].push(r),
+            (None, Some(idx)) => self.classes[idx].push(l),
+            (None, None) => self.classes.push(vec![l, r]),
+        }
+    }
+
+    fn class_of(&self, column: &Column) -> Option<&[Column]> {
+        self.classes
+            .iter()
+            .find(|class| class.contains(column))
+            .map(|class| class.as_slice())
+    }
+
+    /// Returns the predicates implied by `predicate` for the other columns of
+    /// its equivalence classes.
+    ///
+    /// Predicates on a single column are rewritten for every column equal to it.
+    /// For inner joins, predicates on several columns are rewritten by replacing
+    /// the equi-join keys with the key on the other side of the join.
+    fn infer(&self, predicate: &Expr, is_inner_join: bool) -> Result<Vec<Expr>> {
+        if is_volatile_expression(predicate)? {
+            return Ok(vec![]);
+        }
+        let columns = predicate.to_columns()?;
+        if columns.len() == 1 {
+            let column = columns.iter().next().unwrap();
+            let Some(class) = self.class_of(column) else {
+                return Ok(vec![]);
+            };
+            return class
+                .iter()
+                .filter(|other| *other != column)
+                .map(|other| {
+                    replace_col(predicate.clone(), &HashMap::from([(column, other)]))
+                })
+                .collect();
+        }
+        if !is_inner_join {
+            return Ok(vec![]);
+        }
+
+        let mut join_cols_to_replace = HashMap::new();
+        for col in columns.iter() {
+            for (l, r) in self.join_keys.iter() {
+                if col == l {
+                    join_cols_to_replace.insert(col, r);
+                    break;
+                } else if col == r {
+                    join_cols_to_replace.insert(col, l);
+                    break;
+                }
+            }
+        }
+        if join_cols_to_replace.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![replace_col(predicate.clone(), &join_cols_to_replace)?])
+    }
+}
+
 fn push_down_join(
     plan: &LogicalPlan,
     join: &Join,
@@ -533,72 +706,67 @@ fn push_down_join(
         .map(|e| split_conjunction_owned(e.clone()))
         .unwrap_or_default();
 
-    let mut is_inner_join = false;
-    let infer_predicates = if join.join_type == JoinType::Inner {
-        is_inner_join = true;
-        // Only allow both side key is column.
-        let join_col_keys = join
-            .on
-            .iter()
-            .flat_map(|(l, r)| match (l.try_into_col(), r.try_into_col()) {
-                (Ok(l_col), Ok(r_col)) => Some((l_col, r_col)),
-                _ => None,
-            })
-            .collect::<Vec<_>>();
-        // TODO refine the logic, introduce EquivalenceProperties to logical plan and infer additional filters to push down
-        // For inner joins, duplicate filters for joined columns so filters can be pushed down
-        // to both sides. Take the following query as an example:
-        //
-        // ```sql
-        // SELECT * FROM t1 JOIN t2 on t1.id = t2.uid WHERE t1.id > 1
-        // ```
-        //
-        // `t1.id > 1` predicate needs to be pushed down to t1 table scan, while
-        // `t2.uid > 1` predicate needs to be pushed down to t2 table scan.
-        //
-        // Join clauses with `Using` constraints also take advantage of this logic to make sure
-        // predicates reference the shared join columns are pushed to both sides.
-        // This logic should also been applied to conditions in JOIN ON clause
-        predicates
-            .iter()
-            .chain(on_filters.iter())
-            .filter_map(|predicate| {
-                let mut join_cols_to_replace = HashMap::new();
-                let columns = match predicate.to_columns() {
-                    Ok(columns) => columns,
-                    Err(e) => return Some(Err(e)),
-                };
+    let is_inner_join = join.join_type == JoinType::Inner;
 
-                for col in columns.iter() {
-                    for (l, r) in join_col_keys.iter() {
-                        if col == l {
-                            join_cols_to_replace.insert(col, r);
-                            break;
-                        } else if col == r {
-                            join_cols_to_replace.insert(col, l);
-                            break;
-                        }
-                    }
-                }
-
-                if join_cols_to_replace.is_empty() {
-                    return None;
-                }
-
-                let join_side_predicate =
-                    match replace_col(predicate.clone(), &join_cols_to_replace) {
-                        Ok(p) => p,
-                        Err(e) => {
-                            return Some(Err(e));
-                        }
-                    };
-
-                Some(Ok(join_side_predicate))
-            })
-            .collect::<Result<Vec<_>>>()?
-    } else {
-        vec![]
+    // Filters on columns that are known to be equal to other columns can be
+    // duplicated for those columns, so they can be pushed down to more inputs.
+    // Take the following query as an example:
+    //
+    // ```sql
+    // SELECT * FROM t1 JOIN t2 on t1.id = t2.uid WHERE t1.id > 1
+    // ```
+    //
+    // `t1.id > 1` predicate needs to be pushed down to t1 table scan, while
+    // `t2.uid > 1` predicate needs to be pushed down to t2 table scan.
+    //
+    // Join clauses with `Using` constraints also take advantage of this logic to make sure
+    // predicates reference the shared join columns are pushed to both sides.
+    //
+    // The equalities are collected transitively, including the keys of inner joins
+    // below this join, so `t1.id > 1` also reaches `t3` in
+    // `t1 JOIN t2 ON t1.id = t2.uid JOIN t3 ON t2.uid = t3.uid`.
+    //
+    // For outer joins, a filter on the preserved side can only be transferred to
+    // the non-preserved side: rows of the non-preserved side that do not satisfy
+    // it can only join rows that are filtered out anyway.
+    let equivalences = JoinEquivalences::new(join);
+    let (where_to_left, where_to_right) = match lr_is_preserved(plan)? {
+        (true, true) => (true, true),
+        (true, false) => (false, true),
+        (false, true) => (true, false),
+        (false, false) => (false, false),
     };
+    let (on_to_left, on_to_right) = on_lr_is_preserved(plan)?;
+
+    let mut infer_predicates = InferredPredicates::default();
+    for predicate in &predicates {
+        // For outer joins, only filters on the preserved side can be transferred
+        let transferable = is_inner_join
+            || (where_to_right
+                && can_pushdown_join_predicate(predicate, join.left.schema())?)
+            || (where_to_left
+                && can_pushdown_join_predicate(predicate, join.right.schema())?);
+        if transferable {
+            for inferred in equivalences.infer(predicate, is_inner_join)? {
+                infer_predicates.push(
+                    inferred,
+                    (where_to_left, where_to_right),
+                    join,
+                    &predicates,
+                )?;
+            }
+        }
+    }
+    for predicate in &on_filters {
+        for inferred in equivalences.infer(predicate, is_inner_join)? {
+            infer_predicates.push(
+                inferred,
+                (on_to_left, on_to_right),
+                join,
+                &on_filters,
+            )?;
+        }
+    }
 
     if on_filters.is_empty() && predicates.is_empty() && infer_predicates.is_empty() {
         return Ok(None);
@@ -845,7 +1013,7 @@ impl OptimizerRule for PushDownFilter {
                 let right = inputs[1];
                 let plan = push_down_all_join(
                     predicates,
-                    vec![],
+                    InferredPredicates::default(),
                     &join_plan,
                     left,
                     right,
@@ -1916,8 +2084,8 @@ mod tests {
         assert_optimized_plan_eq(&plan, expected)
     }
 
-    /// post-left-join predicate on a column common to both sides is only pushed to the left side
-    /// i.e. - not duplicated to the right side
+    /// post-left-join predicate on a column common to both sides is pushed to the left side
+    /// and transferred to the right side, which can only match rows the filter removes
     #[test]
     fn filter_using_left_join_on_common() -> Result<()> {
         let table_scan = test_table_scan()?;
@@ -1945,17 +2113,17 @@ mod tests {
             \n      TableScan: test2"
         );
 
-        // filter sent to left side of the join, not the right
+        // filter sent to left side of the join, and inferred for the right
         let expected = "\
         Left Join: Using test.a = test2.a\
         \n  TableScan: test, full_filters=[test.a <= Int64(1)]\
         \n  Projection: test2.a\
-        \n    TableScan: test2";
+        \n    TableScan: test2, full_filters=[test2.a <= Int64(1)]";
         assert_optimized_plan_eq(&plan, expected)
     }
 
-    /// post-right-join predicate on a column common to both sides is only pushed to the right side
-    /// i.e. - not duplicated to the left side.
+    /// post-right-join predicate on a column common to both sides is pushed to the right side
+    /// and transferred to the left side, which can only match rows the filter removes
     #[test]
     fn filter_using_right_join_on_common() -> Result<()> {
         let table_scan = test_table_scan()?;
@@ -1983,10 +2151,10 @@ mod tests {
             \n      TableScan: test2"
         );
 
-        // filter sent to right side of join, not duplicated to the left
+        // filter sent to right side of join, and inferred for the left
         let expected = "\
         Right Join: Using test.a = test2.a\
-        \n  TableScan: test\
+        \n  TableScan: test, full_filters=[test.a <= Int64(1)]\
         \n  Projection: test2.a\
         \n    TableScan: test2, full_filters=[test2.a <= Int64(1)]";
         assert_optimized_plan_eq(&plan, expected)
@@ -2156,7 +2324,82 @@ mod tests {
         \n  Projection: test.a, test.b, test.c\
         \n    TableScan: test\
         \n  Projection: test2.a, test2.b, test2.c\
-        \n    TableScan: test2, full_filters=[test2.c > UInt32(4)]";
+        \n    TableScan: test2, full_filters=[test2.a > UInt32(1), test2.c > UInt32(4)]";
+        assert_optimized_plan_eq(&plan, expected)
+    }
+
+    /// post-join predicates are transferred through the keys of nested inner joins
+    #[test]
+    fn filter_transitive_join_keys() -> Result<()> {
+        let t1 = test_table_scan()?;
+        let t2 = test_table_scan_with_name("test2")?;
+        let t3 = test_table_scan_with_name("test3")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(
+                t2,
+                JoinType::Inner,
+                (vec![Column::from("test.a")], vec![Column::from("test2.b")]),
+                None,
+            )?
+            .join(
+                t3,
+                JoinType::Inner,
+                (vec![Column::from("test2.b")], vec![Column::from("test3.c")]),
+                None,
+            )?
+            .filter(col("test.a").gt(lit(1u32)))?
+            .build()?;
+
+        // not part of the test, just good to know:
+        assert_eq!(
+            format!("{plan:?}"),
+            "Filter: test.a > UInt32(1)\
+            \n  Inner Join: test2.b = test3.c\
+            \n    Inner Join: test.a = test2.b\
+            \n      TableScan: test\
+            \n      TableScan: test2\
+            \n    TableScan: test3"
+        );
+
+        // filter reaches test3 although it is not joined with test directly
+        let expected = "\
+        Inner Join: test2.b = test3.c\
+        \n  Inner Join: test.a = test2.b\
+        \n    TableScan: test, full_filters=[test.a > UInt32(1)]\
+        \n    TableScan: test2, full_filters=[test2.b > UInt32(1)]\
+        \n  TableScan: test3, full_filters=[test3.c > UInt32(1)]";
+        assert_optimized_plan_eq(&plan, expected)
+    }
+
+    /// post-left-join predicates on the non-preserved side are not transferred
+    #[test]
+    fn filter_left_join_not_transferred_from_right() -> Result<()> {
+        let left = test_table_scan()?;
+        let right = test_table_scan_with_name("test2")?;
+        let plan = LogicalPlanBuilder::from(left)
+            .join(
+                right,
+                JoinType::Left,
+                (vec![Column::from("test.a")], vec![Column::from("test2.a")]),
+                None,
+            )?
+            .filter(col("test2.a").gt(lit(1u32)))?
+            .build()?;
+
+        // not part of the test, just good to know:
+        assert_eq!(
+            format!("{plan:?}"),
+            "Filter: test2.a > UInt32(1)\
+            \n  Left Join: test.a = test2.a\
+            \n    TableScan: test\
+            \n    TableScan: test2"
+        );
+
+        let expected = "\
+        Filter: test2.a > UInt32(1)\
+        \n  Left Join: test.a = test2.a\
+        \n    TableScan: test\
+        \n    TableScan: test2";
         assert_optimized_plan_eq(&plan, expected)
     }
 
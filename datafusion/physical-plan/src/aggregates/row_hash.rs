@@ -27,9 +27,9 @@ use crate::aggregates::{
     evaluate_group_by, evaluate_many, evaluate_optional, group_schema, AggregateMode,
     PhysicalGroupBy,
 };
-use crate::common::IPCWriter;
-use crate::metrics::{BaselineMetrics, RecordOutput};
-use crate::sorts::sort::{read_spill_as_stream, sort_batch};
+use crate::common::SpillCompression;
+use crate::metrics::{BaselineMetrics, Count, MetricBuilder, RecordOutput};
+use crate::sorts::sort::{read_spill_as_stream, sort_batch, write_spill};
 use crate::sorts::streaming_merge;
 use crate::stream::RecordBatchStreamAdapter;
 use crate::{aggregates, ExecutionPlan, PhysicalExpr};
@@ -41,7 +41,9 @@ use arrow_schema::SortOptions;
 use datafusion_common::{DataFusionError, Result};
 use datafusion_execution::disk_manager::RefCountedTempFile;
 use datafusion_execution::memory_pool::proxy::VecAllocExt;
-use datafusion_execution::memory_pool::{MemoryConsumer, MemoryReservation};
+use datafusion_execution::memory_pool::{
+    MemoryConsumer, MemoryReservation, SpillRequest,
+};
 use datafusion_execution::runtime_env::RuntimeEnv;
 use datafusion_execution::TaskContext;
 use datafusion_expr::{EmitTo, GroupsAccumulator};
@@ -87,6 +89,21 @@ struct SpillState {
 
     /// GROUP BY expressions for merging spilled data
     merging_group_by: PhysicalGroupBy,
+
+    /// The compression of the spill files
+    spill_compression: SpillCompression,
+
+    /// count of spills during the execution of the operator
+    spill_count: Count,
+
+    /// total spilled bytes during the execution of the operator
+    spilled_bytes: Count,
+
+    /// total size of the spill files, after compression
+    spilled_disk_bytes: Count,
+
+    /// Memory the pool asked this stream to release for other consumers
+    spill_request: Arc<SpillRequest>,
 }
 
 /// HashTable based Grouping Aggregator
@@ -336,10 +353,6 @@ impl GroupedHashAggregateStream {
             })
             .collect();
 
-        let name = format!("GroupedHashAggregateStream[{partition}]");
-        let reservation = MemoryConsumer::new(name)
-            .with_can_spill(true)
-            .register(context.memory_pool());
         let (ordering, _) = agg
             .properties()
             .equivalence_properties()
@@ -350,6 +363,18 @@ impl GroupedHashAggregateStream {
             ordering.as_slice(),
         )?;
 
+        let name = format!("GroupedHashAggregateStream[{partition}]");
+        let spill_request = Arc::new(SpillRequest::new());
+        let mut consumer = MemoryConsumer::new(name).with_can_spill(true);
+        // Only streams that can spill are asked to, see `spill_previous_if_necessary`
+        if matches!(group_ordering, GroupOrdering::None)
+            && !matches!(agg.mode, AggregateMode::Partial)
+            && context.runtime_env().disk_manager.tmp_files_enabled()
+        {
+            consumer = consumer.with_spill_callback(spill_request.clone());
+        }
+        let reservation = consumer.register(context.memory_pool());
+
         let group_values = new_group_values(group_schema)?;
         timer.done();
 
@@ -362,6 +387,17 @@ impl GroupedHashAggregateStream {
             is_stream_merging: false,
             merging_aggregate_arguments,
             merging_group_by: PhysicalGroupBy::new_single(agg_group_by.expr.clone()),
+            spill_compression: context
+                .session_config()
+                .options()
+                .execution
+                .spill_compression
+                .parse()?,
+            spill_count: MetricBuilder::new(&agg.metrics).spill_count(partition),
+            spilled_bytes: MetricBuilder::new(&agg.metrics).spilled_bytes(partition),
+            spilled_disk_bytes: MetricBuilder::new(&agg.metrics)
+                .spilled_disk_bytes(partition),
+            spill_request,
         };
 
         Ok(GroupedHashAggregateStream {
@@ -663,15 +699,17 @@ impl GroupedHashAggregateStream {
 
     /// Optimistically, [`Self::group_aggregate_batch`] allows to exceed the memory target slightly
     /// (~ 1 [`RecordBatch`]) for simplicity. In such cases, spill the data to disk and clear the
-    /// memory. Currently only [`GroupOrdering::None`] is supported for spilling.
+    /// memory. Also spill when the memory pool asked this stream to release memory for other
+    /// consumers. Currently only [`GroupOrdering::None`] is supported for spilling.
     fn spill_previous_if_necessary(&mut self, batch: &RecordBatch) -> Result<()> {
+        let spill_requested = self.spill_state.spill_request.take() > 0;
         // TODO: support group_ordering for spilling
         if self.group_values.len() > 0
             && batch.num_rows() > 0
             && matches!(self.group_ordering, GroupOrdering::None)
             && !matches!(self.mode, AggregateMode::Partial)
             && !self.spill_state.is_stream_merging
-            && self.update_memory_reservation().is_err()
+            && (spill_requested || self.update_memory_reservation().is_err())
         {
             // Use input batch (Partial mode) schema for spilling because
             // the spilled data will be merged and re-evaluated later.
@@ -686,20 +724,28 @@ impl GroupedHashAggregateStream {
     fn spill(&mut self) -> Result<()> {
         let emit = self.emit(EmitTo::All, true)?;
         let sorted = sort_batch(&emit, &self.spill_state.spill_expr, None)?;
-        let spillfile = self.runtime.disk_manager.create_tmp_file("HashAggSpill")?;
-        let mut writer = IPCWriter::new(spillfile.path(), &emit.schema())?;
+        let mut spillfile = self.runtime.disk_manager.create_tmp_file("HashAggSpill")?;
         // TODO: slice large `sorted` and write to multiple files in parallel
-        let mut offset = 0;
-        let total_rows = sorted.num_rows();
-
-        while offset < total_rows {
-            let length = std::cmp::min(total_rows - offset, self.batch_size);
-            let batch = sorted.slice(offset, length);
-            offset += batch.num_rows();
-            writer.write(&batch)?;
-        }
+        let batches = (0..sorted.num_rows())
+            .step_by(self.batch_size)
+            .map(|offset| {
+                let length = std::cmp::min(sorted.num_rows() - offset, self.batch_size);
+                sorted.slice(offset, length)
+            });
+        write_spill(
+            batches,
+            &mut spillfile,
+            &emit.schema(),
+            self.spill_state.spill_compression,
+        )?;
 
-        writer.finish()?;
+        self.spill_state.spill_count.add(1);
+        self.spill_state
+            .spilled_bytes
+            .add(sorted.get_array_memory_size());
+        self.spill_state
+            .spilled_disk_bytes
+            .add(spillfile.current_disk_usage() as usize);
         self.spill_state.spills.push(spillfile);
         Ok(())
     }
@@ -20,6 +20,7 @@
 use std::fs;
 use std::fs::{metadata, File};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 
 use super::{ExecutionPlanProperties, SendableRecordBatchStream};
@@ -28,6 +29,7 @@ use crate::{ColumnStatistics, ExecutionPlan, Statistics};
 
 use arrow::datatypes::Schema;
 use arrow::ipc::writer::{FileWriter, IpcWriteOptions};
+use arrow::ipc::CompressionType;
 use arrow::record_batch::RecordBatch;
 use arrow_array::Array;
 use datafusion_common::stats::Precision;
@@ -323,6 +325,46 @@ impl IPCWriter {
     }
 }
 
+/// The compression of the Arrow IPC spill files written by operators, set by
+/// the `datafusion.execution.spill_compression` option
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpillCompression {
+    /// Spill files are not compressed
+    #[default]
+    Uncompressed,
+    /// LZ4 frame compression of the IPC buffers, fast with a moderate ratio
+    Lz4Frame,
+    /// ZSTD compression of the IPC buffers, slower with a better ratio
+    Zstd,
+}
+
+impl SpillCompression {
+    /// Returns the options of an IPC writer using this compression
+    pub fn write_options(&self) -> Result<IpcWriteOptions> {
+        let compression = match self {
+            Self::Uncompressed => None,
+            Self::Lz4Frame => Some(CompressionType::LZ4_FRAME),
+            Self::Zstd => Some(CompressionType::ZSTD),
+        };
+        Ok(IpcWriteOptions::default().try_with_compression(compression)?)
+    }
+}
+
+impl FromStr for SpillCompression {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "uncompressed" => Ok(Self::Uncompressed),
+            "lz4_frame" => Ok(Self::Lz4Frame),
+            "zstd" => Ok(Self::Zstd),
+            _ => plan_err!(
+                "Unsupported spill compression '{s}', expected one of 'uncompressed', 'lz4_frame' or 'zstd'"
+            ),
+        }
+    }
+}
+
 /// Checks if the given projection is valid for the given schema.
 pub fn can_project(
     schema: &arrow_schema::SchemaRef,
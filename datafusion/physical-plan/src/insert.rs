@@ -31,9 +31,9 @@ use crate::stream::RecordBatchStreamAdapter;
 
 use arrow::datatypes::SchemaRef;
 use arrow::record_batch::RecordBatch;
-use arrow_array::{ArrayRef, UInt64Array};
+use arrow_array::{ArrayRef, StringArray, UInt64Array};
 use arrow_schema::{DataType, Field, Schema};
-use datafusion_common::{exec_err, internal_err, Result};
+use datafusion_common::{exec_err, internal_err, not_impl_err, Result};
 use datafusion_execution::TaskContext;
 use datafusion_physical_expr::{
     Distribution, EquivalenceProperties, PhysicalSortRequirement,
@@ -72,11 +72,46 @@ pub trait DataSink: DisplayAs + Debug + Send + Sync {
         data: SendableRecordBatchStream,
         context: &Arc<TaskContext>,
     ) -> Result<u64>;
+
+    /// Writes the data to the sink, returns the files that were written
+    /// along with the number of rows in each of them
+    ///
+    /// This is used instead of [`Self::write_all`] when the
+    /// [`FileSinkExec`] reports the written files (see
+    /// [`FileSinkExec::with_written_files`]). Sinks which do not write
+    /// files return an error.
+    async fn write_files(
+        &self,
+        _data: SendableRecordBatchStream,
+        _context: &Arc<TaskContext>,
+    ) -> Result<Vec<WrittenFile>> {
+        not_impl_err!("{self:?} does not report written files")
+    }
+}
+
+/// A file written by a [`DataSink`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrittenFile {
+    /// Location of the file in its object store
+    pub path: String,
+    /// Number of rows written to the file
+    pub row_count: u64,
+}
+
+impl WrittenFile {
+    /// Create a new [`WrittenFile`]
+    pub fn new(path: impl Into<String>, row_count: u64) -> Self {
+        Self {
+            path: path.into(),
+            row_count,
+        }
+    }
 }
 
 /// Execution plan for writing record batches to a [`DataSink`]
 ///
-/// Returns a single row with the number of values written
+/// Returns a single row with the number of values written, or one row
+/// per written file if created [`with_written_files`](Self::with_written_files)
 pub struct FileSinkExec {
     /// Input plan that produces the record batches to be written.
     input: Arc<dyn ExecutionPlan>,
@@ -88,6 +123,8 @@ pub struct FileSinkExec {
     count_schema: SchemaRef,
     /// Optional required sort order for output data.
     sort_order: Option<Vec<PhysicalSortRequirement>>,
+    /// Whether to output the written files rather than the total count
+    written_files: bool,
     cache: PlanProperties,
 }
 
@@ -106,17 +143,33 @@ impl FileSinkExec {
         sort_order: Option<Vec<PhysicalSortRequirement>>,
     ) -> Self {
         let count_schema = make_count_schema();
-        let cache = Self::create_schema(&input, count_schema);
+        let cache = Self::create_schema(&input, count_schema.clone());
         Self {
             input,
             sink,
             sink_schema,
-            count_schema: make_count_schema(),
+            count_schema,
             sort_order,
+            written_files: false,
             cache,
         }
     }
 
+    /// Output one row with the `path` and row `count` of each file
+    /// written by the sink (see [`DataSink::write_files`]) instead of a
+    /// single row with the total count
+    pub fn with_written_files(mut self, written_files: bool) -> Self {
+        let count_schema = if written_files {
+            make_written_files_schema()
+        } else {
+            make_count_schema()
+        };
+        self.cache = Self::create_schema(&self.input, count_schema.clone());
+        self.count_schema = count_schema;
+        self.written_files = written_files;
+        self
+    }
+
     fn execute_input_stream(
         &self,
         partition: usize,
@@ -172,6 +225,11 @@ impl FileSinkExec {
         &self.sort_order
     }
 
+    /// Whether the written files are output rather than the total count
+    pub fn written_files(&self) -> bool {
+        self.written_files
+    }
+
     /// Returns the metrics of the underlying [DataSink]
     pub fn metrics(&self) -> Option<MetricsSet> {
         self.sink.metrics()
@@ -249,12 +307,15 @@ impl ExecutionPlan for FileSinkExec {
         self: Arc<Self>,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        Ok(Arc::new(Self::new(
-            children[0].clone(),
-            self.sink.clone(),
-            self.sink_schema.clone(),
-            self.sort_order.clone(),
-        )))
+        Ok(Arc::new(
+            Self::new(
+                children[0].clone(),
+                self.sink.clone(),
+                self.sink_schema.clone(),
+                self.sort_order.clone(),
+            )
+            .with_written_files(self.written_files),
+        ))
     }
 
     /// Execute the plan and return a stream of `RecordBatch`es for
@@ -272,10 +333,19 @@ impl ExecutionPlan for FileSinkExec {
         let count_schema = self.count_schema.clone();
         let sink = self.sink.clone();
 
-        let stream = futures::stream::once(async move {
-            sink.write_all(data, &context).await.map(make_count_batch)
-        })
-        .boxed();
+        let stream = if self.written_files {
+            futures::stream::once(async move {
+                sink.write_files(data, &context)
+                    .await
+                    .map(make_written_files_batch)
+            })
+            .boxed()
+        } else {
+            futures::stream::once(async move {
+                sink.write_all(data, &context).await.map(make_count_batch)
+            })
+            .boxed()
+        };
 
         Ok(Box::pin(RecordBatchStreamAdapter::new(
             count_schema,
@@ -308,6 +378,33 @@ fn make_count_schema() -> SchemaRef {
     )]))
 }
 
+/// Create a output record batch with the path and row count of each
+/// written file
+///
+/// ```text
+/// +------------------------+-------+,
+/// | path                   | count |,
+/// +------------------------+-------+,
+/// | out/a=1/6cDC8a.parquet | 4     |,
+/// | out/a=2/6cDC8a.parquet | 2     |,
+/// +------------------------+-------+,
+/// ```
+fn make_written_files_batch(files: Vec<WrittenFile>) -> RecordBatch {
+    let paths = files.iter().map(|f| Some(f.path.as_str()));
+    let paths = Arc::new(StringArray::from_iter(paths)) as ArrayRef;
+    let counts = files.iter().map(|f| f.row_count);
+    let counts = Arc::new(UInt64Array::from_iter_values(counts)) as ArrayRef;
+
+    RecordBatch::try_new(make_written_files_schema(), vec![paths, counts]).unwrap()
+}
+
+fn make_written_files_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new("count", DataType::UInt64, false),
+    ]))
+}
+
 fn check_not_null_contraits(
     batch: RecordBatch,
     column_indices: &Vec<usize>,
@@ -0,0 +1,362 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [`InstrumentedExec`] emits a [`tracing`] span per partition stream of an
+//! [`ExecutionPlan`]
+
+use std::any::Any;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::metrics::{MetricValue, MetricsSet};
+use crate::progress::operator_name;
+use crate::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, PlanProperties,
+    RecordBatchStream, SendableRecordBatchStream,
+};
+
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use datafusion_common::config::ConfigOptions;
+use datafusion_common::tree_node::{Transformed, TransformedResult, TreeNode};
+use datafusion_common::{Result, Statistics};
+use datafusion_execution::TaskContext;
+use datafusion_physical_expr::PhysicalSortRequirement;
+use futures::{Stream, StreamExt};
+use tracing::field::{display, Empty};
+use tracing::Span;
+
+/// Wraps an [`ExecutionPlan`] to run each of its partition streams in an
+/// `execute` span.
+///
+/// The spans carry the `operator` name and `partition`, and record the
+/// `output_rows`, `elapsed_compute` nanoseconds, `spill_count` and
+/// `spilled_bytes` metrics of the partition once its stream is dropped. The
+/// streams of the children of the plan are polled within the span of their
+/// parent, so the spans form the tree of the plan.
+///
+/// The wrapper is transparent: it is displayed as the wrapped plan, and
+/// [`ExecutionPlan::as_any`] returns the wrapped plan so that downcasting an
+/// instrumented plan keeps working.
+#[derive(Debug)]
+pub struct InstrumentedExec {
+    inner: Arc<dyn ExecutionPlan>,
+}
+
+impl InstrumentedExec {
+    /// Instrument the partition streams of `inner`
+    pub fn new(inner: Arc<dyn ExecutionPlan>) -> Self {
+        Self { inner }
+    }
+
+    /// The wrapped plan
+    pub fn inner(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.inner
+    }
+}
+
+/// Wrap every node of `plan` in an [`InstrumentedExec`]
+pub fn instrument_plan(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    plan.transform_up(&|plan| {
+        Ok(Transformed::yes(
+            Arc::new(InstrumentedExec::new(plan)) as Arc<dyn ExecutionPlan>
+        ))
+    })
+    .data()
+}
+
+impl DisplayAs for InstrumentedExec {
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        self.inner.fmt_as(t, f)
+    }
+}
+
+impl ExecutionPlan for InstrumentedExec {
+    fn as_any(&self) -> &dyn Any {
+        self.inner.as_any()
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        self.inner.properties()
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        self.inner.required_input_distribution()
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<Vec<PhysicalSortRequirement>>> {
+        self.inner.required_input_ordering()
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        self.inner.maintains_input_order()
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        self.inner.benefits_from_input_partitioning()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        self.inner.children()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let inner = self.inner.clone().with_new_children(children)?;
+        Ok(Arc::new(Self::new(inner)))
+    }
+
+    fn repartitioned(
+        &self,
+        target_partitions: usize,
+        config: &ConfigOptions,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let inner = self.inner.repartitioned(target_partitions, config)?;
+        Ok(inner.map(|inner| Arc::new(Self::new(inner)) as _))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let span = tracing::info_span!(
+            "execute",
+            operator = operator_name(self.inner.as_ref()),
+            partition,
+            output_rows = Empty,
+            elapsed_compute = Empty,
+            spill_count = Empty,
+            spilled_bytes = Empty,
+            error = Empty,
+        );
+        let input = span.in_scope(|| self.inner.execute(partition, context))?;
+        Ok(Box::pin(InstrumentedStream {
+            input,
+            plan: self.inner.clone(),
+            partition,
+            span,
+        }))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        self.inner.metrics()
+    }
+
+    fn statistics(&self) -> Result<Statistics> {
+        self.inner.statistics()
+    }
+}
+
+/// A partition stream polled in its `execute` span
+struct InstrumentedStream {
+    input: SendableRecordBatchStream,
+    plan: Arc<dyn ExecutionPlan>,
+    partition: usize,
+    span: Span,
+}
+
+impl Stream for InstrumentedStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let span = self.span.clone();
+        let _entered = span.enter();
+        let poll = self.input.poll_next_unpin(cx);
+        if let Poll::Ready(Some(Err(e))) = &poll {
+            span.record("error", display(e));
+        }
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl RecordBatchStream for InstrumentedStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+impl Drop for InstrumentedStream {
+    fn drop(&mut self) {
+        let Some(metrics) = self.plan.metrics() else {
+            return;
+        };
+        let partition_metrics = metrics
+            .iter()
+            .filter(|metric| metric.partition() == Some(self.partition));
+        for metric in partition_metrics {
+            let field = match metric.value() {
+                MetricValue::OutputRows(_) => "output_rows",
+                MetricValue::ElapsedCompute(_) => "elapsed_compute",
+                MetricValue::SpillCount(_) => "spill_count",
+                MetricValue::SpilledBytes(_) => "spilled_bytes",
+                _ => continue,
+            };
+            self.span.record(field, metric.value().as_usize());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryExec;
+    use crate::projection::ProjectionExec;
+    use crate::{collect_partitioned, displayable};
+
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion_physical_expr::expressions::col;
+    use tracing::field::{Field as TraceField, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// The id, name, fields and parent of a span
+    #[derive(Debug, Default, Clone)]
+    struct RecordedSpan {
+        id: u64,
+        name: &'static str,
+        fields: Vec<(&'static str, String)>,
+        parent: Option<u64>,
+    }
+
+    impl Visit for RecordedSpan {
+        fn record_debug(&mut self, field: &TraceField, value: &dyn std::fmt::Debug) {
+            self.fields.push((field.name(), format!("{value:?}")));
+        }
+
+        fn record_str(&mut self, field: &TraceField, value: &str) {
+            self.fields.push((field.name(), value.to_string()));
+        }
+    }
+
+    /// Records the spans created while it is the default subscriber
+    #[derive(Default)]
+    struct Recorder {
+        next_id: AtomicU64,
+        spans: Mutex<Vec<RecordedSpan>>,
+        stack: Mutex<Vec<u64>>,
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut span = RecordedSpan {
+                id,
+                name: attrs.metadata().name(),
+                parent: self.stack.lock().unwrap().last().copied(),
+                ..Default::default()
+            };
+            attrs.record(&mut span);
+            self.spans.lock().unwrap().push(span);
+            Id::from_u64(id)
+        }
+
+        fn record(&self, id: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            values.record(&mut spans[id.into_u64() as usize - 1]);
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, id: &Id) {
+            self.stack.lock().unwrap().push(id.into_u64());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.stack.lock().unwrap().pop();
+        }
+    }
+
+    impl RecordedSpan {
+        fn field(&self, name: &str) -> Option<&str> {
+            self.fields
+                .iter()
+                .rev()
+                .find(|(field, _)| *field == name)
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_spans() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3]))],
+        )?;
+        let memory = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone()], vec![batch]],
+            schema.clone(),
+            None,
+        )?);
+        let expr = vec![(col("a", &schema)?, "b".to_string())];
+        let plan = Arc::new(ProjectionExec::try_new(expr, memory)?);
+        let plan = instrument_plan(plan)?;
+
+        // displayed and downcast as the wrapped plans
+        let expected = "ProjectionExec: expr=[a@0 as b]\n  MemoryExec: partitions=2, partition_sizes=[1, 1]\n";
+        assert_eq!(
+            displayable(plan.as_ref()).indent(true).to_string(),
+            expected
+        );
+        assert!(plan.as_any().is::<ProjectionExec>());
+
+        let recorder = Arc::new(Recorder::default());
+        let dispatch = tracing::Dispatch::from(recorder.clone());
+        let _guard = tracing::dispatcher::set_default(&dispatch);
+        let batches = collect_partitioned(plan, Arc::new(TaskContext::default())).await?;
+        assert_eq!(batches.len(), 2);
+
+        let spans = recorder.spans.lock().unwrap().clone();
+        assert_eq!(spans.len(), 4);
+        assert!(spans.iter().all(|span| span.name == "execute"));
+        for (partition, spans) in spans.chunks(2).enumerate() {
+            let partition = partition.to_string();
+            assert_eq!(spans[0].field("operator"), Some("ProjectionExec"));
+            assert_eq!(spans[0].field("partition"), Some(partition.as_str()));
+            assert_eq!(spans[0].field("output_rows"), Some("3"));
+            assert_eq!(spans[1].field("operator"), Some("MemoryExec"));
+            assert_eq!(spans[1].field("partition"), Some(partition.as_str()));
+            assert_eq!(spans[1].parent, Some(spans[0].id));
+        }
+        Ok(())
+    }
+}
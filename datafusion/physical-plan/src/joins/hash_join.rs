@@ -304,6 +304,9 @@ pub struct HashJoinExec {
     /// Otherwise, rows that have `null`s in the join columns will not be
     /// matched and thus will not appear in the output.
     pub null_equals_null: bool,
+    /// Maximum number of rows to produce per output partition, see
+    /// [`Self::with_fetch`]
+    pub fetch: Option<usize>,
     /// Cache holding plan properties like equivalences, output partitioning etc.
     cache: PlanProperties,
 }
@@ -366,6 +369,7 @@ impl HashJoinExec {
             projection,
             column_indices,
             null_equals_null,
+            fetch: None,
             cache,
         })
     }
@@ -405,6 +409,43 @@ impl HashJoinExec {
         self.null_equals_null
     }
 
+    /// Maximum number of rows produced per output partition
+    pub fn fetch(&self) -> Option<usize> {
+        self.fetch
+    }
+
+    /// Returns true if the join can stop early after producing `fetch` rows.
+    ///
+    /// This is not the case for join types emitting unmatched build-side rows
+    /// once the probe side is exhausted, as stopping the probe side early would
+    /// report rows as unmatched.
+    pub fn supports_fetch(&self) -> bool {
+        !need_produce_result_in_final(self.join_type)
+    }
+
+    /// Return new instance of [HashJoinExec] that stops probing once every
+    /// output partition produced `fetch` rows.
+    pub fn with_fetch(&self, fetch: Option<usize>) -> Result<Self> {
+        if fetch.is_some() && !self.supports_fetch() {
+            return plan_err!(
+                "HashJoinExec does not support fetch for join type {}",
+                self.join_type
+            );
+        }
+        let mut join = Self::try_new(
+            self.left.clone(),
+            self.right.clone(),
+            self.on.clone(),
+            self.filter.clone(),
+            &self.join_type,
+            self.projection.clone(),
+            self.mode,
+            self.null_equals_null,
+        )?;
+        join.fetch = fetch;
+        Ok(join)
+    }
+
     /// Calculate order preservation flags for this hash join.
     fn maintains_input_order(join_type: JoinType) -> Vec<bool> {
         vec![
@@ -438,7 +479,7 @@ impl HashJoinExec {
             },
             None => None,
         };
-        Self::try_new(
+        let mut join = Self::try_new(
             self.left.clone(),
             self.right.clone(),
             self.on.clone(),
@@ -447,7 +488,9 @@ impl HashJoinExec {
             projection,
             self.mode,
             self.null_equals_null,
-        )
+        )?;
+        join.fetch = self.fetch;
+        Ok(join)
     }
 
     /// This function creates the cache object that stores the plan properties such as schema, equivalence properties, ordering, partitioning, etc.
@@ -575,6 +618,9 @@ impl DisplayAs for HashJoinExec {
                 } else {
                     "".to_string()
                 };
+                let display_fetch = self
+                    .fetch
+                    .map_or_else(String::new, |fetch| format!(", fetch={fetch}"));
                 let on = self
                     .on
                     .iter()
@@ -583,8 +629,13 @@ impl DisplayAs for HashJoinExec {
                     .join(", ");
                 write!(
                     f,
-                    "HashJoinExec: mode={:?}, join_type={:?}, on=[{}]{}{}",
-                    self.mode, self.join_type, on, display_filter, display_projections
+                    "HashJoinExec: mode={:?}, join_type={:?}, on=[{}]{}{}{}",
+                    self.mode,
+                    self.join_type,
+                    on,
+                    display_filter,
+                    display_projections,
+                    display_fetch
                 )
             }
         }
@@ -668,7 +719,7 @@ impl ExecutionPlan for HashJoinExec {
         self: Arc<Self>,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        Ok(Arc::new(HashJoinExec::try_new(
+        let mut join = HashJoinExec::try_new(
             children[0].clone(),
             children[1].clone(),
             self.on.clone(),
@@ -677,7 +728,9 @@ impl ExecutionPlan for HashJoinExec {
             self.projection.clone(),
             self.mode,
             self.null_equals_null,
-        )?))
+        )?;
+        join.fetch = self.fetch;
+        Ok(Arc::new(join))
     }
 
     fn execute(
@@ -770,6 +823,7 @@ impl ExecutionPlan for HashJoinExec {
             build_side: BuildSide::Initial(BuildSideInitialState { left_fut }),
             batch_size,
             hashes_buffer: vec![],
+            fetch: self.fetch,
         }))
     }
 
@@ -830,11 +884,20 @@ async fn collect_left_input(
     // 1. creates a [JoinHashMap] of all batches from the stream
     // 2. stores the batches in a vector.
     let initial = (Vec::new(), 0, metrics, reservation);
+    let cancellation = context.cancellation_token();
+    // The build side cannot spill, but can ask spillable operators to spill
+    let spill_timeout = context
+        .session_config()
+        .options()
+        .execution
+        .spill_request_timeout
+        .0;
     let (batches, num_rows, metrics, mut reservation) = stream
         .try_fold(initial, |mut acc, batch| async {
+            cancellation.check()?;
             let batch_size = batch.get_array_memory_size();
             // Reserve memory for incoming batch
-            acc.3.try_grow(batch_size)?;
+            acc.3.try_grow_with_spill(batch_size, spill_timeout).await?;
             // Update metrics
             acc.2.build_mem_used.add(batch_size);
             acc.2.build_input_batches.add(1);
@@ -864,7 +927,9 @@ async fn collect_left_input(
     let estimated_hastable_size =
         16 * estimated_buckets + estimated_buckets + size_of::<JoinHashMap>();
 
-    reservation.try_grow(estimated_hastable_size)?;
+    reservation
+        .try_grow_with_spill(estimated_hastable_size, spill_timeout)
+        .await?;
     metrics.build_mem_used.add(estimated_hastable_size);
 
     let mut hashmap = JoinHashMap::with_capacity(num_rows);
@@ -874,6 +939,7 @@ async fn collect_left_input(
     // Updating hashmap starting from the last batch
     let batches_iter = batches.iter().rev();
     for batch in batches_iter.clone() {
+        cancellation.check()?;
         hashes_buffer.clear();
         hashes_buffer.resize(batch.num_rows(), 0);
         update_hash(
@@ -1093,6 +1159,8 @@ struct HashJoinStream {
     batch_size: usize,
     /// Scratch space for computing hashes
     hashes_buffer: Vec<u64>,
+    /// Remaining number of rows to produce, if limited
+    fetch: Option<usize>,
 }
 
 impl RecordBatchStream for HashJoinStream {
@@ -1454,11 +1522,26 @@ impl HashJoinStream {
             JoinSide::Left,
         )?;
 
+        let result = match self.fetch {
+            Some(fetch) if result.num_rows() >= fetch => {
+                self.fetch = Some(0);
+                result.slice(0, fetch)
+            }
+            Some(fetch) => {
+                self.fetch = Some(fetch - result.num_rows());
+                result
+            }
+            None => result,
+        };
+
         self.join_metrics.output_batches.add(1);
         self.join_metrics.output_rows.add(result.num_rows());
         timer.done();
 
-        if next_offset.is_none() {
+        if self.fetch == Some(0) {
+            // Enough rows were produced, the rest of the probe side is not needed
+            self.state = HashJoinStreamState::Completed;
+        } else if next_offset.is_none() {
             self.state = HashJoinStreamState::FetchProbeBatch;
         } else {
             state.advance(
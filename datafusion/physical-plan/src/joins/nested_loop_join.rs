@@ -45,7 +45,8 @@ use arrow::array::{
 use arrow::datatypes::{Schema, SchemaRef};
 use arrow::record_batch::RecordBatch;
 use arrow::util::bit_util;
-use datafusion_common::{exec_err, JoinSide, Result, Statistics};
+use datafusion_common::{exec_err, DataFusionError, JoinSide, Result, Statistics};
+use datafusion_execution::cancellation::CancellationToken;
 use datafusion_execution::memory_pool::{MemoryConsumer, MemoryReservation};
 use datafusion_execution::TaskContext;
 use datafusion_expr::JoinType;
@@ -239,6 +240,7 @@ impl ExecutionPlan for NestedLoopJoinExec {
         context: Arc<TaskContext>,
     ) -> Result<SendableRecordBatchStream> {
         let join_metrics = BuildProbeJoinMetrics::new(partition, &self.metrics);
+        let cancellation = context.cancellation_token().clone();
 
         // Initialization reservation for load of inner table
         let load_reservation =
@@ -289,6 +291,7 @@ impl ExecutionPlan for NestedLoopJoinExec {
             column_indices: self.column_indices.clone(),
             join_metrics,
             reservation,
+            cancellation,
         }))
     }
 
@@ -340,6 +343,14 @@ async fn load_specified_partition_of_input(
     join_metrics: BuildProbeJoinMetrics,
     reservation: MemoryReservation,
 ) -> Result<JoinLeftData> {
+    let cancellation = context.cancellation_token().clone();
+    // The inner table cannot spill, but can ask spillable operators to spill
+    let spill_timeout = context
+        .session_config()
+        .options()
+        .execution
+        .spill_request_timeout
+        .0;
     let stream = input.execute(partition, context)?;
 
     // Load all batches and count the rows
@@ -347,9 +358,10 @@ async fn load_specified_partition_of_input(
         .try_fold(
             (Vec::new(), 0usize, join_metrics, reservation),
             |mut acc, batch| async {
+                cancellation.check()?;
                 let batch_size = batch.get_array_memory_size();
                 // Reserve memory for incoming batch
-                acc.3.try_grow(batch_size)?;
+                acc.3.try_grow_with_spill(batch_size, spill_timeout).await?;
                 // Update metrics
                 acc.2.build_mem_used.add(batch_size);
                 acc.2.build_input_batches.add(1);
@@ -400,6 +412,8 @@ struct NestedLoopJoinStream {
     join_metrics: BuildProbeJoinMetrics,
     /// Memory reservation for visited_left_side
     reservation: MemoryReservation,
+    /// Cancels the join between the rows of the build side
+    cancellation: CancellationToken,
 }
 
 fn build_join_indices(
@@ -481,6 +495,7 @@ impl NestedLoopJoinStream {
                         &self.column_indices,
                         &self.schema,
                         visited_left_side,
+                        &self.cancellation,
                     );
 
                     // Recording time & updating output metrics
@@ -566,6 +581,7 @@ impl NestedLoopJoinStream {
                         &self.column_indices,
                         &self.schema,
                         &mut empty_visited_left_side,
+                        &self.cancellation,
                     );
 
                     // Recording time & updating output metrics
@@ -583,6 +599,7 @@ impl NestedLoopJoinStream {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn join_left_and_right_batch(
     left_batch: &RecordBatch,
     right_batch: &RecordBatch,
@@ -591,9 +608,11 @@ fn join_left_and_right_batch(
     column_indices: &[ColumnIndex],
     schema: &Schema,
     visited_left_side: &mut BooleanBufferBuilder,
+    cancellation: &CancellationToken,
 ) -> Result<RecordBatch> {
     let indices_result = (0..left_batch.num_rows())
         .map(|left_row_index| {
+            cancellation.check()?;
             build_join_indices(left_row_index, right_batch, left_batch, filter)
         })
         .collect::<Result<Vec<(UInt64Array, UInt32Array)>>>();
@@ -601,6 +620,7 @@ fn join_left_and_right_batch(
     let mut left_indices_builder = UInt64Builder::new();
     let mut right_indices_builder = UInt32Builder::new();
     let left_right_indices = match indices_result {
+        Err(err @ DataFusionError::Cancelled(_)) => Err(err),
         Err(err) => {
             exec_err!("Fail to build join indices in NestedLoopJoinExec, error:{err}")
         }
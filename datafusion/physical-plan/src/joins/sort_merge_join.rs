@@ -83,6 +83,8 @@ pub struct SortMergeJoinExec {
     pub sort_options: Vec<SortOptions>,
     /// If null_equals_null is true, null == null else null != null
     pub null_equals_null: bool,
+    /// Maximum number of rows to produce per output partition
+    pub fetch: Option<usize>,
     /// Cache holding plan properties like equivalences, output partitioning etc.
     cache: PlanProperties,
 }
@@ -151,10 +153,32 @@ impl SortMergeJoinExec {
             right_sort_exprs,
             sort_options,
             null_equals_null,
+            fetch: None,
             cache,
         })
     }
 
+    /// Return new instance of [SortMergeJoinExec] that stops joining once
+    /// every output partition produced `fetch` rows
+    pub fn with_fetch(&self, fetch: Option<usize>) -> Result<Self> {
+        let mut join = Self::try_new(
+            self.left.clone(),
+            self.right.clone(),
+            self.on.clone(),
+            self.filter.clone(),
+            self.join_type,
+            self.sort_options.clone(),
+            self.null_equals_null,
+        )?;
+        join.fetch = fetch;
+        Ok(join)
+    }
+
+    /// Maximum number of rows produced per output partition
+    pub fn fetch(&self) -> Option<usize> {
+        self.fetch
+    }
+
     /// Get probe side (e.g streaming side) information for this sort merge join.
     /// In current implementation, probe side is determined according to join type.
     pub fn probe_side(join_type: &JoinType) -> JoinSide {
@@ -251,10 +275,13 @@ impl DisplayAs for SortMergeJoinExec {
                     .map(|(c1, c2)| format!("({}, {})", c1, c2))
                     .collect::<Vec<String>>()
                     .join(", ");
+                let display_fetch = self
+                    .fetch
+                    .map_or_else(String::new, |fetch| format!(", fetch={fetch}"));
                 write!(
                     f,
-                    "SortMergeJoin: join_type={:?}, on=[{}]{}",
-                    self.join_type, on, display_filter
+                    "SortMergeJoin: join_type={:?}, on=[{}]{}{}",
+                    self.join_type, on, display_filter, display_fetch
                 )
             }
         }
@@ -303,15 +330,19 @@ impl ExecutionPlan for SortMergeJoinExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match &children[..] {
-            [left, right] => Ok(Arc::new(SortMergeJoinExec::try_new(
-                left.clone(),
-                right.clone(),
-                self.on.clone(),
-                self.filter.clone(),
-                self.join_type,
-                self.sort_options.clone(),
-                self.null_equals_null,
-            )?)),
+            [left, right] => {
+                let mut join = SortMergeJoinExec::try_new(
+                    left.clone(),
+                    right.clone(),
+                    self.on.clone(),
+                    self.filter.clone(),
+                    self.join_type,
+                    self.sort_options.clone(),
+                    self.null_equals_null,
+                )?;
+                join.fetch = self.fetch;
+                Ok(Arc::new(join))
+            }
             _ => internal_err!("SortMergeJoin wrong number of children"),
         }
     }
@@ -362,6 +393,7 @@ impl ExecutionPlan for SortMergeJoinExec {
             batch_size,
             SortMergeJoinMetrics::new(partition, &self.metrics),
             reservation,
+            self.fetch,
         )?))
     }
 
@@ -643,6 +675,8 @@ struct SMJStream {
     pub join_metrics: SortMergeJoinMetrics,
     /// Memory reservation
     pub reservation: MemoryReservation,
+    /// Remaining number of rows to produce, if limited
+    pub fetch: Option<usize>,
 }
 
 impl RecordBatchStream for SMJStream {
@@ -658,6 +692,26 @@ impl Stream for SMJStream {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        let Some(fetch) = self.fetch else {
+            return self.poll_next_impl(cx);
+        };
+        if fetch == 0 {
+            return Poll::Ready(None);
+        }
+        self.poll_next_impl(cx).map_ok(|batch| {
+            let batch = batch.slice(0, batch.num_rows().min(fetch));
+            // Once `fetch` reaches zero, the inputs are no longer polled
+            self.fetch = Some(fetch - batch.num_rows());
+            batch
+        })
+    }
+}
+
+impl SMJStream {
+    fn poll_next_impl(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<RecordBatch>>> {
         let join_time = self.join_metrics.join_time.clone();
         let _timer = join_time.timer();
 
@@ -762,6 +816,7 @@ impl SMJStream {
         batch_size: usize,
         join_metrics: SortMergeJoinMetrics,
         reservation: MemoryReservation,
+        fetch: Option<usize>,
     ) -> Result<Self> {
         let streamed_schema = streamed.schema();
         let buffered_schema = buffered.schema();
@@ -790,6 +845,7 @@ impl SMJStream {
             join_type,
             join_metrics,
             reservation,
+            fetch,
         })
     }
 
@@ -24,6 +24,7 @@ use std::sync::Arc;
 use crate::coalesce_partitions::CoalescePartitionsExec;
 use crate::display::DisplayableExecutionPlan;
 use crate::metrics::MetricsSet;
+use crate::progress::track_progress;
 use crate::repartition::RepartitionExec;
 use crate::sorts::sort_preserving_merge::SortPreservingMergeExec;
 
@@ -55,14 +56,18 @@ pub mod empty;
 pub mod explain;
 pub mod filter;
 pub mod insert;
+#[cfg(feature = "tracing")]
+pub mod instrumented;
 pub mod joins;
 pub mod limit;
 pub mod memory;
 pub mod metrics;
 pub mod placeholder_row;
+pub mod progress;
 pub mod projection;
 pub mod recursive_query;
 pub mod repartition;
+pub mod shared;
 pub mod sorts;
 pub mod stream;
 pub mod streaming;
@@ -695,17 +700,19 @@ pub fn execute_stream(
     plan: Arc<dyn ExecutionPlan>,
     context: Arc<TaskContext>,
 ) -> Result<SendableRecordBatchStream> {
-    match plan.output_partitioning().partition_count() {
-        0 => Ok(Box::pin(EmptyRecordBatchStream::new(plan.schema()))),
-        1 => plan.execute(0, context),
+    let stream = match plan.output_partitioning().partition_count() {
+        0 => Box::pin(EmptyRecordBatchStream::new(plan.schema())),
+        1 => plan.execute(0, context.clone())?,
         _ => {
             // merge into a single partition
             let plan = CoalescePartitionsExec::new(plan.clone());
             // CoalescePartitionsExec must produce a single partition
             assert_eq!(1, plan.properties().output_partitioning().partition_count());
-            plan.execute(0, context)
+            plan.execute(0, context.clone())?
         }
-    }
+    };
+    let stream = context.cancellation_token().guard_stream(stream);
+    Ok(track_progress(&plan, &context, stream))
 }
 
 /// Execute the [ExecutionPlan] and collect the results in memory
@@ -762,7 +769,9 @@ pub fn execute_stream_partitioned(
     let num_partitions = plan.output_partitioning().partition_count();
     let mut streams = Vec::with_capacity(num_partitions);
     for i in 0..num_partitions {
-        streams.push(plan.execute(i, context.clone())?);
+        let stream = plan.execute(i, context.clone())?;
+        let stream = context.cancellation_token().guard_stream(stream);
+        streams.push(track_progress(&plan, &context, stream));
     }
     Ok(streams)
 }
@@ -0,0 +1,238 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reporting the progress of the operators of a running [`ExecutionPlan`]
+
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::metrics::MetricValue;
+use crate::{displayable, ExecutionPlan, RecordBatchStream, SendableRecordBatchStream};
+
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use datafusion_common::Result;
+use datafusion_execution::progress::{
+    ActiveOperator, OperatorProgress, QueryProgress, RunningStream,
+};
+use datafusion_execution::TaskContext;
+use futures::{Stream, StreamExt};
+
+/// Reports the state of the operators of an [`ExecutionPlan`] from their
+/// metrics while it runs.
+///
+/// A partition of an operator is running if its start timestamp is recorded
+/// but not its end timestamp, as done by [`BaselineMetrics`].
+///
+/// [`BaselineMetrics`]: crate::metrics::BaselineMetrics
+#[derive(Debug)]
+pub struct PlanProgress {
+    plan: Arc<dyn ExecutionPlan>,
+}
+
+impl PlanProgress {
+    /// Create a new [`PlanProgress`] of `plan`
+    pub fn new(plan: Arc<dyn ExecutionPlan>) -> Self {
+        Self { plan }
+    }
+}
+
+impl OperatorProgress for PlanProgress {
+    fn active_operators(&self) -> Vec<ActiveOperator> {
+        let mut operators = vec![];
+        visit(&self.plan, &mut |plan| {
+            let Some(metrics) = plan.metrics() else {
+                return;
+            };
+            let mut started = HashSet::new();
+            let mut ended = HashSet::new();
+            for metric in metrics.iter() {
+                match metric.value() {
+                    MetricValue::StartTimestamp(ts) if ts.value().is_some() => {
+                        started.insert(metric.partition());
+                    }
+                    MetricValue::EndTimestamp(ts) if ts.value().is_some() => {
+                        ended.insert(metric.partition());
+                    }
+                    _ => {}
+                }
+            }
+            let running_partitions = started.difference(&ended).count();
+            if running_partitions > 0 {
+                operators.push(ActiveOperator {
+                    name: operator_name(plan.as_ref()),
+                    running_partitions,
+                });
+            }
+        });
+        operators
+    }
+
+    fn spills(&self) -> (usize, usize) {
+        let mut spills = (0, 0);
+        visit(&self.plan, &mut |plan| {
+            if let Some(metrics) = plan.metrics() {
+                spills.0 += metrics.spill_count().unwrap_or_default();
+                spills.1 += metrics.spilled_bytes().unwrap_or_default();
+            }
+        });
+        spills
+    }
+}
+
+fn visit(plan: &Arc<dyn ExecutionPlan>, f: &mut impl FnMut(&Arc<dyn ExecutionPlan>)) {
+    f(plan);
+    for child in plan.children() {
+        visit(&child, f);
+    }
+}
+
+/// The name of an operator, e.g. `ProjectionExec` for
+/// `ProjectionExec: expr=[a@0 as a]`
+pub(crate) fn operator_name(plan: &dyn ExecutionPlan) -> String {
+    let line = displayable(plan).one_line().to_string();
+    let line = line.trim();
+    line.split_once(':')
+        .map_or(line, |(name, _)| name)
+        .to_string()
+}
+
+/// Reports the progress of the operators of `plan` to the [`QueryProgress`]
+/// of `context`, if any, while `stream` is consumed
+pub(crate) fn track_progress(
+    plan: &Arc<dyn ExecutionPlan>,
+    context: &TaskContext,
+    stream: SendableRecordBatchStream,
+) -> SendableRecordBatchStream {
+    match context.query_progress() {
+        Some(progress) => {
+            progress.set_operators(Arc::new(PlanProgress::new(plan.clone())));
+            Box::pin(ProgressStream::new(stream, progress))
+        }
+        None => stream,
+    }
+}
+
+/// A stream of a query that is running until it is exhausted or dropped
+struct ProgressStream {
+    input: SendableRecordBatchStream,
+    running: Option<RunningStream>,
+}
+
+impl ProgressStream {
+    fn new(input: SendableRecordBatchStream, progress: &Arc<QueryProgress>) -> Self {
+        Self {
+            input,
+            running: Some(progress.stream_started()),
+        }
+    }
+}
+
+impl Stream for ProgressStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let poll = self.input.poll_next_unpin(cx);
+        if matches!(poll, Poll::Ready(None) | Poll::Ready(Some(Err(_)))) {
+            self.running = None;
+        }
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl RecordBatchStream for ProgressStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorts::sort::SortExec;
+    use crate::test::exec::BlockingExec;
+    use crate::{collect, execute_stream};
+
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow_schema::SortOptions;
+    use datafusion_execution::progress::ProgressRegistry;
+    use datafusion_physical_expr::expressions::col;
+    use datafusion_physical_expr::PhysicalSortExpr;
+
+    fn sorted_blocking_exec() -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let blocking = Arc::new(BlockingExec::new(schema.clone(), 1));
+        let sort_expr = vec![PhysicalSortExpr {
+            expr: col("a", &schema)?,
+            options: SortOptions::default(),
+        }];
+        Ok(Arc::new(SortExec::new(sort_expr, blocking)))
+    }
+
+    #[tokio::test]
+    async fn running_query() -> Result<()> {
+        let registry = ProgressRegistry::new();
+        let progress = registry.register();
+        let context =
+            Arc::new(TaskContext::default().with_query_progress(progress.clone()));
+        let plan = sorted_blocking_exec()?;
+
+        let mut stream = execute_stream(plan, context)?;
+        let running = registry.running();
+        assert_eq!(running.len(), 1);
+        assert_eq!(
+            running[0].active_operators,
+            vec![ActiveOperator {
+                name: "SortExec".to_string(),
+                running_partitions: 1,
+            }]
+        );
+
+        // the input never produces data
+        let poll = futures::poll!(stream.next());
+        assert!(poll.is_pending());
+        assert_eq!(registry.running().len(), 1);
+
+        drop(stream);
+        assert!(registry.running().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn finished_query() -> Result<()> {
+        let registry = ProgressRegistry::new();
+        let progress = registry.register();
+        let context =
+            Arc::new(TaskContext::default().with_query_progress(progress.clone()));
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let plan = Arc::new(crate::empty::EmptyExec::new(schema));
+
+        collect(plan, context).await?;
+        assert!(registry.running().is_empty());
+        assert!(progress.report().active_operators.is_empty());
+        Ok(())
+    }
+}
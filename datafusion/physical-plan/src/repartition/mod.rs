@@ -25,7 +25,7 @@ use std::task::{Context, Poll};
 use std::{any::Any, vec};
 
 use super::common::SharedMemoryReservation;
-use super::metrics::{self, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet};
+use super::metrics::{self, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet};
 use super::{
     DisplayAs, ExecutionPlanProperties, RecordBatchStream, SendableRecordBatchStream,
 };
@@ -35,7 +35,9 @@ use crate::metrics::BaselineMetrics;
 use crate::repartition::distributor_channels::{
     channels, partition_aware_channels, DistributionReceiver, DistributionSender,
 };
+use crate::repartition::skew::{KeySample, SkewRouting};
 use crate::sorts::streaming_merge;
+use crate::stream::RecordBatchStreamAdapter;
 use crate::{DisplayFormatType, ExecutionPlan, Partitioning, PlanProperties, Statistics};
 
 use arrow::array::{ArrayRef, UInt64Builder};
@@ -54,6 +56,9 @@ use log::trace;
 use parking_lot::Mutex;
 
 mod distributor_channels;
+mod skew;
+
+pub use skew::{SkewHandling, SkewedKeys};
 
 type MaybeBatch = Option<Result<RecordBatch>>;
 type InputPartitionsToCurrentPartitionSender = Vec<DistributionSender<MaybeBatch>>;
@@ -89,6 +94,7 @@ enum BatchPartitionerState {
         exprs: Vec<Arc<dyn PhysicalExpr>>,
         num_partitions: usize,
         hash_buffer: Vec<u64>,
+        skew_routing: Option<SkewRouting>,
     },
     RoundRobin {
         num_partitions: usize,
@@ -114,6 +120,7 @@ impl BatchPartitioner {
                 // Use fixed random hash
                 random_state: ahash::RandomState::with_seeds(0, 0, 0, 0),
                 hash_buffer: vec![],
+                skew_routing: None,
             },
             other => return not_impl_err!("Unsupported repartitioning scheme {other:?}"),
         };
@@ -164,26 +171,25 @@ impl BatchPartitioner {
                     exprs,
                     num_partitions: partitions,
                     hash_buffer,
+                    skew_routing,
                 } => {
                     let timer = self.timer.timer();
 
-                    let arrays = exprs
-                        .iter()
-                        .map(|expr| expr.evaluate(&batch)?.into_array(batch.num_rows()))
-                        .collect::<Result<Vec<_>>>()?;
-
-                    hash_buffer.clear();
-                    hash_buffer.resize(batch.num_rows(), 0);
-
-                    create_hashes(&arrays, random_state, hash_buffer)?;
+                    hash_batch(&batch, exprs, random_state, hash_buffer)?;
 
                     let mut indices: Vec<_> = (0..*partitions)
                         .map(|_| UInt64Builder::with_capacity(batch.num_rows()))
                         .collect();
 
                     for (index, hash) in hash_buffer.iter().enumerate() {
+                        let index = index as u64;
+                        if let Some(skew_routing) = skew_routing {
+                            if skew_routing.route(index, *hash, &mut indices) {
+                                continue;
+                            }
+                        }
                         indices[(*hash % *partitions as u64) as usize]
-                            .append_value(index as u64);
+                            .append_value(index);
                     }
 
                     let it = indices
@@ -220,6 +226,35 @@ impl BatchPartitioner {
         Ok(it)
     }
 
+    /// Count the rows of `batch` per hash of their partitioning keys
+    fn sample_keys(
+        &mut self,
+        batch: &RecordBatch,
+        counts: &mut HashMap<u64, usize>,
+    ) -> Result<()> {
+        if let BatchPartitionerState::Hash {
+            random_state,
+            exprs,
+            hash_buffer,
+            ..
+        } = &mut self.state
+        {
+            let _timer = self.timer.timer();
+            hash_batch(batch, exprs, random_state, hash_buffer)?;
+            for hash in hash_buffer.iter() {
+                *counts.entry(*hash).or_default() += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Route the rows of heavy keys with `routing`
+    fn set_skew_routing(&mut self, routing: SkewRouting) {
+        if let BatchPartitionerState::Hash { skew_routing, .. } = &mut self.state {
+            *skew_routing = Some(routing);
+        }
+    }
+
     // return the number of output partitions
     fn num_partitions(&self) -> usize {
         match self.state {
@@ -229,6 +264,25 @@ impl BatchPartitioner {
     }
 }
 
+/// Compute the hashes of the partitioning keys of `batch` into `hash_buffer`
+fn hash_batch(
+    batch: &RecordBatch,
+    exprs: &[Arc<dyn PhysicalExpr>],
+    random_state: &ahash::RandomState,
+    hash_buffer: &mut Vec<u64>,
+) -> Result<()> {
+    let arrays = exprs
+        .iter()
+        .map(|expr| expr.evaluate(batch)?.into_array(batch.num_rows()))
+        .collect::<Result<Vec<_>>>()?;
+
+    hash_buffer.clear();
+    hash_buffer.resize(batch.num_rows(), 0);
+
+    create_hashes(&arrays, random_state, hash_buffer)?;
+    Ok(())
+}
+
 /// Maps `N` input partitions to `M` output partitions based on a
 /// [`Partitioning`] scheme.
 ///
@@ -283,6 +337,13 @@ impl BatchPartitioner {
 /// arbitrary interleaving (and thus unordered) unless
 /// [`Self::with_preserve_order`] specifies otherwise.
 ///
+/// # Skewed keys
+///
+/// Hash partitioning sends all the rows of a key to the same partition, so a
+/// few heavy keys can overload one partition. [`Self::with_skew_handling`]
+/// spreads the rows of the heavy keys across all partitions instead, which
+/// breaks the hash partitioning of the output (see [`SkewHandling`]).
+///
 /// # Footnote
 ///
 /// The "Exchange Operator" was first described in the 1989 paper
@@ -304,6 +365,8 @@ pub struct RepartitionExec {
     /// Boolean flag to decide whether to preserve ordering. If true means
     /// `SortPreservingRepartitionExec`, false means `RepartitionExec`.
     preserve_order: bool,
+    /// How the rows of heavy keys are handled, if at all
+    skew_handling: Option<SkewHandling>,
     /// Cache holding plan properties like equivalences, output partitioning etc.
     cache: PlanProperties,
 }
@@ -316,6 +379,8 @@ struct RepartitionMetrics {
     repartition_time: metrics::Time,
     /// Time in nanos for sending resulting batches to channels
     send_time: metrics::Time,
+    /// Number of rows of heavy keys
+    skewed_rows: Count,
 }
 
 impl RepartitionMetrics {
@@ -338,13 +403,19 @@ impl RepartitionMetrics {
 
         // Time in nanos for sending resulting batches to channels
         let send_time = MetricBuilder::new(metrics)
-            .with_label(label)
+            .with_label(label.clone())
             .subset_time("send_time", output_partition);
 
+        // Number of rows of heavy keys
+        let skewed_rows = MetricBuilder::new(metrics)
+            .with_label(label)
+            .counter("skewed_rows", output_partition);
+
         Self {
             fetch_time,
             repartition_time: repart_time,
             send_time,
+            skewed_rows,
         }
     }
 }
@@ -366,6 +437,11 @@ impl RepartitionExec {
         self.preserve_order
     }
 
+    /// How the rows of heavy keys are handled, if at all
+    pub fn skew_handling(&self) -> Option<&SkewHandling> {
+        self.skew_handling.as_ref()
+    }
+
     /// Get name used to display this Exec
     pub fn name(&self) -> &str {
         "RepartitionExec"
@@ -392,6 +468,10 @@ impl DisplayAs for RepartitionExec {
                     write!(f, ", preserve_order=true")?;
                 }
 
+                if let Some(skew_handling) = &self.skew_handling {
+                    write!(f, ", skew={skew_handling}")?;
+                }
+
                 if let Some(sort_exprs) = self.sort_exprs() {
                     write!(
                         f,
@@ -428,6 +508,9 @@ impl ExecutionPlan for RepartitionExec {
         if self.preserve_order {
             repartition = repartition.with_preserve_order();
         }
+        if let Some(skew_handling) = &self.skew_handling {
+            repartition = repartition.with_skew_handling(skew_handling.clone());
+        }
         Ok(Arc::new(repartition))
     }
 
@@ -477,6 +560,15 @@ impl ExecutionPlan for RepartitionExec {
                 let rxs = rxs.into_iter().map(|item| vec![item]).collect::<Vec<_>>();
                 (txs, rxs)
             };
+            let mut key_samples = match &self.skew_handling {
+                Some(SkewHandling::Split(skewed_keys)) => skewed_keys
+                    .expect_samples(num_input_partitions, num_output_partitions),
+                Some(SkewHandling::Broadcast(skewed_keys)) => {
+                    skewed_keys.expect_broadcast();
+                    vec![]
+                }
+                None => vec![],
+            };
             for (partition, (tx, rx)) in txs.into_iter().zip(rxs).enumerate() {
                 let reservation = Arc::new(Mutex::new(
                     MemoryConsumer::new(format!("{}[{partition}]", self.name()))
@@ -503,6 +595,8 @@ impl ExecutionPlan for RepartitionExec {
                     i,
                     txs.clone(),
                     self.partitioning.clone(),
+                    self.skew_handling.clone(),
+                    key_samples.pop(),
                     r_metrics,
                     context.clone(),
                 ));
@@ -608,6 +702,7 @@ impl RepartitionExec {
             })),
             metrics: ExecutionPlanMetricsSet::new(),
             preserve_order,
+            skew_handling: None,
             cache,
         })
     }
@@ -668,6 +763,22 @@ impl RepartitionExec {
         self
     }
 
+    /// Handle the rows of heavy keys with `skew_handling`.
+    ///
+    /// This is a no op unless the partitioning is a hash partitioning, and
+    /// the order is not preserved.
+    pub fn with_skew_handling(mut self, skew_handling: SkewHandling) -> Self {
+        if self.preserve_order || !matches!(self.partitioning, Partitioning::Hash(_, _)) {
+            return self;
+        }
+        let partition_count = self.partitioning.partition_count();
+        self.skew_handling = Some(skew_handling);
+        self.cache = self
+            .cache
+            .with_partitioning(Partitioning::UnknownPartitioning(partition_count));
+        self
+    }
+
     /// Return the sort expressions that are used to merge
     fn sort_exprs(&self) -> Option<&[PhysicalSortExpr]> {
         if self.preserve_order {
@@ -681,6 +792,7 @@ impl RepartitionExec {
     /// output partitions based on the desired partitioning
     ///
     /// txs hold the output sending channels for each output partition
+    #[allow(clippy::too_many_arguments)]
     async fn pull_from_input(
         input: Arc<dyn ExecutionPlan>,
         partition: usize,
@@ -689,6 +801,8 @@ impl RepartitionExec {
             (DistributionSender<MaybeBatch>, SharedMemoryReservation),
         >,
         partitioning: Partitioning,
+        skew_handling: Option<SkewHandling>,
+        key_sample: Option<KeySample>,
         metrics: RepartitionMetrics,
         context: Arc<TaskContext>,
     ) -> Result<()> {
@@ -700,6 +814,34 @@ impl RepartitionExec {
         let mut stream = input.execute(partition, context)?;
         timer.done();
 
+        if let Some(skew_handling) = skew_handling {
+            let mut sample = vec![];
+            if let Some(key_sample) = key_sample {
+                Self::sample_input(
+                    &mut stream,
+                    &mut partitioner,
+                    key_sample,
+                    &mut sample,
+                    &metrics,
+                )
+                .await?;
+            }
+            let heavy_keys = skew_handling.skewed_keys().heavy_keys().await;
+            if !heavy_keys.is_empty() {
+                partitioner.set_skew_routing(SkewRouting::new(
+                    heavy_keys,
+                    &skew_handling,
+                    metrics.skewed_rows.clone(),
+                ));
+            }
+            // Partition the sampled batches first
+            let schema = stream.schema();
+            stream = Box::pin(RecordBatchStreamAdapter::new(
+                schema,
+                futures::stream::iter(sample.into_iter().map(Ok)).chain(stream),
+            ));
+        }
+
         // While there are still outputs to send to, keep pulling inputs
         let mut batches_until_yield = partitioner.num_partitions();
         while !output_channels.is_empty() {
@@ -759,6 +901,34 @@ impl RepartitionExec {
         Ok(())
     }
 
+    /// Reads the first batches of `stream` into `sample`, until it holds the
+    /// number of rows sampled by `key_sample`, and counts their keys in
+    /// `key_sample`
+    async fn sample_input(
+        stream: &mut SendableRecordBatchStream,
+        partitioner: &mut BatchPartitioner,
+        mut key_sample: KeySample,
+        sample: &mut Vec<RecordBatch>,
+        metrics: &RepartitionMetrics,
+    ) -> Result<()> {
+        // `key_sample` is added when dropped, even on errors, so that the
+        // other inputs do not wait for it
+        while key_sample.rows < key_sample.sample_rows() {
+            let timer = metrics.fetch_time.timer();
+            let result = stream.next().await;
+            timer.done();
+
+            let batch = match result {
+                Some(result) => result?,
+                None => break,
+            };
+            partitioner.sample_keys(&batch, &mut key_sample.counts)?;
+            key_sample.rows += batch.num_rows();
+            sample.push(batch);
+        }
+        Ok(())
+    }
+
     /// Waits for `input_task` which is consuming one of the inputs to
     /// complete. Upon each successful completion, sends a `None` to
     /// each of the output tx channels to signal one of the inputs is
@@ -942,7 +1112,7 @@ mod tests {
     use arrow::array::{ArrayRef, StringArray, UInt32Array};
     use arrow::datatypes::{DataType, Field, Schema};
     use arrow::record_batch::RecordBatch;
-    use datafusion_common::cast::as_string_array;
+    use datafusion_common::cast::{as_string_array, as_uint32_array};
     use datafusion_common::{assert_batches_sorted_eq, exec_err};
     use datafusion_execution::runtime_env::{RuntimeConfig, RuntimeEnv};
 
@@ -1031,6 +1201,134 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn hash_partition_skewed_keys() -> Result<()> {
+        let schema = test_schema();
+        let task_ctx = Arc::new(TaskContext::default());
+        let partitioning = Partitioning::Hash(vec![col("c0", &schema)?], 4);
+        let skewed_keys = Arc::new(SkewedKeys::new(1000));
+
+        // 80 of the 100 rows of each input partition have key 1
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(UInt32Array::from_iter_values((0..100).map(|i| {
+                if i < 80 {
+                    1
+                } else {
+                    i
+                }
+            })))],
+        )?;
+        let input = MemoryExec::try_new(
+            &[vec![batch.clone()], vec![batch]],
+            schema.clone(),
+            None,
+        )?;
+        let split = RepartitionExec::try_new(Arc::new(input), partitioning.clone())?
+            .with_skew_handling(SkewHandling::Split(skewed_keys.clone()));
+        assert!(matches!(
+            split.properties().output_partitioning(),
+            Partitioning::UnknownPartitioning(4)
+        ));
+        let output_partitions =
+            crate::collect_partitioned(Arc::new(split), task_ctx.clone()).await?;
+        assert_eq!(skewed_keys.num_heavy_keys(), Some(1));
+
+        // The rows of key 1 are spread across all partitions
+        for partition in &output_partitions {
+            assert_eq!(count_key(partition, 1), 40);
+        }
+        assert_eq!(count_rows(&output_partitions), 200);
+
+        // The other input sends the rows of key 1 to all partitions
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(UInt32Array::from_iter_values(1..=10))],
+        )?;
+        let input = MemoryExec::try_new(&[vec![batch]], schema, None)?;
+        let broadcast = RepartitionExec::try_new(Arc::new(input), partitioning)?
+            .with_skew_handling(SkewHandling::Broadcast(skewed_keys));
+        let output_partitions =
+            crate::collect_partitioned(Arc::new(broadcast), task_ctx).await?;
+        for partition in &output_partitions {
+            assert_eq!(count_key(partition, 1), 1);
+        }
+        assert_eq!(count_rows(&output_partitions), 13);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn hash_partition_skewed_keys_broadcast_only() -> Result<()> {
+        let schema = test_schema();
+        let task_ctx = Arc::new(TaskContext::default());
+        let partitioning = Partitioning::Hash(vec![col("c0", &schema)?], 4);
+        let skewed_keys = Arc::new(SkewedKeys::new(1000));
+
+        // The split side is never executed, so no key is heavy
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(UInt32Array::from_iter_values(1..=10))],
+        )?;
+        let input = MemoryExec::try_new(&[vec![batch]], schema.clone(), None)?;
+        let broadcast = RepartitionExec::try_new(Arc::new(input), partitioning.clone())?
+            .with_skew_handling(SkewHandling::Broadcast(skewed_keys.clone()));
+        let output_partitions =
+            crate::collect_partitioned(Arc::new(broadcast), task_ctx.clone()).await?;
+        assert_eq!(skewed_keys.num_heavy_keys(), Some(0));
+        assert_eq!(count_key(&output_partitions.concat(), 1), 1);
+        assert_eq!(count_rows(&output_partitions), 10);
+
+        // The split side of the same round agrees, even if its key 1 is heavy
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(UInt32Array::from_iter_values((0..100).map(|i| {
+                if i < 80 {
+                    1
+                } else {
+                    i
+                }
+            })))],
+        )?;
+        let input = MemoryExec::try_new(&[vec![batch]], schema, None)?;
+        let split = Arc::new(
+            RepartitionExec::try_new(Arc::new(input), partitioning)?
+                .with_skew_handling(SkewHandling::Split(skewed_keys.clone())),
+        );
+        let output_partitions =
+            crate::collect_partitioned(split.clone(), task_ctx.clone()).await?;
+        assert_eq!(skewed_keys.num_heavy_keys(), Some(0));
+        let partitions_with_key = output_partitions
+            .iter()
+            .filter(|partition| count_key(partition, 1) > 0)
+            .count();
+        assert_eq!(partitions_with_key, 1);
+
+        // Executing the split side again starts a new round, which samples
+        crate::collect_partitioned(split, task_ctx).await?;
+        assert_eq!(skewed_keys.num_heavy_keys(), Some(1));
+
+        Ok(())
+    }
+
+    fn count_key(batches: &[RecordBatch], key: u32) -> usize {
+        batches
+            .iter()
+            .map(|batch| {
+                let keys = as_uint32_array(batch.column(0)).unwrap();
+                keys.iter().filter(|k| *k == Some(key)).count()
+            })
+            .sum()
+    }
+
+    fn count_rows(partitions: &[Vec<RecordBatch>]) -> usize {
+        partitions
+            .iter()
+            .flatten()
+            .map(|batch| batch.num_rows())
+            .sum()
+    }
+
     fn test_schema() -> Arc<Schema> {
         Arc::new(Schema::new(vec![Field::new("c0", DataType::UInt32, false)]))
     }
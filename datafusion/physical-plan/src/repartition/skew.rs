@@ -0,0 +1,287 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Detection and mitigation of skewed keys in hash repartitioning
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::metrics::Count;
+
+use arrow::array::UInt64Builder;
+use hashbrown::{HashMap, HashSet};
+use parking_lot::Mutex;
+use tokio::sync::watch;
+
+/// How a hash [`RepartitionExec`] handles the rows of heavy keys, i.e. keys
+/// holding more than the fair share of rows of one output partition.
+///
+/// Both variants break the hash partitioning of the output, so the
+/// repartition reports an unknown partitioning.
+///
+/// [`RepartitionExec`]: super::RepartitionExec
+#[derive(Debug, Clone)]
+pub enum SkewHandling {
+    /// Detect the heavy keys from a sample of the first rows of each input
+    /// partition, and spread their rows round robin across all output
+    /// partitions
+    Split(Arc<SkewedKeys>),
+    /// Send the rows of the heavy keys detected by the [`SkewHandling::Split`]
+    /// repartition sharing the same [`SkewedKeys`] to all output partitions
+    Broadcast(Arc<SkewedKeys>),
+}
+
+impl SkewHandling {
+    /// The heavy keys shared by the two repartitions
+    pub fn skewed_keys(&self) -> &Arc<SkewedKeys> {
+        match self {
+            Self::Split(keys) | Self::Broadcast(keys) => keys,
+        }
+    }
+}
+
+impl fmt::Display for SkewHandling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Split(_) => write!(f, "split"),
+            Self::Broadcast(_) => write!(f, "broadcast"),
+        }
+    }
+}
+
+/// The heavy keys detected by a [`SkewHandling::Split`] repartition.
+///
+/// Keys are identified by the hash of their values, so that both inputs of a
+/// join, which hash their keys the same way, agree on them. A key colliding
+/// with a heavy key is handled as a heavy key too, which is still correct.
+#[derive(Debug)]
+pub struct SkewedKeys {
+    /// Number of rows sampled from each input partition
+    sample_rows: usize,
+    state: Mutex<SampleState>,
+    heavy_keys: watch::Sender<Option<Arc<HashSet<u64>>>>,
+}
+
+#[derive(Debug, Default)]
+struct SampleState {
+    /// Whether the split side started executing in the current round
+    split_started: bool,
+    /// Whether the broadcast side started executing in the current round
+    broadcast_started: bool,
+    /// Number of input partitions that still have to add their sample
+    pending_inputs: usize,
+    /// Number of output partitions of the split side
+    num_partitions: usize,
+    sampled_rows: usize,
+    counts: HashMap<u64, usize>,
+}
+
+impl SkewedKeys {
+    /// Create new `SkewedKeys` detected from the first `sample_rows` rows of
+    /// each input partition
+    pub fn new(sample_rows: usize) -> Self {
+        Self {
+            sample_rows,
+            state: Mutex::new(SampleState::default()),
+            heavy_keys: watch::channel(None).0,
+        }
+    }
+
+    /// Number of rows sampled from each input partition
+    pub fn sample_rows(&self) -> usize {
+        self.sample_rows
+    }
+
+    /// The number of heavy keys, once they have been detected
+    pub fn num_heavy_keys(&self) -> Option<usize> {
+        self.heavy_keys.borrow().as_ref().map(|keys| keys.len())
+    }
+
+    /// Start a new round of the split side, which waits for the samples of
+    /// `inputs` input partitions, and return the samples to fill, one per
+    /// input partition.
+    ///
+    /// If the broadcast side already gave up waiting for this round, the
+    /// samples are ignored and no key is heavy.
+    pub(crate) fn expect_samples(
+        self: &Arc<Self>,
+        inputs: usize,
+        num_partitions: usize,
+    ) -> Vec<KeySample> {
+        let mut state = self.state.lock();
+        if state.split_started {
+            self.reset(&mut state);
+        }
+        state.split_started = true;
+        if self.heavy_keys.borrow().is_none() {
+            state.pending_inputs = inputs;
+            state.num_partitions = num_partitions;
+            if inputs == 0 {
+                self.heavy_keys.send_replace(Some(Arc::default()));
+            }
+        }
+
+        (0..inputs)
+            .map(|_| KeySample {
+                skewed_keys: Arc::clone(self),
+                counts: HashMap::new(),
+                rows: 0,
+            })
+            .collect()
+    }
+
+    /// Start a new round of the broadcast side
+    pub(crate) fn expect_broadcast(&self) {
+        let mut state = self.state.lock();
+        if state.broadcast_started {
+            self.reset(&mut state);
+        }
+        state.broadcast_started = true;
+    }
+
+    /// Forget the heavy keys of the previous round
+    fn reset(&self, state: &mut SampleState) {
+        *state = SampleState::default();
+        self.heavy_keys.send_replace(None);
+    }
+
+    /// Add the sampled key hashes of an input partition.
+    ///
+    /// Once all input partitions added theirs, the keys with more than one
+    /// `num_partitions`th of the sampled rows are the heavy keys.
+    fn add_sample(&self, counts: HashMap<u64, usize>, rows: usize) {
+        let mut state = self.state.lock();
+        if state.pending_inputs == 0 {
+            return;
+        }
+        state.sampled_rows += rows;
+        for (hash, count) in counts {
+            *state.counts.entry(hash).or_default() += count;
+        }
+        state.pending_inputs -= 1;
+        if state.pending_inputs > 0 {
+            return;
+        }
+
+        let sampled_rows = state.sampled_rows;
+        let num_partitions = state.num_partitions;
+        let heavy_keys = std::mem::take(&mut state.counts)
+            .into_iter()
+            .filter(|(_, count)| *count > 1 && *count * num_partitions > sampled_rows)
+            .map(|(hash, _)| hash)
+            .collect();
+        self.heavy_keys.send_replace(Some(Arc::new(heavy_keys)));
+    }
+
+    /// Wait until the heavy keys are detected.
+    ///
+    /// If the split side has not started sampling, e.g. because it is never
+    /// executed, no key is heavy.
+    pub(crate) async fn heavy_keys(&self) -> Arc<HashSet<u64>> {
+        {
+            let state = self.state.lock();
+            if !state.split_started {
+                self.heavy_keys.send_if_modified(|keys| {
+                    keys.get_or_insert_with(Arc::default);
+                    true
+                });
+            }
+        }
+
+        let mut receiver = self.heavy_keys.subscribe();
+        // The sender is owned by `self`, so it cannot be dropped
+        let heavy_keys = receiver
+            .wait_for(Option::is_some)
+            .await
+            .expect("sender is alive");
+        Arc::clone(heavy_keys.as_ref().unwrap())
+    }
+}
+
+/// The sampled key hashes of one input partition of the split side.
+///
+/// The sample is added to its [`SkewedKeys`] when dropped, so that the
+/// broadcast side does not wait forever for an input partition that failed,
+/// was cancelled, or was never polled.
+#[derive(Debug)]
+pub(crate) struct KeySample {
+    skewed_keys: Arc<SkewedKeys>,
+    pub(crate) counts: HashMap<u64, usize>,
+    pub(crate) rows: usize,
+}
+
+impl KeySample {
+    /// Number of rows to sample
+    pub(crate) fn sample_rows(&self) -> usize {
+        self.skewed_keys.sample_rows()
+    }
+}
+
+impl Drop for KeySample {
+    fn drop(&mut self) {
+        let counts = std::mem::take(&mut self.counts);
+        self.skewed_keys.add_sample(counts, self.rows);
+    }
+}
+
+/// Routes the rows of heavy keys in a [`BatchPartitioner`]
+///
+/// [`BatchPartitioner`]: super::BatchPartitioner
+#[derive(Debug)]
+pub(crate) struct SkewRouting {
+    heavy_keys: Arc<HashSet<u64>>,
+    broadcast: bool,
+    next_idx: usize,
+    /// Number of rows of heavy keys
+    skewed_rows: Count,
+}
+
+impl SkewRouting {
+    pub(crate) fn new(
+        heavy_keys: Arc<HashSet<u64>>,
+        handling: &SkewHandling,
+        skewed_rows: Count,
+    ) -> Self {
+        Self {
+            heavy_keys,
+            broadcast: matches!(handling, SkewHandling::Broadcast(_)),
+            next_idx: 0,
+            skewed_rows,
+        }
+    }
+
+    /// Add row `index` to the indices of its output partitions if its key
+    /// `hash` is heavy, and return whether it was
+    pub(crate) fn route(
+        &mut self,
+        index: u64,
+        hash: u64,
+        indices: &mut [UInt64Builder],
+    ) -> bool {
+        if !self.heavy_keys.contains(&hash) {
+            return false;
+        }
+        if self.broadcast {
+            indices.iter_mut().for_each(|i| i.append_value(index));
+        } else {
+            indices[self.next_idx].append_value(index);
+            self.next_idx = (self.next_idx + 1) % indices.len();
+        }
+        self.skewed_rows.add(1);
+        true
+    }
+}
@@ -0,0 +1,846 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the shared execution plan, which executes its input once and
+//! replays the output to several consumers
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Debug};
+use std::fs::File;
+use std::io::BufReader;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+
+use super::metrics::{
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet,
+};
+use super::{
+    displayable, DisplayAs, DisplayFormatType, ExecutionPlan, ExecutionPlanProperties,
+    PlanProperties, RecordBatchStream, SendableRecordBatchStream, Statistics,
+};
+use crate::common::{IPCWriter, SpillCompression};
+use crate::stream::RecordBatchReceiverStream;
+
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::reader::FileReader;
+use arrow::record_batch::RecordBatch;
+use datafusion_common::utils::DataPtr;
+use datafusion_common::{internal_err, DataFusionError, Result};
+use datafusion_common_runtime::SpawnedTask;
+use datafusion_execution::disk_manager::RefCountedTempFile;
+use datafusion_execution::TaskContext;
+
+use futures::future::{select, select_all, Either};
+use futures::{FutureExt, Stream, StreamExt};
+use log::debug;
+use parking_lot::Mutex;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::Notify;
+
+/// Number of batches buffered in memory for each consumer of a shared
+/// partition
+const CHANNEL_CAPACITY: usize = 2;
+
+/// The input of a [`SharedExec`], shared between all of its consumers.
+///
+/// Each partition of `input` is executed once, by whichever consumer asks
+/// for it first, for all the consumers alive at that time. A consumer
+/// executing a partition again reads it directly from `input`.
+///
+/// Every batch produced is sent to each consumer through a bounded
+/// channel, waiting for the consumers to read it. If the channel of a
+/// consumer is full while another consumer waits for the next batch, the
+/// rest of the partition is spilled to disk for the first consumer, which
+/// reads it from there after the partition completes. Consumers can
+/// therefore make progress independently of each other (for example, when
+/// one consumer is the build side and another the probe side of the same
+/// join).
+pub struct SharedInput {
+    /// The plan executed once on behalf of all consumers
+    input: Arc<dyn ExecutionPlan>,
+    /// Index of each live consumer, with the number of [`SharedExec`]s
+    /// holding it
+    consumers: Mutex<BTreeMap<usize, usize>>,
+    /// Index handed out to the next [`SharedExec`] created over this input
+    next_consumer: AtomicUsize,
+    /// The input replacing this one in consumers whose input was rewritten
+    rewritten: Mutex<Option<Arc<SharedInput>>>,
+    /// Inputs rewritten for other consumers, replaced by this one
+    aliases: Mutex<Vec<Weak<dyn ExecutionPlan>>>,
+    /// Per partition state, created by the first consumer executing it
+    partitions: Mutex<Vec<Option<SharedPartition>>>,
+}
+
+impl SharedInput {
+    /// Create a new shared input, read by every [`SharedExec`] created
+    /// over it
+    pub fn new(input: Arc<dyn ExecutionPlan>) -> Self {
+        let partition_count = input.output_partitioning().partition_count();
+        Self {
+            input,
+            consumers: Mutex::new(BTreeMap::new()),
+            next_consumer: AtomicUsize::new(0),
+            rewritten: Mutex::new(None),
+            aliases: Mutex::new(vec![]),
+            partitions: Mutex::new((0..partition_count).map(|_| None).collect()),
+        }
+    }
+
+    /// The plan whose output is shared
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// Number of consumers reading this input
+    pub fn consumers(&self) -> usize {
+        self.consumers.lock().len()
+    }
+
+    fn register(&self, consumer: usize) {
+        self.next_consumer
+            .fetch_max(consumer + 1, Ordering::Relaxed);
+        *self.consumers.lock().entry(consumer).or_default() += 1;
+    }
+
+    fn deregister(&self, consumer: usize) {
+        {
+            let mut consumers = self.consumers.lock();
+            let Some(count) = consumers.get_mut(&consumer) else {
+                return;
+            };
+            *count -= 1;
+            if *count > 0 {
+                return;
+            }
+            consumers.remove(&consumer);
+        }
+        // Stop buffering the partitions the consumer did not execute
+        for state in self.partitions.lock().iter_mut().flatten() {
+            state.receivers.remove(&consumer);
+        }
+    }
+
+    /// Return the shared input of a consumer whose input was rewritten
+    /// to `input`.
+    ///
+    /// Physical optimizer rules usually rewrite the input of every
+    /// consumer alike, so the input rewritten for the first consumer is
+    /// shared by the others, which keep reading the same output. A
+    /// consumer whose input was rewritten differently stops sharing it,
+    /// and reads its own input instead.
+    fn rewrite(&self, input: Arc<dyn ExecutionPlan>) -> Arc<SharedInput> {
+        let mut latest = {
+            let mut rewritten = self.rewritten.lock();
+            match rewritten.as_ref() {
+                Some(rewritten) => Arc::clone(rewritten),
+                None => {
+                    let shared = Arc::new(SharedInput::new(input));
+                    *rewritten = Some(Arc::clone(&shared));
+                    return shared;
+                }
+            }
+        };
+        loop {
+            let next = latest.rewritten.lock().clone();
+            match next {
+                Some(next) => latest = next,
+                None => break,
+            }
+        }
+        if !same_plan(&latest.input, &input) {
+            debug!("SharedExec consumer input rewritten differently, no longer shared");
+            return Arc::new(SharedInput::new(input));
+        }
+        latest.aliases.lock().push(Arc::downgrade(&input));
+        latest
+    }
+
+    /// Returns true if `input` is the input of this shared input, or an
+    /// input it replaced
+    fn is_input(&self, input: &Arc<dyn ExecutionPlan>) -> bool {
+        Arc::data_ptr_eq(&self.input, input)
+            || self.aliases.lock().iter().any(|alias| {
+                alias.as_ptr() as *const () == Arc::as_ptr(input) as *const ()
+            })
+    }
+
+    /// Return the receiving end of `partition` for `consumer`, starting
+    /// the execution of that partition if this is the first consumer to
+    /// ask for it. Returns `None` if `consumer` can not read the shared
+    /// output, because it already read it or started after it.
+    fn subscribe(
+        &self,
+        consumer: usize,
+        partition: usize,
+        context: Arc<TaskContext>,
+        metrics: &ExecutionPlanMetricsSet,
+    ) -> Result<Option<Subscription>> {
+        let mut partitions = self.partitions.lock();
+        let Some(state) = partitions.get_mut(partition) else {
+            return internal_err!("SharedExec got an invalid partition {partition}");
+        };
+
+        let state = state.get_or_insert_with(|| {
+            let (senders, receivers): (Vec<_>, HashMap<_, _>) = self
+                .consumers
+                .lock()
+                .keys()
+                .map(|&consumer| {
+                    let (tx, rx) = channel(CHANNEL_CAPACITY);
+                    (tx, (consumer, rx))
+                })
+                .unzip();
+
+            let demand = Arc::new(Notify::new());
+            let task = SpawnedTask::spawn(pull_from_input(
+                Arc::clone(&self.input),
+                partition,
+                context,
+                senders,
+                Arc::clone(&demand),
+                SpillMetrics::new(metrics, partition),
+            ));
+
+            SharedPartition {
+                receivers,
+                demand,
+                task: Some(Arc::new(task)),
+            }
+        });
+
+        let Some(receiver) = state.receivers.remove(&consumer) else {
+            return Ok(None);
+        };
+
+        let task = match state.receivers.is_empty() {
+            // The last consumer subscribed: from now on the task is kept
+            // alive only by the streams reading from it
+            true => state.task.take(),
+            false => state.task.clone(),
+        };
+        let Some(task) = task else {
+            return internal_err!("SharedExec partition {partition} has no input task");
+        };
+
+        Ok(Some(Subscription {
+            receiver,
+            demand: Arc::clone(&state.demand),
+            task,
+        }))
+    }
+}
+
+impl Debug for SharedInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedInput")
+            .field("input", &self.input)
+            .field("consumers", &self.consumers())
+            .finish()
+    }
+}
+
+/// Returns true if `left` and `right` are the same plan, i.e. they have
+/// the same schema and display the same
+fn same_plan(left: &Arc<dyn ExecutionPlan>, right: &Arc<dyn ExecutionPlan>) -> bool {
+    Arc::data_ptr_eq(left, right)
+        || (left.schema() == right.schema()
+            && displayable(left.as_ref()).indent(true).to_string()
+                == displayable(right.as_ref()).indent(true).to_string())
+}
+
+/// The execution state of one partition of a [`SharedInput`]
+struct SharedPartition {
+    /// Receivers not yet taken by their consumers
+    receivers: HashMap<usize, Receiver<SharedMessage>>,
+    /// Notified by the consumers waiting for a batch
+    demand: Arc<Notify>,
+    /// The task driving the input partition, held until every consumer
+    /// has subscribed
+    task: Option<Arc<SpawnedTask<()>>>,
+}
+
+/// The receiving end of one consumer of a shared partition
+struct Subscription {
+    receiver: Receiver<SharedMessage>,
+    demand: Arc<Notify>,
+    task: Arc<SpawnedTask<()>>,
+}
+
+/// Message sent from the task driving a shared partition to its consumers
+enum SharedMessage {
+    /// A batch buffered in memory
+    Batch(RecordBatch),
+    /// The remaining batches of the partition, spilled to disk from the
+    /// given batch on
+    Spill(Arc<RefCountedTempFile>, usize),
+    /// The input failed
+    Error(Arc<DataFusionError>),
+}
+
+/// The sending end of one consumer of a shared partition
+struct SharedSender {
+    sender: Sender<SharedMessage>,
+    /// Index in the spill file of the first batch the consumer did not
+    /// receive, once its channel was full
+    spilled_from: Option<usize>,
+}
+
+/// Spill metrics of a shared partition
+struct SpillMetrics {
+    spill_count: Count,
+    spilled_bytes: Count,
+    spilled_disk_bytes: Count,
+}
+
+impl SpillMetrics {
+    fn new(metrics: &ExecutionPlanMetricsSet, partition: usize) -> Self {
+        Self {
+            spill_count: MetricBuilder::new(metrics).spill_count(partition),
+            spilled_bytes: MetricBuilder::new(metrics).spilled_bytes(partition),
+            spilled_disk_bytes: MetricBuilder::new(metrics).spilled_disk_bytes(partition),
+        }
+    }
+}
+
+/// Drive `partition` of `input` to completion, broadcasting every batch
+/// to all `senders`.
+async fn pull_from_input(
+    input: Arc<dyn ExecutionPlan>,
+    partition: usize,
+    context: Arc<TaskContext>,
+    senders: Vec<Sender<SharedMessage>>,
+    demand: Arc<Notify>,
+    metrics: SpillMetrics,
+) {
+    let mut senders: Vec<_> = senders
+        .into_iter()
+        .map(|sender| SharedSender {
+            sender,
+            spilled_from: None,
+        })
+        .collect();
+    let result =
+        broadcast_input(input, partition, context, &mut senders, &demand, &metrics)
+            .await
+            .map(|spill| spill.map(Arc::new))
+            .map_err(Arc::new);
+
+    // The consumers may be read one after the other, so the spill file
+    // and errors are sent to all of them concurrently. The others are
+    // done once their sender is dropped
+    let sends = senders.into_iter().filter_map(|tx| {
+        let message = match (&result, tx.spilled_from) {
+            (Err(e), _) => SharedMessage::Error(Arc::clone(e)),
+            (Ok(Some(spill)), Some(from)) => {
+                SharedMessage::Spill(Arc::clone(spill), from)
+            }
+            _ => return None,
+        };
+        Some(async move { tx.sender.send(message).await.ok() })
+    });
+    futures::future::join_all(sends).await;
+}
+
+/// Send the output of `partition` to all `senders`. Once the channel of
+/// a consumer is full while another consumer waits for a batch, the
+/// remaining batches of the first consumer are written to a spill file
+/// instead. Returns the spill file, if any.
+async fn broadcast_input(
+    input: Arc<dyn ExecutionPlan>,
+    partition: usize,
+    context: Arc<TaskContext>,
+    senders: &mut [SharedSender],
+    demand: &Notify,
+    metrics: &SpillMetrics,
+) -> Result<Option<RefCountedTempFile>> {
+    let schema = input.schema();
+    let mut stream = input.execute(partition, Arc::clone(&context))?;
+    let mut spill: Option<(RefCountedTempFile, IPCWriter)> = None;
+
+    while let Some(batch) = stream.next().await {
+        let batch = batch?;
+
+        // Stop early once every consumer has hung up (e.g. LIMIT)
+        if senders.iter().all(|tx| tx.sender.is_closed()) {
+            return Ok(None);
+        }
+
+        // Consumers whose channel is full
+        let mut full = vec![];
+        for (index, tx) in senders.iter().enumerate() {
+            if tx.spilled_from.is_none() {
+                let message = SharedMessage::Batch(batch.clone());
+                if let Err(TrySendError::Full(_)) = tx.sender.try_send(message) {
+                    full.push(index);
+                }
+            }
+        }
+
+        // Wait for these consumers to read a batch, unless another
+        // consumer waits for the next one
+        while !full.is_empty() {
+            let reserve = full.iter().map(|&index| {
+                let reserve = senders[index].sender.reserve();
+                reserve.map(move |permit| (index, permit)).boxed()
+            });
+            let sent = match select(select_all(reserve), pin!(demand.notified())).await {
+                Either::Left((((index, permit), _, _), _)) => {
+                    if let Ok(permit) = permit {
+                        permit.send(SharedMessage::Batch(batch.clone()));
+                    }
+                    Some(index)
+                }
+                Either::Right(_) => None,
+            };
+            if let Some(index) = sent {
+                full.retain(|&i| i != index);
+                continue;
+            }
+
+            let starving = senders.iter().enumerate().any(|(index, tx)| {
+                tx.spilled_from.is_none()
+                    && !full.contains(&index)
+                    && !tx.sender.is_closed()
+                    && tx.sender.capacity() == CHANNEL_CAPACITY
+            });
+            if starving {
+                let (_, writer) = match &mut spill {
+                    Some(spill) => spill,
+                    None => spill.insert(create_spill(&context, &schema)?),
+                };
+                for index in full.drain(..) {
+                    senders[index].spilled_from = Some(writer.num_batches as usize);
+                }
+            }
+        }
+
+        if let Some((file, writer)) = spill.as_mut() {
+            writer.write(&batch)?;
+            file.update_disk_usage()?;
+        }
+    }
+
+    let Some((mut file, mut writer)) = spill else {
+        return Ok(None);
+    };
+    writer.finish()?;
+    file.update_disk_usage()?;
+    metrics.spill_count.add(1);
+    metrics.spilled_bytes.add(writer.num_bytes as usize);
+    metrics
+        .spilled_disk_bytes
+        .add(file.current_disk_usage() as usize);
+    debug!(
+        "SharedExec spilled {} batches of partition {partition} ({} rows)",
+        writer.num_batches, writer.num_rows
+    );
+    Ok(Some(file))
+}
+
+/// Create a spill file for the output of a shared partition
+fn create_spill(
+    context: &TaskContext,
+    schema: &SchemaRef,
+) -> Result<(RefCountedTempFile, IPCWriter)> {
+    let file = context
+        .runtime_env()
+        .disk_manager
+        .create_tmp_file("SharedExec")?;
+    let compression: SpillCompression = context
+        .session_config()
+        .options()
+        .execution
+        .spill_compression
+        .parse()?;
+    let writer =
+        IPCWriter::new_with_options(file.path(), schema, compression.write_options()?)?;
+    Ok((file, writer))
+}
+
+/// Replays the output of a [`SharedInput`] to one consumer.
+///
+/// Identical subplans, such as a common table expression referenced
+/// several times in a query, are planned as a single [`SharedInput`]
+/// read by one `SharedExec` per occurrence. The input is executed only
+/// once, and its output is buffered for every consumer.
+///
+/// The shared input is the child of every consumer. When physical
+/// optimizer rules rewrite it, the consumers move to the same rewritten
+/// input, see [`ExecutionPlan::with_new_children`].
+#[derive(Debug)]
+pub struct SharedExec {
+    /// The input shared with the other consumers
+    shared: Arc<SharedInput>,
+    /// Index of this consumer
+    consumer: usize,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+    /// Cache holding plan properties like equivalences, output partitioning etc.
+    cache: PlanProperties,
+}
+
+impl SharedExec {
+    /// Create a new consumer of `shared`
+    pub fn new(shared: Arc<SharedInput>) -> Self {
+        let consumer = shared.next_consumer.fetch_add(1, Ordering::Relaxed);
+        Self::new_consumer(shared, consumer)
+    }
+
+    fn new_consumer(shared: Arc<SharedInput>, consumer: usize) -> Self {
+        shared.register(consumer);
+        let cache = shared.input.properties().clone();
+        Self {
+            shared,
+            consumer,
+            metrics: ExecutionPlanMetricsSet::new(),
+            cache,
+        }
+    }
+
+    /// The input shared with the other consumers
+    pub fn shared(&self) -> &Arc<SharedInput> {
+        &self.shared
+    }
+
+    /// Index of this consumer
+    pub fn consumer(&self) -> usize {
+        self.consumer
+    }
+}
+
+impl Drop for SharedExec {
+    fn drop(&mut self) {
+        self.shared.deregister(self.consumer);
+    }
+}
+
+impl DisplayAs for SharedExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "SharedExec: consumer={}/{}",
+                    self.consumer,
+                    self.shared.consumers()
+                )
+            }
+        }
+    }
+}
+
+impl ExecutionPlan for SharedExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.cache
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![Arc::clone(&self.shared.input)]
+    }
+
+    /// Requirements of the parents are not pushed down to the shared
+    /// input, so that the input of every consumer is rewritten alike
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return internal_err!("SharedExec wrong number of children");
+        }
+        let input = children.swap_remove(0);
+        // Rules rebuilding the plan from the inputs they rewrote may pass
+        // an input that was replaced by the input of another consumer
+        if self.shared.is_input(&input) {
+            return Ok(self);
+        }
+        let shared = self.shared.rewrite(input);
+        Ok(Arc::new(SharedExec::new_consumer(shared, self.consumer)))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let subscription = self.shared.subscribe(
+            self.consumer,
+            partition,
+            Arc::clone(&context),
+            &self.metrics,
+        )?;
+        let Some(Subscription {
+            receiver,
+            demand,
+            task,
+        }) = subscription
+        else {
+            debug!(
+                "SharedExec consumer {} executes partition {partition} again",
+                self.consumer
+            );
+            return self.shared.input.execute(partition, context);
+        };
+
+        Ok(Box::pin(SharedStream {
+            schema: self.schema(),
+            receiver,
+            demand,
+            spill: None,
+            _task: task,
+            baseline_metrics: BaselineMetrics::new(&self.metrics, partition),
+        }))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Result<Statistics> {
+        self.shared.input.statistics()
+    }
+}
+
+/// Stream of one consumer of a shared partition
+struct SharedStream {
+    schema: SchemaRef,
+    receiver: Receiver<SharedMessage>,
+    /// Notified when waiting for the next batch
+    demand: Arc<Notify>,
+    /// Set once the remaining batches have to be read from disk
+    spill: Option<SendableRecordBatchStream>,
+    /// Keeps the input task running while this stream is alive
+    _task: Arc<SpawnedTask<()>>,
+    baseline_metrics: BaselineMetrics,
+}
+
+impl SharedStream {
+    fn poll_next_inner(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<RecordBatch>>> {
+        loop {
+            if let Some(spill) = self.spill.as_mut() {
+                return spill.poll_next_unpin(cx);
+            }
+
+            let Poll::Ready(message) = self.receiver.poll_recv(cx) else {
+                self.demand.notify_one();
+                return Poll::Pending;
+            };
+            match message {
+                Some(SharedMessage::Batch(batch)) => return Poll::Ready(Some(Ok(batch))),
+                Some(SharedMessage::Spill(file, from)) => {
+                    self.spill = Some(read_shared_spill(file, from, self.schema.clone()));
+                }
+                Some(SharedMessage::Error(e)) => {
+                    return Poll::Ready(Some(Err(DataFusionError::External(Box::new(e)))))
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl Stream for SharedStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let poll = self.poll_next_inner(cx);
+        self.baseline_metrics.record_poll(poll)
+    }
+}
+
+impl RecordBatchStream for SharedStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// Read the batches of a spill file from batch `from` on. The file may be
+/// read concurrently by other consumers
+fn read_shared_spill(
+    file: Arc<RefCountedTempFile>,
+    from: usize,
+    schema: SchemaRef,
+) -> SendableRecordBatchStream {
+    let mut builder = RecordBatchReceiverStream::builder(schema, 2);
+    let sender = builder.tx();
+    builder.spawn_blocking(move || {
+        let mut reader =
+            FileReader::try_new(BufReader::new(File::open(file.path())?), None)?;
+        reader.set_index(from)?;
+        for batch in reader {
+            if sender.blocking_send(batch.map_err(Into::into)).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    });
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collect;
+    use crate::memory::MemoryExec;
+    use crate::test::build_table_i32;
+
+    use datafusion_common::assert_batches_eq;
+
+    /// A shared input of `batches` copies of the same batch
+    fn shared_input(batches: usize) -> Result<Arc<SharedInput>> {
+        Ok(Arc::new(SharedInput::new(memory_input(batches)?)))
+    }
+
+    fn memory_input(batches: usize) -> Result<Arc<dyn ExecutionPlan>> {
+        let batch =
+            build_table_i32(("a", &vec![1, 2]), ("b", &vec![3, 4]), ("c", &vec![5, 6]));
+        let schema = batch.schema();
+        let partition = vec![batch; batches];
+        Ok(Arc::new(MemoryExec::try_new(&[partition], schema, None)?))
+    }
+
+    fn expected(batches: usize) -> Vec<&'static str> {
+        let mut expected = vec!["+---+---+---+", "| a | b | c |", "+---+---+---+"];
+        for _ in 0..batches {
+            expected.extend(["| 1 | 3 | 5 |", "| 2 | 4 | 6 |"]);
+        }
+        expected.push("+---+---+---+");
+        expected
+    }
+
+    /// Read the consumers one after the other, so that the output has to
+    /// be buffered for the second one, and return the spill count
+    async fn check_consumers(batches: usize) -> Result<usize> {
+        let shared = shared_input(batches)?;
+        let first = Arc::new(SharedExec::new(Arc::clone(&shared)));
+        let second = Arc::new(SharedExec::new(Arc::clone(&shared)));
+        assert_eq!(shared.consumers(), 2);
+
+        let context = Arc::new(TaskContext::default());
+        let output = collect(first.clone(), Arc::clone(&context)).await?;
+        assert_batches_eq!(expected(batches), &output);
+        let output = collect(second.clone(), Arc::clone(&context)).await?;
+        assert_batches_eq!(expected(batches), &output);
+
+        // Executing the partition again reads the input directly
+        let output = collect(second, context).await?;
+        assert_batches_eq!(expected(batches), &output);
+
+        Ok(first.metrics().unwrap().spill_count().unwrap())
+    }
+
+    #[tokio::test]
+    async fn shared_in_memory() -> Result<()> {
+        assert_eq!(check_consumers(CHANNEL_CAPACITY).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn shared_spill() -> Result<()> {
+        assert_eq!(check_consumers(CHANNEL_CAPACITY + 3).await?, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dropped_consumer() -> Result<()> {
+        let shared = shared_input(CHANNEL_CAPACITY + 3)?;
+        let first = Arc::new(SharedExec::new(Arc::clone(&shared)));
+        drop(SharedExec::new(Arc::clone(&shared)));
+        assert_eq!(shared.consumers(), 1);
+
+        // Nothing is buffered for the dropped consumer
+        let output = collect(first.clone(), Arc::new(TaskContext::default())).await?;
+        assert_batches_eq!(expected(CHANNEL_CAPACITY + 3), &output);
+        assert_eq!(first.metrics().unwrap().spill_count(), Some(0));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rewritten_consumers() -> Result<()> {
+        let shared = shared_input(1)?;
+        let first: Arc<dyn ExecutionPlan> =
+            Arc::new(SharedExec::new(Arc::clone(&shared)));
+        let second: Arc<dyn ExecutionPlan> =
+            Arc::new(SharedExec::new(Arc::clone(&shared)));
+
+        // Both consumers move to the input rewritten for the first one
+        let rewritten = memory_input(1)?;
+        let replaced = memory_input(1)?;
+        let first = first.with_new_children(vec![Arc::clone(&rewritten)])?;
+        let second = second.with_new_children(vec![Arc::clone(&replaced)])?;
+        // and keep it when rebuilt from the input it replaced
+        let second = second.clone().with_new_children(vec![replaced])?;
+        assert_eq!(shared.consumers(), 0);
+
+        let first = first.as_any().downcast_ref::<SharedExec>().unwrap();
+        let second = second.as_any().downcast_ref::<SharedExec>().unwrap();
+        assert!(Arc::ptr_eq(first.shared(), second.shared()));
+        assert!(Arc::ptr_eq(first.shared().input(), &rewritten));
+        assert_eq!(first.shared().consumers(), 2);
+        assert_eq!((first.consumer(), second.consumer()), (0, 1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn differently_rewritten_consumers() -> Result<()> {
+        let shared = shared_input(1)?;
+        let first: Arc<dyn ExecutionPlan> =
+            Arc::new(SharedExec::new(Arc::clone(&shared)));
+        let second: Arc<dyn ExecutionPlan> =
+            Arc::new(SharedExec::new(Arc::clone(&shared)));
+
+        // The second consumer keeps the different input rewritten for it
+        let rewritten = memory_input(1)?;
+        let different = memory_input(2)?;
+        let first = first.with_new_children(vec![Arc::clone(&rewritten)])?;
+        let second = second.with_new_children(vec![Arc::clone(&different)])?;
+        assert_eq!(shared.consumers(), 0);
+
+        let context = Arc::new(TaskContext::default());
+        let output = collect(Arc::clone(&second), context).await?;
+        assert_batches_eq!(expected(2), &output);
+
+        let first = first.as_any().downcast_ref::<SharedExec>().unwrap();
+        let second = second.as_any().downcast_ref::<SharedExec>().unwrap();
+        assert!(Arc::ptr_eq(first.shared().input(), &rewritten));
+        assert!(Arc::ptr_eq(second.shared().input(), &different));
+        assert_eq!(first.shared().consumers(), 1);
+        assert_eq!(second.shared().consumers(), 1);
+        Ok(())
+    }
+}
@@ -24,10 +24,10 @@ use std::fmt;
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
 use std::io::BufReader;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::sync::Arc;
 
-use crate::common::{spawn_buffered, IPCWriter};
+use crate::common::{spawn_buffered, IPCWriter, SpillCompression};
 use crate::expressions::PhysicalSortExpr;
 use crate::metrics::{
     BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet,
@@ -42,7 +42,7 @@ use crate::{
 };
 
 use arrow::compute::{concat_batches, lexsort_to_indices, take, SortColumn};
-use arrow::datatypes::SchemaRef;
+use arrow::datatypes::{Schema, SchemaRef};
 use arrow::ipc::reader::FileReader;
 use arrow::record_batch::RecordBatch;
 use arrow::row::{RowConverter, SortField};
@@ -52,7 +52,7 @@ use datafusion_common::{exec_err, DataFusionError, Result};
 use datafusion_common_runtime::SpawnedTask;
 use datafusion_execution::disk_manager::RefCountedTempFile;
 use datafusion_execution::memory_pool::{
-    human_readable_size, MemoryConsumer, MemoryReservation,
+    human_readable_size, MemoryConsumer, MemoryReservation, SpillRequest,
 };
 use datafusion_execution::runtime_env::RuntimeEnv;
 use datafusion_execution::TaskContext;
@@ -71,6 +71,9 @@ struct ExternalSorterMetrics {
 
     /// total spilled bytes during the execution of the operator
     spilled_bytes: Count,
+
+    /// total size of the spill files, after compression
+    spilled_disk_bytes: Count,
 }
 
 impl ExternalSorterMetrics {
@@ -79,6 +82,7 @@ impl ExternalSorterMetrics {
             baseline: BaselineMetrics::new(metrics, partition),
             spill_count: MetricBuilder::new(metrics).spill_count(partition),
             spilled_bytes: MetricBuilder::new(metrics).spilled_bytes(partition),
+            spilled_disk_bytes: MetricBuilder::new(metrics).spilled_disk_bytes(partition),
         }
     }
 }
@@ -234,6 +238,10 @@ struct ExternalSorter {
     /// the data will be concated and sorted in place rather than
     /// sort/merged.
     sort_in_place_threshold_bytes: usize,
+    /// The compression of the spill files
+    spill_compression: SpillCompression,
+    /// Memory the pool asked this sorter to release for other consumers
+    spill_request: Arc<SpillRequest>,
 }
 
 impl ExternalSorter {
@@ -248,13 +256,18 @@ impl ExternalSorter {
         fetch: Option<usize>,
         sort_spill_reservation_bytes: usize,
         sort_in_place_threshold_bytes: usize,
+        spill_compression: SpillCompression,
         metrics: &ExecutionPlanMetricsSet,
         runtime: Arc<RuntimeEnv>,
     ) -> Self {
         let metrics = ExternalSorterMetrics::new(metrics, partition_id);
-        let reservation = MemoryConsumer::new(format!("ExternalSorter[{partition_id}]"))
-            .with_can_spill(true)
-            .register(&runtime.memory_pool);
+        let spill_request = Arc::new(SpillRequest::new());
+        let mut consumer = MemoryConsumer::new(format!("ExternalSorter[{partition_id}]"))
+            .with_can_spill(true);
+        if runtime.disk_manager.tmp_files_enabled() {
+            consumer = consumer.with_spill_callback(spill_request.clone());
+        }
+        let reservation = consumer.register(&runtime.memory_pool);
 
         let merge_reservation =
             MemoryConsumer::new(format!("ExternalSorterMerge[{partition_id}]"))
@@ -274,6 +287,8 @@ impl ExternalSorter {
             batch_size,
             sort_spill_reservation_bytes,
             sort_in_place_threshold_bytes,
+            spill_compression,
+            spill_request,
         }
     }
 
@@ -284,6 +299,10 @@ impl ExternalSorter {
         if input.num_rows() == 0 {
             return Ok(());
         }
+        if self.spill_request.take() > 0 && !self.in_mem_batches.is_empty() {
+            debug!("Spilling sort data of ExternalSorter requested by the memory pool");
+            self.spill().await?;
+        }
         self.reserve_memory_for_merge()?;
 
         let size = input.get_array_memory_size();
@@ -392,10 +411,19 @@ impl ExternalSorter {
 
         let spillfile = self.runtime.disk_manager.create_tmp_file("Sorting")?;
         let batches = std::mem::take(&mut self.in_mem_batches);
-        spill_sorted_batches(batches, spillfile.path(), self.schema.clone()).await?;
+        let spillfile = spill_sorted_batches(
+            batches,
+            spillfile,
+            self.schema.clone(),
+            self.spill_compression,
+        )
+        .await?;
         let used = self.reservation.free();
         self.metrics.spill_count.add(1);
         self.metrics.spilled_bytes.add(used);
+        self.metrics
+            .spilled_disk_bytes
+            .add(spillfile.current_disk_usage() as usize);
         self.spills.push(spillfile);
         Ok(used)
     }
@@ -652,11 +680,20 @@ pub(crate) fn lexsort_to_indices_multi_columns(
 
 async fn spill_sorted_batches(
     batches: Vec<RecordBatch>,
-    path: &Path,
+    mut file: RefCountedTempFile,
     schema: SchemaRef,
-) -> Result<()> {
-    let path: PathBuf = path.into();
-    let task = SpawnedTask::spawn_blocking(move || write_sorted(batches, path, schema));
+    compression: SpillCompression,
+) -> Result<RefCountedTempFile> {
+    let task = SpawnedTask::spawn_blocking(move || {
+        let writer = write_spill(batches, &mut file, &schema, compression)?;
+        debug!(
+            "Spilled {} batches of total {} rows to disk, memory released {}",
+            writer.num_batches,
+            writer.num_rows,
+            human_readable_size(writer.num_bytes as usize),
+        );
+        Ok(file)
+    });
     match task.join().await {
         Ok(r) => r,
         Err(e) => exec_err!("Error occurred while spilling {e}"),
@@ -681,23 +718,26 @@ pub(crate) fn read_spill_as_stream(
     Ok(builder.build())
 }
 
-fn write_sorted(
-    batches: Vec<RecordBatch>,
-    path: PathBuf,
-    schema: SchemaRef,
-) -> Result<()> {
-    let mut writer = IPCWriter::new(path.as_ref(), schema.as_ref())?;
+/// Writes `batches` to the spill `file` in Arrow IPC format, accounting for
+/// the size of the file in the disk space used by its [`DiskManager`] as it
+/// grows. Returns the finished writer.
+///
+/// [`DiskManager`]: datafusion_execution::disk_manager::DiskManager
+pub(crate) fn write_spill(
+    batches: impl IntoIterator<Item = RecordBatch>,
+    file: &mut RefCountedTempFile,
+    schema: &Schema,
+    compression: SpillCompression,
+) -> Result<IPCWriter> {
+    let mut writer =
+        IPCWriter::new_with_options(file.path(), schema, compression.write_options()?)?;
     for batch in batches {
         writer.write(&batch)?;
+        file.update_disk_usage()?;
     }
     writer.finish()?;
-    debug!(
-        "Spilled {} batches of total {} rows to disk, memory released {}",
-        writer.num_batches,
-        writer.num_rows,
-        human_readable_size(writer.num_bytes as usize),
-    );
-    Ok(())
+    file.update_disk_usage()?;
+    Ok(writer)
 }
 
 fn read_spill(sender: Sender<Result<RecordBatch>>, path: &Path) -> Result<()> {
@@ -922,11 +962,13 @@ impl ExecutionPlan for SortExec {
                 partition,
             )?;
 
+            let cancellation = context.cancellation_token().clone();
             Ok(Box::pin(RecordBatchStreamAdapter::new(
                 self.schema(),
                 futures::stream::once(async move {
                     while let Some(batch) = input.next().await {
                         let batch = batch?;
+                        cancellation.check()?;
                         topk.insert_batch(batch)?;
                     }
                     topk.emit()
@@ -942,15 +984,18 @@ impl ExecutionPlan for SortExec {
                 self.fetch,
                 execution_options.sort_spill_reservation_bytes,
                 execution_options.sort_in_place_threshold_bytes,
+                execution_options.spill_compression.parse()?,
                 &self.metrics_set,
                 context.runtime_env(),
             );
 
+            let cancellation = context.cancellation_token().clone();
             Ok(Box::pin(RecordBatchStreamAdapter::new(
                 self.schema(),
                 futures::stream::once(async move {
                     while let Some(batch) = input.next().await {
                         let batch = batch?;
+                        cancellation.check()?;
                         sorter.insert_batch(batch).await?;
                     }
                     sorter.sort()
@@ -972,6 +1017,7 @@ impl ExecutionPlan for SortExec {
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::time::Duration;
 
     use super::*;
     use crate::coalesce_partitions::CoalescePartitionsExec;
@@ -985,8 +1031,11 @@ mod tests {
     use arrow::array::*;
     use arrow::compute::SortOptions;
     use arrow::datatypes::*;
+    use datafusion_common::assert_contains;
     use datafusion_common::cast::as_primitive_array;
+    use datafusion_execution::cancellation::CancellationToken;
     use datafusion_execution::config::SessionConfig;
+    use datafusion_execution::memory_pool::{FairSpillPool, MemoryPool};
     use datafusion_execution::runtime_env::RuntimeConfig;
 
     use futures::FutureExt;
@@ -1076,6 +1125,130 @@ mod tests {
         Ok(())
     }
 
+    /// Sorts 100 partitions with a memory limit forcing the sort to spill
+    async fn sort_spill(
+        session_config: SessionConfig,
+        rt_config: RuntimeConfig,
+    ) -> Result<(Vec<RecordBatch>, MetricsSet)> {
+        let sort_spill_reservation_bytes = session_config
+            .options()
+            .execution
+            .sort_spill_reservation_bytes;
+        let rt_config =
+            rt_config.with_memory_limit(sort_spill_reservation_bytes + 12288, 1.0);
+        let task_ctx = Arc::new(
+            TaskContext::default()
+                .with_session_config(session_config)
+                .with_runtime(Arc::new(RuntimeEnv::new(rt_config)?)),
+        );
+
+        let input = test::scan_partitioned(100);
+        let schema = input.schema();
+        let sort_exec = Arc::new(SortExec::new(
+            vec![PhysicalSortExpr {
+                expr: col("i", &schema)?,
+                options: SortOptions::default(),
+            }],
+            Arc::new(CoalescePartitionsExec::new(input)),
+        ));
+
+        let result = collect(sort_exec.clone(), task_ctx).await?;
+        Ok((result, sort_exec.metrics().unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_sort_spill_compression() -> Result<()> {
+        let mut disk_bytes = vec![];
+        for compression in ["uncompressed", "lz4_frame", "zstd"] {
+            let session_config = SessionConfig::new()
+                .set_str("datafusion.execution.spill_compression", compression);
+            let (result, metrics) =
+                sort_spill(session_config, RuntimeConfig::new()).await?;
+
+            let i = as_primitive_array::<Int32Type>(result[0].column(0))?;
+            assert_eq!(i.value(0), 0);
+            assert_eq!(metrics.output_rows().unwrap(), 10000);
+            assert!(metrics.spill_count().unwrap() > 0);
+            disk_bytes.push(metrics.spilled_disk_bytes().unwrap());
+        }
+
+        // the compressed spill files are smaller
+        assert!(disk_bytes[1] < disk_bytes[0], "{disk_bytes:?}");
+        assert!(disk_bytes[2] < disk_bytes[0], "{disk_bytes:?}");
+
+        let session_config = SessionConfig::new()
+            .set_str("datafusion.execution.spill_compression", "snappy");
+        let err = sort_spill(session_config, RuntimeConfig::new())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.strip_backtrace(),
+            "Error during planning: Unsupported spill compression 'snappy', expected one of 'uncompressed', 'lz4_frame' or 'zstd'"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sort_spill_disk_limit() -> Result<()> {
+        let rt_config = RuntimeConfig::new().with_max_temp_directory_size(1024);
+        let err = sort_spill(SessionConfig::new(), rt_config)
+            .await
+            .unwrap_err();
+        assert_contains!(
+            err.to_string(),
+            "Resources exhausted: The temporary files of the DiskManager use"
+        );
+        assert_contains!(err.to_string(), "which exceeds its limit of 1024 bytes");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sort_spill_request() -> Result<()> {
+        let pool_size = 100_000;
+        let pool: Arc<dyn MemoryPool> = Arc::new(FairSpillPool::new(pool_size));
+        let runtime = Arc::new(RuntimeEnv::new(
+            RuntimeConfig::new().with_memory_pool(pool.clone()),
+        )?);
+        let batch = test::make_partition(1000);
+        let schema = batch.schema();
+        let mut sorter = ExternalSorter::new(
+            0,
+            schema.clone(),
+            vec![PhysicalSortExpr {
+                expr: col("i", &schema)?,
+                options: SortOptions::default(),
+            }],
+            8192,
+            None,
+            0,
+            1024 * 1024,
+            SpillCompression::default(),
+            &ExecutionPlanMetricsSet::new(),
+            runtime,
+        );
+        sorter.insert_batch(batch.clone()).await?;
+        let batch_size = sorter.used();
+
+        // an unspillable consumer asks the sorter to release memory, which it
+        // spills when it receives its next batch
+        let mut join = MemoryConsumer::new("join").register(&pool);
+        let join_size = pool_size - batch_size * 3 / 2;
+        let insert = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            sorter.insert_batch(batch).await
+        };
+        let (grown, inserted) = tokio::join!(
+            join.try_grow_with_spill(join_size, Duration::from_secs(10)),
+            insert
+        );
+        grown?;
+        inserted?;
+        assert_eq!(sorter.spill_count(), 1);
+        assert_eq!(sorter.used(), batch_size);
+        assert_eq!(pool.reserved(), join_size + batch_size);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_sort_fetch_memory_calculation() -> Result<()> {
         // This test mirrors down the size from the example above.
@@ -1395,4 +1568,28 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_cancellation_token() -> Result<()> {
+        let token = CancellationToken::new();
+        let task_ctx =
+            Arc::new(TaskContext::default().with_cancellation_token(token.clone()));
+        let partitions = vec![test::make_partition(10), test::make_partition(10)];
+        let schema = partitions[0].schema();
+        let input = Arc::new(MemoryExec::try_new(&[partitions], schema.clone(), None)?);
+        let sort_exec = Arc::new(SortExec::new(
+            vec![PhysicalSortExpr {
+                expr: col("i", &schema)?,
+                options: SortOptions::default(),
+            }],
+            input,
+        ));
+
+        // the sort stops buffering its input once the query is cancelled
+        token.cancel();
+        let stream = sort_exec.execute(0, task_ctx)?;
+        let err = crate::common::collect(stream).await.unwrap_err();
+        assert_eq!(err.strip_backtrace(), "Cancelled: the query was cancelled");
+        Ok(())
+    }
 }
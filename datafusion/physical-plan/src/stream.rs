@@ -79,6 +79,10 @@ impl<O: Send + 'static> ReceiverStreamBuilder<O> {
         F: Future<Output = Result<()>>,
         F: Send + 'static,
     {
+        // the spans of the plans executed by the task are children of the
+        // current span
+        #[cfg(feature = "tracing")]
+        let task = tracing::Instrument::in_current_span(task);
         self.join_set.spawn(task);
     }
 
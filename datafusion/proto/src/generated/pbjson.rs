@@ -2575,6 +2575,118 @@ impl<'de> serde::Deserialize<'de> for BinaryExprNode {
         deserializer.deserialize_struct("datafusion.BinaryExprNode", FIELDS, GeneratedVisitor)
     }
 }
+impl serde::Serialize for BucketSpec {
+    #[allow(deprecated)]
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut len = 0;
+        if !self.columns.is_empty() {
+            len += 1;
+        }
+        if self.num_buckets != 0 {
+            len += 1;
+        }
+        let mut struct_ser = serializer.serialize_struct("datafusion.BucketSpec", len)?;
+        if !self.columns.is_empty() {
+            struct_ser.serialize_field("columns", &self.columns)?;
+        }
+        if self.num_buckets != 0 {
+            #[allow(clippy::needless_borrow)]
+            struct_ser.serialize_field("numBuckets", ToString::to_string(&self.num_buckets).as_str())?;
+        }
+        struct_ser.end()
+    }
+}
+impl<'de> serde::Deserialize<'de> for BucketSpec {
+    #[allow(deprecated)]
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        const FIELDS: &[&str] = &[
+            "columns",
+            "num_buckets",
+            "numBuckets",
+        ];
+
+        #[allow(clippy::enum_variant_names)]
+        enum GeneratedField {
+            Columns,
+            NumBuckets,
+        }
+        impl<'de> serde::Deserialize<'de> for GeneratedField {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct GeneratedVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+                    type Value = GeneratedField;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        write!(formatter, "expected one of: {:?}", &FIELDS)
+                    }
+
+                    #[allow(unused_variables)]
+                    fn visit_str<E>(self, value: &str) -> std::result::Result<GeneratedField, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "columns" => Ok(GeneratedField::Columns),
+                            "numBuckets" | "num_buckets" => Ok(GeneratedField::NumBuckets),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+                deserializer.deserialize_identifier(GeneratedVisitor)
+            }
+        }
+        struct GeneratedVisitor;
+        impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+            type Value = BucketSpec;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("struct datafusion.BucketSpec")
+            }
+
+            fn visit_map<V>(self, mut map_: V) -> std::result::Result<BucketSpec, V::Error>
+                where
+                    V: serde::de::MapAccess<'de>,
+            {
+                let mut columns__ = None;
+                let mut num_buckets__ = None;
+                while let Some(k) = map_.next_key()? {
+                    match k {
+                        GeneratedField::Columns => {
+                            if columns__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("columns"));
+                            }
+                            columns__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::NumBuckets => {
+                            if num_buckets__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("numBuckets"));
+                            }
+                            num_buckets__ = 
+                                Some(map_.next_value::<::pbjson::private::NumberDeserialize<_>>()?.0)
+                            ;
+                        }
+                    }
+                }
+                Ok(BucketSpec {
+                    columns: columns__.unwrap_or_default(),
+                    num_buckets: num_buckets__.unwrap_or_default(),
+                })
+            }
+        }
+        deserializer.deserialize_struct("datafusion.BucketSpec", FIELDS, GeneratedVisitor)
+    }
+}
 impl serde::Serialize for BuiltInWindowFunction {
     #[allow(deprecated)]
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -4224,6 +4336,15 @@ impl serde::Serialize for CopyToNode {
         if !self.partition_by.is_empty() {
             len += 1;
         }
+        if !self.sort_by.is_empty() {
+            len += 1;
+        }
+        if self.bucket_by.is_some() {
+            len += 1;
+        }
+        if self.written_files {
+            len += 1;
+        }
         if self.format_options.is_some() {
             len += 1;
         }
@@ -4237,6 +4358,15 @@ impl serde::Serialize for CopyToNode {
         if !self.partition_by.is_empty() {
             struct_ser.serialize_field("partitionBy", &self.partition_by)?;
         }
+        if !self.sort_by.is_empty() {
+            struct_ser.serialize_field("sortBy", &self.sort_by)?;
+        }
+        if let Some(v) = self.bucket_by.as_ref() {
+            struct_ser.serialize_field("bucketBy", v)?;
+        }
+        if self.written_files {
+            struct_ser.serialize_field("writtenFiles", &self.written_files)?;
+        }
         if let Some(v) = self.format_options.as_ref() {
             match v {
                 copy_to_node::FormatOptions::Csv(v) => {
@@ -4271,6 +4401,12 @@ impl<'de> serde::Deserialize<'de> for CopyToNode {
             "outputUrl",
             "partition_by",
             "partitionBy",
+            "sort_by",
+            "sortBy",
+            "bucket_by",
+            "bucketBy",
+            "written_files",
+            "writtenFiles",
             "csv",
             "json",
             "parquet",
@@ -4283,6 +4419,9 @@ impl<'de> serde::Deserialize<'de> for CopyToNode {
             Input,
             OutputUrl,
             PartitionBy,
+            SortBy,
+            BucketBy,
+            WrittenFiles,
             Csv,
             Json,
             Parquet,
@@ -4312,6 +4451,9 @@ impl<'de> serde::Deserialize<'de> for CopyToNode {
                             "input" => Ok(GeneratedField::Input),
                             "outputUrl" | "output_url" => Ok(GeneratedField::OutputUrl),
                             "partitionBy" | "partition_by" => Ok(GeneratedField::PartitionBy),
+                            "sortBy" | "sort_by" => Ok(GeneratedField::SortBy),
+                            "bucketBy" | "bucket_by" => Ok(GeneratedField::BucketBy),
+                            "writtenFiles" | "written_files" => Ok(GeneratedField::WrittenFiles),
                             "csv" => Ok(GeneratedField::Csv),
                             "json" => Ok(GeneratedField::Json),
                             "parquet" => Ok(GeneratedField::Parquet),
@@ -4339,6 +4481,9 @@ impl<'de> serde::Deserialize<'de> for CopyToNode {
                 let mut input__ = None;
                 let mut output_url__ = None;
                 let mut partition_by__ = None;
+                let mut sort_by__ = None;
+                let mut bucket_by__ = None;
+                let mut written_files__ = None;
                 let mut format_options__ = None;
                 while let Some(k) = map_.next_key()? {
                     match k {
@@ -4360,6 +4505,24 @@ impl<'de> serde::Deserialize<'de> for CopyToNode {
                             }
                             partition_by__ = Some(map_.next_value()?);
                         }
+                        GeneratedField::SortBy => {
+                            if sort_by__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("sortBy"));
+                            }
+                            sort_by__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::BucketBy => {
+                            if bucket_by__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("bucketBy"));
+                            }
+                            bucket_by__ = map_.next_value()?;
+                        }
+                        GeneratedField::WrittenFiles => {
+                            if written_files__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("writtenFiles"));
+                            }
+                            written_files__ = Some(map_.next_value()?);
+                        }
                         GeneratedField::Csv => {
                             if format_options__.is_some() {
                                 return Err(serde::de::Error::duplicate_field("csv"));
@@ -4401,6 +4564,9 @@ impl<'de> serde::Deserialize<'de> for CopyToNode {
                     input: input__,
                     output_url: output_url__.unwrap_or_default(),
                     partition_by: partition_by__.unwrap_or_default(),
+                    sort_by: sort_by__.unwrap_or_default(),
+                    bucket_by: bucket_by__,
+                    written_files: written_files__.unwrap_or_default(),
                     format_options: format_options__,
                 })
             }
@@ -6048,6 +6214,9 @@ impl serde::Serialize for CsvSinkExecNode {
         if self.sort_order.is_some() {
             len += 1;
         }
+        if self.written_files {
+            len += 1;
+        }
         let mut struct_ser = serializer.serialize_struct("datafusion.CsvSinkExecNode", len)?;
         if let Some(v) = self.input.as_ref() {
             struct_ser.serialize_field("input", v)?;
@@ -6061,6 +6230,9 @@ impl serde::Serialize for CsvSinkExecNode {
         if let Some(v) = self.sort_order.as_ref() {
             struct_ser.serialize_field("sortOrder", v)?;
         }
+        if self.written_files {
+            struct_ser.serialize_field("writtenFiles", &self.written_files)?;
+        }
         struct_ser.end()
     }
 }
@@ -6077,6 +6249,8 @@ impl<'de> serde::Deserialize<'de> for CsvSinkExecNode {
             "sinkSchema",
             "sort_order",
             "sortOrder",
+            "written_files",
+            "writtenFiles",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -6085,6 +6259,7 @@ impl<'de> serde::Deserialize<'de> for CsvSinkExecNode {
             Sink,
             SinkSchema,
             SortOrder,
+            WrittenFiles,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -6110,6 +6285,7 @@ impl<'de> serde::Deserialize<'de> for CsvSinkExecNode {
                             "sink" => Ok(GeneratedField::Sink),
                             "sinkSchema" | "sink_schema" => Ok(GeneratedField::SinkSchema),
                             "sortOrder" | "sort_order" => Ok(GeneratedField::SortOrder),
+                            "writtenFiles" | "written_files" => Ok(GeneratedField::WrittenFiles),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -6133,6 +6309,7 @@ impl<'de> serde::Deserialize<'de> for CsvSinkExecNode {
                 let mut sink__ = None;
                 let mut sink_schema__ = None;
                 let mut sort_order__ = None;
+                let mut written_files__ = None;
                 while let Some(k) = map_.next_key()? {
                     match k {
                         GeneratedField::Input => {
@@ -6159,6 +6336,12 @@ impl<'de> serde::Deserialize<'de> for CsvSinkExecNode {
                             }
                             sort_order__ = map_.next_value()?;
                         }
+                        GeneratedField::WrittenFiles => {
+                            if written_files__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("writtenFiles"));
+                            }
+                            written_files__ = Some(map_.next_value()?);
+                        }
                     }
                 }
                 Ok(CsvSinkExecNode {
@@ -6166,6 +6349,7 @@ impl<'de> serde::Deserialize<'de> for CsvSinkExecNode {
                     sink: sink__,
                     sink_schema: sink_schema__,
                     sort_order: sort_order__,
+                    written_files: written_files__.unwrap_or_default(),
                 })
             }
         }
@@ -8710,6 +8894,9 @@ impl serde::Serialize for FileScanExecConf {
         if !self.output_ordering.is_empty() {
             len += 1;
         }
+        if self.bucket_by.is_some() {
+            len += 1;
+        }
         let mut struct_ser = serializer.serialize_struct("datafusion.FileScanExecConf", len)?;
         if !self.file_groups.is_empty() {
             struct_ser.serialize_field("fileGroups", &self.file_groups)?;
@@ -8735,6 +8922,9 @@ impl serde::Serialize for FileScanExecConf {
         if !self.output_ordering.is_empty() {
             struct_ser.serialize_field("outputOrdering", &self.output_ordering)?;
         }
+        if let Some(v) = self.bucket_by.as_ref() {
+            struct_ser.serialize_field("bucketBy", v)?;
+        }
         struct_ser.end()
     }
 }
@@ -8757,6 +8947,8 @@ impl<'de> serde::Deserialize<'de> for FileScanExecConf {
             "objectStoreUrl",
             "output_ordering",
             "outputOrdering",
+            "bucket_by",
+            "bucketBy",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -8769,6 +8961,7 @@ impl<'de> serde::Deserialize<'de> for FileScanExecConf {
             TablePartitionCols,
             ObjectStoreUrl,
             OutputOrdering,
+            BucketBy,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -8798,6 +8991,7 @@ impl<'de> serde::Deserialize<'de> for FileScanExecConf {
                             "tablePartitionCols" | "table_partition_cols" => Ok(GeneratedField::TablePartitionCols),
                             "objectStoreUrl" | "object_store_url" => Ok(GeneratedField::ObjectStoreUrl),
                             "outputOrdering" | "output_ordering" => Ok(GeneratedField::OutputOrdering),
+                            "bucketBy" | "bucket_by" => Ok(GeneratedField::BucketBy),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -8825,6 +9019,7 @@ impl<'de> serde::Deserialize<'de> for FileScanExecConf {
                 let mut table_partition_cols__ = None;
                 let mut object_store_url__ = None;
                 let mut output_ordering__ = None;
+                let mut bucket_by__ = None;
                 while let Some(k) = map_.next_key()? {
                     match k {
                         GeneratedField::FileGroups => {
@@ -8878,6 +9073,12 @@ impl<'de> serde::Deserialize<'de> for FileScanExecConf {
                             }
                             output_ordering__ = Some(map_.next_value()?);
                         }
+                        GeneratedField::BucketBy => {
+                            if bucket_by__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("bucketBy"));
+                            }
+                            bucket_by__ = map_.next_value()?;
+                        }
                     }
                 }
                 Ok(FileScanExecConf {
@@ -8889,6 +9090,7 @@ impl<'de> serde::Deserialize<'de> for FileScanExecConf {
                     table_partition_cols: table_partition_cols__.unwrap_or_default(),
                     object_store_url: object_store_url__.unwrap_or_default(),
                     output_ordering: output_ordering__.unwrap_or_default(),
+                    bucket_by: bucket_by__,
                 })
             }
         }
@@ -8921,6 +9123,9 @@ impl serde::Serialize for FileSinkConfig {
         if self.overwrite {
             len += 1;
         }
+        if self.bucket_by.is_some() {
+            len += 1;
+        }
         let mut struct_ser = serializer.serialize_struct("datafusion.FileSinkConfig", len)?;
         if !self.object_store_url.is_empty() {
             struct_ser.serialize_field("objectStoreUrl", &self.object_store_url)?;
@@ -8940,6 +9145,9 @@ impl serde::Serialize for FileSinkConfig {
         if self.overwrite {
             struct_ser.serialize_field("overwrite", &self.overwrite)?;
         }
+        if let Some(v) = self.bucket_by.as_ref() {
+            struct_ser.serialize_field("bucketBy", v)?;
+        }
         struct_ser.end()
     }
 }
@@ -8961,6 +9169,8 @@ impl<'de> serde::Deserialize<'de> for FileSinkConfig {
             "table_partition_cols",
             "tablePartitionCols",
             "overwrite",
+            "bucket_by",
+            "bucketBy",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -8971,6 +9181,7 @@ impl<'de> serde::Deserialize<'de> for FileSinkConfig {
             OutputSchema,
             TablePartitionCols,
             Overwrite,
+            BucketBy,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -8998,6 +9209,7 @@ impl<'de> serde::Deserialize<'de> for FileSinkConfig {
                             "outputSchema" | "output_schema" => Ok(GeneratedField::OutputSchema),
                             "tablePartitionCols" | "table_partition_cols" => Ok(GeneratedField::TablePartitionCols),
                             "overwrite" => Ok(GeneratedField::Overwrite),
+                            "bucketBy" | "bucket_by" => Ok(GeneratedField::BucketBy),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -9023,6 +9235,7 @@ impl<'de> serde::Deserialize<'de> for FileSinkConfig {
                 let mut output_schema__ = None;
                 let mut table_partition_cols__ = None;
                 let mut overwrite__ = None;
+                let mut bucket_by__ = None;
                 while let Some(k) = map_.next_key()? {
                     match k {
                         GeneratedField::ObjectStoreUrl => {
@@ -9061,6 +9274,12 @@ impl<'de> serde::Deserialize<'de> for FileSinkConfig {
                             }
                             overwrite__ = Some(map_.next_value()?);
                         }
+                        GeneratedField::BucketBy => {
+                            if bucket_by__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("bucketBy"));
+                            }
+                            bucket_by__ = map_.next_value()?;
+                        }
                     }
                 }
                 Ok(FileSinkConfig {
@@ -9070,6 +9289,7 @@ impl<'de> serde::Deserialize<'de> for FileSinkConfig {
                     output_schema: output_schema__,
                     table_partition_cols: table_partition_cols__.unwrap_or_default(),
                     overwrite: overwrite__.unwrap_or_default(),
+                    bucket_by: bucket_by__,
                 })
             }
         }
@@ -9929,6 +10149,9 @@ impl serde::Serialize for HashJoinExecNode {
         if !self.projection.is_empty() {
             len += 1;
         }
+        if self.fetch != 0 {
+            len += 1;
+        }
         let mut struct_ser = serializer.serialize_struct("datafusion.HashJoinExecNode", len)?;
         if let Some(v) = self.left.as_ref() {
             struct_ser.serialize_field("left", v)?;
@@ -9958,6 +10181,10 @@ impl serde::Serialize for HashJoinExecNode {
         if !self.projection.is_empty() {
             struct_ser.serialize_field("projection", &self.projection)?;
         }
+        if self.fetch != 0 {
+            #[allow(clippy::needless_borrow)]
+            struct_ser.serialize_field("fetch", ToString::to_string(&self.fetch).as_str())?;
+        }
         struct_ser.end()
     }
 }
@@ -9979,6 +10206,7 @@ impl<'de> serde::Deserialize<'de> for HashJoinExecNode {
             "nullEqualsNull",
             "filter",
             "projection",
+            "fetch",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -9991,6 +10219,7 @@ impl<'de> serde::Deserialize<'de> for HashJoinExecNode {
             NullEqualsNull,
             Filter,
             Projection,
+            Fetch,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -10020,6 +10249,7 @@ impl<'de> serde::Deserialize<'de> for HashJoinExecNode {
                             "nullEqualsNull" | "null_equals_null" => Ok(GeneratedField::NullEqualsNull),
                             "filter" => Ok(GeneratedField::Filter),
                             "projection" => Ok(GeneratedField::Projection),
+                            "fetch" => Ok(GeneratedField::Fetch),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -10047,6 +10277,7 @@ impl<'de> serde::Deserialize<'de> for HashJoinExecNode {
                 let mut null_equals_null__ = None;
                 let mut filter__ = None;
                 let mut projection__ = None;
+                let mut fetch__ = None;
                 while let Some(k) = map_.next_key()? {
                     match k {
                         GeneratedField::Left => {
@@ -10100,6 +10331,14 @@ impl<'de> serde::Deserialize<'de> for HashJoinExecNode {
                                     .into_iter().map(|x| x.0).collect())
                             ;
                         }
+                        GeneratedField::Fetch => {
+                            if fetch__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("fetch"));
+                            }
+                            fetch__ = 
+                                Some(map_.next_value::<::pbjson::private::NumberDeserialize<_>>()?.0)
+                            ;
+                        }
                     }
                 }
                 Ok(HashJoinExecNode {
@@ -10111,6 +10350,7 @@ impl<'de> serde::Deserialize<'de> for HashJoinExecNode {
                     null_equals_null: null_equals_null__.unwrap_or_default(),
                     filter: filter__,
                     projection: projection__.unwrap_or_default(),
+                    fetch: fetch__.unwrap_or_default(),
                 })
             }
         }
@@ -12118,6 +12358,115 @@ impl<'de> serde::Deserialize<'de> for JoinSide {
         deserializer.deserialize_any(GeneratedVisitor)
     }
 }
+impl serde::Serialize for JoinSortOptions {
+    #[allow(deprecated)]
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut len = 0;
+        if self.descending {
+            len += 1;
+        }
+        if self.nulls_first {
+            len += 1;
+        }
+        let mut struct_ser = serializer.serialize_struct("datafusion.JoinSortOptions", len)?;
+        if self.descending {
+            struct_ser.serialize_field("descending", &self.descending)?;
+        }
+        if self.nulls_first {
+            struct_ser.serialize_field("nullsFirst", &self.nulls_first)?;
+        }
+        struct_ser.end()
+    }
+}
+impl<'de> serde::Deserialize<'de> for JoinSortOptions {
+    #[allow(deprecated)]
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        const FIELDS: &[&str] = &[
+            "descending",
+            "nulls_first",
+            "nullsFirst",
+        ];
+
+        #[allow(clippy::enum_variant_names)]
+        enum GeneratedField {
+            Descending,
+            NullsFirst,
+        }
+        impl<'de> serde::Deserialize<'de> for GeneratedField {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct GeneratedVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+                    type Value = GeneratedField;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        write!(formatter, "expected one of: {:?}", &FIELDS)
+                    }
+
+                    #[allow(unused_variables)]
+                    fn visit_str<E>(self, value: &str) -> std::result::Result<GeneratedField, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "descending" => Ok(GeneratedField::Descending),
+                            "nullsFirst" | "nulls_first" => Ok(GeneratedField::NullsFirst),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+                deserializer.deserialize_identifier(GeneratedVisitor)
+            }
+        }
+        struct GeneratedVisitor;
+        impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+            type Value = JoinSortOptions;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("struct datafusion.JoinSortOptions")
+            }
+
+            fn visit_map<V>(self, mut map_: V) -> std::result::Result<JoinSortOptions, V::Error>
+                where
+                    V: serde::de::MapAccess<'de>,
+            {
+                let mut descending__ = None;
+                let mut nulls_first__ = None;
+                while let Some(k) = map_.next_key()? {
+                    match k {
+                        GeneratedField::Descending => {
+                            if descending__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("descending"));
+                            }
+                            descending__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::NullsFirst => {
+                            if nulls_first__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("nullsFirst"));
+                            }
+                            nulls_first__ = Some(map_.next_value()?);
+                        }
+                    }
+                }
+                Ok(JoinSortOptions {
+                    descending: descending__.unwrap_or_default(),
+                    nulls_first: nulls_first__.unwrap_or_default(),
+                })
+            }
+        }
+        deserializer.deserialize_struct("datafusion.JoinSortOptions", FIELDS, GeneratedVisitor)
+    }
+}
 impl serde::Serialize for JoinType {
     #[allow(deprecated)]
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -12450,6 +12799,9 @@ impl serde::Serialize for JsonSinkExecNode {
         if self.sort_order.is_some() {
             len += 1;
         }
+        if self.written_files {
+            len += 1;
+        }
         let mut struct_ser = serializer.serialize_struct("datafusion.JsonSinkExecNode", len)?;
         if let Some(v) = self.input.as_ref() {
             struct_ser.serialize_field("input", v)?;
@@ -12463,6 +12815,9 @@ impl serde::Serialize for JsonSinkExecNode {
         if let Some(v) = self.sort_order.as_ref() {
             struct_ser.serialize_field("sortOrder", v)?;
         }
+        if self.written_files {
+            struct_ser.serialize_field("writtenFiles", &self.written_files)?;
+        }
         struct_ser.end()
     }
 }
@@ -12479,6 +12834,8 @@ impl<'de> serde::Deserialize<'de> for JsonSinkExecNode {
             "sinkSchema",
             "sort_order",
             "sortOrder",
+            "written_files",
+            "writtenFiles",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -12487,6 +12844,7 @@ impl<'de> serde::Deserialize<'de> for JsonSinkExecNode {
             Sink,
             SinkSchema,
             SortOrder,
+            WrittenFiles,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -12512,6 +12870,7 @@ impl<'de> serde::Deserialize<'de> for JsonSinkExecNode {
                             "sink" => Ok(GeneratedField::Sink),
                             "sinkSchema" | "sink_schema" => Ok(GeneratedField::SinkSchema),
                             "sortOrder" | "sort_order" => Ok(GeneratedField::SortOrder),
+                            "writtenFiles" | "written_files" => Ok(GeneratedField::WrittenFiles),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -12535,6 +12894,7 @@ impl<'de> serde::Deserialize<'de> for JsonSinkExecNode {
                 let mut sink__ = None;
                 let mut sink_schema__ = None;
                 let mut sort_order__ = None;
+                let mut written_files__ = None;
                 while let Some(k) = map_.next_key()? {
                     match k {
                         GeneratedField::Input => {
@@ -12561,6 +12921,12 @@ impl<'de> serde::Deserialize<'de> for JsonSinkExecNode {
                             }
                             sort_order__ = map_.next_value()?;
                         }
+                        GeneratedField::WrittenFiles => {
+                            if written_files__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("writtenFiles"));
+                            }
+                            written_files__ = Some(map_.next_value()?);
+                        }
                     }
                 }
                 Ok(JsonSinkExecNode {
@@ -12568,6 +12934,7 @@ impl<'de> serde::Deserialize<'de> for JsonSinkExecNode {
                     sink: sink__,
                     sink_schema: sink_schema__,
                     sort_order: sort_order__,
+                    written_files: written_files__.unwrap_or_default(),
                 })
             }
         }
@@ -16103,6 +16470,9 @@ impl serde::Serialize for ParquetOptions {
         if self.reorder_filters {
             len += 1;
         }
+        if self.late_materialization {
+            len += 1;
+        }
         if self.data_pagesize_limit != 0 {
             len += 1;
         }
@@ -16179,6 +16549,9 @@ impl serde::Serialize for ParquetOptions {
         if self.reorder_filters {
             struct_ser.serialize_field("reorderFilters", &self.reorder_filters)?;
         }
+        if self.late_materialization {
+            struct_ser.serialize_field("lateMaterialization", &self.late_materialization)?;
+        }
         if self.data_pagesize_limit != 0 {
             #[allow(clippy::needless_borrow)]
             struct_ser.serialize_field("dataPagesizeLimit", ToString::to_string(&self.data_pagesize_limit).as_str())?;
@@ -16305,6 +16678,8 @@ impl<'de> serde::Deserialize<'de> for ParquetOptions {
             "pushdownFilters",
             "reorder_filters",
             "reorderFilters",
+            "late_materialization",
+            "lateMaterialization",
             "data_pagesize_limit",
             "dataPagesizeLimit",
             "write_batch_size",
@@ -16352,6 +16727,7 @@ impl<'de> serde::Deserialize<'de> for ParquetOptions {
             SkipMetadata,
             PushdownFilters,
             ReorderFilters,
+            LateMaterialization,
             DataPagesizeLimit,
             WriteBatchSize,
             WriterVersion,
@@ -16398,6 +16774,7 @@ impl<'de> serde::Deserialize<'de> for ParquetOptions {
                             "skipMetadata" | "skip_metadata" => Ok(GeneratedField::SkipMetadata),
                             "pushdownFilters" | "pushdown_filters" => Ok(GeneratedField::PushdownFilters),
                             "reorderFilters" | "reorder_filters" => Ok(GeneratedField::ReorderFilters),
+                            "lateMaterialization" | "late_materialization" => Ok(GeneratedField::LateMaterialization),
                             "dataPagesizeLimit" | "data_pagesize_limit" => Ok(GeneratedField::DataPagesizeLimit),
                             "writeBatchSize" | "write_batch_size" => Ok(GeneratedField::WriteBatchSize),
                             "writerVersion" | "writer_version" => Ok(GeneratedField::WriterVersion),
@@ -16442,6 +16819,7 @@ impl<'de> serde::Deserialize<'de> for ParquetOptions {
                 let mut skip_metadata__ = None;
                 let mut pushdown_filters__ = None;
                 let mut reorder_filters__ = None;
+                let mut late_materialization__ = None;
                 let mut data_pagesize_limit__ = None;
                 let mut write_batch_size__ = None;
                 let mut writer_version__ = None;
@@ -16494,6 +16872,12 @@ impl<'de> serde::Deserialize<'de> for ParquetOptions {
                             }
                             reorder_filters__ = Some(map_.next_value()?);
                         }
+                        GeneratedField::LateMaterialization => {
+                            if late_materialization__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("lateMaterialization"));
+                            }
+                            late_materialization__ = Some(map_.next_value()?);
+                        }
                         GeneratedField::DataPagesizeLimit => {
                             if data_pagesize_limit__.is_some() {
                                 return Err(serde::de::Error::duplicate_field("dataPagesizeLimit"));
@@ -16636,6 +17020,7 @@ impl<'de> serde::Deserialize<'de> for ParquetOptions {
                     skip_metadata: skip_metadata__.unwrap_or_default(),
                     pushdown_filters: pushdown_filters__.unwrap_or_default(),
                     reorder_filters: reorder_filters__.unwrap_or_default(),
+                    late_materialization: late_materialization__.unwrap_or_default(),
                     data_pagesize_limit: data_pagesize_limit__.unwrap_or_default(),
                     write_batch_size: write_batch_size__.unwrap_or_default(),
                     writer_version: writer_version__.unwrap_or_default(),
@@ -16900,6 +17285,9 @@ impl serde::Serialize for ParquetSinkExecNode {
         if self.sort_order.is_some() {
             len += 1;
         }
+        if self.written_files {
+            len += 1;
+        }
         let mut struct_ser = serializer.serialize_struct("datafusion.ParquetSinkExecNode", len)?;
         if let Some(v) = self.input.as_ref() {
             struct_ser.serialize_field("input", v)?;
@@ -16913,6 +17301,9 @@ impl serde::Serialize for ParquetSinkExecNode {
         if let Some(v) = self.sort_order.as_ref() {
             struct_ser.serialize_field("sortOrder", v)?;
         }
+        if self.written_files {
+            struct_ser.serialize_field("writtenFiles", &self.written_files)?;
+        }
         struct_ser.end()
     }
 }
@@ -16929,6 +17320,8 @@ impl<'de> serde::Deserialize<'de> for ParquetSinkExecNode {
             "sinkSchema",
             "sort_order",
             "sortOrder",
+            "written_files",
+            "writtenFiles",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -16937,6 +17330,7 @@ impl<'de> serde::Deserialize<'de> for ParquetSinkExecNode {
             Sink,
             SinkSchema,
             SortOrder,
+            WrittenFiles,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -16962,6 +17356,7 @@ impl<'de> serde::Deserialize<'de> for ParquetSinkExecNode {
                             "sink" => Ok(GeneratedField::Sink),
                             "sinkSchema" | "sink_schema" => Ok(GeneratedField::SinkSchema),
                             "sortOrder" | "sort_order" => Ok(GeneratedField::SortOrder),
+                            "writtenFiles" | "written_files" => Ok(GeneratedField::WrittenFiles),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -16985,6 +17380,7 @@ impl<'de> serde::Deserialize<'de> for ParquetSinkExecNode {
                 let mut sink__ = None;
                 let mut sink_schema__ = None;
                 let mut sort_order__ = None;
+                let mut written_files__ = None;
                 while let Some(k) = map_.next_key()? {
                     match k {
                         GeneratedField::Input => {
@@ -17011,6 +17407,12 @@ impl<'de> serde::Deserialize<'de> for ParquetSinkExecNode {
                             }
                             sort_order__ = map_.next_value()?;
                         }
+                        GeneratedField::WrittenFiles => {
+                            if written_files__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("writtenFiles"));
+                            }
+                            written_files__ = Some(map_.next_value()?);
+                        }
                     }
                 }
                 Ok(ParquetSinkExecNode {
@@ -17018,6 +17420,7 @@ impl<'de> serde::Deserialize<'de> for ParquetSinkExecNode {
                     sink: sink__,
                     sink_schema: sink_schema__,
                     sort_order: sort_order__,
+                    written_files: written_files__.unwrap_or_default(),
                 })
             }
         }
@@ -19868,6 +20271,9 @@ impl serde::Serialize for PhysicalPlanNode {
                 physical_plan_node::PhysicalPlanType::ParquetSink(v) => {
                     struct_ser.serialize_field("parquetSink", v)?;
                 }
+                physical_plan_node::PhysicalPlanType::SortMergeJoin(v) => {
+                    struct_ser.serialize_field("sortMergeJoin", v)?;
+                }
             }
         }
         struct_ser.end()
@@ -19923,6 +20329,8 @@ impl<'de> serde::Deserialize<'de> for PhysicalPlanNode {
             "csvSink",
             "parquet_sink",
             "parquetSink",
+            "sort_merge_join",
+            "sortMergeJoin",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -19955,6 +20363,7 @@ impl<'de> serde::Deserialize<'de> for PhysicalPlanNode {
             PlaceholderRow,
             CsvSink,
             ParquetSink,
+            SortMergeJoin,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -20004,6 +20413,7 @@ impl<'de> serde::Deserialize<'de> for PhysicalPlanNode {
                             "placeholderRow" | "placeholder_row" => Ok(GeneratedField::PlaceholderRow),
                             "csvSink" | "csv_sink" => Ok(GeneratedField::CsvSink),
                             "parquetSink" | "parquet_sink" => Ok(GeneratedField::ParquetSink),
+                            "sortMergeJoin" | "sort_merge_join" => Ok(GeneratedField::SortMergeJoin),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -20220,6 +20630,13 @@ impl<'de> serde::Deserialize<'de> for PhysicalPlanNode {
                                 return Err(serde::de::Error::duplicate_field("parquetSink"));
                             }
                             physical_plan_type__ = map_.next_value::<::std::option::Option<_>>()?.map(physical_plan_node::PhysicalPlanType::ParquetSink)
+;
+                        }
+                        GeneratedField::SortMergeJoin => {
+                            if physical_plan_type__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("sortMergeJoin"));
+                            }
+                            physical_plan_type__ = map_.next_value::<::std::option::Option<_>>()?.map(physical_plan_node::PhysicalPlanType::SortMergeJoin)
 ;
                         }
                     }
@@ -25305,6 +25722,224 @@ impl<'de> serde::Deserialize<'de> for SortExprNode {
         deserializer.deserialize_struct("datafusion.SortExprNode", FIELDS, GeneratedVisitor)
     }
 }
+impl serde::Serialize for SortMergeJoinExecNode {
+    #[allow(deprecated)]
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut len = 0;
+        if self.left.is_some() {
+            len += 1;
+        }
+        if self.right.is_some() {
+            len += 1;
+        }
+        if !self.on.is_empty() {
+            len += 1;
+        }
+        if self.join_type != 0 {
+            len += 1;
+        }
+        if self.filter.is_some() {
+            len += 1;
+        }
+        if !self.sort_options.is_empty() {
+            len += 1;
+        }
+        if self.null_equals_null {
+            len += 1;
+        }
+        if self.fetch != 0 {
+            len += 1;
+        }
+        let mut struct_ser = serializer.serialize_struct("datafusion.SortMergeJoinExecNode", len)?;
+        if let Some(v) = self.left.as_ref() {
+            struct_ser.serialize_field("left", v)?;
+        }
+        if let Some(v) = self.right.as_ref() {
+            struct_ser.serialize_field("right", v)?;
+        }
+        if !self.on.is_empty() {
+            struct_ser.serialize_field("on", &self.on)?;
+        }
+        if self.join_type != 0 {
+            let v = JoinType::try_from(self.join_type)
+                .map_err(|_| serde::ser::Error::custom(format!("Invalid variant {}", self.join_type)))?;
+            struct_ser.serialize_field("joinType", &v)?;
+        }
+        if let Some(v) = self.filter.as_ref() {
+            struct_ser.serialize_field("filter", v)?;
+        }
+        if !self.sort_options.is_empty() {
+            struct_ser.serialize_field("sortOptions", &self.sort_options)?;
+        }
+        if self.null_equals_null {
+            struct_ser.serialize_field("nullEqualsNull", &self.null_equals_null)?;
+        }
+        if self.fetch != 0 {
+            #[allow(clippy::needless_borrow)]
+            struct_ser.serialize_field("fetch", ToString::to_string(&self.fetch).as_str())?;
+        }
+        struct_ser.end()
+    }
+}
+impl<'de> serde::Deserialize<'de> for SortMergeJoinExecNode {
+    #[allow(deprecated)]
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        const FIELDS: &[&str] = &[
+            "left",
+            "right",
+            "on",
+            "join_type",
+            "joinType",
+            "filter",
+            "sort_options",
+            "sortOptions",
+            "null_equals_null",
+            "nullEqualsNull",
+            "fetch",
+        ];
+
+        #[allow(clippy::enum_variant_names)]
+        enum GeneratedField {
+            Left,
+            Right,
+            On,
+            JoinType,
+            Filter,
+            SortOptions,
+            NullEqualsNull,
+            Fetch,
+        }
+        impl<'de> serde::Deserialize<'de> for GeneratedField {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct GeneratedVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+                    type Value = GeneratedField;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        write!(formatter, "expected one of: {:?}", &FIELDS)
+                    }
+
+                    #[allow(unused_variables)]
+                    fn visit_str<E>(self, value: &str) -> std::result::Result<GeneratedField, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "left" => Ok(GeneratedField::Left),
+                            "right" => Ok(GeneratedField::Right),
+                            "on" => Ok(GeneratedField::On),
+                            "joinType" | "join_type" => Ok(GeneratedField::JoinType),
+                            "filter" => Ok(GeneratedField::Filter),
+                            "sortOptions" | "sort_options" => Ok(GeneratedField::SortOptions),
+                            "nullEqualsNull" | "null_equals_null" => Ok(GeneratedField::NullEqualsNull),
+                            "fetch" => Ok(GeneratedField::Fetch),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+                deserializer.deserialize_identifier(GeneratedVisitor)
+            }
+        }
+        struct GeneratedVisitor;
+        impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+            type Value = SortMergeJoinExecNode;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("struct datafusion.SortMergeJoinExecNode")
+            }
+
+            fn visit_map<V>(self, mut map_: V) -> std::result::Result<SortMergeJoinExecNode, V::Error>
+                where
+                    V: serde::de::MapAccess<'de>,
+            {
+                let mut left__ = None;
+                let mut right__ = None;
+                let mut on__ = None;
+                let mut join_type__ = None;
+                let mut filter__ = None;
+                let mut sort_options__ = None;
+                let mut null_equals_null__ = None;
+                let mut fetch__ = None;
+                while let Some(k) = map_.next_key()? {
+                    match k {
+                        GeneratedField::Left => {
+                            if left__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("left"));
+                            }
+                            left__ = map_.next_value()?;
+                        }
+                        GeneratedField::Right => {
+                            if right__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("right"));
+                            }
+                            right__ = map_.next_value()?;
+                        }
+                        GeneratedField::On => {
+                            if on__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("on"));
+                            }
+                            on__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::JoinType => {
+                            if join_type__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("joinType"));
+                            }
+                            join_type__ = Some(map_.next_value::<JoinType>()? as i32);
+                        }
+                        GeneratedField::Filter => {
+                            if filter__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("filter"));
+                            }
+                            filter__ = map_.next_value()?;
+                        }
+                        GeneratedField::SortOptions => {
+                            if sort_options__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("sortOptions"));
+                            }
+                            sort_options__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::NullEqualsNull => {
+                            if null_equals_null__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("nullEqualsNull"));
+                            }
+                            null_equals_null__ = Some(map_.next_value()?);
+                        }
+                        GeneratedField::Fetch => {
+                            if fetch__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("fetch"));
+                            }
+                            fetch__ = 
+                                Some(map_.next_value::<::pbjson::private::NumberDeserialize<_>>()?.0)
+                            ;
+                        }
+                    }
+                }
+                Ok(SortMergeJoinExecNode {
+                    left: left__,
+                    right: right__,
+                    on: on__.unwrap_or_default(),
+                    join_type: join_type__.unwrap_or_default(),
+                    filter: filter__,
+                    sort_options: sort_options__.unwrap_or_default(),
+                    null_equals_null: null_equals_null__.unwrap_or_default(),
+                    fetch: fetch__.unwrap_or_default(),
+                })
+            }
+        }
+        deserializer.deserialize_struct("datafusion.SortMergeJoinExecNode", FIELDS, GeneratedVisitor)
+    }
+}
 impl serde::Serialize for SortNode {
     #[allow(deprecated)]
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
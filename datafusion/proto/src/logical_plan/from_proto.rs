@@ -40,9 +40,9 @@ use arrow::{
 };
 use datafusion::execution::registry::FunctionRegistry;
 use datafusion_common::{
-    arrow_datafusion_err, internal_err, plan_datafusion_err, Column, Constraint,
-    Constraints, DFField, DFSchema, DFSchemaRef, DataFusionError, OwnedTableReference,
-    Result, ScalarValue,
+    arrow_datafusion_err, internal_err, plan_datafusion_err, BucketSpec, Column,
+    Constraint, Constraints, DFField, DFSchema, DFSchemaRef, DataFusionError,
+    OwnedTableReference, Result, ScalarValue,
 };
 use datafusion_expr::expr::Unnest;
 use datafusion_expr::expr::{Alias, Placeholder};
@@ -917,6 +917,17 @@ impl From<protobuf::Constraint> for Constraint {
     }
 }
 
+impl TryFrom<&protobuf::BucketSpec> for BucketSpec {
+    type Error = Error;
+
+    fn try_from(bucket_spec: &protobuf::BucketSpec) -> Result<Self, Self::Error> {
+        Ok(BucketSpec::try_new(
+            bucket_spec.columns.clone(),
+            bucket_spec.num_buckets as usize,
+        )?)
+    }
+}
+
 pub fn parse_i32_to_time_unit(value: &i32) -> Result<TimeUnit, Error> {
     protobuf::TimeUnit::try_from(*value)
         .map(|t| t.into())
@@ -856,8 +856,21 @@ impl AsLogicalPlan for LogicalPlanNode {
                         input: Arc::new(input),
                         output_url: copy.output_url.clone(),
                         partition_by: copy.partition_by.clone(),
+                        sort_by: copy
+                            .sort_by
+                            .iter()
+                            .map(|expr| {
+                                from_proto::parse_expr(expr, ctx, extension_codec)
+                            })
+                            .collect::<Result<Vec<Expr>, _>>()?,
+                        bucket_by: copy
+                            .bucket_by
+                            .as_ref()
+                            .map(|bucket_by| bucket_by.try_into())
+                            .transpose()?,
                         format_options: convert_required!(copy.format_options)?,
                         options: Default::default(),
+                        output_schema: dml::make_dml_schema(copy.written_files),
                     },
                 ))
             }
@@ -1584,6 +1597,9 @@ impl AsLogicalPlan for LogicalPlanNode {
                 output_url,
                 format_options,
                 partition_by,
+                sort_by,
+                bucket_by,
+                output_schema,
                 ..
             }) => {
                 let input = protobuf::LogicalPlanNode::try_from_logical_plan(
@@ -1598,6 +1614,12 @@ impl AsLogicalPlan for LogicalPlanNode {
                             output_url: output_url.to_string(),
                             format_options: Some(format_options.try_into()?),
                             partition_by: partition_by.clone(),
+                            sort_by: sort_by
+                                .iter()
+                                .map(|expr| serialize_expr(expr, extension_codec))
+                                .collect::<Result<Vec<_>, to_proto::Error>>()?,
+                            bucket_by: bucket_by.as_ref().map(|b| b.into()),
+                            written_files: *output_schema == dml::make_dml_schema(true),
                         },
                     ))),
                 })
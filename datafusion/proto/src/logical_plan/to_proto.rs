@@ -43,8 +43,8 @@ use arrow::{
     record_batch::RecordBatch,
 };
 use datafusion_common::{
-    Column, Constraint, Constraints, DFField, DFSchema, DFSchemaRef, OwnedTableReference,
-    ScalarValue,
+    BucketSpec, Column, Constraint, Constraints, DFField, DFSchema, DFSchemaRef,
+    OwnedTableReference, ScalarValue,
 };
 use datafusion_expr::expr::{
     self, AggregateFunctionDefinition, Alias, Between, BinaryExpr, Cast, GetFieldAccess,
@@ -1625,6 +1625,15 @@ impl From<Constraint> for protobuf::Constraint {
     }
 }
 
+impl From<&BucketSpec> for protobuf::BucketSpec {
+    fn from(value: &BucketSpec) -> Self {
+        protobuf::BucketSpec {
+            columns: value.columns.clone(),
+            num_buckets: value.num_buckets as u64,
+        }
+    }
+}
+
 /// Creates a scalar protobuf value from an optional value (T), and
 /// encoding None as the appropriate datatype
 fn create_proto_scalar<I, T: FnOnce(&I) -> protobuf::scalar_value::Value>(
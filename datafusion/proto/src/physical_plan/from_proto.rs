@@ -547,6 +547,11 @@ pub fn parse_protobuf_file_scan_config(
         limit: proto.limit.as_ref().map(|sl| sl.limit as usize),
         table_partition_cols,
         output_ordering,
+        bucket_by: proto
+            .bucket_by
+            .as_ref()
+            .map(TryInto::try_into)
+            .transpose()?,
     })
 }
 
@@ -786,6 +791,7 @@ impl TryFrom<&protobuf::FileSinkConfig> for FileSinkConfig {
             output_schema: Arc::new(convert_required!(conf.output_schema)?),
             table_partition_cols,
             overwrite: conf.overwrite,
+            bucket_by: conf.bucket_by.as_ref().map(TryInto::try_into).transpose()?,
         })
     }
 }
@@ -876,6 +882,7 @@ impl TryFrom<&protobuf::ParquetOptions> for ParquetOptions {
                 .unwrap_or(None),
             pushdown_filters: value.pushdown_filters,
             reorder_filters: value.reorder_filters,
+            late_materialization: value.late_materialization,
             data_pagesize_limit: value.data_pagesize_limit as usize,
             write_batch_size: value.write_batch_size as usize,
             writer_version: value.writer_version.clone(),
@@ -61,7 +61,8 @@ use datafusion::physical_plan::filter::FilterExec;
 use datafusion::physical_plan::insert::FileSinkExec;
 use datafusion::physical_plan::joins::utils::{ColumnIndex, JoinFilter};
 use datafusion::physical_plan::joins::{
-    CrossJoinExec, NestedLoopJoinExec, StreamJoinPartitionMode, SymmetricHashJoinExec,
+    CrossJoinExec, NestedLoopJoinExec, SortMergeJoinExec, StreamJoinPartitionMode,
+    SymmetricHashJoinExec,
 };
 use datafusion::physical_plan::joins::{HashJoinExec, PartitionMode};
 use datafusion::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
@@ -643,16 +644,24 @@ impl AsExecutionPlan for PhysicalPlanNode {
                 } else {
                     None
                 };
-                Ok(Arc::new(HashJoinExec::try_new(
-                    left,
-                    right,
-                    on,
-                    filter,
-                    &join_type.into(),
-                    projection,
-                    partition_mode,
-                    hashjoin.null_equals_null,
-                )?))
+                let fetch = if hashjoin.fetch < 0 {
+                    None
+                } else {
+                    Some(hashjoin.fetch as usize)
+                };
+                Ok(Arc::new(
+                    HashJoinExec::try_new(
+                        left,
+                        right,
+                        on,
+                        filter,
+                        &join_type.into(),
+                        projection,
+                        partition_mode,
+                        hashjoin.null_equals_null,
+                    )?
+                    .with_fetch(fetch)?,
+                ))
             }
             PhysicalPlanType::SymmetricHashJoin(sym_join) => {
                 let left = into_physical_plan(
@@ -782,6 +791,109 @@ impl AsExecutionPlan for PhysicalPlanNode {
                 )
                 .map(|e| Arc::new(e) as _)
             }
+            PhysicalPlanType::SortMergeJoin(sort_merge_join) => {
+                let left = into_physical_plan(
+                    &sort_merge_join.left,
+                    registry,
+                    runtime,
+                    extension_codec,
+                )?;
+                let right = into_physical_plan(
+                    &sort_merge_join.right,
+                    registry,
+                    runtime,
+                    extension_codec,
+                )?;
+                let left_schema = left.schema();
+                let right_schema = right.schema();
+                let on = sort_merge_join
+                    .on
+                    .iter()
+                    .map(|col| {
+                        let left = parse_physical_expr(
+                            &col.left.clone().unwrap(),
+                            registry,
+                            left_schema.as_ref(),
+                            extension_codec,
+                        )?;
+                        let right = parse_physical_expr(
+                            &col.right.clone().unwrap(),
+                            registry,
+                            right_schema.as_ref(),
+                            extension_codec,
+                        )?;
+                        Ok((left, right))
+                    })
+                    .collect::<Result<_>>()?;
+                let join_type = protobuf::JoinType::try_from(sort_merge_join.join_type)
+                    .map_err(|_| {
+                    proto_error(format!(
+                        "Received a SortMergeJoin message with unknown JoinType {}",
+                        sort_merge_join.join_type
+                    ))
+                })?;
+                let filter = sort_merge_join
+                    .filter
+                    .as_ref()
+                    .map(|f| {
+                        let schema = f
+                            .schema
+                            .as_ref()
+                            .ok_or_else(|| proto_error("Missing JoinFilter schema"))?
+                            .try_into()?;
+
+                        let expression = parse_physical_expr(
+                            f.expression.as_ref().ok_or_else(|| {
+                                proto_error("Unexpected empty filter expression")
+                            })?,
+                            registry, &schema,
+                            extension_codec,
+                        )?;
+                        let column_indices = f.column_indices
+                            .iter()
+                            .map(|i| {
+                                let side = protobuf::JoinSide::try_from(i.side)
+                                    .map_err(|_| proto_error(format!(
+                                        "Received a SortMergeJoin message with JoinSide in Filter {}",
+                                        i.side))
+                                    )?;
+
+                                Ok(ColumnIndex {
+                                    index: i.index as usize,
+                                    side: side.into(),
+                                })
+                            })
+                            .collect::<Result<_>>()?;
+
+                        Ok(JoinFilter::new(expression, column_indices, schema))
+                    })
+                    .map_or(Ok(None), |v: Result<JoinFilter>| v.map(Some))?;
+                let sort_options = sort_merge_join
+                    .sort_options
+                    .iter()
+                    .map(|options| SortOptions {
+                        descending: options.descending,
+                        nulls_first: options.nulls_first,
+                    })
+                    .collect();
+                let fetch = if sort_merge_join.fetch < 0 {
+                    None
+                } else {
+                    Some(sort_merge_join.fetch as usize)
+                };
+                Ok(Arc::new(
+                    SortMergeJoinExec::try_new(
+                        left,
+                        right,
+                        on,
+                        filter,
+                        join_type.into(),
+                        sort_options,
+                        sort_merge_join.null_equals_null,
+                    )?
+                    .with_fetch(fetch)?,
+                ))
+            }
             PhysicalPlanType::Union(union) => {
                 let mut inputs: Vec<Arc<dyn ExecutionPlan>> = vec![];
                 for input in &union.inputs {
@@ -1028,12 +1140,15 @@ impl AsExecutionPlan for PhysicalPlanNode {
                             .collect::<Result<Vec<_>>>()
                     })
                     .transpose()?;
-                Ok(Arc::new(FileSinkExec::new(
-                    input,
-                    Arc::new(data_sink),
-                    Arc::new(sink_schema),
-                    sort_order,
-                )))
+                Ok(Arc::new(
+                    FileSinkExec::new(
+                        input,
+                        Arc::new(data_sink),
+                        Arc::new(sink_schema),
+                        sort_order,
+                    )
+                    .with_written_files(sink.written_files),
+                ))
             }
             PhysicalPlanType::CsvSink(sink) => {
                 let input =
@@ -1059,12 +1174,15 @@ impl AsExecutionPlan for PhysicalPlanNode {
                             .collect::<Result<Vec<_>>>()
                     })
                     .transpose()?;
-                Ok(Arc::new(FileSinkExec::new(
-                    input,
-                    Arc::new(data_sink),
-                    Arc::new(sink_schema),
-                    sort_order,
-                )))
+                Ok(Arc::new(
+                    FileSinkExec::new(
+                        input,
+                        Arc::new(data_sink),
+                        Arc::new(sink_schema),
+                        sort_order,
+                    )
+                    .with_written_files(sink.written_files),
+                ))
             }
             PhysicalPlanType::ParquetSink(sink) => {
                 let input =
@@ -1090,12 +1208,15 @@ impl AsExecutionPlan for PhysicalPlanNode {
                             .collect::<Result<Vec<_>>>()
                     })
                     .transpose()?;
-                Ok(Arc::new(FileSinkExec::new(
-                    input,
-                    Arc::new(data_sink),
-                    Arc::new(sink_schema),
-                    sort_order,
-                )))
+                Ok(Arc::new(
+                    FileSinkExec::new(
+                        input,
+                        Arc::new(data_sink),
+                        Arc::new(sink_schema),
+                        sort_order,
+                    )
+                    .with_written_files(sink.written_files),
+                ))
             }
         }
     }
@@ -1288,6 +1409,81 @@ impl AsExecutionPlan for PhysicalPlanNode {
                         projection: exec.projection.as_ref().map_or_else(Vec::new, |v| {
                             v.iter().map(|x| *x as u32).collect::<Vec<u32>>()
                         }),
+                        fetch: exec.fetch().map(|n| n as i64).unwrap_or(-1),
+                    },
+                ))),
+            });
+        }
+
+        if let Some(exec) = plan.downcast_ref::<SortMergeJoinExec>() {
+            let left = protobuf::PhysicalPlanNode::try_from_physical_plan(
+                exec.left().to_owned(),
+                extension_codec,
+            )?;
+            let right = protobuf::PhysicalPlanNode::try_from_physical_plan(
+                exec.right().to_owned(),
+                extension_codec,
+            )?;
+            let on = exec
+                .on()
+                .iter()
+                .map(|tuple| {
+                    let l = serialize_physical_expr(tuple.0.to_owned(), extension_codec)?;
+                    let r = serialize_physical_expr(tuple.1.to_owned(), extension_codec)?;
+                    Ok::<_, DataFusionError>(protobuf::JoinOn {
+                        left: Some(l),
+                        right: Some(r),
+                    })
+                })
+                .collect::<Result<_>>()?;
+            let join_type: protobuf::JoinType = exec.join_type().into();
+            let filter = exec
+                .filter
+                .as_ref()
+                .map(|f| {
+                    let expression = serialize_physical_expr(
+                        f.expression().to_owned(),
+                        extension_codec,
+                    )?;
+                    let column_indices = f
+                        .column_indices()
+                        .iter()
+                        .map(|i| {
+                            let side: protobuf::JoinSide = i.side.to_owned().into();
+                            protobuf::ColumnIndex {
+                                index: i.index as u32,
+                                side: side.into(),
+                            }
+                        })
+                        .collect();
+                    let schema = f.schema().try_into()?;
+                    Ok(protobuf::JoinFilter {
+                        expression: Some(expression),
+                        column_indices,
+                        schema: Some(schema),
+                    })
+                })
+                .map_or(Ok(None), |v: Result<protobuf::JoinFilter>| v.map(Some))?;
+            let sort_options = exec
+                .sort_options
+                .iter()
+                .map(|options| protobuf::JoinSortOptions {
+                    descending: options.descending,
+                    nulls_first: options.nulls_first,
+                })
+                .collect();
+
+            return Ok(protobuf::PhysicalPlanNode {
+                physical_plan_type: Some(PhysicalPlanType::SortMergeJoin(Box::new(
+                    protobuf::SortMergeJoinExecNode {
+                        left: Some(Box::new(left)),
+                        right: Some(Box::new(right)),
+                        on,
+                        join_type: join_type.into(),
+                        filter,
+                        sort_options,
+                        null_equals_null: exec.null_equals_null,
+                        fetch: exec.fetch().map(|n| n as i64).unwrap_or(-1),
                     },
                 ))),
             });
@@ -1913,6 +2109,7 @@ impl AsExecutionPlan for PhysicalPlanNode {
                             sink: Some(sink.try_into()?),
                             sink_schema: Some(exec.schema().as_ref().try_into()?),
                             sort_order,
+                            written_files: exec.written_files(),
                         },
                     ))),
                 });
@@ -1926,6 +2123,7 @@ impl AsExecutionPlan for PhysicalPlanNode {
                             sink: Some(sink.try_into()?),
                             sink_schema: Some(exec.schema().as_ref().try_into()?),
                             sort_order,
+                            written_files: exec.written_files(),
                         },
                     ))),
                 });
@@ -1939,6 +2137,7 @@ impl AsExecutionPlan for PhysicalPlanNode {
                             sink: Some(sink.try_into()?),
                             sink_schema: Some(exec.schema().as_ref().try_into()?),
                             sort_order,
+                            written_files: exec.written_files(),
                         },
                     ))),
                 });
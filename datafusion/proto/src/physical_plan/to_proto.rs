@@ -803,6 +803,7 @@ impl TryFrom<&FileScanConfig> for protobuf::FileScanExecConf {
                     physical_sort_expr_nodes: e,
                 })
                 .collect::<Vec<_>>(),
+            bucket_by: conf.bucket_by.as_ref().map(Into::into),
         })
     }
 }
@@ -924,6 +925,7 @@ impl TryFrom<&FileSinkConfig> for protobuf::FileSinkConfig {
             output_schema: Some(conf.output_schema.as_ref().try_into()?),
             table_partition_cols,
             overwrite: conf.overwrite,
+            bucket_by: conf.bucket_by.as_ref().map(Into::into),
         })
     }
 }
@@ -973,6 +975,7 @@ impl TryFrom<&ParquetOptions> for protobuf::ParquetOptions {
             metadata_size_hint_opt: value.metadata_size_hint.map(|v| protobuf::parquet_options::MetadataSizeHintOpt::MetadataSizeHint(v as u64)),
             pushdown_filters: value.pushdown_filters,
             reorder_filters: value.reorder_filters,
+            late_materialization: value.late_materialization,
             data_pagesize_limit: value.data_pagesize_limit as u64,
             write_batch_size: value.write_batch_size as u64,
             writer_version: value.writer_version.clone(),
@@ -1103,6 +1106,11 @@ impl TryFrom<&FormatOptions> for copy_to_node::FormatOptions {
             }
             FormatOptions::AVRO => copy_to_node::FormatOptions::Avro(AvroOptions {}),
             FormatOptions::ARROW => copy_to_node::FormatOptions::Arrow(ArrowOptions {}),
+            FormatOptions::XLSX(_) | FormatOptions::FIXEDWIDTH(_) => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Serialization of {value} format options is not supported"
+                )))
+            }
         })
     }
 }
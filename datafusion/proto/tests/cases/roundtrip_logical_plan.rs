@@ -34,10 +34,10 @@ use datafusion::test_util::{TestTableFactory, TestTableProvider};
 use datafusion_common::config::{FormatOptions, TableOptions};
 use datafusion_common::scalar::ScalarStructBuilder;
 use datafusion_common::{
-    internal_err, not_impl_err, plan_err, DFField, DFSchema, DFSchemaRef,
+    internal_err, not_impl_err, plan_err, BucketSpec, DFField, DFSchema, DFSchemaRef,
     DataFusionError, FileType, Result, ScalarValue,
 };
-use datafusion_expr::dml::CopyTo;
+use datafusion_expr::dml::{make_dml_schema, CopyTo};
 use datafusion_expr::expr::{
     self, Between, BinaryExpr, Case, Cast, GroupingSet, InList, Like, ScalarFunction,
     Sort, Unnest,
@@ -324,6 +324,9 @@ async fn roundtrip_logical_plan_copy_to_sql_options() -> Result<()> {
         partition_by: vec!["a".to_string(), "b".to_string(), "c".to_string()],
         format_options: FormatOptions::CSV(table_options.csv.clone()),
         options: Default::default(),
+        sort_by: vec![],
+        bucket_by: None,
+        output_schema: make_dml_schema(false),
     });
 
     let bytes = logical_plan_to_bytes(&plan)?;
@@ -358,6 +361,9 @@ async fn roundtrip_logical_plan_copy_to_writer_options() -> Result<()> {
         format_options: FormatOptions::PARQUET(parquet_format.clone()),
         partition_by: vec!["a".to_string(), "b".to_string(), "c".to_string()],
         options: Default::default(),
+        sort_by: vec![col("a").sort(true, false)],
+        bucket_by: Some(BucketSpec::try_new(vec!["b".to_string()], 8)?),
+        output_schema: make_dml_schema(true),
     });
 
     let bytes = logical_plan_to_bytes(&plan)?;
@@ -368,6 +374,12 @@ async fn roundtrip_logical_plan_copy_to_writer_options() -> Result<()> {
         LogicalPlan::Copy(copy_to) => {
             assert_eq!("test.parquet", copy_to.output_url);
             assert_eq!(vec!["a", "b", "c"], copy_to.partition_by);
+            assert_eq!(vec![col("a").sort(true, false)], copy_to.sort_by);
+            assert_eq!(
+                Some(BucketSpec::try_new(vec!["b".to_string()], 8)?),
+                copy_to.bucket_by
+            );
+            assert_eq!(copy_to.output_schema, make_dml_schema(true));
             assert_eq!(
                 copy_to.format_options,
                 FormatOptions::PARQUET(parquet_format)
@@ -390,6 +402,9 @@ async fn roundtrip_logical_plan_copy_to_arrow() -> Result<()> {
         partition_by: vec!["a".to_string(), "b".to_string(), "c".to_string()],
         format_options: FormatOptions::ARROW,
         options: Default::default(),
+        sort_by: vec![],
+        bucket_by: None,
+        output_schema: make_dml_schema(false),
     });
 
     let bytes = logical_plan_to_bytes(&plan)?;
@@ -431,6 +446,9 @@ async fn roundtrip_logical_plan_copy_to_csv() -> Result<()> {
         partition_by: vec!["a".to_string(), "b".to_string(), "c".to_string()],
         format_options: FormatOptions::CSV(csv_format.clone()),
         options: Default::default(),
+        sort_by: vec![],
+        bucket_by: None,
+        output_schema: make_dml_schema(false),
     });
 
     let bytes = logical_plan_to_bytes(&plan)?;
@@ -52,7 +52,8 @@ use datafusion::physical_plan::expressions::{
 use datafusion::physical_plan::filter::FilterExec;
 use datafusion::physical_plan::insert::FileSinkExec;
 use datafusion::physical_plan::joins::{
-    HashJoinExec, NestedLoopJoinExec, PartitionMode, StreamJoinPartitionMode,
+    HashJoinExec, NestedLoopJoinExec, PartitionMode, SortMergeJoinExec,
+    StreamJoinPartitionMode,
 };
 use datafusion::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
 use datafusion::physical_plan::placeholder_row::PlaceholderRowExec;
@@ -230,6 +231,72 @@ fn roundtrip_hash_join() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn roundtrip_hash_join_with_fetch() -> Result<()> {
+    let field_a = Field::new("col", DataType::Int64, false);
+    let schema_left = Arc::new(Schema::new(vec![field_a.clone()]));
+    let schema_right = Arc::new(Schema::new(vec![field_a]));
+    let on = vec![(
+        Arc::new(Column::new("col", 0)) as _,
+        Arc::new(Column::new("col", 0)) as _,
+    )];
+
+    roundtrip_test(Arc::new(
+        HashJoinExec::try_new(
+            Arc::new(EmptyExec::new(schema_left)),
+            Arc::new(EmptyExec::new(schema_right)),
+            on,
+            None,
+            &JoinType::Inner,
+            None,
+            PartitionMode::CollectLeft,
+            false,
+        )?
+        .with_fetch(Some(5))?,
+    ))
+}
+
+#[test]
+fn roundtrip_sort_merge_join() -> Result<()> {
+    let field_a = Field::new("col", DataType::Int64, false);
+    let schema_left = Arc::new(Schema::new(vec![field_a.clone()]));
+    let schema_right = Arc::new(Schema::new(vec![field_a]));
+    let on = vec![(
+        Arc::new(Column::new("col", 0)) as _,
+        Arc::new(Column::new("col", 0)) as _,
+    )];
+    let sort_options = vec![SortOptions {
+        descending: true,
+        nulls_first: false,
+    }];
+
+    for join_type in &[
+        JoinType::Inner,
+        JoinType::Left,
+        JoinType::Right,
+        JoinType::Full,
+        JoinType::LeftAnti,
+        JoinType::RightAnti,
+        JoinType::LeftSemi,
+    ] {
+        for fetch in [None, Some(5)] {
+            roundtrip_test(Arc::new(
+                SortMergeJoinExec::try_new(
+                    Arc::new(EmptyExec::new(schema_left.clone())),
+                    Arc::new(EmptyExec::new(schema_right.clone())),
+                    on.clone(),
+                    None,
+                    *join_type,
+                    sort_options.clone(),
+                    false,
+                )?
+                .with_fetch(fetch)?,
+            ))?;
+        }
+    }
+    Ok(())
+}
+
 #[test]
 fn roundtrip_nested_loop_join() -> Result<()> {
     let field_a = Field::new("col", DataType::Int64, false);
@@ -555,6 +622,7 @@ fn roundtrip_parquet_exec_with_pruning_predicate() -> Result<()> {
         limit: None,
         table_partition_cols: vec![],
         output_ordering: vec![],
+        bucket_by: None,
     };
 
     let predicate = Arc::new(BinaryExpr::new(
@@ -591,6 +659,7 @@ async fn roundtrip_parquet_exec_with_table_partition_cols() -> Result<()> {
             false,
         )],
         output_ordering: vec![],
+        bucket_by: None,
     };
 
     roundtrip_test(Arc::new(ParquetExec::new(
@@ -873,6 +942,7 @@ fn roundtrip_json_sink() -> Result<()> {
         output_schema: schema.clone(),
         table_partition_cols: vec![("plan_type".to_string(), DataType::Utf8)],
         overwrite: true,
+        bucket_by: None,
     };
     let data_sink = Arc::new(JsonSink::new(
         file_sink_config,
@@ -908,6 +978,7 @@ fn roundtrip_csv_sink() -> Result<()> {
         output_schema: schema.clone(),
         table_partition_cols: vec![("plan_type".to_string(), DataType::Utf8)],
         overwrite: true,
+        bucket_by: None,
     };
     let data_sink = Arc::new(CsvSink::new(
         file_sink_config,
@@ -921,18 +992,17 @@ fn roundtrip_csv_sink() -> Result<()> {
         }),
     )];
 
-    let roundtrip_plan = roundtrip_test_and_return(Arc::new(FileSinkExec::new(
-        input,
-        data_sink,
-        schema.clone(),
-        Some(sort_order),
-    )))
+    let roundtrip_plan = roundtrip_test_and_return(Arc::new(
+        FileSinkExec::new(input, data_sink, schema.clone(), Some(sort_order))
+            .with_written_files(true),
+    ))
     .unwrap();
 
     let roundtrip_plan = roundtrip_plan
         .as_any()
         .downcast_ref::<FileSinkExec>()
         .unwrap();
+    assert!(roundtrip_plan.written_files());
     let csv_sink = roundtrip_plan
         .sink()
         .as_any()
@@ -960,6 +1030,7 @@ fn roundtrip_parquet_sink() -> Result<()> {
         output_schema: schema.clone(),
         table_partition_cols: vec![("plan_type".to_string(), DataType::Utf8)],
         overwrite: true,
+        bucket_by: None,
     };
     let data_sink = Arc::new(ParquetSink::new(
         file_sink_config,
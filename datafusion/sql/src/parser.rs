@@ -19,13 +19,16 @@
 
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::ops::ControlFlow;
 use std::str::FromStr;
 
+use datafusion_common::display::{ExplainFormat, ExplainOptions};
 use datafusion_common::parsers::CompressionTypeVariant;
 use sqlparser::{
     ast::{
-        ColumnDef, ColumnOptionDef, ObjectName, OrderByExpr, Query,
-        Statement as SQLStatement, TableConstraint, Value,
+        ColumnDef, ColumnOptionDef, DataType, Expr, ObjectName, OrderByExpr, Query,
+        Statement as SQLStatement, TableConstraint, TableFactor,
+        TableVersion as SQLTableVersion, TimezoneInfo, Value, VisitMut, VisitorMut,
     },
     dialect::{keywords::Keyword, Dialect, GenericDialect},
     parser::{Parser, ParserError},
@@ -49,6 +52,8 @@ fn parse_file_type(s: &str) -> Result<String, ParserError> {
 pub struct ExplainStatement {
     pub analyze: bool,
     pub verbose: bool,
+    /// Options given as `EXPLAIN (FORMAT json, RULES on)`
+    pub options: ExplainOptions,
     pub statement: Box<Statement>,
 }
 
@@ -57,6 +62,7 @@ impl fmt::Display for ExplainStatement {
         let Self {
             analyze,
             verbose,
+            options,
             statement,
         } = self;
 
@@ -67,6 +73,9 @@ impl fmt::Display for ExplainStatement {
         if *verbose {
             write!(f, "VERBOSE ")?;
         }
+        if !options.is_default() {
+            write!(f, "{options} ")?;
+        }
 
         write!(f, "{statement}")
     }
@@ -103,6 +112,10 @@ pub struct CopyToStatement {
     pub target: String,
     /// Partition keys
     pub partitioned_by: Vec<String>,
+    /// Sort expressions ordering the rows within each written file
+    pub sort_by: Vec<OrderByExpr>,
+    /// Number of buckets and the columns hashed to cluster rows into them
+    pub bucket_by: Option<(usize, Vec<String>)>,
     /// Indicates whether there is a header row (e.g. CSV)
     pub has_header: bool,
     /// File type (Parquet, NDJSON, CSV etc.)
@@ -117,6 +130,8 @@ impl fmt::Display for CopyToStatement {
             source,
             target,
             partitioned_by,
+            sort_by,
+            bucket_by,
             stored_as,
             options,
             ..
@@ -129,6 +144,13 @@ impl fmt::Display for CopyToStatement {
         if !partitioned_by.is_empty() {
             write!(f, " PARTITIONED BY ({})", partitioned_by.join(", "))?;
         }
+        if !sort_by.is_empty() {
+            let sort_by: Vec<_> = sort_by.iter().map(|e| e.to_string()).collect();
+            write!(f, " SORT BY ({})", sort_by.join(", "))?;
+        }
+        if let Some((num_buckets, columns)) = bucket_by {
+            write!(f, " BUCKETS {num_buckets} BY ({})", columns.join(", "))?;
+        }
 
         if self.has_header {
             write!(f, " WITH HEADER ROW")?;
@@ -269,6 +291,134 @@ fn ensure_not_set<T>(field: &Option<T>, name: &str) -> Result<(), ParserError> {
     Ok(())
 }
 
+/// A `FOR VERSION AS OF <value>` or `FOR TIMESTAMP AS OF <value>` clause
+/// following a table name
+///
+/// [`sqlparser`] does not parse these clauses, so [`DFParser`] removes them
+/// from the tokens and marks the table name they follow with a unique quote
+/// style. Once the statement is parsed, the version is stored in the
+/// [`TableVersion`] of the marked [`TableFactor::Table`] as
+/// `FOR SYSTEM_TIME AS OF CAST(<value> AS BIGINT)` for versions, and
+/// `FOR SYSTEM_TIME AS OF CAST(<value> AS TIMESTAMP)` for timestamps.
+#[derive(Debug)]
+struct TableVersionClause {
+    /// The quote style marking the table name
+    marker: char,
+    /// The original quote style of the table name
+    quote_style: Option<char>,
+    /// The index of the table name in the tokens
+    index: usize,
+    /// `VERSION` or `TIMESTAMP`
+    kind: &'static str,
+    /// The version of the table
+    version: Expr,
+    /// Whether the version was stored in its table factor
+    resolved: bool,
+}
+
+/// Calls a function on every [`TableFactor`] of a statement
+struct TableFactorVisitor<F>(F);
+
+impl<F: FnMut(&mut TableFactor)> VisitorMut for TableFactorVisitor<F> {
+    type Break = ();
+
+    fn pre_visit_table_factor(
+        &mut self,
+        table_factor: &mut TableFactor,
+    ) -> ControlFlow<()> {
+        (self.0)(table_factor);
+        ControlFlow::Continue(())
+    }
+}
+
+/// The first quote style used to mark table names, in the Unicode private use area
+const TABLE_VERSION_MARKER: u32 = 0xE000;
+
+/// Removes the `FOR VERSION AS OF` and `FOR TIMESTAMP AS OF` clauses from
+/// `tokens`, returning the clauses with the table names they follow marked
+fn parse_table_versions(
+    mut tokens: Vec<Token>,
+) -> Result<(Vec<Token>, Vec<TableVersionClause>), ParserError> {
+    let is_keyword = |token: &Token, keyword: &str| {
+        matches!(token, Token::Word(w)
+            if w.quote_style.is_none() && w.value.eq_ignore_ascii_case(keyword))
+    };
+    let is_whitespace = |token: &Token| matches!(token, Token::Whitespace(_));
+
+    let mut clauses = vec![];
+    let mut idx = 0;
+    while idx < tokens.len() {
+        if !is_keyword(&tokens[idx], "FOR") {
+            idx += 1;
+            continue;
+        }
+        let next: Vec<_> = (idx + 1..tokens.len())
+            .filter(|i| !is_whitespace(&tokens[*i]))
+            .take(4)
+            .collect();
+        let (kind, data_type) = match next[..] {
+            [kind, as_kw, of_kw, _]
+                if is_keyword(&tokens[as_kw], "AS")
+                    && is_keyword(&tokens[of_kw], "OF") =>
+            {
+                if is_keyword(&tokens[kind], "VERSION") {
+                    ("VERSION", DataType::BigInt(None))
+                } else if is_keyword(&tokens[kind], "TIMESTAMP") {
+                    ("TIMESTAMP", DataType::Timestamp(None, TimezoneInfo::None))
+                } else {
+                    idx += 1;
+                    continue;
+                }
+            }
+            _ => {
+                idx += 1;
+                continue;
+            }
+        };
+        let value = match &tokens[next[3]] {
+            Token::Number(n, l) => Value::Number(n.clone(), *l),
+            Token::SingleQuotedString(s) => Value::SingleQuotedString(s.clone()),
+            token => {
+                return parser_err!(format!(
+                    "Expected a literal table {}, found: {token}",
+                    kind.to_lowercase()
+                ))
+            }
+        };
+
+        let index = (0..idx).rev().find(|i| !is_whitespace(&tokens[*i]));
+        let marker = u32::try_from(clauses.len())
+            .ok()
+            .and_then(|n| char::from_u32(TABLE_VERSION_MARKER + n))
+            .filter(|c| *c <= '\u{F8FF}');
+        let (Some(index), Some(marker)) = (index, marker) else {
+            return parser_err!(format!("Expected a table name before FOR {kind} AS OF"));
+        };
+        let Token::Word(name) = &mut tokens[index] else {
+            return parser_err!(format!(
+                "Expected a table name before FOR {kind} AS OF, found: {}",
+                tokens[index]
+            ));
+        };
+        clauses.push(TableVersionClause {
+            marker,
+            quote_style: name.quote_style.replace(marker),
+            index,
+            kind,
+            version: Expr::Cast {
+                expr: Box::new(Expr::Value(value)),
+                data_type,
+                format: None,
+            },
+            resolved: false,
+        });
+        name.keyword = Keyword::NoKeyword;
+
+        tokens.drain(idx..=next[3]);
+    }
+    Ok((tokens, clauses))
+}
+
 /// Datafusion SQL Parser based on [`sqlparser`]
 ///
 /// Parses DataFusion's SQL dialect, often delegating to [`sqlparser`]'s [`Parser`].
@@ -279,6 +429,8 @@ fn ensure_not_set<T>(field: &Option<T>, name: &str) -> Result<(), ParserError> {
 /// [`Statement`] for a list of this special syntax
 pub struct DFParser<'a> {
     parser: Parser<'a>,
+    /// The `FOR VERSION AS OF` and `FOR TIMESTAMP AS OF` clauses of the tokens
+    table_versions: Vec<TableVersionClause>,
 }
 
 impl<'a> DFParser<'a> {
@@ -296,10 +448,11 @@ impl<'a> DFParser<'a> {
         dialect: &'a dyn Dialect,
     ) -> Result<Self, ParserError> {
         let mut tokenizer = Tokenizer::new(dialect, sql);
-        let tokens = tokenizer.tokenize()?;
+        let (tokens, table_versions) = parse_table_versions(tokenizer.tokenize()?)?;
 
         Ok(DFParser {
             parser: Parser::new(dialect).with_tokens(tokens),
+            table_versions,
         })
     }
 
@@ -350,7 +503,7 @@ impl<'a> DFParser<'a> {
 
     /// Parse a new expression
     pub fn parse_statement(&mut self) -> Result<Statement, ParserError> {
-        match self.parser.peek_token().token {
+        let mut statement = match self.parser.peek_token().token {
             Token::Word(w) => {
                 match w.keyword {
                     Keyword::CREATE => {
@@ -380,6 +533,65 @@ impl<'a> DFParser<'a> {
                     self.parser.parse_statement()?,
                 )))
             }
+        }?;
+        self.resolve_table_versions(&mut statement)?;
+        Ok(statement)
+    }
+
+    /// Stores the `FOR VERSION AS OF` and `FOR TIMESTAMP AS OF` clauses in the
+    /// table factors of `statement` with the marked table names
+    fn resolve_table_versions(
+        &mut self,
+        statement: &mut Statement,
+    ) -> Result<(), ParserError> {
+        if self.table_versions.is_empty() {
+            return Ok(());
+        }
+
+        let clauses = &mut self.table_versions;
+        let mut resolve = |table: &mut TableFactor| {
+            if let TableFactor::Table { name, version, .. } = table {
+                let ident = name.0.last_mut();
+                let clause = ident.and_then(|ident| {
+                    let clause = clauses
+                        .iter_mut()
+                        .find(|c| Some(c.marker) == ident.quote_style)?;
+                    ident.quote_style = clause.quote_style;
+                    Some(clause)
+                });
+                if let Some(clause) = clause {
+                    *version =
+                        Some(SQLTableVersion::ForSystemTimeAsOf(clause.version.clone()));
+                    clause.resolved = true;
+                }
+            }
+        };
+        let mut visitor = TableFactorVisitor(&mut resolve);
+        match statement {
+            Statement::Statement(statement) => {
+                let _ = statement.visit(&mut visitor);
+            }
+            Statement::CopyTo(CopyToStatement {
+                source: CopyToSource::Query(query),
+                ..
+            }) => {
+                let _ = query.visit(&mut visitor);
+            }
+            _ => {}
+        }
+
+        // clauses not following the name of a table factor
+        let index = self.parser.index();
+        match self
+            .table_versions
+            .iter()
+            .find(|c| c.index < index && !c.resolved)
+        {
+            Some(clause) => parser_err!(format!(
+                "FOR {} AS OF must follow a table name",
+                clause.kind
+            )),
+            None => Ok(()),
         }
     }
 
@@ -401,6 +613,8 @@ impl<'a> DFParser<'a> {
             stored_as: Option<String>,
             target: Option<String>,
             partitioned_by: Option<Vec<String>>,
+            sort_by: Option<Vec<OrderByExpr>>,
+            bucket_by: Option<(usize, Vec<String>)>,
             has_header: Option<bool>,
             options: Option<Vec<(String, Value)>>,
         }
@@ -412,6 +626,7 @@ impl<'a> DFParser<'a> {
                 Keyword::STORED,
                 Keyword::TO,
                 Keyword::PARTITIONED,
+                Keyword::SORT,
                 Keyword::OPTIONS,
                 Keyword::WITH,
             ]) {
@@ -436,6 +651,11 @@ impl<'a> DFParser<'a> {
                         ensure_not_set(&builder.partitioned_by, "PARTITIONED BY")?;
                         builder.partitioned_by = Some(self.parse_partitions()?);
                     }
+                    Keyword::SORT => {
+                        self.parser.expect_keyword(Keyword::BY)?;
+                        ensure_not_set(&builder.sort_by, "SORT BY")?;
+                        builder.sort_by = Some(self.parse_order_by_exprs()?);
+                    }
                     Keyword::OPTIONS => {
                         ensure_not_set(&builder.options, "OPTIONS")?;
                         builder.options = Some(self.parse_value_options()?);
@@ -444,6 +664,9 @@ impl<'a> DFParser<'a> {
                         unreachable!()
                     }
                 }
+            } else if self.parse_word("BUCKETS") {
+                ensure_not_set(&builder.bucket_by, "BUCKETS")?;
+                builder.bucket_by = Some(self.parse_buckets()?);
             } else {
                 let token = self.parser.next_token();
                 if token == Token::EOF || token == Token::SemiColon {
@@ -466,12 +689,45 @@ impl<'a> DFParser<'a> {
             source,
             target,
             partitioned_by: builder.partitioned_by.unwrap_or(vec![]),
+            sort_by: builder.sort_by.unwrap_or(vec![]),
+            bucket_by: builder.bucket_by,
             has_header: builder.has_header.unwrap_or(false),
             stored_as: builder.stored_as,
             options: builder.options.unwrap_or(vec![]),
         }))
     }
 
+    /// Consumes the next token if it is the (non keyword) word `word`,
+    /// ignoring case
+    fn parse_word(&mut self, word: &str) -> bool {
+        match self.parser.peek_token().token {
+            Token::Word(w) if w.value.eq_ignore_ascii_case(word) => {
+                self.parser.next_token();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Parse the `<n> BY (col, ...)` part of a `BUCKETS` clause
+    fn parse_buckets(&mut self) -> Result<(usize, Vec<String>), ParserError> {
+        let next_token = self.parser.next_token();
+        let num_buckets = match &next_token.token {
+            Token::Number(n, _) => n.parse::<usize>().map_err(|e| {
+                ParserError::ParserError(format!(
+                    "Unexpected error: could not parse '{n}' as number of buckets: {e}"
+                ))
+            })?,
+            _ => return self.parser.expected("number of buckets", next_token),
+        };
+        self.parser.expect_keyword(Keyword::BY)?;
+        let columns = self.parse_partitions()?;
+        if columns.is_empty() {
+            return parser_err!("BUCKETS requires at least one column");
+        }
+        Ok((num_buckets, columns))
+    }
+
     /// Parse the next token as a key name for an option list
     ///
     /// Note this is different than [`parse_literal_string`]
@@ -518,15 +774,63 @@ impl<'a> DFParser<'a> {
     pub fn parse_explain(&mut self) -> Result<Statement, ParserError> {
         let analyze = self.parser.parse_keyword(Keyword::ANALYZE);
         let verbose = self.parser.parse_keyword(Keyword::VERBOSE);
+        let options = self.parse_explain_options()?;
         let statement = self.parse_statement()?;
 
         Ok(Statement::Explain(ExplainStatement {
             statement: Box::new(statement),
             analyze,
             verbose,
+            options,
         }))
     }
 
+    /// Parse the options of `EXPLAIN (option [, ...])`, such as
+    /// `(FORMAT json, RULES on)`
+    fn parse_explain_options(&mut self) -> Result<ExplainOptions, ParserError> {
+        let mut options = ExplainOptions::default();
+
+        // `EXPLAIN (SELECT ...)` explains a parenthesized query
+        let starts_with_option = match &self.parser.peek_nth_token(1).token {
+            Token::Word(w) => {
+                w.keyword == Keyword::FORMAT || w.value.eq_ignore_ascii_case("rules")
+            }
+            _ => false,
+        };
+        if self.parser.peek_token().token != Token::LParen || !starts_with_option {
+            return Ok(options);
+        }
+
+        self.parser.expect_token(&Token::LParen)?;
+        loop {
+            let name = self.parser.parse_identifier(false)?.value.to_lowercase();
+            let value = self.parser.parse_identifier(false)?.value.to_lowercase();
+            match name.as_str() {
+                "format" => {
+                    options.format = ExplainFormat::from_str(&value)
+                        .map_err(|e| ParserError::ParserError(e.strip_backtrace()))?;
+                }
+                "rules" => {
+                    options.rules = match value.as_str() {
+                        "on" | "true" => true,
+                        "off" | "false" => false,
+                        _ => {
+                            return parser_err!(format!(
+                            "Expected on or off for EXPLAIN option RULES, found: {value}"
+                        ))
+                        }
+                    };
+                }
+                _ => return parser_err!(format!("Unknown EXPLAIN option: {name}")),
+            }
+            if !self.parser.consume_token(&Token::Comma) {
+                break;
+            }
+        }
+        self.parser.expect_token(&Token::RParen)?;
+        Ok(options)
+    }
+
     /// Parse a SQL `CREATE` statement handling `CREATE EXTERNAL TABLE`
     pub fn parse_create(&mut self) -> Result<Statement, ParserError> {
         if self.parser.parse_keyword(Keyword::EXTERNAL) {
@@ -893,7 +1197,7 @@ impl<'a> DFParser<'a> {
 mod tests {
     use super::*;
     use sqlparser::ast::Expr::Identifier;
-    use sqlparser::ast::{BinaryOperator, DataType, Expr, Ident};
+    use sqlparser::ast::{BinaryOperator, DataType, Expr, Ident, SetExpr, TableAlias};
     use CompressionTypeVariant::UNCOMPRESSED;
 
     fn expect_parse_ok(sql: &str, expected: Statement) -> Result<(), ParserError> {
@@ -1401,6 +1705,8 @@ mod tests {
             source: object_name("foo"),
             target: "bar".to_string(),
             partitioned_by: vec![],
+            sort_by: vec![],
+            bucket_by: None,
             has_header: false,
             stored_as: Some("CSV".to_owned()),
             options: vec![],
@@ -1437,6 +1743,8 @@ mod tests {
                 source: object_name("foo"),
                 target: "bar".to_string(),
                 partitioned_by: vec![],
+                sort_by: vec![],
+                bucket_by: None,
                 has_header: false,
                 stored_as: Some("PARQUET".to_owned()),
                 options: vec![],
@@ -1444,6 +1752,7 @@ mod tests {
             let expected = Statement::Explain(ExplainStatement {
                 analyze,
                 verbose,
+                options: ExplainOptions::default(),
                 statement: Box::new(expected_copy),
             });
             assert_eq!(verified_stmt(sql), expected);
@@ -1451,6 +1760,55 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn explain_options() -> Result<(), ParserError> {
+        let cases = vec![
+            (
+                "EXPLAIN (FORMAT json, RULES on) COPY foo TO bar STORED AS PARQUET",
+                ExplainFormat::Json,
+                true,
+            ),
+            (
+                "EXPLAIN (RULES on) COPY foo TO bar STORED AS PARQUET",
+                ExplainFormat::Tree,
+                true,
+            ),
+            (
+                "EXPLAIN (FORMAT json) COPY foo TO bar STORED AS PARQUET",
+                ExplainFormat::Json,
+                false,
+            ),
+        ];
+        for (sql, format, rules) in cases {
+            let Statement::Explain(explain) = verified_stmt(sql) else {
+                panic!("Expected explain, got {sql}");
+            };
+            assert_eq!(explain.options, ExplainOptions { format, rules });
+        }
+
+        // options not in their canonical form
+        let sql =
+            "EXPLAIN VERBOSE (rules TRUE, format TREE) COPY foo TO bar STORED AS PARQUET";
+        let canonical = "EXPLAIN VERBOSE (RULES on) COPY foo TO bar STORED AS PARQUET";
+        one_statement_parses_to(sql, canonical);
+
+        // a parenthesized query is not mistaken for options
+        let Statement::Explain(explain) = verified_stmt("EXPLAIN (SELECT 1)") else {
+            panic!("Expected explain");
+        };
+        assert!(explain.options.is_default());
+
+        expect_parse_error(
+            "EXPLAIN (FORMAT yaml) SELECT 1",
+            "Unknown EXPLAIN format 'yaml', expected tree or json",
+        );
+        expect_parse_error(
+            "EXPLAIN (RULES maybe) SELECT 1",
+            "Expected on or off for EXPLAIN option RULES, found: maybe",
+        );
+        Ok(())
+    }
+
     #[test]
     fn copy_to_query_to_table() -> Result<(), ParserError> {
         let statement = verified_stmt("SELECT 1");
@@ -1473,6 +1831,8 @@ mod tests {
             source: CopyToSource::Query(query),
             target: "bar".to_string(),
             partitioned_by: vec![],
+            sort_by: vec![],
+            bucket_by: None,
             has_header: true,
             stored_as: Some("CSV".to_owned()),
             options: vec![],
@@ -1488,6 +1848,8 @@ mod tests {
             source: object_name("foo"),
             target: "bar".to_string(),
             partitioned_by: vec![],
+            sort_by: vec![],
+            bucket_by: None,
             has_header: false,
             stored_as: Some("CSV".to_owned()),
             options: vec![(
@@ -1506,6 +1868,8 @@ mod tests {
             source: object_name("foo"),
             target: "bar".to_string(),
             partitioned_by: vec!["a".to_string()],
+            sort_by: vec![],
+            bucket_by: None,
             has_header: false,
             stored_as: Some("CSV".to_owned()),
             options: vec![(
@@ -1517,6 +1881,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn copy_to_sort_by_and_buckets() -> Result<(), ParserError> {
+        let sql =
+            "COPY foo TO bar STORED AS PARQUET SORT BY (a, b DESC) BUCKETS 32 BY (c)";
+        let expected = Statement::CopyTo(CopyToStatement {
+            source: object_name("foo"),
+            target: "bar".to_string(),
+            partitioned_by: vec![],
+            sort_by: vec![
+                OrderByExpr {
+                    expr: Identifier(Ident::new("a")),
+                    asc: None,
+                    nulls_first: None,
+                },
+                OrderByExpr {
+                    expr: Identifier(Ident::new("b")),
+                    asc: Some(false),
+                    nulls_first: None,
+                },
+            ],
+            bucket_by: Some((32, vec!["c".to_string()])),
+            has_header: false,
+            stored_as: Some("PARQUET".to_owned()),
+            options: vec![],
+        });
+        assert_eq!(verified_stmt(sql), expected);
+
+        expect_parse_error(
+            "COPY foo TO bar BUCKETS 32 BY (c) BUCKETS 16 BY (c)",
+            "BUCKETS specified more than once",
+        );
+        expect_parse_error(
+            "COPY foo TO bar BUCKETS BY (c)",
+            "Expected number of buckets, found: BY",
+        );
+        expect_parse_error(
+            "COPY foo TO bar BUCKETS 32 BY ()",
+            "BUCKETS requires at least one column",
+        );
+        Ok(())
+    }
+
     #[test]
     fn copy_to_multi_options() -> Result<(), ParserError> {
         // order of options is preserved
@@ -1551,6 +1957,91 @@ mod tests {
 
     // For error cases, see: `copy.slt`
 
+    #[test]
+    fn table_versions() {
+        fn table_version(sql: &str) -> (String, Option<SQLTableVersion>) {
+            let mut statements = DFParser::parse_sql(sql).unwrap();
+            assert_eq!(statements.len(), 1);
+            let Statement::Statement(statement) = statements.pop_front().unwrap() else {
+                panic!("Expected a query");
+            };
+            let SQLStatement::Query(query) = *statement else {
+                panic!("Expected a query");
+            };
+            let SetExpr::Select(select) = *query.body else {
+                panic!("Expected a select");
+            };
+            match select.from[0].relation.clone() {
+                TableFactor::Table {
+                    name,
+                    alias,
+                    version,
+                    ..
+                } => (format!("{name} {alias:?}"), version),
+                _ => panic!("Expected a table"),
+            }
+        }
+        let cast = |value: Value, data_type: DataType| {
+            Some(SQLTableVersion::ForSystemTimeAsOf(Expr::Cast {
+                expr: Box::new(Expr::Value(value)),
+                data_type,
+                format: None,
+            }))
+        };
+
+        let (name, version) =
+            table_version("SELECT * FROM t FOR VERSION AS OF 3 AS a JOIN u ON a.x = u.x");
+        assert_eq!(
+            name,
+            format!(
+                "t {:?}",
+                Some(TableAlias {
+                    name: Ident::new("a"),
+                    columns: vec![]
+                })
+            )
+        );
+        assert_eq!(
+            version,
+            cast(
+                Value::Number("3".to_string(), false),
+                DataType::BigInt(None)
+            )
+        );
+
+        let (name, version) = table_version(
+            r#"SELECT * FROM s."T" for timestamp as of '2024-01-01 00:00:00'"#,
+        );
+        assert_eq!(name, r#"s."T" None"#);
+        assert_eq!(
+            version,
+            cast(
+                Value::SingleQuotedString("2024-01-01 00:00:00".to_string()),
+                DataType::Timestamp(None, TimezoneInfo::None)
+            )
+        );
+
+        // table functions with a `version` argument are not versioned tables
+        let (name, version) = table_version("SELECT * FROM f(version => 3)");
+        assert_eq!(name, "f None");
+        assert_eq!(version, None);
+
+        expect_parse_error(
+            "SELECT * FROM t FOR VERSION AS OF x",
+            "Expected a literal table version, found: x",
+        );
+        expect_parse_error(
+            "SELECT a FOR VERSION AS OF 3 FROM t",
+            "FOR VERSION AS OF must follow a table name",
+        );
+        expect_parse_error(
+            "SELECT * FROM t AS a FOR TIMESTAMP AS OF 3",
+            "FOR TIMESTAMP AS OF must follow a table name",
+        );
+        verified_stmt("SELECT 'FOR VERSION AS OF 3' FROM t");
+        verified_stmt(r#"SELECT * FROM "FOR" AS "VERSION""#);
+    }
+
     fn object_name(name: &str) -> CopyToSource {
         CopyToSource::Relation(ObjectName(vec![Ident::new(name)]))
     }
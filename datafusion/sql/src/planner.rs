@@ -38,8 +38,8 @@ use datafusion_common::{
 use datafusion_common::{OwnedTableReference, TableReference};
 use datafusion_expr::logical_plan::{LogicalPlan, LogicalPlanBuilder};
 use datafusion_expr::utils::find_column_exprs;
-use datafusion_expr::TableSource;
 use datafusion_expr::{col, AggregateUDF, Expr, ScalarUDF};
+use datafusion_expr::{TableSource, TableVersion};
 
 use crate::utils::make_decimal_type;
 
@@ -60,6 +60,15 @@ pub trait ContextProvider {
     ) -> Result<Arc<dyn TableSource>> {
         not_impl_err!("Table Functions are not supported")
     }
+    /// Getter for a datasource read at a given version, as in
+    /// `SELECT * FROM t FOR VERSION AS OF 3`
+    fn get_table_source_with_version(
+        &self,
+        _name: TableReference,
+        _version: TableVersion,
+    ) -> Result<Arc<dyn TableSource>> {
+        not_impl_err!("Table versions are not supported")
+    }
 
     /// This provides a worktable (an intermediate table that is used to store the results of a CTE during execution)
     /// We don't directly implement this in the logical plan's ['SqlToRel`]
@@ -16,13 +16,70 @@
 // under the License.
 
 use crate::planner::{ContextProvider, PlannerContext, SqlToRel};
-use datafusion_common::{not_impl_err, plan_err, DFSchema, Result, TableReference};
-use datafusion_expr::{expr::Unnest, Expr, LogicalPlan, LogicalPlanBuilder};
-use sqlparser::ast::{FunctionArg, FunctionArgExpr, TableFactor};
+use arrow_schema::{DataType, TimeUnit};
+use datafusion_common::{
+    not_impl_err, plan_err, DFSchema, Result, ScalarValue, TableReference,
+};
+use datafusion_expr::{
+    expr::Unnest, Expr, LogicalPlan, LogicalPlanBuilder, TableVersion,
+};
+use sqlparser::ast::{
+    DataType as SQLDataType, Expr as SQLExpr, FunctionArg, FunctionArgExpr, TableFactor,
+    TableVersion as SQLTableVersion,
+};
 
 mod join;
 
 impl<'a, S: ContextProvider> SqlToRel<'a, S> {
+    /// Returns the version of a table read with `FOR SYSTEM_TIME AS OF`, or
+    /// with `FOR VERSION AS OF` and `FOR TIMESTAMP AS OF`, which [`DFParser`]
+    /// parses into `FOR SYSTEM_TIME AS OF` with the value cast to `BIGINT`
+    /// and `TIMESTAMP` respectively
+    ///
+    /// [`DFParser`]: crate::parser::DFParser
+    pub fn table_version(
+        &self,
+        version: SQLTableVersion,
+        planner_context: &mut PlannerContext,
+    ) -> Result<TableVersion> {
+        let (kind, expr) = match version {
+            SQLTableVersion::ForSystemTimeAsOf(SQLExpr::Cast {
+                expr,
+                data_type: SQLDataType::BigInt(_),
+                format: None,
+            }) => ("version", *expr),
+            SQLTableVersion::ForSystemTimeAsOf(SQLExpr::Cast {
+                expr,
+                data_type: SQLDataType::Timestamp(..),
+                format: None,
+            }) => ("timestamp", *expr),
+            SQLTableVersion::ForSystemTimeAsOf(expr) => ("timestamp", expr),
+        };
+
+        let value = match self.sql_expr_to_logical_expr(
+            expr,
+            &DFSchema::empty(),
+            planner_context,
+        )? {
+            Expr::Literal(value) => value,
+            expr => return plan_err!("Unsupported table {kind} {expr}"),
+        };
+        let version = if kind == "version" {
+            match value.cast_to(&DataType::Int64)? {
+                ScalarValue::Int64(Some(version)) => TableVersion::Version(version),
+                _ => return plan_err!("Unsupported table version {value}"),
+            }
+        } else {
+            match value.cast_to(&DataType::Timestamp(TimeUnit::Millisecond, None))? {
+                ScalarValue::TimestampMillisecond(Some(timestamp), _) => {
+                    TableVersion::Timestamp(timestamp)
+                }
+                _ => return plan_err!("Unsupported table timestamp {value}"),
+            }
+        };
+        Ok(version)
+    }
+
     /// Create a `LogicalPlan` that scans the named relation
     fn create_relation(
         &self,
@@ -31,9 +88,22 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     ) -> Result<LogicalPlan> {
         let (plan, alias) = match relation {
             TableFactor::Table {
-                name, alias, args, ..
+                name,
+                alias,
+                args,
+                version,
+                ..
             } => {
-                if let Some(func_args) = args {
+                if let Some(version) = version {
+                    let version = self.table_version(version, planner_context)?;
+                    let table_ref = self.object_name_to_table_reference(name)?;
+                    let provider = self
+                        .context_provider
+                        .get_table_source_with_version(table_ref.clone(), version)?;
+                    let plan =
+                        LogicalPlanBuilder::scan(table_ref, provider, None)?.build()?;
+                    (plan, alias)
+                } else if let Some(func_args) = args {
                     let tbl_func_name = name.0.first().unwrap().value.to_string();
                     let args = func_args
                         .into_iter()
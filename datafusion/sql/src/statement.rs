@@ -30,14 +30,15 @@ use crate::planner::{
 use crate::utils::normalize_ident;
 
 use arrow_schema::DataType;
+use datafusion_common::display::ExplainOptions;
 use datafusion_common::parsers::CompressionTypeVariant;
 use datafusion_common::{
     exec_err, not_impl_err, plan_datafusion_err, plan_err, schema_err,
-    unqualified_field_not_found, Column, Constraints, DFField, DFSchema, DFSchemaRef,
-    DataFusionError, FileType, OwnedTableReference, Result, ScalarValue, SchemaError,
-    SchemaReference, TableReference, ToDFSchema,
+    unqualified_field_not_found, BucketSpec, Column, Constraints, DFField, DFSchema,
+    DFSchemaRef, DataFusionError, FileType, OwnedTableReference, Result, ScalarValue,
+    SchemaError, SchemaReference, TableReference, ToDFSchema,
 };
-use datafusion_expr::dml::CopyTo;
+use datafusion_expr::dml::{make_dml_schema, CopyTo};
 use datafusion_expr::expr_rewriter::normalize_col_with_schemas_and_ambiguity_check;
 use datafusion_expr::logical_plan::builder::project;
 use datafusion_expr::logical_plan::DdlStatement;
@@ -149,8 +150,9 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             DFStatement::Explain(ExplainStatement {
                 verbose,
                 analyze,
+                options,
                 statement,
-            }) => self.explain_to_plan(verbose, analyze, *statement),
+            }) => self.explain_to_plan(verbose, analyze, options, *statement),
         }
     }
 
@@ -190,9 +192,12 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 format: _,
                 describe_alias: _,
                 ..
-            } => {
-                self.explain_to_plan(verbose, analyze, DFStatement::Statement(statement))
-            }
+            } => self.explain_to_plan(
+                verbose,
+                analyze,
+                ExplainOptions::default(),
+                DFStatement::Statement(statement),
+            ),
             Statement::Query(query) => self.query_to_plan(*query, planner_context),
             Statement::ShowVariable { variable } => self.show_variable_to_plan(&variable),
             Statement::SetVariable {
@@ -887,14 +892,47 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             .collect::<Result<Vec<_>>>()?
             .into_iter()
             .map(|f| f.name().to_owned())
-            .collect();
+            .collect::<Vec<_>>();
+
+        let sort_by = if statement.sort_by.is_empty() {
+            vec![]
+        } else {
+            self.build_order_by(
+                vec![statement.sort_by],
+                &input_schema,
+                &mut PlannerContext::new(),
+            )?
+            .swap_remove(0)
+        };
+
+        let bucket_by = match statement.bucket_by {
+            Some((num_buckets, columns)) => {
+                let columns = columns
+                    .iter()
+                    .map(|col| input_schema.field_with_name(table_ref.as_ref(), col))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .map(|f| f.name().to_owned())
+                    .collect::<Vec<_>>();
+                if let Some(col) = columns.iter().find(|c| partition_by.contains(c)) {
+                    return plan_err!(
+                        "Column {col} can not be used both for partitioning and bucketing"
+                    );
+                }
+                Some(BucketSpec::try_new(columns, num_buckets)?)
+            }
+            None => None,
+        };
 
         Ok(LogicalPlan::Copy(CopyTo {
             input: Arc::new(input),
             output_url: statement.target,
             format_options: file_type.into(),
             partition_by,
+            sort_by,
+            bucket_by,
             options,
+            output_schema: make_dml_schema(self.report_written_files()),
         }))
     }
 
@@ -1012,12 +1050,16 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         &self,
         verbose: bool,
         analyze: bool,
+        options: ExplainOptions,
         statement: DFStatement,
     ) -> Result<LogicalPlan> {
         let plan = self.statement_to_plan(statement)?;
         if matches!(plan, LogicalPlan::Explain(_)) {
             return plan_err!("Nested EXPLAINs are not supported");
         }
+        if analyze && !options.is_default() {
+            return plan_err!("EXPLAIN ANALYZE does not support options {options}");
+        }
         let plan = Arc::new(plan);
         let schema = LogicalPlan::explain_schema();
         let schema = schema.to_dfschema_ref()?;
@@ -1037,6 +1079,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 stringified_plans,
                 schema,
                 logical_optimization_succeeded: false,
+                options,
             }))
         }
     }
@@ -1183,6 +1226,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             table_schema: schema.into(),
             op: WriteOp::Delete,
             input: Arc::new(source),
+            output_schema: make_dml_schema(false),
         });
         Ok(plan)
     }
@@ -1294,6 +1338,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             table_schema,
             op: WriteOp::Update,
             input: Arc::new(source),
+            output_schema: make_dml_schema(false),
         });
         Ok(plan)
     }
@@ -1418,10 +1463,19 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             table_schema: Arc::new(table_schema),
             op,
             input: Arc::new(source),
+            output_schema: make_dml_schema(self.report_written_files()),
         });
         Ok(plan)
     }
 
+    /// Whether statements writing files output a row per written file
+    fn report_written_files(&self) -> bool {
+        self.context_provider
+            .options()
+            .execution
+            .report_written_files
+    }
+
     fn show_columns_to_plan(
         &self,
         extended: bool,
@@ -112,6 +112,7 @@ pub async fn from_substrait_rel(
                         limit: None,
                         table_partition_cols: vec![],
                         output_ordering: vec![],
+                        bucket_by: None,
                     };
 
                     if let Some(MaskExpression { select, .. }) = &read.projection {